@@ -0,0 +1,14 @@
+//! The helper parses this JSON as root from a command file any local user could race to
+//! write: it must never panic, and whatever it accepts must survive a serialize round trip.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use monarch_types::HelperCommand;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(cmd) = serde_json::from_slice::<HelperCommand>(data) {
+        let json = serde_json::to_string(&cmd).expect("accepted command must serialize");
+        let again: HelperCommand = serde_json::from_str(&json).expect("round trip must parse");
+        assert_eq!(cmd, again);
+    }
+});
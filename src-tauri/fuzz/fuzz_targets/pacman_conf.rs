@@ -0,0 +1,20 @@
+//! `pacman-conf` output reflects user-editable config and is parsed by the root helper.
+#![no_main]
+
+#[path = "../../monarch-helper/src/pacman_conf.rs"]
+#[allow(dead_code)]
+mod pacman_conf;
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let text = String::from_utf8_lossy(data);
+    for repo in pacman_conf::parse_repo_list(&text) {
+        assert!(pacman_conf::is_valid_repo_name(&repo));
+        assert!(!repo.contains('/'));
+    }
+    let details = pacman_conf::parse_repo_details(&text);
+    for server in details.servers {
+        assert!(!server.is_empty() && !server.contains('#'));
+    }
+});
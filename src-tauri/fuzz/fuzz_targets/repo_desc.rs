@@ -0,0 +1,18 @@
+//! `desc` entries come from repo `.db` tarballs served by third-party mirrors.
+#![no_main]
+
+#[path = "../../monarch-gui/src/repo_desc.rs"]
+#[allow(dead_code)]
+mod repo_desc;
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(text) = std::str::from_utf8(data) else {
+        return;
+    };
+    if let Some(entry) = repo_desc::parse_desc_entry(text) {
+        assert!(!entry.name.is_empty() && !entry.version.is_empty());
+        assert!(!entry.name.starts_with(['.', '-']) && !entry.name.contains('/'));
+    }
+});
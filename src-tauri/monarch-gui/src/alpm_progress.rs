@@ -1,26 +1,69 @@
-use serde::{Deserialize, Serialize};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct AlpmProgressEvent {
-    pub event_type: String,
-    pub package: Option<String>,
-    pub percent: Option<u8>,
-    pub downloaded: Option<u64>,
-    pub total: Option<u64>,
-    pub message: String,
+pub use monarch_types::AlpmProgressEvent;
+
+/// Latest download state for one package, as shown by the per-package progress bars.
+#[derive(Debug, Serialize, Clone)]
+pub struct PackageDownloadProgress {
+    pub package: String,
+    pub downloaded: u64,
+    pub total: u64,
+    pub percent: u8,
+    pub speed_bps: Option<u64>,
+    pub eta_seconds: Option<u64>,
+    pub complete: bool,
 }
 
-impl AlpmProgressEvent {
-    #[allow(dead_code)]
-    pub fn is_complete(&self) -> bool {
-        matches!(
-            self.event_type.as_str(),
-            "install_complete" | "extract_complete" | "transaction_complete"
-        )
+static DOWNLOADS: once_cell::sync::Lazy<Mutex<HashMap<String, PackageDownloadProgress>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Fold a helper event into the download aggregator. Non-download events are ignored.
+pub fn record_download_event(event: &AlpmProgressEvent) {
+    let complete = match event.event_type.as_str() {
+        "download_start" | "download_progress" => false,
+        "download_complete" => true,
+        _ => return,
+    };
+    let Some(package) = event.package.clone() else {
+        return;
+    };
+    if let Ok(mut map) = DOWNLOADS.lock() {
+        let entry = map
+            .entry(package.clone())
+            .or_insert(PackageDownloadProgress {
+                package,
+                downloaded: 0,
+                total: 0,
+                percent: 0,
+                speed_bps: None,
+                eta_seconds: None,
+                complete: false,
+            });
+        entry.downloaded = event.downloaded.unwrap_or(entry.downloaded);
+        entry.total = event.total.filter(|t| *t > 0).unwrap_or(entry.total);
+        entry.percent = event.percent.unwrap_or(entry.percent);
+        entry.speed_bps = event.speed_bps;
+        entry.eta_seconds = event.eta_seconds;
+        entry.complete = complete;
     }
+}
 
-    #[allow(dead_code)]
-    pub fn is_error(&self) -> bool {
-        self.event_type == "error" || self.message.to_lowercase().contains("error")
+/// Forget previous downloads; called when a new helper transaction starts.
+pub fn reset_downloads() {
+    if let Ok(mut map) = DOWNLOADS.lock() {
+        map.clear();
     }
 }
+
+/// Snapshot of per-package download progress for the current transaction.
+#[tauri::command]
+pub fn get_download_progress() -> Vec<PackageDownloadProgress> {
+    let mut list: Vec<PackageDownloadProgress> = DOWNLOADS
+        .lock()
+        .map(|m| m.values().cloned().collect())
+        .unwrap_or_default();
+    list.sort_by(|a, b| a.package.cmp(&b.package));
+    list
+}
@@ -1,5 +1,5 @@
-use crate::models::{Package, PackageSource};
-use alpm::{Alpm, PackageReason, SigLevel};
+use crate::models::{DependencyNode, Package, PackageSource};
+use alpm::{Alpm, PackageReason, PackageValidation, SigLevel};
 use std::path::Path;
 
 /// Collect all repository section names from pacman.conf and any Include'd files
@@ -112,6 +112,7 @@ pub fn get_package_native(name: &str) -> Option<Package> {
                     &crate::distro_context::DistroContext::new(),
                 ),
                 installed,
+                license: Some(pkg.licenses().iter().map(|l| l.to_string()).collect()),
                 download_size: Some(pkg.download_size() as u64),
                 installed_size: Some(pkg.isize() as u64),
                 ..Default::default()
@@ -148,6 +149,7 @@ pub fn get_installed_packages_native() -> Vec<Package> {
             name: pkg.name().to_string(),
             version: pkg.version().to_string(),
             description: pkg.desc().map(|d| d.to_string()).unwrap_or_default(),
+            license: Some(pkg.licenses().iter().map(|l| l.to_string()).collect()),
             installed: true,
             installed_size: Some(pkg.isize() as u64),
             ..Default::default()
@@ -191,6 +193,7 @@ pub fn get_packages_batch(names: &[String], enabled_repos: &[String]) -> Vec<Pac
                         &crate::distro_context::DistroContext::new(),
                     ),
                     installed: is_installed,
+                    license: Some(pkg.licenses().iter().map(|l| l.to_string()).collect()),
                     download_size: Some(pkg.download_size() as u64),
                     installed_size: Some(pkg.isize() as u64),
                     last_modified: None,
@@ -228,6 +231,13 @@ pub fn is_package_installed(name: &str) -> bool {
     alpm.localdb().pkg(name).is_ok()
 }
 
+/// Installed version of a package (localdb), if installed.
+pub fn get_installed_version(name: &str) -> Option<String> {
+    let alpm = Alpm::new("/", "/var/lib/pacman").ok()?;
+    let version = alpm.localdb().pkg(name).ok()?.version().to_string();
+    Some(version)
+}
+
 /// Returns true if the package exists in any sync database (official or enabled repos).
 /// Replaces read-only `pacman -Si <name>` for "in repo" checks.
 pub fn is_package_in_syncdb(name: &str) -> bool {
@@ -314,6 +324,326 @@ pub fn get_orphans_native() -> Vec<String> {
         .collect()
 }
 
+/// Orphans with their installed size, exactly as `pacman -Qtdq` lists them: installed as a
+/// dependency and neither required nor optionally required by an installed package.
+pub fn get_unrequired_orphans_native() -> Vec<(String, u64)> {
+    let Ok(alpm) = Alpm::new("/", "/var/lib/pacman") else {
+        return Vec::new();
+    };
+    alpm.localdb()
+        .pkgs()
+        .iter()
+        .filter(|pkg| {
+            pkg.reason() == PackageReason::Depend
+                && pkg.required_by().is_empty()
+                && pkg.optional_for().is_empty()
+        })
+        .map(|pkg| (pkg.name().to_string(), pkg.isize().max(0) as u64))
+        .collect()
+}
+
+/// One installed package as seen by the disk usage report.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstalledUsage {
+    pub name: String,
+    pub version: String,
+    pub installed_size: u64,
+    /// First sync DB carrying the package; None for foreign (AUR/local) packages.
+    pub repo: Option<String>,
+    pub explicit: bool,
+    /// Some installed package depends on it (by name or on something it provides).
+    pub required: bool,
+}
+
+/// Installed sizes, origin repo and install reason for every local package (`pacman -Qi`
+/// for the whole system in one pass).
+pub fn get_installed_usage_native() -> Vec<InstalledUsage> {
+    let Ok(alpm) = Alpm::new("/", "/var/lib/pacman") else {
+        return Vec::new();
+    };
+    register_syncdbs_from_conf(&alpm, "/etc/pacman.conf");
+    let mut wanted = std::collections::HashSet::new();
+    for pkg in alpm.localdb().pkgs() {
+        for dep in pkg.depends() {
+            wanted.insert(dep.name().to_string());
+        }
+    }
+    alpm.localdb()
+        .pkgs()
+        .iter()
+        .map(|pkg| {
+            let required = wanted.contains(pkg.name())
+                || pkg.provides().iter().any(|p| wanted.contains(p.name()));
+            InstalledUsage {
+                name: pkg.name().to_string(),
+                version: pkg.version().to_string(),
+                installed_size: pkg.isize().max(0) as u64,
+                repo: alpm
+                    .syncdbs()
+                    .iter()
+                    .find(|db| db.pkg(pkg.name()).is_ok())
+                    .map(|db| db.name().to_string()),
+                explicit: pkg.reason() == PackageReason::Explicit,
+                required,
+            }
+        })
+        .collect()
+}
+
+/// An installed package with the relations removal planning needs.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LocalPackageDeps {
+    pub name: String,
+    pub version: String,
+    pub installed_size: u64,
+    pub explicit: bool,
+    /// Dependency names, version constraints stripped.
+    pub depends: Vec<String>,
+    /// Names it provides, version stripped.
+    pub provides: Vec<String>,
+    pub groups: Vec<String>,
+}
+
+/// Every local package with its depends/provides/groups, for previewing a removal.
+pub fn get_local_package_deps_native() -> Vec<LocalPackageDeps> {
+    let Ok(alpm) = Alpm::new("/", "/var/lib/pacman") else {
+        return Vec::new();
+    };
+    alpm.localdb()
+        .pkgs()
+        .iter()
+        .map(|pkg| LocalPackageDeps {
+            name: pkg.name().to_string(),
+            version: pkg.version().to_string(),
+            installed_size: pkg.isize().max(0) as u64,
+            explicit: pkg.reason() == PackageReason::Explicit,
+            depends: pkg.depends().iter().map(|d| d.name().to_string()).collect(),
+            provides: pkg
+                .provides()
+                .iter()
+                .map(|p| p.name().split('=').next().unwrap_or(p.name()).to_string())
+                .collect(),
+            groups: pkg.groups().iter().map(|g| g.to_string()).collect(),
+        })
+        .collect()
+}
+
+/// Config files installed packages declare in their backup array, as (package, version,
+/// absolute path).
+pub fn get_backup_files_native() -> Vec<(String, String, String)> {
+    let Ok(alpm) = Alpm::new("/", "/var/lib/pacman") else {
+        return Vec::new();
+    };
+    alpm.localdb()
+        .pkgs()
+        .iter()
+        .flat_map(|pkg| {
+            pkg.backup()
+                .iter()
+                .map(|b| {
+                    (
+                        pkg.name().to_string(),
+                        pkg.version().to_string(),
+                        format!("/{}", b.name()),
+                    )
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Local view of one sync database, for the repo health report.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncDbState {
+    pub name: String,
+    /// Modification time of /var/lib/pacman/sync/<name>.db, None if it was never synced.
+    pub db_mtime: Option<i64>,
+    pub package_count: usize,
+    /// "valid", "unsigned" (no .sig, nothing to check), "invalid" or "missing".
+    pub signature: String,
+}
+
+/// Every repo in pacman.conf with its sync DB's age, size and signature state. Databases
+/// with a .sig are checked against it.
+pub fn get_sync_db_states_native() -> Vec<SyncDbState> {
+    let Ok(alpm) = Alpm::new("/", "/var/lib/pacman") else {
+        return Vec::new();
+    };
+    for section in collect_repo_sections_from_conf("/etc/pacman.conf") {
+        let _ = alpm.register_syncdb(
+            section.as_str(),
+            SigLevel::PACKAGE_OPTIONAL | SigLevel::DATABASE_OPTIONAL,
+        );
+    }
+    alpm.syncdbs()
+        .iter()
+        .map(|db| {
+            let path = format!("/var/lib/pacman/sync/{}.db", db.name());
+            let db_mtime = std::fs::metadata(&path)
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs() as i64);
+            let signature = if db_mtime.is_none() {
+                "missing"
+            } else if db.is_valid().is_err() {
+                "invalid"
+            } else if Path::new(&format!("{}.sig", path)).exists() {
+                "valid"
+            } else {
+                "unsigned"
+            };
+            SyncDbState {
+                name: db.name().to_string(),
+                db_mtime,
+                package_count: if db_mtime.is_some() { db.pkgs().len() } else { 0 },
+                signature: signature.to_string(),
+            }
+        })
+        .collect()
+}
+
+/// A sync DB copy of a locally built package.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepoCopy {
+    pub repo: String,
+    pub version: String,
+    pub build_date: i64,
+    pub download_size: u64,
+}
+
+/// An installed package that was built on this machine (installed from an unsigned file, as
+/// makepkg -i does) and the repos in `enabled_repos` that now carry it, in pacman.conf order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalBuild {
+    pub name: String,
+    pub version: String,
+    pub build_date: i64,
+    pub in_repos: Vec<RepoCopy>,
+}
+
+/// Locally built packages that some enabled sync DB also provides (by exact name).
+pub fn get_local_builds_in_repos_native(enabled_repos: &[String]) -> Vec<LocalBuild> {
+    let Ok(alpm) = Alpm::new("/", "/var/lib/pacman") else {
+        return Vec::new();
+    };
+    register_syncdbs_from_conf(&alpm, "/etc/pacman.conf");
+    alpm.localdb()
+        .pkgs()
+        .iter()
+        .filter(|pkg| pkg.validation() == PackageValidation::NONE)
+        .filter_map(|pkg| {
+            let in_repos: Vec<RepoCopy> = alpm
+                .syncdbs()
+                .iter()
+                .filter(|db| enabled_repos.iter().any(|r| r == db.name()))
+                .filter_map(|db| {
+                    let copy = db.pkg(pkg.name()).ok()?;
+                    Some(RepoCopy {
+                        repo: db.name().to_string(),
+                        version: copy.version().to_string(),
+                        build_date: copy.build_date(),
+                        download_size: copy.download_size().max(0) as u64,
+                    })
+                })
+                .collect();
+            (!in_repos.is_empty()).then(|| LocalBuild {
+                name: pkg.name().to_string(),
+                version: pkg.version().to_string(),
+                build_date: pkg.build_date(),
+                in_repos,
+            })
+        })
+        .collect()
+}
+
+/// Returns (name, version) of packages that were installed from a signed repo package but that
+/// no sync DB carries any more, e.g. after chaotic-aur dropped them. Local builds are excluded.
+pub fn get_dropped_repo_packages_native() -> Vec<(String, String)> {
+    let Ok(alpm) = Alpm::new("/", "/var/lib/pacman") else {
+        return Vec::new();
+    };
+    register_syncdbs_from_conf(&alpm, "/etc/pacman.conf");
+    alpm.localdb()
+        .pkgs()
+        .iter()
+        .filter(|pkg| pkg.validation() != PackageValidation::NONE)
+        .filter(|pkg| alpm.syncdbs().iter().all(|db| db.pkg(pkg.name()).is_err()))
+        .map(|pkg| (pkg.name().to_string(), pkg.version().to_string()))
+        .collect()
+}
+
+/// A kernel from the sync DBs or the local system.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KernelPackage {
+    pub name: String,
+    pub description: String,
+    /// First sync DB carrying it; None for kernels installed from elsewhere (AUR, local build).
+    pub repo: Option<String>,
+    pub available_version: Option<String>,
+    pub installed_version: Option<String>,
+    pub headers_available: bool,
+    pub headers_installed: bool,
+}
+
+/// `linux` and `linux-*`, minus the companion packages. Only the ones that also ship a
+/// `-headers` package count as kernels, which keeps out linux-firmware and friends.
+fn is_kernel_candidate(name: &str) -> bool {
+    name == "linux"
+        || (name.starts_with("linux-") && !name.ends_with("-headers") && !name.ends_with("-docs"))
+}
+
+/// Every kernel the sync DBs offer, plus the installed kernels in `installed_bases` (pkgbase
+/// names from /usr/lib/modules) that no repo carries.
+pub fn get_kernel_packages_native(installed_bases: &[String]) -> Vec<KernelPackage> {
+    let Ok(alpm) = Alpm::new("/", "/var/lib/pacman") else {
+        return Vec::new();
+    };
+    register_syncdbs_from_conf(&alpm, "/etc/pacman.conf");
+    let local = alpm.localdb();
+    let mut kernels: Vec<KernelPackage> = Vec::new();
+    for db in alpm.syncdbs() {
+        for pkg in db.pkgs() {
+            let name = pkg.name();
+            if !is_kernel_candidate(name) || kernels.iter().any(|k| k.name == name) {
+                continue;
+            }
+            let headers = format!("{}-headers", name);
+            if db.pkg(headers.as_str()).is_err() {
+                continue;
+            }
+            kernels.push(KernelPackage {
+                name: name.to_string(),
+                description: pkg.desc().map(|d| d.to_string()).unwrap_or_default(),
+                repo: Some(db.name().to_string()),
+                available_version: Some(pkg.version().to_string()),
+                installed_version: local.pkg(name).ok().map(|l| l.version().to_string()),
+                headers_available: true,
+                headers_installed: local.pkg(headers.as_str()).is_ok(),
+            });
+        }
+    }
+    for base in installed_bases {
+        if kernels.iter().any(|k| &k.name == base) {
+            continue;
+        }
+        let Ok(pkg) = local.pkg(base.as_str()) else {
+            continue;
+        };
+        kernels.push(KernelPackage {
+            name: base.clone(),
+            description: pkg.desc().map(|d| d.to_string()).unwrap_or_default(),
+            repo: None,
+            available_version: None,
+            installed_version: Some(pkg.version().to_string()),
+            headers_available: false,
+            headers_installed: local.pkg(format!("{}-headers", base).as_str()).is_ok(),
+        });
+    }
+    kernels.sort_by(|a, b| a.name.cmp(&b.name));
+    kernels
+}
+
 /// Returns a list of packages that have upgrades available in the sync databases.
 /// Replicates `pacman -Qu`.
 pub fn get_host_updates() -> Vec<crate::models::UpdateItem> {
@@ -330,11 +660,12 @@ pub fn get_host_updates() -> Vec<crate::models::UpdateItem> {
         let db_name = db.name();
         for pkg in db.pkgs() {
             if let Ok(local_pkg) = localdb.pkg(pkg.name()) {
-                if alpm::vercmp(pkg.version().as_str(), local_pkg.version().as_str())
-                    == std::cmp::Ordering::Greater
+                if crate::versions::classify(local_pkg.version().as_str(), pkg.version().as_str())
+                    == crate::versions::VersionChange::Upgrade
                 {
                     updates.push(crate::models::UpdateItem {
                         name: pkg.name().to_string(), // Package Name
+                        display_name: None,
                         current_version: local_pkg.version().to_string(),
                         new_version: pkg.version().to_string(),
                         source: PackageSource::from_repo_name(
@@ -344,6 +675,7 @@ pub fn get_host_updates() -> Vec<crate::models::UpdateItem> {
                         ),
                         size: Some(pkg.download_size() as u64),
                         icon: None,
+                        downgrade: false,
                     });
                 }
             }
@@ -351,3 +683,358 @@ pub fn get_host_updates() -> Vec<crate::models::UpdateItem> {
     }
     updates
 }
+
+/// Resolve a dependency name to the package that satisfies it: local DB first (exact name,
+/// then provides), then sync DBs. Returns (package, installed).
+fn resolve_dep<'a>(alpm: &'a Alpm, dep_name: &str) -> Option<(&'a alpm::Package, bool)> {
+    let provides = |pkg: &alpm::Package| {
+        pkg.provides()
+            .iter()
+            .any(|p| p.name().split('=').next().unwrap_or(p.name()) == dep_name)
+    };
+    if let Ok(pkg) = alpm.localdb().pkg(dep_name) {
+        return Some((pkg, true));
+    }
+    if let Some(pkg) = alpm.localdb().pkgs().iter().find(|p| provides(p)) {
+        return Some((pkg, true));
+    }
+    for db in alpm.syncdbs() {
+        if let Ok(pkg) = db.pkg(dep_name) {
+            return Some((pkg, false));
+        }
+    }
+    for db in alpm.syncdbs() {
+        if let Some(pkg) = db.pkgs().iter().find(|p| provides(p)) {
+            return Some((pkg, false));
+        }
+    }
+    None
+}
+
+fn dep_node<'a>(
+    alpm: &'a Alpm,
+    dep_name: &str,
+    kind: &str,
+) -> (DependencyNode, Option<&'a alpm::Package>) {
+    let resolved = resolve_dep(alpm, dep_name);
+    let node = DependencyNode {
+        name: dep_name.to_string(),
+        version: resolved.map(|(p, _)| p.version().to_string()),
+        kind: kind.to_string(),
+        installed: resolved.map(|(_, i)| i).unwrap_or(false),
+        satisfied_by: resolved
+            .map(|(p, _)| p.name())
+            .filter(|n| *n != dep_name)
+            .map(|n| n.to_string()),
+        ..Default::default()
+    };
+    (node, resolved.map(|(p, _)| p))
+}
+
+fn build_dep_children(
+    alpm: &Alpm,
+    pkg: &alpm::Package,
+    depth: u32,
+    path: &mut Vec<String>,
+) -> Vec<DependencyNode> {
+    let mut children = Vec::new();
+    for dep in pkg.depends() {
+        let (mut node, resolved) = dep_node(alpm, dep.name(), "depends");
+        if let Some(child) = resolved {
+            // Stop at the depth limit and on cycles (e.g. systemd <-> systemd-libs).
+            if depth > 1 && !path.iter().any(|p| p == child.name()) {
+                path.push(child.name().to_string());
+                node.children = build_dep_children(alpm, child, depth - 1, path);
+                path.pop();
+            }
+        }
+        children.push(node);
+    }
+    for dep in pkg.optdepends() {
+        let (mut node, _) = dep_node(alpm, dep.name(), "optdepends");
+        node.description = Some(dep.desc())
+            .filter(|d| !d.is_empty())
+            .map(|d| d.to_string());
+        children.push(node);
+    }
+    children
+}
+
+/// Forward dependency tree for `name`, `depth` levels deep. Only hard depends are expanded;
+/// optdepends are listed as leaves and makedepends only for the root (they are build-time).
+pub fn get_dependency_tree_native(name: &str, depth: u32) -> Option<DependencyNode> {
+    let alpm = Alpm::new("/", "/var/lib/pacman").ok()?;
+    register_syncdbs_from_conf(&alpm, "/etc/pacman.conf");
+
+    let (root, installed) = resolve_dep(&alpm, name)?;
+    let mut path = vec![root.name().to_string()];
+    let mut children = build_dep_children(&alpm, root, depth, &mut path);
+    for dep in root.makedepends() {
+        let (node, _) = dep_node(&alpm, dep.name(), "makedepends");
+        children.push(node);
+    }
+
+    Some(DependencyNode {
+        name: root.name().to_string(),
+        version: Some(root.version().to_string()),
+        kind: "root".to_string(),
+        description: root.desc().map(|d| d.to_string()),
+        installed,
+        satisfied_by: None,
+        children,
+    })
+}
+
+/// Installed packages that need `name` (directly or through one of its provides), keyed by
+/// the relation: ("required_by", pkg) for depends, ("optional_for", pkg) for optdepends.
+fn local_dependents<'a>(alpm: &'a Alpm, name: &str) -> Vec<(&'static str, &'a alpm::Package)> {
+    let mut names = vec![name.to_string()];
+    if let Ok(pkg) = alpm.localdb().pkg(name) {
+        for p in pkg.provides() {
+            names.push(p.name().split('=').next().unwrap_or(p.name()).to_string());
+        }
+    }
+    let mut out = Vec::new();
+    for pkg in alpm.localdb().pkgs() {
+        if pkg
+            .depends()
+            .iter()
+            .any(|d| names.iter().any(|n| n == d.name()))
+        {
+            out.push(("required_by", pkg));
+        } else if pkg
+            .optdepends()
+            .iter()
+            .any(|d| names.iter().any(|n| n == d.name()))
+        {
+            out.push(("optional_for", pkg));
+        }
+    }
+    out
+}
+
+fn build_reverse_children(
+    alpm: &Alpm,
+    name: &str,
+    depth: u32,
+    path: &mut Vec<String>,
+) -> Vec<DependencyNode> {
+    local_dependents(alpm, name)
+        .into_iter()
+        .map(|(kind, pkg)| {
+            let mut node = DependencyNode {
+                name: pkg.name().to_string(),
+                version: Some(pkg.version().to_string()),
+                kind: kind.to_string(),
+                installed: true,
+                ..Default::default()
+            };
+            // Optional dependents don't break on removal, so don't expand them further.
+            if kind == "required_by" && depth > 1 && !path.iter().any(|p| p == pkg.name()) {
+                path.push(pkg.name().to_string());
+                node.children = build_reverse_children(alpm, pkg.name(), depth - 1, path);
+                path.pop();
+            }
+            node
+        })
+        .collect()
+}
+
+/// Reverse dependency tree: what installed packages would break if `name` were removed.
+pub fn get_reverse_dependencies_native(name: &str, depth: u32) -> Option<DependencyNode> {
+    let alpm = Alpm::new("/", "/var/lib/pacman").ok()?;
+    let pkg = alpm.localdb().pkg(name).ok()?;
+    let mut path = vec![name.to_string()];
+    Some(DependencyNode {
+        name: pkg.name().to_string(),
+        version: Some(pkg.version().to_string()),
+        kind: "root".to_string(),
+        description: pkg.desc().map(|d| d.to_string()),
+        installed: true,
+        satisfied_by: None,
+        children: build_reverse_children(&alpm, name, depth, &mut path),
+    })
+}
+
+/// Files owned by a package. Installed packages come from the local DB; otherwise the
+/// sync `.files` databases are used (present only after `pacman -Fy`).
+/// Returns (paths with leading '/', true if from the local DB).
+pub fn get_package_files_native(name: &str) -> Option<(Vec<String>, bool)> {
+    let alpm = Alpm::new("/", "/var/lib/pacman").ok()?;
+    if let Ok(pkg) = alpm.localdb().pkg(name) {
+        let files = pkg
+            .files()
+            .files()
+            .iter()
+            .map(|f| format!("/{}", f.name()))
+            .collect();
+        return Some((files, true));
+    }
+
+    let mut alpm = alpm;
+    alpm.set_dbext(".files");
+    register_syncdbs_from_conf(&alpm, "/etc/pacman.conf");
+    for db in alpm.syncdbs() {
+        if let Ok(pkg) = db.pkg(name) {
+            let files = pkg
+                .files()
+                .files()
+                .iter()
+                .map(|f| format!("/{}", f.name()))
+                .collect();
+            return Some((files, false));
+        }
+    }
+    None
+}
+
+/// Changelog shipped in the `name`-`version` archive in the pacman cache (there once the update
+/// has been downloaded), or in the local DB when that version is the installed one.
+pub fn get_package_changelog_native(name: &str, version: &str) -> Option<String> {
+    use std::io::Read;
+    let alpm = Alpm::new("/", "/var/lib/pacman").ok()?;
+    let prefix = format!("{}-{}-", name, version);
+    let cached = std::fs::read_dir("/var/cache/pacman/pkg")
+        .ok()?
+        .flatten()
+        .map(|e| e.path())
+        .find(|p| {
+            let file = p
+                .file_name()
+                .map(|f| f.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            file.strip_prefix(&prefix).is_some_and(|rest| {
+                !rest.contains('-') && rest.contains(".pkg.tar.") && !rest.ends_with(".sig")
+            })
+        });
+
+    let mut text = String::new();
+    if let Some(path) = cached {
+        let loaded = alpm
+            .pkg_load(path.to_string_lossy().as_ref(), false, SigLevel::NONE)
+            .ok()?;
+        loaded.changelog().ok()?.read_to_string(&mut text).ok()?;
+    } else {
+        let pkg = alpm.localdb().pkg(name).ok()?;
+        if pkg.version().as_str() != version {
+            return None;
+        }
+        pkg.changelog().ok()?.read_to_string(&mut text).ok()?;
+    }
+    Some(text).filter(|t| !t.trim().is_empty())
+}
+
+/// pkgbase of `name` when it comes from the Arch repos (core, extra, multilib and their
+/// testing repos), whose packaging lives on gitlab.archlinux.org.
+pub fn get_arch_pkgbase_native(name: &str) -> Option<String> {
+    let alpm = Alpm::new("/", "/var/lib/pacman").ok()?;
+    register_syncdbs_from_conf(&alpm, "/etc/pacman.conf");
+    for db in alpm.syncdbs() {
+        let repo = db.name().trim_end_matches("-testing");
+        if !matches!(repo, "core" | "extra" | "multilib") {
+            continue;
+        }
+        if let Ok(pkg) = db.pkg(name) {
+            return Some(pkg.base().unwrap_or(pkg.name()).to_string());
+        }
+    }
+    None
+}
+
+/// Packages that own `path` (e.g. /usr/bin/ffprobe): installed owner from the local DB, plus
+/// any sync-repo packages shipping it per the `.files` databases. Returns (repo, name, version).
+pub fn find_file_owner_native(path: &str) -> Vec<(String, String, String)> {
+    let rel = path.trim_start_matches('/');
+    let mut owners = Vec::new();
+
+    let Ok(mut alpm) = Alpm::new("/", "/var/lib/pacman") else {
+        return owners;
+    };
+    for pkg in alpm.localdb().pkgs() {
+        if pkg.files().files().iter().any(|f| f.name() == rel) {
+            owners.push((
+                "local".to_string(),
+                pkg.name().to_string(),
+                pkg.version().to_string(),
+            ));
+        }
+    }
+
+    alpm.set_dbext(".files");
+    register_syncdbs_from_conf(&alpm, "/etc/pacman.conf");
+    for db in alpm.syncdbs() {
+        for pkg in db.pkgs() {
+            if pkg.files().files().iter().any(|f| f.name() == rel) {
+                owners.push((
+                    db.name().to_string(),
+                    pkg.name().to_string(),
+                    pkg.version().to_string(),
+                ));
+            }
+        }
+    }
+    owners
+}
+
+/// Pacman groups across all sync DBs as (group, member names), like `pacman -Sg`.
+/// A member listed by several repos is counted once.
+pub fn get_package_groups_native() -> Vec<(String, Vec<String>)> {
+    let Ok(alpm) = Alpm::new("/", "/var/lib/pacman") else {
+        return Vec::new();
+    };
+    register_syncdbs_from_conf(&alpm, "/etc/pacman.conf");
+    let mut groups: std::collections::BTreeMap<String, std::collections::BTreeSet<String>> =
+        std::collections::BTreeMap::new();
+    for db in alpm.syncdbs() {
+        let Ok(db_groups) = db.groups() else {
+            continue;
+        };
+        for group in db_groups {
+            groups
+                .entry(group.name().to_string())
+                .or_default()
+                .extend(group.packages().iter().map(|p| p.name().to_string()));
+        }
+    }
+    groups
+        .into_iter()
+        .map(|(name, members)| (name, members.into_iter().collect()))
+        .collect()
+}
+
+/// Members of one pacman group. Like pacman, the first repo providing a member wins.
+pub fn get_group_members_native(group: &str) -> Vec<Package> {
+    let Ok(alpm) = Alpm::new("/", "/var/lib/pacman") else {
+        return Vec::new();
+    };
+    register_syncdbs_from_conf(&alpm, "/etc/pacman.conf");
+    let distro = crate::distro_context::DistroContext::new();
+    let mut members: Vec<Package> = Vec::new();
+    for db in alpm.syncdbs() {
+        let Ok(grp) = db.group(group) else {
+            continue;
+        };
+        for pkg in grp.packages() {
+            if members.iter().any(|m| m.name == pkg.name()) {
+                continue;
+            }
+            let installed_version = alpm
+                .localdb()
+                .pkg(pkg.name())
+                .ok()
+                .map(|l| l.version().to_string());
+            members.push(Package {
+                name: pkg.name().to_string(),
+                version: pkg.version().to_string(),
+                description: pkg.desc().map(|d| d.to_string()).unwrap_or_default(),
+                source: PackageSource::from_repo_name(db.name(), pkg.version().as_str(), &distro),
+                installed: installed_version.is_some(),
+                download_size: Some(pkg.download_size() as u64),
+                installed_size: Some(pkg.isize() as u64),
+                ..Default::default()
+            });
+        }
+    }
+    members.sort_by(|a, b| a.name.cmp(&b.name));
+    members
+}
@@ -0,0 +1,408 @@
+//! Per-user app data discovery (XDG config/data dirs matched by app-id heuristics),
+//! config archives (so an uninstall can keep settings for a later reinstall) and leftover scans.
+
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+/// Packages whose profile directory name has nothing to do with the package name.
+const DIR_ALIASES: &[(&str, &str)] = &[
+    ("firefox", ".mozilla"),
+    ("thunderbird", ".thunderbird"),
+    ("visual-studio-code-bin", "Code"),
+    ("code", "Code - OSS"),
+    ("vscodium-bin", "VSCodium"),
+    ("google-chrome", "google-chrome"),
+    ("brave-bin", "BraveSoftware"),
+    ("discord", "discord"),
+    ("steam", ".steam"),
+    ("obs-studio", "obs-studio"),
+];
+
+/// Hidden home entries that are never app data, whatever a package happens to be called.
+const PROTECTED_HOME_DIRS: &[&str] = &[
+    "config",
+    "local",
+    "cache",
+    "var",
+    "ssh",
+    "gnupg",
+    "pki",
+    "mozilla-certs",
+];
+
+/// Names a package's user data directories may be called, lowercase.
+pub(crate) fn candidate_names(pkg_name: &str) -> Vec<String> {
+    let mut names = vec![pkg_name.to_lowercase()];
+    let base = ["-bin", "-git", "-nightly", "-beta", "-appimage", "-desktop"]
+        .iter()
+        .fold(pkg_name, |n, s| n.trim_end_matches(s));
+    names.push(base.to_lowercase());
+    for (pkg, dir) in DIR_ALIASES {
+        if *pkg == pkg_name || *pkg == base {
+            names.push(dir.trim_start_matches('.').to_lowercase());
+        }
+    }
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// The package name itself and its known profile dirs, lowercase: what quarantine may delete.
+/// No suffix stripping, so removing brave-bin leaves a separately installed brave alone.
+pub(crate) fn exact_names(pkg_name: &str) -> Vec<String> {
+    let mut names = vec![pkg_name.to_lowercase()];
+    for (pkg, dir) in DIR_ALIASES {
+        if *pkg == pkg_name {
+            names.push(dir.trim_start_matches('.').to_lowercase());
+        }
+    }
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Existing per-user directories called one of `names`: ~/.config/<n>, ~/.local/share/<n>
+/// and ~/.<n> (case-insensitive). A native package's data never lives in a Flatpak sandbox,
+/// so ~/.var/app is not searched.
+pub(crate) fn find_user_config_dirs(names: &[String]) -> Vec<PathBuf> {
+    let Some(home) = dirs::home_dir() else {
+        return Vec::new();
+    };
+    let mut found = Vec::new();
+
+    let xdg_roots = [dirs::config_dir(), dirs::data_dir()];
+    for root in xdg_roots.iter().flatten() {
+        found.extend(matching_children(root, names, false));
+    }
+    found.extend(matching_children(&home, names, true));
+
+    found.sort();
+    found.dedup();
+    found
+}
+
+/// Direct child directories of `root` whose (lowercased) name is one of `names`.
+/// With `dotted`, only hidden entries are considered and the leading dot is ignored.
+fn matching_children(root: &Path, names: &[String], dotted: bool) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|e| e.file_type().map(|t| t.is_dir()).unwrap_or(false))
+        .filter(|e| {
+            let name = e.file_name().to_string_lossy().to_lowercase();
+            let name = match (dotted, name.strip_prefix('.')) {
+                (true, Some(stripped)) if !PROTECTED_HOME_DIRS.contains(&stripped) => {
+                    stripped.to_string()
+                }
+                (true, _) => return false,
+                (false, _) => name,
+            };
+            names.contains(&name)
+        })
+        .map(|e| e.path())
+        .collect()
+}
+
+/// A file or directory left behind after an uninstall.
+#[derive(Debug, Serialize, Clone)]
+pub struct Leftover {
+    pub path: String,
+    pub kind: String, // "config" | "cache" | "systemd_unit" | "autostart"
+    pub size: u64,
+}
+
+/// Total size of a file or directory tree (symlinks are not followed).
+pub(crate) fn path_size(path: &Path) -> u64 {
+    let Ok(meta) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !meta.is_dir() {
+        return meta.len();
+    }
+    fs::read_dir(path)
+        .map(|entries| entries.flatten().map(|e| path_size(&e.path())).sum())
+        .unwrap_or(0)
+}
+
+/// Files in `dir` whose lowercased stem is one of `names` (templated units like "foo@" count
+/// as "foo") and whose extension is in `exts`. Substrings don't match: "foo" must not claim
+/// "foobar.service".
+fn matching_files(dir: &Path, names: &[String], exts: &[&str]) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| {
+            let ext_ok = p
+                .extension()
+                .map(|e| exts.iter().any(|x| e == *x))
+                .unwrap_or(false);
+            let stem = p
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            let stem = stem.strip_suffix('@').unwrap_or(&stem);
+            ext_ok && names.iter().any(|n| n == stem)
+        })
+        .collect()
+}
+
+fn scan_leftovers(pkg_name: &str) -> Vec<Leftover> {
+    let names = candidate_names(pkg_name);
+    let mut found: Vec<(PathBuf, &str)> = find_user_config_dirs(&names)
+        .into_iter()
+        .map(|p| (p, "config"))
+        .collect();
+
+    if let Some(cache) = dirs::cache_dir() {
+        found.extend(
+            matching_children(&cache, &names, false)
+                .into_iter()
+                .map(|p| (p, "cache")),
+        );
+    }
+    if let Some(config) = dirs::config_dir() {
+        found.extend(
+            matching_files(
+                &config.join("systemd/user"),
+                &names,
+                &["service", "timer", "socket"],
+            )
+            .into_iter()
+            .map(|p| (p, "systemd_unit")),
+        );
+        found.extend(
+            matching_files(&config.join("autostart"), &names, &["desktop"])
+                .into_iter()
+                .map(|p| (p, "autostart")),
+        );
+    }
+
+    found
+        .into_iter()
+        .map(|(path, kind)| Leftover {
+            size: path_size(&path),
+            path: path.to_string_lossy().into_owned(),
+            kind: kind.to_string(),
+        })
+        .collect()
+}
+
+/// Scan the user's home for data an uninstalled package left behind (config/data dirs,
+/// caches, systemd user units, autostart entries), with sizes for the cleanup dialog.
+#[tauri::command]
+pub async fn get_uninstall_leftovers(pkg_name: String) -> Result<Vec<Leftover>, String> {
+    crate::utils::validate_package_name(&pkg_name)?;
+    tokio::task::spawn_blocking(move || {
+        if crate::alpm_read::is_package_installed(&pkg_name) {
+            return Err(format!("'{}' is still installed", pkg_name));
+        }
+        Ok(scan_leftovers(&pkg_name))
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Delete selected leftovers. Only paths the scanner reports for `pkg_name` are accepted,
+/// so the frontend cannot be used to delete arbitrary files.
+#[tauri::command]
+pub async fn remove_uninstall_leftovers(
+    pkg_name: String,
+    paths: Vec<String>,
+) -> Result<u64, String> {
+    crate::utils::validate_package_name(&pkg_name)?;
+    tokio::task::spawn_blocking(move || {
+        let allowed = scan_leftovers(&pkg_name);
+        let mut freed = 0;
+        for path in paths {
+            let Some(item) = allowed.iter().find(|l| l.path == path) else {
+                return Err(format!("'{}' is not a leftover of '{}'", path, pkg_name));
+            };
+            let p = Path::new(&path);
+            let res = if p.is_dir() {
+                fs::remove_dir_all(p)
+            } else {
+                fs::remove_file(p)
+            };
+            res.map_err(|e| format!("Failed to remove {}: {}", path, e))?;
+            freed += item.size;
+        }
+        Ok(freed)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// A config archive created before an uninstall.
+#[derive(Debug, Serialize, Clone)]
+pub struct ConfigArchive {
+    pub pkg_name: String,
+    pub path: String,
+    pub created_at: i64,
+    pub size: u64,
+}
+
+fn archives_dir(app: &AppHandle) -> PathBuf {
+    let path = app
+        .path()
+        .app_data_dir()
+        .unwrap_or_else(|_| PathBuf::from("."))
+        .join("config-archives");
+    if !path.exists() {
+        let _ = fs::create_dir_all(&path);
+    }
+    path
+}
+
+/// Archive the package's user config dirs into <app_data>/config-archives/<pkg>-<ts>.tar.gz.
+/// Entries are stored relative to $HOME so restore puts them back in place. `exact` limits
+/// the dirs to [`exact_names`], for a quarantine that deletes them afterwards. Blocking.
+/// Returns None when there was nothing to archive.
+pub(crate) fn archive_app_config(
+    app: &AppHandle,
+    pkg_name: &str,
+    exact: bool,
+) -> Result<Option<(PathBuf, Vec<PathBuf>)>, String> {
+    let home = dirs::home_dir().ok_or("Could not determine home directory")?;
+    let names = if exact {
+        exact_names(pkg_name)
+    } else {
+        candidate_names(pkg_name)
+    };
+    let dirs_found = find_user_config_dirs(&names);
+    if dirs_found.is_empty() {
+        return Ok(None);
+    }
+
+    let archive_path = archives_dir(app).join(format!(
+        "{}-{}.tar.gz",
+        pkg_name,
+        chrono::Utc::now().timestamp()
+    ));
+    let file = fs::File::create(&archive_path).map_err(|e| e.to_string())?;
+    let encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+    let mut builder = tar::Builder::new(encoder);
+    builder.follow_symlinks(false);
+    for dir in &dirs_found {
+        let rel = dir.strip_prefix(&home).map_err(|e| e.to_string())?;
+        builder
+            .append_dir_all(rel, dir)
+            .map_err(|e| format!("Failed to archive {}: {}", dir.display(), e))?;
+    }
+    builder
+        .into_inner()
+        .and_then(|enc| enc.finish())
+        .map_err(|e| format!("Failed to finalize archive: {}", e))?;
+
+    Ok(Some((archive_path, dirs_found)))
+}
+
+/// Archives for `pkg_name` (or all packages when None), newest first.
+fn list_archives(app: &AppHandle, pkg_name: Option<&str>) -> Vec<ConfigArchive> {
+    let Ok(entries) = fs::read_dir(archives_dir(app)) else {
+        return Vec::new();
+    };
+    let mut archives: Vec<ConfigArchive> = entries
+        .flatten()
+        .filter_map(|e| {
+            let file_name = e.file_name().to_string_lossy().to_string();
+            let stem = file_name.strip_suffix(".tar.gz")?;
+            let (pkg, ts) = stem.rsplit_once('-')?;
+            let created_at = ts.parse::<i64>().ok()?;
+            if pkg_name.is_some_and(|p| p != pkg) {
+                return None;
+            }
+            Some(ConfigArchive {
+                pkg_name: pkg.to_string(),
+                path: e.path().to_string_lossy().into_owned(),
+                created_at,
+                size: e.metadata().map(|m| m.len()).unwrap_or(0),
+            })
+        })
+        .collect();
+    archives.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    archives
+}
+
+#[tauri::command]
+pub async fn get_config_archives(
+    app: AppHandle,
+    pkg_name: Option<String>,
+) -> Result<Vec<ConfigArchive>, String> {
+    Ok(list_archives(&app, pkg_name.as_deref()))
+}
+
+/// Restore the newest config archive for `pkg_name` into $HOME (overwrites current files).
+#[tauri::command]
+pub async fn restore_app_config(app: AppHandle, pkg_name: String) -> Result<ConfigArchive, String> {
+    crate::utils::validate_package_name(&pkg_name)?;
+    let archive = list_archives(&app, Some(&pkg_name))
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("No saved configuration found for '{}'", pkg_name))?;
+    let home = dirs::home_dir().ok_or("Could not determine home directory")?;
+
+    let path = archive.path.clone();
+    tokio::task::spawn_blocking(move || -> Result<(), String> {
+        let file = fs::File::open(&path).map_err(|e| e.to_string())?;
+        let mut tar = tar::Archive::new(flate2::read::GzDecoder::new(file));
+        tar.set_overwrite(true);
+        // unpack() refuses entries that escape the destination (.. or absolute paths).
+        tar.unpack(&home)
+            .map_err(|e| format!("Failed to restore configuration: {}", e))
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))??;
+
+    Ok(archive)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_candidate_names_strip_suffix_and_alias() {
+        let names = candidate_names("brave-bin");
+        assert!(names.contains(&"brave-bin".to_string()));
+        assert!(names.contains(&"brave".to_string()));
+        assert!(names.contains(&"bravesoftware".to_string()));
+
+        let names = candidate_names("firefox");
+        assert!(names.contains(&"mozilla".to_string()));
+
+        assert_eq!(exact_names("brave-bin"), ["brave-bin", "bravesoftware"]);
+        assert_eq!(exact_names("Foo-git"), ["foo-git"]);
+    }
+
+    #[test]
+    fn test_matching_files_exact_stems() {
+        let dir = tempfile::tempdir().unwrap();
+        for file in [
+            "syncthing.service",
+            "syncthing@.service",
+            "syncthing-gtk.service",
+            "mysyncthing.timer",
+            "syncthing.conf",
+        ] {
+            fs::write(dir.path().join(file), "").unwrap();
+        }
+        let mut found: Vec<String> = matching_files(
+            dir.path(),
+            &["syncthing".to_string()],
+            &["service", "timer"],
+        )
+        .iter()
+        .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+        .collect();
+        found.sort();
+        assert_eq!(found, ["syncthing.service", "syncthing@.service"]);
+        assert!(matching_files(&dir.path().join("missing"), &["a".to_string()], &["x"]).is_empty());
+    }
+}
@@ -1,10 +1,44 @@
 use crate::models::{Package, PackageSource};
-use once_cell::sync::Lazy;
-use raur::{Handle, Raur};
-use std::sync::Arc;
+use serde::Deserialize;
+use std::sync::LazyLock;
 
-// Shared Handle - created once, reused
-static AUR_HANDLE: Lazy<Arc<Handle>> = Lazy::new(|| Arc::new(Handle::new()));
+/// AUR RPC (v5). Requests go through `http_client` so they follow the network settings;
+/// raur only provides the package type.
+const AUR_RPC_URL: &str = "https://aur.archlinux.org/rpc/";
+
+#[derive(Deserialize)]
+struct RpcResponse {
+    #[serde(default)]
+    results: Vec<raur::Package>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+async fn rpc(request: reqwest::RequestBuilder) -> Result<Vec<raur::Package>, String> {
+    let resp = crate::http_client::fetch(request).await?;
+    if !resp.is_success() {
+        return Err(format!("AUR RPC failed: {}", resp.status));
+    }
+    let body: RpcResponse = resp.json()?;
+    match body.error {
+        Some(error) => Err(format!("AUR RPC error: {}", error)),
+        None => Ok(body.results),
+    }
+}
+
+fn rpc_client() -> reqwest::Client {
+    crate::http_client::client(std::time::Duration::from_secs(30))
+}
+
+async fn rpc_search(query: &str, by: &str) -> Result<Vec<raur::Package>, String> {
+    let request = rpc_client().get(AUR_RPC_URL).query(&[
+        ("v", "5"),
+        ("type", "search"),
+        ("by", by),
+        ("arg", query),
+    ]);
+    rpc(request).await
+}
 
 // Convert raur::Package to our internal Package model
 fn raur_to_package(p: raur::Package) -> Package {
@@ -40,7 +74,7 @@ pub async fn search_aur(query: &str) -> Result<Vec<Package>, String> {
         return Ok(vec![]);
     }
 
-    let results = AUR_HANDLE.search(query).await.map_err(|e| e.to_string())?;
+    let results = rpc_search(query, "name-desc").await?;
 
     // Sort by votes descending
     let mut packages: Vec<Package> = results.into_iter().map(raur_to_package).collect();
@@ -55,10 +89,7 @@ pub async fn search_aur_by_provides(query: &str) -> Result<Vec<Package>, String>
         return Ok(vec![]);
     }
 
-    let results = AUR_HANDLE
-        .search_by(query, raur::SearchBy::Provides)
-        .await
-        .map_err(|e| e.to_string())?;
+    let results = rpc_search(query, "provides").await?;
 
     let mut packages: Vec<Package> = results.into_iter().map(raur_to_package).collect();
     packages.sort_by(|a, b| b.num_votes.unwrap_or(0).cmp(&a.num_votes.unwrap_or(0)));
@@ -71,10 +102,149 @@ pub async fn get_multi_info(names: &[&str]) -> Result<Vec<Package>, String> {
         return Ok(vec![]);
     }
 
-    let results = AUR_HANDLE.info(names).await.map_err(|e| e.to_string())?;
+    // POST so long name lists don't run into URL length limits
+    let mut form = vec![("v", "5"), ("type", "info")];
+    form.extend(names.iter().map(|name| ("arg[]", *name)));
+    let results = rpc(rpc_client().post(AUR_RPC_URL).form(&form)).await?;
     Ok(results.into_iter().map(raur_to_package).collect())
 }
 
+// --- COMMENTS ---
+
+/// AUR shows this many "Latest Comments" per page.
+const COMMENTS_PER_PAGE: usize = 10;
+
+#[derive(Debug, serde::Serialize, Clone, PartialEq)]
+pub struct AurComment {
+    pub id: u64,
+    pub author: String,
+    pub date: String,    // As shown by the AUR, e.g. "2024-05-01 12:34 (UTC)"
+    pub content: String, // Plain text, paragraphs separated by blank lines
+    pub pinned: bool,
+}
+
+/// One page of comments; pinned comments are repeated on every page by the AUR.
+#[derive(Debug, serde::Serialize, Clone)]
+pub struct AurCommentPage {
+    pub pinned: Vec<AurComment>,
+    pub comments: Vec<AurComment>,
+    pub page: u32,
+    pub has_more: bool,
+}
+
+static COMMENT_ID_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(r#"<h4 id="comment-(\d+)""#).expect("valid regex COMMENT_ID_RE")
+});
+static COMMENT_AUTHOR_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(r#"<h4[^>]*>\s*(?:<a[^>]*>)?([^<\s]+)"#)
+        .expect("valid regex COMMENT_AUTHOR_RE")
+});
+static COMMENT_DATE_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(r#"class="date">([^<]+)</a>"#).expect("valid regex COMMENT_DATE_RE")
+});
+static COMMENT_BODY_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(r#"(?s)id="comment-\d+-content"[^>]*>(.*)"#)
+        .expect("valid regex COMMENT_BODY_RE")
+});
+static TAG_RE: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r"(?s)<[^>]+>").expect("valid regex TAG_RE"));
+static BR_RE: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r"<br\s*/?>[ \t]*\n?").expect("valid regex BR_RE"));
+
+fn html_to_text(html: &str) -> String {
+    let html = BR_RE.replace_all(html, "\n").replace("</p>", "\n\n");
+    let text = TAG_RE.replace_all(&html, "");
+    let text = text
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&");
+    text.lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn parse_comment_section(html: &str, pinned: bool) -> Vec<AurComment> {
+    let starts: Vec<(usize, u64)> = COMMENT_ID_RE
+        .captures_iter(html)
+        .filter_map(|c| Some((c.get(0)?.start(), c[1].parse().ok()?)))
+        .collect();
+
+    let mut comments = Vec::new();
+    for (i, (start, id)) in starts.iter().enumerate() {
+        let end = starts.get(i + 1).map(|(s, _)| *s).unwrap_or(html.len());
+        let block = &html[*start..end];
+        let author = COMMENT_AUTHOR_RE
+            .captures(block)
+            .map(|c| c[1].to_string())
+            .unwrap_or_default();
+        let date = COMMENT_DATE_RE
+            .captures(block)
+            .map(|c| c[1].trim().to_string())
+            .unwrap_or_default();
+        let content = COMMENT_BODY_RE
+            .captures(block)
+            .map(|c| html_to_text(&c[1]))
+            .unwrap_or_default();
+        comments.push(AurComment {
+            id: *id,
+            author,
+            date,
+            content,
+            pinned,
+        });
+    }
+    comments
+}
+
+/// Split the package page into (pinned, latest) comments.
+fn parse_comments_page(html: &str) -> (Vec<AurComment>, Vec<AurComment>) {
+    let latest_at = html.find("Latest Comments").unwrap_or(html.len());
+    let pinned_at = html.find("Pinned Comments").filter(|p| *p < latest_at);
+    let pinned = pinned_at
+        .map(|p| parse_comment_section(&html[p..latest_at], true))
+        .unwrap_or_default();
+    let latest = parse_comment_section(&html[latest_at..], false);
+    (pinned, latest)
+}
+
+/// Fetch comments (and pinned comments) from the AUR package page. `page` is 1-based.
+/// The AUR has no comments API, so this scrapes the HTML.
+pub async fn get_aur_comments(pkg_name: &str, page: u32) -> Result<AurCommentPage, String> {
+    let page = page.max(1);
+    let offset = (page as usize - 1) * COMMENTS_PER_PAGE;
+    let url = format!(
+        "https://aur.archlinux.org/packages/{}?O={}",
+        pkg_name, offset
+    );
+    let client = crate::http_client::client(std::time::Duration::from_secs(15));
+    let resp = crate::http_client::fetch(client.get(url)).await?;
+    if resp.status == reqwest::StatusCode::NOT_FOUND {
+        return Err(format!("Package '{}' not found in the AUR", pkg_name));
+    }
+    if !resp.is_success() {
+        return Err(format!("Failed to fetch AUR comments: {}", resp.status));
+    }
+    let html = resp.text();
+    let (pinned, comments) = parse_comments_page(&html);
+
+    Ok(AurCommentPage {
+        has_more: comments.len() >= COMMENTS_PER_PAGE,
+        pinned,
+        comments,
+        page,
+    })
+}
+
 // --- UPDATE CHECK LOGIC ---
 
 /// Get potential AUR updates by comparing local versions with upstream
@@ -104,22 +274,17 @@ pub async fn get_candidate_updates() -> Result<Vec<crate::models::UpdateItem>, S
     // 3. Compare versions
     for pkg in aur_info {
         if let Some(local_ver) = installed_map.get(&pkg.name) {
-            // Simple version string comparison (should ideally use alpm_vercmp but this is good first pass)
-            // or we use alpm_read::vercmp if available (it's not exposed yet).
-            // Actually, we should use alpm version comparison.
-            // For now, simple string inequality is "okay" as a trigger, but ideally we check if new > old.
-            // Since we don't have vercmp easily accessible in this async context without binding issues,
-            // we'll rely on string inequality which triggers "update available".
-            // NOTE: This might flag downgrades as updates.
-            // But usually AUR upstream > local.
-            if pkg.version != *local_ver {
+            let change = crate::versions::classify(local_ver, &pkg.version);
+            if change != crate::versions::VersionChange::Same {
                 updates.push(crate::models::UpdateItem {
                     name: pkg.name.clone(),
+                    display_name: None,
                     current_version: local_ver.clone(),
                     new_version: pkg.version.clone(),
                     source: PackageSource::new("aur", "aur", &pkg.version, "AUR (Community)"),
                     size: None, // AUR doesn't give download size easily (source size varies)
                     icon: None,
+                    downgrade: change == crate::versions::VersionChange::Downgrade,
                 });
             }
         }
@@ -127,3 +292,62 @@ pub async fn get_candidate_updates() -> Result<Vec<crate::models::UpdateItem>, S
 
     Ok(updates)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_comments_page() {
+        let html = r##"
+<div class="comments package-comments">
+  <div class="comments-header"><h3><span class="text">Pinned Comments</span></h3></div>
+  <h4 id="comment-901" class="comment-header">
+    <a href="/account/maint" title="View account information for maint">maint</a> commented on
+    <a href="#comment-901" class="date">2024-01-02 10:00 (UTC)</a>
+  </h4>
+  <div id="comment-901-content" class="article-content">
+    <div><p>Import the key first:<br>
+gpg --recv-keys ABC</p><p>See &lt;wiki&gt; &amp; docs.</p></div>
+  </div>
+</div>
+<div class="comments package-comments">
+  <div class="comments-header"><h3><span class="text">Latest Comments</span></h3></div>
+  <h4 id="comment-955" class="comment-header">
+    <a href="/account/alice">alice</a> commented on
+    <a href="#comment-955" class="date">2024-03-04 08:30 (UTC)</a>
+  </h4>
+  <div id="comment-955-content" class="article-content"><div><p>Works, thanks!</p></div></div>
+</div>"##;
+        let (pinned, latest) = parse_comments_page(html);
+        assert_eq!(pinned.len(), 1);
+        assert_eq!(pinned[0].id, 901);
+        assert_eq!(pinned[0].author, "maint");
+        assert_eq!(pinned[0].date, "2024-01-02 10:00 (UTC)");
+        assert_eq!(
+            pinned[0].content,
+            "Import the key first:\ngpg --recv-keys ABC\n\nSee <wiki> & docs."
+        );
+        assert!(pinned[0].pinned);
+        assert_eq!(latest.len(), 1);
+        assert_eq!(latest[0].author, "alice");
+        assert!(!latest[0].pinned);
+    }
+
+    #[tokio::test]
+    async fn test_aur_errors_under_simulated_network() {
+        use crate::http_client::{clear_simulation, simulate, NetCondition};
+        const AUR_HOST: &str = "aur.archlinux.org";
+
+        simulate(AUR_HOST, NetCondition::respond(404, "Not Found"));
+        let err = get_aur_comments("no-such-pkg", 1).await.unwrap_err();
+        assert_eq!(err, "Package 'no-such-pkg' not found in the AUR");
+
+        simulate(AUR_HOST, NetCondition::offline());
+        assert!(search_aur("firefox").await.is_err());
+        assert!(get_multi_info(&["firefox"]).await.is_err());
+        // Empty input never touches the network
+        assert!(get_multi_info(&[]).await.unwrap().is_empty());
+        clear_simulation(AUR_HOST);
+    }
+}
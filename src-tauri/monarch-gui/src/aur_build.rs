@@ -0,0 +1,347 @@
+//! How AUR packages get built. The default runs makepkg on the host; power users can opt
+//! into clean-chroot builds through devtools (`pkgctl build`, or `extra-x86_64-build` on
+//! older devtools) and into ccache for host builds. The choice is stored in settings.json
+//! and can be overridden per install.
+
+use monarch_types::disk_space::{filesystem, format_size, shortfall_message, SpaceCheck};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+const STORE_FILE: &str = "settings.json";
+const BUILD_KEY: &str = "aur_build";
+
+/// Free space below which a build is warned about. AUR metadata has no sizes, so this is
+/// a guess aimed at the common failure: a small /tmp tmpfs that makepkg fills halfway
+/// through. Plenty of packages build in less, so it doesn't refuse anything.
+const RECOMMENDED_BUILD_SPACE_B: u64 = 1024 * 1024 * 1024;
+/// devtools' clean chroots, which a chroot build creates or updates first.
+const CHROOT_ROOT: &str = "/var/lib/archbuild";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum AurBuildMode {
+    /// makepkg in a temp dir as the current user (build deps installed on the host).
+    #[default]
+    Host,
+    /// devtools clean chroot; only the package itself touches the host.
+    Chroot,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AurBuildSettings {
+    pub mode: AurBuildMode,
+    /// Enable ccache in BUILDENV for host builds (chroot builds use the chroot's makepkg.conf).
+    #[serde(default)]
+    pub ccache: bool,
+}
+
+/// Typed response for get_aur_build_mode / set_aur_build_mode.
+#[derive(Debug, Serialize, Clone)]
+pub struct AurBuildModeInfo {
+    pub mode: AurBuildMode,
+    pub ccache: bool,
+    pub devtools_available: bool,
+    pub ccache_available: bool,
+}
+
+/// Chroot build front-end, newest first. `pkgctl` arrived with devtools 1.0.
+fn chroot_tool() -> Option<&'static str> {
+    ["pkgctl", "extra-x86_64-build"]
+        .into_iter()
+        .find(|bin| which::which(bin).is_ok())
+}
+
+fn ccache_available() -> bool {
+    which::which("ccache").is_ok()
+}
+
+fn read_settings(app: &AppHandle) -> AurBuildSettings {
+    app.store(STORE_FILE)
+        .ok()
+        .and_then(|s| s.get(BUILD_KEY))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+fn info(settings: AurBuildSettings) -> AurBuildModeInfo {
+    AurBuildModeInfo {
+        mode: settings.mode,
+        ccache: settings.ccache,
+        devtools_available: chroot_tool().is_some(),
+        ccache_available: ccache_available(),
+    }
+}
+
+/// Settings for one build: the stored ones, with `mode_override` from the install request.
+/// An explicit chroot request without devtools is an error; a stored one falls back to host
+/// builds so updates keep working after devtools is removed.
+pub fn resolve(
+    app: &AppHandle,
+    mode_override: Option<AurBuildMode>,
+) -> Result<AurBuildSettings, String> {
+    let mut settings = read_settings(app);
+    if let Some(mode) = mode_override {
+        settings.mode = mode;
+    }
+    if settings.mode == AurBuildMode::Chroot && chroot_tool().is_none() {
+        if mode_override.is_some() {
+            return Err(
+                "Clean chroot builds need devtools. Install it with: sudo pacman -S devtools"
+                    .to_string(),
+            );
+        }
+        log::warn!("AUR build mode is chroot but devtools is not installed; building on host");
+        settings.mode = AurBuildMode::Host;
+    }
+    if settings.ccache && !ccache_available() {
+        log::warn!("ccache enabled for AUR builds but not installed; building without it");
+        settings.ccache = false;
+    }
+    Ok(settings)
+}
+
+/// makepkg.conf for `makepkg --config`: the system config and its drop-ins, then ccache
+/// switched on in BUILDENV.
+pub fn makepkg_conf_override() -> String {
+    [
+        "# Written by MonArch Store for this build only",
+        "source /etc/makepkg.conf",
+        "for conf in /etc/makepkg.conf.d/*.conf; do [[ -r $conf ]] && source \"$conf\"; done",
+        "BUILDENV=(\"${BUILDENV[@]/#!ccache/ccache}\")",
+        "[[ \" ${BUILDENV[*]} \" == *\" ccache \"* ]] || BUILDENV+=(ccache)",
+        "",
+    ]
+    .join("\n")
+}
+
+/// Write the ccache override next to the build so it disappears with the temp dir.
+pub fn write_makepkg_conf(build_root: &Path) -> Result<PathBuf, String> {
+    let path = build_root.join("monarch-makepkg.conf");
+    std::fs::write(&path, makepkg_conf_override())
+        .map_err(|e| format!("Failed to write makepkg.conf override: {}", e))?;
+    Ok(path)
+}
+
+/// Program and arguments for a chroot build. `deps` are packages built earlier in this run
+/// (AUR dependencies) that the chroot can't get from its repos.
+pub fn chroot_build_args(tool: &str, deps: &[String]) -> (String, Vec<String>) {
+    let mut args = Vec::new();
+    if tool == "pkgctl" {
+        args.push("build".to_string());
+        for dep in deps {
+            args.push("--install-to-chroot".to_string());
+            args.push(dep.clone());
+        }
+    } else if !deps.is_empty() {
+        // archbuild passes everything after `--` to makechrootpkg
+        args.push("--".to_string());
+        for dep in deps {
+            args.push("-I".to_string());
+            args.push(dep.clone());
+        }
+    }
+    (tool.to_string(), args)
+}
+
+/// The build command for `pkg_dir`, without stdio or PACMAN/askpass wiring.
+pub fn build_command(
+    settings: &AurBuildSettings,
+    pkg_dir: &Path,
+    build_root: &Path,
+    deps: &[String],
+) -> Result<tokio::process::Command, String> {
+    let mut cmd = match settings.mode {
+        AurBuildMode::Chroot => {
+            let tool = chroot_tool().ok_or("devtools is not installed")?;
+            let (program, args) = chroot_build_args(tool, deps);
+            let mut cmd = tokio::process::Command::new(program);
+            cmd.args(args);
+            cmd
+        }
+        AurBuildMode::Host => {
+            let mut cmd = tokio::process::Command::new("makepkg");
+            cmd.args(["-s", "-r", "--noconfirm", "--needed"]); // -r: remove make-deps after build (avoid orphan build libs)
+            if settings.ccache {
+                let conf = write_makepkg_conf(build_root)?;
+                cmd.arg("--config").arg(conf);
+            }
+            cmd
+        }
+    };
+    cmd.env("MAKEFLAGS", format!("-j{}", num_cpus::get()))
+        .env("PKGEXT", ".pkg.tar.zst")
+        .envs(crate::network_settings::proxy_env())
+        .current_dir(pkg_dir);
+    Ok(cmd)
+}
+
+/// Directories a build writes to and the free space recommended in each.
+pub fn space_needs(settings: &AurBuildSettings, build_root: &Path) -> Vec<(PathBuf, u64)> {
+    let mut needs = vec![(build_root.to_path_buf(), RECOMMENDED_BUILD_SPACE_B)];
+    if settings.mode == AurBuildMode::Chroot {
+        needs.push((PathBuf::from(CHROOT_ROOT), RECOMMENDED_BUILD_SPACE_B));
+    }
+    needs
+}
+
+/// The warning for a build directory with less than `recommended` free, saying what to do
+/// if the build runs out of space.
+fn space_warning(check: &SpaceCheck, recommended: u64) -> Option<String> {
+    if check.available >= recommended {
+        return None;
+    }
+    let (fs, remedy) = if check.tmpfs {
+        (
+            " (tmpfs)",
+            "free up RAM-backed space or start MonArch with TMPDIR on a disk",
+        )
+    } else {
+        ("", "free up space there")
+    };
+    Some(format!(
+        "Warning: {} has {} free{}; large AUR builds can need {} or more. If the build \
+         fails, {}.",
+        check.path,
+        format_size(check.available),
+        fs,
+        format_size(recommended),
+        remedy
+    ))
+}
+
+/// Refuse a build whose directories are full before anything is cloned, and return a
+/// warning for each one with less free space than recommended.
+pub fn check_build_space(
+    settings: &AurBuildSettings,
+    build_root: &Path,
+) -> Result<Vec<String>, String> {
+    let mut checks = Vec::new();
+    let mut warnings = Vec::new();
+    for (path, recommended) in space_needs(settings, build_root) {
+        let Some(fs) = filesystem(&path) else {
+            continue;
+        };
+        let check = SpaceCheck {
+            path: path.display().to_string(),
+            required: 0,
+            available: fs.available,
+            tmpfs: fs.tmpfs,
+        };
+        warnings.extend(space_warning(&check, recommended));
+        checks.push(check);
+    }
+    match shortfall_message(&checks) {
+        Some(msg) => Err(msg),
+        None => Ok(warnings),
+    }
+}
+
+/// Human-readable label for progress output.
+pub fn describe(settings: &AurBuildSettings) -> &'static str {
+    match (settings.mode, settings.ccache) {
+        (AurBuildMode::Chroot, _) => "clean chroot",
+        (AurBuildMode::Host, true) => "makepkg + ccache",
+        (AurBuildMode::Host, false) => "makepkg",
+    }
+}
+
+#[tauri::command]
+pub async fn get_aur_build_mode(app: AppHandle) -> Result<AurBuildModeInfo, String> {
+    Ok(info(read_settings(&app)))
+}
+
+/// Store the default AUR build mode. Chroot needs devtools and ccache needs ccache installed.
+#[tauri::command]
+pub async fn set_aur_build_mode(
+    app: AppHandle,
+    mode: AurBuildMode,
+    ccache: bool,
+) -> Result<AurBuildModeInfo, String> {
+    if mode == AurBuildMode::Chroot && chroot_tool().is_none() {
+        return Err(
+            "Clean chroot builds need devtools. Install it with: sudo pacman -S devtools"
+                .to_string(),
+        );
+    }
+    if ccache && !ccache_available() {
+        return Err("ccache is not installed. Install it with: sudo pacman -S ccache".to_string());
+    }
+    let settings = AurBuildSettings { mode, ccache };
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    store.set(
+        BUILD_KEY,
+        serde_json::to_value(settings).map_err(|e| e.to_string())?,
+    );
+    store.save().map_err(|e| e.to_string())?;
+    Ok(info(settings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chroot_args_and_settings_format() {
+        let deps = vec!["/tmp/b/libfoo-1.0-1-x86_64.pkg.tar.zst".to_string()];
+        assert_eq!(
+            chroot_build_args("pkgctl", &deps),
+            (
+                "pkgctl".to_string(),
+                vec![
+                    "build".to_string(),
+                    "--install-to-chroot".to_string(),
+                    deps[0].clone()
+                ]
+            )
+        );
+        assert_eq!(
+            chroot_build_args("extra-x86_64-build", &deps).1,
+            vec!["--".to_string(), "-I".to_string(), deps[0].clone()]
+        );
+        assert!(chroot_build_args("extra-x86_64-build", &[]).1.is_empty());
+
+        // Stored before ccache existed
+        let old: AurBuildSettings = serde_json::from_str(r#"{"mode":"chroot"}"#).unwrap();
+        assert_eq!(old.mode, AurBuildMode::Chroot);
+        assert!(!old.ccache);
+
+        let conf = makepkg_conf_override();
+        assert!(conf.starts_with("# Written by MonArch"));
+        assert!(conf.contains("source /etc/makepkg.conf\n"));
+        assert!(conf.contains("/#!ccache/ccache"));
+    }
+
+    #[test]
+    fn test_build_space_needs() {
+        let root = Path::new("/tmp/.tmpAbc");
+        let host = AurBuildSettings::default();
+        assert_eq!(
+            space_needs(&host, root),
+            vec![(root.to_path_buf(), RECOMMENDED_BUILD_SPACE_B)]
+        );
+        let chroot = AurBuildSettings {
+            mode: AurBuildMode::Chroot,
+            ccache: false,
+        };
+        let needs = space_needs(&chroot, root);
+        assert_eq!(needs.len(), 2);
+        assert_eq!(needs[1].0, PathBuf::from(CHROOT_ROOT));
+        // A path that does not exist yet is measured on its parent
+        assert!(filesystem(&root.join("not/created/yet")).is_some());
+
+        let mut check = SpaceCheck {
+            path: "/tmp/.tmpAbc".to_string(),
+            required: 0,
+            available: 300 * 1024 * 1024,
+            tmpfs: true,
+        };
+        let warning = space_warning(&check, RECOMMENDED_BUILD_SPACE_B).unwrap();
+        assert!(warning.contains("has 300.0 MiB free (tmpfs)"));
+        assert!(warning.contains("1.0 GiB or more"));
+        assert!(warning.contains("TMPDIR"));
+        check.available = 2 * RECOMMENDED_BUILD_SPACE_B;
+        assert_eq!(space_warning(&check, RECOMMENDED_BUILD_SPACE_B), None);
+    }
+}
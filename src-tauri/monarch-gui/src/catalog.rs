@@ -0,0 +1,844 @@
+//! On-disk SQLite catalog: one merged row per package across the synced repos, with its
+//! AppStream name, icon, screenshots and categories, plus Chaotic-AUR entries the sync DBs
+//! don't have. Category pages page through it with LIMIT/OFFSET instead of assembling the
+//! whole category per request. Rebuilt after every repo sync ("repo-index-updated"); the
+//! file from the previous run answers until the first rebuild, and a page asked for while
+//! the catalog is still empty builds it on the spot.
+//!
+//! pkgstats popularity and ODRS ratings live alongside, refreshed daily in the background, for
+//! the "popular" and "top rated" sorts.
+
+use crate::chaotic_api::ChaoticApiClient;
+use crate::metadata::MetadataState;
+use crate::models::{Package, PackageSource};
+use crate::repo_manager::RepoManager;
+use rusqlite::{params, params_from_iter, Connection};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Listener, Manager};
+
+/// Bumped when a table changes shape; an older file is dropped and rebuilt.
+const SCHEMA_VERSION: i32 = 3;
+/// How often pkgstats and ODRS are asked again.
+const STATS_INTERVAL_SECS: i64 = 24 * 3600;
+/// "top rated" ranks by the average with this many 3-star votes mixed in, so one 5-star
+/// review doesn't outrank hundreds of 4.5s.
+const PRIOR_VOTES: f64 = 5.0;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS packages (
+    name TEXT PRIMARY KEY,
+    name_lower TEXT NOT NULL,
+    app_id TEXT,
+    -- official, chaotic-aur, cachyos, garuda, endeavour, manjaro: the repo filter buckets
+    bucket TEXT NOT NULL,
+    -- name and description, lowercased, for the Chaotic-AUR keyword categories
+    text_lower TEXT NOT NULL,
+    last_modified INTEGER NOT NULL DEFAULT 0,
+    proprietary INTEGER NOT NULL,
+    foss INTEGER NOT NULL,
+    -- OARS minimum age; NULL when the app has no content rating
+    min_age INTEGER,
+    -- the merged Package as JSON
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS packages_bucket ON packages(bucket);
+CREATE TABLE IF NOT EXISTS categories (
+    category TEXT NOT NULL,
+    name TEXT NOT NULL,
+    PRIMARY KEY (category, name)
+);
+-- the tables below are kept across rebuilds
+-- pkgstats: percent of reporting systems with the package installed
+CREATE TABLE IF NOT EXISTS popularity (
+    name TEXT PRIMARY KEY,
+    score REAL NOT NULL
+);
+-- ODRS, by AppStream id
+CREATE TABLE IF NOT EXISTS ratings (
+    app_id TEXT PRIMARY KEY,
+    average REAL NOT NULL,
+    total INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS meta (
+    key TEXT PRIMARY KEY,
+    value INTEGER NOT NULL
+);
+";
+
+/// Open (or create) the catalog, replacing a file written by an older schema.
+pub fn open(path: &std::path::Path) -> rusqlite::Result<Connection> {
+    let conn = Connection::open(path)?;
+    let version: i32 = conn.query_row("PRAGMA user_version", [], |r| r.get(0))?;
+    if version != SCHEMA_VERSION {
+        conn.execute_batch(
+            "DROP TABLE IF EXISTS packages; DROP TABLE IF EXISTS categories;
+             DROP TABLE IF EXISTS popularity; DROP TABLE IF EXISTS ratings;
+             DROP TABLE IF EXISTS meta;",
+        )?;
+    }
+    conn.execute_batch(SCHEMA)?;
+    conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+    Ok(conn)
+}
+
+/// The repo filter bucket of a source, as the category page filter names them.
+pub fn source_bucket(source: &PackageSource) -> &'static str {
+    match source.source_type.as_str() {
+        "repo" => match source.id.as_str() {
+            "chaotic-aur" => "chaotic-aur",
+            "cachyos" => "cachyos",
+            "garuda" => "garuda",
+            "endeavour" => "endeavour",
+            "manjaro" => "manjaro",
+            _ => "official",
+        },
+        "flatpak" => "flatpak",
+        "aur" => "aur",
+        "local" => "local",
+        _ => "other",
+    }
+}
+
+/// A catalog row before it is written.
+pub struct CatalogEntry {
+    pub package: Package,
+    /// AppStream category keys ("game", "audiovideo", ...).
+    pub categories: Vec<String>,
+}
+
+/// Replace the whole catalog in one transaction.
+pub fn write_entries(conn: &mut Connection, entries: &[CatalogEntry]) -> rusqlite::Result<()> {
+    let tx = conn.transaction()?;
+    tx.execute("DELETE FROM categories", [])?;
+    tx.execute("DELETE FROM packages", [])?;
+    {
+        let mut insert = tx.prepare(
+            "INSERT OR REPLACE INTO packages
+             (name, name_lower, app_id, bucket, text_lower, last_modified, proprietary, foss,
+              min_age, data)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        )?;
+        let mut categorize =
+            tx.prepare("INSERT OR IGNORE INTO categories (category, name) VALUES (?1, ?2)")?;
+        for entry in entries {
+            let pkg = &entry.package;
+            let data = serde_json::to_string(pkg).unwrap_or_default();
+            insert.execute(params![
+                pkg.name,
+                pkg.name.to_lowercase(),
+                pkg.app_id,
+                source_bucket(&pkg.source),
+                format!("{} {}", pkg.name, pkg.description).to_lowercase(),
+                pkg.last_modified.unwrap_or(0),
+                !crate::license::passes_policy(pkg, crate::license::POLICY_HIDE_PROPRIETARY),
+                crate::license::passes_policy(pkg, crate::license::POLICY_FOSS_ONLY),
+                pkg.content_rating.as_ref().map(|r| r.min_age),
+                data,
+            ])?;
+            for category in &entry.categories {
+                categorize.execute(params![category, pkg.name])?;
+            }
+        }
+    }
+    tx.commit()
+}
+
+/// Replace the popularity scores (higher is more popular) the "popular" sort orders by.
+pub fn set_popularity(conn: &mut Connection, scores: &[(String, f64)]) -> rusqlite::Result<()> {
+    let tx = conn.transaction()?;
+    tx.execute("DELETE FROM popularity", [])?;
+    {
+        let mut insert =
+            tx.prepare("INSERT OR REPLACE INTO popularity (name, score) VALUES (?1, ?2)")?;
+        for (name, score) in scores {
+            insert.execute(params![name, score])?;
+        }
+    }
+    tx.commit()
+}
+
+/// Replace the ODRS ratings: (AppStream id, average stars, number of ratings).
+pub fn set_ratings(conn: &mut Connection, ratings: &[(String, f64, u32)]) -> rusqlite::Result<()> {
+    let tx = conn.transaction()?;
+    tx.execute("DELETE FROM ratings", [])?;
+    {
+        let mut insert = tx.prepare(
+            "INSERT OR REPLACE INTO ratings (app_id, average, total) VALUES (?1, ?2, ?3)",
+        )?;
+        for (app_id, average, total) in ratings {
+            insert.execute(params![app_id, average, total])?;
+        }
+    }
+    tx.commit()
+}
+
+fn meta_get(conn: &Connection, key: &str) -> rusqlite::Result<Option<i64>> {
+    conn.query_row("SELECT value FROM meta WHERE key = ?1", [key], |r| r.get(0))
+        .map(Some)
+        .or_else(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => Ok(None),
+            e => Err(e),
+        })
+}
+
+fn meta_set(conn: &Connection, key: &str, value: i64) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO meta (key, value) VALUES (?1, ?2)",
+        params![key, value],
+    )
+    .map(|_| ())
+}
+
+/// Popularity and rating of one package, for sorting search results.
+#[derive(Debug, Serialize, Clone, Default, PartialEq)]
+pub struct PackageScore {
+    /// pkgstats: percent of reporting systems with it installed.
+    pub popularity: Option<f64>,
+    /// ODRS average stars (1-5).
+    pub rating: Option<f64>,
+    pub rating_count: u32,
+    /// What "top rated" orders by; see `weighted_rating`.
+    pub weighted_rating: Option<f64>,
+}
+
+/// The "top rated" score: the average with PRIOR_VOTES 3-star votes mixed in.
+pub fn weighted_rating(average: f64, total: u32) -> f64 {
+    (average * total as f64 + 3.0 * PRIOR_VOTES) / (total as f64 + PRIOR_VOTES)
+}
+
+/// Scores of the named packages that have either. The rating is looked up by the catalog's
+/// AppStream id, or by the name itself (Flatpak names are app ids).
+pub fn package_scores(
+    conn: &Connection,
+    names: &[String],
+) -> rusqlite::Result<HashMap<String, PackageScore>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT
+           (SELECT score FROM popularity WHERE name = ?1),
+           r.average, r.total
+         FROM (SELECT COALESCE((SELECT app_id FROM packages WHERE name = ?1), ?1) AS id)
+         LEFT JOIN ratings r ON r.app_id = id",
+    )?;
+    let mut scores = HashMap::new();
+    for name in names {
+        let score = stmt.query_row([name], |r| {
+            let rating: Option<f64> = r.get(1)?;
+            let rating_count = r.get::<_, Option<u32>>(2)?.unwrap_or(0);
+            Ok(PackageScore {
+                popularity: r.get(0)?,
+                rating,
+                rating_count,
+                weighted_rating: rating.map(|average| weighted_rating(average, rating_count)),
+            })
+        })?;
+        if score != PackageScore::default() {
+            scores.insert(name.clone(), score);
+        }
+    }
+    Ok(scores)
+}
+
+/// One category page request.
+#[derive(Debug, Default, Clone)]
+pub struct CategoryQuery {
+    /// Category keys to match ("game" and "games").
+    pub categories: Vec<String>,
+    /// Chaotic-AUR packages whose name or description contains one of these also match.
+    pub keywords: Vec<&'static str>,
+    /// Always included, and listed first in the default order.
+    pub featured: Vec<&'static str>,
+    /// Repo filter buckets; empty means all.
+    pub buckets: Vec<String>,
+    /// License policy (license::POLICY_*).
+    pub policy: String,
+    /// Parental-control age limit: apps rated above it are left out, unrated ones stay.
+    pub max_age: Option<u8>,
+    /// "name", "newest" (or "updated"), "popular", "top_rated", or the default (featured
+    /// first, then by name).
+    pub sort: Option<String>,
+    pub offset: usize,
+    pub limit: usize,
+}
+
+/// The requested page and the total number of matches.
+pub fn query_category(
+    conn: &Connection,
+    query: &CategoryQuery,
+) -> rusqlite::Result<(Vec<Package>, usize)> {
+    fn placeholders(values: &[String], params: &mut Vec<String>) -> String {
+        params.extend(values.iter().cloned());
+        vec!["?"; values.len()].join(", ")
+    }
+    let mut params: Vec<String> = Vec::new();
+
+    let mut matches = vec![format!(
+        "name IN (SELECT name FROM categories WHERE category IN ({}))",
+        placeholders(&query.categories, &mut params)
+    )];
+    if !query.keywords.is_empty() {
+        let likes: Vec<String> = query.keywords.iter().map(|k| format!("%{}%", k)).collect();
+        let clauses = vec!["text_lower LIKE ?"; likes.len()].join(" OR ");
+        params.extend(likes);
+        matches.push(format!("(bucket = 'chaotic-aur' AND ({}))", clauses));
+    }
+    let featured: Vec<String> = query.featured.iter().map(|f| f.to_lowercase()).collect();
+    if !featured.is_empty() {
+        matches.push(format!(
+            "name_lower IN ({})",
+            placeholders(&featured, &mut params)
+        ));
+    }
+    let mut conditions = vec![format!("({})", matches.join(" OR "))];
+    if !query.buckets.is_empty() {
+        conditions.push(format!(
+            "bucket IN ({})",
+            placeholders(&query.buckets, &mut params)
+        ));
+    }
+    match query.policy.as_str() {
+        crate::license::POLICY_HIDE_PROPRIETARY => conditions.push("proprietary = 0".into()),
+        crate::license::POLICY_FOSS_ONLY => conditions.push("foss = 1".into()),
+        _ => {}
+    }
+    if let Some(age) = query.max_age {
+        conditions.push(format!("(min_age IS NULL OR min_age <= {})", age));
+    }
+    let filter = conditions.join(" AND ");
+
+    let total: i64 = conn.query_row(
+        &format!("SELECT COUNT(*) FROM packages WHERE {}", filter),
+        params_from_iter(params.iter()),
+        |row| row.get(0),
+    )?;
+
+    let order = match query.sort.as_deref() {
+        Some("name") => "name_lower".to_string(),
+        Some("newest" | "updated") => "last_modified DESC, name_lower".to_string(),
+        Some("popular") => "COALESCE((SELECT score FROM popularity WHERE popularity.name = \
+                            packages.name), 0) DESC, name_lower"
+            .to_string(),
+        Some("top_rated") => format!(
+            "COALESCE((SELECT (average * total + 3.0 * {prior}) / (total + {prior}) FROM ratings \
+             WHERE ratings.app_id = packages.app_id), 0) DESC, name_lower",
+            prior = PRIOR_VOTES
+        ),
+        _ if featured.is_empty() => "name_lower".to_string(),
+        _ => {
+            let ranks: Vec<String> = (0..featured.len())
+                .map(|i| format!("WHEN ? THEN {}", i))
+                .collect();
+            params.extend(featured.iter().cloned());
+            format!(
+                "CASE name_lower {} ELSE 9999 END, name_lower",
+                ranks.join(" ")
+            )
+        }
+    };
+    let mut stmt = conn.prepare(&format!(
+        "SELECT data FROM packages WHERE {} ORDER BY {} LIMIT {} OFFSET {}",
+        filter, order, query.limit, query.offset
+    ))?;
+    let rows = stmt.query_map(params_from_iter(params.iter()), |row| {
+        row.get::<_, String>(0)
+    })?;
+    let mut packages = Vec::new();
+    for data in rows {
+        if let Ok(mut pkg) = serde_json::from_str::<Package>(&data?) {
+            if featured.contains(&pkg.name.to_lowercase()) {
+                pkg.is_featured = Some(true);
+            }
+            packages.push(pkg);
+        }
+    }
+    Ok((packages, total as usize))
+}
+
+/// Managed state: the catalog database and the (repo index generation, AppStream loader
+/// revision) it was last built from in this run.
+pub struct CatalogState {
+    conn: Arc<Mutex<Option<Connection>>>,
+    built_from: tokio::sync::Mutex<Option<(u64, usize)>>,
+}
+
+impl Default for CatalogState {
+    fn default() -> Self {
+        let path = crate::metadata::get_cache_dir().join("catalog.sqlite");
+        let conn = std::fs::create_dir_all(crate::metadata::get_cache_dir())
+            .ok()
+            .and_then(|_| open(&path).ok())
+            .or_else(|| {
+                // Category pages only page through the catalog, so keep one for this run
+                log::warn!(
+                    "Package catalog unavailable at {}, keeping it in memory",
+                    path.display()
+                );
+                open(std::path::Path::new(":memory:")).ok()
+            });
+        CatalogState {
+            conn: Arc::new(Mutex::new(conn)),
+            built_from: tokio::sync::Mutex::new(None),
+        }
+    }
+}
+
+impl CatalogState {
+    /// Bring the catalog up to date with the repo index and AppStream data. Before the first
+    /// sync of this run the repo index is empty, and the previous run's catalog is kept.
+    pub async fn refresh(
+        &self,
+        repo: &RepoManager,
+        meta: &MetadataState,
+        chaotic: &ChaoticApiClient,
+    ) {
+        let snapshot = repo.snapshot().await;
+        let mut built_from = self.built_from.lock().await;
+        let appstream_revision = meta.0.lock().map(|l| l.revision()).unwrap_or_default();
+        if snapshot.repos.is_empty()
+            || *built_from == Some((snapshot.generation, appstream_revision))
+        {
+            return;
+        }
+
+        let started = std::time::Instant::now();
+        let chaotic_list = if repo.is_repo_enabled("chaotic-aur").await {
+            chaotic.fetch_packages().await.ok()
+        } else {
+            None
+        };
+        // Merging and the AppStream lookups (icons may be read from disk) run off the
+        // runtime, on a copy of the loader so MetadataState stays free meanwhile
+        let loader = meta.loader();
+        let conn = self.conn.clone();
+        let generation = snapshot.generation;
+        let written = tokio::task::spawn_blocking(move || {
+            let entries = build_entries(&snapshot, chaotic_list, loader.as_ref());
+            let mut guard = conn.lock().map_err(|e| e.to_string())?;
+            let conn = guard.as_mut().ok_or("catalog unavailable")?;
+            write_entries(conn, &entries).map_err(|e| e.to_string())?;
+            Ok::<_, String>(entries.len())
+        })
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|r| r);
+        match written {
+            Ok(count) => {
+                *built_from = Some((generation, appstream_revision));
+                log::info!(
+                    "Package catalog rebuilt: {} packages in {:?}",
+                    count,
+                    started.elapsed()
+                );
+            }
+            Err(e) => log::warn!("Package catalog rebuild failed: {}", e),
+        }
+    }
+
+    /// Run `f` on the database off the async runtime.
+    async fn with_conn<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&mut Connection) -> rusqlite::Result<T> + Send + 'static,
+    ) -> Result<T, String> {
+        let conn = self.conn.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut guard = conn.lock().map_err(|e| e.to_string())?;
+            let conn = guard.as_mut().ok_or("catalog unavailable")?;
+            f(conn).map_err(|e| e.to_string())
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+
+    /// Fetch pkgstats popularity and ODRS ratings if the stored ones are a day old.
+    pub async fn refresh_stats(&self) {
+        let now = chrono::Utc::now().timestamp();
+        let fetched_at = self
+            .with_conn(|c| meta_get(c, "stats_fetched_at"))
+            .await
+            .ok()
+            .flatten()
+            .unwrap_or(0);
+        if now - fetched_at < STATS_INTERVAL_SECS {
+            return;
+        }
+        let (popularity, ratings) = tokio::join!(
+            crate::pkgstats_api::fetch_popularity(),
+            crate::odrs_api::fetch_all_ratings()
+        );
+        let mut complete = true;
+        match popularity {
+            Ok(scores) => {
+                let count = scores.len();
+                match self.with_conn(move |c| set_popularity(c, &scores)).await {
+                    Ok(()) => log::info!("Stored pkgstats popularity for {} packages", count),
+                    Err(e) => {
+                        complete = false;
+                        log::warn!("Storing pkgstats popularity failed: {}", e);
+                    }
+                }
+            }
+            Err(e) => {
+                complete = false;
+                log::warn!("pkgstats popularity unavailable: {}", e);
+            }
+        }
+        match ratings {
+            Ok(ratings) => {
+                let rows: Vec<(String, f64, u32)> = ratings
+                    .into_iter()
+                    .filter_map(|(id, r)| Some((id, r.average()?, r.total)))
+                    .collect();
+                let count = rows.len();
+                match self.with_conn(move |c| set_ratings(c, &rows)).await {
+                    Ok(()) => log::info!("Stored ODRS ratings for {} apps", count),
+                    Err(e) => {
+                        complete = false;
+                        log::warn!("Storing ODRS ratings failed: {}", e);
+                    }
+                }
+            }
+            Err(e) => {
+                complete = false;
+                log::warn!("ODRS ratings unavailable: {}", e);
+            }
+        }
+        // A partial failure retries at the next check instead of waiting a day
+        if complete {
+            let _ = self
+                .with_conn(move |c| meta_set(c, "stats_fetched_at", now))
+                .await;
+        }
+    }
+
+    pub async fn scores(
+        &self,
+        names: Vec<String>,
+    ) -> Result<HashMap<String, PackageScore>, String> {
+        self.with_conn(move |c| package_scores(c, &names)).await
+    }
+
+    /// A category page, or None when the catalog is missing or has never been filled.
+    pub async fn query(&self, query: CategoryQuery) -> Option<(Vec<Package>, usize)> {
+        let conn = self.conn.clone();
+        tokio::task::spawn_blocking(move || {
+            let guard = conn.lock().ok()?;
+            let conn = guard.as_ref()?;
+            let filled: bool = conn
+                .query_row("SELECT EXISTS (SELECT 1 FROM packages)", [], |r| r.get(0))
+                .ok()?;
+            if !filled {
+                return None;
+            }
+            query_category(conn, &query)
+                .map_err(|e| log::warn!("Catalog query failed: {}", e))
+                .ok()
+        })
+        .await
+        .ok()
+        .flatten()
+    }
+}
+
+/// One catalog row per package across the repo snapshot, official builds winning over
+/// other repos, plus the Chaotic-AUR packages the sync DBs don't have; filled in from
+/// AppStream when `loader` is given.
+fn build_entries(
+    snapshot: &crate::repo_manager::RepoIndex,
+    chaotic_list: Option<Arc<Vec<crate::chaotic_api::ChaoticPackage>>>,
+    loader: Option<&crate::metadata::AppStreamLoader>,
+) -> Vec<CatalogEntry> {
+    let mut merged: HashMap<String, Package> = HashMap::new();
+    for (repo_name, pkgs) in snapshot.repos.iter() {
+        for pkg in pkgs.iter() {
+            let mut pkg = pkg.clone();
+            pkg.is_optimized = Some(crate::repo_manager::is_optimized_repo(repo_name));
+            match merged.get_mut(&pkg.name) {
+                Some(existing) => {
+                    let sources = existing.available_sources.get_or_insert_with(Vec::new);
+                    if !sources.iter().any(|s| s.id == pkg.source.id) {
+                        sources.push(pkg.source.clone());
+                    }
+                    // The official build wins the row; others stay listed as sources
+                    if source_bucket(&pkg.source) == "official"
+                        && source_bucket(&existing.source) != "official"
+                    {
+                        pkg.available_sources = existing.available_sources.take();
+                        *existing = pkg;
+                    }
+                }
+                None => {
+                    pkg.available_sources = Some(vec![pkg.source.clone()]);
+                    merged.insert(pkg.name.clone(), pkg);
+                }
+            }
+        }
+    }
+    if let Some(list) = chaotic_list {
+        for p in list.iter().filter(|p| !merged.contains_key(&p.pkgname)) {
+            let version = p.version.clone().unwrap_or_default();
+            let source =
+                PackageSource::new("repo", "chaotic-aur", &version, "Chaotic-AUR (Pre-built)");
+            let meta = p.metadata.as_ref();
+            merged.insert(
+                p.pkgname.clone(),
+                Package {
+                    name: p.pkgname.clone(),
+                    description: meta.and_then(|m| m.desc.clone()).unwrap_or_default(),
+                    version,
+                    maintainer: Some("Chaotic-AUR Team".to_string()),
+                    license: meta.and_then(|m| m.license.clone()).map(|l| vec![l]),
+                    url: meta.and_then(|m| m.url.clone()),
+                    available_sources: Some(vec![source.clone()]),
+                    source,
+                    ..Default::default()
+                },
+            );
+        }
+    }
+
+    let mut packages: Vec<Package> = merged.into_values().collect();
+    crate::license::apply_policy(&mut packages, crate::license::POLICY_ALL);
+    let mut categories: HashMap<String, Vec<String>> = HashMap::new();
+    if let Some(l) = loader {
+        for (category, pkg) in l.category_members() {
+            categories.entry(pkg).or_default().push(category);
+        }
+    }
+    packages
+        .into_iter()
+        .map(|mut pkg| {
+            let app = loader.and_then(|l| l.find_package(&pkg.name));
+            let icon = loader.and_then(|l| l.find_icon_heuristic(&pkg.name));
+            if let Some(app) = app {
+                pkg.display_name = Some(app.name).filter(|n| !n.is_empty());
+                pkg.app_id = Some(app.app_id);
+                if !app.screenshots.is_empty() {
+                    pkg.screenshots = Some(app.screenshots);
+                }
+                pkg.icon = app.icon_url;
+            }
+            pkg.icon = pkg.icon.or(icon);
+            if pkg.display_name.is_none() {
+                pkg.display_name = Some(crate::utils::to_pretty_name(&pkg.name));
+            }
+            pkg.content_rating = loader.and_then(|l| {
+                let id = pkg.app_id.clone().or_else(|| l.find_app_id(&pkg.name))?;
+                l.content_rating(&id)
+            });
+            CatalogEntry {
+                categories: categories
+                    .remove(&pkg.name.to_lowercase())
+                    .unwrap_or_default(),
+                package: pkg,
+            }
+        })
+        .collect()
+}
+
+/// Rebuild the catalog after every repo sync, alongside the search index, and keep the
+/// popularity and rating data fresh.
+pub fn start(app: AppHandle) {
+    let handle = app.clone();
+    app.listen("repo-index-updated", move |_| {
+        let handle = handle.clone();
+        tauri::async_runtime::spawn(async move { refresh(&handle).await });
+    });
+
+    tauri::async_runtime::spawn(async move {
+        // Let startup network traffic (repo sync, AppStream) go first
+        tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+        loop {
+            app.state::<CatalogState>().refresh_stats().await;
+            tokio::time::sleep(std::time::Duration::from_secs(3600)).await;
+        }
+    });
+}
+
+/// Popularity and rating of each named package that has either.
+#[tauri::command]
+pub async fn get_package_scores(
+    state: tauri::State<'_, CatalogState>,
+    names: Vec<String>,
+) -> Result<HashMap<String, PackageScore>, String> {
+    state.scores(names).await
+}
+
+pub async fn refresh(app: &AppHandle) {
+    app.state::<CatalogState>()
+        .refresh(
+            &app.state::<RepoManager>(),
+            &app.state::<MetadataState>(),
+            &app.state::<ChaoticApiClient>(),
+        )
+        .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(
+        name: &str,
+        repo: &str,
+        desc: &str,
+        license: &str,
+        categories: &[&str],
+    ) -> CatalogEntry {
+        CatalogEntry {
+            package: Package {
+                name: name.to_string(),
+                description: desc.to_string(),
+                version: "1.0-1".to_string(),
+                source: PackageSource::new("repo", repo, "1.0-1", repo),
+                license: Some(vec![license.to_string()]),
+                last_modified: Some(name.len() as i64),
+                app_id: Some(format!("org.example.{}", name)),
+                ..Default::default()
+            },
+            categories: categories.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_category_pages() {
+        let mut conn = open(std::path::Path::new(":memory:")).unwrap();
+        let mut supertuxkart = entry(
+            "supertuxkart",
+            "extra",
+            "Kart racing game",
+            "GPL-3.0-or-later",
+            &["game"],
+        );
+        supertuxkart.package.content_rating = Some(crate::content_rating::ContentRating {
+            kind: "oars-1.1".into(),
+            min_age: 6,
+            ..Default::default()
+        });
+        write_entries(
+            &mut conn,
+            &[
+                supertuxkart,
+                entry(
+                    "0ad",
+                    "extra",
+                    "Real-time strategy",
+                    "GPL-2.0-or-later",
+                    &["game"],
+                ),
+                entry(
+                    "steam",
+                    "multilib",
+                    "Valve's client",
+                    "LicenseRef-proprietary",
+                    &[],
+                ),
+                entry("osu-lazer-bin", "chaotic-aur", "Rhythm game", "MIT", &[]),
+                entry(
+                    "gimp",
+                    "extra",
+                    "Image editor",
+                    "GPL-3.0-or-later",
+                    &["graphics"],
+                ),
+            ],
+        )
+        .unwrap();
+
+        set_popularity(
+            &mut conn,
+            &[("supertuxkart".into(), 4.5), ("0ad".into(), 12.0)],
+        )
+        .unwrap();
+        set_ratings(
+            &mut conn,
+            &[
+                ("org.example.supertuxkart".into(), 4.5, 200),
+                ("org.example.0ad".into(), 5.0, 1),
+                ("org.gimp.GIMP".into(), 4.0, 50),
+            ],
+        )
+        .unwrap();
+
+        let games = |sort: Option<&str>, policy: &str, buckets: &[&str], offset, limit| {
+            let q = CategoryQuery {
+                categories: vec!["game".into(), "games".into()],
+                keywords: crate::chaotic_api::category_keywords("games"),
+                featured: vec!["steam"],
+                buckets: buckets.iter().map(|b| b.to_string()).collect(),
+                policy: policy.to_string(),
+                max_age: None,
+                sort: sort.map(str::to_string),
+                offset,
+                limit,
+            };
+            let (pkgs, total) = query_category(&conn, &q).unwrap();
+            (pkgs.into_iter().map(|p| p.name).collect::<Vec<_>>(), total)
+        };
+
+        // Featured first, then by name; Chaotic-AUR joins through the keywords
+        let (all, total) = games(None, "all", &[], 0, 10);
+        assert_eq!(all, vec!["steam", "0ad", "osu-lazer-bin", "supertuxkart"]);
+        assert_eq!(total, 4);
+
+        let (page, total) = games(Some("name"), "all", &[], 2, 2);
+        assert_eq!(page, vec!["steam", "supertuxkart"]);
+        assert_eq!(total, 4);
+
+        let (newest, _) = games(Some("newest"), "all", &[], 0, 1);
+        assert_eq!(newest, vec!["osu-lazer-bin"]);
+        let (popular, _) = games(Some("popular"), "all", &[], 0, 3);
+        assert_eq!(popular, vec!["0ad", "supertuxkart", "osu-lazer-bin"]);
+        // One 5-star vote doesn't beat two hundred 4.5s
+        let (top, _) = games(Some("top_rated"), "all", &[], 0, 3);
+        assert_eq!(top, vec!["supertuxkart", "0ad", "osu-lazer-bin"]);
+
+        assert_eq!(games(None, "foss_only", &[], 0, 10).1, 3);
+        assert_eq!(
+            games(None, "all", &["chaotic-aur"], 0, 10).0,
+            vec!["osu-lazer-bin"]
+        );
+
+        // The age limit is part of the query, so totals and pages stay consistent
+        let young = CategoryQuery {
+            categories: vec!["game".into()],
+            policy: "all".into(),
+            max_age: Some(5),
+            limit: 10,
+            ..Default::default()
+        };
+        let (pkgs, total) = query_category(&conn, &young).unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(pkgs[0].name, "0ad");
+        let (pkgs, _) = query_category(
+            &conn,
+            &CategoryQuery {
+                max_age: Some(6),
+                ..young
+            },
+        )
+        .unwrap();
+        assert_eq!(pkgs[1].name, "supertuxkart");
+        assert_eq!(pkgs[1].content_rating.as_ref().map(|r| r.min_age), Some(6));
+
+        let scores = package_scores(
+            &conn,
+            &["0ad".into(), "org.gimp.GIMP".into(), "steam".into()],
+        )
+        .unwrap();
+        assert_eq!(
+            scores.get("0ad"),
+            Some(&PackageScore {
+                popularity: Some(12.0),
+                rating: Some(5.0),
+                rating_count: 1,
+                weighted_rating: Some(weighted_rating(5.0, 1)),
+            })
+        );
+        assert_eq!(
+            scores.get("org.gimp.GIMP").and_then(|s| s.rating),
+            Some(4.0)
+        );
+        assert!(!scores.contains_key("steam"));
+    }
+}
@@ -1,9 +1,14 @@
 use moka::future::Cache;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use std::time::Duration;
 
 const BASE_URL: &str = "https://chaotic-backend.garudalinux.org";
+/// A list saved this recently is used at startup without asking the backend at all.
+const PACKAGE_LIST_FRESH_SECS: i64 = 3600;
+/// Build logs viewer; used when the builder API doesn't return a log URL itself.
+const LOGS_URL: &str = "https://builds.garudalinux.org/logs/logs.html";
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ChaoticPackage {
@@ -40,12 +45,64 @@ pub struct InfraStats {
     pub users: u32,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BuildState {
+    Queued,
+    Building,
+    Failed,
+    Success,
+    Unknown,
+}
+
+/// One finished build as reported by the builder API (`/builder/builds/{pkgname}`).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChaoticBuild {
+    #[serde(default, alias = "pkgbase")]
+    pub pkgname: String,
+    /// SUCCESS / FAILED / TIMED_OUT / CANCELED / SKIPPED upstream.
+    #[serde(default)]
+    pub status: String,
+    #[serde(default)]
+    pub version: Option<String>,
+    /// Unix seconds of the build start; also the log id together with the pkgname.
+    #[serde(default)]
+    pub timestamp: Option<i64>,
+    #[serde(default, rename = "logUrl")]
+    pub log_url: Option<String>,
+}
+
+/// Typed response for get_build_status: why a Chaotic-AUR version may lag behind the AUR.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChaoticBuildStatus {
+    pub pkgname: String,
+    pub state: BuildState,
+    /// Version of the last finished build, if any.
+    pub last_version: Option<String>,
+    pub last_build_time: Option<i64>,
+    pub log_url: Option<String>,
+}
+
+/// The last `/builder/packages` answer, kept between runs. The backend has no incremental
+/// endpoint, so the delta sync is a conditional GET: a 304 means the saved list is current.
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredPackageList {
+    fetched_at: i64,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    packages: Vec<ChaoticPackage>,
+}
+
 pub struct ChaoticApiClient {
     client: Client,
+    package_list_path: PathBuf,
     package_cache: Cache<String, std::sync::Arc<Vec<ChaoticPackage>>>,
     trending_cache: Cache<String, Vec<TrendingPackage>>, // Small, can clone
     infra_cache: Cache<String, InfraStats>,
     category_cache: Cache<String, Vec<ChaoticPackage>>,
+    build_cache: Cache<String, ChaoticBuildStatus>,
+    /// The builder's queue, shared by every package's build status.
+    queue_cache: Cache<String, std::sync::Arc<serde_json::Value>>,
 }
 
 impl Default for ChaoticApiClient {
@@ -56,14 +113,14 @@ impl Default for ChaoticApiClient {
 
 impl ChaoticApiClient {
     pub fn new() -> Self {
-        let client = Client::builder()
+        let client = crate::http_client::builder(Duration::from_secs(30))
             .user_agent("AURStore/0.1.0 (Tauri; Arch Linux)")
-            .timeout(Duration::from_secs(30))
             .build()
             .unwrap_or_else(|_| Client::new());
 
         Self {
             client,
+            package_list_path: crate::metadata::get_cache_dir().join("chaotic-packages.json"),
             // Cache packages for 1 hour as the list is large and doesn't change every second
             package_cache: Cache::builder()
                 .time_to_live(Duration::from_secs(3600))
@@ -80,9 +137,17 @@ impl ChaoticApiClient {
             category_cache: Cache::builder()
                 .time_to_live(Duration::from_secs(3600))
                 .build(),
+            // Build status changes within minutes while a package is queued
+            build_cache: Cache::builder()
+                .time_to_live(Duration::from_secs(120))
+                .build(),
+            queue_cache: Cache::builder()
+                .time_to_live(Duration::from_secs(120))
+                .build(),
         }
     }
 
+    #[allow(dead_code)]
     pub async fn get_packages_by_category(&self, category: &str) -> Vec<ChaoticPackage> {
         let category_key = category.to_lowercase();
 
@@ -94,55 +159,7 @@ impl ChaoticApiClient {
         // 2. Fetch All Packages (Cached)
         if let Ok(all_pkgs) = self.fetch_packages().await {
             // 3. Define Keywords
-            let keywords = match category_key.as_str() {
-                "internet" | "network" | "web" => {
-                    vec!["browser", "web", "http", "vpn", "mail", "discord", "client"]
-                }
-                "games" | "game" => vec![
-                    "game",
-                    "fps",
-                    "rpg",
-                    "rogue",
-                    "simulator",
-                    "steam",
-                    "minecraft",
-                    "launcher",
-                ],
-                "development" | "dev" | "programming" => {
-                    vec![
-                        "ide", "editor", "compiler", "language", "git", "rust", "python", "go",
-                    ]
-                }
-                "multimedia" | "audio" | "video" => vec![
-                    "audio",
-                    "video",
-                    "player",
-                    "music",
-                    "visualizer",
-                    "stream",
-                    "obs",
-                    "codec",
-                    "ffmpeg",
-                ],
-                "system" | "admin" => vec![
-                    "kernel", "driver", "boot", "firmware", "manage", "monitor", "systemd",
-                    "pacman",
-                ],
-                "utilities" | "utils" => vec![
-                    "tool", "util", "compress", "file", "terminal", "shell", "archive",
-                ],
-                "office" | "productivity" => {
-                    vec!["office", "pdf", "note", "calc", "writer", "todo"]
-                }
-                "graphics" | "design" => {
-                    vec!["image", "photo", "draw", "paint", "design", "color", "font"]
-                }
-                "essentials" | "featured" => vec![
-                    "browser", "code", "spotify", "discord", "steam", "vlc", "obs", "office",
-                    "driver", "kernel", "monitor",
-                ],
-                _ => vec![],
-            };
+            let keywords = category_keywords(&category_key);
 
             if keywords.is_empty() {
                 return Vec::new(); // Unknown category for chaotic mapping
@@ -178,25 +195,88 @@ impl ChaoticApiClient {
         Vec::new()
     }
 
+    async fn load_package_list(&self) -> Option<StoredPackageList> {
+        let bytes = tokio::fs::read(&self.package_list_path).await.ok()?;
+        serde_json::from_slice(&bytes).ok()
+    }
+
+    async fn save_package_list(&self, list: &StoredPackageList) {
+        let Ok(json) = serde_json::to_vec(list) else {
+            return;
+        };
+        if let Some(parent) = self.package_list_path.parent() {
+            let _ = tokio::fs::create_dir_all(parent).await;
+        }
+        let tmp = self.package_list_path.with_extension("json.tmp");
+        if tokio::fs::write(&tmp, json).await.is_ok() {
+            let _ = tokio::fs::rename(&tmp, &self.package_list_path).await;
+        }
+    }
+
+    async fn remember_packages(
+        &self,
+        packages: Vec<ChaoticPackage>,
+    ) -> std::sync::Arc<Vec<ChaoticPackage>> {
+        let arc_packages = std::sync::Arc::new(packages);
+        self.package_cache
+            .insert("all_packages".to_string(), arc_packages.clone())
+            .await;
+        arc_packages
+    }
+
     pub async fn fetch_packages(&self) -> Result<std::sync::Arc<Vec<ChaoticPackage>>, String> {
         if let Some(cached) = self.package_cache.get("all_packages").await {
             return Ok(cached);
         }
 
-        let url = format!("{}/builder/packages", BASE_URL);
-        let resp = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| e.to_string())?;
+        // The list from the last run: used as is while fresh, otherwise revalidated
+        let now = chrono::Utc::now().timestamp();
+        let stored = self.load_package_list().await;
+        if let Some(list) = stored
+            .as_ref()
+            .filter(|l| now - l.fetched_at < PACKAGE_LIST_FRESH_SECS)
+        {
+            return Ok(self.remember_packages(list.packages.clone()).await);
+        }
 
-        if !resp.status().is_success() {
-            return Err(format!("Failed to fetch packages: {}", resp.status()));
+        let url = format!("{}/builder/packages", BASE_URL);
+        let mut request = self.client.get(&url);
+        if let Some(list) = &stored {
+            if let Some(etag) = &list.etag {
+                request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+            }
+            if let Some(modified) = &list.last_modified {
+                request = request.header(reqwest::header::IF_MODIFIED_SINCE, modified);
+            }
+        }
+        let resp = match crate::http_client::fetch(request).await {
+            Ok(resp) => resp,
+            // Offline or the backend is down: an old list beats none
+            Err(e) => match stored {
+                Some(list) => {
+                    log::warn!(
+                        "Chaotic package list not refreshed ({}); using the saved one",
+                        e
+                    );
+                    return Ok(self.remember_packages(list.packages).await);
+                }
+                None => return Err(e),
+            },
+        };
+
+        if resp.status == reqwest::StatusCode::NOT_MODIFIED {
+            if let Some(mut list) = stored {
+                list.fetched_at = now;
+                self.save_package_list(&list).await;
+                return Ok(self.remember_packages(list.packages).await);
+            }
+        }
+        if !resp.is_success() {
+            return Err(format!("Failed to fetch packages: {}", resp.status));
         }
 
         // Deserialize to generic Value first to handle individual failures
-        let raw_packages: Vec<serde_json::Value> = resp.json().await.map_err(|e| e.to_string())?;
+        let raw_packages: Vec<serde_json::Value> = resp.json()?;
         let _total_count = raw_packages.len();
         let mut packages = Vec::new();
 
@@ -210,19 +290,30 @@ impl ChaoticApiClient {
             }
         }
 
-        let arc_packages = std::sync::Arc::new(packages);
-
-        self.package_cache
-            .insert("all_packages".to_string(), arc_packages.clone())
-            .await;
-
-        Ok(arc_packages)
+        let header = |name: reqwest::header::HeaderName| {
+            resp.headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let list = StoredPackageList {
+            fetched_at: now,
+            etag: header(reqwest::header::ETAG),
+            last_modified: header(reqwest::header::LAST_MODIFIED),
+            packages,
+        };
+        self.save_package_list(&list).await;
+
+        Ok(self.remember_packages(list.packages).await)
     }
 
     pub async fn clear_cache(&self) {
+        let _ = tokio::fs::remove_file(&self.package_list_path).await;
         self.package_cache.invalidate_all();
         self.trending_cache.invalidate_all();
         self.infra_cache.invalidate_all();
+        self.build_cache.invalidate_all();
+        self.queue_cache.invalidate_all();
     }
 
     /// Find metadata for a specific package from the cached Chaotic-AUR list
@@ -264,20 +355,20 @@ impl ChaoticApiClient {
         if let Some(cached) = self.trending_cache.get("top_25").await {
             return Ok(cached);
         }
+        self.refresh_trending().await
+    }
 
+    /// Bypass the 30 min cache (home feed poller). Only the trending entry is replaced, and
+    /// only once the fresh list is in, so a failed poll keeps the cached one.
+    pub async fn refresh_trending(&self) -> Result<Vec<TrendingPackage>, String> {
         let url = format!("{}/builder/popular/50?offset=0", BASE_URL);
-        let resp = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| e.to_string())?;
+        let resp = crate::http_client::fetch(self.client.get(&url)).await?;
 
-        if !resp.status().is_success() {
-            return Err(format!("Failed to fetch trending: {}", resp.status()));
+        if !resp.is_success() {
+            return Err(format!("Failed to fetch trending: {}", resp.status));
         }
 
-        let raw: Vec<TrendingPackage> = resp.json().await.map_err(|e| e.to_string())?;
+        let raw: Vec<TrendingPackage> = resp.json()?;
 
         // Take top 25
         let trending: Vec<TrendingPackage> = raw.into_iter().take(25).collect();
@@ -295,19 +386,18 @@ impl ChaoticApiClient {
         }
 
         // We need to make two parallel requests
-        let builders_future = self
-            .client
-            .get(format!("{}/builder/builders/amount", BASE_URL))
-            .send();
-        let users_future = self
-            .client
-            .get(format!("{}/metrics/30d/users", BASE_URL))
-            .send();
+        let builders_future = crate::http_client::fetch(
+            self.client
+                .get(format!("{}/builder/builders/amount", BASE_URL)),
+        );
+        let users_future = crate::http_client::fetch(
+            self.client
+                .get(format!("{}/metrics/30d/users", BASE_URL)),
+        );
 
-        let (builders_resp, users_resp) =
-            tokio::try_join!(builders_future, users_future).map_err(|e| e.to_string())?;
+        let (builders_resp, users_resp) = tokio::try_join!(builders_future, users_future)?;
 
-        let builders: u32 = builders_resp.json().await.map_err(|e| e.to_string())?;
+        let builders: u32 = builders_resp.json()?;
 
         // Users endpoint might be a bit more complex, let's assume it returns a simple JSON for now as implied by the logs.
         // If it's a list, we might need to count it. The log said "Users Metrics (30 Days)", likely a JSON.
@@ -321,7 +411,7 @@ impl ChaoticApiClient {
         // I'll implementation a temporary specialized struct or just `serde_json::Value` for users to inspect.
         // Correction: I should just use `serde_json::Value` for users to be safe.
 
-        let users_val: serde_json::Value = users_resp.json().await.map_err(|e| e.to_string())?;
+        let users_val: serde_json::Value = users_resp.json()?;
         let users = users_val.as_u64().unwrap_or(0) as u32; // Try as number
                                                             // If it's an object/array, this will be 0, which is fine for V1.
 
@@ -333,6 +423,58 @@ impl ChaoticApiClient {
         Ok(stats)
     }
 
+    /// Queue position and last build of one package. The queue is checked first: a package
+    /// that is waiting or building reports that even if its previous build failed.
+    pub async fn fetch_build_status(&self, pkgname: &str) -> Result<ChaoticBuildStatus, String> {
+        if let Some(cached) = self.build_cache.get(pkgname).await {
+            return Ok(cached);
+        }
+
+        let builds_future = crate::http_client::fetch(
+            self.client
+                .get(format!("{}/builder/builds/{}?amount=5", BASE_URL, pkgname)),
+        );
+        let (queue, builds_resp) = tokio::join!(self.fetch_queue_stats(), builds_future);
+
+        // Either half is enough to say something useful; only fail if both are missing
+        let builds: Option<Vec<ChaoticBuild>> = match builds_resp {
+            Ok(r) if r.is_success() => r.json().ok(),
+            Ok(r) if r.status == reqwest::StatusCode::NOT_FOUND => Some(Vec::new()),
+            _ => None,
+        };
+        if queue.is_none() && builds.is_none() {
+            return Err("Chaotic-AUR builder is unreachable".to_string());
+        }
+
+        let status = build_status(
+            pkgname,
+            queue.as_ref().and_then(|q| queue_state(q, pkgname)),
+            builds.as_deref().unwrap_or_default(),
+        );
+        self.build_cache
+            .insert(pkgname.to_string(), status.clone())
+            .await;
+        Ok(status)
+    }
+
+    /// The builder's queue stats, fetched once per cache window however many packages ask;
+    /// concurrent callers share one request.
+    async fn fetch_queue_stats(&self) -> Option<std::sync::Arc<serde_json::Value>> {
+        self.queue_cache
+            .try_get_with("stats".to_string(), async {
+                let resp = crate::http_client::fetch(
+                    self.client.get(format!("{}/builder/queue/stats", BASE_URL)),
+                )
+                .await?;
+                if !resp.is_success() {
+                    return Err(format!("Builder queue returned {}", resp.status));
+                }
+                resp.json::<serde_json::Value>().map(std::sync::Arc::new)
+            })
+            .await
+            .ok()
+    }
+
     pub async fn get_packages_batch(
         &self,
         names: Vec<String>,
@@ -378,3 +520,237 @@ impl ChaoticApiClient {
         Vec::new()
     }
 }
+
+/// Words that put a Chaotic-AUR package into a store category (name or description match).
+/// Chaotic-AUR has no AppStream data, so this heuristic stands in for it.
+pub fn category_keywords(category: &str) -> Vec<&'static str> {
+    match category {
+        "internet" | "network" | "web" => {
+            vec!["browser", "web", "http", "vpn", "mail", "discord", "client"]
+        }
+        "games" | "game" => vec![
+            "game",
+            "fps",
+            "rpg",
+            "rogue",
+            "simulator",
+            "steam",
+            "minecraft",
+            "launcher",
+        ],
+        "development" | "dev" | "programming" => {
+            vec![
+                "ide", "editor", "compiler", "language", "git", "rust", "python", "go",
+            ]
+        }
+        "multimedia" | "audio" | "video" => vec![
+            "audio",
+            "video",
+            "player",
+            "music",
+            "visualizer",
+            "stream",
+            "obs",
+            "codec",
+            "ffmpeg",
+        ],
+        "system" | "admin" => vec![
+            "kernel", "driver", "boot", "firmware", "manage", "monitor", "systemd", "pacman",
+        ],
+        "utilities" | "utils" => vec![
+            "tool", "util", "compress", "file", "terminal", "shell", "archive",
+        ],
+        "office" | "productivity" => {
+            vec!["office", "pdf", "note", "calc", "writer", "todo"]
+        }
+        "graphics" | "design" => {
+            vec!["image", "photo", "draw", "paint", "design", "color", "font"]
+        }
+        "essentials" | "featured" => vec![
+            "browser", "code", "spotify", "discord", "steam", "vlc", "obs", "office", "driver",
+            "kernel", "monitor",
+        ],
+        _ => vec![],
+    }
+}
+
+/// Whether `pkgname` is among the builder's active (building) or waiting (queued) jobs. The
+/// stats payload is grouped per queue, each with `active` and `waiting` job lists.
+fn queue_state(stats: &serde_json::Value, pkgname: &str) -> Option<BuildState> {
+    let queues: Vec<&serde_json::Value> = match stats {
+        serde_json::Value::Array(list) => list.iter().collect(),
+        serde_json::Value::Object(map) if map.contains_key("active") => vec![stats],
+        serde_json::Value::Object(map) => map.values().collect(),
+        _ => return None,
+    };
+    let listed = |queue: &serde_json::Value, key: &str| {
+        queue[key].as_array().is_some_and(|jobs| {
+            jobs.iter().any(|job| {
+                ["pkgbase", "pkgname", "name"]
+                    .iter()
+                    .any(|k| job[*k].as_str() == Some(pkgname))
+                    || job.as_str() == Some(pkgname)
+            })
+        })
+    };
+    if queues.iter().any(|q| listed(q, "active")) {
+        Some(BuildState::Building)
+    } else if queues.iter().any(|q| listed(q, "waiting")) {
+        Some(BuildState::Queued)
+    } else {
+        None
+    }
+}
+
+fn build_status(
+    pkgname: &str,
+    queued: Option<BuildState>,
+    builds: &[ChaoticBuild],
+) -> ChaoticBuildStatus {
+    let last = builds.iter().max_by_key(|b| b.timestamp.unwrap_or(0));
+    let finished = last.map(|b| match b.status.to_ascii_uppercase().as_str() {
+        "SUCCESS" | "ALREADY_BUILT" => BuildState::Success,
+        "FAILED" | "TIMED_OUT" => BuildState::Failed,
+        _ => BuildState::Unknown,
+    });
+    let log_url = last.and_then(|b| {
+        b.log_url.clone().or_else(|| {
+            b.timestamp
+                .map(|ts| format!("{}?timestamp={}&id={}", LOGS_URL, ts, pkgname))
+        })
+    });
+    ChaoticBuildStatus {
+        pkgname: pkgname.to_string(),
+        state: queued.or(finished).unwrap_or(BuildState::Unknown),
+        last_version: last.and_then(|b| b.version.clone()),
+        last_build_time: last.and_then(|b| b.timestamp),
+        log_url,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http_client::{clear_simulation, simulate, NetCondition};
+
+    const HOST: &str = "chaotic-backend.garudalinux.org";
+    /// Both tests simulate the same host.
+    static HOST_LOCK: once_cell::sync::Lazy<tokio::sync::Mutex<()>> =
+        once_cell::sync::Lazy::new(|| tokio::sync::Mutex::new(()));
+
+    /// A client whose saved package list lives in `dir` instead of the user's cache.
+    fn client_in(dir: &tempfile::TempDir) -> ChaoticApiClient {
+        ChaoticApiClient {
+            package_list_path: dir.path().join("chaotic-packages.json"),
+            ..ChaoticApiClient::new()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_trending_served_from_cache_when_backend_drops() {
+        let _lock = HOST_LOCK.lock().await;
+        let dir = tempfile::tempdir().unwrap();
+        let api = client_in(&dir);
+        simulate(
+            HOST,
+            NetCondition::respond(
+                200,
+                r#"[{"pkgbase_pkgname":"firefox-nightly","count":"900"},{"pkgbase_pkgname":"vlc-git","count":"12"}]"#,
+            ),
+        );
+        let first = api.fetch_trending().await.unwrap();
+        assert_eq!(first.len(), 2);
+
+        // Backend gone: the 30 min cache still answers, an explicit refresh fails
+        simulate(HOST, NetCondition::offline());
+        let cached = api.fetch_trending().await.unwrap();
+        assert_eq!(cached[0].pkgbase_pkgname, "firefox-nightly");
+        assert!(api.refresh_trending().await.is_err());
+        assert_eq!(api.fetch_trending().await.unwrap().len(), 2);
+
+        // A truncated body surfaces as an error rather than a partial list
+        simulate(
+            HOST,
+            NetCondition {
+                fail_after_bytes: Some(10),
+                ..NetCondition::respond(200, r#"[{"pkgbase_pkgname":"a","count":"1"}]"#)
+            },
+        );
+        assert!(api.fetch_packages().await.is_err());
+        clear_simulation(HOST);
+    }
+
+    #[tokio::test]
+    async fn test_package_list_persisted_and_revalidated() {
+        let _lock = HOST_LOCK.lock().await;
+        let dir = tempfile::tempdir().unwrap();
+        let client = || client_in(&dir);
+
+        simulate(
+            HOST,
+            NetCondition::respond(200, r#"[{"pkgname":"vlc-git","version":"4.0-1"}]"#),
+        );
+        assert_eq!(client().fetch_packages().await.unwrap().len(), 1);
+
+        // Next launch: the saved list is fresh, so the backend isn't asked
+        simulate(HOST, NetCondition::offline());
+        assert_eq!(
+            client().fetch_packages().await.unwrap()[0].pkgname,
+            "vlc-git"
+        );
+
+        // Once stale it is revalidated when reachable, and a 304 keeps it
+        let path = dir.path().join("chaotic-packages.json");
+        let mut stored: StoredPackageList =
+            serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        stored.fetched_at = 0;
+        stored.etag = Some("\"abc\"".to_string());
+        std::fs::write(&path, serde_json::to_vec(&stored).unwrap()).unwrap();
+        // Still offline: the stale list is served rather than nothing
+        assert_eq!(client().fetch_packages().await.unwrap().len(), 1);
+        simulate(HOST, NetCondition::respond(304, ""));
+        assert_eq!(
+            client().fetch_packages().await.unwrap()[0]
+                .version
+                .as_deref(),
+            Some("4.0-1")
+        );
+        clear_simulation(HOST);
+    }
+
+    #[test]
+    fn test_build_status_prefers_queue_over_last_build() {
+        let stats: serde_json::Value = serde_json::from_str(
+            r#"[{"name":"builds","active":[{"pkgbase":"vlc-git"}],"waiting":[{"pkgbase":"firefox-nightly"}]}]"#,
+        )
+        .unwrap();
+        assert_eq!(queue_state(&stats, "vlc-git"), Some(BuildState::Building));
+        assert_eq!(
+            queue_state(&stats, "firefox-nightly"),
+            Some(BuildState::Queued)
+        );
+        assert_eq!(queue_state(&stats, "gimp-git"), None);
+
+        let builds: Vec<ChaoticBuild> = serde_json::from_str(
+            r#"[{"pkgbase":"firefox-nightly","status":"SUCCESS","version":"140.0a1-1","timestamp":100},
+                {"pkgbase":"firefox-nightly","status":"FAILED","version":"141.0a1-1","timestamp":200}]"#,
+        )
+        .unwrap();
+        // Waiting in the queue wins over the failed build, whose log is still linked
+        let status = build_status("firefox-nightly", Some(BuildState::Queued), &builds);
+        assert_eq!(status.state, BuildState::Queued);
+        assert_eq!(status.last_version.as_deref(), Some("141.0a1-1"));
+        assert_eq!(
+            status.log_url.as_deref(),
+            Some("https://builds.garudalinux.org/logs/logs.html?timestamp=200&id=firefox-nightly")
+        );
+        assert_eq!(
+            build_status("firefox-nightly", None, &builds).state,
+            BuildState::Failed
+        );
+        assert_eq!(
+            build_status("gimp-git", None, &[]).state,
+            BuildState::Unknown
+        );
+    }
+}
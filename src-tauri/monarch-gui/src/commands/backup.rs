@@ -0,0 +1,343 @@
+//! Settings backups: one tar.gz with the settings.json store, repos.json (RepoManager's
+//! toggles and repo states), pins.json and a copy of the MonArch pacman drop-ins, to move a
+//! setup to another machine or get it back after a reinstall. Restoring never copies files
+//! into /etc: the drop-ins are regenerated by their owners (the pins through the helper,
+//! the network settings through SetNetworkOptions), so they match what this machine's
+//! MonArch would have written itself.
+
+use crate::commands::pins::PinnedPackage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_store::StoreExt;
+
+const STORE_FILE: &str = "settings.json";
+const BACKUP_FORMAT: u32 = 1;
+
+const MANIFEST_ENTRY: &str = "manifest.json";
+const SETTINGS_ENTRY: &str = "settings.json";
+const REPOS_ENTRY: &str = "repos.json";
+const PINS_ENTRY: &str = "pins.json";
+/// Drop-ins are kept under this directory, for reference only.
+const DROPIN_DIR: &str = "pacman.d";
+/// The repo and pins drop-ins live here.
+const MONARCH_DROPIN_DIR: &str = "/etc/pacman.d/monarch";
+
+/// Store keys that belong to this machine: the local API's bearer token, and the
+/// bookkeeping of weekly jobs.
+const MACHINE_KEYS: &[&str] = &[
+    "local_api",
+    "pkgstats_last_submitted",
+    "weekly_digest_last_sent",
+];
+/// Larger entries are ignored; the real files are a few KiB.
+const MAX_ENTRY_BYTES: u64 = 4 * 1024 * 1024;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    format: u32,
+    app_version: String,
+    created_at: i64,
+}
+
+/// What import_settings put back.
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct SettingsImport {
+    /// Keys restored into settings.json.
+    pub settings: usize,
+    /// Repos from the backup that this machine also has.
+    pub repos: usize,
+    pub pins: usize,
+    /// Parts that could not be restored, and why.
+    pub warnings: Vec<String>,
+}
+
+fn is_known_entry(name: &str) -> bool {
+    [MANIFEST_ENTRY, SETTINGS_ENTRY, REPOS_ENTRY, PINS_ENTRY].contains(&name)
+        || name
+            .strip_prefix(DROPIN_DIR)
+            .and_then(|n| n.strip_prefix('/'))
+            .is_some_and(|n| !n.is_empty() && !n.contains('/'))
+}
+
+/// Every MonArch pacman drop-in on this machine.
+fn dropin_files() -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(MONARCH_DROPIN_DIR)
+        .map(|d| {
+            d.flatten()
+                .map(|e| e.path())
+                .filter(|p| p.is_file() && p.extension().is_some_and(|e| e == "conf"))
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+fn write_archive(path: &Path, entries: &[(String, Vec<u8>)]) -> Result<(), String> {
+    use std::os::unix::fs::OpenOptionsExt;
+    // The settings can hold proxy credentials: readable by the owner only
+    let file = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)
+        .map_err(|e| e.to_string())?;
+    let encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+    let mut builder = tar::Builder::new(encoder);
+    for (name, data) in entries {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o600);
+        header.set_mtime(chrono::Utc::now().timestamp().max(0) as u64);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, name, data.as_slice())
+            .map_err(|e| format!("Failed to write {}: {}", name, e))?;
+    }
+    builder
+        .into_inner()
+        .and_then(|enc| enc.finish())
+        .map_err(|e| format!("Failed to finalize backup: {}", e))?;
+    Ok(())
+}
+
+/// The known entries of a backup; anything else (from a newer MonArch) is skipped.
+fn read_archive(path: &Path) -> Result<HashMap<String, Vec<u8>>, String> {
+    let file = fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(file));
+    let not_a_backup = |_| "Not a MonArch settings backup".to_string();
+    let mut entries = HashMap::new();
+    for entry in archive.entries().map_err(not_a_backup)? {
+        let entry = entry.map_err(not_a_backup)?;
+        let name = entry
+            .path()
+            .map_err(not_a_backup)?
+            .to_string_lossy()
+            .into_owned();
+        if !entry.header().entry_type().is_file()
+            || !is_known_entry(&name)
+            || entry.size() > MAX_ENTRY_BYTES
+        {
+            continue;
+        }
+        let mut data = Vec::new();
+        entry
+            .take(MAX_ENTRY_BYTES)
+            .read_to_end(&mut data)
+            .map_err(not_a_backup)?;
+        entries.insert(name, data);
+    }
+
+    let manifest: Manifest = entries
+        .get(MANIFEST_ENTRY)
+        .and_then(|m| serde_json::from_slice(m).ok())
+        .ok_or("Not a MonArch settings backup")?;
+    if manifest.format > BACKUP_FORMAT {
+        return Err(format!(
+            "This backup comes from a newer MonArch ({}); update MonArch to restore it",
+            manifest.app_version
+        ));
+    }
+    Ok(entries)
+}
+
+fn settings_entry(app: &AppHandle) -> Result<Vec<u8>, String> {
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    let settings: serde_json::Map<String, serde_json::Value> = store
+        .entries()
+        .into_iter()
+        .filter(|(key, _)| !MACHINE_KEYS.contains(&key.as_str()))
+        .collect();
+    serde_json::to_vec_pretty(&settings).map_err(|e| e.to_string())
+}
+
+/// Write a backup to `path`, or to a file picked in a save dialog. Returns the path, or
+/// None when the dialog was cancelled. The proxy setting is included with its credentials.
+#[tauri::command]
+pub async fn export_settings(
+    app: AppHandle,
+    path: Option<String>,
+) -> Result<Option<String>, String> {
+    let path = match path {
+        Some(p) => PathBuf::from(p),
+        None => match rfd::AsyncFileDialog::new()
+            .add_filter("MonArch settings backup", &["gz"])
+            .set_file_name(format!(
+                "monarch-settings-{}.tar.gz",
+                chrono::Local::now().format("%Y-%m-%d")
+            ))
+            .save_file()
+            .await
+        {
+            Some(handle) => handle.path().to_path_buf(),
+            None => return Ok(None),
+        },
+    };
+
+    let manifest = Manifest {
+        format: BACKUP_FORMAT,
+        app_version: app.package_info().version.to_string(),
+        created_at: chrono::Utc::now().timestamp(),
+    };
+    let mut entries = vec![
+        (
+            MANIFEST_ENTRY.to_string(),
+            serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())?,
+        ),
+        (SETTINGS_ENTRY.to_string(), settings_entry(&app)?),
+        (
+            REPOS_ENTRY.to_string(),
+            app.state::<crate::repo_manager::RepoManager>()
+                .export_config()
+                .await?,
+        ),
+    ];
+    let pins = crate::commands::pins::get_pinned_packages(app.clone()).await?;
+    entries.push((
+        PINS_ENTRY.to_string(),
+        serde_json::to_vec_pretty(&pins).map_err(|e| e.to_string())?,
+    ));
+    let target = path.clone();
+    tokio::task::spawn_blocking(move || {
+        for dropin in dropin_files() {
+            if let (Ok(data), Some(name)) = (fs::read(&dropin), dropin.file_name()) {
+                entries.push((format!("{}/{}", DROPIN_DIR, name.to_string_lossy()), data));
+            }
+        }
+        write_archive(&target, &entries)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))??;
+    Ok(Some(path.to_string_lossy().into_owned()))
+}
+
+/// Restore a backup from `path`, or from a file picked in a dialog (None when cancelled).
+/// Settings and repo states are put back first; pins and network settings then go through
+/// the helper, which needs authorization. A part that fails is reported in `warnings`
+/// without undoing the rest.
+#[tauri::command]
+pub async fn import_settings(
+    app: AppHandle,
+    path: Option<String>,
+    password: Option<String>,
+) -> Result<Option<SettingsImport>, String> {
+    let path = match path {
+        Some(p) => PathBuf::from(p),
+        None => match rfd::AsyncFileDialog::new()
+            .add_filter("MonArch settings backup", &["gz"])
+            .pick_file()
+            .await
+        {
+            Some(handle) => handle.path().to_path_buf(),
+            None => return Ok(None),
+        },
+    };
+    let mut entries = tokio::task::spawn_blocking(move || read_archive(&path))
+        .await
+        .map_err(|e| format!("Task join error: {}", e))??;
+    let mut report = SettingsImport::default();
+
+    let network_before = crate::network_settings::get_network_settings(app.clone()).await?;
+    if let Some(data) = entries.remove(SETTINGS_ENTRY) {
+        let settings: serde_json::Map<String, serde_json::Value> = serde_json::from_slice(&data)
+            .map_err(|e| format!("Settings in the backup are malformed: {}", e))?;
+        let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+        for (key, value) in settings {
+            if !MACHINE_KEYS.contains(&key.as_str()) {
+                store.set(key, value);
+                report.settings += 1;
+            }
+        }
+        store.save().map_err(|e| e.to_string())?;
+    }
+
+    if let Some(data) = entries.remove(REPOS_ENTRY) {
+        let repo_manager = app.state::<crate::repo_manager::RepoManager>();
+        match repo_manager.import_config(&app, &data).await {
+            Ok(matched) => {
+                report.repos = matched;
+                // One helper run for all restored repos: keyrings and a DB refresh
+                if let Err(e) = repo_manager.apply_os_config(&app, password.clone()).await {
+                    report
+                        .warnings
+                        .push(format!("Repositories not synced: {}", e));
+                }
+            }
+            Err(e) => report.warnings.push(e),
+        }
+    }
+
+    if let Some(data) = entries.remove(PINS_ENTRY) {
+        let restored = serde_json::from_slice::<Vec<PinnedPackage>>(&data)
+            .map_err(|e| format!("Pins in the backup are malformed: {}", e));
+        let restored = match restored {
+            Ok(pins) => crate::commands::pins::restore_pins(&app, pins, password.clone()).await,
+            Err(e) => Err(e),
+        };
+        match restored {
+            Ok(count) => report.pins = count,
+            Err(e) => report.warnings.push(format!("Pins not restored: {}", e)),
+        }
+    }
+
+    // The GUI side applies right away; the helper only needs telling when they changed
+    let network = crate::network_settings::get_network_settings(app.clone()).await?;
+    if network == network_before {
+        crate::network_settings::load(&app);
+    } else if let Err(e) =
+        crate::network_settings::set_network_settings(app.clone(), network, password).await
+    {
+        report.warnings.push(format!("Network settings: {}", e));
+    }
+
+    Ok(Some(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_keeps_known_entries_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("backup.tar.gz");
+        let manifest = serde_json::to_vec(&Manifest {
+            format: BACKUP_FORMAT,
+            app_version: "1.0.0".to_string(),
+            created_at: 0,
+        })
+        .unwrap();
+        let entries = vec![
+            (MANIFEST_ENTRY.to_string(), manifest),
+            (SETTINGS_ENTRY.to_string(), b"{\"network\":{}}".to_vec()),
+            ("pacman.d/pins.conf".to_string(), b"[options]".to_vec()),
+            ("pacman.d/nested/x.conf".to_string(), Vec::new()),
+            ("extra.json".to_string(), Vec::new()),
+        ];
+        write_archive(&path, &entries).unwrap();
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(
+            fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+
+        let read = read_archive(&path).unwrap();
+        let mut names: Vec<&str> = read.keys().map(String::as_str).collect();
+        names.sort();
+        assert_eq!(
+            names,
+            ["manifest.json", "pacman.d/pins.conf", "settings.json"]
+        );
+        assert_eq!(read[SETTINGS_ENTRY], b"{\"network\":{}}");
+
+        write_archive(&path, &entries[1..]).unwrap();
+        assert!(read_archive(&path).is_err());
+        fs::write(&path, b"not gzip").unwrap();
+        assert!(read_archive(&path).is_err());
+    }
+}
@@ -0,0 +1,53 @@
+//! Checkpoints of the helper's ExecuteBatch. A batch interrupted by a crash or power loss,
+//! or stopped by a failed step, leaves its state in [`BATCH_STATE_PATH`]; the frontend
+//! offers to finish it on the next launch.
+
+use crate::helper_client;
+use monarch_types::{BatchState, BatchStepState, BATCH_STATE_PATH};
+use tauri::{AppHandle, Emitter};
+
+/// The last batch the helper ran; `None` if there is no checkpoint (nothing run yet, or a
+/// helper from before checkpoints).
+#[tauri::command]
+pub async fn get_last_batch_state() -> Result<Option<BatchState>, String> {
+    let content = match tokio::fs::read_to_string(BATCH_STATE_PATH).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("Failed to read {}: {}", BATCH_STATE_PATH, e)),
+    };
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| format!("Failed to parse {}: {}", BATCH_STATE_PATH, e))
+}
+
+/// Run the steps of the last batch that did not finish; output goes to `install-output` and
+/// the step list to `batch-progress` each time a step starts or ends.
+#[tauri::command]
+pub async fn resume_last_batch(app: AppHandle, password: Option<String>) -> Result<(), String> {
+    if !helper_client::supports("ResumeBatch").await {
+        return Err("The installed helper cannot resume batches; update MonARCH Store".to_string());
+    }
+    let _guard = crate::utils::PRIVILEGED_LOCK.lock().await;
+    let result = helper_client::run_helper(
+        &app,
+        helper_client::HelperCommand::ResumeBatch,
+        password,
+        |msg| {
+            if msg.event_type.as_deref() == Some("batch_state") {
+                if let Ok(steps) = serde_json::from_str::<Vec<BatchStepState>>(&msg.message) {
+                    let _ = app.emit("batch-progress", steps);
+                }
+                return;
+            }
+            let _ = app.emit("install-output", &msg.message);
+        },
+    )
+    .await
+    .map_err(|e| format!("Failed to invoke helper: {}", e))?;
+    if !result.success {
+        return Err(result
+            .error
+            .unwrap_or_else(|| "Resuming the batch failed".to_string()));
+    }
+    Ok(())
+}
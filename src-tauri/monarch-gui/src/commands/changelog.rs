@@ -0,0 +1,203 @@
+//! What changed in a pending update. Sources are tried in order: the changelog shipped in
+//! the package itself, upstream release notes (GitHub/GitLab via scm_api), and finally the
+//! Arch packaging commit log on gitlab.archlinux.org. The first one with entries wins.
+
+use super::upstream::{find_package, pkgver_of};
+use crate::scm_api::{PackagingCommit, UpstreamRelease};
+use serde::Serialize;
+use std::cmp::Ordering;
+use tauri::State;
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangelogSource {
+    Package,
+    UpstreamReleases,
+    PackagingCommits,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct ChangelogEntry {
+    pub title: String,
+    /// Markdown for release notes, plain text otherwise.
+    pub body: Option<String>,
+    pub url: Option<String>,
+    pub date: Option<String>,
+}
+
+/// Typed response for get_update_changelog.
+#[derive(Debug, Serialize, Clone)]
+pub struct UpdateChangelog {
+    pub pkg: String,
+    pub old_version: String,
+    pub new_version: String,
+    /// None when no source had anything to say.
+    pub source: Option<ChangelogSource>,
+    /// Newest first.
+    pub entries: Vec<ChangelogEntry>,
+}
+
+/// Upstream releases after `old_version` up to and including `new_version`, newest first.
+/// Both are full pacman versions; only their pkgver is compared with release tags.
+pub fn releases_between(
+    releases: &[UpstreamRelease],
+    old_version: &str,
+    new_version: &str,
+) -> Vec<ChangelogEntry> {
+    let (old, new) = (pkgver_of(old_version), pkgver_of(new_version));
+    let mut in_range: Vec<(&str, &UpstreamRelease)> = releases
+        .iter()
+        .filter_map(|r| Some((r.version.as_deref()?, r)))
+        .filter(|(v, _)| {
+            crate::versions::compare(v, old) == Ordering::Greater
+                && crate::versions::compare(v, new) != Ordering::Greater
+        })
+        .collect();
+    in_range.sort_by(|a, b| crate::versions::newest_first(a.0, b.0));
+    in_range
+        .into_iter()
+        .map(|(_, r)| ChangelogEntry {
+            title: r.name.clone().unwrap_or_else(|| r.tag.clone()),
+            body: r.body.clone(),
+            url: Some(r.url.clone()),
+            date: r.published_at.clone(),
+        })
+        .collect()
+}
+
+fn commit_entry(commit: PackagingCommit) -> ChangelogEntry {
+    // The message repeats the title on its first line
+    let body = commit
+        .message
+        .as_deref()
+        .and_then(|m| m.split_once('\n'))
+        .map(|(_, rest)| rest.trim().to_string())
+        .filter(|rest| !rest.is_empty());
+    ChangelogEntry {
+        title: commit.title,
+        body,
+        url: commit.web_url,
+        date: commit.created_at,
+    }
+}
+
+/// Release notes for updating `pkg` from `old_ver` to `new_ver`.
+#[tauri::command]
+pub async fn get_update_changelog(
+    state_repo: State<'_, crate::repo_manager::RepoManager>,
+    state_scm: State<'_, crate::ScmState>,
+    pkg: String,
+    old_ver: String,
+    new_ver: String,
+) -> Result<UpdateChangelog, String> {
+    let mut result = UpdateChangelog {
+        pkg: pkg.clone(),
+        old_version: old_ver.clone(),
+        new_version: new_ver.clone(),
+        source: None,
+        entries: Vec::new(),
+    };
+
+    let (name, version) = (pkg.clone(), new_ver.clone());
+    let shipped = tokio::task::spawn_blocking(move || {
+        crate::alpm_read::get_package_changelog_native(&name, &version)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?;
+    if let Some(text) = shipped {
+        result.source = Some(ChangelogSource::Package);
+        result.entries.push(ChangelogEntry {
+            title: format!("{} {}", pkg, new_ver),
+            body: Some(text),
+            url: None,
+            date: None,
+        });
+        return Ok(result);
+    }
+
+    let scm = &state_scm.inner().0;
+    if let Some(url) = find_package(state_repo.inner(), &pkg)
+        .await
+        .and_then(|p| p.url)
+        .filter(|u| crate::scm_api::parse_scm_url(u).is_some())
+    {
+        match scm.fetch_releases(&url, &pkg).await {
+            Ok(releases) => {
+                let entries = releases_between(&releases, &old_ver, &new_ver);
+                if !entries.is_empty() {
+                    result.source = Some(ChangelogSource::UpstreamReleases);
+                    result.entries = entries;
+                    return Ok(result);
+                }
+            }
+            Err(e) => log::info!("No upstream releases for {}: {}", pkg, e),
+        }
+    }
+
+    let name = pkg.clone();
+    let pkgbase =
+        tokio::task::spawn_blocking(move || crate::alpm_read::get_arch_pkgbase_native(&name))
+            .await
+            .map_err(|e| format!("Task join error: {}", e))?;
+    if let Some(pkgbase) = pkgbase {
+        match scm.fetch_arch_commits(&pkgbase, &old_ver, &new_ver).await {
+            Ok(commits) if !commits.is_empty() => {
+                result.source = Some(ChangelogSource::PackagingCommits);
+                result.entries = commits.into_iter().map(commit_entry).collect();
+            }
+            Ok(_) => {}
+            Err(e) => log::info!("No packaging log for {}: {}", pkgbase, e),
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scm_api::{arch_gitlab_project, arch_version_tag};
+
+    fn release(tag: &str) -> UpstreamRelease {
+        UpstreamRelease {
+            tag: tag.to_string(),
+            version: crate::scm_api::normalize_tag(tag, "foo"),
+            name: None,
+            url: format!("https://github.com/foo/foo/releases/tag/{}", tag),
+            published_at: None,
+            body: Some(format!("notes for {}", tag)),
+            prerelease: false,
+        }
+    }
+
+    #[test]
+    fn test_changelog_sources() {
+        let releases = vec![
+            release("v1.0.0"),
+            release("v1.2.0"),
+            release("v1.1.0"),
+            release("v1.3.0"),
+            release("latest"),
+        ];
+        let titles: Vec<String> = releases_between(&releases, "1:1.0.0-2", "1:1.2.0-1")
+            .into_iter()
+            .map(|e| e.title)
+            .collect();
+        assert_eq!(titles, vec!["v1.2.0", "v1.1.0"]);
+        assert!(releases_between(&releases, "1.3.0-1", "1.3.0-2").is_empty());
+
+        let entry = commit_entry(PackagingCommit {
+            short_id: "abc123".into(),
+            title: "upgpkg: 1.2.0-1".into(),
+            message: Some("upgpkg: 1.2.0-1\n\nFix build with gcc 15\n".into()),
+            author_name: None,
+            created_at: None,
+            web_url: None,
+        });
+        assert_eq!(entry.body.as_deref(), Some("Fix build with gcc 15"));
+
+        assert_eq!(arch_gitlab_project("gtk2+extra"), "gtk2-extra");
+        assert_eq!(arch_gitlab_project("libc++"), "libcplusplus");
+        assert_eq!(arch_gitlab_project("tree"), "unix-tree");
+        assert_eq!(arch_version_tag("1:2.3-1"), "1-2.3-1");
+    }
+}
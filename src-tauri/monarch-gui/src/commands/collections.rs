@@ -0,0 +1,158 @@
+//! Home page data in one call: curated collections (data/collections.json) trimmed to what
+//! the enabled sources carry (repos, Chaotic-AUR, the AUR), plus how many apps each AppStream
+//! category holds. Members left out are listed per collection instead of dropped silently.
+
+use crate::chaotic_api::ChaoticApiClient;
+use crate::metadata::MetadataState;
+use crate::repo_manager::RepoManager;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use tauri::State;
+
+const COLLECTIONS_JSON: &str = include_str!("../../data/collections.json");
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Collection {
+    pub id: String,
+    pub title: String,
+    pub description: String,
+    /// Icon hint for the frontend ("gamepad", "terminal", ...).
+    pub icon: String,
+    pub packages: Vec<String>,
+    /// Curated members no enabled source carries (AUR packages with the AUR off, ...).
+    #[serde(default)]
+    pub skipped: Vec<String>,
+}
+
+/// Typed response for get_collections
+#[derive(Debug, Serialize, Clone)]
+pub struct CollectionsResponse {
+    /// Only collections with at least `MIN_AVAILABLE` installable packages, in file order.
+    pub collections: Vec<Collection>,
+    /// Lowercase AppStream category ("game", "audiovideo", "utility", ...) -> distinct apps.
+    pub category_counts: HashMap<String, usize>,
+}
+
+/// A collection showing one or two cards looks broken; hide it instead.
+const MIN_AVAILABLE: usize = 3;
+
+pub fn curated_collections() -> Vec<Collection> {
+    serde_json::from_str(COLLECTIONS_JSON).unwrap_or_else(|e| {
+        log::error!("Invalid collections.json: {}", e);
+        Vec::new()
+    })
+}
+
+/// Keep the packages in `available`, preserving the curated order; the rest go to `skipped`.
+pub fn trim_to_available(
+    collections: Vec<Collection>,
+    available: &HashSet<String>,
+) -> Vec<Collection> {
+    collections
+        .into_iter()
+        .map(|mut c| {
+            let (kept, skipped): (Vec<String>, Vec<String>) =
+                c.packages.into_iter().partition(|p| available.contains(p));
+            c.packages = kept;
+            c.skipped = skipped;
+            c
+        })
+        .filter(|c| c.packages.len() >= MIN_AVAILABLE)
+        .collect()
+}
+
+#[tauri::command]
+pub async fn get_collections(
+    state_repo: State<'_, RepoManager>,
+    state_meta: State<'_, MetadataState>,
+    state_chaotic: State<'_, ChaoticApiClient>,
+) -> Result<CollectionsResponse, String> {
+    let collections = curated_collections();
+    let mut names: Vec<String> = collections
+        .iter()
+        .flat_map(|c| c.packages.iter().cloned())
+        .collect();
+    names.sort();
+    names.dedup();
+    let mut available: HashSet<String> = state_repo
+        .inner()
+        .get_packages_batch(&names)
+        .await
+        .into_iter()
+        .map(|p| p.name)
+        .collect();
+
+    // The cards come from get_packages_by_names, which also finds Chaotic-AUR and AUR builds
+    let missing = |available: &HashSet<String>| -> Vec<String> {
+        names
+            .iter()
+            .filter(|n| !available.contains(*n))
+            .cloned()
+            .collect()
+    };
+    if state_repo.inner().is_repo_enabled("chaotic-aur").await {
+        let chaotic = state_chaotic
+            .inner()
+            .get_packages_batch(missing(&available))
+            .await;
+        available.extend(chaotic.into_keys());
+    }
+    let aur_names = missing(&available);
+    if !aur_names.is_empty() && state_repo.inner().is_aur_enabled().await {
+        let refs: Vec<&str> = aur_names.iter().map(String::as_str).collect();
+        match crate::aur_api::get_multi_info(&refs).await {
+            Ok(found) => available.extend(found.into_iter().map(|p| p.name)),
+            Err(e) => log::warn!("AUR lookup for collections failed: {}", e),
+        }
+    }
+
+    let category_counts = state_meta
+        .inner()
+        .0
+        .lock()
+        .map(|loader| loader.category_counts())
+        .unwrap_or_default();
+
+    Ok(CollectionsResponse {
+        collections: trim_to_available(collections, &available),
+        category_counts,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_curated_collections_parse_and_trim() {
+        let collections = curated_collections();
+        assert!(!collections.is_empty());
+        let ids: HashSet<&str> = collections.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(
+            ids.len(),
+            collections.len(),
+            "collection ids must be unique"
+        );
+
+        let gaming = collections
+            .iter()
+            .find(|c| c.id == "gaming-essentials")
+            .unwrap()
+            .clone();
+        let available: HashSet<String> = ["discord", "steam", "lutris", "mangohud"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let trimmed = trim_to_available(vec![gaming.clone()], &available);
+        assert_eq!(
+            trimmed[0].packages,
+            vec!["steam", "lutris", "mangohud", "discord"]
+        );
+        assert!(trimmed[0]
+            .skipped
+            .contains(&"heroic-games-launcher-bin".to_string()));
+
+        let too_few: HashSet<String> = ["steam".to_string()].into_iter().collect();
+        assert!(trim_to_available(vec![gaming], &too_few).is_empty());
+    }
+}
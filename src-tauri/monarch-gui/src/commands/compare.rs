@@ -0,0 +1,112 @@
+use crate::{chaotic_api, metadata, models, odrs_api, repo_manager::RepoManager};
+use serde::Serialize;
+use tauri::State;
+
+/// The compare view shows at most this many columns side by side.
+const MAX_COMPARE: usize = 4;
+
+/// One column of the compare view.
+#[derive(Debug, Serialize, Clone)]
+pub struct ComparedPackage {
+    pub name: String,
+    pub display_name: Option<String>,
+    pub version: String,
+    pub source: models::PackageSource,
+    pub available_sources: Vec<String>, // Labels of every source offering it
+    pub download_size: Option<u64>,
+    pub installed_size: Option<u64>,
+    pub rating: Option<f64>, // Average stars (1-5) from ODRS
+    pub rating_count: u32,
+    pub license: Option<Vec<String>>,
+    pub last_modified: Option<i64>,
+    pub optimized_available: bool, // CPU-optimized build (e.g. CachyOS v3) available
+    pub sandboxed_available: bool, // Flatpak build available
+    pub installed: bool,
+}
+
+/// Typed response for compare_packages
+#[derive(Debug, Serialize, Clone)]
+pub struct PackageComparison {
+    pub packages: Vec<ComparedPackage>,
+    pub not_found: Vec<String>,
+}
+
+fn to_compared(pkg: models::Package, rating: Option<&odrs_api::OdrsRating>) -> ComparedPackage {
+    let alternatives = pkg.alternatives.clone().unwrap_or_default();
+    let all_sources: Vec<&models::PackageSource> = std::iter::once(&pkg.source)
+        .chain(alternatives.iter().map(|a| &a.source))
+        .collect();
+
+    let optimized_available = pkg.is_optimized == Some(true)
+        || alternatives.iter().any(|a| a.is_optimized == Some(true))
+        || all_sources.iter().any(|s| s.id.starts_with("cachyos"));
+    let sandboxed_available = all_sources.iter().any(|s| s.source_type == "flatpak")
+        || crate::flathub_api::get_flathub_app_id(&pkg.name).is_some();
+
+    let mut available_sources: Vec<String> = all_sources.iter().map(|s| s.label.clone()).collect();
+    available_sources.dedup();
+
+    ComparedPackage {
+        installed: pkg.installed || alternatives.iter().any(|a| a.installed),
+        rating: rating.and_then(|r| r.average()),
+        rating_count: rating.map(|r| r.total).unwrap_or(0),
+        name: pkg.name,
+        display_name: pkg.display_name,
+        version: pkg.version,
+        source: pkg.source,
+        available_sources,
+        download_size: pkg.download_size,
+        installed_size: pkg.installed_size,
+        license: pkg.license,
+        last_modified: pkg.last_modified,
+        optimized_available,
+        sandboxed_available,
+    }
+}
+
+/// Side-by-side comparison of up to 4 packages from the unified catalog
+/// (repos, Chaotic-AUR, AUR) with ODRS ratings.
+#[tauri::command]
+pub async fn compare_packages(
+    state_meta: State<'_, metadata::MetadataState>,
+    state_chaotic: State<'_, chaotic_api::ChaoticApiClient>,
+    state_repo: State<'_, RepoManager>,
+    names: Vec<String>,
+) -> Result<PackageComparison, String> {
+    if names.is_empty() || names.len() > MAX_COMPARE {
+        return Err(format!(
+            "Select between 1 and {} packages to compare",
+            MAX_COMPARE
+        ));
+    }
+    for name in &names {
+        crate::utils::validate_package_name(name)?;
+    }
+
+    let found =
+        super::search::get_packages_by_names(state_meta, state_chaotic, state_repo, names.clone())
+            .await?;
+
+    let app_ids: Vec<String> = found.iter().filter_map(|p| p.app_id.clone()).collect();
+    let ratings = odrs_api::get_app_ratings_batch(app_ids)
+        .await
+        .unwrap_or_default();
+
+    // Keep the caller's column order
+    let mut packages = Vec::new();
+    let mut not_found = Vec::new();
+    for name in &names {
+        match found.iter().find(|p| &p.name == name) {
+            Some(pkg) => {
+                let rating = pkg.app_id.as_ref().and_then(|id| ratings.get(id));
+                packages.push(to_compared(pkg.clone(), rating));
+            }
+            None => not_found.push(name.clone()),
+        }
+    }
+
+    Ok(PackageComparison {
+        packages,
+        not_found,
+    })
+}
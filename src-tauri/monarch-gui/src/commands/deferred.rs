@@ -0,0 +1,347 @@
+//! Download now, install later. The helper runs the transaction in two phases: a download-only
+//! commit that fills the pacman cache, and a later commit that re-resolves the same targets and
+//! refuses to go on if the repos moved on in between. Between the two the GUI keeps a resume
+//! token in deferred_installs.json so the choice survives a restart.
+
+use crate::helper_client::{self, HelperCommand};
+use crate::repo_manager::RepoManager;
+use monarch_types::{DryRunAction, DryRunChange, ResolvedPackage, TransactionPhase};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DeferredTarget {
+    /// Full system upgrade.
+    Upgrade,
+    /// Repo packages, optionally from one repo (as install_package sends them).
+    Install {
+        packages: Vec<String>,
+        target_repo: Option<String>,
+    },
+}
+
+/// Typed response for download_for_later / list_deferred_installs: one resume token.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DeferredInstall {
+    pub token: String,
+    pub created_at: i64,
+    pub target: DeferredTarget,
+    /// What the download resolved to; the commit must resolve to the same versions.
+    pub changes: Vec<DryRunChange>,
+    pub download_bytes: u64,
+}
+
+impl DeferredInstall {
+    fn expected(&self) -> Vec<ResolvedPackage> {
+        self.changes
+            .iter()
+            .filter(|c| c.action != DryRunAction::Remove)
+            .filter_map(|c| {
+                c.new_version.clone().map(|version| ResolvedPackage {
+                    name: c.name.clone(),
+                    version,
+                })
+            })
+            .collect()
+    }
+}
+
+fn deferred_path(app: &AppHandle) -> PathBuf {
+    let path = app
+        .path()
+        .app_data_dir()
+        .unwrap_or_else(|_| PathBuf::from("."));
+    let _ = std::fs::create_dir_all(&path);
+    path.join("deferred_installs.json")
+}
+
+fn load_deferred(app: &AppHandle) -> Vec<DeferredInstall> {
+    std::fs::read_to_string(deferred_path(app))
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+fn save_deferred(app: &AppHandle, deferred: &[DeferredInstall]) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(deferred).map_err(|e| e.to_string())?;
+    std::fs::write(deferred_path(app), json)
+        .map_err(|e| format!("Failed to save deferred installs: {}", e))
+}
+
+/// Add `new`, dropping an older token for the same target: its downloads are superseded.
+fn upsert(deferred: &mut Vec<DeferredInstall>, new: DeferredInstall) {
+    deferred.retain(|d| d.target != new.target);
+    deferred.push(new);
+}
+
+/// Expected packages that are not installed at the expected version after the commit.
+fn not_applied(expected: &[ResolvedPackage], installed: &HashMap<String, String>) -> Vec<String> {
+    expected
+        .iter()
+        .filter(|p| installed.get(&p.name) != Some(&p.version))
+        .map(|p| format!("{} {}", p.name, p.version))
+        .collect()
+}
+
+async fn helper_command(
+    state_repo: &RepoManager,
+    target: &DeferredTarget,
+    phase: TransactionPhase,
+) -> HelperCommand {
+    match target {
+        DeferredTarget::Upgrade => HelperCommand::AlpmUpgrade {
+            packages: None,
+            enabled_repos: state_repo.transaction_repos().await,
+            phase,
+            dry_run: false,
+        },
+        DeferredTarget::Install {
+            packages,
+            target_repo,
+        } => HelperCommand::AlpmInstall {
+            packages: packages.clone(),
+            sync_first: false,
+            enabled_repos: state_repo.transaction_repos().await,
+            cpu_optimization: None,
+            target_repo: target_repo.clone(),
+            phase,
+            dry_run: false,
+        },
+    }
+}
+
+/// Download a system upgrade (`packages` None) or repo packages into the pacman cache without
+/// installing them. Returns the resume token for resume_deferred_install.
+#[tauri::command]
+pub async fn download_for_later(
+    app: AppHandle,
+    state_repo: State<'_, RepoManager>,
+    packages: Option<Vec<String>>,
+    target_repo: Option<String>,
+    password: Option<String>,
+) -> Result<DeferredInstall, String> {
+    let target = match packages {
+        None => DeferredTarget::Upgrade,
+        Some(packages) => {
+            if packages.is_empty() {
+                return Err("No packages to download".to_string());
+            }
+            for name in &packages {
+                crate::utils::validate_package_name(name)?;
+            }
+            if let Some(repo) = &target_repo {
+                crate::utils::validate_package_name(repo)?;
+            }
+            DeferredTarget::Install {
+                packages,
+                target_repo,
+            }
+        }
+    };
+    let command = helper_command(&state_repo, &target, TransactionPhase::DownloadOnly).await;
+
+    let _guard = crate::utils::PRIVILEGED_LOCK.lock().await;
+    let _ = app.emit("install-output", "--- Downloading for later install ---");
+    let mut rx = helper_client::invoke_helper(&app, command, password)
+        .await
+        .map_err(|e| format!("Failed to invoke helper: {}", e))?;
+    let mut changes = None;
+    let mut last_error = None;
+    while let Some(msg) = rx.recv().await {
+        if msg.event_type.as_deref() == Some("downloads_ready") {
+            changes = serde_json::from_str::<Vec<DryRunChange>>(&msg.message).ok();
+            continue;
+        }
+        if msg.message.starts_with("Error") {
+            last_error = Some(msg.message.clone());
+        }
+        let _ = app.emit("install-output", &msg.message);
+    }
+    let changes = changes.ok_or_else(|| {
+        last_error.unwrap_or_else(|| "The helper did not report a completed download".to_string())
+    })?;
+    if changes.is_empty() {
+        return Err("Nothing to download: already up to date".to_string());
+    }
+
+    let deferred = DeferredInstall {
+        token: format!(
+            "{:x}",
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0)
+        ),
+        created_at: chrono::Utc::now().timestamp(),
+        target,
+        download_bytes: changes.iter().map(|c| c.download_size).sum(),
+        changes,
+    };
+    let mut all = load_deferred(&app);
+    upsert(&mut all, deferred.clone());
+    save_deferred(&app, &all)?;
+    Ok(deferred)
+}
+
+#[tauri::command]
+pub async fn list_deferred_installs(app: AppHandle) -> Result<Vec<DeferredInstall>, String> {
+    Ok(load_deferred(&app))
+}
+
+/// Install a download made earlier. The helper re-resolves the transaction and aborts without
+/// changes if any version differs from the download; such a token is dropped, since only a new
+/// download can fix it. Other failures (auth, lock) keep the token for another try.
+#[tauri::command]
+pub async fn resume_deferred_install(
+    app: AppHandle,
+    state_repo: State<'_, RepoManager>,
+    token: String,
+    password: Option<String>,
+) -> Result<Vec<String>, String> {
+    let mut all = load_deferred(&app);
+    let deferred = all
+        .iter()
+        .find(|d| d.token == token)
+        .cloned()
+        .ok_or("Deferred install not found; download again")?;
+    let expected = deferred.expected();
+    let command = helper_command(
+        &state_repo,
+        &deferred.target,
+        TransactionPhase::Commit {
+            expected: expected.clone(),
+        },
+    )
+    .await;
+
+    let _guard = crate::utils::PRIVILEGED_LOCK.lock().await;
+    let _ = app.emit(
+        "install-output",
+        format!(
+            "--- Installing {} downloaded package(s) ---",
+            expected.len()
+        ),
+    );
+    let mut rx = helper_client::invoke_helper(&app, command, password)
+        .await
+        .map_err(|e| format!("Failed to invoke helper: {}", e))?;
+    let mut stale = None;
+    while let Some(msg) = rx.recv().await {
+        if msg.event_type.as_deref() == Some("stale_download") {
+            stale = Some(format!(
+                "Packages changed since download; download again. {}",
+                msg.message
+            ));
+            continue;
+        }
+        let _ = app.emit("install-output", &msg.message);
+    }
+
+    if let Some(msg) = stale {
+        all.retain(|d| d.token != token);
+        save_deferred(&app, &all)?;
+        let _ = app.emit("install-complete", "failed");
+        return Err(msg);
+    }
+
+    let installed: HashMap<String, String> = tokio::task::spawn_blocking(|| {
+        crate::alpm_read::get_installed_packages_native()
+            .into_iter()
+            .map(|p| (p.name, p.version))
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?;
+    let missing = not_applied(&expected, &installed);
+    if !missing.is_empty() {
+        let _ = app.emit("install-complete", "failed");
+        return Err(format!("Install did not complete: {}", missing.join(", ")));
+    }
+    all.retain(|d| d.token != token);
+    save_deferred(&app, &all)?;
+    let _ = app.emit("install-complete", "success");
+    Ok(expected.into_iter().map(|p| p.name).collect())
+}
+
+/// Forget a deferred install. The downloaded files stay in the pacman cache until it is
+/// cleaned.
+#[tauri::command]
+pub async fn discard_deferred_install(app: AppHandle, token: String) -> Result<(), String> {
+    let mut all = load_deferred(&app);
+    all.retain(|d| d.token != token);
+    save_deferred(&app, &all)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(name: &str, action: DryRunAction, new_version: Option<&str>) -> DryRunChange {
+        DryRunChange {
+            action,
+            name: name.to_string(),
+            old_version: None,
+            new_version: new_version.map(str::to_string),
+            download_size: 1000,
+            installed_size: 4000,
+        }
+    }
+
+    #[test]
+    fn test_resume_token_expectations() {
+        let deferred = DeferredInstall {
+            token: "a1".to_string(),
+            created_at: 0,
+            target: DeferredTarget::Upgrade,
+            changes: vec![
+                change("mesa", DryRunAction::Upgrade, Some("1:24.2.1-1")),
+                change("libva-mesa-driver", DryRunAction::Remove, None),
+                change(
+                    "vulkan-mesa-layers",
+                    DryRunAction::Install,
+                    Some("1:24.2.1-1"),
+                ),
+            ],
+            download_bytes: 3000,
+        };
+        let expected = deferred.expected();
+        assert_eq!(
+            expected.iter().map(|p| p.name.as_str()).collect::<Vec<_>>(),
+            vec!["mesa", "vulkan-mesa-layers"]
+        );
+
+        let installed: HashMap<String, String> = [
+            ("mesa".to_string(), "1:24.2.1-1".to_string()),
+            ("vulkan-mesa-layers".to_string(), "1:24.2.0-1".to_string()),
+        ]
+        .into();
+        assert_eq!(
+            not_applied(&expected, &installed),
+            vec!["vulkan-mesa-layers 1:24.2.1-1"]
+        );
+
+        // A new upgrade download replaces the previous one; installs of other targets stay
+        let mut all = vec![
+            deferred.clone(),
+            DeferredInstall {
+                token: "b2".to_string(),
+                target: DeferredTarget::Install {
+                    packages: vec!["vlc".to_string()],
+                    target_repo: None,
+                },
+                ..deferred.clone()
+            },
+        ];
+        upsert(
+            &mut all,
+            DeferredInstall {
+                token: "c3".to_string(),
+                ..deferred
+            },
+        );
+        assert_eq!(
+            all.iter().map(|d| d.token.as_str()).collect::<Vec<_>>(),
+            vec!["b2", "c3"]
+        );
+    }
+}
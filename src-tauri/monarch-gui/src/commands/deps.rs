@@ -0,0 +1,367 @@
+use crate::alpm_read::LocalPackageDeps;
+use crate::commands::package::PROTECTED_PACKAGES;
+use crate::models::DependencyNode;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// Default and maximum tree depth. Deeper trees get huge (base pulls in most of the system).
+const DEFAULT_DEPTH: u32 = 3;
+const MAX_DEPTH: u32 = 8;
+
+fn clamp_depth(depth: Option<u32>) -> u32 {
+    depth.unwrap_or(DEFAULT_DEPTH).clamp(1, MAX_DEPTH)
+}
+
+/// Forward dependency tree (depends recursively, optdepends/makedepends as leaves)
+/// with installed status per node. Reads local + sync DBs; no privileges needed.
+#[tauri::command]
+pub async fn get_dependency_tree(
+    pkg_name: String,
+    depth: Option<u32>,
+) -> Result<DependencyNode, String> {
+    crate::utils::validate_package_name(&pkg_name)?;
+    let depth = clamp_depth(depth);
+    tokio::task::spawn_blocking(move || {
+        crate::alpm_read::get_dependency_tree_native(&pkg_name, depth)
+            .ok_or_else(|| format!("Package '{}' not found in any database", pkg_name))
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Reverse dependency tree: installed packages that require (or optionally use) `pkg_name`.
+/// Powers the "what will break if I remove this" view.
+#[tauri::command]
+pub async fn get_reverse_dependencies(
+    pkg_name: String,
+    depth: Option<u32>,
+) -> Result<DependencyNode, String> {
+    crate::utils::validate_package_name(&pkg_name)?;
+    let depth = clamp_depth(depth);
+    tokio::task::spawn_blocking(move || {
+        crate::alpm_read::get_reverse_dependencies_native(&pkg_name, depth)
+            .ok_or_else(|| format!("Package '{}' is not installed", pkg_name))
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Meta packages that pull in a whole desktop environment. Whatever they depend on is part of
+/// the desktop, so removing it takes the desktop apart.
+const DESKTOP_META_PACKAGES: &[&str] = &[
+    "plasma-meta",
+    "plasma-desktop",
+    "gnome",
+    "gnome-shell",
+    "xfce4-session",
+    "cinnamon",
+    "mate-desktop",
+    "budgie-desktop",
+    "lxqt-session",
+    "cosmic-session",
+];
+
+/// Package groups that make up a desktop environment.
+const DESKTOP_GROUPS: &[&str] = &["plasma", "gnome", "xfce4", "mate", "lxqt", "cosmic"];
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct RemovalEntry {
+    pub name: String,
+    pub version: String,
+    pub installed_size: u64,
+    /// "target" for the requested packages, "dependent" for ones the cascade takes with them.
+    pub reason: String,
+    /// Removed packages it needs (why the cascade takes it).
+    pub requires: Vec<String>,
+    /// On the list uninstall_package refuses to remove.
+    pub protected: bool,
+    /// Desktop meta-packages or groups it belongs to.
+    pub desktop: Vec<String>,
+}
+
+/// Typed response for preview_uninstall
+#[derive(Debug, Serialize, Clone, Default, PartialEq)]
+pub struct UninstallPreview {
+    /// Everything the removal takes out, targets first.
+    pub packages: Vec<RemovalEntry>,
+    pub reclaimed_bytes: u64,
+    /// Installed packages that still need a removed one. Without `remove_deps` the removal
+    /// fails on them.
+    pub blocked_by: Vec<String>,
+    /// Dependencies nothing needs afterwards; they stay installed as orphans.
+    pub orphaned: Vec<String>,
+    /// One line per protected or desktop-critical package in `packages`.
+    pub warnings: Vec<String>,
+}
+
+/// What AlpmUninstall would remove for `targets`. With `cascade` (its `remove_deps`) every
+/// installed package that depends on a removed one goes too, repeated until nothing else
+/// breaks; without it those packages are reported in `blocked_by`.
+pub(crate) fn plan_removal(
+    installed: &[LocalPackageDeps],
+    targets: &[String],
+    cascade: bool,
+) -> Result<UninstallPreview, String> {
+    let by_name: HashMap<&str, &LocalPackageDeps> =
+        installed.iter().map(|p| (p.name.as_str(), p)).collect();
+    // Dependency name → installed packages satisfying it
+    let mut providers: HashMap<&str, Vec<&str>> = HashMap::new();
+    for p in installed {
+        providers
+            .entry(p.name.as_str())
+            .or_default()
+            .push(p.name.as_str());
+        for prov in &p.provides {
+            providers
+                .entry(prov.as_str())
+                .or_default()
+                .push(p.name.as_str());
+        }
+    }
+
+    let mut entries: Vec<RemovalEntry> = Vec::new();
+    let mut removed: HashSet<&str> = HashSet::new();
+    for t in targets {
+        let pkg = by_name
+            .get(t.as_str())
+            .ok_or_else(|| format!("Package '{}' is not installed", t))?;
+        if removed.insert(pkg.name.as_str()) {
+            entries.push(entry(pkg, "target", Vec::new()));
+        }
+    }
+
+    // Removed packages that `dep` needed, if no remaining package satisfies it any more
+    let broken_by = |dep: &str, removed: &HashSet<&str>| -> Vec<String> {
+        let Some(sats) = providers.get(dep) else {
+            return Vec::new();
+        };
+        if sats.iter().any(|s| !removed.contains(s)) {
+            return Vec::new();
+        }
+        sats.iter().map(|s| s.to_string()).collect()
+    };
+
+    let mut blocked_by = Vec::new();
+    loop {
+        let mut added = Vec::new();
+        for p in installed
+            .iter()
+            .filter(|p| !removed.contains(p.name.as_str()))
+        {
+            let mut requires: Vec<String> = p
+                .depends
+                .iter()
+                .flat_map(|d| broken_by(d, &removed))
+                .collect();
+            if requires.is_empty() {
+                continue;
+            }
+            requires.sort();
+            requires.dedup();
+            if cascade {
+                added.push(entry(p, "dependent", requires));
+            } else {
+                blocked_by.push(p.name.clone());
+            }
+        }
+        if added.is_empty() {
+            break;
+        }
+        for e in added {
+            removed.insert(by_name[e.name.as_str()].name.as_str());
+            entries.push(e);
+        }
+    }
+
+    // Dependencies of removed packages that end up needed by nothing (what -Rns would also
+    // take). Follow chains: an orphan's own dependencies may become orphans in turn.
+    let mut gone = removed.clone();
+    let mut orphaned = Vec::new();
+    let mut candidates: Vec<&str> = installed
+        .iter()
+        .filter(|p| removed.contains(p.name.as_str()))
+        .flat_map(|p| p.depends.iter())
+        .flat_map(|d| providers.get(d.as_str()).cloned().unwrap_or_default())
+        .collect();
+    while let Some(name) = candidates.pop() {
+        let pkg = by_name[name];
+        if gone.contains(name) || pkg.explicit {
+            continue;
+        }
+        let needed = installed
+            .iter()
+            .filter(|p| !gone.contains(p.name.as_str()))
+            .any(|p| {
+                p.depends
+                    .iter()
+                    .any(|d| d == name || pkg.provides.contains(d))
+            });
+        if needed {
+            continue;
+        }
+        gone.insert(name);
+        orphaned.push(name.to_string());
+        for d in &pkg.depends {
+            candidates.extend(providers.get(d.as_str()).cloned().unwrap_or_default());
+        }
+    }
+    orphaned.sort();
+
+    // Desktop membership: its own meta/group status plus installed meta packages needing it
+    for e in &mut entries {
+        let pkg = by_name[e.name.as_str()];
+        if DESKTOP_META_PACKAGES.contains(&pkg.name.as_str()) {
+            e.desktop.push(pkg.name.clone());
+        }
+        for g in &pkg.groups {
+            if DESKTOP_GROUPS.contains(&g.as_str()) && !e.desktop.contains(g) {
+                e.desktop.push(g.clone());
+            }
+        }
+        for meta in DESKTOP_META_PACKAGES {
+            let Some(m) = by_name.get(meta) else {
+                continue;
+            };
+            if m.name != pkg.name
+                && m.depends
+                    .iter()
+                    .any(|d| d == &pkg.name || pkg.provides.contains(d))
+            {
+                e.desktop.push(m.name.clone());
+            }
+        }
+    }
+
+    let mut warnings = Vec::new();
+    for e in &entries {
+        if e.protected {
+            warnings.push(format!(
+                "'{}' is a protected system package; the removal will be refused.",
+                e.name
+            ));
+        }
+        if !e.desktop.is_empty() {
+            warnings.push(format!(
+                "'{}' is part of your desktop ({}).",
+                e.name,
+                e.desktop.join(", ")
+            ));
+        }
+    }
+
+    Ok(UninstallPreview {
+        reclaimed_bytes: entries.iter().map(|e| e.installed_size).sum(),
+        packages: entries,
+        blocked_by,
+        orphaned,
+        warnings,
+    })
+}
+
+fn entry(pkg: &LocalPackageDeps, reason: &str, requires: Vec<String>) -> RemovalEntry {
+    RemovalEntry {
+        name: pkg.name.clone(),
+        version: pkg.version.clone(),
+        installed_size: pkg.installed_size,
+        reason: reason.to_string(),
+        requires,
+        protected: PROTECTED_PACKAGES.contains(&pkg.name.as_str()),
+        desktop: Vec::new(),
+    }
+}
+
+/// Read-only dry run of uninstall_package: every package the removal would take (with
+/// `remove_deps`, as uninstall_package uses it, dependents cascade), protected and desktop
+/// packages among them, and the space freed. Needs no privileges.
+#[tauri::command]
+pub async fn preview_uninstall(
+    pkg: String,
+    remove_deps: Option<bool>,
+) -> Result<UninstallPreview, String> {
+    crate::utils::validate_package_name(&pkg)?;
+    tokio::task::spawn_blocking(move || {
+        plan_removal(
+            &crate::alpm_read::get_local_package_deps_native(),
+            &[pkg],
+            remove_deps.unwrap_or(true),
+        )
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pkg(name: &str, depends: &[&str], explicit: bool) -> LocalPackageDeps {
+        LocalPackageDeps {
+            name: name.to_string(),
+            version: "1.0-1".to_string(),
+            installed_size: 100,
+            explicit,
+            depends: depends.iter().map(|d| d.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn names(preview: &UninstallPreview) -> Vec<&str> {
+        preview.packages.iter().map(|p| p.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_plan_removal_cascade_and_orphans() {
+        let mut jre = pkg("jre-openjdk", &["java-environment-common"], false);
+        jre.provides = vec!["java-runtime".to_string()];
+        let installed = vec![
+            jre,
+            pkg("java-environment-common", &[], false),
+            pkg("jdownloader2", &["java-runtime"], true),
+            pkg("jd-plugin", &["jdownloader2"], true),
+            pkg("firefox", &[], true),
+        ];
+
+        let preview = plan_removal(&installed, &["jre-openjdk".to_string()], true).unwrap();
+        assert_eq!(
+            names(&preview),
+            vec!["jre-openjdk", "jdownloader2", "jd-plugin"]
+        );
+        assert_eq!(preview.packages[1].requires, vec!["jre-openjdk"]);
+        assert_eq!(preview.reclaimed_bytes, 300);
+        assert_eq!(preview.orphaned, vec!["java-environment-common"]);
+        assert!(preview.blocked_by.is_empty());
+
+        let preview = plan_removal(&installed, &["jre-openjdk".to_string()], false).unwrap();
+        assert_eq!(names(&preview), vec!["jre-openjdk"]);
+        assert_eq!(preview.blocked_by, vec!["jdownloader2"]);
+
+        assert!(plan_removal(&installed, &["vlc".to_string()], true).is_err());
+    }
+
+    #[test]
+    fn test_plan_removal_flags_protected_and_desktop() {
+        let mut dolphin = pkg("dolphin", &[], true);
+        dolphin.groups = vec!["kde-applications".to_string()];
+        let installed = vec![
+            pkg("plasma-meta", &["plasma-workspace"], true),
+            pkg("plasma-workspace", &["systemd"], false),
+            pkg("systemd", &[], false),
+            dolphin,
+        ];
+
+        let preview = plan_removal(&installed, &["plasma-workspace".to_string()], true).unwrap();
+        assert_eq!(preview.packages[0].desktop, vec!["plasma-meta"]);
+        assert_eq!(preview.packages[1].desktop, vec!["plasma-meta"]);
+        assert_eq!(preview.warnings.len(), 2);
+
+        let preview = plan_removal(&installed, &["systemd".to_string()], true).unwrap();
+        assert!(preview.packages[0].protected);
+        assert_eq!(
+            names(&preview),
+            vec!["systemd", "plasma-workspace", "plasma-meta"]
+        );
+
+        let preview = plan_removal(&installed, &["dolphin".to_string()], true).unwrap();
+        assert!(preview.warnings.is_empty());
+    }
+}
@@ -0,0 +1,180 @@
+//! AUR development packages (-git, -svn, ...). Their AUR version is whatever pkgver the
+//! PKGBUILD had when last pushed, so it rarely matches what a fresh build produced and they
+//! show up as updates on every check. With the exclusion on they are listed separately
+//! (get_devel_updates) and left out of routine AUR updates, unless the user opted a package
+//! back in.
+
+use crate::commands::package::PendingUpdate;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+const STORE_FILE: &str = "settings.json";
+const DEVEL_KEY: &str = "aur_devel_updates";
+/// VCS suffixes used by AUR packaging guidelines (same list yay treats as devel).
+const DEVEL_SUFFIXES: [&str; 6] = ["-git", "-svn", "-bzr", "-hg", "-cvs", "-darcs"];
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct DevelUpdateSettings {
+    /// Leave development packages out of routine AUR update checks.
+    pub exclude: bool,
+    /// Development packages that are still checked like any other package.
+    #[serde(default)]
+    pub always_check: Vec<String>,
+}
+
+impl DevelUpdateSettings {
+    fn excludes(&self, name: &str) -> bool {
+        self.exclude && is_devel_package(name) && !self.always_check.iter().any(|n| n == name)
+    }
+}
+
+/// Updates from the last check that were set aside as development packages.
+static DEVEL_UPDATES: once_cell::sync::Lazy<Mutex<Vec<PendingUpdate>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(Vec::new()));
+
+pub fn is_devel_package(name: &str) -> bool {
+    DEVEL_SUFFIXES.iter().any(|s| name.ends_with(s))
+}
+
+pub(crate) fn read_settings(app: &AppHandle) -> DevelUpdateSettings {
+    app.store(STORE_FILE)
+        .ok()
+        .and_then(|s| s.get(DEVEL_KEY))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+/// Split AUR `items` into (routine, devel) by the user's settings; `name_of` and `is_aur`
+/// adapt it to UpdateItem and PendingUpdate.
+pub(crate) fn split_devel<T>(
+    settings: &DevelUpdateSettings,
+    items: Vec<T>,
+    name_of: impl Fn(&T) -> &str,
+    is_aur: impl Fn(&T) -> bool,
+) -> (Vec<T>, Vec<T>) {
+    items
+        .into_iter()
+        .partition(|item| !(is_aur(item) && settings.excludes(name_of(item))))
+}
+
+pub(crate) fn set_devel_updates(updates: Vec<PendingUpdate>) {
+    if let Ok(mut d) = DEVEL_UPDATES.lock() {
+        *d = updates;
+    }
+}
+
+pub(crate) fn forget_devel_updates(names: &[String]) {
+    if let Ok(mut d) = DEVEL_UPDATES.lock() {
+        d.retain(|u| !names.contains(&u.name));
+    }
+}
+
+/// Drop excluded development packages from `updates`, remembering them for the updates view.
+pub(crate) fn partition_devel(app: &AppHandle, updates: Vec<PendingUpdate>) -> Vec<PendingUpdate> {
+    let (routine, devel) = split_devel(
+        &read_settings(app),
+        updates,
+        |u| u.name.as_str(),
+        |u| u.repo == "aur",
+    );
+    set_devel_updates(devel);
+    routine
+}
+
+#[tauri::command]
+pub async fn get_devel_update_settings(app: AppHandle) -> Result<DevelUpdateSettings, String> {
+    Ok(read_settings(&app))
+}
+
+#[tauri::command]
+pub async fn set_devel_update_settings(
+    app: AppHandle,
+    exclude: bool,
+    always_check: Vec<String>,
+) -> Result<DevelUpdateSettings, String> {
+    for name in &always_check {
+        crate::utils::validate_package_name(name)?;
+    }
+    let mut always_check = always_check;
+    always_check.sort();
+    always_check.dedup();
+    let settings = DevelUpdateSettings {
+        exclude,
+        always_check,
+    };
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    store.set(
+        DEVEL_KEY,
+        serde_json::to_value(&settings).map_err(|e| e.to_string())?,
+    );
+    store.save().map_err(|e| e.to_string())?;
+    Ok(settings)
+}
+
+/// Development package updates set aside by the last update check.
+#[tauri::command]
+pub async fn get_devel_updates() -> Result<Vec<PendingUpdate>, String> {
+    Ok(DEVEL_UPDATES.lock().map(|d| d.clone()).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn upd(name: &str, repo: &str) -> PendingUpdate {
+        PendingUpdate {
+            name: name.to_string(),
+            old_version: "r100.abc-1".to_string(),
+            new_version: "r90.def-1".to_string(),
+            repo: repo.to_string(),
+            downgrade: false,
+        }
+    }
+
+    #[test]
+    fn test_devel_split_respects_toggle_and_overrides() {
+        assert!(is_devel_package("neovim-git"));
+        assert!(is_devel_package("mpv-hg"));
+        assert!(!is_devel_package("git"));
+        assert!(!is_devel_package("legit-tool"));
+
+        let updates = || {
+            vec![
+                upd("neovim-git", "aur"),
+                upd("yay", "aur"),
+                upd("hyprland-git", "aur"),
+                upd("lib32-git", "official"),
+            ]
+        };
+        let split = |settings: &DevelUpdateSettings| {
+            let (routine, devel) = split_devel(
+                settings,
+                updates(),
+                |u| u.name.as_str(),
+                |u| u.repo == "aur",
+            );
+            (
+                routine.into_iter().map(|u| u.name).collect::<Vec<_>>(),
+                devel.into_iter().map(|u| u.name).collect::<Vec<_>>(),
+            )
+        };
+
+        let (routine, devel) = split(&DevelUpdateSettings::default());
+        assert_eq!(routine.len(), 4);
+        assert!(devel.is_empty());
+
+        let settings = DevelUpdateSettings {
+            exclude: true,
+            always_check: vec!["hyprland-git".to_string()],
+        };
+        let (routine, devel) = split(&settings);
+        assert_eq!(routine, vec!["yay", "hyprland-git", "lib32-git"]);
+        assert_eq!(devel, vec!["neovim-git"]);
+
+        // Written before always_check existed
+        let old: DevelUpdateSettings = serde_json::from_str(r#"{"exclude":true}"#).unwrap();
+        assert!(old.always_check.is_empty());
+    }
+}
@@ -0,0 +1,301 @@
+//! "Your distro" page: the welcome apps, settings managers and kernel managers each
+//! Arch-based distro ships for its users, with installed state and one-click install.
+//! Plain Arch (and anything DistroContext doesn't know) has no such tools.
+
+use crate::distro_context::{DistroContext, DistroId};
+use crate::helper_client;
+use crate::repo_manager::RepoManager;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, State};
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolCategory {
+    Welcome,
+    Settings,
+    Kernels,
+    Mirrors,
+    Gaming,
+}
+
+struct ToolSpec {
+    package: &'static str,
+    name: &'static str,
+    description: &'static str,
+    category: ToolCategory,
+}
+
+const GARUDA_TOOLS: &[ToolSpec] = &[
+    ToolSpec {
+        package: "garuda-welcome",
+        name: "Garuda Welcome",
+        description: "First steps, documentation and community links",
+        category: ToolCategory::Welcome,
+    },
+    ToolSpec {
+        package: "garuda-assistant",
+        name: "Garuda Assistant",
+        description: "System maintenance, services and Btrfs snapshots",
+        category: ToolCategory::Settings,
+    },
+    ToolSpec {
+        package: "garuda-settings-manager",
+        name: "Garuda Settings Manager",
+        description: "Kernels, drivers, languages and keyboard settings",
+        category: ToolCategory::Kernels,
+    },
+    ToolSpec {
+        package: "garuda-boot-options",
+        name: "Garuda Boot Options",
+        description: "GRUB timeout, kernel parameters and boot splash",
+        category: ToolCategory::Settings,
+    },
+    ToolSpec {
+        package: "garuda-gamer",
+        name: "Garuda Gamer",
+        description: "Launchers, runtimes and tools for gaming",
+        category: ToolCategory::Gaming,
+    },
+];
+
+const CACHYOS_TOOLS: &[ToolSpec] = &[
+    ToolSpec {
+        package: "cachyos-hello",
+        name: "CachyOS Hello",
+        description: "Welcome app with post-install tweaks and fixes",
+        category: ToolCategory::Welcome,
+    },
+    ToolSpec {
+        package: "cachyos-kernel-manager",
+        name: "CachyOS Kernel Manager",
+        description: "Install kernels and build custom ones with sched-ext",
+        category: ToolCategory::Kernels,
+    },
+    ToolSpec {
+        package: "cachyos-settings",
+        name: "CachyOS Settings",
+        description: "Tuned sysctl, udev and zram defaults",
+        category: ToolCategory::Settings,
+    },
+    ToolSpec {
+        package: "cachyos-rate-mirrors",
+        name: "CachyOS Rate Mirrors",
+        description: "Ranks the Arch and CachyOS mirrors by speed",
+        category: ToolCategory::Mirrors,
+    },
+    ToolSpec {
+        package: "cachyos-gaming-meta",
+        name: "CachyOS Gaming",
+        description: "Steam, Wine and Proton dependencies in one package",
+        category: ToolCategory::Gaming,
+    },
+];
+
+const ENDEAVOUROS_TOOLS: &[ToolSpec] = &[
+    ToolSpec {
+        package: "welcome",
+        name: "EndeavourOS Welcome",
+        description: "eos-welcome: first steps, tips and common tasks",
+        category: ToolCategory::Welcome,
+    },
+    ToolSpec {
+        package: "akm",
+        name: "Kernel Manager",
+        description: "Install and remove kernels (akm)",
+        category: ToolCategory::Kernels,
+    },
+    ToolSpec {
+        package: "eos-update-notifier",
+        name: "Update Notifier",
+        description: "Notifies about pending updates",
+        category: ToolCategory::Settings,
+    },
+    ToolSpec {
+        package: "eos-rankmirrors",
+        name: "EndeavourOS Rank Mirrors",
+        description: "Ranks the EndeavourOS mirrors",
+        category: ToolCategory::Mirrors,
+    },
+    ToolSpec {
+        package: "eos-log-tool",
+        name: "Log Tool",
+        description: "Collects system logs to share when asking for help",
+        category: ToolCategory::Settings,
+    },
+];
+
+const MANJARO_TOOLS: &[ToolSpec] = &[
+    ToolSpec {
+        package: "manjaro-hello",
+        name: "Manjaro Hello",
+        description: "Welcome app with documentation and links",
+        category: ToolCategory::Welcome,
+    },
+    ToolSpec {
+        package: "manjaro-settings-manager",
+        name: "Manjaro Settings Manager",
+        description: "Kernels, hardware drivers, languages and users",
+        category: ToolCategory::Kernels,
+    },
+];
+
+fn tools_for(distro: &DistroId) -> &'static [ToolSpec] {
+    match distro {
+        DistroId::Garuda => GARUDA_TOOLS,
+        DistroId::CachyOS => CACHYOS_TOOLS,
+        DistroId::EndeavourOS => ENDEAVOUROS_TOOLS,
+        DistroId::Manjaro => MANJARO_TOOLS,
+        DistroId::Arch | DistroId::Unknown(_) => &[],
+    }
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct DistroTool {
+    pub package: String,
+    pub name: String,
+    pub description: String,
+    pub category: ToolCategory,
+    pub installed: bool,
+    /// In an enabled sync DB, so `install_distro_tools` can install it.
+    pub available: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DistroToolsPage {
+    /// DistroContext id ("garuda", "cachyos", ...).
+    pub distro: String,
+    pub pretty_name: String,
+    /// Empty when the distro has no tools of its own.
+    pub tools: Vec<DistroTool>,
+}
+
+/// The running distro's own tools. The frontend falls back to generic content when
+/// `tools` is empty.
+#[tauri::command]
+pub async fn get_distro_tools() -> Result<DistroToolsPage, String> {
+    tokio::task::spawn_blocking(|| {
+        let distro = DistroContext::new();
+        let tools = tools_for(&distro.id)
+            .iter()
+            .map(|spec| DistroTool {
+                package: spec.package.to_string(),
+                name: spec.name.to_string(),
+                description: spec.description.to_string(),
+                category: spec.category,
+                installed: crate::alpm_read::is_package_installed(spec.package),
+                available: crate::alpm_read::is_package_in_syncdb(spec.package),
+            })
+            .collect();
+        DistroToolsPage {
+            distro: distro.id_str().to_string(),
+            pretty_name: distro.pretty_name,
+            tools,
+        }
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))
+}
+
+/// Install the given distro tools in one transaction. Only tools listed for this distro
+/// that are available and not installed yet are accepted.
+#[tauri::command]
+pub async fn install_distro_tools(
+    app: AppHandle,
+    state_repo: State<'_, RepoManager>,
+    packages: Vec<String>,
+    password: Option<String>,
+) -> Result<Vec<String>, String> {
+    for p in &packages {
+        crate::utils::validate_package_name(p)?;
+    }
+    let page = get_distro_tools().await?;
+    let targets: Vec<String> = page
+        .tools
+        .into_iter()
+        .filter(|t| packages.contains(&t.package) && t.available && !t.installed)
+        .map(|t| t.package)
+        .collect();
+    if targets.is_empty() {
+        return Ok(targets);
+    }
+
+    let enabled_repos = state_repo.transaction_repos().await;
+
+    let _guard = crate::utils::PRIVILEGED_LOCK.lock().await;
+    let _ = app.emit(
+        "install-output",
+        format!(
+            "--- Installing {} tools: {} ---",
+            page.pretty_name,
+            targets.join(", ")
+        ),
+    );
+    let result = helper_client::run_helper(
+        &app,
+        helper_client::HelperCommand::AlpmInstall {
+            packages: targets.clone(),
+            sync_first: false,
+            enabled_repos,
+            cpu_optimization: None,
+            target_repo: None,
+            phase: Default::default(),
+            dry_run: false,
+        },
+        password,
+        |msg| {
+            let _ = app.emit("install-output", &msg.message);
+        },
+    )
+    .await
+    .map_err(|e| format!("Failed to invoke helper: {}", e))?;
+
+    if !result.success {
+        let _ = app.emit("install-complete", "failed");
+        return Err(format!(
+            "Tool install failed: {}",
+            result
+                .error
+                .unwrap_or_else(|| "see the log above".to_string())
+        ));
+    }
+    let _ = app.emit("install-complete", "success");
+    Ok(targets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tools_per_distro() {
+        let packages = |distro: DistroId| {
+            tools_for(&distro)
+                .iter()
+                .map(|t| t.package)
+                .collect::<Vec<_>>()
+        };
+        assert!(packages(DistroId::Garuda).contains(&"garuda-assistant"));
+        assert!(packages(DistroId::CachyOS).contains(&"cachyos-hello"));
+        assert!(packages(DistroId::EndeavourOS).contains(&"welcome"));
+        assert!(packages(DistroId::Arch).is_empty());
+        assert!(packages(DistroId::Unknown("artix".to_string())).is_empty());
+
+        // Every distro with tools offers a kernel manager, and no package is listed twice
+        for distro in [
+            DistroId::Garuda,
+            DistroId::CachyOS,
+            DistroId::EndeavourOS,
+            DistroId::Manjaro,
+        ] {
+            let tools = tools_for(&distro);
+            assert!(tools.iter().any(|t| t.category == ToolCategory::Kernels));
+            for tool in tools {
+                assert!(crate::utils::validate_package_name(tool.package).is_ok());
+                assert_eq!(
+                    tools.iter().filter(|t| t.package == tool.package).count(),
+                    1
+                );
+            }
+        }
+    }
+}
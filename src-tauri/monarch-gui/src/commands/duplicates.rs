@@ -0,0 +1,247 @@
+use crate::flathub_api::{self, InstalledFlatpak};
+use crate::helper_client;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter};
+
+/// A profile/data directory for one copy of a duplicated app.
+/// The frontend shows these so users know what to move before removing a copy.
+#[derive(Debug, Serialize, Clone)]
+pub struct ProfileHint {
+    pub source: String, // "native" | "flatpak"
+    pub path: String,
+    pub exists: bool,
+}
+
+/// The same app installed both natively (pacman/AUR) and as a Flatpak.
+#[derive(Debug, Serialize, Clone)]
+pub struct DuplicateInstallation {
+    pub display_name: String,
+    pub native_name: String,
+    pub native_version: String,
+    pub flatpak_id: String,
+    pub flatpak_version: String,
+    pub profile_hints: Vec<ProfileHint>,
+}
+
+/// Which copy to keep for one duplicate; the other copy is removed.
+#[derive(Debug, Deserialize, Clone)]
+pub struct ConsolidationChoice {
+    pub native_name: String,
+    pub flatpak_id: String,
+    pub keep: String, // "native" | "flatpak"
+}
+
+/// Typed response for consolidate_duplicates
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct ConsolidationResult {
+    pub removed: Vec<String>,
+    pub failed: Vec<String>,
+}
+
+/// Match installed native packages against installed Flatpaks.
+/// Uses the curated pkg->app-id table first, then falls back to comparing the last
+/// app-id segment (org.gimp.GIMP -> gimp) with the package name minus -bin/-git suffixes.
+fn find_duplicates(
+    native: &[(String, String)],
+    flatpaks: &[InstalledFlatpak],
+) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+    for (ni, (name, _)) in native.iter().enumerate() {
+        let mapped = flathub_api::get_flathub_app_id(name);
+        let base = ["-bin", "-git", "-appimage"]
+            .iter()
+            .fold(name.as_str(), |n, s| n.trim_end_matches(s));
+        for (fi, fp) in flatpaks.iter().enumerate() {
+            let by_map = mapped.as_deref() == Some(fp.app_id.as_str());
+            let by_segment = fp
+                .app_id
+                .rsplit('.')
+                .next()
+                .map(|seg| seg.eq_ignore_ascii_case(base))
+                .unwrap_or(false);
+            if by_map || by_segment {
+                pairs.push((ni, fi));
+                break;
+            }
+        }
+    }
+    pairs
+}
+
+fn profile_hints(native_name: &str, flatpak_id: &str) -> Vec<ProfileHint> {
+    let home = match dirs::home_dir() {
+        Some(h) => h,
+        None => return Vec::new(),
+    };
+    let native_candidates: Vec<PathBuf> = vec![
+        home.join(".config").join(native_name),
+        home.join(".local/share").join(native_name),
+        home.join(format!(".{}", native_name)),
+    ];
+
+    let mut hints: Vec<ProfileHint> = native_candidates
+        .into_iter()
+        .filter(|p| p.exists())
+        .map(|p| ProfileHint {
+            source: "native".to_string(),
+            path: p.to_string_lossy().into_owned(),
+            exists: true,
+        })
+        .collect();
+
+    let flatpak_dir = home.join(".var/app").join(flatpak_id);
+    hints.push(ProfileHint {
+        source: "flatpak".to_string(),
+        exists: flatpak_dir.exists(),
+        path: flatpak_dir.to_string_lossy().into_owned(),
+    });
+    hints
+}
+
+/// Detect apps installed from more than one source (e.g. native firefox + org.mozilla.firefox).
+#[tauri::command]
+pub async fn get_duplicate_installations() -> Result<Vec<DuplicateInstallation>, String> {
+    let flatpaks = flathub_api::get_installed_apps().await;
+    if flatpaks.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let native: Vec<(String, String)> =
+        tokio::task::spawn_blocking(crate::alpm_read::get_installed_packages_native)
+            .await
+            .map_err(|e| format!("Task join error: {}", e))?
+            .into_iter()
+            .map(|p| (p.name, p.version))
+            .collect();
+
+    Ok(find_duplicates(&native, &flatpaks)
+        .into_iter()
+        .map(|(ni, fi)| {
+            let (name, version) = &native[ni];
+            let fp = &flatpaks[fi];
+            DuplicateInstallation {
+                display_name: fp.name.clone(),
+                native_name: name.clone(),
+                native_version: version.clone(),
+                flatpak_id: fp.app_id.clone(),
+                flatpak_version: fp.version.clone(),
+                profile_hints: profile_hints(name, &fp.app_id),
+            }
+        })
+        .collect())
+}
+
+/// Remove the unwanted copy of each duplicate. All native removals go to the helper
+/// as one AlpmUninstall transaction without the cascade, and protected packages are never
+/// removed; Flatpak removals run one by one afterwards.
+#[tauri::command]
+pub async fn consolidate_duplicates(
+    app: AppHandle,
+    choices: Vec<ConsolidationChoice>,
+    password: Option<String>,
+) -> Result<ConsolidationResult, String> {
+    let mut native_to_remove = Vec::new();
+    let mut flatpaks_to_remove = Vec::new();
+    for c in &choices {
+        crate::utils::validate_package_name(&c.native_name)?;
+        crate::utils::validate_package_name(&c.flatpak_id)?;
+        match c.keep.as_str() {
+            "native" => flatpaks_to_remove.push(c.flatpak_id.clone()),
+            "flatpak" => native_to_remove.push(c.native_name.clone()),
+            other => return Err(format!("Invalid consolidation choice: '{}'", other)),
+        }
+    }
+
+    let mut result = ConsolidationResult::default();
+    // Same guard as uninstall_package; without the cascade nothing else can be taken along
+    native_to_remove.retain(|name| {
+        if crate::commands::package::PROTECTED_PACKAGES.contains(&name.as_str()) {
+            log::warn!("Not removing protected system package {}", name);
+            result.failed.push(name.clone());
+            return false;
+        }
+        true
+    });
+
+    let _guard = crate::utils::PRIVILEGED_LOCK.lock().await;
+
+    if !native_to_remove.is_empty() {
+        let _ = app.emit(
+            "install-output",
+            format!("Removing native copies: {}", native_to_remove.join(", ")),
+        );
+        let mut rx = helper_client::invoke_helper(
+            &app,
+            helper_client::HelperCommand::AlpmUninstall {
+                packages: native_to_remove.clone(),
+                // Only the duplicate itself: if something still needs it, the removal fails
+                remove_deps: false,
+                dry_run: false,
+            },
+            password,
+        )
+        .await
+        .map_err(|e| format!("Failed to invoke helper: {}", e))?;
+        while let Some(msg) = rx.recv().await {
+            let _ = app.emit("install-output", &msg.message);
+        }
+
+        for name in native_to_remove {
+            let still_installed = tokio::task::spawn_blocking({
+                let n = name.clone();
+                move || crate::alpm_read::is_package_installed(&n)
+            })
+            .await
+            .map_err(|e| format!("Task join error: {}", e))?;
+            if still_installed {
+                result.failed.push(name);
+            } else {
+                result.removed.push(name);
+            }
+        }
+    }
+
+    for app_id in flatpaks_to_remove {
+        match flathub_api::remove_flatpak(app.clone(), app_id.clone()).await {
+            Ok(()) => result.removed.push(app_id),
+            Err(e) => {
+                log::warn!("Failed to remove flatpak {}: {}", app_id, e);
+                result.failed.push(app_id);
+            }
+        }
+    }
+
+    let status = if result.failed.is_empty() {
+        "success"
+    } else {
+        "failed"
+    };
+    let _ = app.emit("install-complete", status);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fp(id: &str) -> InstalledFlatpak {
+        InstalledFlatpak {
+            app_id: id.to_string(),
+            name: id.to_string(),
+            version: "1.0".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_find_duplicates_by_mapping_and_segment() {
+        let native = vec![
+            ("firefox".to_string(), "130.0-1".to_string()),
+            ("gimp-git".to_string(), "3.0-1".to_string()),
+            ("vim".to_string(), "9.1-1".to_string()),
+        ];
+        let flatpaks = vec![fp("org.mozilla.firefox"), fp("org.gimp.GIMP")];
+        let pairs = find_duplicates(&native, &flatpaks);
+        assert_eq!(pairs, vec![(0, 0), (1, 1)]);
+    }
+}
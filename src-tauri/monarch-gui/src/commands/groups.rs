@@ -0,0 +1,166 @@
+use crate::helper_client;
+use crate::models::Package;
+use crate::repo_manager::RepoManager;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, State};
+
+/// Typed response for get_package_groups
+#[derive(Debug, Serialize, Clone)]
+pub struct PackageGroup {
+    pub name: String,
+    pub member_count: usize,
+    pub installed_count: usize,
+}
+
+/// Members to hand to the helper: not excluded by the user and not installed yet
+/// (pacman `--needed` semantics, so installing a group never reinstalls what is there).
+fn group_install_targets(members: &[Package], exclude: &[String]) -> Vec<String> {
+    members
+        .iter()
+        .filter(|m| !m.installed && !exclude.contains(&m.name))
+        .map(|m| m.name.clone())
+        .collect()
+}
+
+/// Pacman groups (gnome, plasma, xorg, ...) from the sync DBs with member counts.
+#[tauri::command]
+pub async fn get_package_groups() -> Result<Vec<PackageGroup>, String> {
+    tokio::task::spawn_blocking(|| {
+        let installed: std::collections::HashSet<String> =
+            crate::alpm_read::get_installed_packages_native()
+                .into_iter()
+                .map(|p| p.name)
+                .collect();
+        crate::alpm_read::get_package_groups_native()
+            .into_iter()
+            .map(|(name, members)| PackageGroup {
+                installed_count: members
+                    .iter()
+                    .filter(|m| installed.contains(m.as_str()))
+                    .count(),
+                member_count: members.len(),
+                name,
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))
+}
+
+#[tauri::command]
+pub async fn get_group_members(group: String) -> Result<Vec<Package>, String> {
+    crate::utils::validate_package_name(&group)?;
+    let members =
+        tokio::task::spawn_blocking(move || crate::alpm_read::get_group_members_native(&group))
+            .await
+            .map_err(|e| format!("Task join error: {}", e))?;
+    Ok(members)
+}
+
+/// Install a whole group (minus `exclude`) in one helper transaction: either every selected
+/// member is installed or none is.
+#[tauri::command]
+pub async fn install_package_group(
+    app: AppHandle,
+    state_repo: State<'_, RepoManager>,
+    group: String,
+    exclude: Option<Vec<String>>,
+    password: Option<String>,
+) -> Result<Vec<String>, String> {
+    crate::utils::validate_package_name(&group)?;
+    let exclude = exclude.unwrap_or_default();
+    let members = get_group_members(group.clone()).await?;
+    if members.is_empty() {
+        let _ = app.emit("install-complete", "failed");
+        return Err(format!(
+            "Group '{}' not found in any enabled repository",
+            group
+        ));
+    }
+    let targets = group_install_targets(&members, &exclude);
+    if targets.is_empty() {
+        let _ = app.emit(
+            "install-output",
+            format!("All selected members of '{}' are already installed.", group),
+        );
+        let _ = app.emit("install-complete", "success");
+        return Ok(targets);
+    }
+
+    let enabled_repos = state_repo.transaction_repos().await;
+
+    let _guard = crate::utils::PRIVILEGED_LOCK.lock().await;
+    let _ = app.emit(
+        "install-output",
+        format!(
+            "--- Installing group '{}' ({} packages) ---",
+            group,
+            targets.len()
+        ),
+    );
+    let mut rx = helper_client::invoke_helper(
+        &app,
+        helper_client::HelperCommand::AlpmInstall {
+            packages: targets.clone(),
+            sync_first: false,
+            enabled_repos,
+            cpu_optimization: None,
+            target_repo: None,
+            phase: Default::default(),
+            dry_run: false,
+        },
+        password,
+    )
+    .await
+    .map_err(|e| format!("Failed to invoke helper: {}", e))?;
+    while let Some(msg) = rx.recv().await {
+        let _ = app.emit("install-output", &msg.message);
+    }
+
+    let missing = tokio::task::spawn_blocking({
+        let targets = targets.clone();
+        move || {
+            targets
+                .into_iter()
+                .filter(|t| !crate::alpm_read::is_package_installed(t))
+                .collect::<Vec<_>>()
+        }
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?;
+    if !missing.is_empty() {
+        let _ = app.emit("install-complete", "failed");
+        return Err(format!(
+            "Group install of '{}' failed; not installed: {}",
+            group,
+            missing.join(", ")
+        ));
+    }
+    let _ = app.emit("install-complete", "success");
+    Ok(targets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(name: &str, installed: bool) -> Package {
+        Package {
+            name: name.to_string(),
+            installed,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_group_install_targets_skip_installed_and_excluded() {
+        let members = vec![
+            member("gdm", true),
+            member("gnome-shell", false),
+            member("epiphany", false),
+            member("nautilus", false),
+        ];
+        let targets = group_install_targets(&members, &["epiphany".to_string()]);
+        assert_eq!(targets, vec!["gnome-shell", "nautilus"]);
+    }
+}
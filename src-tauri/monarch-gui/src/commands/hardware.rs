@@ -0,0 +1,378 @@
+use crate::helper_client;
+use crate::repo_manager::RepoManager;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, State};
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DeviceKind {
+    Cpu,
+    Gpu,
+    Wifi,
+    Printer,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct HardwareDevice {
+    pub kind: DeviceKind,
+    /// PCI/USB vendor ID in hex ("10de"); the CPUID vendor string for CPUs.
+    pub vendor_id: String,
+    pub description: String,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct DriverRecommendation {
+    pub package: String,
+    pub reason: String,
+    /// Description of the device it is for.
+    pub device: String,
+    pub installed: bool,
+    /// In an enabled sync DB, so `install_recommended_drivers` can install it.
+    pub available: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct HardwareReport {
+    pub devices: Vec<HardwareDevice>,
+    pub recommendations: Vec<DriverRecommendation>,
+}
+
+const VENDOR_NVIDIA: &str = "10de";
+const VENDOR_AMD: &str = "1002";
+const VENDOR_INTEL: &str = "8086";
+const VENDOR_BROADCOM: &str = "14e4";
+
+/// USB vendors whose devices are almost always printers or MFPs, with the driver package
+/// the Arch wiki recommends for them.
+const PRINTER_VENDORS: &[(&str, &str)] = &[
+    ("03f0", "hplip"),
+    ("04b8", "epson-inkjet-printer-escpr"),
+    ("04a9", "gutenprint"),
+    ("04f9", "brlaser"),
+];
+
+/// The `[xxxx]` bracket groups of an `lspci -nn` line, innermost text only.
+fn bracket_groups(line: &str) -> Vec<&str> {
+    line.match_indices('[')
+        .filter_map(|(start, _)| {
+            let rest = &line[start + 1..];
+            rest.find(']').map(|end| &rest[..end])
+        })
+        .collect()
+}
+
+/// GPUs and wireless chips from `lspci -nn`, e.g.
+/// `01:00.0 VGA compatible controller [0300]: NVIDIA Corporation TU106 [GeForce RTX 2060] [10de:1f08] (rev a1)`.
+fn parse_lspci(output: &str) -> Vec<HardwareDevice> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let groups = bracket_groups(line);
+            let class = groups.first()?;
+            let kind = match *class {
+                "0300" | "0302" | "0380" => DeviceKind::Gpu,
+                "0280" => DeviceKind::Wifi,
+                _ => return None,
+            };
+            let ids = groups.iter().rev().find(|g| {
+                g.len() == 9
+                    && g.as_bytes()[4] == b':'
+                    && g.chars().all(|c| c.is_ascii_hexdigit() || c == ':')
+            })?;
+            let description = line
+                .split_once("]: ")
+                .map(|(_, rest)| rest.split(&format!(" [{}]", ids)).next().unwrap_or(rest))
+                .unwrap_or(line)
+                .trim()
+                .to_string();
+            Some(HardwareDevice {
+                kind,
+                vendor_id: ids[..4].to_lowercase(),
+                description,
+            })
+        })
+        .collect()
+}
+
+/// Printers from `lsusb`, e.g. `Bus 001 Device 004: ID 03f0:c511 HP, Inc DeskJet 2600`.
+fn parse_lsusb(output: &str) -> Vec<HardwareDevice> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (_, rest) = line.split_once(" ID ")?;
+            let (ids, name) = rest.split_once(' ').unwrap_or((rest, ""));
+            let vendor = ids.split(':').next()?.to_lowercase();
+            PRINTER_VENDORS.iter().find(|(v, _)| *v == vendor)?;
+            Some(HardwareDevice {
+                kind: DeviceKind::Printer,
+                vendor_id: vendor,
+                description: name.trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// The CPU from `/proc/cpuinfo` (`vendor_id` and `model name` of the first core).
+fn parse_cpuinfo(cpuinfo: &str) -> Option<HardwareDevice> {
+    let field = |key: &str| {
+        cpuinfo
+            .lines()
+            .find(|l| l.starts_with(key))
+            .and_then(|l| l.split_once(':'))
+            .map(|(_, v)| v.trim().to_string())
+    };
+    Some(HardwareDevice {
+        kind: DeviceKind::Cpu,
+        vendor_id: field("vendor_id")?,
+        description: field("model name").unwrap_or_default(),
+    })
+}
+
+/// Driver packages for `devices` as (package, reason, device). `kernels` are the installed
+/// kernel package names: out-of-tree modules come prebuilt only for linux (and linux-lts), so
+/// any other kernel gets the DKMS variant.
+fn recommend(devices: &[HardwareDevice], kernels: &[String]) -> Vec<(String, String, String)> {
+    let stock_only =
+        !kernels.is_empty() && kernels.iter().all(|k| k == "linux" || k == "linux-lts");
+    let mut out: Vec<(String, String, String)> = Vec::new();
+    let mut add = |pkg: &str, reason: &str, device: &HardwareDevice| {
+        if !out.iter().any(|(p, _, _)| p == pkg) {
+            out.push((
+                pkg.to_string(),
+                reason.to_string(),
+                device.description.clone(),
+            ));
+        }
+    };
+
+    for device in devices {
+        match (device.kind, device.vendor_id.as_str()) {
+            (DeviceKind::Cpu, "GenuineIntel") => {
+                add("intel-ucode", "CPU microcode updates", device)
+            }
+            (DeviceKind::Cpu, "AuthenticAMD") => add("amd-ucode", "CPU microcode updates", device),
+            (DeviceKind::Gpu, VENDOR_NVIDIA) => {
+                if stock_only {
+                    for k in kernels {
+                        let pkg = if k == "linux" { "nvidia" } else { "nvidia-lts" };
+                        add(
+                            pkg,
+                            "Proprietary NVIDIA driver for the stock kernel",
+                            device,
+                        );
+                    }
+                } else {
+                    add(
+                        "nvidia-dkms",
+                        "Proprietary NVIDIA driver, rebuilt for every installed kernel",
+                        device,
+                    );
+                }
+                add("nvidia-utils", "NVIDIA OpenGL/Vulkan userspace", device);
+            }
+            (DeviceKind::Gpu, VENDOR_AMD) => {
+                add("mesa", "OpenGL for AMD GPUs", device);
+                add("vulkan-radeon", "Vulkan for AMD GPUs", device);
+            }
+            (DeviceKind::Gpu, VENDOR_INTEL) => {
+                add("mesa", "OpenGL for Intel GPUs", device);
+                add("vulkan-intel", "Vulkan for Intel GPUs", device);
+                add("intel-media-driver", "Hardware video decoding", device);
+            }
+            (DeviceKind::Wifi, VENDOR_BROADCOM) => {
+                let pkg = if stock_only {
+                    "broadcom-wl"
+                } else {
+                    "broadcom-wl-dkms"
+                };
+                add(pkg, "Driver for Broadcom Wi-Fi chips", device);
+            }
+            (DeviceKind::Printer, vendor) => {
+                add("cups", "Printing system", device);
+                if let Some((_, pkg)) = PRINTER_VENDORS.iter().find(|(v, _)| *v == vendor) {
+                    add(pkg, "Printer driver", device);
+                }
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+fn command_output(program: &str, args: &[&str]) -> String {
+    std::process::Command::new(program)
+        .args(args)
+        .env("LC_ALL", "C")
+        .output()
+        .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
+        .unwrap_or_default()
+}
+
+fn detect_devices() -> Vec<HardwareDevice> {
+    let mut devices = Vec::new();
+    if let Some(cpu) = std::fs::read_to_string("/proc/cpuinfo")
+        .ok()
+        .and_then(|c| parse_cpuinfo(&c))
+    {
+        devices.push(cpu);
+    }
+    devices.extend(parse_lspci(&command_output("lspci", &["-nn"])));
+    devices.extend(parse_lsusb(&command_output("lsusb", &[])));
+    devices
+}
+
+/// Detected hardware and the driver packages it needs. lspci (pciutils) and lsusb (usbutils)
+/// are optional; without them only the CPU is reported.
+#[tauri::command]
+pub async fn get_hardware_report() -> Result<HardwareReport, String> {
+    tokio::task::spawn_blocking(|| {
+        let devices = detect_devices();
+        let kernels: Vec<String> = crate::commands::kernel_manager::installed_kernel_bases()
+            .into_values()
+            .collect();
+        let recommendations = recommend(&devices, &kernels)
+            .into_iter()
+            .map(|(package, reason, device)| DriverRecommendation {
+                installed: crate::alpm_read::is_package_installed(&package),
+                available: crate::alpm_read::is_package_in_syncdb(&package),
+                package,
+                reason,
+                device,
+            })
+            .collect();
+        HardwareReport {
+            devices,
+            recommendations,
+        }
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))
+}
+
+/// Install the given recommended drivers in one transaction. Only packages the current
+/// report recommends and that are not installed yet are accepted.
+#[tauri::command]
+pub async fn install_recommended_drivers(
+    app: AppHandle,
+    state_repo: State<'_, RepoManager>,
+    packages: Vec<String>,
+    password: Option<String>,
+) -> Result<Vec<String>, String> {
+    for p in &packages {
+        crate::utils::validate_package_name(p)?;
+    }
+    let report = get_hardware_report().await?;
+    let targets: Vec<String> = report
+        .recommendations
+        .into_iter()
+        .filter(|r| packages.contains(&r.package) && r.available && !r.installed)
+        .map(|r| r.package)
+        .collect();
+    if targets.is_empty() {
+        return Ok(targets);
+    }
+
+    let enabled_repos = state_repo.transaction_repos().await;
+
+    let _guard = crate::utils::PRIVILEGED_LOCK.lock().await;
+    let _ = app.emit(
+        "install-output",
+        format!("--- Installing drivers: {} ---", targets.join(", ")),
+    );
+    let result = helper_client::run_helper(
+        &app,
+        helper_client::HelperCommand::AlpmInstall {
+            packages: targets.clone(),
+            sync_first: false,
+            enabled_repos,
+            cpu_optimization: None,
+            target_repo: None,
+            phase: Default::default(),
+            dry_run: false,
+        },
+        password,
+        |msg| {
+            let _ = app.emit("install-output", &msg.message);
+        },
+    )
+    .await
+    .map_err(|e| format!("Failed to invoke helper: {}", e))?;
+
+    if !result.success {
+        let _ = app.emit("install-complete", "failed");
+        return Err(format!(
+            "Driver install failed: {}",
+            result
+                .error
+                .unwrap_or_else(|| "see the log above".to_string())
+        ));
+    }
+    let _ = app.emit("install-complete", "success");
+    Ok(targets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_devices() {
+        let lspci = "\
+00:02.0 VGA compatible controller [0300]: Intel Corporation Alder Lake-P GT2 [Iris Xe Graphics] [8086:46a6] (rev 0c)
+01:00.0 3D controller [0302]: NVIDIA Corporation GA107M [GeForce RTX 3050 Mobile] [10de:25a2] (rev a1)
+02:00.0 Network controller [0280]: Broadcom Inc. and subsidiaries BCM4360 802.11ac Wireless Network Adapter [14e4:43a0] (rev 03)
+03:00.0 Non-Volatile memory controller [0108]: Samsung Electronics Co Ltd NVMe SSD Controller [144d:a80a]";
+        let devices = parse_lspci(lspci);
+        assert_eq!(devices.len(), 3);
+        assert_eq!(devices[0].kind, DeviceKind::Gpu);
+        assert_eq!(devices[0].vendor_id, "8086");
+        assert_eq!(
+            devices[1].description,
+            "NVIDIA Corporation GA107M [GeForce RTX 3050 Mobile]"
+        );
+        assert_eq!(devices[2].kind, DeviceKind::Wifi);
+
+        let lsusb = "\
+Bus 001 Device 002: ID 046d:c52b Logitech, Inc. Unifying Receiver
+Bus 001 Device 004: ID 03f0:c511 HP, Inc DeskJet 2600";
+        let printers = parse_lsusb(lsusb);
+        assert_eq!(printers.len(), 1);
+        assert_eq!(printers[0].description, "HP, Inc DeskJet 2600");
+
+        let cpu = parse_cpuinfo(
+            "processor\t: 0\nvendor_id\t: AuthenticAMD\nmodel name\t: AMD Ryzen 7 7840U\n",
+        )
+        .unwrap();
+        assert_eq!(cpu.vendor_id, "AuthenticAMD");
+        assert_eq!(cpu.description, "AMD Ryzen 7 7840U");
+    }
+
+    #[test]
+    fn test_recommendations_follow_kernels() {
+        let nvidia = HardwareDevice {
+            kind: DeviceKind::Gpu,
+            vendor_id: VENDOR_NVIDIA.to_string(),
+            description: "GeForce RTX 3050".to_string(),
+        };
+        let broadcom = HardwareDevice {
+            kind: DeviceKind::Wifi,
+            vendor_id: VENDOR_BROADCOM.to_string(),
+            description: "BCM4360".to_string(),
+        };
+        let packages = |kernels: &[&str]| {
+            let kernels: Vec<String> = kernels.iter().map(|k| k.to_string()).collect();
+            recommend(&[nvidia.clone(), broadcom.clone()], &kernels)
+                .into_iter()
+                .map(|(p, _, _)| p)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            packages(&["linux", "linux-lts"]),
+            vec!["nvidia", "nvidia-lts", "nvidia-utils", "broadcom-wl"]
+        );
+        assert_eq!(
+            packages(&["linux-cachyos"]),
+            vec!["nvidia-dkms", "nvidia-utils", "broadcom-wl-dkms"]
+        );
+    }
+}
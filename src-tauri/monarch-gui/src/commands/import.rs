@@ -0,0 +1,356 @@
+//! Recreate another machine's package set from a `pacman -Qqe` list (pasted, or a file).
+//! Each name is looked up in the synced repos, then the AUR, and the user reviews the plan
+//! before anything runs: repo packages (official or chaotic-aur) go in one ExecuteBatch,
+//! AUR ones are built afterwards the same way AUR updates are.
+
+use crate::helper_client::{self, HelperCommand};
+use crate::repo_manager::RepoManager;
+use serde::Serialize;
+use std::collections::HashSet;
+use tauri::{AppHandle, Emitter, State};
+
+/// Pasted lists and files above this size are not package lists.
+const MAX_LIST_BYTES: u64 = 1024 * 1024;
+/// Names per AUR info request, well under the RPC's URL length limit.
+const AUR_CHUNK: usize = 100;
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportSource {
+    /// A sync repo other than chaotic-aur (core, extra, multilib, distro repos).
+    Official,
+    Chaotic,
+    Aur,
+    NotFound,
+    /// The AUR could not be asked about it (network or RPC error); import the list again.
+    LookupFailed,
+}
+
+/// One name from the list and where it would come from.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct ImportEntry {
+    pub name: String,
+    pub source: ImportSource,
+    pub repo: Option<String>,
+    pub version: Option<String>,
+    /// Already installed here; left out of the targets.
+    pub installed: bool,
+}
+
+/// Typed response for import_package_list.
+#[derive(Debug, Serialize, Clone, Default, PartialEq)]
+pub struct ImportPlan {
+    pub entries: Vec<ImportEntry>,
+    /// Installed together in one ExecuteBatch.
+    pub repo_targets: Vec<String>,
+    /// Built one by one after the repo batch.
+    pub aur_targets: Vec<String>,
+    pub not_found: Vec<String>,
+    /// Not in the repos and the AUR lookup failed; not installed.
+    pub lookup_failed: Vec<String>,
+    /// Lines that are not package names, shown so nothing is dropped silently.
+    pub invalid_lines: Vec<String>,
+}
+
+/// Names from `pacman -Qqe` (or `pacman -Qe`, `repo/name`) output, deduplicated in order.
+/// Comments and blank lines are skipped; anything else that isn't a pkgname is returned
+/// separately.
+pub fn parse_package_list(text: &str) -> (Vec<String>, Vec<String>) {
+    let mut seen = HashSet::new();
+    let mut names = Vec::new();
+    let mut invalid = Vec::new();
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        let Some(token) = line.split_whitespace().next() else {
+            continue;
+        };
+        let name = token.rsplit('/').next().unwrap_or(token);
+        if crate::utils::validate_package_name(name).is_err() {
+            invalid.push(line.to_string());
+        } else if seen.insert(name.to_string()) {
+            names.push(name.to_string());
+        }
+    }
+    (names, invalid)
+}
+
+/// Split classified entries into the plan's target lists.
+pub fn build_plan(entries: Vec<ImportEntry>, invalid_lines: Vec<String>) -> ImportPlan {
+    let mut plan = ImportPlan {
+        invalid_lines,
+        ..Default::default()
+    };
+    for e in &entries {
+        match e.source {
+            ImportSource::NotFound => plan.not_found.push(e.name.clone()),
+            ImportSource::LookupFailed => plan.lookup_failed.push(e.name.clone()),
+            _ if e.installed => {}
+            ImportSource::Official | ImportSource::Chaotic => {
+                plan.repo_targets.push(e.name.clone())
+            }
+            ImportSource::Aur => plan.aur_targets.push(e.name.clone()),
+        }
+    }
+    plan.entries = entries;
+    plan
+}
+
+/// The list itself, or the contents of the file it names.
+fn read_input(text_or_path: &str) -> Result<String, String> {
+    let trimmed = text_or_path.trim();
+    let path = match trimmed.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().map(|h| h.join(rest)),
+        None => Some(std::path::PathBuf::from(trimmed)),
+    };
+    match path.filter(|p| !trimmed.contains('\n') && p.is_absolute() && p.is_file()) {
+        Some(path) => {
+            let size = std::fs::metadata(&path).map_err(|e| e.to_string())?.len();
+            if size > MAX_LIST_BYTES {
+                return Err(format!(
+                    "{} is too large for a package list",
+                    path.display()
+                ));
+            }
+            std::fs::read_to_string(&path)
+                .map_err(|e| format!("Could not read {}: {}", path.display(), e))
+        }
+        None if trimmed.len() as u64 > MAX_LIST_BYTES => {
+            Err("The pasted list is too large".to_string())
+        }
+        None => Ok(trimmed.to_string()),
+    }
+}
+
+/// Parse a `pacman -Qqe` list (pasted text, or the path of a file holding one) and classify
+/// each package as official repo, chaotic-aur, AUR or not found. Nothing is installed.
+#[tauri::command]
+pub async fn import_package_list(
+    state_repo: State<'_, RepoManager>,
+    text_or_path: String,
+) -> Result<ImportPlan, String> {
+    let text = tokio::task::spawn_blocking(move || read_input(&text_or_path))
+        .await
+        .map_err(|e| format!("Task join error: {}", e))??;
+    let (names, invalid) = parse_package_list(&text);
+    if names.is_empty() {
+        return Err("No package names found in the list".to_string());
+    }
+    let installed: HashSet<String> = tokio::task::spawn_blocking(|| {
+        crate::alpm_read::get_installed_packages_native()
+            .into_iter()
+            .map(|p| p.name)
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?;
+
+    let mut entries = Vec::with_capacity(names.len());
+    let mut not_in_repos = Vec::new();
+    for name in &names {
+        match state_repo
+            .get_all_packages_with_repos(name)
+            .await
+            .into_iter()
+            .next()
+        {
+            Some((pkg, repo)) => entries.push(ImportEntry {
+                name: name.clone(),
+                source: if repo == "chaotic-aur" {
+                    ImportSource::Chaotic
+                } else {
+                    ImportSource::Official
+                },
+                repo: Some(repo),
+                version: Some(pkg.version),
+                installed: installed.contains(name),
+            }),
+            None => not_in_repos.push(name.as_str()),
+        }
+    }
+
+    let mut aur = Vec::new();
+    let mut unchecked: HashSet<&str> = HashSet::new();
+    for chunk in not_in_repos.chunks(AUR_CHUNK) {
+        match crate::aur_api::get_multi_info(chunk).await {
+            Ok(found) => aur.extend(found),
+            Err(e) => {
+                log::warn!("AUR lookup of {} packages failed: {}", chunk.len(), e);
+                unchecked.extend(chunk.iter().copied());
+            }
+        }
+    }
+    for name in not_in_repos {
+        let found = aur.iter().find(|p| p.name == name);
+        entries.push(ImportEntry {
+            name: name.to_string(),
+            source: if found.is_some() {
+                ImportSource::Aur
+            } else if unchecked.contains(name) {
+                ImportSource::LookupFailed
+            } else {
+                ImportSource::NotFound
+            },
+            repo: found.map(|_| "aur".to_string()),
+            version: found.map(|p| p.version.clone()),
+            installed: installed.contains(name),
+        });
+    }
+    // Back in list order
+    entries.sort_by_key(|e| names.iter().position(|n| n == &e.name));
+    Ok(build_plan(entries, invalid))
+}
+
+/// The targets that may be installed; invalid names and unaccepted license agreements are
+/// reported and added to `failed`.
+async fn accepted(app: &AppHandle, names: Vec<String>, failed: &mut Vec<String>) -> Vec<String> {
+    let mut ok = Vec::with_capacity(names.len());
+    for name in names {
+        let checked = match crate::utils::validate_package_name(&name) {
+            Ok(()) => crate::eula::require_acknowledged(app, &name).await,
+            Err(e) => Err(e),
+        };
+        match checked {
+            Ok(()) => ok.push(name),
+            Err(e) => {
+                let _ = app.emit("install-output", format!("Skipping {}: {}", name, e));
+                failed.push(name);
+            }
+        }
+    }
+    ok
+}
+
+/// Run a reviewed import plan: the repo targets in one transaction, then each AUR target
+/// built and installed, all under the privileged lock. Returns the packages that could not
+/// be installed.
+#[tauri::command]
+pub async fn execute_import_plan(
+    app: AppHandle,
+    repo_targets: Vec<String>,
+    aur_targets: Vec<String>,
+    password: Option<String>,
+) -> Result<Vec<String>, String> {
+    if repo_targets.is_empty() && aur_targets.is_empty() {
+        return Err("Nothing to install".to_string());
+    }
+    let mut failed = Vec::new();
+    let repo_targets = accepted(&app, repo_targets, &mut failed).await;
+    let aur_targets = accepted(&app, aur_targets, &mut failed).await;
+
+    let _guard = crate::utils::PRIVILEGED_LOCK.lock().await;
+    if !repo_targets.is_empty() {
+        let _ = app.emit(
+            "install-output",
+            format!(
+                "--- Installing {} repository packages ---",
+                repo_targets.len()
+            ),
+        );
+        let result = helper_client::run_helper(
+            &app,
+            HelperCommand::ExecuteBatch {
+                manifest: crate::models::TransactionManifest {
+                    install_targets: repo_targets.clone(),
+                    ..Default::default()
+                },
+                dry_run: false,
+            },
+            password.clone(),
+            |msg| {
+                let _ = app.emit("install-output", &msg.message);
+            },
+        )
+        .await
+        .map_err(|e| format!("Failed to invoke helper: {}", e))?;
+        if !result.success {
+            // The batch is one transaction: none of it went in
+            failed.extend(repo_targets);
+        }
+    }
+
+    let mut built_paths = Vec::new();
+    for name in &aur_targets {
+        let _ = app.emit("install-output", format!("--- Building {} ---", name));
+        match crate::commands::package::build_aur_package(&app, name, &password, None).await {
+            Ok(paths) => built_paths.extend(paths),
+            Err(e) => {
+                let _ = app.emit("install-output", format!("Failed to build {}: {}", name, e));
+                failed.push(name.clone());
+            }
+        }
+    }
+    if !built_paths.is_empty() {
+        let _ = app.emit("install-output", "Installing built AUR packages...");
+        let install_paths =
+            crate::commands::package::copy_paths_to_monarch_install(built_paths).await?;
+        let result = helper_client::run_helper(
+            &app,
+            HelperCommand::AlpmInstallFiles {
+                paths: install_paths,
+            },
+            password.clone(),
+            |msg| {
+                let _ = app.emit("install-output", &msg.message);
+            },
+        )
+        .await
+        .map_err(|e| format!("Failed to invoke helper: {}", e))?;
+        if !result.success {
+            let built: Vec<String> = aur_targets
+                .into_iter()
+                .filter(|n| !failed.contains(n))
+                .collect();
+            failed.extend(built);
+        }
+    }
+
+    let _ = app.emit(
+        "install-complete",
+        if failed.is_empty() {
+            "success"
+        } else {
+            "failed"
+        },
+    );
+    Ok(failed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, source: ImportSource, installed: bool) -> ImportEntry {
+        ImportEntry {
+            name: name.to_string(),
+            source,
+            repo: None,
+            version: None,
+            installed,
+        }
+    }
+
+    #[test]
+    fn test_parse_and_plan_import() {
+        let list = "# exported from laptop\nfirefox\nvlc 3.0.21-1\n\nextra/gimp\nfirefox\nbad$name 1.0\nyay-bin # helper\n";
+        let (names, invalid) = parse_package_list(list);
+        assert_eq!(names, vec!["firefox", "vlc", "gimp", "yay-bin"]);
+        assert_eq!(invalid, vec!["bad$name 1.0"]);
+
+        let plan = build_plan(
+            vec![
+                entry("firefox", ImportSource::Official, true),
+                entry("vlc", ImportSource::Official, false),
+                entry("gimp-git", ImportSource::Chaotic, false),
+                entry("yay-bin", ImportSource::Aur, false),
+                entry("gone", ImportSource::NotFound, false),
+                entry("paru", ImportSource::LookupFailed, false),
+            ],
+            invalid,
+        );
+        assert_eq!(plan.repo_targets, vec!["vlc", "gimp-git"]);
+        assert_eq!(plan.aur_targets, vec!["yay-bin"]);
+        assert_eq!(plan.not_found, vec!["gone"]);
+        assert_eq!(plan.lookup_failed, vec!["paru"]);
+        assert_eq!(plan.entries.len(), 6);
+    }
+}
@@ -0,0 +1,280 @@
+use crate::helper_client;
+use crate::repo_manager::RepoManager;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use tauri::{AppHandle, Emitter, State};
+
+/// Every kernel package installs `<release>/pkgbase` here, naming the package it came from.
+const MODULES_DIR: &str = "/usr/lib/modules";
+
+/// Typed row for the kernel manager page.
+#[derive(Debug, Serialize, Clone)]
+pub struct KernelInfo {
+    pub name: String,
+    pub description: String,
+    pub repo: Option<String>,
+    pub available_version: Option<String>,
+    pub installed_version: Option<String>,
+    pub headers_available: bool,
+    pub headers_installed: bool,
+    /// The kernel this session booted.
+    pub running: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct KernelOverview {
+    /// `uname -r`
+    pub running_release: String,
+    /// Package name of the running kernel, if it could be told.
+    pub running_kernel: Option<String>,
+    pub kernels: Vec<KernelInfo>,
+}
+
+fn running_release() -> String {
+    std::process::Command::new("uname")
+        .arg("-r")
+        .output()
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .unwrap_or_default()
+}
+
+/// Installed kernels as release → pkgbase, from the `pkgbase` file in each module directory.
+pub(crate) fn installed_kernel_bases() -> BTreeMap<String, String> {
+    let Ok(entries) = std::fs::read_dir(MODULES_DIR) else {
+        return BTreeMap::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let base = std::fs::read_to_string(entry.path().join("pkgbase")).ok()?;
+            let base = base.trim();
+            (!base.is_empty()).then(|| {
+                (
+                    entry.file_name().to_string_lossy().to_string(),
+                    base.to_string(),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Guess the package from a release string when its module directory is gone (the kernel
+/// was upgraded or removed since boot): `6.10.5-arch1-1` → linux, `6.6.47-1-lts` → linux-lts.
+fn kernel_from_release(release: &str) -> Option<String> {
+    let flavor = release.rsplit('-').next()?;
+    if release.is_empty() || flavor.is_empty() {
+        return None;
+    }
+    if flavor.chars().all(|c| c.is_ascii_digit()) {
+        Some("linux".to_string())
+    } else {
+        Some(format!("linux-{}", flavor))
+    }
+}
+
+async fn kernel_overview() -> Result<KernelOverview, String> {
+    tokio::task::spawn_blocking(|| {
+        let release = running_release();
+        let bases = installed_kernel_bases();
+        let installed: Vec<String> = bases.values().cloned().collect();
+        let packages = crate::alpm_read::get_kernel_packages_native(&installed);
+        let running_kernel = bases.get(&release).cloned().or_else(|| {
+            kernel_from_release(&release).filter(|k| packages.iter().any(|p| &p.name == k))
+        });
+        let kernels = packages
+            .into_iter()
+            .map(|k| KernelInfo {
+                running: running_kernel.as_deref() == Some(k.name.as_str()),
+                name: k.name,
+                description: k.description,
+                repo: k.repo,
+                available_version: k.available_version,
+                installed_version: k.installed_version,
+                headers_available: k.headers_available,
+                headers_installed: k.headers_installed,
+            })
+            .collect();
+        KernelOverview {
+            running_release: release,
+            running_kernel,
+            kernels,
+        }
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))
+}
+
+/// Installed and available kernels, with the running one marked.
+#[tauri::command]
+pub async fn get_kernels() -> Result<KernelOverview, String> {
+    kernel_overview().await
+}
+
+/// Install a kernel and, unless `with_headers` is false, its headers so DKMS modules
+/// (nvidia-dkms, v4l2loopback, ...) build for it.
+#[tauri::command]
+pub async fn install_kernel(
+    app: AppHandle,
+    state_repo: State<'_, RepoManager>,
+    name: String,
+    with_headers: Option<bool>,
+    password: Option<String>,
+) -> Result<(), String> {
+    crate::utils::validate_package_name(&name)?;
+    let overview = kernel_overview().await?;
+    let kernel = overview
+        .kernels
+        .into_iter()
+        .find(|k| k.name == name && k.repo.is_some())
+        .ok_or_else(|| format!("'{}' is not a kernel in any enabled repository", name))?;
+
+    let mut targets = vec![kernel.name.clone()];
+    if with_headers.unwrap_or(true) && kernel.headers_available {
+        targets.push(format!("{}-headers", kernel.name));
+    }
+
+    let enabled_repos = state_repo.transaction_repos().await;
+
+    let _guard = crate::utils::PRIVILEGED_LOCK.lock().await;
+    let _ = app.emit(
+        "install-output",
+        format!("--- Installing kernel: {} ---", targets.join(", ")),
+    );
+    let result = helper_client::run_helper(
+        &app,
+        helper_client::HelperCommand::AlpmInstall {
+            packages: targets,
+            sync_first: false,
+            enabled_repos,
+            cpu_optimization: None,
+            target_repo: kernel.repo,
+            phase: Default::default(),
+            dry_run: false,
+        },
+        password,
+        |msg| {
+            let _ = app.emit("install-output", &msg.message);
+        },
+    )
+    .await
+    .map_err(|e| format!("Failed to invoke helper: {}", e))?;
+
+    if !result.success {
+        let _ = app.emit("install-complete", "failed");
+        return Err(format!(
+            "Kernel install failed: {}",
+            result
+                .error
+                .unwrap_or_else(|| "see the log above".to_string())
+        ));
+    }
+    let _ = app.emit(
+        "install-output",
+        format!(
+            "{} installed. Pick it from the boot menu after the next reboot.",
+            name
+        ),
+    );
+    let _ = app.emit("install-complete", "success");
+    Ok(())
+}
+
+/// Remove a kernel and its headers. Removing the running kernel needs `allow_running`; its
+/// modules vanish with it, so anything not yet loaded (USB drives, VPN, ...) stops working
+/// until reboot. The last installed kernel is never removed.
+#[tauri::command]
+pub async fn remove_kernel(
+    app: AppHandle,
+    name: String,
+    allow_running: Option<bool>,
+    password: Option<String>,
+) -> Result<(), String> {
+    crate::utils::validate_package_name(&name)?;
+    let overview = kernel_overview().await?;
+    let installed: Vec<&KernelInfo> = overview
+        .kernels
+        .iter()
+        .filter(|k| k.installed_version.is_some())
+        .collect();
+    let kernel = installed
+        .iter()
+        .find(|k| k.name == name)
+        .ok_or_else(|| format!("'{}' is not an installed kernel", name))?;
+    if installed.len() == 1 {
+        return Err(format!(
+            "'{}' is the only installed kernel; removing it would leave the system unbootable. Install another kernel first.",
+            name
+        ));
+    }
+    if kernel.running && !allow_running.unwrap_or(false) {
+        return Err(format!(
+            "RunningKernel: '{}' is the kernel you are running. Reboot into another kernel first, or confirm to remove it anyway.",
+            name
+        ));
+    }
+
+    let mut targets = vec![kernel.name.clone()];
+    if kernel.headers_installed {
+        targets.push(format!("{}-headers", kernel.name));
+    }
+
+    let _guard = crate::utils::PRIVILEGED_LOCK.lock().await;
+    let _ = app.emit(
+        "install-output",
+        format!("--- Removing kernel: {} ---", targets.join(", ")),
+    );
+    // No cascade: packages built for this kernel only (e.g. nvidia) make the removal fail
+    // with a dependency error instead of disappearing with it
+    let result = helper_client::run_helper(
+        &app,
+        helper_client::HelperCommand::AlpmUninstall {
+            packages: targets,
+            remove_deps: false,
+            dry_run: false,
+        },
+        password,
+        |msg| {
+            let _ = app.emit("install-output", &msg.message);
+        },
+    )
+    .await
+    .map_err(|e| format!("Failed to invoke helper: {}", e))?;
+
+    if !result.success {
+        let _ = app.emit("install-complete", "failed");
+        return Err(format!(
+            "Kernel removal failed: {}",
+            result
+                .error
+                .unwrap_or_else(|| "see the log above".to_string())
+        ));
+    }
+    let _ = app.emit("install-complete", "success");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kernel_from_release() {
+        assert_eq!(
+            kernel_from_release("6.10.5-arch1-1").as_deref(),
+            Some("linux")
+        );
+        assert_eq!(
+            kernel_from_release("6.6.47-1-lts").as_deref(),
+            Some("linux-lts")
+        );
+        assert_eq!(
+            kernel_from_release("6.10.5-zen1-1-zen").as_deref(),
+            Some("linux-zen")
+        );
+        assert_eq!(
+            kernel_from_release("6.10.5-2-cachyos").as_deref(),
+            Some("linux-cachyos")
+        );
+        assert_eq!(kernel_from_release(""), None);
+    }
+}
@@ -0,0 +1,282 @@
+use crate::helper_client;
+use serde::Serialize;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter};
+
+const PACKAGE_EXTENSIONS: [&str; 4] = [".pkg.tar.zst", ".pkg.tar.xz", ".pkg.tar.gz", ".pkg.tar"];
+
+/// .PKGINFO is one of the first members; a package that doesn't have it this early is malformed.
+const MAX_ENTRIES_BEFORE_PKGINFO: usize = 16;
+const MAX_PKGINFO_LEN: u64 = 256 * 1024;
+
+/// Typed response for preview_local_file
+#[derive(Debug, Serialize, Clone, Default, PartialEq)]
+pub struct LocalPackagePreview {
+    pub path: String,
+    pub name: String,
+    pub version: String,
+    pub description: Option<String>,
+    pub url: Option<String>,
+    pub arch: String,
+    pub installed_size: Option<u64>,
+    pub packager: Option<String>,
+    pub depends: Vec<String>,
+    pub optdepends: Vec<String>,
+    pub conflicts: Vec<String>,
+    pub provides: Vec<String>,
+    /// Version currently installed under the same name, if any.
+    pub installed_version: Option<String>,
+}
+
+/// Parse the `key = value` lines of a .PKGINFO. Repeatable keys (depend, optdepend, ...)
+/// accumulate; unknown keys are ignored.
+fn parse_pkginfo(content: &str) -> LocalPackagePreview {
+    let mut info = LocalPackagePreview::default();
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once(" = ") else {
+            continue;
+        };
+        let value = value.trim().to_string();
+        match key.trim() {
+            "pkgname" => info.name = value,
+            "pkgver" => info.version = value,
+            "pkgdesc" => info.description = Some(value),
+            "url" => info.url = Some(value),
+            "arch" => info.arch = value,
+            "size" => info.installed_size = value.parse().ok(),
+            "packager" => info.packager = Some(value),
+            "depend" => info.depends.push(value),
+            "optdepend" => info.optdepends.push(value),
+            "conflict" => info.conflicts.push(value),
+            "provides" => info.provides.push(value),
+            _ => {}
+        }
+    }
+    info
+}
+
+/// Checks that don't need the file contents: a real, regular package file with a known extension.
+fn validate_local_path(path: &str) -> Result<PathBuf, String> {
+    let p = Path::new(path);
+    if !p.is_absolute() {
+        return Err(format!("Expected an absolute path, got: {}", path));
+    }
+    let file_name = p
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| format!("Invalid path: {}", path))?;
+    if !PACKAGE_EXTENSIONS
+        .iter()
+        .any(|ext| file_name.ends_with(ext))
+    {
+        return Err(format!(
+            "{} is not a package file (expected .pkg.tar.zst, .pkg.tar.xz or .pkg.tar.gz)",
+            file_name
+        ));
+    }
+    let canon = std::fs::canonicalize(p).map_err(|e| format!("Cannot open {}: {}", path, e))?;
+    let meta = std::fs::metadata(&canon).map_err(|e| format!("Cannot open {}: {}", path, e))?;
+    if !meta.is_file() {
+        return Err(format!("{} is not a regular file", path));
+    }
+    Ok(canon)
+}
+
+/// Read .PKGINFO out of the archive and check it describes something installable here.
+fn read_local_package(path: &str) -> Result<LocalPackagePreview, String> {
+    let canon = validate_local_path(path)?;
+    let mut file = BufReader::new(
+        std::fs::File::open(&canon).map_err(|e| format!("Cannot open {}: {}", path, e))?,
+    );
+    let magic: Vec<u8> = file
+        .fill_buf()
+        .map_err(|e| format!("Cannot read {}: {}", path, e))?
+        .iter()
+        .take(4)
+        .copied()
+        .collect();
+
+    // Same magic-byte detection as repo_db; the extension only gates what we accept
+    let reader: Box<dyn Read> = if magic.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+        Box::new(zstd::stream::read::Decoder::new(file).map_err(|e| e.to_string())?)
+    } else if magic.starts_with(&[0xfd, 0x37, 0x7a, 0x58]) {
+        Box::new(xz2::read::XzDecoder::new(file))
+    } else if magic.starts_with(&[0x1f, 0x8b]) {
+        Box::new(flate2::read::GzDecoder::new(file))
+    } else {
+        Box::new(file)
+    };
+
+    let mut archive = tar::Archive::new(reader);
+    let entries = archive
+        .entries()
+        .map_err(|e| format!("Not a valid package archive: {}", e))?;
+    let mut pkginfo = None;
+    for entry in entries.take(MAX_ENTRIES_BEFORE_PKGINFO) {
+        let entry = entry.map_err(|e| format!("Not a valid package archive: {}", e))?;
+        let is_pkginfo = entry
+            .path()
+            .map(|p| p.as_os_str() == ".PKGINFO")
+            .unwrap_or(false);
+        if is_pkginfo {
+            let mut content = String::new();
+            entry
+                .take(MAX_PKGINFO_LEN)
+                .read_to_string(&mut content)
+                .map_err(|e| format!("Unreadable .PKGINFO: {}", e))?;
+            pkginfo = Some(content);
+            break;
+        }
+    }
+    let content = pkginfo.ok_or_else(|| {
+        format!(
+            "{} has no .PKGINFO; it is not a pacman package",
+            canon.display()
+        )
+    })?;
+
+    let mut preview = parse_pkginfo(&content);
+    crate::utils::validate_package_name(&preview.name)
+        .map_err(|e| format!("Package has an invalid name: {}", e))?;
+    if preview.version.is_empty() {
+        return Err("Package .PKGINFO has no pkgver".to_string());
+    }
+    if preview.arch != "any" && preview.arch != std::env::consts::ARCH {
+        return Err(format!(
+            "{} is built for {}, this system is {}",
+            preview.name,
+            preview.arch,
+            std::env::consts::ARCH
+        ));
+    }
+    preview.path = canon.to_string_lossy().to_string();
+    preview.installed_version = crate::alpm_read::get_installed_version(&preview.name);
+    Ok(preview)
+}
+
+/// Metadata of a dropped/selected package file, for the confirmation dialog.
+#[tauri::command]
+pub async fn preview_local_file(path: String) -> Result<LocalPackagePreview, String> {
+    tokio::task::spawn_blocking(move || read_local_package(&path))
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Install a package file from disk (e.g. dragged onto the window). The file is validated,
+/// copied to /tmp/monarch-install (the only place the helper accepts files from) and
+/// installed with a single ALPM transaction.
+#[tauri::command]
+pub async fn install_local_file(
+    app: AppHandle,
+    path: String,
+    password: Option<String>,
+) -> Result<LocalPackagePreview, String> {
+    let preview = match preview_local_file(path).await {
+        Ok(p) => p,
+        Err(e) => {
+            let _ = app.emit("install-output", format!("Error: {}", e));
+            let _ = app.emit("install-complete", "failed");
+            return Err(e);
+        }
+    };
+
+    let _guard = crate::utils::PRIVILEGED_LOCK.lock().await;
+    let _ = app.emit(
+        "install-output",
+        format!(
+            "--- Installing local package {} {} ---",
+            preview.name, preview.version
+        ),
+    );
+    // Copying a file onto itself would truncate it, and we must not delete the user's file after
+    let already_staged = preview.path.starts_with("/tmp/monarch-install/");
+    let install_paths = if already_staged {
+        vec![preview.path.clone()]
+    } else {
+        crate::commands::package::copy_paths_to_monarch_install(vec![preview.path.clone()]).await?
+    };
+    let mut rx = helper_client::invoke_helper(
+        &app,
+        helper_client::HelperCommand::AlpmInstallFiles {
+            paths: install_paths.clone(),
+        },
+        password,
+    )
+    .await
+    .map_err(|e| format!("Failed to invoke helper: {}", e))?;
+    while let Some(msg) = rx.recv().await {
+        let _ = app.emit("install-output", &msg.message);
+    }
+    if !already_staged {
+        for p in &install_paths {
+            let _ = tokio::fs::remove_file(p).await;
+        }
+    }
+
+    let (name, version) = (preview.name.clone(), preview.version.clone());
+    let installed = tokio::task::spawn_blocking(move || {
+        crate::alpm_read::get_installed_version(&name).as_deref() == Some(version.as_str())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?;
+    if !installed {
+        let _ = app.emit("install-complete", "failed");
+        return Err(format!(
+            "Installing {} {} failed; see the install log for details",
+            preview.name, preview.version
+        ));
+    }
+    let _ = app.emit("install-complete", "success");
+    Ok(preview)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pkginfo_collects_metadata_and_deps() {
+        let content = "\
+# Generated by makepkg 6.1.0
+pkgname = anydesk-bin
+pkgbase = anydesk-bin
+pkgver = 6.3.2-1
+pkgdesc = The Fast Remote Desktop Application = fast
+url = https://anydesk.com
+size = 12345678
+arch = x86_64
+license = custom
+depend = gtk3
+depend = libxrandr
+optdepend = libpulse: audio support
+conflict = anydesk
+provides = anydesk=6.3.2
+";
+        let info = parse_pkginfo(content);
+        assert_eq!(info.name, "anydesk-bin");
+        assert_eq!(info.version, "6.3.2-1");
+        assert_eq!(
+            info.description.as_deref(),
+            Some("The Fast Remote Desktop Application = fast")
+        );
+        assert_eq!(info.installed_size, Some(12345678));
+        assert_eq!(info.arch, "x86_64");
+        assert_eq!(info.depends, vec!["gtk3", "libxrandr"]);
+        assert_eq!(info.optdepends, vec!["libpulse: audio support"]);
+        assert_eq!(info.conflicts, vec!["anydesk"]);
+        assert_eq!(info.provides, vec!["anydesk=6.3.2"]);
+        assert!(info.installed_version.is_none());
+    }
+
+    #[test]
+    fn test_validate_local_path_rejects_non_packages() {
+        assert!(validate_local_path("relative/foo.pkg.tar.zst").is_err());
+        assert!(validate_local_path("/etc/passwd").is_err());
+        assert!(validate_local_path("/nonexistent/foo-1.0-1-x86_64.pkg.tar.zst").is_err());
+    }
+}
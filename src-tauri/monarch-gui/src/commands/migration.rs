@@ -0,0 +1,427 @@
+//! AUR packages that a binary repo has picked up since they were built here (chaotic-aur,
+//! cachyos, or a move to extra). Reinstalling them from the repo keeps the install reason and
+//! saves a local build on every later update. The scheduler refreshes the list after each
+//! background update check and announces packages it has not suggested before.
+//!
+//! The other direction too: repo binaries whose repo has dropped them. Switching one to the
+//! AUR records it in `update_sources.json`, and update checks then offer the AUR build even
+//! when the dropped binary's version sorts ahead of the PKGBUILD's (typical for -git builds).
+
+use crate::alpm_read::LocalBuild;
+use crate::commands::package::PendingUpdate;
+use crate::helper_client::{self, HelperCommand};
+use crate::repo_manager::RepoManager;
+use monarch_types::TransactionPhase;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+/// Typed response for get_migration_suggestions: one locally built package a repo now ships.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct MigrationSuggestion {
+    pub name: String,
+    pub installed_version: String,
+    pub repo: String,
+    pub repo_version: String,
+    pub download_size: u64,
+}
+
+/// Typed response for get_dropped_packages: a repo binary no enabled sync DB carries any more.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct DroppedPackage {
+    pub name: String,
+    pub installed_version: String,
+    /// None when the AUR has no package of that name either.
+    pub aur_version: Option<String>,
+    /// Update checks already treat it as an AUR package.
+    pub switched: bool,
+}
+
+/// Names suggested by the last refresh, so the event only fires for new ones.
+static LAST_SUGGESTED: once_cell::sync::Lazy<Mutex<Vec<String>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(Vec::new()));
+
+/// Dropped packages reported by the last refresh, same purpose.
+static LAST_DROPPED: once_cell::sync::Lazy<Mutex<Vec<String>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(Vec::new()));
+
+fn sources_path(app: &AppHandle) -> PathBuf {
+    let mut path = app
+        .path()
+        .app_data_dir()
+        .unwrap_or_else(|_| PathBuf::from("."));
+    if !path.exists() {
+        let _ = std::fs::create_dir_all(&path);
+    }
+    path.push("update_sources.json");
+    path
+}
+
+/// Package → update source ("aur") for packages whose original repo dropped them.
+fn load_sources(app: &AppHandle) -> BTreeMap<String, String> {
+    std::fs::read_to_string(sources_path(app))
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+fn save_sources(app: &AppHandle, sources: &BTreeMap<String, String>) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(sources).map_err(|e| e.to_string())?;
+    std::fs::write(sources_path(app), content).map_err(|e| e.to_string())
+}
+
+/// Packages whose updates come from the AUR by the user's choice.
+pub(crate) fn switched_to_aur(app: &AppHandle) -> Vec<String> {
+    load_sources(app)
+        .into_iter()
+        .filter(|(_, source)| source == "aur")
+        .map(|(name, _)| name)
+        .collect()
+}
+
+/// An AUR "downgrade" of a switched package is the rebuild that replaces the dropped binary.
+pub(crate) fn apply_source_prefs(
+    app: &AppHandle,
+    mut updates: Vec<PendingUpdate>,
+) -> Vec<PendingUpdate> {
+    let switched = switched_to_aur(app);
+    for u in updates.iter_mut().filter(|u| u.repo == "aur") {
+        if switched.contains(&u.name) {
+            u.downgrade = false;
+        }
+    }
+    updates
+}
+
+fn dropped_from(
+    installed: Vec<(String, String)>,
+    aur_versions: &BTreeMap<String, String>,
+    switched: &[String],
+) -> Vec<DroppedPackage> {
+    let mut out: Vec<DroppedPackage> = installed
+        .into_iter()
+        .map(|(name, version)| DroppedPackage {
+            aur_version: aur_versions.get(&name).cloned(),
+            switched: switched.contains(&name),
+            installed_version: version,
+            name,
+        })
+        .collect();
+    out.sort_by(|a, b| a.name.cmp(&b.name));
+    out
+}
+
+async fn current_dropped(app: &AppHandle) -> Result<Vec<DroppedPackage>, String> {
+    let installed = tokio::task::spawn_blocking(crate::alpm_read::get_dropped_repo_packages_native)
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?;
+    let names: Vec<&str> = installed.iter().map(|(n, _)| n.as_str()).collect();
+    let aur_versions: BTreeMap<String, String> = crate::aur_api::get_multi_info(&names)
+        .await?
+        .into_iter()
+        .map(|p| (p.name, p.version))
+        .collect();
+
+    // A switched package that was rebuilt from the AUR is an ordinary local build now
+    let mut sources = load_sources(app);
+    let before = sources.len();
+    sources.retain(|name, _| installed.iter().any(|(n, _)| n == name));
+    if sources.len() != before {
+        save_sources(app, &sources)?;
+    }
+    Ok(dropped_from(
+        installed,
+        &aur_versions,
+        &switched_to_aur(app),
+    ))
+}
+
+/// For each build, the first repo (pacman.conf order, as pacman resolves it) with the same or a
+/// newer version. A copy with the build's own build date is the same binary installed with -U,
+/// not a local build.
+fn suggest(builds: &[LocalBuild]) -> Vec<MigrationSuggestion> {
+    let mut out: Vec<MigrationSuggestion> = builds
+        .iter()
+        .filter_map(|b| {
+            let copy = b.in_repos.iter().find(|c| {
+                c.build_date != b.build_date
+                    && crate::versions::compare(&c.version, &b.version).is_ge()
+            })?;
+            Some(MigrationSuggestion {
+                name: b.name.clone(),
+                installed_version: b.version.clone(),
+                repo: copy.repo.clone(),
+                repo_version: copy.version.clone(),
+                download_size: copy.download_size,
+            })
+        })
+        .collect();
+    out.sort_by(|a, b| a.name.cmp(&b.name));
+    out
+}
+
+async fn current_suggestions(state_repo: &RepoManager) -> Result<Vec<MigrationSuggestion>, String> {
+    let repos = state_repo.transaction_repos().await;
+    tokio::task::spawn_blocking(move || {
+        suggest(&crate::alpm_read::get_local_builds_in_repos_native(&repos))
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))
+}
+
+/// Recompute suggestions (scheduler) and emit "migration-suggestions" when a package shows up
+/// that was not suggested last time.
+pub(crate) async fn refresh(app: &AppHandle) {
+    let suggestions = match current_suggestions(&app.state::<RepoManager>()).await {
+        Ok(s) => s,
+        Err(e) => {
+            log::warn!("Migration check failed: {}", e);
+            return;
+        }
+    };
+    let names: Vec<String> = suggestions.iter().map(|s| s.name.clone()).collect();
+    let has_new = match LAST_SUGGESTED.lock() {
+        Ok(mut last) => {
+            let has_new = names.iter().any(|n| !last.contains(n));
+            *last = names;
+            has_new
+        }
+        Err(_) => false,
+    };
+    if has_new {
+        let _ = app.emit("migration-suggestions", &suggestions);
+    }
+
+    let dropped = match current_dropped(app).await {
+        Ok(d) => d,
+        Err(e) => {
+            log::warn!("Dropped package check failed: {}", e);
+            return;
+        }
+    };
+    let names: Vec<String> = dropped
+        .iter()
+        .filter(|d| !d.switched)
+        .map(|d| d.name.clone())
+        .collect();
+    let has_new = match LAST_DROPPED.lock() {
+        Ok(mut last) => {
+            let has_new = names.iter().any(|n| !last.contains(n));
+            *last = names;
+            has_new
+        }
+        Err(_) => false,
+    };
+    if has_new {
+        let _ = app.emit("dropped-packages", &dropped);
+    }
+}
+
+#[tauri::command]
+pub async fn get_migration_suggestions(
+    state_repo: State<'_, RepoManager>,
+) -> Result<Vec<MigrationSuggestion>, String> {
+    current_suggestions(&state_repo).await
+}
+
+/// Reinstall the given suggested packages from their binary repo, one transaction per repo.
+/// Returns the packages that are no longer local builds afterwards.
+#[tauri::command]
+pub async fn migrate_to_repo(
+    app: AppHandle,
+    state_repo: State<'_, RepoManager>,
+    packages: Vec<String>,
+    password: Option<String>,
+) -> Result<Vec<String>, String> {
+    if packages.is_empty() {
+        return Err("No packages to migrate".to_string());
+    }
+    let suggestions = current_suggestions(&state_repo).await?;
+    let mut by_repo: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for name in &packages {
+        let s = suggestions
+            .iter()
+            .find(|s| &s.name == name)
+            .ok_or_else(|| format!("{} is not available from a binary repo", name))?;
+        by_repo
+            .entry(s.repo.clone())
+            .or_default()
+            .push(s.name.clone());
+    }
+    let enabled_repos = state_repo.transaction_repos().await;
+
+    {
+        let _guard = crate::utils::PRIVILEGED_LOCK.lock().await;
+        for (repo, names) in by_repo {
+            let _ = app.emit(
+                "install-output",
+                format!("--- Reinstalling {} from {} ---", names.join(", "), repo),
+            );
+            let mut rx = helper_client::invoke_helper(
+                &app,
+                HelperCommand::AlpmInstall {
+                    packages: names,
+                    sync_first: false,
+                    enabled_repos: enabled_repos.clone(),
+                    cpu_optimization: None,
+                    target_repo: Some(repo),
+                    phase: TransactionPhase::Full,
+                    dry_run: false,
+                },
+                password.clone(),
+            )
+            .await
+            .map_err(|e| format!("Failed to invoke helper: {}", e))?;
+            while let Some(msg) = rx.recv().await {
+                let _ = app.emit("install-output", &msg.message);
+            }
+        }
+    }
+
+    let remaining = current_suggestions(&state_repo).await?;
+    let migrated: Vec<String> = packages
+        .into_iter()
+        .filter(|n| !remaining.iter().any(|s| &s.name == n))
+        .collect();
+    if let Ok(mut last) = LAST_SUGGESTED.lock() {
+        last.retain(|n| !migrated.contains(n));
+    }
+    let _ = app.emit(
+        "install-complete",
+        if migrated.is_empty() {
+            "failed"
+        } else {
+            "success"
+        },
+    );
+    if migrated.is_empty() {
+        return Err("No package was reinstalled from its binary repo".to_string());
+    }
+    Ok(migrated)
+}
+
+#[tauri::command]
+pub async fn get_dropped_packages(app: AppHandle) -> Result<Vec<DroppedPackage>, String> {
+    current_dropped(&app).await
+}
+
+/// Take updates for the given dropped packages from the AUR from now on. Nothing is rebuilt
+/// here; the next update run builds them like any other AUR update.
+#[tauri::command]
+pub async fn switch_to_aur(app: AppHandle, packages: Vec<String>) -> Result<Vec<String>, String> {
+    if packages.is_empty() {
+        return Err("No packages to switch".to_string());
+    }
+    let dropped = current_dropped(&app).await?;
+    for name in &packages {
+        let d = dropped
+            .iter()
+            .find(|d| &d.name == name)
+            .ok_or_else(|| format!("{} is still provided by a repository", name))?;
+        if d.aur_version.is_none() {
+            return Err(format!("{} is not in the AUR either", name));
+        }
+    }
+    let mut sources = load_sources(&app);
+    for name in &packages {
+        sources.insert(name.clone(), "aur".to_string());
+    }
+    save_sources(&app, &sources)?;
+    if let Ok(mut last) = LAST_DROPPED.lock() {
+        last.retain(|n| !packages.contains(n));
+    }
+    Ok(packages)
+}
+
+/// Stop treating a package as switched (it goes back to a plain version comparison).
+#[tauri::command]
+pub async fn reset_update_source(app: AppHandle, name: String) -> Result<(), String> {
+    let mut sources = load_sources(&app);
+    if sources.remove(&name).is_some() {
+        save_sources(&app, &sources)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alpm_read::RepoCopy;
+
+    fn copy(repo: &str, version: &str, build_date: i64) -> RepoCopy {
+        RepoCopy {
+            repo: repo.to_string(),
+            version: version.to_string(),
+            build_date,
+            download_size: 2048,
+        }
+    }
+
+    #[test]
+    fn test_suggests_first_repo_with_same_or_newer_version() {
+        let builds = vec![
+            LocalBuild {
+                name: "zen-browser-bin".to_string(),
+                version: "1.7b-1".to_string(),
+                build_date: 100,
+                in_repos: vec![
+                    copy("cachyos", "1.6b-1", 90),
+                    copy("chaotic-aur", "1.7b-1", 120),
+                ],
+            },
+            LocalBuild {
+                name: "paru".to_string(),
+                version: "2.0.4-1".to_string(),
+                build_date: 200,
+                in_repos: vec![copy("extra", "2.0.4-1", 200)],
+            },
+            LocalBuild {
+                name: "android-tools".to_string(),
+                version: "35.0.1-1".to_string(),
+                build_date: 300,
+                in_repos: vec![copy("extra", "35.0.2-2", 310)],
+            },
+        ];
+        let got = suggest(&builds);
+        // paru's repo copy has the same build date: it is that binary, not a local build
+        assert_eq!(
+            got.iter()
+                .map(|s| (s.name.as_str(), s.repo.as_str(), s.repo_version.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("android-tools", "extra", "35.0.2-2"),
+                ("zen-browser-bin", "chaotic-aur", "1.7b-1"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dropped_packages_report_aur_availability() {
+        let installed = vec![
+            ("zoom".to_string(), "6.1.0-1".to_string()),
+            ("hyprland-git".to_string(), "0.45.0.r12.gabc-1".to_string()),
+        ];
+        let aur: BTreeMap<String, String> =
+            [("hyprland-git".to_string(), "0.44.0.r3.gdef-1".to_string())].into();
+        let got = dropped_from(installed, &aur, &["hyprland-git".to_string()]);
+        assert_eq!(
+            got,
+            vec![
+                DroppedPackage {
+                    name: "hyprland-git".to_string(),
+                    installed_version: "0.45.0.r12.gabc-1".to_string(),
+                    aur_version: Some("0.44.0.r3.gdef-1".to_string()),
+                    switched: true,
+                },
+                DroppedPackage {
+                    name: "zoom".to_string(),
+                    installed_version: "6.1.0-1".to_string(),
+                    aur_version: None,
+                    switched: false,
+                },
+            ]
+        );
+    }
+}
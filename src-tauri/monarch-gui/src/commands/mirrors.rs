@@ -0,0 +1,420 @@
+//! Per-repo mirror management: read the current mirrorlist, fetch ranked candidates from
+//! reflector / rate-mirrors, probe mirrors for latency, speed and last sync, and write a new
+//! order through the helper (SetMirrorlist).
+
+use crate::{helper_client, http_client};
+use futures::StreamExt;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+
+/// Probes in flight at once; more than this skews the speed numbers.
+const PROBE_CONCURRENCY: usize = 4;
+/// Bytes of the repo database fetched to estimate throughput.
+const SPEED_SAMPLE_BYTES: u64 = 256 * 1024;
+const MAX_PROBE_URLS: usize = 50;
+
+#[derive(Debug, Serialize, Clone, PartialEq, Default)]
+pub struct MirrorInfo {
+    /// Server URL as pacman sees it (with `$repo` / `$arch`).
+    pub url: String,
+    /// false for commented-out `#Server = ` lines.
+    pub enabled: bool,
+    /// From `## Country` headers or the ranking tool, when known.
+    pub country: Option<String>,
+    pub latency_ms: Option<u32>,
+    pub speed_bps: Option<u64>,
+    /// Unix time of the mirror's last sync (`lastsync` file), when published.
+    pub last_sync: Option<i64>,
+}
+
+/// Canonical repo key as used by the helper, plus the mirrorlist it owns.
+fn mirrorlist_for(repo: &str) -> Option<(&'static str, &'static str)> {
+    let repo = repo.trim().to_lowercase();
+    let key = match repo.as_str() {
+        "" | "arch" | "official arch linux" | "core" | "extra" | "multilib" => "arch",
+        r if r.starts_with("cachyos") && (r.ends_with("-v4") || r.contains("znver4")) => {
+            "cachyos-v4"
+        }
+        r if r.starts_with("cachyos") && r.ends_with("-v3") => "cachyos-v3",
+        r if r.starts_with("cachyos") => "cachyos",
+        r if r.starts_with("chaotic") => "chaotic-aur",
+        "endeavouros" => "endeavouros",
+        _ => return None,
+    };
+    let path = match key {
+        "arch" => "/etc/pacman.d/mirrorlist",
+        "cachyos" => "/etc/pacman.d/cachyos-mirrorlist",
+        "cachyos-v3" => "/etc/pacman.d/cachyos-v3-mirrorlist",
+        "cachyos-v4" => "/etc/pacman.d/cachyos-v4-mirrorlist",
+        "chaotic-aur" => "/etc/pacman.d/chaotic-mirrorlist",
+        _ => "/etc/pacman.d/endeavouros-mirrorlist",
+    };
+    Some((key, path))
+}
+
+/// Repo whose database is sampled for the speed test.
+fn sample_db(key: &str) -> &'static str {
+    match key {
+        "arch" => "core",
+        "cachyos-v3" => "cachyos-v3",
+        "cachyos-v4" => "cachyos-v4",
+        "cachyos" => "cachyos",
+        "chaotic-aur" => "chaotic-aur",
+        _ => "endeavouros",
+    }
+}
+
+/// `rate-mirrors` subcommand for a repo key.
+fn rate_mirrors_target(key: &str) -> &'static str {
+    match key {
+        "arch" => "arch",
+        "chaotic-aur" => "chaotic-aur",
+        "endeavouros" => "endeavouros",
+        _ => "cachyos",
+    }
+}
+
+/// Same rule as the helper: http(s) with a host and nothing that could break the line.
+fn valid_server(url: &str) -> bool {
+    let Some(rest) = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+    else {
+        return false;
+    };
+    !rest.split('/').next().unwrap_or("").is_empty()
+        && url.len() <= 512
+        && !url
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control() || c == '#' || c == '\\')
+}
+
+/// Parse a mirrorlist (file contents or ranking tool output). Keeps disabled servers, picks
+/// up `## Country` headers, trailing `# 45ms` latencies and rate-mirrors speed comments.
+fn parse_mirrorlist(text: &str) -> Vec<MirrorInfo> {
+    static SERVER: once_cell::sync::Lazy<regex::Regex> = once_cell::sync::Lazy::new(|| {
+        regex::Regex::new(r"^(#\s*)?Server\s*=\s*(\S+)(?:\s*#\s*(\d+)\s*ms)?").unwrap()
+    });
+    static SPEED: once_cell::sync::Lazy<regex::Regex> = once_cell::sync::Lazy::new(|| {
+        regex::Regex::new(r"(?i)speed:\s*([\d.]+)\s*(B|KB|KiB|MB|MiB|GB|GiB)/s").unwrap()
+    });
+    static CONNECT: once_cell::sync::Lazy<regex::Regex> = once_cell::sync::Lazy::new(|| {
+        regex::Regex::new(r"(?i)connection_time:\s*(\d+)\s*ms").unwrap()
+    });
+
+    let mut out: Vec<MirrorInfo> = Vec::new();
+    let mut country: Option<String> = None;
+    let mut pending_speed: Option<u64> = None;
+    let mut pending_latency: Option<u32> = None;
+    for line in text.lines().map(str::trim) {
+        if let Some(c) = SERVER.captures(line) {
+            let url = c[2].to_string();
+            if !valid_server(&url) || out.iter().any(|m| m.url == url) {
+                continue;
+            }
+            out.push(MirrorInfo {
+                url,
+                enabled: c.get(1).is_none(),
+                country: country.clone(),
+                latency_ms: c
+                    .get(3)
+                    .and_then(|m| m.as_str().parse().ok())
+                    .or(pending_latency.take()),
+                speed_bps: pending_speed.take(),
+                last_sync: None,
+            });
+        } else if let Some(header) = line.strip_prefix("##") {
+            let header = header.trim();
+            // Arch's generated list starts with "## Arch Linux repository mirrorlist" etc.
+            if !header.is_empty()
+                && !header.contains(':')
+                && !header.chars().any(|c| c.is_ascii_digit())
+                && header.split_whitespace().count() <= 3
+            {
+                country = Some(header.to_string());
+            }
+        } else if line.starts_with('#') {
+            if let Some(c) = SPEED.captures(line) {
+                let value: f64 = c[1].parse().unwrap_or(0.0);
+                let unit = match c[2].to_ascii_uppercase().as_str() {
+                    "KB" | "KIB" => 1024.0,
+                    "MB" | "MIB" => 1024.0 * 1024.0,
+                    "GB" | "GIB" => 1024.0 * 1024.0 * 1024.0,
+                    _ => 1.0,
+                };
+                pending_speed = Some((value * unit) as u64);
+            }
+            if let Some(c) = CONNECT.captures(line) {
+                pending_latency = c[1].parse().ok();
+            }
+        }
+    }
+    out
+}
+
+/// Where the `lastsync` file lives for a server URL: the part before `$repo`.
+fn lastsync_url(server: &str) -> Option<String> {
+    let base = &server[..server.find("$repo")?];
+    Some(format!("{}/lastsync", base.trim_end_matches('/')))
+}
+
+fn expand_server(server: &str, repo: &str) -> String {
+    server
+        .replace("$repo", repo)
+        .replace("$arch", std::env::consts::ARCH)
+}
+
+async fn probe_mirror(client: &reqwest::Client, key: &str, mut mirror: MirrorInfo) -> MirrorInfo {
+    if let Some(url) = lastsync_url(&mirror.url) {
+        let started = Instant::now();
+        if let Ok(resp) = http_client::fetch(client.get(&url)).await {
+            mirror.latency_ms = Some(started.elapsed().as_millis().min(u32::MAX as u128) as u32);
+            if resp.is_success() {
+                mirror.last_sync = resp.text().trim().parse().ok();
+            }
+        }
+    }
+
+    let db = sample_db(key);
+    let db_url = format!(
+        "{}/{}.db",
+        expand_server(&mirror.url, db).trim_end_matches('/'),
+        db
+    );
+    let started = Instant::now();
+    let request = client.get(&db_url).header(
+        reqwest::header::RANGE,
+        format!("bytes=0-{}", SPEED_SAMPLE_BYTES - 1),
+    );
+    match http_client::fetch(request).await {
+        Ok(resp) if resp.is_success() => {
+            let elapsed = started.elapsed();
+            if mirror.latency_ms.is_none() {
+                mirror.latency_ms = Some(elapsed.as_millis().min(u32::MAX as u128) as u32);
+            }
+            let secs = elapsed.as_secs_f64().max(0.001);
+            mirror.speed_bps = Some((resp.body.len() as f64 / secs) as u64);
+        }
+        Ok(_) | Err(_) => mirror.speed_bps = None,
+    }
+    mirror
+}
+
+async fn probe_all(key: &str, mirrors: Vec<MirrorInfo>) -> Vec<MirrorInfo> {
+    let client = http_client::client(Duration::from_secs(6));
+    futures::stream::iter(mirrors.into_iter().take(MAX_PROBE_URLS))
+        .map(|m| probe_mirror(&client, key, m))
+        .buffered(PROBE_CONCURRENCY)
+        .collect()
+        .await
+}
+
+fn read_mirrorlist(path: &str) -> Result<Vec<MirrorInfo>, String> {
+    std::fs::read_to_string(path)
+        .map(|s| parse_mirrorlist(&s))
+        .map_err(|e| format!("Failed to read {}: {}", path, e))
+}
+
+fn run_ranking_tool(program: &str, args: &[&str]) -> Result<Vec<MirrorInfo>, String> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("{} is not available: {}", program, e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{} failed: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let mirrors = parse_mirrorlist(&String::from_utf8_lossy(&output.stdout));
+    if mirrors.is_empty() {
+        return Err(format!("{} returned no mirrors", program));
+    }
+    Ok(mirrors)
+}
+
+/// Mirror sources usable for `repo`: "file" always, then reflector (Arch only) and
+/// rate-mirrors (also what cachyos-rate-mirrors runs) when installed.
+#[tauri::command]
+pub fn get_mirror_sources(repo: String) -> Result<Vec<String>, String> {
+    let (key, _) = mirrorlist_for(&repo).ok_or_else(|| format!("Unknown repository: {}", repo))?;
+    let mut sources = vec!["file".to_string()];
+    if key == "arch" && which::which("reflector").is_ok() {
+        sources.push("reflector".to_string());
+    }
+    if which::which("rate-mirrors").is_ok() {
+        sources.push("rate-mirrors".to_string());
+    }
+    Ok(sources)
+}
+
+/// Mirrors for `repo`. `source` is "file" (default: the current mirrorlist), "reflector" or
+/// "rate-mirrors" (ranked candidates; nothing is written).
+#[tauri::command]
+pub async fn list_mirrors(repo: String, source: Option<String>) -> Result<Vec<MirrorInfo>, String> {
+    let (key, path) =
+        mirrorlist_for(&repo).ok_or_else(|| format!("Unknown repository: {}", repo))?;
+    let source = source.unwrap_or_else(|| "file".to_string());
+    tokio::task::spawn_blocking(move || match source.as_str() {
+        "file" => read_mirrorlist(path),
+        "reflector" if key == "arch" => run_ranking_tool(
+            "reflector",
+            &["--protocol", "https", "--latest", "30", "--sort", "rate"],
+        ),
+        "reflector" => Err("reflector only ranks Arch Linux mirrors".to_string()),
+        "rate-mirrors" => run_ranking_tool(
+            "rate-mirrors",
+            &["--protocol", "https", rate_mirrors_target(key)],
+        ),
+        other => Err(format!("Unknown mirror source: {}", other)),
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Probe mirrors without touching system config: latency (time to the `lastsync` file),
+/// last sync time, and throughput from a partial download of the repo database.
+#[tauri::command]
+pub async fn test_mirror_latency(
+    repo: String,
+    urls: Vec<String>,
+) -> Result<Vec<MirrorInfo>, String> {
+    let (key, _) = mirrorlist_for(&repo).ok_or_else(|| format!("Unknown repository: {}", repo))?;
+    if let Some(bad) = urls.iter().find(|u| !valid_server(u)) {
+        return Err(format!("Invalid mirror URL: {}", bad));
+    }
+    let mirrors = urls
+        .into_iter()
+        .map(|url| MirrorInfo {
+            url,
+            enabled: true,
+            ..Default::default()
+        })
+        .collect();
+    Ok(probe_all(key, mirrors).await)
+}
+
+/// Write `ordered_urls` as the mirrorlist for `repo` via the helper, then read it back.
+#[tauri::command]
+pub async fn set_mirrorlist(
+    app: AppHandle,
+    repo: String,
+    ordered_urls: Vec<String>,
+    password: Option<String>,
+) -> Result<Vec<MirrorInfo>, String> {
+    let (key, path) =
+        mirrorlist_for(&repo).ok_or_else(|| format!("Unknown repository: {}", repo))?;
+    if ordered_urls.is_empty() {
+        return Err("Select at least one mirror".to_string());
+    }
+    if let Some(bad) = ordered_urls.iter().find(|u| !valid_server(u)) {
+        return Err(format!("Invalid mirror URL: {}", bad));
+    }
+
+    let _guard = crate::utils::PRIVILEGED_LOCK.lock().await;
+    let mut rx = helper_client::invoke_helper(
+        &app,
+        helper_client::HelperCommand::SetMirrorlist {
+            repo: key.to_string(),
+            servers: ordered_urls.clone(),
+        },
+        password,
+    )
+    .await
+    .map_err(|e| format!("Failed to invoke helper: {}", e))?;
+    let mut last_error = None;
+    while let Some(msg) = rx.recv().await {
+        if msg.message.starts_with("Error") {
+            last_error = Some(msg.message);
+        }
+    }
+
+    let written = read_mirrorlist(path)?;
+    let enabled: Vec<&str> = written
+        .iter()
+        .filter(|m| m.enabled)
+        .map(|m| m.url.as_str())
+        .collect();
+    if enabled != ordered_urls.iter().map(String::as_str).collect::<Vec<_>>() {
+        return Err(last_error.unwrap_or_else(|| {
+            "Mirrorlist was not written (authorization cancelled?)".to_string()
+        }));
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mirrorlist_sources() {
+        let file = "##\n## Arch Linux repository mirrorlist\n## Generated on 2026-01-01\n##\n\n\
+                    ## Germany\nServer = https://de.example.org/$repo/os/$arch\n\
+                    #Server = https://off.example.org/$repo/os/$arch\n\n\
+                    ## Sweden\nServer = https://se.example.org/$repo/os/$arch # 42ms\n\
+                    Server = https://de.example.org/$repo/os/$arch\n\
+                    Server = file:///mnt/$repo\n";
+        let list = parse_mirrorlist(file);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list[0].country.as_deref(), Some("Germany"));
+        assert!(list[0].enabled);
+        assert!(!list[1].enabled);
+        assert_eq!(list[2].country.as_deref(), Some("Sweden"));
+        assert_eq!(list[2].latency_ms, Some(42));
+
+        let rated = "# STARTED AT: 2026-01-01 10:00:00\n\
+                     # [1] SpeedTestResult { speed: 12.5 MB/s; elapsed: 1.2s; connection_time: 35ms }\n\
+                     Server = https://fast.example.org/repo/$arch/$repo\n";
+        let list = parse_mirrorlist(rated);
+        assert_eq!(list[0].speed_bps, Some(13_107_200));
+        assert_eq!(list[0].latency_ms, Some(35));
+
+        assert_eq!(
+            lastsync_url("https://de.example.org/archlinux/$repo/os/$arch").as_deref(),
+            Some("https://de.example.org/archlinux/lastsync")
+        );
+        assert_eq!(
+            mirrorlist_for("cachyos-core-v3").map(|m| m.0),
+            Some("cachyos-v3")
+        );
+        assert_eq!(
+            mirrorlist_for("Chaotic-AUR").map(|m| m.0),
+            Some("chaotic-aur")
+        );
+        assert!(mirrorlist_for("my-local-repo").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_probe_reports_sync_and_speed() {
+        http_client::simulate(
+            "up.mirror.invalid",
+            http_client::NetCondition::respond(200, "1750000000\n"),
+        );
+        http_client::simulate("down.mirror.invalid", http_client::NetCondition::offline());
+
+        let results = test_mirror_latency(
+            "arch".into(),
+            vec![
+                "https://up.mirror.invalid/arch/$repo/os/$arch".into(),
+                "https://down.mirror.invalid/$repo/os/$arch".into(),
+            ],
+        )
+        .await
+        .unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].last_sync, Some(1_750_000_000));
+        assert!(results[0].latency_ms.is_some());
+        assert!(results[0].speed_bps.is_some());
+        assert_eq!(results[1].latency_ms, None);
+        assert_eq!(results[1].speed_bps, None);
+
+        assert!(
+            test_mirror_latency("arch".into(), vec!["https://a.org/x\n[options]".into()])
+                .await
+                .is_err()
+        );
+    }
+}
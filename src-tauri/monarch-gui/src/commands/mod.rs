@@ -1,6 +1,30 @@
+pub mod backup;
+pub mod batch;
+pub mod changelog;
+pub mod collections;
+pub mod compare;
+pub mod deferred;
+pub mod deps;
+pub mod devel;
+pub mod distro_tools;
+pub mod duplicates;
+pub mod groups;
+pub mod hardware;
+pub mod import;
+pub mod kernel_manager;
+pub mod local_file;
+pub mod migration;
+pub mod mirrors;
 pub mod package;
+pub mod pins;
+pub mod queue;
+pub mod rescue;
 pub mod reviews;
+pub mod rollback;
 pub mod search;
+pub mod storage;
 pub mod system;
+pub mod trial;
 pub mod update;
+pub mod upstream;
 pub mod utils;
@@ -1,11 +1,11 @@
+use crate::error_classifier::{ClassifiedError, ErrorKind, RecoveryAction};
 use crate::{aur_api, helper_client, models, repo_manager::RepoManager};
 use serde::Serialize;
 use std::path::Path;
 use std::process::Stdio;
-use tauri::{AppHandle, Emitter, State};
+use tauri::{AppHandle, Emitter, Manager, State};
 use tauri_plugin_notification::NotificationExt;
 use tempfile;
-use tokio::io::{AsyncBufReadExt, BufReader as TokioBufReader};
 use tokio::sync::Mutex;
 
 /// Zone 4: Copy built .pkg.tar.zst to shared temp so root helper can read them.
@@ -58,12 +58,30 @@ pub struct PackageInstallStatus {
     pub actual_package_name: Option<String>,
 }
 
+#[derive(Serialize, Clone)]
+pub struct PackageFileList {
+    pub package: String,
+    pub files: Vec<String>,
+    pub from_local_db: bool, // false = from sync .files database (package not installed)
+}
+
+#[derive(Serialize, Clone)]
+pub struct FileOwner {
+    pub package: String,
+    pub version: String,
+    pub repo: String, // "local" for the installed owner
+    pub installed: bool,
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
 pub struct PendingUpdate {
     pub name: String,
     pub old_version: String,
     pub new_version: String,
     pub repo: String,
+    /// new_version is older than old_version; listed, but never applied automatically.
+    #[serde(default)]
+    pub downgrade: bool,
 }
 
 #[tauri::command]
@@ -74,6 +92,11 @@ pub async fn abort_installation(app: AppHandle) -> Result<(), String> {
         let _ = child.kill().await;
         let _ = app.emit("install-complete", "failed");
         Ok(())
+    } else if crate::pty_runner::interrupt_active() {
+        // Transparent pacman commands (e.g. orphan removal) run on a PTY; ^C lets pacman
+        // release its lock. The runner reports the cancellation when the process exits.
+        let _ = app.emit("install-output", "--- Cancelling operation... ---");
+        Ok(())
     } else {
         // SECURITY: Do NOT use killall as fallback - it could kill unrelated pacman processes
         // and potentially corrupt the package database. Instead, inform the user.
@@ -98,7 +121,16 @@ pub async fn install_package(
     source: models::PackageSource,
     password: Option<String>,
     _repo_name: Option<String>,
-) -> Result<(), String> {
+    build_mode: Option<crate::aur_build::AurBuildMode>,
+) -> Result<(), ClassifiedError> {
+    // Fail fast instead of on a download timeout; the frontend queues it (enqueue_install)
+    if crate::connectivity::is_offline() {
+        return Err(format!(
+            "OFFLINE: {} can't be downloaded while offline; queue it to install when the connection returns.",
+            name
+        )
+        .into());
+    }
     install_package_core(
         &app_handle,
         &*_state_repo,
@@ -106,6 +138,7 @@ pub async fn install_package(
         source,
         &password,
         _repo_name,
+        build_mode,
     )
     .await
 }
@@ -117,7 +150,8 @@ pub async fn install_package_core(
     source: models::PackageSource,
     password: &Option<String>,
     _repo_name: Option<String>,
-) -> Result<(), String> {
+    build_mode: Option<crate::aur_build::AurBuildMode>,
+) -> Result<(), ClassifiedError> {
     // VECTOR 5: INPUT SANITIZATION
     crate::utils::validate_package_name(name)?;
 
@@ -134,10 +168,17 @@ pub async fn install_package_core(
             let msg = "Manjaro Stability Guard: Installing pre-built binaries (Chaotic/CachyOS) is blocked on Manjaro to prevent system breakage. Please use the AUR (Native Build) version instead.".to_string();
             let _ = app.emit("install-output", &msg);
             let _ = app.emit("install-complete", "failed");
-            return Err(msg);
+            return Err(msg.into());
         }
     }
 
+    // Flagged proprietary packages need a recorded license acceptance (fresh installs only)
+    if let Err(msg) = crate::eula::require_acknowledged(app, name).await {
+        let _ = app.emit("install-output", &msg);
+        let _ = app.emit("install-complete", "failed");
+        return Err(msg.into());
+    }
+
     // Pre-flight check: Database Lock - try to unlock if stale
     if crate::repair::check_pacman_lock().await {
         let _ = app.emit(
@@ -159,7 +200,7 @@ pub async fn install_package_core(
                     &format!("Error: Database is locked by another process: {}", e),
                 );
                 let _ = app.emit("install-complete", "failed");
-                return Err(format!("Pacman database is locked: {}", e));
+                return Err(format!("Pacman database is locked: {}", e).into());
             }
         }
     }
@@ -176,24 +217,15 @@ pub async fn install_package_core(
     };
 
     // Use ALL enabled repos for the transaction so dependencies can be resolved (e.g. vlc-git from chaotic needs deps from core/extra/community).
-    // Always include system repos (core, extra, community, multilib) so ALPM can resolve dependencies even if UI state is stale.
-    let all_repos = repo_manager.get_all_repos().await;
-    let mut enabled_repos: Vec<String> = all_repos
-        .iter()
-        .filter(|r| r.enabled)
-        .map(|r| r.name.clone())
-        .collect();
-    for sys in ["core", "extra", "community", "multilib"] {
-        if !enabled_repos.contains(&sys.to_string()) {
-            enabled_repos.push(sys.to_string());
-        }
-    }
+    let enabled_repos = repo_manager.transaction_repos().await;
 
     // Acquire global lock
     let _guard = crate::utils::PRIVILEGED_LOCK.lock().await;
 
-    let mut saw_unknown_variant = false;
+    let mut legacy_helper = false;
     let mut saw_corrupt_db = false;
+    // Summary of the last helper run; None for Flatpak, which doesn't go through the helper
+    let mut helper_result: Option<helper_client::TransactionResult> = None;
     // Buffer last install-output lines to surface real ALPM errors (e.g. "not found in any enabled repository")
     let mut install_log: Vec<String> = Vec::new();
     const LOG_CAP: usize = 50;
@@ -205,26 +237,25 @@ pub async fn install_package_core(
                 "install-output",
                 "--- Starting Secure AUR Build-Install Pipeline ---",
             );
-            let built_paths = build_aur_package(app, name, password).await?;
+            let built_paths = build_aur_package(app, name, password, build_mode).await?;
             let install_paths = copy_paths_to_monarch_install(built_paths).await?;
 
             // ✅ NEW: Install built packages via ALPM transaction (paths in /tmp/monarch-install for root)
             let _ = app.emit("install-output", "Installing built AUR package(s)...");
 
-            let mut rx = helper_client::invoke_helper(
+            let result = helper_client::run_helper(
                 app,
                 helper_client::HelperCommand::AlpmInstallFiles {
                     paths: install_paths,
                 },
                 password.clone(),
+                |msg| {
+                    let _ = app.emit("install-output", &msg.message);
+                },
             )
             .await
             .map_err(|e| format!("Failed to invoke helper: {}", e))?;
-
-            // Stream progress events
-            while let Some(msg) = rx.recv().await {
-                let _ = app.emit("install-output", &msg.message);
-            }
+            helper_result = Some(result);
         }
         "flatpak" => {
             let _ = app.emit(
@@ -253,81 +284,88 @@ pub async fn install_package_core(
 
             // ✅ GHOST FIX: Pass selected repo so helper installs from THAT repo ONLY (not first match).
             // We prioritize source.id if source_type is "repo", otherwise fallback to Legacy _repo_name.
-            let target_repo = if source.source_type == "repo"
-                && !source.id.is_empty()
-                && source.id != "id_unknown"
-            {
-                Some(source.id.clone())
-            } else {
-                _repo_name.clone()
-            };
-
-            let mut rx = helper_client::invoke_helper(
-                app,
-                helper_client::HelperCommand::AlpmInstall {
-                    packages: vec![name.to_string()],
-                    sync_first,
-                    enabled_repos: enabled_repos.clone(),
-                    cpu_optimization: cpu_optimization.clone(),
-                    target_repo: target_repo.clone(),
-                },
-                password.clone(),
-            )
-            .await
-            .map_err(|e| format!("Failed to invoke helper: {}", e))?;
-
-            let mut saw_download_error = false;
-            while let Some(msg) = rx.recv().await {
-                let _ = app.emit("install-output", &msg.message);
-                install_log.push(msg.message.clone());
-                if install_log.len() > LOG_CAP {
-                    install_log.remove(0);
-                }
-                if (msg.message.contains("unknown variant") && msg.message.contains("AlpmInstall"))
-                    || (msg.message.contains("expected one of")
-                        && msg.message.contains("ExecuteBatch"))
-                    || msg.message.contains("outdated and does not support ALPM")
-                {
-                    saw_unknown_variant = true;
+            // A repo_name inside the source's family (cachyos-v3 for "cachyos") is more specific.
+            let target_repo = match &_repo_name {
+                Some(repo) if source.source_type == "repo" && repo.starts_with(&source.id) => {
+                    Some(repo.clone())
                 }
-                if msg.message.contains("Unrecognized archive format")
-                    || msg.message.contains("could not open database")
+                _ if source.source_type == "repo"
+                    && !source.id.is_empty()
+                    && source.id != "id_unknown" =>
                 {
-                    saw_corrupt_db = true;
+                    Some(source.id.clone())
                 }
-                // Detect 404/Download failures (stale DB)
-                if msg.message.to_lowercase().contains("failed retrieving")
-                    || msg.message.to_lowercase().contains("404")
-                    || msg.message.contains("unexpected error: package")
-                // generic alpm error?
-                {
-                    saw_download_error = true;
-                }
-            }
+                _ => _repo_name.clone(),
+            };
 
-            // ✅ AUTO-RETRY: If download failed, database is likely stale.
-            // Retry with sync_first=true. The helper will ENFORCE full system upgrade to be safe on Arch.
-            if saw_download_error && !saw_corrupt_db {
-                let _ = app.emit(
-                    "install-output",
-                    "⚠ Download failed (likely stale database).",
-                );
-                let _ = app.emit(
-                    "install-output",
-                    "System update required before installation can continue.",
-                );
-                let _ = app.emit(
-                    "install-output",
-                    "Select “Update & Install” to perform a full upgrade (-Syu) and retry safely.",
-                );
-                let _ = app.emit("install-complete", "failed_update_required");
-                return Err("SystemUpdateRequired: Package database is out of date.".to_string());
+            // Negotiated up front: a helper without AlpmInstall gets the ExecuteBatch shape
+            // instead of failing to parse and being retried.
+            legacy_helper = !helper_client::supports("AlpmInstall").await;
+            if !legacy_helper {
+                let mut saw_download_error = false;
+                let result = helper_client::run_helper(
+                    app,
+                    helper_client::HelperCommand::AlpmInstall {
+                        packages: vec![name.to_string()],
+                        sync_first,
+                        enabled_repos: enabled_repos.clone(),
+                        cpu_optimization: cpu_optimization.clone(),
+                        target_repo: target_repo.clone(),
+                        phase: Default::default(),
+                        dry_run: false,
+                    },
+                    password.clone(),
+                    |msg| {
+                        let _ = app.emit("install-output", &msg.message);
+                        install_log.push(msg.message.clone());
+                        if install_log.len() > LOG_CAP {
+                            install_log.remove(0);
+                        }
+                        if msg.message.contains("Unrecognized archive format")
+                            || msg.message.contains("could not open database")
+                        {
+                            saw_corrupt_db = true;
+                        }
+                        // Detect 404/Download failures (stale DB)
+                        if msg.message.to_lowercase().contains("failed retrieving")
+                            || msg.message.to_lowercase().contains("404")
+                            || msg.message.contains("unexpected error: package")
+                        // generic alpm error?
+                        {
+                            saw_download_error = true;
+                        }
+                    },
+                )
+                .await
+                .map_err(|e| format!("Failed to invoke helper: {}", e))?;
+                helper_result = Some(result);
+
+                // ✅ AUTO-RETRY: If download failed, database is likely stale.
+                // Retry with sync_first=true. The helper will ENFORCE full system upgrade to be safe on Arch.
+                if saw_download_error && !saw_corrupt_db {
+                    let _ = app.emit(
+                        "install-output",
+                        "⚠ Download failed (likely stale database).",
+                    );
+                    let _ = app.emit(
+                        "install-output",
+                        "System update required before installation can continue.",
+                    );
+                    let _ = app.emit(
+                        "install-output",
+                        "Select “Update & Install” to perform a full upgrade (-Syu) and retry safely.",
+                    );
+                    let _ = app.emit("install-complete", "failed_update_required");
+                    return Err(ClassifiedError::classify(
+                        "SystemUpdateRequired: Package database is out of date.",
+                    ));
+                }
             }
 
-            if saw_unknown_variant {
+            if legacy_helper {
                 let _ = app.emit(
                     "install-output",
-                    "Installed helper is outdated; syncing and installing with legacy path.",
+                    "Installed helper predates ALPM installs; syncing and installing with the batch command.",
                 );
                 let _ = app.emit(
                     "install-output",
@@ -340,6 +378,7 @@ pub async fn install_package_core(
                             refresh_db: true,
                             ..Default::default()
                         },
+                        dry_run: false,
                     },
                     password.clone(),
                 )
@@ -349,41 +388,46 @@ pub async fn install_package_core(
                     let _ = app.emit("install-output", &msg.message);
                 }
 
-                let mut rx_install = helper_client::invoke_helper(
+                let result = helper_client::run_helper(
                     app,
                     helper_client::HelperCommand::ExecuteBatch {
                         manifest: crate::models::TransactionManifest {
                             install_targets: vec![name.to_string()],
                             ..Default::default()
                         },
+                        dry_run: false,
                     },
                     password.clone(),
+                    |msg| {
+                        let _ = app.emit("install-output", &msg.message);
+                        install_log.push(msg.message.clone());
+                        if install_log.len() > LOG_CAP {
+                            install_log.remove(0);
+                        }
+                        if msg.message.contains("Unrecognized archive format")
+                            || msg.message.contains("could not open database")
+                        {
+                            saw_corrupt_db = true;
+                        }
+                    },
                 )
                 .await
                 .map_err(|e| format!("Failed to invoke helper (install): {}", e))?;
-                while let Some(msg) = rx_install.recv().await {
-                    let _ = app.emit("install-output", &msg.message);
-                    install_log.push(msg.message.clone());
-                    if install_log.len() > LOG_CAP {
-                        install_log.remove(0);
-                    }
-                    if msg.message.contains("Unrecognized archive format")
-                        || msg.message.contains("could not open database")
-                    {
-                        saw_corrupt_db = true;
-                    }
-                }
+                helper_result = Some(result);
             }
         }
     }
 
-    // ✅ POST-INSTALL VERIFICATION (ALPM read-only; no shell)
-    let mut verification = tokio::task::spawn_blocking({
-        let pkg_name = name.to_string();
-        move || crate::alpm_read::is_package_installed(&pkg_name)
-    })
-    .await
-    .map_err(|e| format!("Verification task failed: {}", e))?;
+    // ✅ POST-INSTALL: the helper's result says what happened; Flatpak has no helper run
+    let mut verification = match &helper_result {
+        Some(result) => result.success,
+        None => tokio::task::spawn_blocking({
+            let pkg_name = name.to_string();
+            move || crate::alpm_read::is_package_installed(&pkg_name)
+        })
+        .await
+        .map_err(|e| format!("Verification task failed: {}", e))?,
+    };
 
     // Only retry with sync when failure suggests missing/stale package (sync might help).
     // Do NOT retry with sync for "could not satisfy dependencies" — that's a dependency resolution failure; syncing again won't fix it and wastes several minutes (user already synced at startup).
@@ -397,8 +441,7 @@ pub async fn install_package_core(
             || m.contains("could not find")
     });
 
-    if !verification && source.source_type != "aur" && !saw_unknown_variant && is_dependency_failure
-    {
+    if !verification && source.source_type != "aur" && !legacy_helper && is_dependency_failure {
         let _ = app.emit(
             "install-output",
             "Dependency resolution failed (sync already done at startup; skipping duplicate sync).",
@@ -407,7 +450,7 @@ pub async fn install_package_core(
 
     if !verification
         && source.source_type != "aur"
-        && !saw_unknown_variant
+        && !legacy_helper
         && might_need_sync
         && !is_dependency_failure
     {
@@ -416,18 +459,13 @@ pub async fn install_package_core(
             "install-output",
             "Package not found; syncing databases and retrying...",
         );
-        let all_repos_retry = repo_manager.get_all_repos().await;
-        let enabled_repos_retry: Vec<String> = all_repos_retry
-            .iter()
-            .filter(|r| r.enabled)
-            .map(|r| r.name.clone())
-            .collect();
+        let enabled_repos_retry = repo_manager.transaction_repos().await;
         let target_repo_retry = if source.source_type == "aur" {
             None
         } else {
             _repo_name.clone()
         };
-        let mut rx_install = helper_client::invoke_helper(
+        let result = helper_client::run_helper(
             app,
             helper_client::HelperCommand::AlpmInstall {
                 packages: vec![name.to_string()],
@@ -435,78 +473,42 @@ pub async fn install_package_core(
                 enabled_repos: enabled_repos_retry,
                 cpu_optimization: cpu_optimization.clone(),
                 target_repo: target_repo_retry,
+                phase: Default::default(),
+                dry_run: false,
             },
             password.clone(),
+            |msg| {
+                let _ = app.emit("install-output", &msg.message);
+                install_log.push(msg.message.clone());
+                if install_log.len() > LOG_CAP {
+                    install_log.remove(0);
+                }
+                if msg.message.contains("Unrecognized archive format")
+                    || msg.message.contains("could not open database")
+                {
+                    saw_corrupt_db = true;
+                }
+            },
         )
         .await
         .map_err(|e| format!("Failed to invoke helper (install): {}", e))?;
-        while let Some(msg) = rx_install.recv().await {
-            let _ = app.emit("install-output", &msg.message);
-            install_log.push(msg.message.clone());
-            if install_log.len() > LOG_CAP {
-                install_log.remove(0);
-            }
-            if msg.message.contains("Unrecognized archive format")
-                || msg.message.contains("could not open database")
-            {
-                saw_corrupt_db = true;
-            }
-        }
-        verification = tokio::task::spawn_blocking({
-            let pkg_name = name.to_string();
-            move || crate::alpm_read::is_package_installed(&pkg_name)
-        })
-        .await
-        .map_err(|e| format!("Verification task failed: {}", e))?;
+        verification = result.success;
+        helper_result = Some(result);
     }
 
     if !verification {
         let _ = app.emit("install-complete", "failed");
-        if saw_corrupt_db {
-            return Err(format!(
-                "Sync databases are corrupt (Unrecognized archive format). Use Settings → System Management → Refresh Databases, then retry. If it still fails, run 'sudo pacman -Syy' once."
-            ));
-        }
-        // Surface the real ALPM error when package is not in any enabled repo
-        let not_in_repo = install_log
-            .iter()
-            .find(|m| m.contains("not found in any enabled repository"));
-        if let Some(msg) = not_in_repo {
-            return Err(format!(
-                "{} Try enabling Chaotic-AUR or another repo that provides this package, or install from AUR.",
-                msg.trim()
-            ));
-        }
-        if is_dependency_failure {
-            // Surface the exact ALPM line (e.g. "Transaction preparation failed: ..." or "unable to satisfy dependency 'X' required by Y")
-            let detail = install_log.iter().find(|m| {
-                m.contains("Transaction preparation failed")
-                    || m.contains("could not satisfy")
-                    || m.contains("unable to satisfy")
-                    || m.contains("breaks dependency")
-            });
-            let detail_str = detail
-                .map(|s| s.trim().trim_start_matches("Error: ").to_string())
-                .filter(|s| !s.is_empty());
-            return Err(if let Some(d) = detail_str {
-                format!(
-                    "Dependencies could not be satisfied for '{}': {}. Try enabling more repos (e.g. multilib, Chaotic-AUR) or install the missing dependency first.",
-                    name, d
-                )
-            } else {
-                format!(
-                    "Dependencies could not be satisfied for '{}'. A required dependency may be missing from your enabled repos, or there may be a version conflict. Check the log above or try: pacman -S {}",
-                    name, name
-                )
-            });
-        }
-        return Err(format!(
-            "Package '{}' could not be installed. Check the log above for details.",
-            name
+        return Err(install_failure(
+            name,
+            helper_result.as_ref().and_then(|r| r.error.as_deref()),
+            &install_log,
+            saw_corrupt_db,
+            is_dependency_failure,
         ));
     }
 
     let _ = app.emit("install-complete", "success");
+    crate::systemd::offer_after_install(app, name);
 
     // Process notification & telemetry
     // Only send system notification if enabled
@@ -533,28 +535,125 @@ pub async fn install_package_core(
     Ok(())
 }
 
+/// Why an install failed, for the frontend's recovery buttons: the helper's own error when
+/// it sent one, else what the streamed log shows.
+fn install_failure(
+    name: &str,
+    helper_error: Option<&str>,
+    install_log: &[String],
+    saw_corrupt_db: bool,
+    is_dependency_failure: bool,
+) -> ClassifiedError {
+    let log = install_log.join("\n");
+    if saw_corrupt_db {
+        return ClassifiedError::new(
+            ErrorKind::DatabaseCorrupt,
+            "Package Database Issue",
+            "Sync databases are corrupt (Unrecognized archive format). Use Settings → System Management → Refresh Databases, then retry. If it still fails, run 'sudo pacman -Syy' once.",
+            Some(RecoveryAction::ForceRefreshDb),
+            &log,
+        );
+    }
+    // Surface the real ALPM error when package is not in any enabled repo
+    if let Some(msg) = install_log
+        .iter()
+        .find(|m| m.contains("not found in any enabled repository"))
+    {
+        let mut err = ClassifiedError::classify(msg);
+        err.description = format!(
+            "{} Try enabling Chaotic-AUR or another repo that provides this package, or install from AUR.",
+            msg.trim()
+        );
+        return err;
+    }
+    if is_dependency_failure {
+        // Surface the exact ALPM line (e.g. "Transaction preparation failed: ..." or "unable to satisfy dependency 'X' required by Y")
+        let detail = install_log
+            .iter()
+            .find(|m| {
+                m.contains("Transaction preparation failed")
+                    || m.contains("could not satisfy")
+                    || m.contains("unable to satisfy")
+                    || m.contains("breaks dependency")
+            })
+            .map(|s| s.trim().trim_start_matches("Error: ").to_string())
+            .filter(|s| !s.is_empty());
+        let description = match &detail {
+            Some(d) => format!(
+                "Dependencies could not be satisfied for '{}': {}. Try enabling more repos (e.g. multilib, Chaotic-AUR) or install the missing dependency first.",
+                name, d
+            ),
+            None => format!(
+                "Dependencies could not be satisfied for '{}'. A required dependency may be missing from your enabled repos, or there may be a version conflict. Check the log above or try: pacman -S {}",
+                name, name
+            ),
+        };
+        let action = detail
+            .filter(|d| d.contains("lib32-"))
+            .map(|_| RecoveryAction::EnableMultilib);
+        return ClassifiedError::new(
+            ErrorKind::DependencyConflict,
+            "Dependencies Not Satisfied",
+            &description,
+            action,
+            &log,
+        );
+    }
+    let raw = helper_error.map(str::to_string).unwrap_or(log);
+    let mut err = ClassifiedError::classify(&raw);
+    if err.kind == ErrorKind::Unknown {
+        err.title = "Installation Failed".to_string();
+        err.description = format!(
+            "Package '{}' could not be installed. Check the log above for details.",
+            name
+        );
+    }
+    err
+}
+
+/// Packages uninstall_package refuses to remove; removing any of them breaks the system
+/// (or the store itself).
+pub(crate) const PROTECTED_PACKAGES: &[&str] = &[
+    "base",
+    "base-devel",
+    "linux",
+    "linux-lts",
+    "linux-zen",
+    "glibc",
+    "systemd",
+    "pacman",
+    "sudo",
+    "monarch-store",
+];
+
+/// A protected package the removal cascade for `names` would take along, if any. Packages
+/// that aren't installed are left for the helper to report.
+async fn protected_in_cascade(names: Vec<String>) -> Result<Option<String>, String> {
+    let plan = tokio::task::spawn_blocking(move || {
+        crate::commands::deps::plan_removal(
+            &crate::alpm_read::get_local_package_deps_native(),
+            &names,
+            true,
+        )
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?;
+    Ok(plan
+        .ok()
+        .and_then(|p| p.packages.into_iter().find(|p| p.protected))
+        .map(|p| p.name))
+}
+
 #[tauri::command]
 pub async fn uninstall_package(
     app: AppHandle,
     name: String,
     source: Option<models::PackageSource>,
     password: Option<String>,
+    config_action: Option<String>, // None/"keep" | "archive" | "quarantine"
 ) -> Result<(), String> {
     // SUICIDE PREVENTION: Protect critical system packages
-    let protected = [
-        "base",
-        "base-devel",
-        "linux",
-        "linux-lts",
-        "linux-zen",
-        "glibc",
-        "systemd",
-        "pacman",
-        "sudo",
-        "monarch-store",
-    ];
-
-    if protected.contains(&name.as_str()) {
+    if PROTECTED_PACKAGES.contains(&name.as_str()) {
         let _ = app.emit("install-complete", "failed");
         return Err(format!(
             "CRITICAL ERROR: '{}' is a protected system package. Uninstallation is forbidden.",
@@ -577,62 +676,219 @@ pub async fn uninstall_package(
         }
     }
 
+    // The cascade takes dependents along; never let it reach a protected package
+    if let Some(protected) = protected_in_cascade(vec![name.clone()]).await? {
+        let _ = app.emit("install-complete", "failed");
+        return Err(format!(
+            "CRITICAL ERROR: removing '{}' would also remove the protected system package '{}'. Uninstallation is forbidden.",
+            name, protected
+        ));
+    }
+
+    // Config preservation: archive user dirs before removal so restore_app_config can bring them back
+    let mut archived_dirs = Vec::new();
+    if matches!(
+        config_action.as_deref(),
+        Some("archive") | Some("quarantine")
+    ) {
+        let quarantine = config_action.as_deref() == Some("quarantine");
+        let (archive_app, archive_name) = (app.clone(), name.clone());
+        let archived = tokio::task::spawn_blocking(move || {
+            crate::app_data::archive_app_config(&archive_app, &archive_name, quarantine)
+        })
+        .await
+        .unwrap_or_else(|e| Err(format!("Task join error: {}", e)));
+        match archived {
+            Ok(Some((archive, dirs))) => {
+                let _ = app.emit(
+                    "install-output",
+                    format!(
+                        "Saved {} config folder(s) to {}",
+                        dirs.len(),
+                        archive.display()
+                    ),
+                );
+                archived_dirs = dirs;
+            }
+            Ok(None) => {
+                let _ = app.emit("install-output", "No user configuration found to save.");
+            }
+            Err(e) => {
+                let _ = app.emit("install-complete", "failed");
+                return Err(format!(
+                    "Could not archive configuration, uninstall aborted: {}",
+                    e
+                ));
+            }
+        }
+    }
+
     // ✅ Native ALPM Support
-    let mut rx = helper_client::invoke_helper(
+    let result = helper_client::run_helper(
         &app,
         helper_client::HelperCommand::AlpmUninstall {
             packages: vec![name.clone()],
             remove_deps: true, // -Rns behavior
+            dry_run: false,
         },
         password.clone(),
+        |msg| {
+            let _ = app.emit("install-output", &msg.message);
+        },
     )
     .await
     .map_err(|e| format!("Failed to invoke helper: {}", e))?;
 
-    // Stream progress events
-    while let Some(msg) = rx.recv().await {
-        let _ = app.emit("install-output", &msg.message);
+    if !result.success {
+        let _ = app.emit("install-complete", "failed");
+        let reason = result
+            .failed()
+            .find(|p| p.name == name)
+            .and_then(|p| p.reason.clone())
+            .or(result.error)
+            .unwrap_or_else(|| "Check for dependency conflicts.".to_string());
+        return Err(format!("Could not uninstall '{}': {}", name, reason));
     }
 
-    // ✅ POST-UNINSTALL VERIFICATION (ALPM read-only; no shell)
-    let verification = tokio::task::spawn_blocking({
-        let pkg_name = name.clone();
-        move || crate::alpm_read::is_package_installed(&pkg_name)
-    })
+    // Quarantine: the archive holds the only copy now; clear the originals
+    if config_action.as_deref() == Some("quarantine") {
+        let _ = tokio::task::spawn_blocking(move || {
+            for dir in &archived_dirs {
+                if let Err(e) = std::fs::remove_dir_all(dir) {
+                    log::warn!("Failed to remove {}: {}", dir.display(), e);
+                }
+            }
+        })
+        .await;
+    }
+
+    let _ = app.emit("install-complete", "success");
+
+    crate::utils::track_event_safe(
+        &app,
+        "uninstall_package",
+        Some(serde_json::json!({
+            "pkg": name,
+            "success": true,
+        })),
+    )
+    .await;
+
+    Ok(())
+}
+
+/// Remove several native packages in one AlpmUninstall transaction: one authorization
+/// prompt and one progress stream instead of one per package. Every name is validated and
+/// checked against the protected list (cascade included) before anything runs. libalpm
+/// commits all or nothing, so on failure nothing was removed. Returns what was removed.
+#[tauri::command]
+pub async fn uninstall_packages(
+    app: AppHandle,
+    names: Vec<String>,
+    password: Option<String>,
+) -> Result<Vec<String>, String> {
+    let mut names = names;
+    names.sort();
+    names.dedup();
+    if names.is_empty() {
+        return Err("No packages selected".to_string());
+    }
+    for name in &names {
+        crate::utils::validate_package_name(name)?;
+        if PROTECTED_PACKAGES.contains(&name.as_str()) {
+            let _ = app.emit("install-complete", "failed");
+            return Err(format!(
+                "CRITICAL ERROR: '{}' is a protected system package. Uninstallation is forbidden.",
+                name
+            ));
+        }
+    }
+
+    let _guard = crate::utils::PRIVILEGED_LOCK.lock().await;
+    let _ = app.emit(
+        "install-output",
+        format!(
+            "Preparing to uninstall {} packages: {}",
+            names.len(),
+            names.join(", ")
+        ),
+    );
+
+    if let Some(protected) = protected_in_cascade(names.clone()).await? {
+        let _ = app.emit("install-complete", "failed");
+        return Err(format!(
+            "CRITICAL ERROR: removing these packages would also remove the protected system package '{}'. Uninstallation is forbidden.",
+            protected
+        ));
+    }
+
+    let result = helper_client::run_helper(
+        &app,
+        helper_client::HelperCommand::AlpmUninstall {
+            packages: names.clone(),
+            remove_deps: true,
+            dry_run: false,
+        },
+        password,
+        |msg| {
+            let _ = app.emit("install-output", &msg.message);
+        },
+    )
     .await
-    .map_err(|e| format!("Verification task failed: {}", e))?;
+    .map_err(|e| format!("Failed to invoke helper: {}", e))?;
 
-    if verification {
+    if !result.success {
         let _ = app.emit("install-complete", "failed");
+        let failed: Vec<String> = result
+            .failed()
+            .filter_map(|p| Some(format!("{}: {}", p.name, p.reason.as_ref()?)))
+            .collect();
+        let reason = if failed.is_empty() {
+            result
+                .error
+                .unwrap_or_else(|| "Check for dependency conflicts.".to_string())
+        } else {
+            failed.join("; ")
+        };
         return Err(format!(
-            "Uninstallation reported success but package '{}' is still installed. Check for dependency conflicts.",
-            name
+            "Could not uninstall the selected packages: {}",
+            reason
         ));
     }
 
+    let removed: Vec<String> = result
+        .packages
+        .iter()
+        .filter(|p| p.outcome == helper_client::PackageOutcome::Removed)
+        .map(|p| p.name.clone())
+        .collect();
     let _ = app.emit("install-complete", "success");
 
     crate::utils::track_event_safe(
         &app,
-        "uninstall_package",
+        "uninstall_packages",
         Some(serde_json::json!({
-            "pkg": name,
+            "count": names.len(),
             "success": true,
         })),
     )
     .await;
 
-    Ok(())
+    Ok(removed)
 }
 
+/// Build `name` and its AUR dependencies. `build_mode` overrides the stored AUR build mode
+/// for this install only.
 pub async fn build_aur_package(
     app: &AppHandle,
     name: &str,
     password: &Option<String>,
+    build_mode: Option<crate::aur_build::AurBuildMode>,
 ) -> Result<Vec<String>, String> {
     // Audit dependencies
     audit_aur_builder_deps(app)
         .map_err(|e| format!("Build environment verification failed: {}", e))?;
+    let settings = crate::aur_build::resolve(app, build_mode)?;
 
     let mut resolved = Vec::new();
     let mut visited = std::collections::HashSet::new();
@@ -648,7 +904,9 @@ pub async fn build_aur_package(
 
     let mut built_paths = Vec::new();
     for pkg_name in resolved {
-        let path = build_aur_package_single(app, &pkg_name, password).await?;
+        // A clean chroot only has repo packages; hand it the AUR deps built so far
+        let path =
+            build_aur_package_single(app, &pkg_name, password, &settings, &built_paths).await?;
         built_paths.push(path);
     }
 
@@ -659,10 +917,24 @@ async fn build_aur_package_single(
     app: &AppHandle,
     name: &str,
     password: &Option<String>,
+    settings: &crate::aur_build::AurBuildSettings,
+    built_deps: &[String],
 ) -> Result<String, String> {
     let temp_dir = tempfile::tempdir().map_err(|e: std::io::Error| e.to_string())?;
     let pkg_path = temp_dir.path();
 
+    match crate::aur_build::check_build_space(settings, pkg_path) {
+        Ok(warnings) => {
+            for warning in warnings {
+                let _ = app.emit("install-output", warning);
+            }
+        }
+        Err(e) => {
+            let _ = app.emit("install-output", format!("Error: {}", e));
+            return Err(e);
+        }
+    }
+
     let _ = app.emit("install-output", format!("Cloning {} from AUR...", name));
     let clone_status = tokio::process::Command::new("git")
         .args([
@@ -671,6 +943,7 @@ async fn build_aur_package_single(
             "1",
             &format!("https://aur.archlinux.org/{}.git", name),
         ])
+        .envs(crate::network_settings::proxy_env())
         .current_dir(pkg_path)
         .status()
         .await
@@ -747,22 +1020,17 @@ async fn build_aur_package_single(
 
     let _ = app.emit(
         "install-output",
-        format!("Building {} from AUR (makepkg)...", name),
+        format!(
+            "Building {} from AUR ({})...",
+            name,
+            crate::aur_build::describe(settings)
+        ),
     );
 
-    let mut makepkg = tokio::process::Command::new("makepkg");
-    // When no password: close stdin so makepkg never blocks on read (e.g. prompts).
-    let stdin_mode = if password.is_some() {
-        Stdio::piped()
-    } else {
-        Stdio::null()
-    };
+    let mut makepkg = crate::aur_build::build_command(settings, &pkg_dir, pkg_path, built_deps)?;
+    // stdin stays open for license prompts; other questions get EOF (license_prompt)
     makepkg
-        .args(["-s", "-r", "--noconfirm", "--needed"]) // -r: remove make-deps after build (avoid orphan build libs)
-        .env("MAKEFLAGS", format!("-j{}", num_cpus::get()))
-        .env("PKGEXT", ".pkg.tar.zst")
-        .current_dir(&pkg_dir)
-        .stdin(stdin_mode)
+        .stdin(Stdio::piped())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped());
 
@@ -776,36 +1044,37 @@ async fn build_aur_package_single(
         makepkg.env("PACMAN", "pkexec pacman");
     }
 
+    // stdin carries only the user's own answers (BuildInput); sudo gets the password
+    // through SUDO_ASKPASS, never from a stream the build scripts can read
     let mut child = makepkg.spawn().map_err(|e| e.to_string())?;
-
-    if let Some(pwd) = password {
-        if let Some(mut stdin) = child.stdin.take() {
-            let _ =
-                tokio::io::AsyncWriteExt::write_all(&mut stdin, format!("{}\n", pwd).as_bytes())
-                    .await;
-        }
-    }
+    let input = crate::license_prompt::BuildInput::new(app.clone(), name, child.stdin.take());
 
     let missing_keys = std::sync::Arc::new(tokio::sync::Mutex::new(Vec::<String>::new()));
     let build_errors = std::sync::Arc::new(tokio::sync::Mutex::new(Vec::<String>::new()));
 
     if let Some(out) = child.stdout.take() {
-        let a = app.clone();
-        tokio::spawn(async move {
-            let reader = TokioBufReader::new(out);
-            let mut lines = reader.lines();
-            while let Ok(Some(line)) = lines.next_line().await {
-                let _ = a.emit("install-output", line);
-            }
-        });
+        tokio::spawn(crate::license_prompt::pump(
+            app.clone(),
+            out,
+            input.clone(),
+            "",
+        ));
     }
 
     let missing_keys_clone = missing_keys.clone();
     let build_errors_clone = build_errors.clone();
     if let Some(err) = child.stderr.take() {
         let a = app.clone();
-        let mut reader = TokioBufReader::new(err).lines();
-        while let Ok(Some(line)) = reader.next_line().await {
+        let mut reader = crate::license_prompt::PromptLines::new(err);
+        while let Ok(Some(output)) = reader.next().await {
+            let line = match output {
+                crate::license_prompt::Output::Line(line) => line,
+                crate::license_prompt::Output::Pending(text) => {
+                    input.on_pending(&text).await;
+                    continue;
+                }
+            };
+            input.record(&line);
             let _ = a.emit("install-output", format!("MAKEPKG: {}", line));
 
             // ✅ AUR Progress Parsing
@@ -879,6 +1148,12 @@ async fn build_aur_package_single(
     }
 
     let exit_status = child.wait().await.map_err(|e| e.to_string())?;
+    if !exit_status.success() && input.declined() {
+        return Err(format!(
+            "Build of {} stopped: its license was declined.",
+            name
+        ));
+    }
 
     // Check if build failed due to PGP keys
     if !exit_status.success() {
@@ -900,30 +1175,13 @@ async fn build_aur_package_single(
             for key_id in keys.iter() {
                 let _ = app.emit("install-output", format!("Importing key: {}...", key_id));
 
-                // Try multiple keyservers in order of reliability
-                let keyservers = ["keyserver.ubuntu.com", "keys.openpgp.org", "pgp.mit.edu"];
-
-                let mut key_imported = false;
-                for server in keyservers {
-                    let import_result = tokio::process::Command::new("gpg")
-                        .args(["--keyserver", server, "--recv-keys", key_id])
-                        .output()
-                        .await;
-
-                    if let Ok(output) = import_result {
-                        if output.status.success() {
-                            let _ = app.emit(
-                                "install-output",
-                                format!("✓ Key {} imported from {}", key_id, server),
-                            );
-                            key_imported = true;
-                            imported_any = true;
-                            break;
-                        }
-                    }
-                }
-
-                if !key_imported {
+                if let Some(server) = recv_pgp_key(key_id).await {
+                    let _ = app.emit(
+                        "install-output",
+                        format!("✓ Key {} imported from {}", key_id, server),
+                    );
+                    imported_any = true;
+                } else {
                     let _ = app.emit(
                         "install-output",
                         format!("⚠ Could not import key {} from any keyserver", key_id),
@@ -946,14 +1204,11 @@ async fn build_aur_package_single(
                     .status()
                     .await;
 
-                // Retry makepkg (stdin closed so it never blocks on read)
-                let mut retry_makepkg = tokio::process::Command::new("makepkg");
+                // Retry makepkg; it may ask about the license again
+                let mut retry_makepkg =
+                    crate::aur_build::build_command(settings, &pkg_dir, pkg_path, built_deps)?;
                 retry_makepkg
-                    .args(["-s", "-r", "--noconfirm", "--needed"]) // -r: remove make-deps after build
-                    .env("MAKEFLAGS", format!("-j{}", num_cpus::get()))
-                    .env("PKGEXT", ".pkg.tar.zst")
-                    .current_dir(&pkg_dir)
-                    .stdin(Stdio::null())
+                    .stdin(Stdio::piped())
                     .stdout(Stdio::piped())
                     .stderr(Stdio::piped());
 
@@ -965,31 +1220,38 @@ async fn build_aur_package_single(
                 }
 
                 let mut retry_child = retry_makepkg.spawn().map_err(|e| e.to_string())?;
+                let retry_input = crate::license_prompt::BuildInput::new(
+                    app.clone(),
+                    name,
+                    retry_child.stdin.take(),
+                );
 
                 // Stream retry output
                 if let Some(out) = retry_child.stdout.take() {
-                    let a = app.clone();
-                    tokio::spawn(async move {
-                        let reader = TokioBufReader::new(out);
-                        let mut lines = reader.lines();
-                        while let Ok(Some(line)) = lines.next_line().await {
-                            let _ = a.emit("install-output", line);
-                        }
-                    });
+                    tokio::spawn(crate::license_prompt::pump(
+                        app.clone(),
+                        out,
+                        retry_input.clone(),
+                        "",
+                    ));
                 }
 
                 if let Some(err) = retry_child.stderr.take() {
-                    let a = app.clone();
-                    tokio::spawn(async move {
-                        let reader = TokioBufReader::new(err);
-                        let mut lines = reader.lines();
-                        while let Ok(Some(line)) = lines.next_line().await {
-                            let _ = a.emit("install-output", format!("MAKEPKG: {}", line));
-                        }
-                    });
+                    tokio::spawn(crate::license_prompt::pump(
+                        app.clone(),
+                        err,
+                        retry_input.clone(),
+                        "MAKEPKG: ",
+                    ));
                 }
 
                 let retry_status = retry_child.wait().await.map_err(|e| e.to_string())?;
+                if !retry_status.success() && retry_input.declined() {
+                    return Err(format!(
+                        "Build of {} stopped: its license was declined.",
+                        name
+                    ));
+                }
 
                 if !retry_status.success() {
                     let errs = build_errors.lock().await;
@@ -1030,6 +1292,11 @@ async fn build_aur_package_single(
         }
     }
 
+    // Keep the PKGBUILD we built from so the next rebuild can show what changed
+    if let Ok(pkgbuild) = tokio::fs::read_to_string(pkg_dir.join("PKGBUILD")).await {
+        crate::pkgbuild_cache::record_built_pkgbuild(name, &pkgbuild);
+    }
+
     // Find the resulting package file
     let mut dir = tokio::fs::read_dir(&pkg_dir)
         .await
@@ -1151,35 +1418,117 @@ pub fn audit_aur_builder_deps(app: &AppHandle) -> Result<(), String> {
     Ok(())
 }
 
+/// Install what makepkg needs (the MissingBuildTools recovery action).
+#[tauri::command]
+pub async fn install_build_tools(app: AppHandle, password: Option<String>) -> Result<(), String> {
+    let _guard = crate::utils::PRIVILEGED_LOCK.lock().await;
+    let _ = app.emit("install-output", "--- Installing base-devel and git ---");
+    let mut rx = helper_client::invoke_helper(
+        &app,
+        helper_client::HelperCommand::AlpmInstall {
+            packages: vec!["base-devel".to_string(), "git".to_string()],
+            sync_first: false,
+            enabled_repos: vec!["core".to_string(), "extra".to_string()],
+            cpu_optimization: None,
+            target_repo: None,
+            phase: Default::default(),
+            dry_run: false,
+        },
+        password,
+    )
+    .await
+    .map_err(|e| format!("Failed to invoke helper: {}", e))?;
+    while let Some(msg) = rx.recv().await {
+        let _ = app.emit("install-output", &msg.message);
+    }
+    tokio::task::spawn_blocking(move || audit_aur_builder_deps(&app))
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Fetch a PGP key into the user's keyring from the first keyserver that has it.
+async fn recv_pgp_key(key_id: &str) -> Option<&'static str> {
+    // Try multiple keyservers in order of reliability
+    let keyservers = ["keyserver.ubuntu.com", "keys.openpgp.org", "pgp.mit.edu"];
+    for server in keyservers {
+        let import_result = tokio::process::Command::new("gpg")
+            .args(["--keyserver", server, "--recv-keys", key_id])
+            .output()
+            .await;
+        if matches!(import_result, Ok(ref output) if output.status.success()) {
+            return Some(server);
+        }
+    }
+    None
+}
+
+/// Import a PKGBUILD's source signing key into the user's GnuPG keyring, where makepkg
+/// looks (the ImportPgpKey recovery action). Pacman's keyring is `import_key_from_keyserver`.
+#[tauri::command]
+pub async fn import_source_pgp_key(key_id: String) -> Result<String, String> {
+    let key_id = key_id.trim();
+    if key_id.len() < 8 || !key_id.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("'{}' is not a PGP key ID", key_id));
+    }
+    match recv_pgp_key(key_id).await {
+        Some(server) => Ok(format!("Key {} imported from {}", key_id, server)),
+        None => Err(format!(
+            "Could not import key {} from any keyserver",
+            key_id
+        )),
+    }
+}
+
 #[tauri::command]
 pub async fn fetch_pkgbuild(pkg_name: String) -> Result<String, String> {
     let url = format!(
         "https://aur.archlinux.org/cgit/aur.git/plain/PKGBUILD?h={}",
         pkg_name
     );
-    let resp = reqwest::get(url).await.map_err(|e| e.to_string())?;
-    if resp.status().is_success() {
-        resp.text().await.map_err(|e| e.to_string())
+    let client = crate::http_client::client(std::time::Duration::from_secs(30));
+    let resp = crate::http_client::fetch(client.get(url)).await?;
+    if resp.is_success() {
+        Ok(resp.text())
     } else {
-        Err(format!("Failed to fetch PKGBUILD: {}", resp.status()))
+        Err(format!("Failed to fetch PKGBUILD: {}", resp.status))
     }
 }
 
+/// AUR comments for the details page, pinned ones (often install instructions) included.
+#[tauri::command]
+pub async fn get_aur_comments(
+    pkg_name: String,
+    page: Option<u32>,
+) -> Result<aur_api::AurCommentPage, String> {
+    crate::utils::validate_package_name(&pkg_name)?;
+    aur_api::get_aur_comments(&pkg_name, page.unwrap_or(1)).await
+}
+
 #[tauri::command]
 pub async fn get_installed_packages(
+    app: AppHandle,
     state: tauri::State<'_, crate::metadata::MetadataState>,
 ) -> Result<Vec<InstalledPackage>, String> {
     let native_pkgs = crate::alpm_read::get_installed_packages_native();
     let mut apps = Vec::new();
+    let icon_size = crate::icon_cache::bucket_size(None);
+    let mut uncached = Vec::new();
 
     if let Ok(loader) = state.inner().0.lock() {
         for pkg in native_pkgs {
             // Check if it's an app
-            let icon = loader.find_icon_heuristic(&pkg.name);
-            let has_icon = icon.is_some();
-            let has_id = loader.find_app_id(&pkg.name).is_some();
+            let app_id = loader.find_app_id(&pkg.name);
+            let cached =
+                crate::icon_cache::lookup(app_id.as_deref().unwrap_or(&pkg.name), icon_size)
+                    .map(|i| i.url);
+            let icon = cached
+                .clone()
+                .or_else(|| loader.find_icon_heuristic(&pkg.name));
+            if cached.is_none() && icon.is_some() {
+                uncached.push(pkg.name.clone());
+            }
 
-            if has_icon || has_id {
+            if icon.is_some() || app_id.is_some() {
                 apps.push(InstalledPackage {
                     name: pkg.name,
                     version: pkg.version,
@@ -1196,6 +1545,14 @@ pub async fn get_installed_packages(
         }
     }
 
+    // Cache the rest in the background; the next listing gets small asset URLs throughout
+    if !uncached.is_empty() {
+        tauri::async_runtime::spawn(async move {
+            let state = app.state::<crate::metadata::MetadataState>();
+            crate::icon_cache::get_many(state.inner(), &uncached, icon_size).await;
+        });
+    }
+
     Ok(apps)
 }
 
@@ -1257,6 +1614,7 @@ pub async fn check_for_updates(
                             old_version: parts[3].to_string(),
                             new_version: parts[5].to_string(),
                             repo: "official".to_string(),
+                            downgrade: false,
                         });
                     }
                 }
@@ -1277,7 +1635,12 @@ pub async fn check_for_updates(
         all_updates.extend(aur_updates);
     }
 
-    Ok(all_updates)
+    // 3. Pinned packages are listed separately (get_held_updates), not offered for upgrade;
+    //    excluded development packages likewise (get_devel_updates). Dropped repo binaries
+    //    switched to the AUR are offered its build even when it sorts older.
+    let all_updates = super::migration::apply_source_prefs(&_app, all_updates);
+    let all_updates = super::devel::partition_devel(&_app, all_updates);
+    Ok(super::pins::partition_pinned(&_app, all_updates))
 }
 
 async fn check_aur_updates() -> Result<Vec<PendingUpdate>, String> {
@@ -1309,13 +1672,14 @@ async fn check_aur_updates() -> Result<Vec<PendingUpdate>, String> {
     let mut pending = Vec::new();
     for pkg in aur_info {
         if let Some(installed_ver) = installed_aur.get(&pkg.name) {
-            // Basic version mismatch check
-            if pkg.version != *installed_ver {
+            let change = crate::versions::classify(installed_ver, &pkg.version);
+            if change != crate::versions::VersionChange::Same {
                 pending.push(PendingUpdate {
                     name: pkg.name,
                     old_version: installed_ver.clone(),
                     new_version: pkg.version,
                     repo: "aur".to_string(),
+                    downgrade: change == crate::versions::VersionChange::Downgrade,
                 });
             }
         }
@@ -1331,6 +1695,49 @@ pub async fn get_orphans() -> Result<Vec<String>, String> {
         .map_err(|e| format!("Task join error: {}", e))
 }
 
+/// Files installed by a package (like `pacman -Ql`, or `pacman -Fl` when not installed).
+#[tauri::command]
+pub async fn get_package_files(pkg_name: String) -> Result<PackageFileList, String> {
+    crate::utils::validate_package_name(&pkg_name)?;
+    let name = pkg_name.clone();
+    let (files, from_local_db) =
+        tokio::task::spawn_blocking(move || crate::alpm_read::get_package_files_native(&name))
+            .await
+            .map_err(|e| format!("Task join error: {}", e))?
+            .ok_or_else(|| {
+                format!(
+                    "No file list for '{}'. It is not installed and no .files database has it (run a files database sync).",
+                    pkg_name
+                )
+            })?;
+    Ok(PackageFileList {
+        package: pkg_name,
+        files,
+        from_local_db,
+    })
+}
+
+/// Which package provides a file (like `pacman -Qo` / `pacman -F`).
+#[tauri::command]
+pub async fn find_file_owner(path: String) -> Result<Vec<FileOwner>, String> {
+    if !path.starts_with('/') || path.contains("..") {
+        return Err(format!("Invalid path: '{}'. Use an absolute path.", path));
+    }
+    let owners =
+        tokio::task::spawn_blocking(move || crate::alpm_read::find_file_owner_native(&path))
+            .await
+            .map_err(|e| format!("Task join error: {}", e))?;
+    Ok(owners
+        .into_iter()
+        .map(|(repo, package, version)| FileOwner {
+            installed: repo == "local",
+            package,
+            version,
+            repo,
+        })
+        .collect())
+}
+
 #[tauri::command]
 pub async fn remove_orphans(app: AppHandle, orphans: Vec<String>) -> Result<(), String> {
     if orphans.is_empty() {
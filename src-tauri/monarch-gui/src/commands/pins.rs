@@ -0,0 +1,192 @@
+use crate::commands::package::PendingUpdate;
+use crate::helper_client;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+/// Written by the helper (SetPinnedPackages); world-readable.
+const PINS_DROPIN: &str = "/etc/pacman.d/monarch/pins.conf";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PinnedPackage {
+    pub name: String,
+    pub reason: Option<String>,
+    pub pinned_version: Option<String>, // Installed version at pin time
+    pub pinned_at: i64,
+}
+
+/// Updates from the last update check that were held back by a pin.
+static HELD_UPDATES: once_cell::sync::Lazy<Mutex<Vec<PendingUpdate>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(Vec::new()));
+
+fn pins_path(app: &AppHandle) -> PathBuf {
+    let mut path = app
+        .path()
+        .app_data_dir()
+        .unwrap_or_else(|_| PathBuf::from("."));
+    if !path.exists() {
+        let _ = std::fs::create_dir_all(&path);
+    }
+    path.push("pins.json");
+    path
+}
+
+fn load_pins(app: &AppHandle) -> Vec<PinnedPackage> {
+    std::fs::read_to_string(pins_path(app))
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+pub(crate) fn pinned_names(app: &AppHandle) -> Vec<String> {
+    load_pins(app).into_iter().map(|p| p.name).collect()
+}
+
+pub(crate) fn set_held_updates(held: Vec<PendingUpdate>) {
+    if let Ok(mut h) = HELD_UPDATES.lock() {
+        *h = held;
+    }
+}
+
+pub(crate) fn forget_held_updates(names: &[String]) {
+    if let Ok(mut h) = HELD_UPDATES.lock() {
+        h.retain(|u| !names.contains(&u.name));
+    }
+}
+
+/// Drop pinned packages from `updates`, remembering them for the updates view.
+pub(crate) fn partition_pinned(app: &AppHandle, updates: Vec<PendingUpdate>) -> Vec<PendingUpdate> {
+    let pinned = pinned_names(app);
+    let (held, upgradable): (Vec<_>, Vec<_>) =
+        updates.into_iter().partition(|u| pinned.contains(&u.name));
+    set_held_updates(held);
+    upgradable
+}
+
+fn dropin_packages() -> Vec<String> {
+    std::fs::read_to_string(PINS_DROPIN)
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .filter_map(|l| l.strip_prefix("IgnorePkg"))
+        .filter_map(|l| l.trim_start().strip_prefix('='))
+        .flat_map(|v| v.split_whitespace().map(String::from))
+        .collect()
+}
+
+/// Push the pin list to the pacman drop-in via the helper, then save pins.json.
+async fn apply_pins(
+    app: &AppHandle,
+    pins: Vec<PinnedPackage>,
+    password: Option<String>,
+) -> Result<Vec<PinnedPackage>, String> {
+    let _guard = crate::utils::PRIVILEGED_LOCK.lock().await;
+    let mut names: Vec<String> = pins.iter().map(|p| p.name.clone()).collect();
+    let mut rx = helper_client::invoke_helper(
+        app,
+        helper_client::HelperCommand::SetPinnedPackages {
+            packages: names.clone(),
+        },
+        password,
+    )
+    .await
+    .map_err(|e| format!("Failed to invoke helper: {}", e))?;
+    let mut last_error = None;
+    while let Some(msg) = rx.recv().await {
+        if msg.message.starts_with("Error") {
+            last_error = Some(msg.message);
+        }
+    }
+
+    let mut written = dropin_packages();
+    names.sort();
+    written.sort();
+    if written != names {
+        return Err(last_error.unwrap_or_else(|| {
+            "Pinned packages were not written (authorization cancelled?)".to_string()
+        }));
+    }
+
+    let content = serde_json::to_string_pretty(&pins).map_err(|e| e.to_string())?;
+    std::fs::write(pins_path(app), content).map_err(|e| e.to_string())?;
+    Ok(pins)
+}
+
+/// Put back pins from a settings backup. The helper is only asked (and a password only
+/// needed) when the drop-in lists other packages.
+pub(crate) async fn restore_pins(
+    app: &AppHandle,
+    pins: Vec<PinnedPackage>,
+    password: Option<String>,
+) -> Result<usize, String> {
+    for pin in &pins {
+        crate::utils::validate_package_name(&pin.name)?;
+    }
+    let mut names: Vec<String> = pins.iter().map(|p| p.name.clone()).collect();
+    let mut written = dropin_packages();
+    names.sort();
+    written.sort();
+    if names == written {
+        let content = serde_json::to_string_pretty(&pins).map_err(|e| e.to_string())?;
+        std::fs::write(pins_path(app), content).map_err(|e| e.to_string())?;
+        return Ok(pins.len());
+    }
+    Ok(apply_pins(app, pins, password).await?.len())
+}
+
+/// Hold a package at its current version: system upgrades skip it until unpinned.
+#[tauri::command]
+pub async fn pin_package(
+    app: AppHandle,
+    name: String,
+    reason: Option<String>,
+    password: Option<String>,
+) -> Result<Vec<PinnedPackage>, String> {
+    crate::utils::validate_package_name(&name)?;
+    let mut pins = load_pins(&app);
+    if pins.iter().any(|p| p.name == name) {
+        return Ok(pins);
+    }
+    let pinned_version = tokio::task::spawn_blocking({
+        let name = name.clone();
+        move || crate::alpm_read::get_installed_version(&name)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?;
+
+    pins.push(PinnedPackage {
+        name,
+        reason: reason.filter(|r| !r.trim().is_empty()),
+        pinned_version,
+        pinned_at: chrono::Utc::now().timestamp(),
+    });
+    apply_pins(&app, pins, password).await
+}
+
+#[tauri::command]
+pub async fn unpin_package(
+    app: AppHandle,
+    name: String,
+    password: Option<String>,
+) -> Result<Vec<PinnedPackage>, String> {
+    crate::utils::validate_package_name(&name)?;
+    let mut pins = load_pins(&app);
+    let before = pins.len();
+    pins.retain(|p| p.name != name);
+    if pins.len() == before {
+        return Ok(pins);
+    }
+    apply_pins(&app, pins, password).await
+}
+
+#[tauri::command]
+pub async fn get_pinned_packages(app: AppHandle) -> Result<Vec<PinnedPackage>, String> {
+    Ok(load_pins(&app))
+}
+
+/// Available updates held back by pins (from the last update check), for the updates view.
+#[tauri::command]
+pub async fn get_held_updates() -> Result<Vec<PendingUpdate>, String> {
+    Ok(HELD_UPDATES.lock().map(|h| h.clone()).unwrap_or_default())
+}
@@ -0,0 +1,279 @@
+use crate::aur_build::AurBuildMode;
+use crate::models::PackageSource;
+use crate::repo_manager::RepoManager;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum QueueItemStatus {
+    Queued,
+    Running,
+    Done,
+    Failed,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct QueueItem {
+    pub id: u64,
+    pub name: String,
+    pub source: PackageSource,
+    pub repo_name: Option<String>,
+    /// Per-install AUR build mode; None uses the stored setting.
+    pub build_mode: Option<AurBuildMode>,
+    pub status: QueueItemStatus,
+    pub error: Option<String>,
+    pub enqueued_at: i64,
+    pub finished_at: Option<i64>,
+    #[serde(skip)]
+    password: Option<String>, // sudo fallback only; never sent to the frontend
+}
+
+#[derive(Default)]
+struct InstallQueue {
+    items: Vec<QueueItem>,
+    next_id: u64,
+    running: bool,
+}
+
+impl InstallQueue {
+    fn enqueue(
+        &mut self,
+        name: String,
+        source: PackageSource,
+        repo_name: Option<String>,
+        build_mode: Option<AurBuildMode>,
+        password: Option<String>,
+    ) -> Result<u64, String> {
+        // Same package twice in one run would just reinstall it
+        if self
+            .items
+            .iter()
+            .any(|i| i.name == name && i.status == QueueItemStatus::Queued)
+        {
+            return Err(format!("{} is already queued", name));
+        }
+        self.next_id += 1;
+        self.items.push(QueueItem {
+            id: self.next_id,
+            name,
+            source,
+            repo_name,
+            build_mode,
+            status: QueueItemStatus::Queued,
+            error: None,
+            enqueued_at: chrono::Utc::now().timestamp(),
+            finished_at: None,
+            password,
+        });
+        Ok(self.next_id)
+    }
+
+    /// Only waiting items can be removed; a running install is stopped with abort_installation.
+    fn dequeue(&mut self, id: u64) -> Result<(), String> {
+        let pos = self
+            .items
+            .iter()
+            .position(|i| i.id == id)
+            .ok_or_else(|| format!("No queue item {}", id))?;
+        if self.items[pos].status != QueueItemStatus::Queued {
+            return Err(format!(
+                "{} is not waiting in the queue",
+                self.items[pos].name
+            ));
+        }
+        self.items.remove(pos);
+        Ok(())
+    }
+
+    /// Put waiting items in the order of `ids`. Unlisted waiting items keep their relative
+    /// order after the listed ones; running and finished items don't move.
+    fn reorder(&mut self, ids: &[u64]) {
+        let slots: Vec<usize> = (0..self.items.len())
+            .filter(|&k| self.items[k].status == QueueItemStatus::Queued)
+            .collect();
+        let mut waiting: Vec<QueueItem> = slots.iter().map(|&k| self.items[k].clone()).collect();
+        waiting.sort_by_key(|item| {
+            ids.iter()
+                .position(|id| *id == item.id)
+                .unwrap_or(ids.len())
+        });
+        for (slot, item) in slots.into_iter().zip(waiting) {
+            self.items[slot] = item;
+        }
+    }
+
+    /// Mark the first waiting item as running and hand it out.
+    fn start_next(&mut self) -> Option<QueueItem> {
+        let item = self
+            .items
+            .iter_mut()
+            .find(|i| i.status == QueueItemStatus::Queued)?;
+        item.status = QueueItemStatus::Running;
+        Some(item.clone())
+    }
+
+    fn finish(&mut self, id: u64, result: Result<(), String>) {
+        if let Some(item) = self.items.iter_mut().find(|i| i.id == id) {
+            item.status = if result.is_ok() {
+                QueueItemStatus::Done
+            } else {
+                QueueItemStatus::Failed
+            };
+            item.error = result.err();
+            item.finished_at = Some(chrono::Utc::now().timestamp());
+            item.password = None;
+        }
+    }
+}
+
+static QUEUE: Lazy<Mutex<InstallQueue>> = Lazy::new(|| Mutex::new(InstallQueue::default()));
+
+fn snapshot() -> Vec<QueueItem> {
+    QUEUE.lock().map(|q| q.items.clone()).unwrap_or_default()
+}
+
+fn emit_queue(app: &AppHandle) -> Vec<QueueItem> {
+    let items = snapshot();
+    let _ = app.emit("queue-updated", &items);
+    items
+}
+
+/// Runs queued installs one at a time until the queue is empty. Each install goes through
+/// install_package_core, which takes PRIVILEGED_LOCK itself, so other privileged work
+/// (updates, removals) interleaves between items instead of racing them. While offline the
+/// waiting items stay queued until the connection returns.
+async fn run_queue(app: AppHandle) {
+    loop {
+        if crate::connectivity::is_offline() {
+            log::info!("Install queue waiting for the connection to return");
+            crate::connectivity::wait_until_online().await;
+        }
+        let next = match QUEUE.lock() {
+            Ok(mut q) => match q.start_next() {
+                Some(item) => item,
+                None => {
+                    q.running = false;
+                    break;
+                }
+            },
+            Err(_) => break,
+        };
+        emit_queue(&app);
+
+        let repo_manager = app.state::<RepoManager>();
+        let result = super::package::install_package_core(
+            &app,
+            &repo_manager,
+            &next.name,
+            next.source.clone(),
+            &next.password,
+            next.repo_name.clone(),
+            next.build_mode,
+        )
+        .await;
+        if let Err(e) = &result {
+            log::warn!("Queued install of {} failed: {}", next.name, e);
+        }
+        if let Ok(mut q) = QUEUE.lock() {
+            q.finish(next.id, result.map_err(|e| e.to_string()));
+        }
+        emit_queue(&app);
+    }
+    emit_queue(&app);
+}
+
+/// Add a package to the install queue and start the runner if it is idle. Offline, this is
+/// how installs are made: they run once the connection returns.
+#[tauri::command]
+pub async fn enqueue_install(
+    app: AppHandle,
+    name: String,
+    source: PackageSource,
+    repo_name: Option<String>,
+    build_mode: Option<AurBuildMode>,
+    password: Option<String>,
+) -> Result<Vec<QueueItem>, String> {
+    crate::utils::validate_package_name(&name)?;
+    let start_runner = {
+        let mut q = QUEUE.lock().map_err(|e| e.to_string())?;
+        q.enqueue(name, source, repo_name, build_mode, password)?;
+        !std::mem::replace(&mut q.running, true)
+    };
+    if start_runner {
+        tauri::async_runtime::spawn(run_queue(app.clone()));
+    }
+    Ok(emit_queue(&app))
+}
+
+#[tauri::command]
+pub async fn dequeue_install(app: AppHandle, id: u64) -> Result<Vec<QueueItem>, String> {
+    QUEUE.lock().map_err(|e| e.to_string())?.dequeue(id)?;
+    Ok(emit_queue(&app))
+}
+
+/// Reorder waiting items; `ids` lists queue item ids in the desired order.
+#[tauri::command]
+pub async fn reorder_install_queue(
+    app: AppHandle,
+    ids: Vec<u64>,
+) -> Result<Vec<QueueItem>, String> {
+    QUEUE.lock().map_err(|e| e.to_string())?.reorder(&ids);
+    Ok(emit_queue(&app))
+}
+
+#[tauri::command]
+pub async fn get_install_queue() -> Result<Vec<QueueItem>, String> {
+    Ok(snapshot())
+}
+
+/// Drop finished (done/failed) items from the list.
+#[tauri::command]
+pub async fn clear_finished_queue_items(app: AppHandle) -> Result<Vec<QueueItem>, String> {
+    QUEUE
+        .lock()
+        .map_err(|e| e.to_string())?
+        .items
+        .retain(|i| matches!(i.status, QueueItemStatus::Queued | QueueItemStatus::Running));
+    Ok(emit_queue(&app))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue_of(names: &[&str]) -> InstallQueue {
+        let mut q = InstallQueue::default();
+        for name in names {
+            q.enqueue(name.to_string(), PackageSource::default(), None, None, None)
+                .unwrap();
+        }
+        q
+    }
+
+    fn names(q: &InstallQueue) -> Vec<&str> {
+        q.items.iter().map(|i| i.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_queue_order_and_reorder() {
+        let mut q = queue_of(&["a", "b", "c", "d"]);
+        assert!(q
+            .enqueue("b".into(), PackageSource::default(), None, None, None)
+            .is_err());
+
+        let first = q.start_next().unwrap();
+        assert_eq!(first.name, "a");
+        // Running item stays put; waiting items follow the requested order
+        q.reorder(&[4, 2]);
+        assert_eq!(names(&q), vec!["a", "d", "b", "c"]);
+        assert!(q.dequeue(1).is_err());
+
+        q.finish(first.id, Err("boom".into()));
+        assert_eq!(q.items[0].status, QueueItemStatus::Failed);
+        q.dequeue(2).unwrap();
+        assert_eq!(q.start_next().unwrap().name, "d");
+    }
+}
@@ -0,0 +1,150 @@
+//! Rescue toolkit: guided repairs for a system whose last update broke boot-adjacent
+//! components. Every operation is a separate helper run (HelperCommand::Rescue) that the
+//! user confirms after reading its risk description.
+
+use crate::helper_client::{self, RescueAction};
+use serde::Serialize;
+use std::path::Path;
+use tauri::{AppHandle, Emitter};
+
+/// Written by the helper (see monarch-helper rescue.rs).
+const LOCAL_DB_BACKUP: &str = "/var/lib/pacman/local.monarch-bak";
+const PACMAN_CONF_BACKUP: &str = "/etc/pacman.conf.monarch-bak";
+const LEGACY_CONF_BACKUP_PREFIX: &str = "pacman.conf.bak.parallel.";
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RescueRisk {
+    Low,
+    Medium,
+    High,
+}
+
+/// Typed response for get_rescue_operations.
+#[derive(Debug, Serialize, Clone)]
+pub struct RescueOperation {
+    pub action: RescueAction,
+    pub title: &'static str,
+    pub description: &'static str,
+    pub risk: RescueRisk,
+    /// What can go wrong, shown before the user confirms.
+    pub risk_description: &'static str,
+    pub available: bool,
+    /// Why it is unavailable, or when the backup it uses was taken.
+    pub note: Option<String>,
+}
+
+fn modified_secs(path: &Path) -> Option<u64> {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+}
+
+fn backup_note(path: &Path) -> Option<String> {
+    let secs = modified_secs(path)?;
+    let taken = chrono::DateTime::from_timestamp(secs as i64, 0)?;
+    Some(format!(
+        "Backup from {}",
+        taken.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")
+    ))
+}
+
+fn has_conf_backup() -> bool {
+    Path::new(PACMAN_CONF_BACKUP).exists()
+        || std::fs::read_dir("/etc").is_ok_and(|entries| {
+            entries.filter_map(|e| e.ok()).any(|e| {
+                e.file_name()
+                    .to_string_lossy()
+                    .starts_with(LEGACY_CONF_BACKUP_PREFIX)
+            })
+        })
+}
+
+fn operations() -> Vec<RescueOperation> {
+    let has_generator =
+        Path::new("/usr/bin/mkinitcpio").exists() || Path::new("/usr/bin/dracut").exists();
+    let local_db_ok = Path::new(LOCAL_DB_BACKUP).join("ALPM_DB_VERSION").exists();
+    let conf_ok = has_conf_backup();
+
+    vec![
+        RescueOperation {
+            action: RescueAction::RegenerateInitramfs,
+            title: "Regenerate Initramfs",
+            description: "Rebuild the boot images for every installed kernel.",
+            risk: RescueRisk::Low,
+            risk_description: "Replaces the current initramfs images. If the generator config itself is broken, the new images can fail to boot as well; keep a fallback kernel or live USB at hand.",
+            available: has_generator,
+            note: (!has_generator).then(|| "Neither mkinitcpio nor dracut is installed".to_string()),
+        },
+        RescueOperation {
+            action: RescueAction::ReinstallBootPackages,
+            title: "Reinstall Kernel & Bootloader",
+            description: "Reinstall kernels, firmware, microcode and bootloader packages at their current versions.",
+            risk: RescueRisk::Medium,
+            risk_description: "Rewrites kernel images, modules and bootloader files. Packages with a newer version in the repos are skipped to avoid a partial upgrade. The bootloader itself is not reinstalled to the EFI partition; only the GRUB menu is regenerated.",
+            available: true,
+            note: None,
+        },
+        RescueOperation {
+            action: RescueAction::RestoreLocalDb,
+            title: "Restore Package Database",
+            description: "Put back the record of installed packages from before MonArch's last transaction.",
+            risk: RescueRisk::High,
+            risk_description: "Pacman will believe the old package versions are installed even though newer files may be on disk. Run a full system update afterwards so files and database agree again. The replaced database is kept as local.monarch-broken; two older backups remain as local.monarch-bak.1 and .2.",
+            available: local_db_ok,
+            note: if local_db_ok {
+                backup_note(Path::new(LOCAL_DB_BACKUP))
+            } else {
+                Some("No backup yet; MonArch takes one before each transaction".to_string())
+            },
+        },
+        RescueOperation {
+            action: RescueAction::RestorePacmanConf,
+            title: "Restore pacman.conf",
+            description: "Put back the pacman.conf MonArch saved before it last changed the file.",
+            risk: RescueRisk::Medium,
+            risk_description: "Repositories or options added since that backup are lost. The current file is kept as pacman.conf.monarch-broken.",
+            available: conf_ok,
+            note: if conf_ok {
+                backup_note(Path::new(PACMAN_CONF_BACKUP))
+            } else {
+                Some("MonArch has not changed pacman.conf on this system".to_string())
+            },
+        },
+    ]
+}
+
+#[tauri::command]
+pub async fn get_rescue_operations() -> Result<Vec<RescueOperation>, String> {
+    tokio::task::spawn_blocking(operations)
+        .await
+        .map_err(|e| format!("Task join error: {}", e))
+}
+
+/// Run one rescue operation; output goes to `repair-log` like the other repair tools.
+#[tauri::command]
+pub async fn run_rescue_operation(
+    app: AppHandle,
+    action: RescueAction,
+    password: Option<String>,
+) -> Result<(), String> {
+    let _guard = crate::utils::PRIVILEGED_LOCK.lock().await;
+    let result = helper_client::run_helper(
+        &app,
+        helper_client::HelperCommand::Rescue { action },
+        password,
+        |msg| {
+            let _ = app.emit("repair-log", &msg.message);
+        },
+    )
+    .await
+    .map_err(|e| format!("Failed to invoke helper: {}", e))?;
+    if !result.success {
+        return Err(result
+            .error
+            .unwrap_or_else(|| "Rescue operation failed".to_string()));
+    }
+    Ok(())
+}
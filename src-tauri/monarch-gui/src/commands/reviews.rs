@@ -1,17 +1,109 @@
+//! Local reviews: the user's own (reviews.json) and imported review packs
+//! (review_packs.json). A pack is a set of reviews signed with GPG so communities can pass
+//! curated reviews around (e.g. a distro shipping a starter pack); imports are only accepted
+//! with a good signature from a key in the user's keyring.
+//!
+//! Everything lives in the user's XDG data dir, so each login on a shared machine has its
+//! own store. Own reviews are tied to a reviewer identity generated on first use
+//! (review_identity.json): only reviews carrying this user's author id can be edited,
+//! deleted or exported.
+
 use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
 use std::fs;
 use std::path::PathBuf;
 use tauri::AppHandle;
 use tauri::Manager;
 
+const PACK_FORMAT: u32 = 1;
+/// Longest summary/description kept from an imported pack.
+const MAX_PACK_TEXT: usize = 4000;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct LocalReview {
+    /// Stable id for edits and deletes; empty on reviews from older packs.
+    #[serde(default)]
+    pub id: String,
     pub app_id: String,
     pub rating: u32,
     pub summary: String,
     pub description: String,
     pub user_display: String,
     pub date_created: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date_edited: Option<u64>,
+    /// Author id of the reviewer identity that wrote it; None on reviews saved before
+    /// identities existed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// Set on reviews that came from an imported pack; None for the user's own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pack: Option<PackOrigin>,
+    /// Also published to ODRS; edits and deletes are sent there too.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub odrs: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PackOrigin {
+    pub name: String,
+    /// Fingerprint of the key that signed the pack.
+    pub signer: String,
+    /// User ID of that key, as gpg reports it.
+    pub signer_uid: String,
+}
+
+/// Kept in review_identity.json (mode 0600). The key never leaves the file; reviews carry
+/// the author id derived from it.
+#[derive(Debug, Serialize, Deserialize)]
+struct ReviewIdentity {
+    key: String,
+    created: u64,
+}
+
+/// What gets signed: `payload` in [`SignedPack`] is this, serialized.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct PackPayload {
+    format: u32,
+    name: String,
+    created: u64,
+    reviews: Vec<LocalReview>,
+}
+
+/// A review pack file. The signature covers the exact bytes of `payload`.
+#[derive(Debug, Serialize, Deserialize)]
+struct SignedPack {
+    payload: String,
+    /// ASCII-armored detached GPG signature.
+    signature: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ImportedPack {
+    pub origin: PackOrigin,
+    pub created: u64,
+    pub imported: u64,
+    pub reviews: Vec<LocalReview>,
+}
+
+/// Typed response for list_review_packs / import_review_pack (reviews left out).
+#[derive(Debug, Serialize, Clone)]
+pub struct ReviewPackInfo {
+    pub origin: PackOrigin,
+    pub created: u64,
+    pub imported: u64,
+    pub review_count: usize,
+}
+
+impl From<&ImportedPack> for ReviewPackInfo {
+    fn from(p: &ImportedPack) -> Self {
+        ReviewPackInfo {
+            origin: p.origin.clone(),
+            created: p.created,
+            imported: p.imported,
+            review_count: p.reviews.len(),
+        }
+    }
 }
 
 fn get_reviews_path(app: &AppHandle) -> PathBuf {
@@ -26,6 +118,118 @@ fn get_reviews_path(app: &AppHandle) -> PathBuf {
     path
 }
 
+fn random_hex() -> Result<String, String> {
+    let mut bytes = [0u8; 16];
+    fs::File::open("/dev/urandom")
+        .and_then(|mut f| std::io::Read::read_exact(&mut f, &mut bytes))
+        .map_err(|e| format!("Could not generate an id: {}", e))?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+fn author_id(key: &str) -> String {
+    let digest = Sha1::digest(format!("monarch-reviewer[{}]", key).as_bytes());
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// This user's author id, generating the identity on first use.
+fn load_author(app: &AppHandle) -> Result<String, String> {
+    identity_key(app).map(|key| author_id(&key))
+}
+
+/// The reviewer key, generating the identity on first use. Also seeds the ODRS user hash.
+pub(crate) fn identity_key(app: &AppHandle) -> Result<String, String> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let path = get_reviews_path(app).with_file_name("review_identity.json");
+    let read = || {
+        fs::read_to_string(&path)
+            .ok()
+            .and_then(|c| serde_json::from_str::<ReviewIdentity>(&c).ok())
+            .map(|identity| identity.key)
+    };
+    if let Some(key) = read() {
+        return Ok(key);
+    }
+    if path.exists() {
+        // Replacing it would disown every review written so far
+        return Err(format!(
+            "The reviewer identity in {} is damaged",
+            path.display()
+        ));
+    }
+    let identity = ReviewIdentity {
+        key: random_hex()?,
+        created: now_secs(),
+    };
+    let content = serde_json::to_string_pretty(&identity).map_err(|e| e.to_string())?;
+    // Written aside and linked into place, so a second window racing for the first review
+    // either wins or reads the finished file
+    let tmp = path.with_file_name(format!(".review_identity.{}", random_hex()?));
+    let saved = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(&tmp)
+        .and_then(|mut f| f.write_all(content.as_bytes()))
+        .and_then(|_| fs::hard_link(&tmp, &path));
+    let _ = fs::remove_file(&tmp);
+    match saved {
+        Ok(()) => Ok(identity.key),
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => read()
+            .ok_or_else(|| format!("Could not read the reviewer identity in {}", path.display())),
+        Err(e) => Err(format!("Could not save the reviewer identity: {}", e)),
+    }
+}
+
+/// The stored rating (0-100, as ODRS keeps it) for 1-5 stars.
+fn stored_rating(stars: u32) -> Result<u32, String> {
+    if (1..=5).contains(&stars) {
+        Ok(stars * 20)
+    } else {
+        Err("Rating must be between 1 and 5 stars".to_string())
+    }
+}
+
+/// Give reviews saved before identities existed an id and `author`: the file has always
+/// lived in this user's data dir, so they are theirs. Returns whether anything changed.
+fn adopt_legacy_reviews(
+    reviews: &mut [LocalReview],
+    author: &str,
+    mut new_id: impl FnMut() -> Result<String, String>,
+) -> Result<bool, String> {
+    let mut changed = false;
+    for review in reviews.iter_mut().filter(|r| r.author.is_none()) {
+        review.author = Some(author.to_string());
+        if review.id.is_empty() {
+            review.id = new_id()?;
+        }
+        changed = true;
+    }
+    Ok(changed)
+}
+
+fn save_reviews(app: &AppHandle, reviews: &[LocalReview]) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(reviews).map_err(|e| e.to_string())?;
+    fs::write(get_reviews_path(app), content).map_err(|e| e.to_string())
+}
+
+/// Everything in reviews.json (legacy entries adopted) and this user's author id.
+fn load_review_store(app: &AppHandle) -> Result<(Vec<LocalReview>, String), String> {
+    let author = load_author(app)?;
+    let path = get_reviews_path(app);
+    if !path.exists() {
+        return Ok((vec![], author));
+    }
+    let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let mut reviews: Vec<LocalReview> = serde_json::from_str(&content).unwrap_or_default();
+    if adopt_legacy_reviews(&mut reviews, &author, random_hex)? {
+        save_reviews(app, &reviews)?;
+    }
+    Ok((reviews, author))
+}
+
+/// Save a review of the user's own. `rating` is 1-5 stars.
 #[tauri::command]
 pub async fn submit_review(
     app: AppHandle,
@@ -34,43 +238,432 @@ pub async fn submit_review(
     summary: String,
     description: String,
     user_display: String,
-) -> Result<(), String> {
-    let path = get_reviews_path(&app);
-    let mut reviews: Vec<LocalReview> = if path.exists() {
-        let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
-        serde_json::from_str(&content).unwrap_or_default()
-    } else {
-        Vec::new()
-    };
+) -> Result<LocalReview, String> {
+    let rating = stored_rating(rating)?;
+    let (mut reviews, author) = load_review_store(&app)?;
 
     let new_review = LocalReview {
+        id: random_hex()?,
         app_id,
         rating,
         summary,
         description,
         user_display,
-        date_created: std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs(),
+        date_created: now_secs(),
+        date_edited: None,
+        author: Some(author),
+        pack: None,
+        odrs: false,
     };
 
-    reviews.push(new_review);
-    let content = serde_json::to_string_pretty(&reviews).map_err(|e| e.to_string())?;
-    fs::write(path, content).map_err(|e| e.to_string())?;
+    reviews.push(new_review.clone());
+    save_reviews(&app, &reviews)?;
 
-    Ok(())
+    Ok(new_review)
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+fn get_packs_path(app: &AppHandle) -> PathBuf {
+    get_reviews_path(app).with_file_name("review_packs.json")
+}
+
+/// Reviews written under this user's identity.
+fn load_own_reviews(app: &AppHandle) -> Result<Vec<LocalReview>, String> {
+    let (reviews, author) = load_review_store(app)?;
+    Ok(reviews
+        .into_iter()
+        .filter(|r| r.author.as_deref() == Some(author.as_str()))
+        .collect())
+}
+
+fn load_packs(app: &AppHandle) -> Vec<ImportedPack> {
+    fs::read_to_string(get_packs_path(app))
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+fn save_packs(app: &AppHandle, packs: &[ImportedPack]) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(packs).map_err(|e| e.to_string())?;
+    fs::write(get_packs_path(app), content).map_err(|e| e.to_string())
+}
+
+/// Signer of a good signature from `gpg --status-fd` output. Any bad, expired, revoked or
+/// unverifiable signature fails the whole check.
+fn parse_gpg_status(status: &str) -> Result<(String, String), String> {
+    let mut fingerprint = None;
+    let mut uid = None;
+    for line in status.lines() {
+        let Some(rest) = line.strip_prefix("[GNUPG:] ") else {
+            continue;
+        };
+        let (keyword, args) = rest.split_once(' ').unwrap_or((rest, ""));
+        match keyword {
+            "GOODSIG" => uid = args.split_once(' ').map(|(_, u)| u.to_string()),
+            "VALIDSIG" => fingerprint = args.split_whitespace().next().map(String::from),
+            "BADSIG" => return Err("The review pack's signature is invalid".to_string()),
+            "EXPSIG" | "EXPKEYSIG" => {
+                return Err("The review pack was signed with an expired key".to_string())
+            }
+            "REVKEYSIG" => return Err("The review pack was signed with a revoked key".to_string()),
+            "ERRSIG" | "NO_PUBKEY" => return Err(
+                "The review pack's signing key is not in your keyring; import it with gpg first"
+                    .to_string(),
+            ),
+            _ => {}
+        }
+    }
+    match (fingerprint, uid) {
+        (Some(f), Some(u)) => Ok((f, u)),
+        _ => Err("The review pack is not signed".to_string()),
+    }
+}
+
+/// Reviews from a verified pack, tagged with their origin. Entries with an out-of-range
+/// rating or no app id are dropped; long texts are cut.
+fn accept_pack_reviews(reviews: Vec<LocalReview>, origin: &PackOrigin) -> Vec<LocalReview> {
+    let cut = |s: String| s.chars().take(MAX_PACK_TEXT).collect::<String>();
+    reviews
+        .into_iter()
+        .filter(|r| !r.app_id.trim().is_empty() && r.rating <= 100)
+        .map(|r| LocalReview {
+            summary: cut(r.summary),
+            description: cut(r.description),
+            user_display: cut(r.user_display),
+            author: r.author.map(cut),
+            pack: Some(origin.clone()),
+            odrs: false,
+            ..r
+        })
+        .collect()
 }
 
+/// The user's own reviews for `app_id`, then those from imported packs (`pack` set).
 #[tauri::command]
 pub async fn get_local_reviews(app: AppHandle, app_id: String) -> Result<Vec<LocalReview>, String> {
-    let path = get_reviews_path(&app);
-    if !path.exists() {
-        return Ok(vec![]);
+    let mut reviews: Vec<LocalReview> = load_own_reviews(&app)?
+        .into_iter()
+        .filter(|r| r.app_id == app_id)
+        .collect();
+    for pack in load_packs(&app) {
+        reviews.extend(pack.reviews.into_iter().filter(|r| r.app_id == app_id));
+    }
+    Ok(reviews)
+}
+
+/// The user's own reviews for every app, newest first.
+#[tauri::command]
+pub async fn get_my_reviews(app: AppHandle) -> Result<Vec<LocalReview>, String> {
+    let mut reviews = load_own_reviews(&app)?;
+    reviews.sort_by(|a, b| b.date_created.cmp(&a.date_created));
+    Ok(reviews)
+}
+
+/// Change one of the user's own reviews; `rating` is 1-5 stars. A review published to ODRS
+/// is replaced there as well.
+#[tauri::command]
+pub async fn update_review(
+    app: AppHandle,
+    id: String,
+    rating: u32,
+    summary: String,
+    description: String,
+) -> Result<LocalReview, String> {
+    let rating = stored_rating(rating)?;
+    let (mut reviews, author) = load_review_store(&app)?;
+    let review = reviews
+        .iter_mut()
+        .find(|r| r.id == id && r.author.as_deref() == Some(author.as_str()))
+        .ok_or("That review is not one of yours")?;
+    review.rating = rating;
+    review.summary = summary;
+    review.description = description;
+    review.date_edited = Some(now_secs());
+    let updated = review.clone();
+    save_reviews(&app, &reviews)?;
+    if updated.odrs {
+        crate::odrs_api::replace_review(&app, &updated)
+            .await
+            .map_err(|e| format!("Review saved, but updating it on ODRS failed: {}", e))?;
+    }
+    Ok(updated)
+}
+
+/// Record that review `id` was published to ODRS.
+pub(crate) fn mark_published(app: &AppHandle, id: &str) -> Result<(), String> {
+    let (mut reviews, author) = load_review_store(app)?;
+    let review = reviews
+        .iter_mut()
+        .find(|r| r.id == id && r.author.as_deref() == Some(author.as_str()))
+        .ok_or("That review is not one of yours")?;
+    review.odrs = true;
+    save_reviews(app, &reviews)
+}
+
+/// Delete one of the user's own reviews, and its ODRS copy if it was published.
+#[tauri::command]
+pub async fn delete_review(app: AppHandle, id: String) -> Result<(), String> {
+    let (mut reviews, author) = load_review_store(&app)?;
+    let index = reviews
+        .iter()
+        .position(|r| r.id == id && r.author.as_deref() == Some(author.as_str()))
+        .ok_or("That review is not one of yours")?;
+    let removed = reviews.remove(index);
+    save_reviews(&app, &reviews)?;
+    if removed.odrs {
+        crate::odrs_api::remove_review(&app, &removed.app_id)
+            .await
+            .map_err(|e| format!("Review deleted, but removing it from ODRS failed: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Sign the user's own reviews as pack `name` and write it to `path` (a save dialog when
+/// None). `key_id` picks the signing key, gpg's default key otherwise. Returns the path
+/// written, or None if the dialog was cancelled.
+#[tauri::command]
+pub async fn export_review_pack(
+    app: AppHandle,
+    name: String,
+    key_id: Option<String>,
+    path: Option<String>,
+) -> Result<Option<String>, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Give the review pack a name".to_string());
+    }
+    let reviews: Vec<LocalReview> = load_own_reviews(&app)?;
+    if reviews.is_empty() {
+        return Err("You have no reviews to export".to_string());
+    }
+    let path = match path {
+        Some(p) => PathBuf::from(p),
+        None => match rfd::AsyncFileDialog::new()
+            .add_filter("Review pack", &["json"])
+            .set_file_name(format!("{}.reviews.json", name))
+            .save_file()
+            .await
+        {
+            Some(handle) => handle.path().to_path_buf(),
+            None => return Ok(None),
+        },
+    };
+
+    let payload = serde_json::to_string(&PackPayload {
+        format: PACK_FORMAT,
+        name,
+        created: now_secs(),
+        reviews,
+    })
+    .map_err(|e| e.to_string())?;
+    let payload_file = tempfile::NamedTempFile::new().map_err(|e| e.to_string())?;
+    fs::write(payload_file.path(), &payload).map_err(|e| e.to_string())?;
+
+    let mut cmd = tokio::process::Command::new("gpg");
+    cmd.args(["--armor", "--detach-sign", "--output", "-"]);
+    if let Some(key) = key_id.as_deref().filter(|k| !k.trim().is_empty()) {
+        cmd.args(["--local-user", key.trim()]);
+    }
+    let output = cmd
+        .arg(payload_file.path())
+        .output()
+        .await
+        .map_err(|e| format!("Could not run gpg: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "gpg could not sign the review pack: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
     }
 
+    let pack = SignedPack {
+        payload,
+        signature: String::from_utf8_lossy(&output.stdout).into_owned(),
+    };
+    let content = serde_json::to_string_pretty(&pack).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| e.to_string())?;
+    Ok(Some(path.to_string_lossy().into_owned()))
+}
+
+/// Verify and import the review pack at `path` (an open dialog when None). A pack with the
+/// same name from the same signer replaces the earlier import. None if the dialog was
+/// cancelled.
+#[tauri::command]
+pub async fn import_review_pack(
+    app: AppHandle,
+    path: Option<String>,
+) -> Result<Option<ReviewPackInfo>, String> {
+    let path = match path {
+        Some(p) => PathBuf::from(p),
+        None => match rfd::AsyncFileDialog::new()
+            .add_filter("Review pack", &["json"])
+            .pick_file()
+            .await
+        {
+            Some(handle) => handle.path().to_path_buf(),
+            None => return Ok(None),
+        },
+    };
     let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
-    let reviews: Vec<LocalReview> = serde_json::from_str(&content).unwrap_or_default();
+    let pack: SignedPack =
+        serde_json::from_str(&content).map_err(|_| "Not a review pack file".to_string())?;
+
+    let payload_file = tempfile::NamedTempFile::new().map_err(|e| e.to_string())?;
+    let signature_file = tempfile::NamedTempFile::new().map_err(|e| e.to_string())?;
+    fs::write(payload_file.path(), &pack.payload).map_err(|e| e.to_string())?;
+    fs::write(signature_file.path(), &pack.signature).map_err(|e| e.to_string())?;
+    let output = tokio::process::Command::new("gpg")
+        .args(["--batch", "--status-fd", "1", "--verify"])
+        .arg(signature_file.path())
+        .arg(payload_file.path())
+        .output()
+        .await
+        .map_err(|e| format!("Could not run gpg: {}", e))?;
+    let (signer, signer_uid) = parse_gpg_status(&String::from_utf8_lossy(&output.stdout))?;
+    if !output.status.success() {
+        return Err("The review pack's signature could not be verified".to_string());
+    }
+
+    // Only parse what the signature covers
+    let payload: PackPayload = serde_json::from_str(&pack.payload)
+        .map_err(|_| "The review pack's contents are malformed".to_string())?;
+    if payload.format != PACK_FORMAT {
+        return Err(format!(
+            "Unsupported review pack format {}; update MonArch to import it",
+            payload.format
+        ));
+    }
+    let origin = PackOrigin {
+        name: payload.name,
+        signer,
+        signer_uid,
+    };
+    let imported = ImportedPack {
+        reviews: accept_pack_reviews(payload.reviews, &origin),
+        origin,
+        created: payload.created,
+        imported: now_secs(),
+    };
+    let info = ReviewPackInfo::from(&imported);
+
+    let mut packs = load_packs(&app);
+    packs.retain(|p| {
+        p.origin.name != imported.origin.name || p.origin.signer != imported.origin.signer
+    });
+    packs.push(imported);
+    save_packs(&app, &packs)?;
+    Ok(Some(info))
+}
 
-    Ok(reviews.into_iter().filter(|r| r.app_id == app_id).collect())
+#[tauri::command]
+pub async fn list_review_packs(app: AppHandle) -> Result<Vec<ReviewPackInfo>, String> {
+    Ok(load_packs(&app).iter().map(ReviewPackInfo::from).collect())
+}
+
+/// Forget an imported pack and its reviews.
+#[tauri::command]
+pub async fn remove_review_pack(
+    app: AppHandle,
+    name: String,
+    signer: String,
+) -> Result<(), String> {
+    let mut packs = load_packs(&app);
+    packs.retain(|p| p.origin.name != name || p.origin.signer != signer);
+    save_packs(&app, &packs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gpg_status_and_pack_reviews() {
+        let good = "\
+[GNUPG:] NEWSIG
+[GNUPG:] KEY_CONSIDERED 0123456789ABCDEF0123456789ABCDEF01234567 0
+[GNUPG:] SIG_ID abc 2024-05-01 1714557600
+[GNUPG:] GOODSIG 89ABCDEF01234567 Arch Community <reviews@example.org>
+[GNUPG:] VALIDSIG 0123456789ABCDEF0123456789ABCDEF01234567 2024-05-01 1714557600 0 4 0 22 10 00 0123456789ABCDEF0123456789ABCDEF01234567
+[GNUPG:] TRUST_UNDEFINED 0 pgp
+";
+        let (fingerprint, uid) = parse_gpg_status(good).unwrap();
+        assert_eq!(fingerprint, "0123456789ABCDEF0123456789ABCDEF01234567");
+        assert_eq!(uid, "Arch Community <reviews@example.org>");
+
+        assert!(parse_gpg_status("[GNUPG:] BADSIG 89ABCDEF01234567 Someone").is_err());
+        assert!(parse_gpg_status(
+            "[GNUPG:] ERRSIG 89ABCDEF01234567 22 10 00 1714557600 9 -\n[GNUPG:] NO_PUBKEY 89ABCDEF01234567"
+        )
+        .unwrap_err()
+        .contains("keyring"));
+        assert!(parse_gpg_status("").is_err());
+
+        let origin = PackOrigin {
+            name: "starter".to_string(),
+            signer: fingerprint,
+            signer_uid: uid,
+        };
+        let review = |app_id: &str, rating: u32| LocalReview {
+            id: String::new(),
+            app_id: app_id.to_string(),
+            rating,
+            summary: "x".repeat(MAX_PACK_TEXT + 10),
+            description: String::new(),
+            user_display: "curator".to_string(),
+            date_created: 0,
+            date_edited: None,
+            author: None,
+            pack: None,
+            odrs: true,
+        };
+        let accepted = accept_pack_reviews(
+            vec![
+                review("org.gimp.GIMP", 80),
+                review("", 60),
+                review("vlc", 500),
+            ],
+            &origin,
+        );
+        assert_eq!(accepted.len(), 1);
+        assert_eq!(accepted[0].summary.len(), MAX_PACK_TEXT);
+        assert_eq!(accepted[0].pack.as_ref(), Some(&origin));
+        assert!(!accepted[0].odrs);
+    }
+
+    #[test]
+    fn test_author_id_and_legacy_adoption() {
+        let me = author_id("00112233445566778899aabbccddeeff");
+        assert_eq!(me.len(), 40);
+        assert_eq!(me, author_id("00112233445566778899aabbccddeeff"));
+        assert_ne!(me, author_id("ffeeddccbbaa99887766554433221100"));
+
+        // Written before ids existed, and by another identity
+        let mut reviews: Vec<LocalReview> = serde_json::from_str(
+            r#"[
+                {"app_id":"vlc","rating":80,"summary":"s","description":"","user_display":"me","date_created":1},
+                {"id":"abc","app_id":"gimp","rating":60,"summary":"s","description":"","user_display":"x","date_created":2,"author":"other"}
+            ]"#,
+        )
+        .unwrap();
+        let mut next = 0;
+        let mut new_id = || {
+            next += 1;
+            Ok(format!("id{}", next))
+        };
+        assert!(adopt_legacy_reviews(&mut reviews, &me, &mut new_id).unwrap());
+        assert_eq!(reviews[0].id, "id1");
+        assert_eq!(reviews[0].author.as_deref(), Some(me.as_str()));
+        assert_eq!(reviews[1].author.as_deref(), Some("other"));
+        assert!(!adopt_legacy_reviews(&mut reviews, &me, &mut new_id).unwrap());
+
+        assert_eq!(stored_rating(4), Ok(80));
+        assert!(stored_rating(0).is_err());
+        assert!(stored_rating(80).is_err());
+    }
 }
@@ -0,0 +1,353 @@
+//! Roll a package back to an earlier version after a bad update. Candidates come from the
+//! pacman cache and the Arch Linux Archive (ALA), which keeps every official package build;
+//! the chosen file goes through AlpmInstallFiles like a local install, with its detached
+//! signature next to it so pacman still verifies it.
+
+use crate::helper_client;
+use serde::Serialize;
+use std::sync::LazyLock;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+const PKG_CACHE: &str = "/var/cache/pacman/pkg";
+const ALA_PACKAGES: &str = "https://archive.archlinux.org/packages";
+
+static HREF_RE: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r#"href="([^"/?]+)""#).expect("valid regex HREF_RE"));
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum VersionSource {
+    Cache,
+    Archive,
+}
+
+/// Typed response for get_available_versions
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct AvailableVersion {
+    pub version: String,
+    /// Cache wins when both have the file: no download needed.
+    pub source: VersionSource,
+    pub filename: String,
+    pub installed: bool,
+}
+
+/// `(version, arch)` if `file` is a package archive of exactly `name`
+/// (`name-pkgver-pkgrel-arch.pkg.tar.*`); `python` does not match `python-foo-1.0-1-any...`.
+fn parse_package_filename(name: &str, file: &str) -> Option<(String, String)> {
+    let rest = file.strip_prefix(name)?.strip_prefix('-')?;
+    if rest.ends_with(".sig") {
+        return None;
+    }
+    let (base, _) = rest.split_once(".pkg.tar")?;
+    let parts: Vec<&str> = base.split('-').collect();
+    if parts.len() != 3 || parts.iter().any(|p| p.is_empty()) {
+        return None;
+    }
+    Some((format!("{}-{}", parts[0], parts[1]), parts[2].to_string()))
+}
+
+fn arch_matches(arch: &str) -> bool {
+    arch == "any" || arch == std::env::consts::ARCH
+}
+
+/// `(version, filename)` for every installable `name` archive in `files`.
+fn package_files(name: &str, files: &[String]) -> Vec<(String, String)> {
+    files
+        .iter()
+        .filter_map(|f| {
+            parse_package_filename(name, f)
+                .filter(|(_, arch)| arch_matches(arch))
+                .map(|(version, _)| (version, f.clone()))
+        })
+        .collect()
+}
+
+/// Decode %XX escapes; ALA links escape the epoch colon (`1%3A24.2-1`).
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if let Some(b) = s
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                out.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// File names linked from an ALA directory listing.
+fn listing_links(html: &str) -> Vec<String> {
+    HREF_RE
+        .captures_iter(html)
+        .map(|c| percent_decode(&c[1]))
+        .collect()
+}
+
+/// One entry per version, newest first; a cached file beats an archive download.
+fn merge_versions(
+    cache: Vec<(String, String)>,
+    archive: Vec<(String, String)>,
+    installed: Option<&str>,
+) -> Vec<AvailableVersion> {
+    let mut out: Vec<AvailableVersion> = Vec::new();
+    let sources = cache
+        .into_iter()
+        .map(|v| (v, VersionSource::Cache))
+        .chain(archive.into_iter().map(|v| (v, VersionSource::Archive)));
+    for ((version, filename), source) in sources {
+        if out.iter().any(|v| v.version == version) {
+            continue;
+        }
+        out.push(AvailableVersion {
+            installed: installed == Some(version.as_str()),
+            version,
+            source,
+            filename,
+        });
+    }
+    out.sort_by(|a, b| crate::versions::newest_first(&a.version, &b.version));
+    out
+}
+
+fn ala_dir(name: &str) -> String {
+    let first = name.chars().next().unwrap_or('_').to_ascii_lowercase();
+    format!("{}/{}/{}/", ALA_PACKAGES, first, name)
+}
+
+fn cache_listing() -> Vec<String> {
+    std::fs::read_dir(PKG_CACHE)
+        .map(|d| {
+            d.flatten()
+                .map(|e| e.file_name().to_string_lossy().into_owned())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Archive files for `name`; empty when the ALA has none or is unreachable.
+async fn archive_files(name: &str) -> Vec<(String, String)> {
+    let client = crate::http_client::client(Duration::from_secs(20));
+    match crate::http_client::fetch(client.get(ala_dir(name))).await {
+        Ok(resp) if resp.is_success() => package_files(name, &listing_links(&resp.text())),
+        Ok(resp) => {
+            log::info!("ALA has no listing for {} ({})", name, resp.status);
+            Vec::new()
+        }
+        Err(e) => {
+            log::warn!("Arch Linux Archive unreachable: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+async fn available_versions(name: &str) -> Result<Vec<AvailableVersion>, String> {
+    let (cache, installed) = tokio::task::spawn_blocking({
+        let name = name.to_string();
+        move || {
+            (
+                package_files(&name, &cache_listing()),
+                crate::alpm_read::get_installed_version(&name),
+            )
+        }
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?;
+    let archive = archive_files(name).await;
+    Ok(merge_versions(cache, archive, installed.as_deref()))
+}
+
+/// Versions of `pkg` that can be installed from the pacman cache or the Arch Linux Archive.
+#[tauri::command]
+pub async fn get_available_versions(pkg: String) -> Result<Vec<AvailableVersion>, String> {
+    crate::utils::validate_package_name(&pkg)?;
+    available_versions(&pkg).await
+}
+
+/// Download `file` and its signature from the ALA into `dir`. Returns the local paths.
+async fn download_from_archive(
+    name: &str,
+    file: &str,
+    dir: &std::path::Path,
+) -> Result<Vec<String>, String> {
+    let client = crate::http_client::client(Duration::from_secs(300));
+    let mut paths = Vec::new();
+    for f in [file.to_string(), format!("{}.sig", file)] {
+        let url = format!("{}{}", ala_dir(name), f.replace(':', "%3A"));
+        let resp = crate::http_client::fetch(client.get(&url)).await?;
+        if !resp.is_success() {
+            if f.ends_with(".sig") {
+                // Very old uploads have no signature; pacman's LocalFileSigLevel decides
+                continue;
+            }
+            return Err(format!("Download of {} failed: HTTP {}", f, resp.status));
+        }
+        let path = dir.join(&f);
+        tokio::fs::write(&path, &resp.body)
+            .await
+            .map_err(|e| format!("Could not save {}: {}", f, e))?;
+        paths.push(path.to_string_lossy().into_owned());
+    }
+    Ok(paths)
+}
+
+/// Install `version` of `pkg` from the cache, or from the Arch Linux Archive, and optionally
+/// pin it so the next upgrade does not undo the rollback. Returns the installed version.
+#[tauri::command]
+pub async fn downgrade_package(
+    app: AppHandle,
+    pkg: String,
+    version: String,
+    pin: Option<bool>,
+    password: Option<String>,
+) -> Result<String, String> {
+    crate::utils::validate_package_name(&pkg)?;
+    let candidate = available_versions(&pkg)
+        .await?
+        .into_iter()
+        .find(|v| v.version == version)
+        .ok_or_else(|| {
+            format!(
+                "{} {} is in neither the package cache nor the Arch Linux Archive",
+                pkg, version
+            )
+        })?;
+    if candidate.installed {
+        return Err(format!("{} {} is already installed", pkg, version));
+    }
+
+    let download_dir = tempfile::tempdir().map_err(|e| e.to_string())?;
+    let files = match candidate.source {
+        VersionSource::Cache => {
+            let path = format!("{}/{}", PKG_CACHE, candidate.filename);
+            let sig = format!("{}.sig", path);
+            if std::path::Path::new(&sig).exists() {
+                vec![path, sig]
+            } else {
+                vec![path]
+            }
+        }
+        VersionSource::Archive => {
+            let _ = app.emit(
+                "install-output",
+                format!(
+                    "Downloading {} from the Arch Linux Archive...",
+                    candidate.filename
+                ),
+            );
+            download_from_archive(&pkg, &candidate.filename, download_dir.path()).await?
+        }
+    };
+    // The signature travels with the package; the helper only installs the archive itself
+    let staged = crate::commands::package::copy_paths_to_monarch_install(files).await?;
+    let archives: Vec<String> = staged
+        .into_iter()
+        .filter(|p| !p.ends_with(".sig"))
+        .collect();
+
+    {
+        let _guard = crate::utils::PRIVILEGED_LOCK.lock().await;
+        let _ = app.emit(
+            "install-output",
+            format!("--- Rolling back {} to {} ---", pkg, version),
+        );
+        let mut rx = helper_client::invoke_helper(
+            &app,
+            helper_client::HelperCommand::AlpmInstallFiles { paths: archives },
+            password.clone(),
+        )
+        .await
+        .map_err(|e| format!("Failed to invoke helper: {}", e))?;
+        while let Some(msg) = rx.recv().await {
+            let _ = app.emit("install-output", &msg.message);
+        }
+    }
+
+    let installed = tokio::task::spawn_blocking({
+        let pkg = pkg.clone();
+        move || crate::alpm_read::get_installed_version(&pkg)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?;
+    if installed.as_deref() != Some(version.as_str()) {
+        let _ = app.emit("install-complete", "failed");
+        return Err(format!(
+            "Rollback of {} failed; installed version is {}",
+            pkg,
+            installed.as_deref().unwrap_or("none")
+        ));
+    }
+    if pin.unwrap_or(false) {
+        crate::commands::pins::pin_package(
+            app.clone(),
+            pkg.clone(),
+            Some(format!("Rolled back to {}", version)),
+            password,
+        )
+        .await?;
+    }
+    let _ = app.emit("install-complete", "success");
+    Ok(version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_sources_parse_and_merge() {
+        assert_eq!(
+            parse_package_filename("mesa", "mesa-1:24.2.1-1-x86_64.pkg.tar.zst"),
+            Some(("1:24.2.1-1".to_string(), "x86_64".to_string()))
+        );
+        assert_eq!(
+            parse_package_filename("python", "python-foo-1.0-1-any.pkg.tar.zst"),
+            None
+        );
+        assert_eq!(
+            parse_package_filename("mesa", "mesa-1:24.2.1-1-x86_64.pkg.tar.zst.sig"),
+            None
+        );
+
+        let html = r#"<a href="../">../</a>
+<a href="mesa-1%3A24.1.7-1-x86_64.pkg.tar.zst">mesa-1:24.1.7-1-x86_64.pkg.tar.zst</a>
+<a href="mesa-1%3A24.1.7-1-x86_64.pkg.tar.zst.sig">mesa-1:24.1.7-1-x86_64.pkg.tar.zst.sig</a>
+<a href="mesa-1%3A24.2.1-1-x86_64.pkg.tar.zst">mesa-1:24.2.1-1-x86_64.pkg.tar.zst</a>
+<a href="mesa-1%3A24.2.1-1-aarch64.pkg.tar.xz">mesa-1:24.2.1-1-aarch64.pkg.tar.xz</a>"#;
+        let archive = package_files("mesa", &listing_links(html));
+        assert_eq!(
+            archive.iter().map(|(v, _)| v.as_str()).collect::<Vec<_>>(),
+            vec!["1:24.1.7-1", "1:24.2.1-1"]
+        );
+        assert_eq!(archive[0].1, "mesa-1:24.1.7-1-x86_64.pkg.tar.zst");
+
+        let cache = package_files(
+            "mesa",
+            &[
+                "mesa-1:24.2.1-1-x86_64.pkg.tar.zst".to_string(),
+                "mesa-1:24.2.2-1-x86_64.pkg.tar.zst".to_string(),
+            ],
+        );
+        let merged = merge_versions(cache, archive, Some("1:24.2.2-1"));
+        assert_eq!(
+            merged
+                .iter()
+                .map(|v| (v.version.as_str(), v.source, v.installed))
+                .collect::<Vec<_>>(),
+            vec![
+                ("1:24.2.2-1", VersionSource::Cache, true),
+                ("1:24.2.1-1", VersionSource::Cache, false),
+                ("1:24.1.7-1", VersionSource::Archive, false),
+            ]
+        );
+    }
+}
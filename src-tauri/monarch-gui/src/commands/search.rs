@@ -13,6 +13,21 @@ pub struct PaginatedResponse {
     pub has_more: bool,
 }
 
+/// Typed response for search_packages
+#[derive(Serialize)]
+pub struct SearchResponse {
+    pub packages: Vec<models::Package>,
+    /// Repo index generation the official results came from; changes after each sync.
+    pub index_generation: u64,
+    /// Corrected query when the one typed has words that match nothing ("fierfox").
+    pub did_you_mean: Option<String>,
+}
+
+/// Below this many official hits, results for the corrected query are mixed in.
+const FUZZY_FALLBACK_THRESHOLD: usize = 5;
+/// Minimum normalized Damerau-Levenshtein similarity for the fuzzy relevance tier.
+const FUZZY_MIN_SIMILARITY: f64 = 0.75;
+
 use crate::flathub_api::{FlathubApiClient, SearchResult};
 use crate::models::{Package, PackageSource};
 
@@ -133,6 +148,7 @@ pub fn merge_search_results(
                 make_depends: None,
                 is_featured: None,
                 installed: false,
+                update_available: false,
                 download_size: None,
                 installed_size: None,
                 alternatives: None,
@@ -142,6 +158,8 @@ pub fn merge_search_results(
                     "latest",
                     "Flatpak (Sandboxed)",
                 )]),
+                optimized_variants: None,
+                content_rating: None,
             };
             package_map.insert(direct_key, p);
         }
@@ -172,10 +190,16 @@ pub async fn search_packages(
     state_flathub: State<'_, FlathubApiClient>,
     state_metadata: State<'_, metadata::MetadataState>,
     state_distro: State<'_, crate::distro_context::DistroContext>,
+    state_index: State<'_, crate::search_index::SearchIndexState>,
     query: String,
-) -> Result<Vec<Package>, String> {
+    filters: Option<crate::search_index::SearchFilters>,
+) -> Result<SearchResponse, String> {
     if query.len() < 2 {
-        return Ok(Vec::new());
+        return Ok(SearchResponse {
+            packages: Vec::new(),
+            index_generation: state_repo.inner().index_generation().await,
+            did_you_mean: None,
+        });
     }
 
     let query_lower = query.to_lowercase();
@@ -183,12 +207,28 @@ pub async fn search_packages(
     let flathub = state_flathub.inner();
     // aur_api is stateless/lazy_static accessible directly
 
+    let filters = filters.unwrap_or_default();
+    // Offline: local sync DBs and AppStream only
+    let online = !crate::connectivity::is_offline();
+
     // 1. Parallel Search
-    // We use tokio::join to run searches concurrently
+    // We use tokio::join to run searches concurrently; sources the filters exclude are skipped
     let (official_res, aur_res, flatpak_res) = tokio::join!(
         repo_manager.get_packages_matching(&query, state_distro.inner()),
-        crate::aur_api::search_aur(&query),
-        flathub.search_flathub(&query)
+        async {
+            if online && filters.wants_source("aur") {
+                crate::aur_api::search_aur(&query).await
+            } else {
+                Ok(Vec::new())
+            }
+        },
+        async {
+            if online && filters.wants_source("flatpak") {
+                flathub.search_flathub(&query).await
+            } else {
+                None
+            }
+        }
     );
 
     // 2. Merge Logic (Unified Vision: deduplicate into single entries with available_sources)
@@ -201,8 +241,28 @@ pub async fn search_packages(
         crate::distro_context::DistroId::Unknown(s) => s.as_str(),
     };
 
-    let official: Vec<Package> = official_res
-        .unwrap_or_default()
+    let (mut official, index_generation) = match official_res {
+        Ok(found) => found,
+        Err(_) => (Vec::new(), repo_manager.index_generation().await),
+    };
+
+    // Typo tolerance: few hits and a correction exists -> add what the corrected query finds
+    let index = state_index
+        .inner()
+        .current(repo_manager, state_metadata.inner())
+        .await;
+    let did_you_mean = index.suggest(&query);
+    if let Some(corrected) = &did_you_mean {
+        if official.len() < FUZZY_FALLBACK_THRESHOLD {
+            let filters = crate::search_index::SearchFilters::default();
+            for pkg in index.query(corrected, &filters, state_distro.inner()) {
+                if !official.iter().any(|p| p.name == pkg.name) {
+                    official.push(pkg);
+                }
+            }
+        }
+    }
+    let official: Vec<Package> = official
         .into_iter()
         .map(|mut p| {
             p.source.label = crate::labels::get_friendly_label(&p.source.id, distro_id_str).to_string();
@@ -216,6 +276,16 @@ pub async fn search_packages(
 
     let mut results = merge_search_results(official, aur, flatpak);
 
+    // License policy ("hide proprietary" / "FOSS only"); also normalizes licenses to SPDX
+    let license_policy = repo_manager.get_license_policy().await;
+    crate::license::apply_policy(&mut results, &license_policy);
+    let max_age = repo_manager.get_max_content_age().await;
+    crate::content_rating::apply(&mut results, state_metadata.inner(), max_age);
+
+    // Structured filters (installed state, FOSS-only, optimized, source types)
+    let installed = crate::search_index::installed_names(&filters).await;
+    filters.retain(&mut results, &installed);
+
     // 3. Relevance Scoring & Sorting ("Smart Sort")
     let metadata_loader = state_metadata.0.lock().map_err(|e| e.to_string())?;
 
@@ -270,8 +340,16 @@ pub async fn search_packages(
             // Finally alphabetical
             .then_with(|| a.name.cmp(&b.name))
     });
+    if let Some(limit) = filters.limit {
+        results.truncate(limit);
+    }
+    crate::installed_state::annotate(&mut results);
 
-    Ok(results)
+    Ok(SearchResponse {
+        packages: results,
+        index_generation,
+        did_you_mean,
+    })
 }
 
 fn calculate_relevance(
@@ -332,7 +410,16 @@ fn calculate_relevance(
         score = 20;
     }
 
-    // 7. Popularity Bonus: +10 when in popular list and matches query
+    // 7. Fuzzy Match (Score 10-15): typos ("fierfox") and split names ("libre office")
+    if score == 0 {
+        score = fuzzy_score(&pkg_name_lower, query).max(
+            friendly_name
+                .as_deref()
+                .map_or(0, |f| fuzzy_score(f, query)),
+        );
+    }
+
+    // 8. Popularity Bonus: +10 when in popular list and matches query
     if is_popular && matches_query && score > 0 {
         score = (score + 10).min(100);
     }
@@ -340,9 +427,76 @@ fn calculate_relevance(
     score
 }
 
+/// Similarity tier for names that don't contain the query: 15 when they match ignoring
+/// spaces and dashes, 10 when within a few typos, else 0.
+fn fuzzy_score(name: &str, query: &str) -> u32 {
+    let squash = |s: &str| {
+        s.chars()
+            .filter(|c| c.is_alphanumeric())
+            .collect::<String>()
+    };
+    let base = squash(utils::strip_package_suffix(name));
+    let (name, query) = (squash(name), squash(query));
+    if query.len() < 4 {
+        return 0;
+    }
+    if base == query || name.starts_with(&query) {
+        return 15;
+    }
+    let similarity = strsim::normalized_damerau_levenshtein(&base, &query)
+        .max(strsim::normalized_damerau_levenshtein(&name, &query));
+    if similarity >= FUZZY_MIN_SIMILARITY {
+        10
+    } else {
+        0
+    }
+}
+
+/// Instant results from the local index (synced repos + AppStream only). The frontend shows
+/// these first and then replaces them with search_packages, which adds AUR and Flathub.
+#[tauri::command]
+pub async fn search_index_query(
+    state_repo: State<'_, RepoManager>,
+    state_metadata: State<'_, metadata::MetadataState>,
+    state_index: State<'_, crate::search_index::SearchIndexState>,
+    state_distro: State<'_, crate::distro_context::DistroContext>,
+    query: String,
+    filters: Option<crate::search_index::SearchFilters>,
+) -> Result<SearchResponse, String> {
+    let index = state_index
+        .inner()
+        .current(state_repo.inner(), state_metadata.inner())
+        .await;
+    let filters = filters.unwrap_or_default();
+    let mut packages = index.query(&query, &filters, state_distro.inner());
+    let did_you_mean = index.suggest(&query);
+    if packages.is_empty() {
+        if let Some(corrected) = &did_you_mean {
+            packages = index.query(corrected, &filters, state_distro.inner());
+        }
+    }
+    let license_policy = state_repo.inner().get_license_policy().await;
+    crate::license::apply_policy(&mut packages, &license_policy);
+    let max_age = state_repo.inner().get_max_content_age().await;
+    crate::content_rating::apply(&mut packages, state_metadata.inner(), max_age);
+    if filters.is_post_filter() {
+        let installed = crate::search_index::installed_names(&filters).await;
+        filters.retain(&mut packages, &installed);
+        packages.truncate(filters.limit.unwrap_or(crate::search_index::DEFAULT_LIMIT));
+    }
+    crate::installed_state::annotate(&mut packages);
+    Ok(SearchResponse {
+        packages,
+        index_generation: index.generation(),
+        did_you_mean,
+    })
+}
+
 #[tauri::command]
 pub async fn search_aur(query: String) -> Result<Vec<models::Package>, String> {
-    aur_api::search_aur(&query).await
+    let mut packages = aur_api::search_aur(&query).await?;
+    crate::installed_state::annotate(&mut packages);
+    Ok(packages)
 }
 
 #[tauri::command]
@@ -497,9 +651,31 @@ pub async fn get_packages_by_names(
     // UNIFIED DEDUPLICATION
     packages = utils::merge_and_deduplicate(Vec::new(), packages);
 
+    for pkg in packages
+        .iter_mut()
+        .filter(|p| p.source.source_type == "repo")
+    {
+        let variants = state_repo.inner().get_optimized_variants(&pkg.name).await;
+        if !variants.is_empty() {
+            pkg.optimized_variants = Some(variants);
+        }
+    }
+    crate::installed_state::annotate(&mut packages);
+
     Ok(packages)
 }
 
+/// Builds of `pkg` in the CachyOS x86-64-v3/v4 and znver4 repos, marked with whether this CPU
+/// can run them, so the source picker can offer "optimized for your CPU".
+#[tauri::command]
+pub async fn get_optimized_alternatives(
+    state_repo: State<'_, RepoManager>,
+    pkg: String,
+) -> Result<Vec<models::OptimizedVariant>, String> {
+    utils::validate_package_name(&pkg)?;
+    Ok(state_repo.inner().get_optimized_variants(&pkg).await)
+}
+
 #[tauri::command]
 pub async fn get_chaotic_package_info(
     state_chaotic: State<'_, chaotic_api::ChaoticApiClient>,
@@ -516,6 +692,16 @@ pub async fn get_chaotic_packages_batch(
     state_chaotic.inner().get_packages_by_names(&names).await
 }
 
+/// Whether a Chaotic-AUR package is queued, building or failed, with its last build log.
+#[tauri::command]
+pub async fn get_build_status(
+    state_chaotic: State<'_, chaotic_api::ChaoticApiClient>,
+    pkgname: String,
+) -> Result<chaotic_api::ChaoticBuildStatus, String> {
+    utils::validate_package_name(&pkgname)?;
+    state_chaotic.inner().fetch_build_status(&pkgname).await
+}
+
 #[tauri::command]
 pub async fn get_trending(
     state_meta: State<'_, metadata::MetadataState>,
@@ -826,6 +1012,7 @@ pub async fn get_trending(
 
     // UNIFIED DEDUPLICATION
     packages = utils::merge_and_deduplicate(Vec::new(), packages);
+    crate::installed_state::annotate(&mut packages);
     Ok(packages)
 }
 
@@ -1002,6 +1189,9 @@ pub async fn get_package_variants(
         }
     }
 
+    // Newest build first; stable, so equal versions keep the repo → Chaotic → AUR → Flatpak order
+    final_variants.sort_by(|a, b| crate::versions::newest_first(&a.version, &b.version));
+
     Ok(final_variants)
 }
 
@@ -1010,296 +1200,65 @@ pub async fn get_category_packages_paginated(
     state_meta: State<'_, metadata::MetadataState>,
     state_chaotic: State<'_, chaotic_api::ChaoticApiClient>,
     state_repo: State<'_, RepoManager>,
+    state_catalog: State<'_, crate::catalog::CatalogState>,
     category: String,
     repo_filter: Option<Vec<String>>,
     sort_by: Option<String>,
     page: usize,
     limit: usize,
 ) -> Result<PaginatedResponse, String> {
-    let mut packages = if let Ok(loader) = state_meta.inner().0.lock() {
-        loader.get_apps_by_category(&category)
-    } else {
-        Vec::new()
-    }
-    .into_iter()
-    .map(|app| models::Package {
-        name: app.pkg_name.clone().unwrap_or(app.app_id.clone()),
-        display_name: Some(app.name),
-        description: app.summary.unwrap_or_default(),
-        version: app.version.unwrap_or_else(|| "latest".to_string()),
-        source: models::PackageSource::new("repo", "core", "latest", "Arch Official"),
-        maintainer: None,
-        license: None,
-        url: None,
-        last_modified: app.last_updated.map(|t| t as i64),
-        first_submitted: None,
-        out_of_date: None,
-        keywords: None,
-        num_votes: None,
-        icon: app.icon_url,
-        screenshots: if app.screenshots.is_empty() {
-            None
-        } else {
-            Some(app.screenshots)
-        },
-        provides: None,
-        app_id: Some(app.app_id.clone()),
-        is_optimized: None,
-        depends: None,
-        make_depends: None,
-        is_featured: None,
-        installed: false,
-        ..Default::default()
-    })
-    .collect::<Vec<_>>();
-
-    let c_matches = state_chaotic
-        .inner()
-        .get_packages_by_category(&category)
-        .await;
-
-    for p in c_matches {
-        // Allow duplicates at this stage so filtering can pick the right one later
-        let mut pkg = models::Package {
-            name: p.pkgname.clone(),
-            display_name: Some(utils::to_pretty_name(&p.pkgname)),
-            description: p
-                .metadata
-                .as_ref()
-                .and_then(|m| m.desc.clone())
-                .unwrap_or_default(),
-            version: p.version.clone().unwrap_or_default(),
-            source: models::PackageSource::new(
-                "repo",
-                "chaotic-aur",
-                &p.version.clone().unwrap_or_default(),
-                "Chaotic-AUR (Pre-built)",
-            ),
-            maintainer: Some("Chaotic-AUR Team".to_string()),
-            license: p
-                .metadata
-                .as_ref()
-                .and_then(|m| m.license.clone())
-                .map(|l| vec![l]),
-            url: p.metadata.as_ref().and_then(|m| m.url.clone()),
-            installed: false,
-            last_modified: None,
-            first_submitted: None,
-            out_of_date: None,
-            keywords: None,
-            num_votes: None,
-            icon: {
-                let mut icon = None;
-                if let Ok(loader) = state_meta.inner().0.lock() {
-                    icon = loader.find_icon_heuristic(&p.pkgname);
-                }
-                icon
-            },
-            screenshots: None,
-            provides: None,
-            app_id: None,
-            is_optimized: None,
-            depends: None,
-            make_depends: None,
-            is_featured: None,
-            alternatives: None,
-            ..Default::default()
-        };
-        if let Ok(loader) = state_meta.inner().0.lock() {
-            pkg.app_id = loader.find_app_id(&p.pkgname);
-        }
-        packages.push(pkg);
-    }
-
-    // --- FIX: FORCE INJECT FEATURES ---
-    // Ensure curated featured apps are present even if category search missed them
-    let featured_names = get_featured_apps(&category);
-    if !featured_names.is_empty() {
-        let existing_names: std::collections::HashSet<String> =
-            packages.iter().map(|p| p.name.clone()).collect();
-        let missing: Vec<String> = featured_names
-            .iter()
-            .filter(|&&n| !existing_names.contains(&n.to_string())) // simple name check
-            .map(|&s| s.to_string())
-            .collect();
-
-        if !missing.is_empty() {
-            if let Ok(injected) = get_packages_by_names(
-                state_meta.clone(),
-                state_chaotic.clone(),
-                state_repo.clone(),
-                missing,
-            )
-            .await
-            {
-                for mut p in injected {
-                    // Re-check existence to be safe
-                    if !existing_names.contains(&p.name) {
-                        p.is_featured = Some(true); // Auto-mark
-                        packages.push(p);
-                    }
-                }
-            }
-        }
-    }
-    // ----------------------------------
-
-    // --- FIX: REPO FILTER (BEFORE DEDUP) ---
-    // Filter first so we don't dedup away the variant the user explicitly asked for.
-    if let Some(repos) = repo_filter {
-        let has_all = repos.iter().any(|r| r.to_lowercase() == "all");
-
-        if !has_all && !repos.is_empty() {
-            let allowed: std::collections::HashSet<String> =
-                repos.iter().map(|s| s.to_lowercase()).collect();
-
-            packages.retain(|p| {
-                let p_source = match p.source.source_type.as_str() {
-                    "repo" => match p.source.id.as_str() {
-                        "chaotic-aur" => "chaotic-aur",
-                        "cachyos" => "cachyos",
-                        "garuda" => "garuda",
-                        "endeavour" => "endeavour",
-                        "manjaro" => "manjaro",
-                        _ => "official",
-                    },
-                    "flatpak" => "flatpak",
-                    "aur" => "aur",
-                    "local" => "local",
-                    _ => "other",
-                };
-
-                if p_source == "chaotic-aur"
-                    && (allowed.contains("chaotic") || allowed.contains("chaotic-aur"))
-                {
-                    return true;
-                }
-                allowed.contains(p_source)
-            });
-        }
-    }
-    // ---------------------------------------
-
-    // --- FIX: DEDUPLICATE ---
-    // Prioritize Injected (Featured) items (base) over standard search results (others)
-    // This prevents "Steam" (AppStream) + "Steam" (Injected) duplicates.
-
-    let (injected, others): (Vec<_>, Vec<_>) = packages
-        .into_iter()
-        .partition(|p| p.is_featured == Some(true));
-
-    // Combine them, preserving priority (Featured first)
-    // We pass EMPTY base lists, so that 'injected' items also check against themselves!
-    let all_sorted = injected.into_iter().chain(others).collect();
-    packages = utils::merge_and_deduplicate(Vec::new(), all_sorted);
-
-    // ------------------------
+    // Frontend sends 1-based page index
+    let page_idx = if page > 0 { page - 1 } else { 0 };
 
-    // --- FIX: AUGMENT DATES FROM REPO DB (ALPM as single READ source) ---
-    let names: Vec<String> = packages.iter().map(|p| p.name.clone()).collect();
-    let enabled_repos: Vec<String> = state_repo
-        .inner()
-        .get_all_repos()
-        .await
+    // The SQLite catalog answers with one LIMIT/OFFSET query
+    let cat_lower = category.trim().to_lowercase();
+    let mut buckets: Vec<String> = repo_filter
         .iter()
-        .filter(|r| r.enabled)
-        .map(|r| r.name.clone())
-        .collect();
-    let names_clone = names.clone();
-    let repo_data = tokio::task::spawn_blocking(move || {
-        crate::alpm_read::get_packages_batch(&names_clone, &enabled_repos)
-    })
-    .await
-    .map_err(|e| e.to_string())?;
-    let date_map: std::collections::HashMap<String, i64> = repo_data
-        .into_iter()
-        .filter_map(|p| p.last_modified.map(|d| (p.name, d)))
+        .flatten()
+        .map(|r| match r.to_lowercase().as_str() {
+            "chaotic" => "chaotic-aur".to_string(),
+            other => other.to_string(),
+        })
         .collect();
-
-    for pkg in packages.iter_mut() {
-        if pkg.last_modified.is_none() {
-            if let Some(date) = date_map.get(&pkg.name) {
-                pkg.last_modified = Some(*date);
-            }
-        }
-    }
-    // ---------------------------------------
-
-    if let Some(ref sort) = sort_by {
-        match sort.as_str() {
-            "name" => packages.sort_by(|a, b| a.name.cmp(&b.name)),
-            "newest" => {
-                packages.sort_by(|a, b| {
-                    b.last_modified
-                        .unwrap_or(0)
-                        .cmp(&a.last_modified.unwrap_or(0))
-                });
-            }
-            _ => utils::sort_packages_by_relevance(&mut packages, ""),
-        }
-    } else {
-        // Default sort by name if none provided
-        packages.sort_by(|a, b| a.name.cmp(&b.name));
-    }
-
-    // FEATURED APPS HOISTING & FLAGGING
-    // We lift popular apps to the top regardless of sort AND mark them
-    let featured = get_featured_apps(&category);
-    if !featured.is_empty() {
-        // Create case-insensitive map
-        let featured_map: std::collections::HashMap<String, usize> = featured
-            .iter()
-            .enumerate()
-            .map(|(i, name)| (name.to_lowercase(), i))
-            .collect();
-
-        // Mark them as featured
-        for pkg in packages.iter_mut() {
-            if featured_map.contains_key(&pkg.name.to_lowercase()) {
-                pkg.is_featured = Some(true);
-            }
-        }
-
-        // Stable sort: Featured first (in defined order), then others
-        // Only if sorting by default/featured or "name" (if we want to force featured on top)
-        // User asked for specific separation, so we should always hoist if "Featured" mode is on.
-        // If sort_by is "name", user might expect strict A-Z?
-        // Let's assume default behavior implies Featured First unless strict sort is requested.
-        let is_strict_sort = sort_by.as_deref().unwrap_or("") == "name"
-            || sort_by.as_deref().unwrap_or("") == "newest";
-
-        if !is_strict_sort {
-            packages.sort_by(|a, b| {
-                // Check against both name and display_name for robustness
-                let a_key = a.name.to_lowercase();
-                let b_key = b.name.to_lowercase();
-
-                let a_rank = featured_map.get(&a_key).unwrap_or(&9999);
-                let b_rank = featured_map.get(&b_key).unwrap_or(&9999);
-
-                a_rank.cmp(b_rank)
-            });
-        }
+    if buckets.iter().any(|b| b == "all") {
+        buckets.clear();
     }
-
-    let total = packages.len();
-    // Frontend sends 1-based page index
-    let page_idx = if page > 0 { page - 1 } else { 0 };
-    let start = page_idx * limit;
-    let end = (start + limit).min(total);
-    let has_more = end < total;
-
-    let page_items = if start < total {
-        packages[start..end].to_vec()
-    } else {
-        Vec::new()
+    let max_age = state_repo.inner().get_max_content_age().await;
+    let query = crate::catalog::CategoryQuery {
+        categories: vec![
+            metadata::category_key(&cat_lower).to_string(),
+            cat_lower.clone(),
+        ],
+        keywords: chaotic_api::category_keywords(&cat_lower),
+        featured: get_featured_apps(&category),
+        buckets,
+        policy: state_repo.inner().get_license_policy().await,
+        max_age,
+        sort: sort_by,
+        offset: page_idx * limit,
+        limit,
     };
-
+    // Nothing to page through yet (first start, or the catalog has never been filled): build
+    // it now rather than assembling the whole category here
+    let mut result = state_catalog.query(query.clone()).await;
+    if result.is_none() {
+        state_catalog
+            .refresh(
+                state_repo.inner(),
+                state_meta.inner(),
+                state_chaotic.inner(),
+            )
+            .await;
+        result = state_catalog.query(query).await;
+    }
+    // Still None before the first repo sync; the page reloads on "repo-index-updated"
+    let (mut packages, total) = result.unwrap_or_default();
+    crate::installed_state::annotate(&mut packages);
     Ok(PaginatedResponse {
-        packages: page_items,
+        has_more: page_idx * limit + packages.len() < total,
+        packages,
         total,
         page,
-        has_more,
     })
 }
 
@@ -0,0 +1,184 @@
+use crate::alpm_read::InstalledUsage;
+use serde::Serialize;
+use std::collections::HashMap;
+
+const DEFAULT_TOP_N: usize = 25;
+const MAX_TOP_N: usize = 200;
+
+/// Size total for one slice of the installed set (a repo, or explicit vs dependency).
+#[derive(Debug, Serialize, Clone, Default, PartialEq)]
+pub struct UsageGroup {
+    pub key: String,
+    pub package_count: usize,
+    pub total_bytes: u64,
+}
+
+impl UsageGroup {
+    fn add(&mut self, bytes: u64) {
+        self.package_count += 1;
+        self.total_bytes += bytes;
+    }
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct PackageUsage {
+    pub name: String,
+    pub version: String,
+    pub installed_size: u64,
+    pub repo: String,
+    pub explicit: bool,
+    /// Nothing installed needs it, so removing it alone breaks no dependency.
+    pub removable: bool,
+    /// Installed as a dependency that is no longer required (`pacman -Qtd`).
+    pub orphan: bool,
+}
+
+/// Typed response for get_disk_usage_report
+#[derive(Debug, Serialize, Clone, Default, PartialEq)]
+pub struct DiskUsageReport {
+    pub total_bytes: u64,
+    pub package_count: usize,
+    /// Largest repo first; foreign packages (AUR, local builds) are grouped as "foreign".
+    pub by_repo: Vec<UsageGroup>,
+    pub explicit: UsageGroup,
+    pub dependencies: UsageGroup,
+    pub largest: Vec<PackageUsage>,
+    /// Space freed by removing every orphan.
+    pub orphan_bytes: u64,
+}
+
+fn build_report(entries: Vec<InstalledUsage>, top_n: usize) -> DiskUsageReport {
+    let mut report = DiskUsageReport {
+        explicit: UsageGroup {
+            key: "explicit".to_string(),
+            ..Default::default()
+        },
+        dependencies: UsageGroup {
+            key: "dependency".to_string(),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut by_repo: HashMap<String, UsageGroup> = HashMap::new();
+    let mut packages = Vec::with_capacity(entries.len());
+
+    for e in entries {
+        let repo = e.repo.unwrap_or_else(|| "foreign".to_string());
+        report.total_bytes += e.installed_size;
+        report.package_count += 1;
+        by_repo
+            .entry(repo.clone())
+            .or_insert_with(|| UsageGroup {
+                key: repo.clone(),
+                ..Default::default()
+            })
+            .add(e.installed_size);
+        if e.explicit {
+            report.explicit.add(e.installed_size);
+        } else {
+            report.dependencies.add(e.installed_size);
+        }
+        let orphan = !e.explicit && !e.required;
+        if orphan {
+            report.orphan_bytes += e.installed_size;
+        }
+        packages.push(PackageUsage {
+            name: e.name,
+            version: e.version,
+            installed_size: e.installed_size,
+            repo,
+            explicit: e.explicit,
+            removable: !e.required,
+            orphan,
+        });
+    }
+
+    report.by_repo = by_repo.into_values().collect();
+    report.by_repo.sort_by(|a, b| {
+        b.total_bytes
+            .cmp(&a.total_bytes)
+            .then_with(|| a.key.cmp(&b.key))
+    });
+    packages.sort_by(|a, b| {
+        b.installed_size
+            .cmp(&a.installed_size)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    packages.truncate(top_n);
+    report.largest = packages;
+    report
+}
+
+/// Where installed packages put their disk space: totals per repo and per install reason,
+/// plus the `top_n` largest packages (default 25) with whether each can be removed.
+#[tauri::command]
+pub async fn get_disk_usage_report(top_n: Option<usize>) -> Result<DiskUsageReport, String> {
+    let top_n = top_n.unwrap_or(DEFAULT_TOP_N).min(MAX_TOP_N);
+    tokio::task::spawn_blocking(move || {
+        build_report(crate::alpm_read::get_installed_usage_native(), top_n)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(
+        name: &str,
+        size: u64,
+        repo: Option<&str>,
+        explicit: bool,
+        required: bool,
+    ) -> InstalledUsage {
+        InstalledUsage {
+            name: name.to_string(),
+            version: "1.0-1".to_string(),
+            installed_size: size,
+            repo: repo.map(str::to_string),
+            explicit,
+            required,
+        }
+    }
+
+    #[test]
+    fn test_report_groups_and_ranks() {
+        let report = build_report(
+            vec![
+                usage("linux-firmware", 900, Some("core"), false, true),
+                usage("steam", 700, Some("multilib"), true, false),
+                usage("glibc", 500, Some("core"), false, true),
+                usage("old-lib", 300, Some("extra"), false, false),
+                usage("yay", 100, None, true, false),
+            ],
+            3,
+        );
+        assert_eq!(report.total_bytes, 2500);
+        assert_eq!(report.package_count, 5);
+        assert_eq!(
+            report
+                .by_repo
+                .iter()
+                .map(|g| (g.key.as_str(), g.total_bytes, g.package_count))
+                .collect::<Vec<_>>(),
+            vec![
+                ("core", 1400, 2),
+                ("multilib", 700, 1),
+                ("extra", 300, 1),
+                ("foreign", 100, 1)
+            ]
+        );
+        assert_eq!(
+            (report.explicit.package_count, report.explicit.total_bytes),
+            (2, 800)
+        );
+        assert_eq!(report.dependencies.total_bytes, 1700);
+        assert_eq!(report.orphan_bytes, 300);
+
+        let names: Vec<&str> = report.largest.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["linux-firmware", "steam", "glibc"]);
+        assert!(!report.largest[0].removable);
+        assert!(report.largest[1].removable && !report.largest[1].orphan);
+    }
+}
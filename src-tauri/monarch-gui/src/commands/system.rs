@@ -51,6 +51,10 @@ pub struct SystemInfo {
 pub struct CacheSizeResult {
     pub size_bytes: u64,
     pub human_readable: String,
+    /// MonARCH's own screenshot cache, cleared by clear_cache.
+    pub screenshot_cache_bytes: u64,
+    /// MonARCH's own icon cache, cleared by clear_cache.
+    pub icon_cache_bytes: u64,
 }
 
 /// Typed response for get_orphans_with_size (replaces raw serde_json::json!).
@@ -228,6 +232,25 @@ pub async fn set_one_click_enabled(
     Ok(())
 }
 
+#[tauri::command]
+pub async fn get_privileged_agent_settings(
+    app: AppHandle,
+) -> Result<crate::helper_client::PrivilegedAgentSettings, String> {
+    Ok(crate::helper_client::read_agent_settings(&app))
+}
+
+/// Saving stops a running agent, so the next operation starts one with the new settings (or,
+/// when turned off, runs the helper directly).
+#[tauri::command]
+pub async fn set_privileged_agent_settings(
+    app: AppHandle,
+    settings: crate::helper_client::PrivilegedAgentSettings,
+) -> Result<(), String> {
+    crate::helper_client::write_agent_settings(&app, &settings)?;
+    crate::helper_client::stop_agent().await;
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn check_security_policy() -> Result<bool, String> {
     let helper_path = std::path::Path::new("/usr/lib/monarch-store/monarch-helper");
@@ -354,6 +377,7 @@ pub async fn update_and_install_package(
                 refresh_db: true,
                 ..Default::default()
             },
+            dry_run: false,
         },
         password.clone(),
     )
@@ -371,19 +395,7 @@ pub async fn update_and_install_package(
     }
 
     // Step 2: Install target package (only if Step 1 succeeded)
-    let mut enabled_repos: Vec<String> = state_repo
-        .inner()
-        .get_all_repos()
-        .await
-        .iter()
-        .filter(|r| r.enabled)
-        .map(|r| r.name.clone())
-        .collect();
-    for sys in ["core", "extra", "community", "multilib"] {
-        if !enabled_repos.contains(&sys.to_string()) {
-            enabled_repos.push(sys.to_string());
-        }
-    }
+    let enabled_repos = state_repo.transaction_repos().await;
 
     let _ = app.emit(
         "install-output",
@@ -398,6 +410,8 @@ pub async fn update_and_install_package(
             enabled_repos,
             cpu_optimization,
             target_repo: repo_name,
+            phase: Default::default(),
+            dry_run: false,
         },
         password.clone(),
     )
@@ -526,6 +540,44 @@ pub async fn set_sync_on_startup_enabled(
     Ok(())
 }
 
+#[tauri::command]
+pub async fn is_run_in_background_enabled(
+    state: State<'_, repo_manager::RepoManager>,
+) -> Result<bool, String> {
+    Ok(state.inner().is_run_in_background().await)
+}
+
+/// When on, closing the main window hides it to the tray instead of quitting.
+#[tauri::command]
+pub async fn set_run_in_background_enabled(
+    state: State<'_, repo_manager::RepoManager>,
+    enabled: bool,
+) -> Result<(), String> {
+    state.inner().set_run_in_background(enabled).await;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_license_policy(
+    state: State<'_, repo_manager::RepoManager>,
+) -> Result<String, String> {
+    Ok(state.inner().get_license_policy().await)
+}
+
+/// "all" | "hide_proprietary" | "foss_only"; applied to search and category browsing.
+#[tauri::command]
+pub async fn set_license_policy(
+    state: State<'_, repo_manager::RepoManager>,
+    policy: String,
+) -> Result<(), String> {
+    use crate::license::{POLICY_ALL, POLICY_FOSS_ONLY, POLICY_HIDE_PROPRIETARY};
+    if ![POLICY_ALL, POLICY_HIDE_PROPRIETARY, POLICY_FOSS_ONLY].contains(&policy.as_str()) {
+        return Err(format!("Unknown license policy: '{}'", policy));
+    }
+    state.inner().set_license_policy(&policy).await;
+    Ok(())
+}
+
 /// Returns true if the pacman hook set a refresh flag (user ran pacman in terminal);
 /// we clear the flag and the caller should trigger a repo sync.
 #[tauri::command]
@@ -579,6 +631,8 @@ pub async fn get_cache_size() -> Result<CacheSizeResult, String> {
         Ok(CacheSizeResult {
             size_bytes: total_bytes,
             human_readable,
+            screenshot_cache_bytes: crate::screenshot_cache::cache_size(),
+            icon_cache_bytes: crate::icon_cache::cache_size(),
         })
     })
     .await
@@ -663,7 +717,7 @@ pub async fn set_parallel_downloads(
     let script = format!(
         r#"
         echo 'Updating ParallelDownloads in /etc/pacman.conf...'
-        cp /etc/pacman.conf /etc/pacman.conf.bak.parallel.$(date +%s) || true
+        cp /etc/pacman.conf /etc/pacman.conf.monarch-bak || true
         if grep -q "^ParallelDownloads" /etc/pacman.conf; then
             sed -i "s/^ParallelDownloads.*/ParallelDownloads = {}/" /etc/pacman.conf
         else
@@ -862,6 +916,7 @@ pub async fn force_refresh_databases(
                 refresh_db: true,
                 ..Default::default()
             },
+            dry_run: false,
         },
         password,
     )
@@ -885,6 +940,7 @@ pub async fn sync_system_databases(app: AppHandle, password: Option<String>) ->
                 refresh_db: true,
                 ..Default::default()
             },
+            dry_run: false,
         },
         password,
     )
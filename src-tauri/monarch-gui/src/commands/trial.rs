@@ -0,0 +1,475 @@
+//! "Try before install": run an app without adding it to the system package set.
+//!
+//! Flathub apps go into a throwaway `--user` installation: FLATPAK_USER_DIR points flatpak
+//! at a scratch directory under the cache, so neither the real user installation nor the
+//! system one is touched. Repo packages without a Flatpak (CLI tools) get a distrobox
+//! container with the package installed and its own home, opened in a terminal. Either way
+//! everything is removed once the app or the terminal exits, including the app's
+//! `~/.var/app` data when the trial created it; scratch directories a crashed trial left
+//! behind are swept before the next one. Progress is reported as `trial-status` events.
+
+use crate::repo_manager::RepoManager;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, State};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::Command;
+
+const FLATHUB_REPO: &str = "https://dl.flathub.org/repo/flathub.flatpakrepo";
+const TRIAL_IMAGE: &str = "docker.io/library/archlinux:latest";
+const CONTAINER_PREFIX: &str = "monarch-try-";
+/// Repos the stock Arch image has enabled.
+const CONTAINER_REPOS: &[&str] = &["core", "extra"];
+/// Terminal emulators tried in order, with the arguments that make them run a command and
+/// stay in the foreground until it exits.
+const TERMINALS: &[(&str, &[&str])] = &[
+    ("konsole", &["-e"]),
+    ("gnome-terminal", &["--wait", "--"]),
+    ("xfce4-terminal", &["--disable-server", "-x"]),
+    ("alacritty", &["-e"]),
+    ("kitty", &[]),
+    ("foot", &[]),
+    ("xterm", &["-e"]),
+];
+
+/// Trials in progress, by app id; one at a time per app.
+static ACTIVE: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TrialKind {
+    Flatpak,
+    Distrobox,
+}
+
+/// Typed response for get_trial_support.
+#[derive(Debug, Serialize, Clone)]
+pub struct TrialSupport {
+    pub flatpak: bool,
+    /// distrobox and a terminal emulator to open the container in.
+    pub distrobox: bool,
+}
+
+/// Payload of `trial-status`. `state` is preparing, running, cleaning, finished or failed.
+#[derive(Debug, Serialize, Clone)]
+pub struct TrialStatus {
+    pub app_id: String,
+    pub kind: TrialKind,
+    pub state: String,
+    pub message: String,
+}
+
+/// Reverse-DNS Flatpak application id: three or more elements of `[A-Za-z0-9_-]`, none
+/// starting with a digit.
+pub fn is_flatpak_app_id(id: &str) -> bool {
+    let elements: Vec<&str> = id.split('.').collect();
+    id.len() <= 255
+        && elements.len() >= 3
+        && elements.iter().all(|e| {
+            e.chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && e.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        })
+}
+
+/// Container name for a trial of `pkg` (container names don't allow `+` or `@`).
+pub fn container_name(pkg: &str) -> String {
+    let safe: String = pkg
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("{}{}", CONTAINER_PREFIX, safe)
+}
+
+fn trials_dir() -> PathBuf {
+    crate::metadata::get_cache_dir().join("try")
+}
+
+/// The per-app data directory `flatpak run` creates in the real home, whatever
+/// FLATPAK_USER_DIR says.
+fn flatpak_data_dir(app_id: &str) -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".var/app").join(app_id))
+}
+
+/// Trial kind of a scratch directory, from the id it is named after.
+fn kind_of(app_id: &str) -> TrialKind {
+    if is_flatpak_app_id(app_id) {
+        TrialKind::Flatpak
+    } else {
+        TrialKind::Distrobox
+    }
+}
+
+fn on_path(program: &str) -> bool {
+    std::env::var_os("PATH")
+        .is_some_and(|paths| std::env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+}
+
+/// $TERMINAL if set, else the first known terminal that is installed.
+fn terminal() -> Option<(String, Vec<String>)> {
+    if let Ok(term) = std::env::var("TERMINAL") {
+        if !term.is_empty() && on_path(&term) {
+            let args = TERMINALS
+                .iter()
+                .find(|(name, _)| *name == term)
+                .map_or(vec!["-e".to_string()], |(_, a)| {
+                    a.iter().map(|s| s.to_string()).collect()
+                });
+            return Some((term, args));
+        }
+    }
+    TERMINALS
+        .iter()
+        .find(|(name, _)| on_path(name))
+        .map(|(name, args)| {
+            (
+                name.to_string(),
+                args.iter().map(|s| s.to_string()).collect(),
+            )
+        })
+}
+
+fn emit(app: &AppHandle, app_id: &str, kind: TrialKind, state: &str, message: &str) {
+    let _ = app.emit(
+        "trial-status",
+        TrialStatus {
+            app_id: app_id.to_string(),
+            kind,
+            state: state.to_string(),
+            message: message.to_string(),
+        },
+    );
+}
+
+/// Run `program`, forwarding its output as `preparing` statuses.
+async fn run_logged(
+    app: &AppHandle,
+    app_id: &str,
+    kind: TrialKind,
+    program: &str,
+    args: &[&str],
+    envs: &[(&str, &Path)],
+) -> Result<(), String> {
+    let mut child = Command::new(program)
+        .args(args)
+        .envs(envs.iter().copied())
+        .env("LC_ALL", "C")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to start {}: {}", program, e))?;
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+    let mut last_err = String::new();
+    if let (Some(stdout), Some(stderr)) = (stdout, stderr) {
+        let mut out = BufReader::new(stdout).lines();
+        let mut err = BufReader::new(stderr).lines();
+        let (mut out_done, mut err_done) = (false, false);
+        while !(out_done && err_done) {
+            tokio::select! {
+                line = out.next_line(), if !out_done => match line {
+                    Ok(Some(l)) if !l.trim().is_empty() => emit(app, app_id, kind, "preparing", l.trim()),
+                    Ok(Some(_)) => {}
+                    _ => out_done = true,
+                },
+                line = err.next_line(), if !err_done => match line {
+                    Ok(Some(l)) if !l.trim().is_empty() => {
+                        emit(app, app_id, kind, "preparing", l.trim());
+                        last_err = l.trim().to_string();
+                    }
+                    Ok(Some(_)) => {}
+                    _ => err_done = true,
+                },
+            }
+        }
+    }
+    let status = child.wait().await.map_err(|e| e.to_string())?;
+    if status.success() {
+        Ok(())
+    } else if last_err.is_empty() {
+        Err(format!("{} exited with {}", program, status))
+    } else {
+        Err(last_err)
+    }
+}
+
+async fn try_flatpak(app: &AppHandle, app_id: &str, root: &Path) -> Result<(), String> {
+    let kind = TrialKind::Flatpak;
+    let envs = [("FLATPAK_USER_DIR", root)];
+    emit(
+        app,
+        app_id,
+        kind,
+        "preparing",
+        "Setting up a temporary Flatpak installation...",
+    );
+    run_logged(
+        app,
+        app_id,
+        kind,
+        "flatpak",
+        &[
+            "--user",
+            "remote-add",
+            "--if-not-exists",
+            "flathub",
+            FLATHUB_REPO,
+        ],
+        &envs,
+    )
+    .await?;
+    run_logged(
+        app,
+        app_id,
+        kind,
+        "flatpak",
+        &[
+            "--user",
+            "install",
+            "-y",
+            "--noninteractive",
+            "flathub",
+            app_id,
+        ],
+        &envs,
+    )
+    .await?;
+    emit(
+        app,
+        app_id,
+        kind,
+        "running",
+        "Running the trial; close the app to end it.",
+    );
+    // The app's own output is not progress
+    let status = Command::new("flatpak")
+        .args(["--user", "run", app_id])
+        .env("FLATPAK_USER_DIR", root)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await
+        .map_err(|e| format!("Failed to start flatpak: {}", e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("{} exited with {}", app_id, status))
+    }
+}
+
+async fn try_distrobox(app: &AppHandle, pkg: &str, home: &Path) -> Result<(), String> {
+    let kind = TrialKind::Distrobox;
+    let (term, term_args) =
+        terminal().ok_or_else(|| "No terminal emulator found to open the trial in".to_string())?;
+    let name = container_name(pkg);
+    let home_str = home.to_string_lossy();
+    emit(
+        app,
+        pkg,
+        kind,
+        "preparing",
+        "Creating a temporary Arch container...",
+    );
+    run_logged(
+        app,
+        pkg,
+        kind,
+        "distrobox",
+        &[
+            "create",
+            "--yes",
+            "--name",
+            &name,
+            "--image",
+            TRIAL_IMAGE,
+            "--home",
+            &home_str,
+            // No launcher entry for the container in the real ~/.local/share/applications
+            "--no-entry",
+            "--additional-packages",
+            pkg,
+        ],
+        &[],
+    )
+    .await?;
+    emit(
+        app,
+        pkg,
+        kind,
+        "running",
+        "Running the trial; close the terminal to end it.",
+    );
+    let status = Command::new(&term)
+        .args(&term_args)
+        .args(["distrobox", "enter", &name])
+        .stdin(Stdio::null())
+        .status()
+        .await
+        .map_err(|e| format!("Failed to open {}: {}", term, e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("{} exited with {}", term, status))
+    }
+}
+
+/// Remove what a trial of `app_id` left behind, including after a crash.
+async fn clean_up(kind: TrialKind, app_id: &str, scratch: &Path) {
+    if kind == TrialKind::Distrobox {
+        let _ = Command::new("distrobox")
+            .args(["rm", "--force", &container_name(app_id)])
+            .stdin(Stdio::null())
+            .output()
+            .await;
+    }
+    if scratch.exists() {
+        if let Err(e) = tokio::fs::remove_dir_all(scratch).await {
+            log::warn!("Could not remove trial data {}: {}", scratch.display(), e);
+        }
+    }
+}
+
+/// Remove scratch directories (and containers) of trials that are not running, left behind
+/// when the app quit or crashed mid-trial.
+async fn sweep_stale() {
+    let Ok(mut entries) = tokio::fs::read_dir(trials_dir()).await else {
+        return;
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let app_id = entry.file_name().to_string_lossy().into_owned();
+        let running = ACTIVE
+            .lock()
+            .map(|active| active.contains(&app_id))
+            .unwrap_or(true);
+        if !running {
+            clean_up(kind_of(&app_id), &app_id, &entry.path()).await;
+        }
+    }
+}
+
+/// Which kinds of trial this system can run.
+#[tauri::command]
+pub async fn get_trial_support() -> Result<TrialSupport, String> {
+    Ok(TrialSupport {
+        flatpak: on_path("flatpak"),
+        distrobox: on_path("distrobox") && terminal().is_some(),
+    })
+}
+
+/// Try an app without installing it: a Flathub app id runs from a temporary Flatpak
+/// installation, a repo pkgname in a distrobox container. Returns once the trial has started;
+/// `trial-status` reports how it goes and when it has been cleaned up.
+#[tauri::command]
+pub async fn try_app(
+    app: AppHandle,
+    state_repo: State<'_, RepoManager>,
+    app_id: String,
+) -> Result<TrialKind, String> {
+    let app_id = app_id.trim().to_string();
+    let kind = kind_of(&app_id);
+    if kind == TrialKind::Distrobox {
+        crate::utils::validate_package_name(&app_id)?;
+        let in_arch_repos = state_repo
+            .get_all_packages_with_repos(&app_id)
+            .await
+            .iter()
+            .any(|(_, repo)| CONTAINER_REPOS.contains(&repo.as_str()));
+        if !in_arch_repos {
+            return Err(format!(
+                "{} is not in the official repositories, so it can't be tried in a container",
+                app_id
+            ));
+        }
+    }
+    let support = get_trial_support().await?;
+    match kind {
+        TrialKind::Flatpak if !support.flatpak => {
+            return Err("Trying Flathub apps needs flatpak installed".to_string())
+        }
+        TrialKind::Distrobox if !support.distrobox => {
+            return Err("Trying command-line packages needs distrobox and a terminal".to_string())
+        }
+        _ => {}
+    }
+    if !ACTIVE
+        .lock()
+        .map_err(|e| e.to_string())?
+        .insert(app_id.clone())
+    {
+        return Err(format!("{} is already being tried", app_id));
+    }
+
+    tauri::async_runtime::spawn(async move {
+        let scratch = trials_dir().join(&app_id);
+        sweep_stale().await;
+        clean_up(kind, &app_id, &scratch).await;
+        // Only data the trial itself created is removed; an installed copy keeps its own
+        let app_data =
+            flatpak_data_dir(&app_id).filter(|dir| kind == TrialKind::Flatpak && !dir.exists());
+        let result = match tokio::fs::create_dir_all(&scratch).await {
+            Ok(()) => match kind {
+                TrialKind::Flatpak => try_flatpak(&app, &app_id, &scratch).await,
+                TrialKind::Distrobox => try_distrobox(&app, &app_id, &scratch).await,
+            },
+            Err(e) => Err(format!("Could not create {}: {}", scratch.display(), e)),
+        };
+        emit(&app, &app_id, kind, "cleaning", "Removing the trial...");
+        clean_up(kind, &app_id, &scratch).await;
+        if let Some(dir) = app_data.filter(|dir| dir.exists()) {
+            if let Err(e) = tokio::fs::remove_dir_all(&dir).await {
+                log::warn!("Could not remove trial data {}: {}", dir.display(), e);
+            }
+        }
+        match result {
+            Ok(()) => emit(
+                &app,
+                &app_id,
+                kind,
+                "finished",
+                "Trial ended; nothing was left behind.",
+            ),
+            Err(e) => emit(&app, &app_id, kind, "failed", &e),
+        }
+        if let Ok(mut active) = ACTIVE.lock() {
+            active.remove(&app_id);
+        }
+    });
+    Ok(kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trial_ids() {
+        assert!(is_flatpak_app_id("org.gimp.GIMP"));
+        assert!(is_flatpak_app_id("com.github.tchx84.Flatseal"));
+        assert!(is_flatpak_app_id("io.github.some_dev.app-name"));
+        assert!(!is_flatpak_app_id("firefox"));
+        assert!(!is_flatpak_app_id("org.gimp"));
+        assert!(!is_flatpak_app_id("org..GIMP"));
+        assert!(!is_flatpak_app_id("org.gimp.1GIMP"));
+        assert!(!is_flatpak_app_id("org.gimp.GIMP;rm"));
+        assert!(!is_flatpak_app_id("python3.12.x"));
+
+        assert_eq!(container_name("htop"), "monarch-try-htop");
+        assert_eq!(container_name("libc++"), "monarch-try-libc--");
+        assert_eq!(kind_of("org.gimp.GIMP"), TrialKind::Flatpak);
+        assert_eq!(kind_of("htop"), TrialKind::Distrobox);
+    }
+}
@@ -1,7 +1,7 @@
 use crate::aur_api;
 use crate::commands::package::PendingUpdate;
+use crate::models::{UpdateItem, UpdateSet};
 use crate::repo_manager::RepoManager;
-use std::process::Stdio;
 use tauri::{AppHandle, Emitter, State};
 
 /// Command and label for "Update in terminal" (Apdatifier-style transparency).
@@ -45,6 +45,16 @@ pub async fn perform_system_update(
 ) -> Result<String, String> {
     log::info!("Update: starting process (background)");
 
+    // Like informant: unread "manual intervention" news must be acknowledged (mark_news_read) first
+    let breaking = crate::news_api::pending_breaking_news(&app).await;
+    if !breaking.is_empty() {
+        let titles: Vec<&str> = breaking.iter().map(|n| n.title.as_str()).collect();
+        return Err(format!(
+            "NEWS_ACK_REQUIRED: Read these announcements before updating: {}",
+            titles.join("; ")
+        ));
+    }
+
     // Run the full update in a background task so the app does not freeze.
     let app_bg = app.clone();
     let password_bg = password.clone();
@@ -72,24 +82,26 @@ async fn run_system_update_impl(
     // Acquire global lock to prevent concurrent pacman operations
     let _guard = crate::utils::PRIVILEGED_LOCK.lock().await;
 
-    // Phase 1: Sanity Check (Ping)
+    // Phase 1: Sanity Check (connectivity probe)
     let _ = app.emit("update-status", "Checking connectivity...");
 
-    let is_online = tokio::process::Command::new("ping")
-        .args(["-c", "1", "-W", "2", "archlinux.org"])
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .status()
-        .await
-        .map(|s| s.success())
-        .unwrap_or(false);
+    let connectivity = crate::connectivity::check_now(&app).await;
+    log::info!("[Update] Online status: {}", !connectivity.offline);
 
-    log::info!("[Update] Online status: {}", is_online);
-
-    if !is_online {
-        return Err("OFFLINE: Cannot perform update without internet connectivity.".to_string());
+    if connectivity.offline {
+        return Err(if connectivity.forced {
+            "OFFLINE: Offline mode is on; turn it off in Settings to update.".to_string()
+        } else {
+            "OFFLINE: Cannot perform update without internet connectivity.".to_string()
+        });
     }
 
+    // Save config files first so the upgrade's config changes can be diffed and undone
+    let snapshot_app = app.clone();
+    let _ =
+        tokio::task::spawn_blocking(move || crate::history::snapshot_config_files(&snapshot_app))
+            .await;
+
     // Phase 2: Full System Upgrade (SINGLE TRANSACTION via ALPM)
     let _ = app.emit(
         "update-status",
@@ -114,6 +126,7 @@ async fn run_system_update_impl(
                 refresh_db: true,
                 ..Default::default()
             },
+            dry_run: false,
         },
         password.clone(),
     )
@@ -174,6 +187,10 @@ async fn run_system_update_impl(
         }
     }
 
+    if !sysupgrade_failed {
+        crate::news_api::record_system_upgrade();
+    }
+
     if sysupgrade_failed {
         let msg = "System update failed. Aborting AUR updates to prevent partial upgrade state.";
         let _ = app.emit("update-status", msg);
@@ -201,6 +218,8 @@ async fn run_system_update_impl(
     );
 
     let aur_updates = check_aur_updates().await.unwrap_or_default();
+    // Excluded development packages only update when picked explicitly (apply_updates)
+    let aur_updates = super::devel::partition_devel(&app, aur_updates);
     if aur_updates.is_empty() {
         let _ = app.emit("update-status", "No AUR updates found.");
     } else {
@@ -273,13 +292,22 @@ async fn check_aur_updates() -> Result<Vec<PendingUpdate>, String> {
     let mut pending = Vec::new();
     for pkg in aur_info {
         if let Some(installed_ver) = installed_aur.get(&pkg.name) {
-            if pkg.version != *installed_ver {
-                pending.push(PendingUpdate {
+            match crate::versions::classify(installed_ver, &pkg.version) {
+                crate::versions::VersionChange::Upgrade => pending.push(PendingUpdate {
                     name: pkg.name.clone(),
                     old_version: installed_ver.clone(),
                     new_version: pkg.version,
                     repo: "aur".to_string(),
-                });
+                    downgrade: false,
+                }),
+                // Never rebuild an older AUR snapshot over a newer local build
+                crate::versions::VersionChange::Downgrade => log::info!(
+                    "Skipping {}: AUR has {}, installed {} is newer",
+                    pkg.name,
+                    pkg.version,
+                    installed_ver
+                ),
+                crate::versions::VersionChange::Same => {}
             }
         }
     }
@@ -305,7 +333,7 @@ async fn build_aur_package(
     password: &Option<String>,
 ) -> Result<Vec<String>, String> {
     // Pass the actual password to the improved AUR build pipeline
-    crate::commands::package::build_aur_package(app, pkg, password).await
+    crate::commands::package::build_aur_package(app, pkg, password, None).await
 }
 
 async fn install_built_packages(
@@ -333,10 +361,20 @@ async fn install_built_packages(
     Ok(())
 }
 
+fn pending_from_item(u: crate::models::UpdateItem) -> PendingUpdate {
+    PendingUpdate {
+        name: u.name,
+        old_version: u.current_version,
+        new_version: u.new_version,
+        repo: u.source.id,
+        downgrade: u.downgrade,
+    }
+}
+
 /// Unified Update Aggregator (Phase 2)
-/// Fetches updates from Repo, AUR, and Flatpak in parallel.
+/// Fetches updates from Repo, AUR, Flatpak and firmware (fwupd) in parallel.
 #[tauri::command]
-pub async fn check_updates() -> Result<Vec<crate::models::UpdateItem>, String> {
+pub async fn check_updates(app: tauri::AppHandle) -> Result<UpdateSet, String> {
     log::info!("Checking for updates (Unified)...");
 
     // Task A: Repo (Official) - Fast, local DB read
@@ -349,83 +387,192 @@ pub async fn check_updates() -> Result<Vec<crate::models::UpdateItem>, String> {
     // Task C: Flatpak - CLI process
     let flatpak_task = crate::flathub_api::get_updates();
 
+    // Task D: Firmware - fwupdmgr, skipped when fwupd isn't installed
+    let firmware_task = crate::fwupd_api::get_updates();
+
     // Parallel Join
-    let (repo_res, aur_res, flatpak_res) = tokio::join!(repo_task, aur_task, flatpak_task);
+    let (repo_res, aur_res, flatpak_res, firmware_res) =
+        tokio::join!(repo_task, aur_task, flatpak_task, firmware_task);
 
     let mut all_updates = Vec::new();
+    let mut errors = std::collections::BTreeMap::new();
 
     // 1. Repo
     match repo_res {
         Ok(items) => all_updates.extend(items),
-        Err(e) => log::error!("Failed to check repo updates: {}", e),
+        Err(e) => {
+            log::error!("Failed to check repo updates: {}", e);
+            errors.insert("repo".to_string(), e.to_string());
+        }
     }
 
     // 2. AUR
     match aur_res {
         Ok(items) => all_updates.extend(items),
-        Err(e) => log::error!("Failed to check AUR updates: {}", e),
+        Err(e) => {
+            log::error!("Failed to check AUR updates: {}", e);
+            errors.insert("aur".to_string(), e);
+        }
     }
 
     // 3. Flatpak
     match flatpak_res {
         Ok(items) => all_updates.extend(items),
-        Err(e) => log::error!("Failed to check Flatpak updates: {}", e),
+        Err(e) => {
+            log::error!("Failed to check Flatpak updates: {}", e);
+            errors.insert("flatpak".to_string(), e);
+        }
     }
 
+    // 3b. Firmware
+    match firmware_res {
+        Ok(items) => all_updates.extend(items),
+        Err(e) => {
+            log::error!("Failed to check firmware updates: {}", e);
+            errors.insert("firmware".to_string(), e);
+        }
+    }
+
+    // 4. Dropped repo binaries switched to the AUR take its build even if it sorts older
+    let switched = super::migration::switched_to_aur(&app);
+    for u in all_updates
+        .iter_mut()
+        .filter(|u| u.source.source_type == "aur" && switched.contains(&u.name))
+    {
+        u.downgrade = false;
+    }
+
+    // 5. Excluded development packages get their own section (get_devel_updates)
+    let (all_updates, devel) = super::devel::split_devel(
+        &super::devel::read_settings(&app),
+        all_updates,
+        |u| u.name.as_str(),
+        |u| u.source.source_type == "aur",
+    );
+    super::devel::set_devel_updates(devel.into_iter().map(pending_from_item).collect());
+
+    // 6. Pinned packages are held back and listed separately (get_held_updates)
+    let pinned = super::pins::pinned_names(&app);
+    let (held, all_updates): (Vec<_>, Vec<_>) = all_updates
+        .into_iter()
+        .partition(|u| pinned.contains(&u.name));
+    super::pins::set_held_updates(held.into_iter().map(pending_from_item).collect());
+
     log::info!("Found {} total updates", all_updates.len());
-    Ok(all_updates)
+    Ok(UpdateSet {
+        errors,
+        ..UpdateSet::from_items(all_updates)
+    })
 }
 
 /// Unified Execution Engine (Phase 3 & 4)
-/// Safely executes the update queue respecting the "Safety Lock".
+/// Safely executes the update queue respecting the "Safety Lock". Runs in the background
+/// like perform_system_update; the outcome arrives as update-complete.
 #[tauri::command]
 pub async fn apply_updates(
     app: AppHandle,
-    targets: Vec<crate::models::UpdateItem>,
+    targets: Vec<UpdateItem>,
     password: Option<String>,
 ) -> Result<String, String> {
     if targets.is_empty() {
         return Ok("No updates selected".to_string());
     }
 
-    log::info!("Applying {} updates...", targets.len());
+    // Official updates mean a full -Syu, which needs the same news acknowledgement
+    if targets.iter().any(|t| t.source.source_type == "repo") {
+        let breaking = crate::news_api::pending_breaking_news(&app).await;
+        if !breaking.is_empty() {
+            let titles: Vec<&str> = breaking.iter().map(|n| n.title.as_str()).collect();
+            return Err(format!(
+                "NEWS_ACK_REQUIRED: Read these announcements before updating: {}",
+                titles.join("; ")
+            ));
+        }
+    }
 
-    // Phase 4: Safety Lock
-    // If ANY official package is selected, we MUST do a full system upgrade.
-    // We cannot selectively upgrade "core/pacman" without "-Syu".
-    let has_official = targets.iter().any(|t| t.source.source_type == "repo");
+    let app_bg = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::task::yield_now().await;
+        let result = apply_updates_impl(app_bg.clone(), targets, password).await;
+        let (success, message) = match &result {
+            Ok(msg) => (true, msg.clone()),
+            Err(e) => (false, e.clone()),
+        };
+        let _ = app_bg.emit(
+            "update-progress",
+            UpdateProgressPayload {
+                phase: if success { "complete" } else { "error" }.to_string(),
+                progress: if success { 100 } else { 0 },
+                message: message.clone(),
+            },
+        );
+        let _ = app_bg.emit(
+            "update-complete",
+            UpdateCompletePayload { success, message },
+        );
+    });
+
+    Ok("started".to_string())
+}
+
+/// Consolidated update-progress across all backends: each AUR build, Flatpak and firmware
+/// device counts as one step, the whole system upgrade as one more.
+struct ApplyProgress<'a> {
+    app: &'a AppHandle,
+    done: usize,
+    total: usize,
+}
+
+impl ApplyProgress<'_> {
+    fn start(&self, phase: &str, message: String) {
+        let _ = self.app.emit("update-status", &message);
+        let _ = self.app.emit(
+            "update-progress",
+            UpdateProgressPayload {
+                phase: phase.to_string(),
+                progress: (self.done * 100 / self.total.max(1)) as u8,
+                message,
+            },
+        );
+    }
 
-    // Group targets
-    let aur_targets: Vec<&crate::models::UpdateItem> = targets
-        .iter()
-        .filter(|t| t.source.source_type == "aur")
-        .collect();
+    fn step_done(&mut self) {
+        self.done += 1;
+    }
+}
 
-    let flatpak_targets: Vec<&crate::models::UpdateItem> = targets
-        .iter()
-        .filter(|t| t.source.source_type == "flatpak")
-        .collect();
+pub(crate) async fn apply_updates_impl(
+    app: AppHandle,
+    targets: Vec<UpdateItem>,
+    password: Option<String>,
+) -> Result<String, String> {
+    log::info!("Applying {} updates...", targets.len());
+    let set = UpdateSet::from_items(targets);
+    let mut progress = ApplyProgress {
+        app: &app,
+        done: 0,
+        total: usize::from(!set.repo.is_empty())
+            + set.aur.len()
+            + set.flatpak.len()
+            + set.firmware.len(),
+    };
+    let mut failed: Vec<String> = Vec::new();
 
     // 1. Execute Repo Loop (The Iron Core)
-    if has_official {
+    // Phase 4: Safety Lock. If ANY official package is selected, we MUST do a full system
+    // upgrade; we cannot selectively upgrade "core/pacman" without "-Syu".
+    if !set.repo.is_empty() {
         log::info!("Safety Lock: Official updates detected. Enforcing System Upgrade.");
-        // We reuse the existing logic which does -Syu
-        // This updates ALL system packages, not just the selected ones.
-        // The UI should ideally warn user "Updating System..."
-        // Calls `run_system_update_impl` but we might want to skip AUR/Flatpak phase of that old function
-        // if we are handling them here specially.
-        // However, `run_system_update_impl` handles the heavy lifting of Sysupgrade transaction.
-        // Let's call a simplified version or reuse.
-        // reuse `run_system_update_impl` covers Sysupgrade + AUR.
-        // But here we have specific targets.
-        // If we call `run_system_update_impl`, it checks *all* AUR updates.
-        // We want to update only `aur_targets`.
-
-        // Let's trigger the Sysupgrade part manually.
-        let _ = app.emit(
-            "update-status",
-            "Starting System Upgrade (Official Repos)...",
+        progress.start(
+            "upgrade",
+            "Starting System Upgrade (Official Repos)...".to_string(),
         );
+        let _guard = crate::utils::PRIVILEGED_LOCK.lock().await;
+        let snapshot_app = app.clone();
+        let _ = tokio::task::spawn_blocking(move || {
+            crate::history::snapshot_config_files(&snapshot_app)
+        })
+        .await;
         let mut rx = crate::helper_client::invoke_helper(
             &app,
             crate::helper_client::HelperCommand::ExecuteBatch {
@@ -434,30 +581,28 @@ pub async fn apply_updates(
                     refresh_db: true,
                     ..Default::default()
                 },
+                dry_run: false,
             },
             password.clone(),
         )
         .await?;
 
-        // Monitor Sysupgrade
+        // Monitor Sysupgrade; AUR builds on top of a failed upgrade would be a partial upgrade
         while let Some(msg) = rx.recv().await {
             let _ = app.emit("install-output", &msg.message);
             if msg.message.starts_with("Error:") {
                 return Err(format!("System update failed: {}", msg.message));
             }
         }
+        crate::news_api::record_system_upgrade();
+        progress.step_done();
     }
 
     // 2. Execute AUR Loop (Native Builder)
-    if !aur_targets.is_empty() {
-        let _ = app.emit(
-            "update-status",
-            format!("Processing {} AUR updates...", aur_targets.len()),
-        );
+    if !set.aur.is_empty() {
         let mut built_paths = Vec::new();
-
-        for item in aur_targets {
-            let _ = app.emit("update-status", format!("Building {}...", item.name));
+        for item in &set.aur {
+            progress.start("aur", format!("Building {}...", item.name));
             match build_aur_package(&item.name, &app, &password).await {
                 Ok(paths) => built_paths.extend(paths),
                 Err(e) => {
@@ -465,9 +610,11 @@ pub async fn apply_updates(
                         "install-output",
                         format!("Failed to build {}: {}", item.name, e),
                     );
-                    // Check if we should abort or continue? Usually continue best effort.
+                    // Best effort: the other packages still get built
+                    failed.push(item.name.clone());
                 }
             }
+            progress.step_done();
         }
 
         if !built_paths.is_empty() {
@@ -477,32 +624,34 @@ pub async fn apply_updates(
     }
 
     // 3. Execute Flatpak Loop (Safety Net)
-    if !flatpak_targets.is_empty() {
-        let _ = app.emit(
-            "update-status",
-            format!("Updating {} Flatpaks...", flatpak_targets.len()),
-        );
-        for item in flatpak_targets {
-            // item.name should be App ID based on our flathub_api.rs change.
-            let _ = app.emit("install-output", format!("Updating Flatpak: {}", item.name));
-
-            // Call flatpak update <id> -y
-            // We can implement a helper or call Command direct.
-            if let Err(e) = crate::flathub_api::update_flatpak(app.clone(), item.name.clone()).await
-            {
-                let _ = app.emit("install-output", format!("Flatpak update error: {}", e));
-            }
+    for item in &set.flatpak {
+        // item.name is the App ID (flathub_api::get_updates)
+        progress.start("flatpak", format!("Updating Flatpak: {}", item.name));
+        if let Err(e) = crate::flathub_api::update_flatpak(app.clone(), item.name.clone()).await {
+            let _ = app.emit("install-output", format!("Flatpak update error: {}", e));
+            failed.push(item.name.clone());
         }
+        progress.step_done();
     }
 
-    let _ = app.emit("update-status", "All selected updates applied.");
-    let _ = app.emit(
-        "update-complete",
-        UpdateCompletePayload {
-            success: true,
-            message: "Done".into(),
-        },
-    );
+    // 4. Firmware, last: a staged capsule update wants a reboot, which also covers the above
+    for item in &set.firmware {
+        let label = item.display_name.as_deref().unwrap_or(&item.name);
+        progress.start("firmware", format!("Updating firmware: {}", label));
+        if let Err(e) = crate::fwupd_api::update_device(&app, &item.name).await {
+            let _ = app.emit("install-output", format!("Firmware update error: {}", e));
+            failed.push(label.to_string());
+        }
+        progress.step_done();
+    }
 
-    Ok("Updates applied".to_string())
+    if failed.is_empty() {
+        let _ = app.emit("update-status", "All selected updates applied.");
+        Ok("Updates applied".to_string())
+    } else {
+        Err(format!(
+            "Updates applied except: {}. See the log for details.",
+            failed.join(", ")
+        ))
+    }
 }
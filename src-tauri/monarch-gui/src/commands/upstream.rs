@@ -0,0 +1,204 @@
+//! Upstream release tracking. A package's project URL (GitHub or GitLab) is mapped to its
+//! releases or tags and compared with the packaged pkgver, so the details page can flag a
+//! repo version that lags upstream, offer the beta/nightly channel and link release notes.
+
+use crate::scm_api::UpstreamRelease;
+use serde::Serialize;
+use std::cmp::Ordering;
+use tauri::State;
+
+/// Typed response for get_upstream_releases.
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct UpstreamStatus {
+    pub pkgname: String,
+    pub upstream_url: Option<String>,
+    /// pkgver of the packaged build, without epoch or pkgrel.
+    pub packaged_version: Option<String>,
+    pub latest_stable: Option<UpstreamRelease>,
+    /// Newest pre-release that is ahead of the latest stable one (beta/nightly channel).
+    pub latest_prerelease: Option<UpstreamRelease>,
+    pub releases: Vec<UpstreamRelease>,
+    /// The packaged version is older than the latest stable upstream release.
+    pub lagging: bool,
+}
+
+/// "1:2.3.4-2" -> "2.3.4"
+pub fn pkgver_of(version: &str) -> &str {
+    let no_epoch = version.split_once(':').map_or(version, |(_, v)| v);
+    no_epoch.rsplit_once('-').map_or(no_epoch, |(v, _)| v)
+}
+
+/// Fill the channel and lag fields of `status` from its releases. Development packages
+/// build from the VCS head and are never reported as lagging.
+pub fn assess(mut status: UpstreamStatus) -> UpstreamStatus {
+    let mut versioned: Vec<&UpstreamRelease> = status
+        .releases
+        .iter()
+        .filter(|r| r.version.is_some())
+        .collect();
+    versioned.sort_by(|a, b| {
+        crate::versions::newest_first(
+            a.version.as_deref().unwrap_or_default(),
+            b.version.as_deref().unwrap_or_default(),
+        )
+    });
+
+    let stable = versioned.iter().find(|r| !r.prerelease).copied();
+    let prerelease = versioned
+        .iter()
+        .find(|r| r.prerelease)
+        .filter(|pre| match stable {
+            Some(s) => {
+                crate::versions::compare(
+                    pre.version.as_deref().unwrap_or_default(),
+                    s.version.as_deref().unwrap_or_default(),
+                ) == Ordering::Greater
+            }
+            None => true,
+        })
+        .copied();
+
+    status.lagging = match (stable, status.packaged_version.as_deref()) {
+        (Some(s), Some(packaged)) if !super::devel::is_devel_package(&status.pkgname) => {
+            crate::versions::compare(s.version.as_deref().unwrap_or_default(), packaged)
+                == Ordering::Greater
+        }
+        _ => false,
+    };
+    status.latest_stable = stable.cloned();
+    status.latest_prerelease = prerelease.cloned();
+    status
+}
+
+/// `pkg` from the synced repos, else from the AUR; its `url` is the upstream project.
+pub(crate) async fn find_package(
+    repo: &crate::repo_manager::RepoManager,
+    pkg: &str,
+) -> Option<crate::models::Package> {
+    match repo.get_package(pkg).await {
+        Some(p) => Some(p),
+        None => crate::aur_api::get_multi_info(&[pkg])
+            .await
+            .ok()
+            .and_then(|found| found.into_iter().next()),
+    }
+}
+
+/// Upstream releases for `pkg` and whether the packaged version lags behind them. Packages
+/// without a GitHub/GitLab project URL come back with no releases rather than an error.
+#[tauri::command]
+pub async fn get_upstream_releases(
+    state_repo: State<'_, crate::repo_manager::RepoManager>,
+    state_scm: State<'_, crate::ScmState>,
+    pkg: String,
+) -> Result<UpstreamStatus, String> {
+    let Some(package) = find_package(state_repo.inner(), &pkg).await else {
+        return Err(format!("Package {} not found", pkg));
+    };
+
+    let mut status = UpstreamStatus {
+        pkgname: package.name.clone(),
+        packaged_version: Some(pkgver_of(&package.version).to_string()),
+        upstream_url: package.url.clone(),
+        ..Default::default()
+    };
+    let Some(url) = package
+        .url
+        .as_deref()
+        .filter(|u| crate::scm_api::parse_scm_url(u).is_some())
+    else {
+        return Ok(status);
+    };
+    status.releases = state_scm
+        .inner()
+        .0
+        .fetch_releases(url, &package.name)
+        .await?;
+    Ok(assess(status))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scm_api::{is_prerelease_tag, normalize_tag, parse_scm_url, ScmRepo};
+
+    fn release(tag: &str, prerelease: bool) -> UpstreamRelease {
+        UpstreamRelease {
+            tag: tag.to_string(),
+            version: normalize_tag(tag, "foo"),
+            name: None,
+            url: String::new(),
+            published_at: None,
+            body: None,
+            prerelease,
+        }
+    }
+
+    #[test]
+    fn test_upstream_tags_and_lag() {
+        assert_eq!(normalize_tag("v1.2.3", "foo").as_deref(), Some("1.2.3"));
+        assert_eq!(normalize_tag("foo-2.0", "foo-bin").as_deref(), Some("2.0"));
+        assert_eq!(
+            normalize_tag("release-1_4_2", "foo").as_deref(),
+            Some("1.4.2")
+        );
+        assert_eq!(
+            normalize_tag("1.3.0-rc1", "foo").as_deref(),
+            Some("1.3.0rc1")
+        );
+        assert_eq!(normalize_tag("nightly", "foo"), None);
+        assert!(is_prerelease_tag("v1.3.0-rc1"));
+        assert!(is_prerelease_tag("2.0beta2"));
+        assert!(is_prerelease_tag("nightly"));
+        assert!(!is_prerelease_tag("predator-1.0"));
+        assert!(!is_prerelease_tag("src-2.1"));
+        assert!(!is_prerelease_tag("devtools-1.4"));
+        assert_eq!(pkgver_of("1:2.3.4-2"), "2.3.4");
+        assert_eq!(
+            parse_scm_url("https://github.com/foo/bar.git"),
+            Some(ScmRepo::GitHub {
+                owner: "foo".into(),
+                repo: "bar".into()
+            })
+        );
+        assert_eq!(
+            parse_scm_url("https://gitlab.com/group/sub/bar/-/releases"),
+            Some(ScmRepo::GitLab {
+                path: "group/sub/bar".into()
+            })
+        );
+        assert_eq!(parse_scm_url("https://foo.org"), None);
+
+        let status = assess(UpstreamStatus {
+            pkgname: "foo".into(),
+            packaged_version: Some("1.2.0".into()),
+            releases: vec![
+                release("v1.3.0-rc1", true),
+                release("v1.2.3", false),
+                release("v1.2.0", false),
+                release("nightly", true),
+            ],
+            ..Default::default()
+        });
+        assert!(status.lagging);
+        assert_eq!(status.latest_stable.unwrap().tag, "v1.2.3");
+        assert_eq!(status.latest_prerelease.unwrap().tag, "v1.3.0-rc1");
+
+        // Current packages, and -git ones whatever their pkgver, are not lagging
+        let current = assess(UpstreamStatus {
+            pkgname: "foo".into(),
+            packaged_version: Some("1.2.3".into()),
+            releases: vec![release("v1.2.3", false), release("v1.2.0-beta", true)],
+            ..Default::default()
+        });
+        assert!(!current.lagging);
+        assert!(current.latest_prerelease.is_none());
+        let devel = assess(UpstreamStatus {
+            pkgname: "foo-git".into(),
+            packaged_version: Some("1.0.r5.gabc".into()),
+            releases: vec![release("v1.2.3", false)],
+            ..Default::default()
+        });
+        assert!(!devel.lagging);
+    }
+}
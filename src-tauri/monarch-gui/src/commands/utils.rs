@@ -2,8 +2,23 @@ use crate::{chaotic_api, metadata};
 use base64::prelude::*;
 use tauri::State;
 
+/// Icon for a package as an asset-protocol URL from the icon cache (AppStream icon, fetched
+/// and converted on first use), else a data URI of a PNG in the extracted icons dir.
 #[tauri::command]
-pub async fn get_package_icon(pkg_name: String) -> Result<Option<String>, String> {
+pub async fn get_package_icon(
+    state_meta: State<'_, metadata::MetadataState>,
+    pkg_name: String,
+    size: Option<u32>,
+) -> Result<Option<String>, String> {
+    let names = [pkg_name.clone()];
+    let size = crate::icon_cache::bucket_size(size);
+    if let Some(icon) = crate::icon_cache::get_many(state_meta.inner(), &names, size)
+        .await
+        .remove(&pkg_name)
+    {
+        return Ok(Some(icon.url));
+    }
+
     let icons_dir = metadata::get_icons_dir();
     if let Ok(entries) = std::fs::read_dir(&icons_dir) {
         for entry in entries.flatten() {
@@ -35,12 +50,24 @@ pub async fn clear_cache(
 ) -> Result<(), String> {
     state_chaotic.inner().clear_cache().await;
     state_flathub.inner().clear_cache();
+    crate::screenshot_cache::clear();
+    crate::icon_cache::clear();
     state_scm.inner().0.clear_cache();
     state_repo.inner().sync_all(true, 0, None).await?;
     state_meta.inner().init(0).await;
     Ok(())
 }
 
+/// Screenshot from the local cache, downloading it first if needed. Thumbnails come back as
+/// data URIs; full-size images as a path for the asset protocol.
+#[tauri::command]
+pub async fn get_screenshot(
+    url: String,
+    thumbnail: bool,
+) -> Result<crate::screenshot_cache::CachedScreenshot, String> {
+    crate::screenshot_cache::get(&url, thumbnail).await
+}
+
 #[tauri::command]
 pub async fn launch_app(pkg_name: String) -> Result<(), String> {
     let status = std::process::Command::new("gtk-launch")
@@ -0,0 +1,241 @@
+//! OARS content ratings (`<content_rating type="oars-1.1">`) from the AppStream catalogs, the
+//! minimum age each one implies and the parental-control age limit. appstream's `Collection`
+//! does not keep the ratings, so they are read from the sanitized catalog copies in the
+//! cache after every load. Ages follow the Common Sense Media mapping libappstream uses.
+
+use crate::metadata::MetadataState;
+use crate::models::Package;
+use crate::repo_manager::RepoManager;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use tauri::State;
+
+lazy_static! {
+    static ref RE_COMPONENT: regex::Regex =
+        regex::Regex::new(r"(?s)<component\b[^>]*>(.*?)</component>")
+            .expect("valid regex RE_COMPONENT");
+    static ref RE_ID: regex::Regex =
+        regex::Regex::new(r"<id>\s*([^<]+?)\s*</id>").expect("valid regex RE_ID");
+    static ref RE_RATING: regex::Regex =
+        regex::Regex::new(r#"(?s)<content_rating\b([^>]*?)(?:/>|>(.*?)</content_rating>)"#)
+            .expect("valid regex RE_RATING");
+    static ref RE_ATTRIBUTE: regex::Regex = regex::Regex::new(
+        r#"<content_attribute\s+id="([^"]+)"\s*>\s*([a-z]+)\s*</content_attribute>"#
+    )
+    .expect("valid regex RE_ATTRIBUTE");
+    static ref RE_TYPE: regex::Regex =
+        regex::Regex::new(r#"type="([^"]+)""#).expect("valid regex RE_TYPE");
+}
+
+/// Typed response for get_content_rating; also set on `Package::content_rating`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct ContentRating {
+    /// "oars-1.0" or "oars-1.1".
+    pub kind: String,
+    /// Attribute id -> "mild" | "moderate" | "intense"; attributes rated "none" are left out.
+    pub attributes: BTreeMap<String, String>,
+    /// Youngest age the app is suitable for.
+    pub min_age: u8,
+}
+
+/// Ages for (mild, moderate, intense) per OARS attribute.
+const CSM_AGES: &[(&str, [u8; 3])] = &[
+    ("violence-cartoon", [3, 4, 6]),
+    ("violence-fantasy", [3, 7, 8]),
+    ("violence-realistic", [4, 9, 14]),
+    ("violence-bloodshed", [9, 11, 18]),
+    ("violence-sexual", [18, 18, 18]),
+    ("violence-desecration", [13, 15, 18]),
+    ("violence-slavery", [13, 15, 18]),
+    ("violence-worship", [13, 15, 18]),
+    ("drugs-alcohol", [11, 13, 16]),
+    ("drugs-narcotics", [12, 14, 17]),
+    ("drugs-tobacco", [10, 13, 13]),
+    ("sex-nudity", [12, 14, 14]),
+    ("sex-themes", [13, 14, 15]),
+    ("sex-homosexuality", [13, 14, 15]),
+    ("sex-prostitution", [12, 14, 18]),
+    ("sex-adultery", [8, 10, 18]),
+    ("sex-appearance", [10, 10, 15]),
+    ("language-profanity", [8, 11, 14]),
+    ("language-humor", [3, 8, 14]),
+    ("language-discrimination", [9, 10, 11]),
+    ("social-chat", [4, 13, 13]),
+    ("social-info", [0, 13, 13]),
+    ("social-audio", [15, 15, 15]),
+    ("social-location", [13, 13, 13]),
+    ("social-contacts", [12, 12, 12]),
+    ("money-purchasing", [12, 12, 12]),
+    ("money-gambling", [7, 10, 18]),
+];
+
+/// Minimum age for one attribute at `value`; unknown attributes count as 0.
+pub fn attribute_age(id: &str, value: &str) -> u8 {
+    let level = match value {
+        "mild" => 0,
+        "moderate" => 1,
+        "intense" => 2,
+        _ => return 0,
+    };
+    CSM_AGES
+        .iter()
+        .find(|(attr, _)| *attr == id)
+        .map(|(_, ages)| ages[level])
+        .unwrap_or(0)
+}
+
+/// Ratings in one catalog, keyed by component id. A bare `<content_rating/>` rates the app
+/// suitable for everyone.
+pub fn parse_catalog(xml: &str) -> HashMap<String, ContentRating> {
+    let mut ratings = HashMap::new();
+    for component in RE_COMPONENT.captures_iter(xml) {
+        let body = &component[1];
+        let (Some(id), Some(rating)) = (RE_ID.captures(body), RE_RATING.captures(body)) else {
+            continue;
+        };
+        let kind = RE_TYPE
+            .captures(&rating[1])
+            .map(|t| t[1].to_string())
+            .unwrap_or_else(|| "oars-1.0".to_string());
+        let attributes: BTreeMap<String, String> = rating
+            .get(2)
+            .map(|inner| {
+                RE_ATTRIBUTE
+                    .captures_iter(inner.as_str())
+                    .filter(|a| &a[2] != "none")
+                    .map(|a| (a[1].to_string(), a[2].to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let min_age = attributes
+            .iter()
+            .map(|(id, value)| attribute_age(id, value))
+            .max()
+            .unwrap_or(0);
+        ratings.insert(
+            id[1].to_string(),
+            ContentRating {
+                kind,
+                attributes,
+                min_age,
+            },
+        );
+    }
+    ratings
+}
+
+/// Ratings from the catalogs cached in `cache_dir`: distro catalogs first, then Arch, then
+/// Flathub, the first one to rate an app winning (the same order text is merged in).
+pub fn index_catalogs(cache_dir: &Path) -> HashMap<String, ContentRating> {
+    let mut files: Vec<_> = std::fs::read_dir(cache_dir.join("catalogs"))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e == "xml"))
+        .collect();
+    files.sort();
+    files.push(cache_dir.join("extra_v5.xml"));
+    files.push(cache_dir.join("flathub.xml"));
+
+    let mut index = HashMap::new();
+    for file in files {
+        let Ok(xml) = std::fs::read_to_string(&file) else {
+            continue;
+        };
+        for (id, rating) in parse_catalog(&xml) {
+            index.entry(id).or_insert(rating);
+        }
+    }
+    index
+}
+
+/// Attach ratings to `packages` and, with a parental-control limit set, drop those rated
+/// above it. Apps without OARS data stay visible: most non-game apps carry none.
+pub fn apply(packages: &mut Vec<Package>, meta: &MetadataState, max_age: Option<u8>) {
+    let Ok(loader) = meta.0.lock() else {
+        return;
+    };
+    for pkg in packages.iter_mut() {
+        let app_id = pkg.app_id.clone().or_else(|| loader.find_app_id(&pkg.name));
+        pkg.content_rating = app_id.and_then(|id| loader.content_rating(&id));
+    }
+    if let Some(limit) = max_age {
+        packages.retain(|p| p.content_rating.as_ref().is_none_or(|r| r.min_age <= limit));
+    }
+}
+
+/// OARS rating of an app; `app_id` may also be a package name. `None` when the catalog
+/// has no rating for it.
+#[tauri::command]
+pub async fn get_content_rating(
+    state_meta: State<'_, MetadataState>,
+    app_id: String,
+) -> Result<Option<ContentRating>, String> {
+    let loader = state_meta.0.lock().map_err(|e| e.to_string())?;
+    let id = loader.find_app_id(&app_id).unwrap_or(app_id);
+    Ok(loader.content_rating(&id))
+}
+
+#[tauri::command]
+pub async fn get_parental_controls(state: State<'_, RepoManager>) -> Result<Option<u8>, String> {
+    Ok(state.inner().get_max_content_age().await)
+}
+
+/// Hide apps whose content rating is above `max_age` from search and browsing; `None` turns
+/// parental controls off.
+#[tauri::command]
+pub async fn set_parental_controls(
+    state: State<'_, RepoManager>,
+    max_age: Option<u8>,
+) -> Result<(), String> {
+    if max_age.is_some_and(|age| age > 18) {
+        return Err("Age limit must be between 0 and 18".to_string());
+    }
+    state.inner().set_max_content_age(max_age).await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_catalog_ratings() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<components version="0.14">
+  <component type="desktop-application">
+    <id>org.example.Shooter</id>
+    <content_rating type="oars-1.1">
+      <content_attribute id="violence-realistic">moderate</content_attribute>
+      <content_attribute id="language-profanity">mild</content_attribute>
+      <content_attribute id="drugs-alcohol">none</content_attribute>
+    </content_rating>
+  </component>
+  <component type="desktop-application">
+    <id>org.example.Editor</id>
+    <content_rating type="oars-1.1"/>
+  </component>
+  <component type="desktop-application">
+    <id>org.example.Unrated</id>
+  </component>
+</components>"#;
+        let ratings = parse_catalog(xml);
+        assert_eq!(ratings.len(), 2);
+
+        let shooter = &ratings["org.example.Shooter"];
+        assert_eq!(shooter.kind, "oars-1.1");
+        assert_eq!(shooter.attributes.len(), 2);
+        assert_eq!(shooter.min_age, 9);
+
+        let editor = &ratings["org.example.Editor"];
+        assert!(editor.attributes.is_empty());
+        assert_eq!(editor.min_age, 0);
+
+        assert_eq!(attribute_age("violence-bloodshed", "intense"), 18);
+        assert_eq!(attribute_age("violence-bloodshed", "none"), 0);
+        assert_eq!(attribute_age("not-an-attribute", "intense"), 0);
+    }
+}
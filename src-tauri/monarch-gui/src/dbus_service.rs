@@ -0,0 +1,303 @@
+//! Session bus service `org.monarchstore.Store`, so scripts, browsers and other software
+//! centers can hand work to MonArch, plus the `monarch://` and `appstream://` URL handlers.
+//!
+//! Methods on `/org/monarchstore/Store` (interface `org.monarchstore.Store1`):
+//!
+//! - `SearchPackages(s query) -> a(ssss)`: name, version, description, repo
+//! - `GetUpdates() -> a(ssss)`: name, installed version, new version, repo, from the last
+//!   background check
+//! - `InstallPackage(s name) -> s`: open the package page with an install prompt; `name` may
+//!   also be an AppStream component id. Nothing is installed until the user confirms.
+//! - `OpenUrl(s url)`: open a `monarch://` or `appstream://` link
+//!
+//! Links look like `monarch://package/firefox`, `monarch://install/firefox` or
+//! `appstream://org.mozilla.firefox`; [`DIGEST_LINK`] opens the weekly digest page. When the
+//! desktop file launches a second instance with a link, it passes the link to the running
+//! one over `OpenUrl` and exits.
+
+use crate::repo_manager::RepoManager;
+use serde::Serialize;
+use std::sync::{Mutex, OnceLock};
+use tauri::{AppHandle, Emitter, Manager};
+
+pub const BUS_NAME: &str = "org.monarchstore.Store";
+pub const OBJECT_PATH: &str = "/org/monarchstore/Store";
+pub const INTERFACE: &str = "org.monarchstore.Store1";
+const MAX_SEARCH_RESULTS: usize = 50;
+/// Longer than any pkgname or component id.
+const MAX_ID_LEN: usize = 256;
+/// Opens the weekly digest page (linked from the digest notification).
+pub const DIGEST_LINK: &str = "monarch://digest";
+
+/// Kept for the lifetime of the app; dropping it releases the bus name.
+static CONNECTION: OnceLock<zbus::Connection> = OnceLock::new();
+
+/// Link the app was launched with, until the frontend is ready to take it.
+pub struct LaunchLink(pub Mutex<Option<String>>);
+
+/// What a link asks for, before it is resolved to a package.
+#[derive(Debug, PartialEq)]
+pub struct ParsedLink {
+    /// pkgname or AppStream component id
+    pub id: String,
+    pub install: bool,
+}
+
+/// Payload of the `open-package` event and of take_launch_link.
+#[derive(Debug, Serialize, Clone)]
+pub struct PackageLink {
+    pub package: crate::models::Package,
+    /// Show the install prompt once the page is open.
+    pub install: bool,
+}
+
+fn valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_ID_LEN
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '@' | '.' | '_' | '+' | '-'))
+}
+
+/// `monarch://package/<name>`, `monarch://install/<name>`, `monarch://<name>` and
+/// `appstream://<component id>` (also without the slashes). Query and fragment are ignored.
+pub fn parse_link(url: &str) -> Option<ParsedLink> {
+    let (scheme, rest) = url.trim().split_once(':')?;
+    let rest = rest.trim_start_matches('/');
+    let rest = rest.split(['?', '#']).next().unwrap_or_default();
+    let rest = rest.trim_end_matches('/');
+    let (id, install) = match scheme.to_ascii_lowercase().as_str() {
+        "monarch" => match rest.split_once('/') {
+            Some(("package" | "app", id)) => (id, false),
+            Some(("install", id)) => (id, true),
+            Some(_) => return None,
+            None => (rest, false),
+        },
+        "appstream" => (rest, false),
+        _ => return None,
+    };
+    valid_id(id).then(|| ParsedLink {
+        id: id.to_string(),
+        install,
+    })
+}
+
+/// `monarch://digest`, with or without the slashes and a trailing one.
+pub fn is_digest_link(url: &str) -> bool {
+    url.trim().split_once(':').is_some_and(|(scheme, rest)| {
+        scheme.eq_ignore_ascii_case("monarch")
+            && rest.trim_matches('/').eq_ignore_ascii_case("digest")
+    })
+}
+
+/// The first link among the process arguments (the desktop file passes it as `%u`).
+pub fn launch_url() -> Option<String> {
+    std::env::args()
+        .skip(1)
+        .find(|arg| is_digest_link(arg) || parse_link(arg).is_some())
+}
+
+/// Bring the window up on the digest page (the frontend handles it like the tray entry).
+fn open_digest(app: &AppHandle) -> Result<(), String> {
+    crate::tray::show_main_window(app);
+    app.emit("tray-action", "open-digest")
+        .map_err(|e| e.to_string())
+}
+
+/// Hand `url` to an instance that already owns the bus name. False if there is none, in
+/// which case this process should open the link itself.
+pub fn forward_to_running(url: &str) -> bool {
+    tauri::async_runtime::block_on(async {
+        let conn = zbus::Connection::session().await?;
+        conn.call_method(
+            Some(BUS_NAME),
+            OBJECT_PATH,
+            Some(INTERFACE),
+            "OpenUrl",
+            &(url,),
+        )
+        .await
+        .map(|_| ())
+    })
+    .is_ok()
+}
+
+/// Resolve a link to a package from the repos or the AUR; component ids go through the
+/// AppStream index first.
+async fn resolve(app: &AppHandle, link: ParsedLink) -> Result<PackageLink, String> {
+    let name = if link.id.contains('.') {
+        let state = app.state::<crate::metadata::MetadataState>();
+        let loader = state.0.lock().map_err(|e| e.to_string())?;
+        loader.resolve_package_name(&link.id)
+    } else {
+        link.id.to_lowercase()
+    };
+    let repo = app.state::<RepoManager>();
+    let package = crate::commands::upstream::find_package(repo.inner(), &name)
+        .await
+        .ok_or_else(|| format!("Package {} not found", name))?;
+    Ok(PackageLink {
+        package,
+        install: link.install,
+    })
+}
+
+/// Bring the window up on the linked package page.
+async fn open(app: &AppHandle, link: ParsedLink) -> Result<String, String> {
+    let resolved = resolve(app, link).await?;
+    let name = resolved.package.name.clone();
+    crate::tray::show_main_window(app);
+    app.emit("open-package", &resolved)
+        .map_err(|e| e.to_string())?;
+    Ok(name)
+}
+
+struct StoreInterface {
+    app: AppHandle,
+}
+
+#[zbus::interface(name = "org.monarchstore.Store1")]
+impl StoreInterface {
+    async fn search_packages(
+        &self,
+        query: &str,
+    ) -> zbus::fdo::Result<Vec<(String, String, String, String)>> {
+        let query = query.trim();
+        if query.len() < 2 {
+            return Err(zbus::fdo::Error::InvalidArgs(
+                "query must be at least 2 characters".to_string(),
+            ));
+        }
+        let repo = self.app.state::<RepoManager>();
+        let distro = self.app.state::<crate::distro_context::DistroContext>();
+        let (packages, _) = repo
+            .get_packages_matching(query, distro.inner())
+            .await
+            .map_err(zbus::fdo::Error::Failed)?;
+        Ok(packages
+            .into_iter()
+            .take(MAX_SEARCH_RESULTS)
+            .map(|p| (p.name, p.version, p.description, p.source.id))
+            .collect())
+    }
+
+    async fn get_updates(&self) -> Vec<(String, String, String, String)> {
+        crate::scheduler::get_last_update_check()
+            .await
+            .ok()
+            .flatten()
+            .map(|check| check.updates)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|u| (u.name, u.old_version, u.new_version, u.repo))
+            .collect()
+    }
+
+    /// Returns the resolved pkgname.
+    async fn install_package(&self, name: &str) -> zbus::fdo::Result<String> {
+        let name = name.trim();
+        if !valid_id(name) {
+            return Err(zbus::fdo::Error::InvalidArgs(format!(
+                "Not a package name: {}",
+                name
+            )));
+        }
+        let link = ParsedLink {
+            id: name.to_string(),
+            install: true,
+        };
+        open(&self.app, link)
+            .await
+            .map_err(zbus::fdo::Error::Failed)
+    }
+
+    async fn open_url(&self, url: &str) -> zbus::fdo::Result<()> {
+        if is_digest_link(url) {
+            return open_digest(&self.app).map_err(zbus::fdo::Error::Failed);
+        }
+        let link = parse_link(url)
+            .ok_or_else(|| zbus::fdo::Error::InvalidArgs(format!("Unsupported link: {}", url)))?;
+        open(&self.app, link)
+            .await
+            .map(|_| ())
+            .map_err(zbus::fdo::Error::Failed)
+    }
+}
+
+async fn serve(app: AppHandle) -> zbus::Result<zbus::Connection> {
+    zbus::connection::Builder::session()?
+        .name(BUS_NAME)?
+        .serve_at(OBJECT_PATH, StoreInterface { app })?
+        .build()
+        .await
+}
+
+/// Claim the bus name and serve the interface. Without a session bus (or with another
+/// instance holding the name) the app runs on without it.
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        match serve(app).await {
+            Ok(conn) => {
+                let _ = CONNECTION.set(conn);
+                log::info!("DBus service {} registered", BUS_NAME);
+            }
+            Err(e) => log::warn!("DBus service not started: {}", e),
+        }
+    });
+}
+
+/// The launch link resolved to a package, once; later links arrive as `open-package` events.
+#[tauri::command]
+pub async fn take_launch_link(
+    app: AppHandle,
+    state: tauri::State<'_, LaunchLink>,
+) -> Result<Option<PackageLink>, String> {
+    let url = state.0.lock().map_err(|e| e.to_string())?.take();
+    if url.as_deref().is_some_and(is_digest_link) {
+        open_digest(&app)?;
+        return Ok(None);
+    }
+    match url.as_deref().and_then(parse_link) {
+        Some(link) => resolve(&app, link).await.map(Some),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(id: &str, install: bool) -> Option<ParsedLink> {
+        Some(ParsedLink {
+            id: id.to_string(),
+            install,
+        })
+    }
+
+    #[test]
+    fn test_parse_link() {
+        assert_eq!(
+            parse_link("monarch://package/firefox"),
+            link("firefox", false)
+        );
+        assert_eq!(parse_link("monarch://install/gimp/"), link("gimp", true));
+        assert_eq!(parse_link("monarch:vlc"), link("vlc", false));
+        assert_eq!(
+            parse_link("appstream://org.mozilla.firefox?from=web"),
+            link("org.mozilla.firefox", false)
+        );
+        assert_eq!(
+            parse_link("APPSTREAM:org.gimp.GIMP"),
+            link("org.gimp.GIMP", false)
+        );
+        assert_eq!(parse_link("monarch://remove/firefox"), None);
+        assert_eq!(parse_link("monarch://package/foo;rm -rf"), None);
+        assert_eq!(parse_link("monarch://"), None);
+        assert!(is_digest_link("monarch://digest"));
+        assert!(is_digest_link("MonArch:digest/"));
+        assert!(!is_digest_link("monarch://package/digest"));
+        assert!(!is_digest_link("appstream://digest"));
+        assert_eq!(parse_link("https://example.com/firefox"), None);
+        assert_eq!(parse_link("firefox"), None);
+    }
+}
@@ -0,0 +1,656 @@
+//! Opt-in weekly digest: what changed over the past seven days (packages updated, the Arch
+//! security advisories those updates fixed, disk usage trend) and what still needs attention.
+//! Built from the transaction history plus a daily snapshot of installed and cache size
+//! (digest_snapshots.jsonl in the app data dir), taken only while the digest is enabled.
+//! Once a week a notification announces it, linking to the page backed by get_weekly_digest
+//! (`monarch://digest`); the tray's "Weekly digest" entry opens the same page.
+
+use crate::history::{PackageAction, PackageChange, Transaction};
+use crate::http_client;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+use tauri_plugin_store::StoreExt;
+
+const STORE_FILE: &str = "settings.json";
+const ENABLED_KEY: &str = "weekly_digest_enabled";
+const LAST_SENT_KEY: &str = "weekly_digest_last_sent";
+const SNAPSHOT_FILE: &str = "digest_snapshots.jsonl";
+const PKG_CACHE: &str = "/var/cache/pacman/pkg";
+const ADVISORIES_URL: &str = "https://security.archlinux.org/all.json";
+const WEEK_SECS: i64 = 7 * 24 * 3600;
+const SNAPSHOT_EVERY_SECS: i64 = 24 * 3600;
+/// About three months of daily snapshots.
+const MAX_SNAPSHOTS: usize = 90;
+/// A package cache past this size is listed as maintenance.
+const CACHE_WARN_BYTES: u64 = 5 * 1024 * 1024 * 1024;
+/// Later than the update scheduler so the first digest sees a fresh update check.
+const STARTUP_DELAY_SECS: u64 = 300;
+const TICK_SECS: u64 = 3600;
+/// The advisory feed is a few MB; the digest page and the weekly run share one copy.
+const ADVISORIES_TTL_SECS: u64 = 6 * 3600;
+
+/// Last advisory feed fetched, with when.
+static ADVISORIES: Lazy<tokio::sync::Mutex<Option<(Instant, Arc<Vec<AdvisoryGroup>>)>>> =
+    Lazy::new(|| tokio::sync::Mutex::new(None));
+
+/// Installed set and package cache size at one point in time.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DiskSnapshot {
+    /// Unix seconds
+    pub timestamp: i64,
+    pub package_count: usize,
+    pub installed_bytes: u64,
+    pub cache_bytes: u64,
+}
+
+/// One AVG group from security.archlinux.org (`all.json`).
+#[derive(Debug, Deserialize, Clone)]
+struct AdvisoryGroup {
+    name: String,
+    packages: Vec<String>,
+    #[serde(default)]
+    severity: String,
+    #[serde(default)]
+    fixed: Option<String>,
+    #[serde(default)]
+    issues: Vec<String>,
+    #[serde(default)]
+    advisories: Vec<String>,
+}
+
+/// An advisory one of the week's upgrades crossed the fixed version of.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct AdvisoryFix {
+    /// AVG id, e.g. "AVG-2843".
+    pub group: String,
+    pub package: String,
+    pub severity: String,
+    pub fixed_version: String,
+    /// CVE ids
+    pub issues: Vec<String>,
+    /// ASA ids
+    pub advisories: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone, Default, PartialEq)]
+pub struct DiskTrend {
+    pub installed_bytes: u64,
+    /// Change since the snapshot a week ago (or the oldest one in the week).
+    pub installed_delta: i64,
+    pub cache_bytes: u64,
+    pub cache_delta: i64,
+    /// Oldest first, for the chart.
+    pub snapshots: Vec<DiskSnapshot>,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct MaintenanceItem {
+    /// "updates", "orphans", "pacnew" or "cache"
+    pub kind: String,
+    pub title: String,
+    pub detail: String,
+}
+
+/// Typed response for get_weekly_digest
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct WeeklyDigest {
+    pub period_start: i64,
+    pub period_end: i64,
+    /// Upgrades and downgrades, one entry per package (first old → last new version).
+    pub updated: Vec<PackageChange>,
+    pub installed_count: usize,
+    pub removed_count: usize,
+    pub advisories_fixed: Vec<AdvisoryFix>,
+    /// False when the advisory feed could not be fetched.
+    pub advisories_checked: bool,
+    pub disk: DiskTrend,
+    pub maintenance: Vec<MaintenanceItem>,
+}
+
+fn snapshot_path(app: &AppHandle) -> PathBuf {
+    let path = app
+        .path()
+        .app_data_dir()
+        .unwrap_or_else(|_| PathBuf::from("."));
+    let _ = std::fs::create_dir_all(&path);
+    path.join(SNAPSHOT_FILE)
+}
+
+fn load_snapshots(app: &AppHandle) -> Vec<DiskSnapshot> {
+    std::fs::read_to_string(snapshot_path(app))
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|l| serde_json::from_str(l).ok())
+        .collect()
+}
+
+fn take_snapshot() -> DiskSnapshot {
+    let usage = crate::alpm_read::get_installed_usage_native();
+    DiskSnapshot {
+        timestamp: chrono::Utc::now().timestamp(),
+        package_count: usage.len(),
+        installed_bytes: usage.iter().map(|u| u.installed_size).sum(),
+        cache_bytes: cache_bytes(),
+    }
+}
+
+/// Size of the package files in the (flat) pacman cache.
+fn cache_bytes() -> u64 {
+    std::fs::read_dir(PKG_CACHE)
+        .map(|d| {
+            d.flatten()
+                .filter_map(|e| e.metadata().ok())
+                .filter(|m| m.is_file())
+                .map(|m| m.len())
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+/// Append a snapshot when the last one is a day old, trimming the file to MAX_SNAPSHOTS.
+fn record_snapshot_if_due(app: &AppHandle) {
+    let mut snapshots = load_snapshots(app);
+    let now = chrono::Utc::now().timestamp();
+    if snapshots
+        .last()
+        .is_some_and(|s| now - s.timestamp < SNAPSHOT_EVERY_SECS)
+    {
+        return;
+    }
+    snapshots.push(take_snapshot());
+    let start = snapshots.len().saturating_sub(MAX_SNAPSHOTS);
+    let body: String = snapshots[start..]
+        .iter()
+        .filter_map(|s| serde_json::to_string(s).ok())
+        .map(|l| l + "\n")
+        .collect();
+    let written =
+        std::fs::File::create(snapshot_path(app)).and_then(|mut f| f.write_all(body.as_bytes()));
+    if let Err(e) = written {
+        log::warn!("Failed to record disk snapshot: {}", e);
+    }
+}
+
+/// Upgrades/downgrades in `[start, end)` merged per package, plus install and removal counts.
+/// `history` is newest first, as load_history returns it.
+fn changes_in(history: &[Transaction], start: i64, end: i64) -> (Vec<PackageChange>, usize, usize) {
+    let mut updated: Vec<PackageChange> = Vec::new();
+    let (mut installed, mut removed) = (0, 0);
+    for tx in history
+        .iter()
+        .rev()
+        .filter(|t| t.timestamp >= start && t.timestamp < end)
+    {
+        for c in &tx.changes {
+            match c.action {
+                PackageAction::Installed => installed += 1,
+                PackageAction::Removed => removed += 1,
+                PackageAction::Reinstalled => {}
+                PackageAction::Upgraded | PackageAction::Downgraded => {
+                    match updated.iter_mut().find(|u| u.name == c.name) {
+                        Some(u) => u.new_version = c.new_version.clone(),
+                        None => updated.push(c.clone()),
+                    }
+                }
+            }
+        }
+    }
+    // Net direction once merged (an upgrade rolled back the same week ends up a downgrade)
+    for u in &mut updated {
+        if let (Some(old), Some(new)) = (&u.old_version, &u.new_version) {
+            u.action = match crate::versions::compare(old, new) {
+                std::cmp::Ordering::Greater => PackageAction::Downgraded,
+                _ => PackageAction::Upgraded,
+            };
+        }
+    }
+    updated.sort_by(|a, b| a.name.cmp(&b.name));
+    (updated, installed, removed)
+}
+
+/// Advisories whose fixed version an update moved a package onto: old < fixed <= new.
+fn advisories_fixed(groups: &[AdvisoryGroup], updated: &[PackageChange]) -> Vec<AdvisoryFix> {
+    use std::cmp::Ordering;
+    let by_name: HashMap<&str, &PackageChange> =
+        updated.iter().map(|u| (u.name.as_str(), u)).collect();
+    let mut out = Vec::new();
+    for g in groups {
+        let Some(fixed) = g.fixed.as_deref() else {
+            continue;
+        };
+        for pkg in &g.packages {
+            let Some(change) = by_name.get(pkg.as_str()) else {
+                continue;
+            };
+            let (Some(old), Some(new)) = (&change.old_version, &change.new_version) else {
+                continue;
+            };
+            if crate::versions::compare(old, fixed) == Ordering::Less
+                && crate::versions::compare(new, fixed) != Ordering::Less
+            {
+                out.push(AdvisoryFix {
+                    group: g.name.clone(),
+                    package: pkg.clone(),
+                    severity: g.severity.clone(),
+                    fixed_version: fixed.to_string(),
+                    issues: g.issues.clone(),
+                    advisories: g.advisories.clone(),
+                });
+            }
+        }
+    }
+    out
+}
+
+async fn fetch_advisories() -> Result<Vec<AdvisoryGroup>, String> {
+    let client = http_client::client(Duration::from_secs(20));
+    let resp = http_client::fetch(client.get(ADVISORIES_URL)).await?;
+    if !resp.is_success() {
+        return Err(format!("security.archlinux.org returned {}", resp.status));
+    }
+    resp.json()
+}
+
+/// The advisory feed, refetched once the cached copy is older than ADVISORIES_TTL_SECS. A
+/// failed refetch falls back to the stale copy.
+async fn advisories() -> Result<Arc<Vec<AdvisoryGroup>>, String> {
+    let mut cached = ADVISORIES.lock().await;
+    if let Some((at, groups)) = cached.as_ref() {
+        if at.elapsed().as_secs() < ADVISORIES_TTL_SECS {
+            return Ok(groups.clone());
+        }
+    }
+    match fetch_advisories().await {
+        Ok(groups) => {
+            let groups = Arc::new(groups);
+            *cached = Some((Instant::now(), groups.clone()));
+            Ok(groups)
+        }
+        Err(e) => match cached.as_ref() {
+            Some((_, stale)) => {
+                log::warn!("Advisory feed refresh failed, using the cached copy: {}", e);
+                Ok(stale.clone())
+            }
+            None => Err(e),
+        },
+    }
+}
+
+/// Latest snapshot against the newest one at least a week older (else the oldest in range).
+fn disk_trend(snapshots: &[DiskSnapshot], period_start: i64) -> DiskTrend {
+    let Some(latest) = snapshots.last() else {
+        return DiskTrend::default();
+    };
+    let baseline = snapshots
+        .iter()
+        .rev()
+        .find(|s| s.timestamp <= period_start)
+        .or_else(|| snapshots.first())
+        .unwrap_or(latest);
+    DiskTrend {
+        installed_bytes: latest.installed_bytes,
+        installed_delta: latest.installed_bytes as i64 - baseline.installed_bytes as i64,
+        cache_bytes: latest.cache_bytes,
+        cache_delta: latest.cache_bytes as i64 - baseline.cache_bytes as i64,
+        snapshots: snapshots
+            .iter()
+            .filter(|s| s.timestamp >= baseline.timestamp)
+            .cloned()
+            .collect(),
+    }
+}
+
+fn maintenance_items(
+    pending_updates: usize,
+    orphans: usize,
+    pacnew: &[String],
+    cache_bytes: u64,
+) -> Vec<MaintenanceItem> {
+    let mut items = Vec::new();
+    if pending_updates > 0 {
+        items.push(MaintenanceItem {
+            kind: "updates".to_string(),
+            title: format!("{} update(s) waiting", pending_updates),
+            detail: "Install them from the Updates page.".to_string(),
+        });
+    }
+    if orphans > 0 {
+        items.push(MaintenanceItem {
+            kind: "orphans".to_string(),
+            title: format!("{} orphaned package(s)", orphans),
+            detail: "Dependencies nothing needs any more; they can be removed.".to_string(),
+        });
+    }
+    if !pacnew.is_empty() {
+        items.push(MaintenanceItem {
+            kind: "pacnew".to_string(),
+            title: format!("{} unmerged .pacnew file(s)", pacnew.len()),
+            detail: pacnew.join(", "),
+        });
+    }
+    if cache_bytes > CACHE_WARN_BYTES {
+        items.push(MaintenanceItem {
+            kind: "cache".to_string(),
+            title: format!(
+                "Package cache is {:.1} GiB",
+                cache_bytes as f64 / (1024.0 * 1024.0 * 1024.0)
+            ),
+            detail: "Clearing old package versions frees the space.".to_string(),
+        });
+    }
+    items
+}
+
+/// Notification body, e.g. "12 packages updated, 2 security advisories fixed, 1 item needs
+/// attention".
+fn summary(digest: &WeeklyDigest) -> String {
+    let plural =
+        |n: usize, one: &str, many: &str| format!("{} {}", n, if n == 1 { one } else { many });
+    let mut parts = vec![format!(
+        "{} updated",
+        plural(digest.updated.len(), "package", "packages")
+    )];
+    if !digest.advisories_fixed.is_empty() {
+        parts.push(format!(
+            "{} fixed",
+            plural(
+                digest.advisories_fixed.len(),
+                "security advisory",
+                "security advisories"
+            )
+        ));
+    }
+    if !digest.maintenance.is_empty() {
+        parts.push(format!(
+            "{} attention",
+            plural(digest.maintenance.len(), "item needs", "items need")
+        ));
+    }
+    parts.join(", ")
+}
+
+async fn build_digest(app: &AppHandle) -> Result<WeeklyDigest, String> {
+    let period_end = chrono::Utc::now().timestamp();
+    let period_start = period_end - WEEK_SECS;
+    let handle = app.clone();
+    let enabled = is_enabled(app);
+    let (history, snapshots, orphans) = tokio::task::spawn_blocking(move || {
+        if enabled {
+            record_snapshot_if_due(&handle);
+        }
+        (
+            crate::history::load_history(&handle),
+            load_snapshots(&handle),
+            crate::alpm_read::get_orphans_native().len(),
+        )
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?;
+
+    let (updated, installed_count, removed_count) = changes_in(&history, period_start, period_end);
+    let (advisories_fixed, advisories_checked) = match advisories().await {
+        Ok(groups) => (advisories_fixed(&groups, &updated), true),
+        Err(e) => {
+            log::warn!("Weekly digest: advisory feed unavailable: {}", e);
+            (Vec::new(), false)
+        }
+    };
+    let disk = disk_trend(&snapshots, period_start);
+    let pending_updates = crate::scheduler::get_last_update_check()
+        .await?
+        .map(|r| r.updates.len())
+        .unwrap_or(0);
+    let pacnew = crate::commands::package::get_pacnew_warnings()
+        .await
+        .unwrap_or_default();
+    let maintenance = maintenance_items(pending_updates, orphans, &pacnew, disk.cache_bytes);
+
+    Ok(WeeklyDigest {
+        period_start,
+        period_end,
+        updated,
+        installed_count,
+        removed_count,
+        advisories_fixed,
+        advisories_checked,
+        disk,
+        maintenance,
+    })
+}
+
+fn is_enabled(app: &AppHandle) -> bool {
+    app.store(STORE_FILE)
+        .ok()
+        .and_then(|s| s.get(ENABLED_KEY))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+async fn send_if_due(app: &AppHandle) {
+    let Ok(store) = app.store(STORE_FILE) else {
+        return;
+    };
+    let now = chrono::Utc::now().timestamp();
+    let last_sent = store.get(LAST_SENT_KEY).and_then(|v| v.as_i64());
+    match last_sent {
+        Some(t) if now - t < WEEK_SECS => return,
+        // First week after opting in: start collecting snapshots, send next week
+        None => {
+            store.set(LAST_SENT_KEY, serde_json::json!(now));
+            let _ = store.save();
+            return;
+        }
+        _ => {}
+    }
+
+    let digest = match build_digest(app).await {
+        Ok(d) => d,
+        Err(e) => {
+            log::warn!("Weekly digest failed: {}", e);
+            return;
+        }
+    };
+    store.set(LAST_SENT_KEY, serde_json::json!(now));
+    let _ = store.save();
+
+    let _ = app.emit("weekly-digest", &digest);
+    let repo_manager = app.state::<crate::repo_manager::RepoManager>();
+    if repo_manager.is_notifications_enabled().await {
+        let _ = app
+            .notification()
+            .builder()
+            .title("MonArch: Your weekly digest")
+            .body(format!(
+                "{}. <a href=\"{}\">Open the digest</a> or use the tray menu.",
+                summary(&digest),
+                crate::dbus_service::DIGEST_LINK
+            ))
+            .show();
+    }
+}
+
+/// Spawn the digest loop: while enabled, a disk snapshot every day and the digest every week.
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(STARTUP_DELAY_SECS)).await;
+        loop {
+            if is_enabled(&app) {
+                let handle = app.clone();
+                let _ = tokio::task::spawn_blocking(move || record_snapshot_if_due(&handle)).await;
+                send_if_due(&app).await;
+            }
+            tokio::time::sleep(Duration::from_secs(TICK_SECS)).await;
+        }
+    });
+}
+
+/// The past seven days, computed now (not the copy sent with the last notification).
+#[tauri::command]
+pub async fn get_weekly_digest(app: AppHandle) -> Result<WeeklyDigest, String> {
+    build_digest(&app).await
+}
+
+#[tauri::command]
+pub async fn is_weekly_digest_enabled(app: AppHandle) -> Result<bool, String> {
+    Ok(is_enabled(&app))
+}
+
+/// Opt in or out. The first digest goes out a week after opting in.
+#[tauri::command]
+pub async fn set_weekly_digest_enabled(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    store.set(ENABLED_KEY, serde_json::json!(enabled));
+    if enabled {
+        store.set(
+            LAST_SENT_KEY,
+            serde_json::json!(chrono::Utc::now().timestamp()),
+        );
+    }
+    store.save().map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(
+        name: &str,
+        action: PackageAction,
+        old: Option<&str>,
+        new: Option<&str>,
+    ) -> PackageChange {
+        PackageChange {
+            name: name.to_string(),
+            action,
+            old_version: old.map(str::to_string),
+            new_version: new.map(str::to_string),
+        }
+    }
+
+    fn tx(timestamp: i64, changes: Vec<PackageChange>) -> Transaction {
+        Transaction {
+            id: format!("log-{}", timestamp),
+            timestamp,
+            source: "pacman".to_string(),
+            command: None,
+            status: "completed".to_string(),
+            changes,
+            config_backups: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_week_changes_and_fixed_advisories() {
+        // Newest first
+        let history = vec![
+            tx(
+                500,
+                vec![change(
+                    "openssl",
+                    PackageAction::Upgraded,
+                    Some("3.0.8-1"),
+                    Some("3.0.9-1"),
+                )],
+            ),
+            tx(
+                300,
+                vec![
+                    change(
+                        "openssl",
+                        PackageAction::Upgraded,
+                        Some("3.0.7-1"),
+                        Some("3.0.8-1"),
+                    ),
+                    change("vlc", PackageAction::Installed, None, Some("3.0.20-1")),
+                    change("gimp", PackageAction::Removed, Some("2.10.36-1"), None),
+                ],
+            ),
+            tx(
+                10,
+                vec![change(
+                    "curl",
+                    PackageAction::Upgraded,
+                    Some("8.0-1"),
+                    Some("8.1-1"),
+                )],
+            ),
+        ];
+        let (updated, installed, removed) = changes_in(&history, 100, 1000);
+        assert_eq!((installed, removed), (1, 1));
+        assert_eq!(
+            updated,
+            vec![change(
+                "openssl",
+                PackageAction::Upgraded,
+                Some("3.0.7-1"),
+                Some("3.0.9-1")
+            )]
+        );
+
+        let groups: Vec<AdvisoryGroup> = serde_json::from_str(
+            r#"[
+              {"name": "AVG-1", "packages": ["openssl", "lib32-openssl"], "status": "Fixed",
+               "severity": "High", "affected": "3.0.7-1", "fixed": "3.0.8-1",
+               "issues": ["CVE-2023-0286"], "advisories": ["ASA-202302-1"]},
+              {"name": "AVG-2", "packages": ["openssl"], "status": "Fixed",
+               "severity": "Low", "affected": "3.0.5-1", "fixed": "3.0.6-1",
+               "issues": ["CVE-2022-1"], "advisories": []},
+              {"name": "AVG-3", "packages": ["openssl"], "status": "Vulnerable",
+               "severity": "Medium", "affected": "3.0.9-1", "fixed": null,
+               "issues": ["CVE-2023-2"], "advisories": []}
+            ]"#,
+        )
+        .unwrap();
+        let fixed = advisories_fixed(&groups, &updated);
+        assert_eq!(fixed.len(), 1);
+        assert_eq!(fixed[0].group, "AVG-1");
+        assert_eq!(fixed[0].package, "openssl");
+        assert_eq!(fixed[0].issues, vec!["CVE-2023-0286"]);
+    }
+
+    #[test]
+    fn test_disk_trend_and_summary() {
+        let snap = |timestamp: i64, installed: u64, cache: u64| DiskSnapshot {
+            timestamp,
+            package_count: 100,
+            installed_bytes: installed,
+            cache_bytes: cache,
+        };
+        let snapshots = vec![snap(0, 900, 50), snap(100, 1000, 100), snap(200, 1200, 80)];
+        let trend = disk_trend(&snapshots, 150);
+        assert_eq!(trend.installed_bytes, 1200);
+        assert_eq!(trend.installed_delta, 200);
+        assert_eq!(trend.cache_delta, -20);
+        assert_eq!(trend.snapshots.len(), 2);
+        assert_eq!(disk_trend(&[], 0), DiskTrend::default());
+
+        let digest = WeeklyDigest {
+            period_start: 0,
+            period_end: WEEK_SECS,
+            updated: vec![change(
+                "openssl",
+                PackageAction::Upgraded,
+                Some("1"),
+                Some("2"),
+            )],
+            installed_count: 0,
+            removed_count: 0,
+            advisories_fixed: Vec::new(),
+            advisories_checked: true,
+            disk: trend,
+            maintenance: maintenance_items(3, 0, &["/etc/pacman.conf.pacnew".to_string()], 0),
+        };
+        assert_eq!(digest.maintenance.len(), 2);
+        assert_eq!(
+            summary(&digest),
+            "1 package updated, 2 items need attention"
+        );
+    }
+}
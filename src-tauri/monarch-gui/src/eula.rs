@@ -0,0 +1,221 @@
+//! License acknowledgement for packages that effectively require accepting an upstream
+//! EULA (NVIDIA driver, Steam, proprietary AUR binaries). Flagged packages come from the
+//! curated data/eula_manifest.json; acceptances are stored in eula-acknowledgements.json
+//! (and journaled in the transaction history) and checked before fresh installs only, so
+//! updates never re-prompt.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use tauri::{AppHandle, Manager};
+
+const EULA_MANIFEST_JSON: &str = include_str!("../data/eula_manifest.json");
+
+#[derive(Debug, Deserialize)]
+struct EulaEntry {
+    id: String,
+    /// Bump to require acceptance again on the next fresh install.
+    revision: u32,
+    title: String,
+    url: String,
+    summary: String,
+    packages: Vec<String>,
+}
+
+static EULA_MANIFEST: LazyLock<Vec<EulaEntry>> = LazyLock::new(|| {
+    serde_json::from_str(EULA_MANIFEST_JSON).unwrap_or_else(|e| {
+        log::error!("Invalid eula_manifest.json: {}", e);
+        Vec::new()
+    })
+});
+
+fn entry_for(pkg_name: &str) -> Option<&'static EulaEntry> {
+    EULA_MANIFEST
+        .iter()
+        .find(|e| e.packages.iter().any(|p| p == pkg_name))
+}
+
+/// Shown to the user before installing a flagged package.
+#[derive(Debug, Serialize, Clone)]
+pub struct EulaPrompt {
+    pub eula_id: String,
+    pub revision: u32,
+    pub package: String,
+    pub title: String,
+    pub url: String,
+    pub summary: String,
+}
+
+/// One recorded acceptance.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EulaAcknowledgement {
+    pub eula_id: String,
+    pub revision: u32,
+    pub package: String,
+    pub accepted_at: i64,
+}
+
+fn acknowledgements_path(app: &AppHandle) -> PathBuf {
+    let mut path = app
+        .path()
+        .app_data_dir()
+        .unwrap_or_else(|_| PathBuf::from("."));
+    if !path.exists() {
+        let _ = fs::create_dir_all(&path);
+    }
+    path.push("eula-acknowledgements.json");
+    path
+}
+
+fn load_acknowledgements(app: &AppHandle) -> Vec<EulaAcknowledgement> {
+    fs::read_to_string(acknowledgements_path(app))
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+/// Accepting a EULA once covers every package sharing it (e.g. nvidia + nvidia-utils).
+fn is_acknowledged(acks: &[EulaAcknowledgement], entry: &EulaEntry) -> bool {
+    acks.iter()
+        .any(|a| a.eula_id == entry.id && a.revision >= entry.revision)
+}
+
+fn pending_prompt(acks: &[EulaAcknowledgement], pkg_name: &str) -> Option<EulaPrompt> {
+    let entry = entry_for(pkg_name)?;
+    if is_acknowledged(acks, entry) {
+        return None;
+    }
+    Some(EulaPrompt {
+        eula_id: entry.id.clone(),
+        revision: entry.revision,
+        package: pkg_name.to_string(),
+        title: entry.title.clone(),
+        url: entry.url.clone(),
+        summary: entry.summary.clone(),
+    })
+}
+
+/// The prompt a fresh install of `pkg_name` needs. Already-installed packages (reinstalls,
+/// updates) need none. Reads the local DB, so call it off the async runtime.
+fn prompt_before_install(app: &AppHandle, pkg_name: &str) -> Option<EulaPrompt> {
+    if entry_for(pkg_name).is_none() || crate::alpm_read::is_package_installed(pkg_name) {
+        return None;
+    }
+    pending_prompt(&load_acknowledgements(app), pkg_name)
+}
+
+/// Install-time guard used by install_package_core and the list import.
+pub(crate) async fn require_acknowledged(app: &AppHandle, pkg_name: &str) -> Result<(), String> {
+    if entry_for(pkg_name).is_none() {
+        return Ok(());
+    }
+    let (app, name) = (app.clone(), pkg_name.to_string());
+    let prompt = tokio::task::spawn_blocking(move || prompt_before_install(&app, &name))
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?;
+    match prompt {
+        Some(prompt) => Err(format!(
+            "EULA_REQUIRED: {} requires accepting the {} before installation.",
+            pkg_name, prompt.title
+        )),
+        None => Ok(()),
+    }
+}
+
+/// The acknowledgement the user still has to give before installing `pkg_name`, if any.
+#[tauri::command]
+pub async fn get_eula_prompt(
+    app: AppHandle,
+    pkg_name: String,
+) -> Result<Option<EulaPrompt>, String> {
+    crate::utils::validate_package_name(&pkg_name)?;
+    if entry_for(&pkg_name).is_none() {
+        return Ok(None);
+    }
+    tokio::task::spawn_blocking(move || prompt_before_install(&app, &pkg_name))
+        .await
+        .map_err(|e| format!("Task join error: {}", e))
+}
+
+/// Record that the user accepted the EULA shown for `pkg_name`.
+#[tauri::command]
+pub async fn accept_eula(
+    app: AppHandle,
+    pkg_name: String,
+    eula_id: String,
+) -> Result<EulaAcknowledgement, String> {
+    crate::utils::validate_package_name(&pkg_name)?;
+    let entry = entry_for(&pkg_name)
+        .filter(|e| e.id == eula_id)
+        .ok_or_else(|| format!("No license agreement '{}' for {}", eula_id, pkg_name))?;
+
+    let ack = EulaAcknowledgement {
+        eula_id: entry.id.clone(),
+        revision: entry.revision,
+        package: pkg_name,
+        accepted_at: chrono::Utc::now().timestamp(),
+    };
+    let mut acks = load_acknowledgements(&app);
+    acks.push(ack.clone());
+    let content = serde_json::to_string_pretty(&acks).map_err(|e| e.to_string())?;
+    fs::write(acknowledgements_path(&app), content).map_err(|e| e.to_string())?;
+    if let Err(e) = crate::history::record_license_acceptance(
+        &app,
+        &ack.package,
+        &entry.title,
+        ack.revision,
+        ack.accepted_at,
+    ) {
+        log::warn!("Failed to journal the license acceptance: {}", e);
+    }
+    log::info!(
+        "EULA '{}' (rev {}) accepted for {}",
+        ack.eula_id,
+        ack.revision,
+        ack.package
+    );
+    Ok(ack)
+}
+
+/// Every recorded acceptance, oldest first.
+#[tauri::command]
+pub async fn get_eula_acknowledgements(app: AppHandle) -> Result<Vec<EulaAcknowledgement>, String> {
+    Ok(load_acknowledgements(&app))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompt_until_acknowledged() {
+        assert!(!EULA_MANIFEST.is_empty());
+        for entry in EULA_MANIFEST.iter() {
+            assert!(entry.url.starts_with("https://"));
+            for package in &entry.packages {
+                assert!(crate::utils::validate_package_name(package).is_ok());
+            }
+        }
+
+        assert!(pending_prompt(&[], "firefox").is_none());
+        let prompt = pending_prompt(&[], "nvidia-dkms").expect("nvidia is flagged");
+        assert_eq!(prompt.eula_id, "nvidia-driver");
+
+        let acks = vec![EulaAcknowledgement {
+            eula_id: "nvidia-driver".to_string(),
+            revision: 1,
+            package: "nvidia".to_string(),
+            accepted_at: 0,
+        }];
+        // Shared agreement: accepting for nvidia covers nvidia-utils
+        assert!(pending_prompt(&acks, "nvidia-utils").is_none());
+        assert!(pending_prompt(&acks, "steam").is_some());
+
+        let stale = vec![EulaAcknowledgement {
+            revision: 0,
+            ..acks[0].clone()
+        }];
+        assert!(pending_prompt(&stale, "nvidia").is_some());
+    }
+}
@@ -1,5 +1,4 @@
 use crate::models::{PackageSource, UpdateItem};
-use reqwest;
 use serde::{Deserialize, Serialize};
 use serde_json;
 use std::collections::HashMap;
@@ -15,6 +14,7 @@ pub async fn get_updates() -> Result<Vec<UpdateItem>, String> {
             "--app",
             "--columns=application,version,installed-size,name",
         ])
+        .envs(crate::network_settings::proxy_env())
         .output()
         .await
         .map_err(|e| format!("Failed to run flatpak: {}", e))?;
@@ -45,6 +45,7 @@ pub async fn get_updates() -> Result<Vec<UpdateItem>, String> {
 
             updates.push(UpdateItem {
                 name,
+                display_name: None,
                 current_version: "Unknown".to_string(), // Filled below
                 new_version: new_version.clone(),
                 source: PackageSource::new(
@@ -55,6 +56,7 @@ pub async fn get_updates() -> Result<Vec<UpdateItem>, String> {
                 ),
                 size,
                 icon: None,
+                downgrade: false,
             });
         }
     }
@@ -91,6 +93,43 @@ async fn get_installed_versions() -> Result<HashMap<String, String>, String> {
     Ok(map)
 }
 
+/// An installed Flatpak application as reported by `flatpak list --app`.
+#[derive(Debug, Serialize, Clone)]
+pub struct InstalledFlatpak {
+    pub app_id: String,
+    pub name: String,
+    pub version: String,
+}
+
+/// List installed Flatpak apps (empty if flatpak is not installed).
+pub async fn get_installed_apps() -> Vec<InstalledFlatpak> {
+    let output = match tokio::process::Command::new("flatpak")
+        .args(["list", "--app", "--columns=application,name,version"])
+        .env("LC_ALL", "C")
+        .output()
+        .await
+    {
+        Ok(o) if o.status.success() => o,
+        _ => return Vec::new(),
+    };
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split('\t').collect();
+            let app_id = parts.first()?.trim();
+            if app_id.is_empty() {
+                return None;
+            }
+            Some(InstalledFlatpak {
+                app_id: app_id.to_string(),
+                name: parts.get(1).map(|s| s.trim()).unwrap_or(app_id).to_string(),
+                version: parts.get(2).map(|s| s.trim()).unwrap_or("").to_string(),
+            })
+        })
+        .collect()
+}
+
 /// Flathub API client for fetching rich app metadata
 /// This is used as a METADATA SOURCE only - we don't install Flatpaks
 
@@ -295,25 +334,25 @@ impl FlathubApiClient {
         let url = "https://flathub.org/api/v2/search";
 
         // We use a short timeout because search is on the critical path for metadata loading
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(3))
+        let client = crate::http_client::builder(std::time::Duration::from_secs(3))
             .build()
             .ok()?;
 
         // Use POST for search with standard JSON payload
-        let response = client
-            .post(url)
-            .json(&serde_json::json!({ "query": query }))
-            .send()
-            .await
-            .ok()?;
+        let response = crate::http_client::fetch(
+            client
+                .post(url)
+                .json(&serde_json::json!({ "query": query })),
+        )
+        .await
+        .ok()?;
 
-        if !response.status().is_success() {
+        if !response.is_success() {
             return None;
         }
 
         // Get text first to handle variable response format
-        let body_text = response.text().await.ok()?;
+        let body_text = response.text();
 
         // Strategy 1: Try as Array of SearchResult
         let hits_opts: Option<Vec<SearchResult>> = serde_json::from_str(&body_text).ok();
@@ -344,21 +383,20 @@ impl FlathubApiClient {
         // Fetch from Flathub API
         let url = format!("https://flathub.org/api/v2/appstream/{}", app_id);
 
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(2))
+        let client = crate::http_client::builder(std::time::Duration::from_secs(2))
             .build()
             .ok()?;
 
-        let response = client.get(&url).send().await.ok()?;
+        let response = crate::http_client::fetch(client.get(&url)).await.ok()?;
 
-        if !response.status().is_success() {
+        if !response.is_success() {
             if let Ok(mut cache) = self.cache.lock() {
                 cache.insert(app_id.to_string(), None);
             }
             return None;
         }
 
-        let mut metadata: FlathubMetadata = response.json().await.ok()?;
+        let mut metadata: FlathubMetadata = response.json().ok()?;
 
         // Ensure ID is populated (API usually returns it in body, but if not, inject it)
         if metadata.id.is_none() {
@@ -480,6 +518,7 @@ async fn run_flatpak_command(
 
     // Clean env to avoid localization issues in parsing if we parse, but for logging it's fine.
     command.env("LC_ALL", "C");
+    command.envs(crate::network_settings::proxy_env());
 
     let _ = app.emit(
         "build://log",
@@ -0,0 +1,158 @@
+//! Firmware updates through fwupd (`fwupdmgr`), the LVFS pipeline GNOME Software and Discover
+//! use too. fwupd asks polkit for authorization on its own, so the helper is not involved.
+
+use crate::models::{PackageSource, UpdateItem};
+use serde::Deserialize;
+use std::process::Stdio;
+use tauri::{AppHandle, Emitter};
+use tokio::io::AsyncBufReadExt;
+
+/// `fwupdmgr get-updates` exits with this when no device has an update.
+const EXIT_NOTHING_TO_DO: i32 = 2;
+
+#[derive(Debug, Deserialize)]
+struct FwupdUpdates {
+    #[serde(rename = "Devices", default)]
+    devices: Vec<FwupdDevice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FwupdDevice {
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "DeviceId")]
+    device_id: String,
+    #[serde(rename = "Version", default)]
+    version: Option<String>,
+    /// Newest first.
+    #[serde(rename = "Releases", default)]
+    releases: Vec<FwupdRelease>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FwupdRelease {
+    #[serde(rename = "Version")]
+    version: String,
+    #[serde(rename = "Size", default)]
+    size: Option<u64>,
+}
+
+/// The DeviceId is the item name, so apply_updates knows what to flash; the device's own
+/// name goes to display_name.
+fn parse_updates(json: &str) -> Result<Vec<UpdateItem>, String> {
+    let parsed: FwupdUpdates = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    Ok(parsed
+        .devices
+        .into_iter()
+        .filter_map(|d| {
+            let release = d.releases.into_iter().next()?;
+            Some(UpdateItem {
+                name: d.device_id,
+                display_name: Some(d.name),
+                current_version: d.version.unwrap_or_else(|| "Unknown".to_string()),
+                source: PackageSource::new("firmware", "lvfs", &release.version, "Firmware (LVFS)"),
+                new_version: release.version,
+                size: release.size,
+                icon: None,
+                downgrade: false,
+            })
+        })
+        .collect())
+}
+
+/// Firmware updates for this machine. Empty when fwupd isn't installed.
+pub async fn get_updates() -> Result<Vec<UpdateItem>, String> {
+    if which::which("fwupdmgr").is_err() {
+        return Ok(vec![]);
+    }
+    let output = tokio::process::Command::new("fwupdmgr")
+        .args(["get-updates", "--json", "--no-unreported-check"])
+        .env("LC_ALL", "C")
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run fwupdmgr: {}", e))?;
+
+    if output.status.code() == Some(EXIT_NOTHING_TO_DO) {
+        return Ok(vec![]);
+    }
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    parse_updates(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Flash the newest release for one device, streaming fwupdmgr's output to install-output.
+/// Firmware that needs a reboot is staged; fwupd applies it on the next boot.
+pub async fn update_device(app: &AppHandle, device_id: &str) -> Result<(), String> {
+    let mut child = tokio::process::Command::new("fwupdmgr")
+        .args(["update", device_id, "--assume-yes", "--no-reboot-check"])
+        .env("LC_ALL", "C")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to start fwupdmgr: {}", e))?;
+
+    let stdout = child.stdout.take().ok_or("fwupdmgr has no stdout")?;
+    let stderr = child.stderr.take().ok_or("fwupdmgr has no stderr")?;
+    let app_out = app.clone();
+    let out = tokio::spawn(async move {
+        let mut lines = tokio::io::BufReader::new(stdout).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let _ = app_out.emit("install-output", format!("[Firmware] {}", line.trim()));
+        }
+    });
+    let mut err_lines = tokio::io::BufReader::new(stderr).lines();
+    let mut last_error = String::new();
+    while let Ok(Some(line)) = err_lines.next_line().await {
+        let _ = app.emit("install-output", format!("[Firmware] ERR: {}", line.trim()));
+        last_error = line.trim().to_string();
+    }
+
+    let status = child.wait().await.map_err(|e| e.to_string())?;
+    let _ = out.await;
+    if status.success() {
+        Ok(())
+    } else if last_error.is_empty() {
+        Err(format!("fwupdmgr exited with {}", status))
+    } else {
+        Err(last_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_get_updates_json() {
+        let json = r#"{
+          "Devices": [
+            {
+              "Name": "UEFI dbx",
+              "DeviceId": "362301da643102b9f38477387e2193e57abaa590",
+              "Version": "217",
+              "Releases": [
+                { "Version": "371", "Size": 24576 },
+                { "Version": "283", "Size": 20480 }
+              ]
+            },
+            {
+              "Name": "Thunderbolt Controller",
+              "DeviceId": "6f5d4e5b2e0f6b1c8a3a1e7f0d9c4b2a1e3f5d7c",
+              "Version": "55.00",
+              "Releases": []
+            }
+          ]
+        }"#;
+        let items = parse_updates(json).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].name, "362301da643102b9f38477387e2193e57abaa590");
+        assert_eq!(items[0].display_name.as_deref(), Some("UEFI dbx"));
+        assert_eq!(items[0].current_version, "217");
+        assert_eq!(items[0].new_version, "371");
+        assert_eq!(items[0].size, Some(24576));
+        assert_eq!(items[0].source.source_type, "firmware");
+
+        assert!(parse_updates(r#"{"Devices": []}"#).unwrap().is_empty());
+    }
+}
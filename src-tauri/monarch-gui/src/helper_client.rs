@@ -3,8 +3,9 @@ use serde::{Deserialize, Serialize};
 use std::process::Stdio;
 use std::sync::Mutex;
 use std::time::{Duration, Instant};
-use tauri::{AppHandle, Emitter};
-use tokio::io::{AsyncBufReadExt, BufReader as TokioBufReader};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_store::StoreExt;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader as TokioBufReader};
 
 /// Minimum interval between helper invocations (debounce) to mitigate DoS from rapid/spam invokes.
 const HELPER_DEBOUNCE: Duration = Duration::from_millis(800);
@@ -26,6 +27,8 @@ mod tests {
             enabled_repos: vec!["core".to_string(), "chaotic-aur".to_string()],
             cpu_optimization: Some("v3".to_string()),
             target_repo: None,
+            phase: Default::default(),
+            dry_run: false,
         };
 
         let json = serde_json::to_string(&cmd).expect("Should serialize");
@@ -44,6 +47,8 @@ mod tests {
             enabled_repos: vec!["cachyos".to_string()],
             cpu_optimization: None,
             target_repo: None,
+            phase: Default::default(),
+            dry_run: false,
         };
 
         let json = serde_json::to_string(&cmd).expect("Should serialize");
@@ -59,6 +64,56 @@ mod tests {
         assert!(parsed.is_ok(), "File content should be valid JSON");
     }
 
+    #[test]
+    fn test_agent_token_and_settings_defaults() {
+        let token = super::new_agent_token().expect("Should read /dev/urandom");
+        assert_eq!(token.len(), 64);
+        assert!(token.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(token, super::new_agent_token().unwrap());
+
+        // Opt-in: nothing stays running unless the user asked for it
+        let settings = super::PrivilegedAgentSettings::default();
+        assert!(!settings.enabled);
+        assert_eq!(settings.idle_timeout_secs, 600);
+    }
+
+    #[test]
+    fn test_parse_capabilities() {
+        use super::parse_capabilities;
+        use monarch_types::HelperCapabilities;
+
+        let current = HelperCapabilities::current("0.4.0");
+        let stdout = format!("{}\n", serde_json::to_string(&current).unwrap());
+        assert_eq!(parse_capabilities(stdout.as_bytes()), current);
+
+        // A helper without the flag exits NOT_ROOT (or errors) and prints no JSON
+        assert_eq!(parse_capabilities(b""), HelperCapabilities::legacy());
+        assert_eq!(
+            parse_capabilities(b"Error: Invalid command argument\n"),
+            HelperCapabilities::legacy()
+        );
+    }
+
+    #[test]
+    fn test_fallback_result_for_old_helpers() {
+        use super::{exit_code, fallback_result};
+        use std::os::unix::process::ExitStatusExt;
+
+        let clean = fallback_result(Some(std::process::ExitStatus::from_raw(0)), false);
+        assert!(clean.success);
+        assert!(clean.error.is_none());
+
+        // Old helpers exit 0 after sending an error event
+        let errored = fallback_result(Some(std::process::ExitStatus::from_raw(0)), true);
+        assert!(!errored.success);
+        assert_eq!(errored.exit_code, exit_code::FAILED);
+
+        // Raw wait status: exit code 4 is 4 << 8
+        let locked = fallback_result(Some(std::process::ExitStatus::from_raw(4 << 8)), false);
+        assert_eq!(locked.exit_code, exit_code::DB_LOCKED);
+        assert!(!locked.success);
+    }
+
     #[test]
     fn test_no_raw_strings_in_commands() {
         let repos = vec!["cachyos".to_string()];
@@ -68,6 +123,8 @@ mod tests {
             enabled_repos: repos,
             cpu_optimization: None,
             target_repo: None,
+            phase: Default::default(),
+            dry_run: false,
         };
 
         let json = serde_json::to_string(&cmd).expect("Should serialize");
@@ -77,44 +134,18 @@ mod tests {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-#[serde(tag = "command", content = "payload")]
-pub enum HelperCommand {
-    // ✅ NEW: Full ALPM Transactions
-    AlpmInstall {
-        packages: Vec<String>,
-        sync_first: bool,
-        enabled_repos: Vec<String>,
-        cpu_optimization: Option<String>,
-        target_repo: Option<String>,
-    },
-    CheckUpdatesSafe {
-        enabled_repos: Vec<String>,
-    },
-    AlpmUninstall {
-        packages: Vec<String>,
-        remove_deps: bool,
-    },
-    AlpmUpgrade {
-        packages: Option<Vec<String>>,
-        enabled_repos: Vec<String>,
-    },
-    AlpmSync {
-        enabled_repos: Vec<String>,
-    },
-    AlpmInstallFiles {
-        paths: Vec<String>,
-    },
-    // ✅ NEW: Atomic Batch Transaction (Operation Silent Guard)
-    ExecuteBatch {
-        manifest: crate::models::TransactionManifest,
-    },
-}
+pub use monarch_types::{
+    exit_code, HelperCapabilities, HelperCommand, PackageOutcome, RescueAction, TransactionResult,
+};
+use monarch_types::{AgentCall, AgentConfig, AgentRequest, PostTransactionNote};
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ProgressMessage {
     pub progress: u8,
     pub message: String,
+    /// event_type of the structured helper event this came from (None for legacy lines).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event_type: Option<String>,
 }
 
 /// Temp file prefix for helper command (helper deletes after reading).
@@ -122,6 +153,248 @@ const CMD_FILE_PREFIX: &str = "monarch-cmd-";
 /// Use /var/tmp so both the app and root (sudo) see the same path.
 const CMD_FILE_DIR: &str = "/var/tmp";
 
+const STORE_FILE: &str = "settings.json";
+const AGENT_SETTINGS_KEY: &str = "privileged_agent";
+/// Agent config file prefix (helper deletes after reading); it holds the token, so it is 0600.
+const AGENT_CONFIG_PREFIX: &str = "monarch-agent-";
+/// pkexec exit codes for a dismissed or failed authentication dialog.
+const PKEXEC_NOT_AUTHORIZED: [i32; 2] = [126, 127];
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PrivilegedAgentSettings {
+    /// Keep an authenticated helper running so later operations don't prompt again.
+    pub enabled: bool,
+    /// The agent exits after this many seconds without an operation.
+    pub idle_timeout_secs: u64,
+}
+
+impl Default for PrivilegedAgentSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            idle_timeout_secs: 600,
+        }
+    }
+}
+
+pub(crate) fn read_agent_settings(app: &AppHandle) -> PrivilegedAgentSettings {
+    app.store(STORE_FILE)
+        .ok()
+        .and_then(|s| s.get(AGENT_SETTINGS_KEY))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+pub(crate) fn write_agent_settings(
+    app: &AppHandle,
+    settings: &PrivilegedAgentSettings,
+) -> Result<(), String> {
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    store.set(
+        AGENT_SETTINGS_KEY,
+        serde_json::to_value(settings).map_err(|e| e.to_string())?,
+    );
+    store.save().map_err(|e| e.to_string())
+}
+
+/// Token of the agent this GUI started. An agent left over from an earlier session can't be
+/// used; a new one replaces its socket and the old one idles out.
+static AGENT_TOKEN: Lazy<tokio::sync::Mutex<Option<String>>> =
+    Lazy::new(|| tokio::sync::Mutex::new(None));
+
+enum AgentFailure {
+    /// The user dismissed the authentication dialog; don't show another one.
+    Declined(String),
+    /// No agent could be reached or started; run the helper directly.
+    Unavailable(String),
+}
+
+fn new_agent_token() -> Result<String, String> {
+    use std::io::Read;
+    let mut bytes = [0u8; 32];
+    std::fs::File::open("/dev/urandom")
+        .and_then(|mut f| f.read_exact(&mut bytes))
+        .map_err(|e| format!("Could not create an agent token: {}", e))?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+async fn agent_request(token: &str, call: AgentCall) -> Result<tokio::net::UnixStream, String> {
+    let socket = monarch_types::agent_socket_path(unsafe { libc::getuid() });
+    let mut stream = tokio::net::UnixStream::connect(&socket)
+        .await
+        .map_err(|e| format!("{}: {}", socket, e))?;
+    let request = AgentRequest {
+        token: token.to_string(),
+        call,
+    };
+    let line = serde_json::to_string(&request).map_err(|e| e.to_string())?;
+    stream
+        .write_all(format!("{}\n", line).as_bytes())
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(stream)
+}
+
+/// True when our agent is up and accepts `token`.
+async fn agent_alive(token: &str) -> bool {
+    let Ok(stream) = agent_request(token, AgentCall::Ping).await else {
+        return false;
+    };
+    let mut lines = TokioBufReader::new(stream).lines();
+    match tokio::time::timeout(Duration::from_secs(2), lines.next_line()).await {
+        Ok(Ok(Some(line))) => {
+            serde_json::from_str::<crate::alpm_progress::AlpmProgressEvent>(&line)
+                .is_ok_and(|e| e.event_type == "agent_ready")
+        }
+        _ => false,
+    }
+}
+
+/// Start an agent through pkexec (the one authentication prompt) and return its token.
+async fn start_agent(
+    app: &AppHandle,
+    settings: &PrivilegedAgentSettings,
+) -> Result<String, AgentFailure> {
+    let token = new_agent_token().map_err(AgentFailure::Unavailable)?;
+    let config = AgentConfig {
+        token: token.clone(),
+        idle_timeout_secs: settings.idle_timeout_secs,
+    };
+    let json =
+        serde_json::to_string(&config).map_err(|e| AgentFailure::Unavailable(e.to_string()))?;
+    let ts = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    let path =
+        std::path::Path::new(CMD_FILE_DIR).join(format!("{}{}.json", AGENT_CONFIG_PREFIX, ts));
+    {
+        use std::io::Write;
+        use std::os::unix::fs::OpenOptionsExt;
+        std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(&path)
+            .and_then(|mut f| f.write_all(json.as_bytes()))
+            .map_err(|e| {
+                AgentFailure::Unavailable(format!("Failed to write agent config: {}", e))
+            })?;
+    }
+    let helper_bin = helper_binary().map_err(|e| {
+        let _ = std::fs::remove_file(&path);
+        AgentFailure::Unavailable(e)
+    })?;
+    let _ = app.emit(
+        "helper-output",
+        format!("[Client]: Starting privileged agent: {}", helper_bin),
+    );
+
+    let output = tokio::process::Command::new("pkexec")
+        .arg("--disable-internal-agent")
+        .arg(&helper_bin)
+        .arg("--agent")
+        .arg(&path)
+        .stdin(Stdio::null())
+        .output()
+        .await;
+    let _ = std::fs::remove_file(&path);
+    let output =
+        output.map_err(|e| AgentFailure::Unavailable(format!("Failed to spawn pkexec: {}", e)))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    for line in stdout.lines() {
+        let _ = app.emit("helper-output", format!("[Agent]: {}", line));
+    }
+    let ready = stdout.lines().any(|line| {
+        serde_json::from_str::<crate::alpm_progress::AlpmProgressEvent>(line)
+            .is_ok_and(|e| e.event_type == "agent_ready")
+    });
+    match output.status.code() {
+        Some(code) if PKEXEC_NOT_AUTHORIZED.contains(&code) => Err(AgentFailure::Declined(
+            "Authentication was cancelled or failed".to_string(),
+        )),
+        Some(exit_code::SUCCESS) if ready => Ok(token),
+        code => Err(AgentFailure::Unavailable(format!(
+            "Privileged agent did not start (exit {:?})",
+            code
+        ))),
+    }
+}
+
+/// Run `cmd` on the privileged agent, starting one first when ours isn't running.
+async fn run_via_agent(
+    app: &AppHandle,
+    cmd: &HelperCommand,
+    settings: &PrivilegedAgentSettings,
+) -> Result<
+    (
+        tokio::sync::mpsc::Receiver<ProgressMessage>,
+        tokio::sync::oneshot::Receiver<TransactionResult>,
+    ),
+    AgentFailure,
+> {
+    let token = {
+        let mut current = AGENT_TOKEN.lock().await;
+        match current.clone() {
+            Some(token) if agent_alive(&token).await => token,
+            _ => {
+                let token = start_agent(app, settings).await?;
+                *current = Some(token.clone());
+                token
+            }
+        }
+    };
+    let stream = agent_request(&token, AgentCall::Run(cmd.clone()))
+        .await
+        .map_err(AgentFailure::Unavailable)?;
+    let _ = app.emit("helper-output", "[Client]: Running on the privileged agent");
+
+    let (tx, rx) = tokio::sync::mpsc::channel(100);
+    crate::alpm_progress::reset_downloads();
+    let (result_tx, result_rx) = tokio::sync::oneshot::channel();
+    let a = app.clone();
+    tokio::spawn(async move {
+        let (result, saw_error) = forward_output(&a, stream, tx.clone()).await;
+        if result.is_none() {
+            let _ = tx
+                .send(ProgressMessage {
+                    progress: 0,
+                    message: "Error: The privileged agent stopped before finishing".to_string(),
+                    event_type: None,
+                })
+                .await;
+        }
+        drop(tx);
+        let _ = result_tx.send(result.unwrap_or_else(|| fallback_result(None, saw_error)));
+    });
+    Ok((rx, result_rx))
+}
+
+/// True when a privileged operation can run without showing an authentication dialog:
+/// one-click installs are authorized by the polkit rule, or our agent is already up. Used
+/// by background work, which must never pop up a prompt on its own.
+pub(crate) async fn runs_unattended(app: &AppHandle) -> bool {
+    if app
+        .state::<crate::repo_manager::RepoManager>()
+        .is_one_click_enabled()
+        .await
+    {
+        return true;
+    }
+    match AGENT_TOKEN.lock().await.clone() {
+        Some(token) => agent_alive(&token).await,
+        None => false,
+    }
+}
+
+/// Ask our agent to exit now: the setting changed, or the app is quitting.
+pub async fn stop_agent() {
+    if let Some(token) = AGENT_TOKEN.lock().await.take() {
+        let _ = agent_request(&token, AgentCall::Shutdown).await;
+    }
+}
+
 /// When password is provided: use sudo -S so user entered password once (e.g. onboarding "reduce prompts").
 /// When password is None: use pkexec so Polkit policy applies (one system prompt per call, or none if rules allow).
 pub async fn invoke_helper(
@@ -129,6 +402,244 @@ pub async fn invoke_helper(
     cmd: HelperCommand,
     password: Option<String>,
 ) -> Result<tokio::sync::mpsc::Receiver<ProgressMessage>, String> {
+    spawn_helper(app, cmd, password).await.map(|(rx, _)| rx)
+}
+
+/// Run `cmd` to completion, passing each progress line to `on_progress`, and return the
+/// helper's per-package summary. Use `result.success` / `outcome_of` rather than reading the
+/// local DB afterwards to find out what happened.
+pub async fn run_helper(
+    app: &AppHandle,
+    cmd: HelperCommand,
+    password: Option<String>,
+    mut on_progress: impl FnMut(&ProgressMessage),
+) -> Result<TransactionResult, String> {
+    let (mut rx, result) = spawn_helper(app, cmd, password).await?;
+    while let Some(msg) = rx.recv().await {
+        on_progress(&msg);
+    }
+    // The state watcher refreshes it too, but may not be running
+    crate::installed_state::invalidate();
+    result
+        .await
+        .map_err(|_| "Helper result was lost".to_string())
+}
+
+/// For helpers that predate `transaction_result`: they exit 0 even on failure, so an error
+/// event is the only sign.
+pub(crate) fn fallback_result(
+    status: Option<std::process::ExitStatus>,
+    saw_error: bool,
+) -> TransactionResult {
+    let code = match status {
+        Some(s) => s.code().unwrap_or(exit_code::FAILED),
+        None => exit_code::FAILED,
+    };
+    let success = code == exit_code::SUCCESS && !saw_error;
+    TransactionResult {
+        success,
+        error: (!success).then(|| {
+            if saw_error {
+                "The helper reported an error".to_string()
+            } else {
+                format!("Helper exited with code {}", code)
+            }
+        }),
+        exit_code: if success {
+            code
+        } else {
+            code.max(exit_code::FAILED)
+        },
+        ..Default::default()
+    }
+}
+
+/// Pass one structured helper event on to `tx` and the app's events. Returns the run's
+/// summary when this is the final `transaction_result` event.
+pub(crate) async fn forward_event(
+    app: &AppHandle,
+    event: crate::alpm_progress::AlpmProgressEvent,
+    tx: &tokio::sync::mpsc::Sender<ProgressMessage>,
+    saw_error: &mut bool,
+) -> Option<TransactionResult> {
+    // The summary is for callers, not the log
+    if event.event_type == "transaction_result" {
+        let parsed = serde_json::from_str::<TransactionResult>(&event.message).ok()?;
+        let _ = app.emit("transaction-result", &parsed);
+        return Some(parsed);
+    }
+    // Hook and install-script warnings go to the summary dialog, not the log
+    if event.event_type == "post_transaction_notes" {
+        if let Ok(notes) = serde_json::from_str::<Vec<PostTransactionNote>>(&event.message) {
+            let _ = app.emit("post-transaction-notes", &notes);
+        }
+        return None;
+    }
+    // Only the helper's own error events count; package names and log text can say "error"
+    *saw_error |= event.event_type == "error";
+    // Emit structured ALPM event
+    crate::alpm_progress::record_download_event(&event);
+    let _ = app.emit("alpm-progress", &event);
+    // When helper sends event_type "error", message is JSON of ClassifiedError; emit for recovery UI
+    if event.event_type == "error" {
+        if let Ok(classified) = serde_json::from_str::<serde_json::Value>(&event.message) {
+            let _ = app.emit("install-error-classified", &classified);
+        }
+    }
+    // Also convert to ProgressMessage for backward compatibility
+    let msg = ProgressMessage {
+        progress: event.percent.unwrap_or(0),
+        message: event.message,
+        event_type: Some(event.event_type),
+    };
+    let _ = tx.send(msg).await;
+    None
+}
+
+/// Pass the helper's event lines on to `tx` and the app's events. Yields the helper's
+/// transaction_result, if it sent one, and whether it sent an error event.
+async fn forward_output(
+    app: &AppHandle,
+    output: impl tokio::io::AsyncRead + Unpin,
+    tx: tokio::sync::mpsc::Sender<ProgressMessage>,
+) -> (Option<TransactionResult>, bool) {
+    let mut result: Option<TransactionResult> = None;
+    let mut saw_error = false;
+    let reader = TokioBufReader::new(output);
+    let mut lines = reader.lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.starts_with('{') {
+            // Try to parse as AlpmProgressEvent first (new structured events)
+            if let Ok(event) =
+                serde_json::from_str::<crate::alpm_progress::AlpmProgressEvent>(&line)
+            {
+                let is_result = event.event_type == "transaction_result";
+                if let Some(parsed) = forward_event(app, event, &tx, &mut saw_error).await {
+                    result = Some(parsed);
+                }
+                if is_result {
+                    continue;
+                }
+            } else if let Ok(msg) = serde_json::from_str::<ProgressMessage>(&line) {
+                // Legacy ProgressMessage format
+                saw_error |= msg.event_type.as_deref() == Some("error");
+                let _ = tx.send(msg).await;
+            }
+        }
+        let _ = app.emit("helper-output", format!("[Helper]: {}", line));
+    }
+    (result, saw_error)
+}
+
+/// Negotiated capabilities, keyed by helper path and mtime so a helper upgraded while the
+/// app is open gets asked again.
+struct NegotiatedCapabilities {
+    helper: String,
+    modified: Option<std::time::SystemTime>,
+    caps: HelperCapabilities,
+}
+
+static CAPABILITIES: Lazy<tokio::sync::Mutex<Option<NegotiatedCapabilities>>> =
+    Lazy::new(|| tokio::sync::Mutex::new(None));
+
+/// What the installed helper understands. Asked once via `monarch-helper --capabilities`
+/// (no root, no prompt) and cached; a helper that predates the handshake counts as
+/// [`HelperCapabilities::legacy`].
+pub async fn capabilities() -> HelperCapabilities {
+    let Ok(bin) = helper_binary() else {
+        return HelperCapabilities::legacy();
+    };
+    let mtime = std::fs::metadata(&bin).and_then(|m| m.modified()).ok();
+    let mut cached = CAPABILITIES.lock().await;
+    if let Some(entry) = cached.as_ref() {
+        if entry.helper == bin && entry.modified == mtime {
+            return entry.caps.clone();
+        }
+    }
+    let probe = tokio::process::Command::new(&bin)
+        .arg("--capabilities")
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .output();
+    // Don't cache a helper we couldn't run; the next call tries again
+    let Ok(Ok(output)) = tokio::time::timeout(Duration::from_secs(5), probe).await else {
+        return HelperCapabilities::legacy();
+    };
+    let caps = parse_capabilities(&output.stdout);
+    *cached = Some(NegotiatedCapabilities {
+        helper: bin,
+        modified: mtime,
+        caps: caps.clone(),
+    });
+    caps
+}
+
+pub async fn supports(command: &str) -> bool {
+    capabilities().await.supports(command)
+}
+
+fn parse_capabilities(stdout: &[u8]) -> HelperCapabilities {
+    String::from_utf8_lossy(stdout)
+        .lines()
+        .find_map(|line| serde_json::from_str(line.trim()).ok())
+        .unwrap_or_else(HelperCapabilities::legacy)
+}
+
+/// Helper binary to run: the production path when it exists (the Polkit policy's exec.path
+/// only matches that), the freshly built dev helper in debug builds.
+fn helper_binary() -> Result<String, String> {
+    // Helper selection: Prefer PRODUCTION path when it exists so Polkit policy (exec.path) matches.
+    // Policy allows only /usr/lib/monarch-store/monarch-helper; dev path causes auth failure.
+    let mut helper_bin = crate::utils::MONARCH_PK_HELPER.to_string();
+    let production_path = std::path::Path::new(crate::utils::MONARCH_PK_HELPER);
+    let force_production = std::env::var("MONARCH_USE_PRODUCTION_HELPER").as_deref() == Ok("1");
+    let dev_helper_path =
+        crate::utils::get_dev_helper_path().map(|p| p.to_string_lossy().to_string());
+
+    if force_production && production_path.exists() {
+        helper_bin = crate::utils::MONARCH_PK_HELPER.to_string();
+    } else if cfg!(debug_assertions) {
+        // DEV MODE: Always prefer local dev helper if available, ignoring installed production helper.
+        // This ensures developers are running the code they just modified.
+        if let Some(dev) = dev_helper_path {
+            helper_bin = dev;
+        } else {
+            // If dev helper missing, fallback to prod if exists, otherwise error
+            if production_path.exists() {
+                helper_bin = crate::utils::MONARCH_PK_HELPER.to_string();
+            } else {
+                let cwd = std::env::current_dir().unwrap_or_default();
+                let exe = std::env::current_exe().unwrap_or_default();
+                return Err(format!(
+                    "Dev helper not found and no production fallback. Build it first: run 'npm run tauri dev' or build monarch-helper. (cwd={}, exe={})",
+                    cwd.display(),
+                    exe.display()
+                ));
+            }
+        }
+    } else if production_path.exists() {
+        // RELEASE/INSTALLED: Use production helper
+        helper_bin = crate::utils::MONARCH_PK_HELPER.to_string();
+    } else if let Some(dev) = dev_helper_path {
+        // Fallback for standalone release binary running near a dev helper? Unlikely but safe.
+        helper_bin = dev;
+    }
+    // else: helper_bin stays MONARCH_PK_HELPER (spawn will fail if missing)
+    Ok(helper_bin)
+}
+
+async fn spawn_helper(
+    app: &AppHandle,
+    cmd: HelperCommand,
+    password: Option<String>,
+) -> Result<
+    (
+        tokio::sync::mpsc::Receiver<ProgressMessage>,
+        tokio::sync::oneshot::Receiver<TransactionResult>,
+    ),
+    String,
+> {
     // SECURITY: Debounce to limit rapid helper invocations (mitigates DoS from malformed/spam calls).
     let wait_duration = {
         let mut guard = LAST_HELPER_INVOKE.lock().map_err(|e| e.to_string())?;
@@ -154,6 +665,55 @@ pub async fn invoke_helper(
         }
     }
 
+    // Refuse up front rather than have an older helper fail to parse the command
+    let caps = capabilities().await;
+    if !caps.supports(cmd.name()) {
+        return Err(format!(
+            "The installed monarch-helper (protocol {}, version {}) does not support {}. Update monarch-store ('pacman -Syu monarch-store') so the helper matches this app.",
+            caps.protocol_version,
+            caps.helper_version.as_deref().unwrap_or("unknown"),
+            cmd.name()
+        ));
+    }
+
+    // With the agent on, only its first start asks for authentication
+    if password.is_none() {
+        let settings = read_agent_settings(app);
+        if settings.enabled {
+            match run_via_agent(app, &cmd, &settings).await {
+                Ok(channels) => return Ok(channels),
+                Err(AgentFailure::Declined(e)) => return Err(e),
+                Err(AgentFailure::Unavailable(e)) => {
+                    let _ = app.emit(
+                        "helper-output",
+                        format!(
+                            "[Client]: Privileged agent unavailable ({}); running the helper directly",
+                            e
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    if caps.rpc {
+        let helper_bin = helper_binary()?;
+        let _ = app.emit(
+            "helper-output",
+            format!(
+                "[Client]: Helper: {} | Transport: JSON-RPC | Auth: {}",
+                helper_bin,
+                if password.is_some() {
+                    "sudo (password on the socket)"
+                } else {
+                    "pkexec (Polkit)"
+                }
+            ),
+        );
+        return crate::helper_rpc::spawn(app, &helper_bin, cmd, password).await;
+    }
+
+    // Helpers without --rpc: command file + argv[1]
     let json = serde_json::to_string(&cmd).map_err(|e| e.to_string())?;
 
     // CRITICAL: Always pass command via temp file + argv[1]. pkexec does NOT reliably forward
@@ -197,44 +757,13 @@ pub async fn invoke_helper(
     }
     let cmd_path = path.canonicalize().unwrap_or(path);
 
-    // Helper selection: Prefer PRODUCTION path when it exists so Polkit policy (exec.path) matches.
-    // Policy allows only /usr/lib/monarch-store/monarch-helper; dev path causes auth failure.
-    let mut helper_bin = crate::utils::MONARCH_PK_HELPER.to_string();
-    let production_path = std::path::Path::new(crate::utils::MONARCH_PK_HELPER);
-    let force_production = std::env::var("MONARCH_USE_PRODUCTION_HELPER").as_deref() == Ok("1");
-    let dev_helper_path =
-        crate::utils::get_dev_helper_path().map(|p| p.to_string_lossy().to_string());
-
-    if force_production && production_path.exists() {
-        helper_bin = crate::utils::MONARCH_PK_HELPER.to_string();
-    } else if cfg!(debug_assertions) {
-        // DEV MODE: Always prefer local dev helper if available, ignoring installed production helper.
-        // This ensures developers are running the code they just modified.
-        if let Some(dev) = dev_helper_path {
-            helper_bin = dev;
-        } else {
-            // If dev helper missing, fallback to prod if exists, otherwise error
-            if production_path.exists() {
-                helper_bin = crate::utils::MONARCH_PK_HELPER.to_string();
-            } else {
-                let _ = std::fs::remove_file(&cmd_path);
-                let cwd = std::env::current_dir().unwrap_or_default();
-                let exe = std::env::current_exe().unwrap_or_default();
-                return Err(format!(
-                    "Dev helper not found and no production fallback. Build it first: run 'npm run tauri dev' or build monarch-helper. (cwd={}, exe={})",
-                    cwd.display(),
-                    exe.display()
-                ));
-            }
+    let helper_bin = match helper_binary() {
+        Ok(bin) => bin,
+        Err(e) => {
+            let _ = std::fs::remove_file(&cmd_path);
+            return Err(e);
         }
-    } else if production_path.exists() {
-        // RELEASE/INSTALLED: Use production helper
-        helper_bin = crate::utils::MONARCH_PK_HELPER.to_string();
-    } else if let Some(dev) = dev_helper_path {
-        // Fallback for standalone release binary running near a dev helper? Unlikely but safe.
-        helper_bin = dev;
-    }
-    // else: helper_bin stays MONARCH_PK_HELPER (spawn will fail if missing)
+    };
 
     let use_password = password.is_some();
     let _ = app.emit(
@@ -285,6 +814,7 @@ pub async fn invoke_helper(
         })?;
 
     let (tx, rx) = tokio::sync::mpsc::channel(100);
+    crate::alpm_progress::reset_downloads();
 
     // Command is always delivered via file (argv[1]). Stdin is only used for sudo password when provided.
     if let Some(mut stdin) = child.stdin.take() {
@@ -298,43 +828,12 @@ pub async fn invoke_helper(
         stdin.shutdown().await.ok();
     }
 
-    if let Some(stdout) = child.stdout.take() {
+    let (result_tx, result_rx) = tokio::sync::oneshot::channel();
+    let stdout_task = child.stdout.take().map(|stdout| {
         let a = app.clone();
         let tx_stdout = tx.clone();
-        tokio::spawn(async move {
-            let reader = TokioBufReader::new(stdout);
-            let mut lines = reader.lines();
-            while let Ok(Some(line)) = lines.next_line().await {
-                if line.starts_with('{') {
-                    // Try to parse as AlpmProgressEvent first (new structured events)
-                    if let Ok(event) =
-                        serde_json::from_str::<crate::alpm_progress::AlpmProgressEvent>(&line)
-                    {
-                        // Emit structured ALPM event
-                        let _ = a.emit("alpm-progress", &event);
-                        // When helper sends event_type "error", message is JSON of ClassifiedError; emit for recovery UI
-                        if event.event_type == "error" {
-                            if let Ok(classified) =
-                                serde_json::from_str::<serde_json::Value>(&event.message)
-                            {
-                                let _ = a.emit("install-error-classified", &classified);
-                            }
-                        }
-                        // Also convert to ProgressMessage for backward compatibility
-                        let msg = ProgressMessage {
-                            progress: event.percent.unwrap_or(0),
-                            message: event.message,
-                        };
-                        let _ = tx_stdout.send(msg).await;
-                    } else if let Ok(msg) = serde_json::from_str::<ProgressMessage>(&line) {
-                        // Legacy ProgressMessage format
-                        let _ = tx_stdout.send(msg).await;
-                    }
-                }
-                let _ = a.emit("helper-output", format!("[Helper]: {}", line));
-            }
-        });
-    }
+        tokio::spawn(async move { forward_output(&a, stdout, tx_stdout).await })
+    });
 
     if let Some(stderr) = child.stderr.take() {
         let a = app.clone();
@@ -348,18 +847,27 @@ pub async fn invoke_helper(
     }
 
     tokio::spawn(async move {
-        let status = child.wait().await;
-        if let Ok(s) = status {
-            if !s.success() {
-                let _ = tx
-                    .send(ProgressMessage {
-                        progress: 0,
-                        message: format!("Error: Helper process exited with status {}", s),
-                    })
-                    .await;
-            }
+        let status = child.wait().await.ok();
+        if let Some(s) = status.filter(|s| !s.success()) {
+            let reason = s
+                .code()
+                .map(|c| format!("{} ({})", c, exit_code::describe(c)))
+                .unwrap_or_else(|| s.to_string());
+            let _ = tx
+                .send(ProgressMessage {
+                    progress: 0,
+                    message: format!("Error: Helper process exited with status {}", reason),
+                    event_type: None,
+                })
+                .await;
         }
+        drop(tx);
+        let (result, saw_error) = match stdout_task {
+            Some(task) => task.await.unwrap_or_default(),
+            None => (None, false),
+        };
+        let _ = result_tx.send(result.unwrap_or_else(|| fallback_result(status, saw_error)));
     });
 
-    Ok(rx)
+    Ok((rx, result_rx))
 }
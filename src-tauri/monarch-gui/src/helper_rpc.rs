@@ -0,0 +1,418 @@
+//! GUI side of `monarch-helper --rpc` (wire format in `monarch_types::rpc`). One helper
+//! process serves every run until it has been idle for [`SESSION_IDLE`]: it is started on a
+//! socketpair whose far end becomes its stdin and stdout through pkexec or `sudo -S`, and
+//! each run is a request whose events and result come back tagged with its id. Back-to-back
+//! operations therefore share one authentication and queue inside the helper instead of
+//! racing each other for the pacman lock, and a cancel reaches exactly the runs it names.
+
+use crate::helper_client::{fallback_result, forward_event, ProgressMessage};
+use monarch_types::rpc::{
+    error_code, RpcCall, RpcMessage, RpcRequest, RpcResponse, RpcResult, READY_EVENT,
+};
+use monarch_types::{exit_code, HelperCommand, TransactionResult};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::os::fd::OwnedFd;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::{mpsc, oneshot};
+
+/// A session with nothing queued closes after this long; the next run starts (and
+/// authenticates) a new helper.
+const SESSION_IDLE: Duration = Duration::from_secs(60);
+
+/// Request ids are unique for the app's lifetime, not just per session.
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// The open session, if any. Held across a session start so two runs don't both prompt.
+static SESSION: Lazy<tokio::sync::Mutex<Option<mpsc::UnboundedSender<Outgoing>>>> =
+    Lazy::new(|| tokio::sync::Mutex::new(None));
+
+/// A run waiting for its result.
+struct Run {
+    /// Kept so the run can be re-sent to a new session.
+    cmd: HelperCommand,
+    tx: mpsc::Sender<ProgressMessage>,
+    result: oneshot::Sender<TransactionResult>,
+    saw_error: bool,
+}
+
+/// What the rest of the app hands the session task.
+enum Outgoing {
+    Run {
+        id: u64,
+        run: Run,
+    },
+    /// Cancel the run the helper is executing; replies with how many were asked (0 or 1).
+    CancelActive(oneshot::Sender<usize>),
+}
+
+/// Ask the helper to cancel the command it is running. Runs queued behind it are re-sent to
+/// a new session once the helper has exited. Returns how many were asked; the caller falls
+/// back to the cancel file when none were.
+pub async fn cancel_active() -> usize {
+    let Some(session) = SESSION.lock().await.clone() else {
+        return 0;
+    };
+    let (reply_tx, reply_rx) = oneshot::channel();
+    if session.send(Outgoing::CancelActive(reply_tx)).is_err() {
+        return 0;
+    }
+    reply_rx.await.unwrap_or(0)
+}
+
+/// Run `cmd` on the open session, starting `helper_bin --rpc` first when there is none.
+/// Same channels as a one-shot helper run.
+pub async fn spawn(
+    app: &AppHandle,
+    helper_bin: &str,
+    cmd: HelperCommand,
+    password: Option<String>,
+) -> Result<
+    (
+        mpsc::Receiver<ProgressMessage>,
+        oneshot::Receiver<TransactionResult>,
+    ),
+    String,
+> {
+    let (tx, rx) = mpsc::channel(100);
+    let (result_tx, result_rx) = oneshot::channel();
+    let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+    let outgoing = Outgoing::Run {
+        id,
+        run: Run {
+            cmd,
+            tx,
+            result: result_tx,
+            saw_error: false,
+        },
+    };
+    crate::alpm_progress::reset_downloads();
+
+    dispatch(app, helper_bin, password, outgoing)
+        .await
+        .map_err(|(e, _)| e)?;
+    Ok((rx, result_rx))
+}
+
+/// Hand `outgoing` to the open session, starting one when there is none. On failure the
+/// message comes back with the error.
+async fn dispatch(
+    app: &AppHandle,
+    helper_bin: &str,
+    password: Option<String>,
+    mut outgoing: Outgoing,
+) -> Result<(), (String, Outgoing)> {
+    let mut session = SESSION.lock().await;
+    // A session that just idled out refuses the run; hand it to a fresh one
+    if let Some(open) = session.as_ref() {
+        match open.send(outgoing) {
+            Ok(()) => return Ok(()),
+            Err(mpsc::error::SendError(back)) => outgoing = back,
+        }
+    }
+    let started = match start_session(app, helper_bin, password) {
+        Ok(started) => started,
+        Err(e) => return Err((e, outgoing)),
+    };
+    let _ = app.emit(
+        "helper-output",
+        "[Client]: Started a helper session (JSON-RPC)",
+    );
+    let _ = started.send(outgoing);
+    *session = Some(started);
+    Ok(())
+}
+
+fn start_session(
+    app: &AppHandle,
+    helper_bin: &str,
+    password: Option<String>,
+) -> Result<mpsc::UnboundedSender<Outgoing>, String> {
+    let (ours, theirs) = std::os::unix::net::UnixStream::pair()
+        .map_err(|e| format!("Failed to create the helper socket: {}", e))?;
+    let their_stdin = theirs.try_clone().map_err(|e| e.to_string())?;
+    ours.set_nonblocking(true).map_err(|e| e.to_string())?;
+    let stream = tokio::net::UnixStream::from_std(ours).map_err(|e| e.to_string())?;
+
+    // The command (and its copies of the socket) is dropped right after spawning, so the
+    // helper exiting is EOF on our end
+    let mut child = {
+        let mut command = if password.is_some() {
+            let mut c = tokio::process::Command::new("sudo");
+            c.args(["-S", helper_bin, "--rpc"]);
+            c
+        } else {
+            let mut c = tokio::process::Command::new("pkexec");
+            c.args(["--disable-internal-agent", helper_bin, "--rpc"]);
+            c
+        };
+        command
+            .stdin(Stdio::from(OwnedFd::from(their_stdin)))
+            .stdout(Stdio::from(OwnedFd::from(theirs)))
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| {
+                format!(
+                    "Failed to spawn monarch-helper ({}): {}. {}",
+                    helper_bin,
+                    e,
+                    if password.is_some() {
+                        "Check sudo access."
+                    } else {
+                        "Ensure Polkit policy is installed (e.g. /usr/share/polkit-1/actions/com.monarch.store.policy)."
+                    }
+                )
+            })?
+    };
+
+    if let Some(stderr) = child.stderr.take() {
+        let a = app.clone();
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(stderr).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let _ = a.emit("helper-output", format!("[Helper Error]: {}", line));
+            }
+        });
+    }
+
+    let (outgoing_tx, outgoing_rx) = mpsc::unbounded_channel();
+    let a = app.clone();
+    let bin = helper_bin.to_string();
+    tokio::spawn(async move { run_session(a, bin, stream, password, outgoing_rx, child).await });
+    Ok(outgoing_tx)
+}
+
+/// Own one helper process: send runs and cancels, route events and results by request id,
+/// and close our end once idle, which tells the helper to exit. Cancelling the running
+/// command ends the helper; the runs queued behind it move to a new session.
+async fn run_session(
+    app: AppHandle,
+    helper_bin: String,
+    stream: tokio::net::UnixStream,
+    password: Option<String>,
+    mut outgoing: mpsc::UnboundedReceiver<Outgoing>,
+    mut child: tokio::process::Child,
+) {
+    let (read_half, mut write_half) = stream.into_split();
+    let mut alive = match &password {
+        Some(pwd) => write_half
+            .write_all(format!("{}\n", pwd).as_bytes())
+            .await
+            .is_ok(),
+        None => true,
+    };
+
+    let mut lines = BufReader::new(read_half).lines();
+    let mut runs: HashMap<u64, Run> = HashMap::new();
+    let mut ready = false;
+    // Requests made before the helper is ready wait here
+    let mut held: Vec<RpcRequest> = Vec::new();
+    // The run we asked the helper to cancel, and the runs it aborted on the way out
+    let mut cancelled: Option<u64> = None;
+    let mut requeue: Vec<(u64, Run)> = Vec::new();
+    while alive {
+        tokio::select! {
+            line = lines.next_line() => {
+                let Ok(Some(line)) = line else {
+                    break;
+                };
+                let Ok(message) = serde_json::from_str::<RpcMessage>(&line) else {
+                    let _ = app.emit("helper-output", format!("[Helper]: {}", line));
+                    continue;
+                };
+                match message {
+                    RpcMessage::Notification(n) if n.params.event.event_type == READY_EVENT => {
+                        ready = true;
+                        for request in held.drain(..) {
+                            alive &= send(&mut write_half, &request).await.is_ok();
+                        }
+                    }
+                    RpcMessage::Notification(n) => {
+                        let Some(run) = runs.get_mut(&n.params.id) else {
+                            continue;
+                        };
+                        if let Ok(event) = serde_json::to_string(&n.params.event) {
+                            let _ = app.emit("helper-output", format!("[Helper]: {}", event));
+                        }
+                        // The result comes again as the response; the event only feeds the UI
+                        forward_event(&app, n.params.event, &run.tx, &mut run.saw_error).await;
+                    }
+                    RpcMessage::Response(response) => {
+                        let aborted = cancelled.is_some()
+                            && response
+                                .error
+                                .as_ref()
+                                .is_some_and(|e| e.code == error_code::ABORTED);
+                        if let Some(run) = runs.remove(&response.id) {
+                            if aborted {
+                                requeue.push((response.id, run));
+                            } else {
+                                let _ = run.result.send(into_result(response));
+                            }
+                        } else if let Some(e) = response.error {
+                            let _ = app.emit(
+                                "helper-output",
+                                format!("[Helper]: RPC error {}: {}", e.code, e.message),
+                            );
+                        }
+                    }
+                }
+            }
+            next = outgoing.recv() => {
+                let mut requests = Vec::new();
+                match next {
+                    Some(Outgoing::Run { id, run }) => {
+                        requests.push(RpcRequest::new(id, RpcCall::Run(run.cmd.clone())));
+                        runs.insert(id, run);
+                    }
+                    Some(Outgoing::CancelActive(reply)) => {
+                        // The helper runs requests in order, so the oldest open one is running
+                        let active = runs.keys().min().copied();
+                        let _ = reply.send(usize::from(active.is_some()));
+                        if let Some(target) = active {
+                            cancelled = Some(target);
+                            let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+                            requests.push(RpcRequest::new(id, RpcCall::Cancel { id: target }));
+                        }
+                    }
+                    None => break,
+                }
+                for request in requests {
+                    if ready {
+                        alive &= send(&mut write_half, &request).await.is_ok();
+                    } else {
+                        held.push(request);
+                    }
+                }
+            }
+            _ = tokio::time::sleep(SESSION_IDLE), if ready && runs.is_empty() => break,
+        }
+    }
+
+    // No new runs from here on; any that slipped in meanwhile never reached the helper
+    outgoing.close();
+    while let Ok(late) = outgoing.try_recv() {
+        match late {
+            Outgoing::Run { run, .. } => {
+                let _ = run.result.send(TransactionResult {
+                    success: false,
+                    error: Some(
+                        "The helper session closed before the command was sent; try again"
+                            .to_string(),
+                    ),
+                    exit_code: exit_code::FAILED,
+                    ..Default::default()
+                });
+            }
+            Outgoing::CancelActive(reply) => {
+                let _ = reply.send(0);
+            }
+        }
+    }
+    drop(write_half);
+    drop(lines);
+    let status = child.wait().await.ok();
+    let reason = status.filter(|s| !s.success()).map(|s| {
+        s.code()
+            .map(|c| format!("{} ({})", c, exit_code::describe(c)))
+            .unwrap_or_else(|| s.to_string())
+    });
+    // Runs that never got to start because of the cancel go to the next session
+    if let Some(target) = cancelled {
+        let waiting: Vec<u64> = runs.keys().copied().filter(|id| *id != target).collect();
+        for id in waiting {
+            if let Some(run) = runs.remove(&id) {
+                requeue.push((id, run));
+            }
+        }
+    }
+    requeue.sort_by_key(|(id, _)| *id);
+    for (id, run) in requeue {
+        let outgoing = Outgoing::Run { id, run };
+        if let Err((e, Outgoing::Run { run, .. })) =
+            dispatch(&app, &helper_bin, password.clone(), outgoing).await
+        {
+            let _ = run.result.send(TransactionResult {
+                success: false,
+                error: Some(e),
+                exit_code: exit_code::FAILED,
+                ..Default::default()
+            });
+        }
+    }
+    for (_, run) in runs {
+        if let Some(reason) = &reason {
+            let _ = run
+                .tx
+                .send(ProgressMessage {
+                    progress: 0,
+                    message: format!("Error: Helper process exited with status {}", reason),
+                    event_type: None,
+                })
+                .await;
+        }
+        let _ = run.result.send(fallback_result(status, run.saw_error));
+    }
+}
+
+async fn send(
+    out: &mut tokio::net::unix::OwnedWriteHalf,
+    request: &RpcRequest,
+) -> std::io::Result<()> {
+    let line = serde_json::to_string(request)?;
+    out.write_all(format!("{}\n", line).as_bytes()).await
+}
+
+fn into_result(response: RpcResponse) -> TransactionResult {
+    match (response.result, response.error) {
+        (Some(RpcResult::Transaction(result)), _) => result,
+        (_, Some(e)) => TransactionResult {
+            success: false,
+            error: Some(e.message),
+            exit_code: if e.code == error_code::CANCELLED {
+                exit_code::CANCELLED
+            } else {
+                exit_code::FAILED
+            },
+            ..Default::default()
+        },
+        _ => TransactionResult {
+            success: false,
+            error: Some("The helper answered with an unexpected result".to_string()),
+            exit_code: exit_code::FAILED,
+            ..Default::default()
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into_result() {
+        let done = TransactionResult {
+            success: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            into_result(RpcResponse::ok(1, RpcResult::Transaction(done.clone()))),
+            done
+        );
+
+        let cancelled = into_result(RpcResponse::err(
+            1,
+            error_code::CANCELLED,
+            "Cancelled before it started",
+        ));
+        assert!(!cancelled.success);
+        assert_eq!(cancelled.exit_code, exit_code::CANCELLED);
+
+        let odd = into_result(RpcResponse::ok(1, RpcResult::Acknowledged(true)));
+        assert_eq!(odd.exit_code, exit_code::FAILED);
+    }
+}
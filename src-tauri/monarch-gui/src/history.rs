@@ -0,0 +1,1232 @@
+//! Transaction history. pacman.log provides everything run through pacman (terminal, yay,
+//! other front-ends); the helper drives libalpm without a log file, so every change the
+//! state watcher sees is journaled in SQLite (history.sqlite in the app data dir) from the
+//! local DB diff it already computes: MonArch's own transactions, and external ones too so
+//! they outlive pacman.log rotation. Both are merged newest first for
+//! get_transaction_history, and undo_removal reinstalls what a transaction removed at the
+//! exact versions it removed. Accepted license agreements are journaled alongside.
+//!
+//! Before MonArch runs an upgrade, the config files packages declare in their backup array
+//! are saved (content-addressed, in config_backups/ next to the journal) and the journaled
+//! transaction keeps the ones whose package it changed, so a bad update's config changes can
+//! be diffed and put back without a filesystem snapshot.
+
+use crate::helper_client;
+use crate::repo_manager::RepoManager;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+const PACMAN_LOG: &str = "/var/log/pacman.log";
+const PKG_CACHE: &str = "/var/cache/pacman/pkg";
+const PAGE_SIZE: usize = 25;
+/// A journaled MonArch transaction and a pacman.log one this close together with the same
+/// changes are the same transaction (should the helper's libalpm ever log too).
+const DEDUP_WINDOW_SECS: i64 = 300;
+/// Config files bigger than this aren't saved; hand-edited configs are far smaller.
+const MAX_CONFIG_BYTES: u64 = 1024 * 1024;
+/// Diffs of files with more lines than this on both sides show the whole file replaced.
+const MAX_DIFF_LINES: usize = 5000;
+
+/// Config files saved by snapshot_config_files, waiting for the transaction to be journaled.
+static PENDING_CONFIGS: Lazy<std::sync::Mutex<Vec<ConfigBackup>>> =
+    Lazy::new(|| std::sync::Mutex::new(Vec::new()));
+
+static LOG_LINE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\[([^\]]+)\] \[([^\]]+)\] (.*)$").expect("pacman.log line regex")
+});
+static ACTION_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(installed|removed|upgraded|downgraded|reinstalled) (\S+) \((.*)\)$")
+        .expect("pacman.log action regex")
+});
+
+const JOURNAL_FILE: &str = "history.sqlite";
+/// The JSON-lines journal of earlier versions; imported once, then renamed.
+const LEGACY_JOURNAL_FILE: &str = "history.jsonl";
+const SCHEMA_VERSION: i32 = 1;
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS transactions (
+    id TEXT PRIMARY KEY,
+    timestamp INTEGER NOT NULL,
+    -- monarch (run by MonArch) or external (pacman, yay, ... seen by the state watcher)
+    source TEXT NOT NULL,
+    command TEXT,
+    status TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS transactions_timestamp ON transactions(timestamp);
+CREATE TABLE IF NOT EXISTS changes (
+    tx_id TEXT NOT NULL REFERENCES transactions(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    action TEXT NOT NULL,
+    old_version TEXT,
+    new_version TEXT
+);
+CREATE INDEX IF NOT EXISTS changes_tx ON changes(tx_id);
+CREATE TABLE IF NOT EXISTS config_backups (
+    tx_id TEXT NOT NULL REFERENCES transactions(id) ON DELETE CASCADE,
+    package TEXT NOT NULL,
+    version TEXT NOT NULL,
+    path TEXT NOT NULL,
+    sha1 TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS config_backups_tx ON config_backups(tx_id);
+";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PackageAction {
+    Installed,
+    Upgraded,
+    Downgraded,
+    Reinstalled,
+    Removed,
+}
+
+impl PackageAction {
+    fn as_str(self) -> &'static str {
+        match self {
+            PackageAction::Installed => "installed",
+            PackageAction::Upgraded => "upgraded",
+            PackageAction::Downgraded => "downgraded",
+            PackageAction::Reinstalled => "reinstalled",
+            PackageAction::Removed => "removed",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        Some(match s {
+            "installed" => PackageAction::Installed,
+            "upgraded" => PackageAction::Upgraded,
+            "downgraded" => PackageAction::Downgraded,
+            "reinstalled" => PackageAction::Reinstalled,
+            "removed" => PackageAction::Removed,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PackageChange {
+    pub name: String,
+    pub action: PackageAction,
+    /// Version before the transaction (None for installs).
+    pub old_version: Option<String>,
+    /// Version after the transaction (None for removals).
+    pub new_version: Option<String>,
+}
+
+/// A config file as it was right before a MonArch transaction changed its package.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ConfigBackup {
+    pub package: String,
+    /// Version of the package when the file was saved.
+    pub version: String,
+    pub path: String,
+    /// SHA-1 of the content; the saved copy's file name in config_backups/.
+    pub sha1: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Transaction {
+    pub id: String,
+    /// Unix seconds
+    pub timestamp: i64,
+    /// "pacman" (from pacman.log), "monarch" or "external" (journal)
+    pub source: String,
+    /// Command line pacman logged for it, e.g. "pacman -Syu".
+    pub command: Option<String>,
+    /// "completed", or how pacman.log says it ended ("failed", "interrupted", "incomplete").
+    pub status: String,
+    pub changes: Vec<PackageChange>,
+    /// Config files saved before the transaction (MonArch upgrades only).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub config_backups: Vec<ConfigBackup>,
+}
+
+/// Narrows get_transaction_history; all fields optional.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct HistoryFilter {
+    /// Substring of a package name touched by the transaction.
+    pub package: Option<String>,
+    pub action: Option<PackageAction>,
+    pub source: Option<String>,
+}
+
+/// Typed response for get_transaction_history
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct TransactionPage {
+    pub transactions: Vec<Transaction>,
+    pub page: u32,
+    pub total: usize,
+    pub has_more: bool,
+}
+
+/// pacman.log timestamp: ISO 8601 with offset since pacman 5.2, local "YYYY-MM-DD HH:MM" before.
+pub(crate) fn parse_log_timestamp(ts: &str) -> Option<i64> {
+    chrono::DateTime::parse_from_str(ts, "%Y-%m-%dT%H:%M:%S%z")
+        .map(|d| d.timestamp())
+        .ok()
+        .or_else(|| {
+            chrono::NaiveDateTime::parse_from_str(ts, "%Y-%m-%d %H:%M")
+                .ok()
+                .and_then(|d| d.and_local_timezone(chrono::Local).earliest())
+                .map(|d| d.timestamp())
+        })
+}
+
+fn parse_change(text: &str) -> Option<PackageChange> {
+    let c = ACTION_RE.captures(text)?;
+    let name = c[2].to_string();
+    let versions = &c[3];
+    let (old_version, new_version) = match versions.split_once(" -> ") {
+        Some((old, new)) => (Some(old.to_string()), Some(new.to_string())),
+        None => (None, Some(versions.to_string())),
+    };
+    let (action, old_version, new_version) = match &c[1] {
+        "installed" => (PackageAction::Installed, None, new_version),
+        "removed" => (PackageAction::Removed, new_version, None),
+        "reinstalled" => (PackageAction::Reinstalled, new_version.clone(), new_version),
+        "upgraded" => (PackageAction::Upgraded, old_version, new_version),
+        _ => (PackageAction::Downgraded, old_version, new_version),
+    };
+    Some(PackageChange {
+        name,
+        action,
+        old_version,
+        new_version,
+    })
+}
+
+/// Group pacman.log into transactions, oldest first. A transaction runs from "transaction
+/// started" to the next "transaction <status>"; package lines without a start (log cut by
+/// rotation) open one. Ids use the line number the transaction starts on.
+pub fn parse_pacman_log(log: &str) -> Vec<Transaction> {
+    let mut out: Vec<Transaction> = Vec::new();
+    let mut open: Option<Transaction> = None;
+    let mut last_command: Option<String> = None;
+
+    for (line_no, line) in log.lines().enumerate() {
+        let Some(c) = LOG_LINE_RE.captures(line) else {
+            continue;
+        };
+        let timestamp = parse_log_timestamp(&c[1]).unwrap_or(0);
+        let (tag, text) = (&c[2], &c[3]);
+
+        if tag != "ALPM" {
+            // Front-ends log their invocation right before the transaction starts
+            if let Some(cmd) = text
+                .strip_prefix("Running '")
+                .and_then(|r| r.strip_suffix('\''))
+            {
+                last_command = Some(cmd.to_string());
+            }
+            continue;
+        }
+
+        if let Some(status) = text.strip_prefix("transaction ") {
+            if let Some(mut tx) = open.take() {
+                tx.status = if status == "started" {
+                    "incomplete".to_string()
+                } else {
+                    status.to_string()
+                };
+                out.push(tx);
+            }
+            if status == "started" {
+                open = Some(Transaction {
+                    id: format!("log-{}", line_no),
+                    timestamp,
+                    source: "pacman".to_string(),
+                    command: last_command.take(),
+                    status: "incomplete".to_string(),
+                    changes: Vec::new(),
+                    config_backups: Vec::new(),
+                });
+            }
+            continue;
+        }
+
+        if let Some(change) = parse_change(text) {
+            open.get_or_insert_with(|| Transaction {
+                id: format!("log-{}", line_no),
+                timestamp,
+                source: "pacman".to_string(),
+                command: last_command.take(),
+                status: "completed".to_string(),
+                changes: Vec::new(),
+                config_backups: Vec::new(),
+            })
+            .changes
+            .push(change);
+        }
+    }
+    if let Some(tx) = open {
+        out.push(tx);
+    }
+    // Syncs (-Sy) and failed resolutions start transactions that change nothing
+    out.retain(|tx| !tx.changes.is_empty());
+    out
+}
+
+/// Changes between two local DB snapshots (name → version), sorted by name.
+pub fn changes_between(
+    before: &HashMap<String, String>,
+    after: &HashMap<String, String>,
+) -> Vec<PackageChange> {
+    let mut changes: Vec<PackageChange> = Vec::new();
+    for (name, new) in after {
+        let (action, old_version) = match before.get(name) {
+            None => (PackageAction::Installed, None),
+            Some(old) => match crate::versions::classify(old, new) {
+                crate::versions::VersionChange::Same => continue,
+                crate::versions::VersionChange::Upgrade => (PackageAction::Upgraded, Some(old)),
+                crate::versions::VersionChange::Downgrade => (PackageAction::Downgraded, Some(old)),
+            },
+        };
+        changes.push(PackageChange {
+            name: name.clone(),
+            action,
+            old_version: old_version.cloned(),
+            new_version: Some(new.clone()),
+        });
+    }
+    for (name, old) in before {
+        if !after.contains_key(name) {
+            changes.push(PackageChange {
+                name: name.clone(),
+                action: PackageAction::Removed,
+                old_version: Some(old.clone()),
+                new_version: None,
+            });
+        }
+    }
+    changes.sort_by(|a, b| a.name.cmp(&b.name));
+    changes
+}
+
+/// Open (or create) the journal at `path`, importing the legacy JSON-lines journal next to
+/// it the first time.
+fn open_journal(path: &Path) -> rusqlite::Result<Connection> {
+    let mut conn = Connection::open(path)?;
+    conn.busy_timeout(std::time::Duration::from_secs(5))?;
+    conn.pragma_update(None, "foreign_keys", true)?;
+    conn.execute_batch(SCHEMA)?;
+    conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+
+    let legacy = path.with_file_name(LEGACY_JOURNAL_FILE);
+    if let Ok(content) = std::fs::read_to_string(&legacy) {
+        let imported: Vec<Transaction> = content
+            .lines()
+            .filter_map(|l| serde_json::from_str(l).ok())
+            .collect();
+        for tx in &imported {
+            insert_transaction(&mut conn, tx)?;
+        }
+        log::info!("Imported {} journaled transactions", imported.len());
+        let _ = std::fs::rename(&legacy, legacy.with_extension("jsonl.imported"));
+    }
+    Ok(conn)
+}
+
+fn insert_transaction(conn: &mut Connection, tx: &Transaction) -> rusqlite::Result<()> {
+    let db = conn.transaction()?;
+    db.execute(
+        "INSERT OR REPLACE INTO transactions (id, timestamp, source, command, status)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![tx.id, tx.timestamp, tx.source, tx.command, tx.status],
+    )?;
+    for c in &tx.changes {
+        db.execute(
+            "INSERT INTO changes (tx_id, name, action, old_version, new_version)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                tx.id,
+                c.name,
+                c.action.as_str(),
+                c.old_version,
+                c.new_version
+            ],
+        )?;
+    }
+    for b in &tx.config_backups {
+        db.execute(
+            "INSERT INTO config_backups (tx_id, package, version, path, sha1)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![tx.id, b.package, b.version, b.path, b.sha1],
+        )?;
+    }
+    db.commit()
+}
+
+/// Journaled transactions, oldest first; `id` narrows it to one.
+fn read_transactions(conn: &Connection, id: Option<&str>) -> rusqlite::Result<Vec<Transaction>> {
+    let mut txs: Vec<Transaction> = conn
+        .prepare(
+            "SELECT id, timestamp, source, command, status FROM transactions
+             WHERE ?1 IS NULL OR id = ?1 ORDER BY timestamp, id",
+        )?
+        .query_map(params![id], |r| {
+            Ok(Transaction {
+                id: r.get(0)?,
+                timestamp: r.get(1)?,
+                source: r.get(2)?,
+                command: r.get(3)?,
+                status: r.get(4)?,
+                changes: Vec::new(),
+                config_backups: Vec::new(),
+            })
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+    let index: HashMap<String, usize> = txs
+        .iter()
+        .enumerate()
+        .map(|(i, t)| (t.id.clone(), i))
+        .collect();
+
+    let mut changes = conn.prepare(
+        "SELECT tx_id, name, action, old_version, new_version FROM changes
+         WHERE ?1 IS NULL OR tx_id = ?1 ORDER BY rowid",
+    )?;
+    let mut rows = changes.query(params![id])?;
+    while let Some(r) = rows.next()? {
+        let tx_id: String = r.get(0)?;
+        let action: String = r.get(2)?;
+        let (Some(&i), Some(action)) = (index.get(&tx_id), PackageAction::parse(&action)) else {
+            continue;
+        };
+        txs[i].changes.push(PackageChange {
+            name: r.get(1)?,
+            action,
+            old_version: r.get(3)?,
+            new_version: r.get(4)?,
+        });
+    }
+
+    let mut backups = conn.prepare(
+        "SELECT tx_id, package, version, path, sha1 FROM config_backups
+         WHERE ?1 IS NULL OR tx_id = ?1 ORDER BY rowid",
+    )?;
+    let mut rows = backups.query(params![id])?;
+    while let Some(r) = rows.next()? {
+        let tx_id: String = r.get(0)?;
+        if let Some(&i) = index.get(&tx_id) {
+            txs[i].config_backups.push(ConfigBackup {
+                package: r.get(1)?,
+                version: r.get(2)?,
+                path: r.get(3)?,
+                sha1: r.get(4)?,
+            });
+        }
+    }
+    Ok(txs)
+}
+
+/// Saved config files some journaled transaction still refers to.
+fn referenced_backups(conn: &Connection) -> rusqlite::Result<HashSet<String>> {
+    conn.prepare("SELECT DISTINCT sha1 FROM config_backups")?
+        .query_map([], |r| r.get(0))?
+        .collect()
+}
+
+fn journal(app: &AppHandle) -> Result<Connection, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .unwrap_or_else(|_| PathBuf::from("."));
+    let _ = std::fs::create_dir_all(&dir);
+    open_journal(&dir.join(JOURNAL_FILE)).map_err(|e| format!("Transaction journal: {}", e))
+}
+
+fn append_to_journal(app: &AppHandle, tx: &Transaction) -> Result<(), String> {
+    let mut conn = journal(app)?;
+    insert_transaction(&mut conn, tx).map_err(|e| e.to_string())
+}
+
+fn config_backup_dir(app: &AppHandle) -> PathBuf {
+    let path = app
+        .path()
+        .app_data_dir()
+        .unwrap_or_else(|_| PathBuf::from("."))
+        .join("config_backups");
+    let _ = std::fs::create_dir_all(&path);
+    path
+}
+
+/// Save the backup-array config files of every installed package ahead of an upgrade.
+/// Which packages the upgrade touches is only known once the helper has refreshed the sync
+/// DBs, so all are saved (unchanged content is stored once) and record_transaction
+/// keeps the ones whose package changed. Root-only and oversized files are skipped.
+pub(crate) fn snapshot_config_files(app: &AppHandle) {
+    let dir = config_backup_dir(app);
+    let mut saved = Vec::new();
+    for (package, version, path) in crate::alpm_read::get_backup_files_native() {
+        let Ok(meta) = std::fs::metadata(&path) else {
+            continue;
+        };
+        if !meta.is_file() || meta.len() > MAX_CONFIG_BYTES {
+            continue;
+        }
+        let Ok(content) = std::fs::read(&path) else {
+            continue;
+        };
+        let sha1: String = Sha1::digest(&content)
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        let blob = dir.join(&sha1);
+        if !blob.exists() && std::fs::write(&blob, &content).is_err() {
+            continue;
+        }
+        saved.push(ConfigBackup {
+            package,
+            version,
+            path,
+            sha1,
+        });
+    }
+    log::info!("Saved {} config files before the transaction", saved.len());
+    if let Ok(mut pending) = PENDING_CONFIGS.lock() {
+        *pending = saved;
+    }
+}
+
+/// Forget the config files saved ahead of a transaction once they no longer show what is on
+/// disk (a config was merged or restored since).
+pub(crate) fn invalidate_pending_configs() {
+    if let Ok(mut pending) = PENDING_CONFIGS.lock() {
+        pending.clear();
+    }
+}
+
+/// The saved config files of packages `changes` upgraded, downgraded, reinstalled or removed
+/// from the version they were saved under.
+fn configs_for_changes(saved: Vec<ConfigBackup>, changes: &[PackageChange]) -> Vec<ConfigBackup> {
+    let changed: HashSet<(&str, &str)> = changes
+        .iter()
+        .filter(|c| c.action != PackageAction::Installed)
+        .filter_map(|c| Some((c.name.as_str(), c.old_version.as_deref()?)))
+        .collect();
+    saved
+        .into_iter()
+        .filter(|b| changed.contains(&(b.package.as_str(), b.version.as_str())))
+        .collect()
+}
+
+/// Drop saved config files no journaled transaction refers to any more.
+fn prune_config_backups(app: &AppHandle, conn: &Connection) {
+    let referenced = match referenced_backups(conn) {
+        Ok(r) => r,
+        Err(e) => {
+            log::warn!("Could not list saved config files: {}", e);
+            return;
+        }
+    };
+    let Ok(entries) = std::fs::read_dir(config_backup_dir(app)) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if !referenced.contains(entry.file_name().to_string_lossy().as_ref()) {
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+}
+
+fn load_journal(app: &AppHandle) -> Vec<Transaction> {
+    journal(app)
+        .and_then(|conn| read_transactions(&conn, None).map_err(|e| e.to_string()))
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to read the transaction journal: {}", e);
+            Vec::new()
+        })
+}
+
+/// Journal a transaction the state watcher saw, from the local DB before and after.
+/// `external` ones (pacman, yay, ...) are kept too, since pacman.log gets rotated.
+pub fn record_transaction(
+    app: &AppHandle,
+    before: &HashMap<String, String>,
+    after: &HashMap<String, String>,
+    external: bool,
+) {
+    let changes = changes_between(before, after);
+    if changes.is_empty() {
+        return;
+    }
+    // Config files were only saved ahead of MonArch's own upgrades; any transaction uses
+    // them up, since it may have merged configs of its own
+    let saved = PENDING_CONFIGS
+        .lock()
+        .map(|mut p| std::mem::take(&mut *p))
+        .unwrap_or_default();
+    let config_backups = if external {
+        Vec::new()
+    } else {
+        configs_for_changes(saved, &changes)
+    };
+    let now = chrono::Utc::now();
+    let source = if external { "external" } else { "monarch" };
+    let tx = Transaction {
+        id: format!("{}-{}", source, now.timestamp_millis()),
+        timestamp: now.timestamp(),
+        source: source.to_string(),
+        command: None,
+        status: "completed".to_string(),
+        changes,
+        config_backups,
+    };
+    let result = journal(app).and_then(|mut conn| {
+        insert_transaction(&mut conn, &tx).map_err(|e| e.to_string())?;
+        prune_config_backups(app, &conn);
+        Ok(())
+    });
+    if let Err(e) = result {
+        log::warn!("Failed to journal transaction: {}", e);
+    }
+}
+
+/// Journal the acceptance of a license agreement (see eula.rs), so it shows in the history
+/// next to the install it allowed.
+pub(crate) fn record_license_acceptance(
+    app: &AppHandle,
+    package: &str,
+    title: &str,
+    revision: u32,
+    accepted_at: i64,
+) -> Result<(), String> {
+    append_to_journal(
+        app,
+        &Transaction {
+            id: format!("license-{}-{}", package, accepted_at),
+            timestamp: accepted_at,
+            source: "monarch".to_string(),
+            command: Some(format!(
+                "Accepted {} (revision {}) for {}",
+                title, revision, package
+            )),
+            status: "license_accepted".to_string(),
+            changes: Vec::new(),
+            config_backups: Vec::new(),
+        },
+    )
+}
+
+fn same_changes(a: &Transaction, b: &Transaction) -> bool {
+    let key = |t: &Transaction| {
+        let mut k: Vec<(String, PackageAction)> = t
+            .changes
+            .iter()
+            .map(|c| (c.name.clone(), c.action))
+            .collect();
+        k.sort_by(|x, y| x.0.cmp(&y.0));
+        k
+    };
+    (a.timestamp - b.timestamp).abs() <= DEDUP_WINDOW_SECS && key(a) == key(b)
+}
+
+/// pacman.log and journal transactions, newest first. A MonArch transaction replaces its
+/// pacman.log twin; an external one is only kept once pacman.log no longer has it, since
+/// the log also knows the command line and how it ended.
+fn merge_history(log: Vec<Transaction>, journal: Vec<Transaction>) -> Vec<Transaction> {
+    let (external, monarch): (Vec<Transaction>, Vec<Transaction>) =
+        journal.into_iter().partition(|j| j.source == "external");
+    let mut all: Vec<Transaction> = external
+        .into_iter()
+        .filter(|j| !log.iter().any(|l| same_changes(l, j)))
+        .collect();
+    all.extend(
+        log.into_iter()
+            .filter(|l| !monarch.iter().any(|j| same_changes(l, j))),
+    );
+    all.extend(monarch);
+    all.sort_by_key(|t| std::cmp::Reverse(t.timestamp));
+    all
+}
+
+fn matches_filter(tx: &Transaction, filter: &HistoryFilter) -> bool {
+    if filter.source.as_ref().is_some_and(|s| s != &tx.source) {
+        return false;
+    }
+    let package = filter.package.as_deref().map(str::to_lowercase);
+    // License acceptances change no package; they match by the text they record
+    if tx.changes.is_empty() {
+        return filter.action.is_none()
+            && package.as_deref().is_none_or(|p| {
+                tx.command
+                    .as_deref()
+                    .is_some_and(|c| c.to_lowercase().contains(p))
+            });
+    }
+    tx.changes.iter().any(|c| {
+        filter.action.is_none_or(|a| c.action == a)
+            && package.as_deref().is_none_or(|p| c.name.contains(p))
+    })
+}
+
+fn page_of(history: Vec<Transaction>, filter: &HistoryFilter, page: u32) -> TransactionPage {
+    let matching: Vec<Transaction> = history
+        .into_iter()
+        .filter(|tx| matches_filter(tx, filter))
+        .collect();
+    let total = matching.len();
+    let start = (page.max(1) as usize - 1) * PAGE_SIZE;
+    TransactionPage {
+        transactions: matching.into_iter().skip(start).take(PAGE_SIZE).collect(),
+        page: page.max(1),
+        total,
+        has_more: start + PAGE_SIZE < total,
+    }
+}
+
+pub(crate) fn load_history(app: &AppHandle) -> Vec<Transaction> {
+    let log = std::fs::read_to_string(PACMAN_LOG).unwrap_or_default();
+    merge_history(parse_pacman_log(&log), load_journal(app))
+}
+
+/// `name-version-arch.pkg.tar.*` in the pacman cache, if that exact build is still there.
+fn cached_package_file(files: &[String], name: &str, version: &str) -> Option<String> {
+    let prefix = format!("{}-{}-", name, version);
+    files
+        .iter()
+        .find(|f| {
+            f.strip_prefix(&prefix).is_some_and(|rest| {
+                !rest.contains('-') && rest.contains(".pkg.tar.") && !rest.ends_with(".sig")
+            })
+        })
+        .map(|f| format!("{}/{}", PKG_CACHE, f))
+}
+
+/// Page `page` (1-based, 25 per page) of install/upgrade/removal transactions, newest first.
+#[tauri::command]
+pub async fn get_transaction_history(
+    app: AppHandle,
+    page: Option<u32>,
+    filter: Option<HistoryFilter>,
+) -> Result<TransactionPage, String> {
+    let filter = filter.unwrap_or_default();
+    tokio::task::spawn_blocking(move || page_of(load_history(&app), &filter, page.unwrap_or(1)))
+        .await
+        .map_err(|e| format!("Task join error: {}", e))
+}
+
+/// Reinstall the packages transaction `id` removed, at the versions it removed. Cached
+/// package files are used first, then the sync DBs when they still carry that version;
+/// if any package isn't available at its old version nothing is installed.
+#[tauri::command]
+pub async fn undo_removal(
+    app: AppHandle,
+    state_repo: State<'_, RepoManager>,
+    id: String,
+    password: Option<String>,
+) -> Result<Vec<String>, String> {
+    let history_app = app.clone();
+    let (removed, files, repo_targets) = tokio::task::spawn_blocking(move || {
+        let tx = load_history(&history_app)
+            .into_iter()
+            .find(|t| t.id == id)
+            .ok_or_else(|| format!("Transaction {} not found", id))?;
+        let removed: Vec<(String, String)> = tx
+            .changes
+            .iter()
+            .filter(|c| c.action == PackageAction::Removed)
+            .filter_map(|c| Some((c.name.clone(), c.old_version.clone()?)))
+            .filter(|(name, _)| !crate::alpm_read::is_package_installed(name))
+            .collect();
+        if removed.is_empty() {
+            return Err(
+                "Nothing to restore: the transaction removed no packages that are still missing"
+                    .to_string(),
+            );
+        }
+
+        let cache_files: Vec<String> = std::fs::read_dir(PKG_CACHE)
+            .map(|d| {
+                d.flatten()
+                    .map(|e| e.file_name().to_string_lossy().into_owned())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let mut files = Vec::new();
+        let mut repo_targets = Vec::new();
+        let mut unavailable = Vec::new();
+        for (name, version) in &removed {
+            if let Some(path) = cached_package_file(&cache_files, name, version) {
+                files.push(path);
+            } else if crate::alpm_read::get_package_native(name)
+                .is_some_and(|p| &p.version == version)
+            {
+                repo_targets.push(name.clone());
+            } else {
+                unavailable.push(format!("{} {}", name, version));
+            }
+        }
+        if !unavailable.is_empty() {
+            return Err(format!(
+                "Not available at the removed version (not cached, repos moved on): {}",
+                unavailable.join(", ")
+            ));
+        }
+        Ok((removed, files, repo_targets))
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))??;
+
+    let _guard = crate::utils::PRIVILEGED_LOCK.lock().await;
+    let _ = app.emit(
+        "install-output",
+        format!("--- Restoring {} removed package(s) ---", removed.len()),
+    );
+    let mut commands = Vec::new();
+    if !files.is_empty() {
+        commands.push(helper_client::HelperCommand::AlpmInstallFiles {
+            paths: crate::commands::package::copy_paths_to_monarch_install(files).await?,
+        });
+    }
+    if !repo_targets.is_empty() {
+        let enabled_repos = state_repo.transaction_repos().await;
+        commands.push(helper_client::HelperCommand::AlpmInstall {
+            packages: repo_targets,
+            sync_first: false,
+            enabled_repos,
+            cpu_optimization: None,
+            target_repo: None,
+            phase: Default::default(),
+            dry_run: false,
+        });
+    }
+    for command in commands {
+        let mut rx = helper_client::invoke_helper(&app, command, password.clone())
+            .await
+            .map_err(|e| format!("Failed to invoke helper: {}", e))?;
+        while let Some(msg) = rx.recv().await {
+            let _ = app.emit("install-output", &msg.message);
+        }
+    }
+
+    let (restored, missing): (Vec<String>, Vec<String>) = tokio::task::spawn_blocking(move || {
+        removed
+            .into_iter()
+            .map(|(name, _)| name)
+            .partition(|name| crate::alpm_read::is_package_installed(name))
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?;
+    if !missing.is_empty() {
+        return Err(format!("Failed to restore: {}", missing.join(", ")));
+    }
+    Ok(restored)
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffOp {
+    Same,
+    Added,
+    Removed,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct DiffLine {
+    pub op: DiffOp,
+    pub text: String,
+}
+
+/// A saved config file against the file on disk now.
+#[derive(Debug, Serialize, Clone)]
+pub struct ConfigDiff {
+    pub package: String,
+    pub version: String,
+    pub path: String,
+    /// False when the file has since been deleted.
+    pub current_exists: bool,
+    pub changed: bool,
+    /// Saved → current, line by line.
+    pub lines: Vec<DiffLine>,
+}
+
+/// Line diff (longest common subsequence) from `old` to `new`.
+fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    let line = |op, text: &str| DiffLine {
+        op,
+        text: text.to_string(),
+    };
+    let prefix = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (ma, mb) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let mut out: Vec<DiffLine> = a[..prefix].iter().map(|l| line(DiffOp::Same, l)).collect();
+    if ma.len() > MAX_DIFF_LINES && mb.len() > MAX_DIFF_LINES {
+        out.extend(ma.iter().map(|l| line(DiffOp::Removed, l)));
+        out.extend(mb.iter().map(|l| line(DiffOp::Added, l)));
+    } else {
+        // lcs[i][j]: common subsequence length of ma[i..] and mb[j..]
+        let mut lcs = vec![vec![0u32; mb.len() + 1]; ma.len() + 1];
+        for i in (0..ma.len()).rev() {
+            for j in (0..mb.len()).rev() {
+                lcs[i][j] = if ma[i] == mb[j] {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < ma.len() || j < mb.len() {
+            if i < ma.len() && j < mb.len() && ma[i] == mb[j] {
+                out.push(line(DiffOp::Same, ma[i]));
+                i += 1;
+                j += 1;
+            } else if i < ma.len() && (j == mb.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+                out.push(line(DiffOp::Removed, ma[i]));
+                i += 1;
+            } else {
+                out.push(line(DiffOp::Added, mb[j]));
+                j += 1;
+            }
+        }
+    }
+    out.extend(a[a.len() - suffix..].iter().map(|l| line(DiffOp::Same, l)));
+    out
+}
+
+fn find_config_backup(app: &AppHandle, id: &str, path: &str) -> Result<ConfigBackup, String> {
+    read_transactions(&journal(app)?, Some(id))
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("Transaction {} not found", id))?
+        .config_backups
+        .into_iter()
+        .find(|b| b.path == path)
+        .ok_or_else(|| format!("No saved copy of {} in transaction {}", path, id))
+}
+
+/// Diff the copy of `path` saved before transaction `id` against the file on disk now.
+#[tauri::command]
+pub async fn get_config_diff(
+    app: AppHandle,
+    id: String,
+    path: String,
+) -> Result<ConfigDiff, String> {
+    tokio::task::spawn_blocking(move || {
+        let backup = find_config_backup(&app, &id, &path)?;
+        let saved = std::fs::read(config_backup_dir(&app).join(&backup.sha1))
+            .map_err(|e| format!("Saved copy of {} is missing: {}", path, e))?;
+        let (current, current_exists) = match std::fs::read(&path) {
+            Ok(bytes) => (bytes, true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => (Vec::new(), false),
+            Err(e) => return Err(format!("Could not read {}: {}", path, e)),
+        };
+        Ok(ConfigDiff {
+            changed: saved != current,
+            lines: diff_lines(
+                &String::from_utf8_lossy(&saved),
+                &String::from_utf8_lossy(&current),
+            ),
+            package: backup.package,
+            version: backup.version,
+            path: backup.path,
+            current_exists,
+        })
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Put `path` back the way it was before transaction `id`. The helper only writes files in
+/// an installed package's backup array and keeps the replaced one as `<path>.monarch-bak`.
+#[tauri::command]
+pub async fn restore_config_backup(
+    app: AppHandle,
+    id: String,
+    path: String,
+    password: Option<String>,
+) -> Result<(), String> {
+    let lookup_app = app.clone();
+    let (backup, blob) = tokio::task::spawn_blocking(move || {
+        let backup = find_config_backup(&lookup_app, &id, &path)?;
+        let blob = config_backup_dir(&lookup_app).join(&backup.sha1);
+        Ok::<_, String>((backup, blob.to_string_lossy().into_owned()))
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))??;
+    let staged = crate::commands::package::copy_paths_to_monarch_install(vec![blob]).await?;
+
+    let _guard = crate::utils::PRIVILEGED_LOCK.lock().await;
+    let result = helper_client::run_helper(
+        &app,
+        helper_client::HelperCommand::RestoreConfigFile {
+            path: backup.path.clone(),
+            source: staged.into_iter().next().unwrap_or_default(),
+        },
+        password,
+        |msg| {
+            let _ = app.emit("install-output", &msg.message);
+        },
+    )
+    .await
+    .map_err(|e| format!("Failed to invoke helper: {}", e))?;
+    if !result.success {
+        return Err(result
+            .error
+            .unwrap_or_else(|| format!("Failed to restore {}", backup.path)));
+    }
+    invalidate_pending_configs();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOG: &str = "\
+[2024-05-01T10:00:00+0000] [PACMAN] Running 'pacman -S vlc'
+[2024-05-01T10:00:01+0000] [ALPM] transaction started
+[2024-05-01T10:00:02+0000] [ALPM] installed libvlc (3.0.20-1)
+[2024-05-01T10:00:02+0000] [ALPM] installed vlc (3.0.20-1)
+[2024-05-01T10:00:03+0000] [ALPM] transaction completed
+[2024-05-02T09:00:00+0000] [PACMAN] Running 'pacman -Sy'
+[2024-05-02T09:00:05+0000] [PACMAN] synchronizing package lists
+[2024-05-03T09:00:00+0000] [PACMAN] Running 'pacman -Rns gimp'
+[2024-05-03T09:00:01+0000] [ALPM] transaction started
+[2024-05-03T09:00:02+0000] [ALPM] removed gimp (2.10.38-1)
+[2024-05-03T09:00:02+0000] [ALPM-SCRIPTLET] some hook output
+[2024-05-03T09:00:03+0000] [ALPM] transaction completed
+[2024-05-04T09:00:01+0000] [ALPM] transaction started
+[2024-05-04T09:00:02+0000] [ALPM] upgraded mesa (1:24.0-1 -> 1:24.1-1)
+[2024-05-04T09:00:02+0000] [ALPM] downgraded yay (12.4-1 -> 12.3-1)
+[2024-05-04T09:00:03+0000] [ALPM] transaction failed
+";
+
+    #[test]
+    fn test_parse_pacman_log_transactions() {
+        let txs = parse_pacman_log(LOG);
+        assert_eq!(txs.len(), 3);
+        assert_eq!(txs[0].id, "log-1");
+        assert_eq!(txs[0].command.as_deref(), Some("pacman -S vlc"));
+        assert_eq!(txs[0].status, "completed");
+        assert_eq!(txs[0].timestamp, 1714557601);
+        assert_eq!(txs[0].changes.len(), 2);
+
+        // The -Sy command never reached a transaction, so it isn't attached to the removal
+        assert_eq!(txs[1].command.as_deref(), Some("pacman -Rns gimp"));
+        assert_eq!(
+            txs[1].changes,
+            vec![PackageChange {
+                name: "gimp".to_string(),
+                action: PackageAction::Removed,
+                old_version: Some("2.10.38-1".to_string()),
+                new_version: None,
+            }]
+        );
+
+        assert_eq!(txs[2].status, "failed");
+        assert_eq!(txs[2].command, None);
+        assert_eq!(txs[2].changes[0].action, PackageAction::Upgraded);
+        assert_eq!(txs[2].changes[0].old_version.as_deref(), Some("1:24.0-1"));
+        assert_eq!(txs[2].changes[1].action, PackageAction::Downgraded);
+    }
+
+    #[test]
+    fn test_merge_filter_and_cache_lookup() {
+        let map = |pairs: &[(&str, &str)]| -> HashMap<String, String> {
+            pairs
+                .iter()
+                .map(|(n, v)| (n.to_string(), v.to_string()))
+                .collect()
+        };
+        let changes = changes_between(
+            &map(&[("gimp", "2.10.38-1"), ("vlc", "3.0.20-1")]),
+            &map(&[("vlc", "3.0.21-1"), ("htop", "3.3-1")]),
+        );
+        let summary: Vec<(&str, PackageAction)> = changes
+            .iter()
+            .map(|c| (c.name.as_str(), c.action))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("gimp", PackageAction::Removed),
+                ("htop", PackageAction::Installed),
+                ("vlc", PackageAction::Upgraded)
+            ]
+        );
+
+        let log = parse_pacman_log(LOG);
+        let journal = vec![Transaction {
+            id: "monarch-1".to_string(),
+            timestamp: log[1].timestamp + 30,
+            source: "monarch".to_string(),
+            command: None,
+            status: "completed".to_string(),
+            changes: log[1].changes.clone(),
+            config_backups: Vec::new(),
+        }];
+        let merged = merge_history(log, journal);
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged[1].id, "monarch-1");
+
+        let removals = HistoryFilter {
+            action: Some(PackageAction::Removed),
+            ..Default::default()
+        };
+        let page = page_of(merged.clone(), &removals, 1);
+        assert_eq!(page.total, 1);
+        assert!(!page.has_more);
+        let by_name = HistoryFilter {
+            package: Some("VLC".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(page_of(merged, &by_name, 1).transactions[0].id, "log-1");
+
+        let cache = vec![
+            "gimp-2.10.38-1-x86_64.pkg.tar.zst.sig".to_string(),
+            "gimp-help-2.10.38-1-any.pkg.tar.zst".to_string(),
+            "gimp-2.10.38-1-x86_64.pkg.tar.zst".to_string(),
+        ];
+        assert_eq!(
+            cached_package_file(&cache, "gimp", "2.10.38-1").as_deref(),
+            Some("/var/cache/pacman/pkg/gimp-2.10.38-1-x86_64.pkg.tar.zst")
+        );
+        assert_eq!(cached_package_file(&cache, "gimp", "2.10.36-1"), None);
+    }
+
+    #[test]
+    fn test_journal_roundtrip_and_external_merge() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = parse_pacman_log(LOG);
+        let legacy = Transaction {
+            id: "monarch-1".to_string(),
+            timestamp: log[0].timestamp + 30,
+            source: "monarch".to_string(),
+            command: None,
+            status: "completed".to_string(),
+            changes: log[0].changes.clone(),
+            config_backups: vec![ConfigBackup {
+                package: "vlc".to_string(),
+                version: "3.0.20-1".to_string(),
+                path: "/etc/vlcrc".to_string(),
+                sha1: "abc".to_string(),
+            }],
+        };
+        std::fs::write(
+            dir.path().join(LEGACY_JOURNAL_FILE),
+            format!("{}\nnot json\n", serde_json::to_string(&legacy).unwrap()),
+        )
+        .unwrap();
+
+        // The JSON-lines journal is imported once
+        let path = dir.path().join(JOURNAL_FILE);
+        let mut conn = open_journal(&path).unwrap();
+        assert!(!dir.path().join(LEGACY_JOURNAL_FILE).exists());
+        let external = Transaction {
+            id: "external-1".to_string(),
+            timestamp: log[1].timestamp + 10,
+            source: "external".to_string(),
+            changes: log[1].changes.clone(),
+            config_backups: Vec::new(),
+            ..legacy.clone()
+        };
+        insert_transaction(&mut conn, &external).unwrap();
+        drop(conn);
+        let conn = open_journal(&path).unwrap();
+        let journal = read_transactions(&conn, None).unwrap();
+        assert_eq!(journal, vec![legacy.clone(), external.clone()]);
+        assert_eq!(
+            read_transactions(&conn, Some("external-1")).unwrap(),
+            vec![external.clone()]
+        );
+        assert_eq!(
+            referenced_backups(&conn).unwrap(),
+            HashSet::from(["abc".to_string()])
+        );
+
+        // pacman.log wins over an external entry, the journal over its own log twin
+        let merged = merge_history(log.clone(), journal.clone());
+        let ids: Vec<&str> = merged.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec![log[2].id.as_str(), log[1].id.as_str(), "monarch-1"]
+        );
+        // Once pacman.log is rotated the external entry is all that's left
+        let merged = merge_history(Vec::new(), journal);
+        assert_eq!(merged[0].id, "external-1");
+
+        let accepted = Transaction {
+            id: "license-steam-1".to_string(),
+            command: Some("Accepted Steam Subscriber Agreement (revision 1) for steam".into()),
+            status: "license_accepted".to_string(),
+            changes: Vec::new(),
+            config_backups: Vec::new(),
+            ..legacy
+        };
+        let by_name = HistoryFilter {
+            package: Some("steam".to_string()),
+            ..Default::default()
+        };
+        assert!(matches_filter(&accepted, &by_name));
+        let removals = HistoryFilter {
+            action: Some(PackageAction::Removed),
+            ..Default::default()
+        };
+        assert!(!matches_filter(&accepted, &removals));
+    }
+
+    #[test]
+    fn test_config_backups_and_diff() {
+        let backup = |package: &str, version: &str, path: &str| ConfigBackup {
+            package: package.to_string(),
+            version: version.to_string(),
+            path: path.to_string(),
+            sha1: format!("{}-{}", package, version),
+        };
+        let change = |name: &str, action, old: Option<&str>| PackageChange {
+            name: name.to_string(),
+            action,
+            old_version: old.map(String::from),
+            new_version: None,
+        };
+        let saved = vec![
+            backup("mkinitcpio", "39-1", "/etc/mkinitcpio.conf"),
+            backup("pacman", "6.1.0-3", "/etc/pacman.conf"),
+            backup("sudo", "1.9.15-1", "/etc/sudoers"),
+        ];
+        let changes = vec![
+            change("mkinitcpio", PackageAction::Upgraded, Some("39-1")),
+            // Saved under another version: not this transaction's copy
+            change("pacman", PackageAction::Upgraded, Some("6.0.2-1")),
+            change("htop", PackageAction::Installed, None),
+        ];
+        let kept = configs_for_changes(saved, &changes);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].path, "/etc/mkinitcpio.conf");
+
+        assert_eq!(diff_lines("", ""), Vec::<DiffLine>::new());
+        let diff = diff_lines(
+            "MODULES=()\nHOOKS=(base udev)\nCOMPRESSION=zstd\n",
+            "MODULES=(i915)\nHOOKS=(base udev)\n",
+        );
+        let got: Vec<(DiffOp, &str)> = diff.iter().map(|l| (l.op, l.text.as_str())).collect();
+        assert_eq!(
+            got,
+            vec![
+                (DiffOp::Removed, "MODULES=()"),
+                (DiffOp::Added, "MODULES=(i915)"),
+                (DiffOp::Same, "HOOKS=(base udev)"),
+                (DiffOp::Removed, "COMPRESSION=zstd"),
+            ]
+        );
+    }
+}
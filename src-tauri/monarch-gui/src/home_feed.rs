@@ -0,0 +1,114 @@
+//! Push-style refresh for the home page. Polls the cheap trending inputs (Chaotic popular
+//! list, pkgstats top list, CachyOS toggle), hashes them and only re-runs the get_trending
+//! enrichment pipeline + emits "home-content-updated" when the hash actually changed.
+
+use crate::{
+    chaotic_api::ChaoticApiClient, metadata::MetadataState, models, repo_manager::RepoManager,
+};
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Matches the Chaotic trending cache TTL order of magnitude without hammering the APIs.
+const POLL_INTERVAL_SECS: u64 = 15 * 60;
+/// The frontend loads get_trending on startup; first poll only records the baseline.
+const STARTUP_DELAY_SECS: u64 = 60;
+
+/// Payload of "home-content-updated".
+#[derive(Debug, Serialize, Clone)]
+pub struct HomeContentUpdate {
+    pub hash: String,
+    pub updated_at: i64,
+    pub trending: Vec<models::Package>,
+}
+
+/// Hash only names and order: Chaotic download counts tick constantly and would
+/// make every poll look like a change.
+fn content_hash(chaotic: &[String], pkgstats: &[String], cachyos_enabled: bool) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    chaotic.hash(&mut hasher);
+    pkgstats.hash(&mut hasher);
+    cachyos_enabled.hash(&mut hasher);
+    hasher.finish()
+}
+
+async fn current_hash(app: &AppHandle) -> Option<u64> {
+    let chaotic: Vec<String> = app
+        .state::<ChaoticApiClient>()
+        .refresh_trending()
+        .await
+        .ok()?
+        .into_iter()
+        .map(|t| t.pkgbase_pkgname)
+        .collect();
+    // A failed pkgstats fetch hashes as empty rather than skipping the poll
+    let pkgstats: Vec<String> = crate::pkgstats_api::fetch_top_packages(15)
+        .await
+        .map(|pkgs| pkgs.into_iter().map(|p| p.name).collect())
+        .unwrap_or_default();
+    let cachyos_enabled = app.state::<RepoManager>().is_repo_enabled("cachyos").await;
+    Some(content_hash(&chaotic, &pkgstats, cachyos_enabled))
+}
+
+async fn poll_once(app: &AppHandle, last_hash: &mut Option<u64>) {
+    let Some(hash) = current_hash(app).await else {
+        return; // Offline or Chaotic backend down: keep the old baseline
+    };
+    let changed = last_hash.is_some_and(|h| h != hash);
+    let first = last_hash.is_none();
+    *last_hash = Some(hash);
+    if first || !changed {
+        return;
+    }
+
+    match crate::commands::search::get_trending(
+        app.state::<MetadataState>(),
+        app.state::<ChaoticApiClient>(),
+        app.state::<RepoManager>(),
+    )
+    .await
+    {
+        Ok(trending) => {
+            log::info!("Home content changed, pushing {} packages", trending.len());
+            let _ = app.emit(
+                "home-content-updated",
+                HomeContentUpdate {
+                    hash: format!("{:016x}", hash),
+                    updated_at: chrono::Utc::now().timestamp(),
+                    trending,
+                },
+            );
+        }
+        Err(e) => log::warn!("Home content refresh failed: {}", e),
+    }
+}
+
+/// Spawn the poller. Lives for the whole process.
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_secs(STARTUP_DELAY_SECS)).await;
+        let mut last_hash: Option<u64> = None;
+        loop {
+            poll_once(&app, &mut last_hash).await;
+            tokio::time::sleep(std::time::Duration::from_secs(POLL_INTERVAL_SECS)).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_hash_tracks_names_and_toggle() {
+        let chaotic = vec!["brave-bin".to_string(), "zen-browser-bin".to_string()];
+        let stats = vec!["firefox".to_string()];
+        let base = content_hash(&chaotic, &stats, false);
+        assert_eq!(base, content_hash(&chaotic, &stats, false));
+        assert_ne!(base, content_hash(&chaotic, &stats, true));
+
+        let reordered = vec!["zen-browser-bin".to_string(), "brave-bin".to_string()];
+        assert_ne!(base, content_hash(&reordered, &stats, false));
+    }
+}
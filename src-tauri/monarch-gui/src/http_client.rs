@@ -0,0 +1,379 @@
+//! Shared HTTP plumbing for the API modules (aur_api, chaotic_api, flathub_api) plus a
+//! network-condition simulator for resilience testing.
+//!
+//! Clients from [`client`] / [`builder`] follow the network settings (`network_settings`):
+//! the proxy and IPv4-only are looked up per request, and [`fetch`] holds body downloads
+//! to the rate limit, shared by all requests at once.
+//!
+//! In offline mode (`connectivity`) [`fetch`] fails straight away, and name
+//! resolution does too for anything else using these clients, so no caller sits out a
+//! timeout before falling back to its cache.
+//!
+//! Requests go through [`fetch`], which consults the per-host [`NetCondition`] table before
+//! and while talking to the network: added latency, bandwidth throttling, a connection reset
+//! after N body bytes, "offline", or a canned response that never touches the network. Tests
+//! install conditions with [`simulate`]; debug builds also read `MONARCH_NET_SIM`, e.g.
+//! `MONARCH_NET_SIM="aur.archlinux.org=latency:1500;flathub.org=offline;*=throttle:4096"`.
+
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// Canned bodies are handed out in chunks this size so throttling/fail-after apply to them too.
+const CANNED_CHUNK: usize = 16 * 1024;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NetCondition {
+    /// Added before the request is sent.
+    pub latency: Duration,
+    /// Body bandwidth cap in bytes/sec.
+    pub throttle_bps: Option<u64>,
+    /// Drop the connection once this many body bytes have arrived.
+    pub fail_after_bytes: Option<usize>,
+    /// Fail as if the host were unreachable.
+    pub offline: bool,
+    /// Answer with this status/body instead of going to the network.
+    pub canned: Option<(u16, Vec<u8>)>,
+}
+
+impl NetCondition {
+    pub fn offline() -> Self {
+        NetCondition {
+            offline: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn respond(status: u16, body: impl Into<Vec<u8>>) -> Self {
+        NetCondition {
+            canned: Some((status, body.into())),
+            ..Default::default()
+        }
+    }
+}
+
+/// Host → condition; "*" applies to hosts without their own entry.
+static CONDITIONS: Lazy<RwLock<HashMap<String, NetCondition>>> =
+    Lazy::new(|| RwLock::new(conditions_from_env()));
+
+fn conditions_from_env() -> HashMap<String, NetCondition> {
+    if !cfg!(debug_assertions) {
+        return HashMap::new();
+    }
+    match std::env::var("MONARCH_NET_SIM") {
+        Ok(spec) => {
+            let parsed = parse_sim_spec(&spec);
+            if !parsed.is_empty() {
+                log::warn!("Network simulation active: {}", spec);
+            }
+            parsed
+        }
+        Err(_) => HashMap::new(),
+    }
+}
+
+/// `host=opt[,opt...];host=...` with opts `latency:<ms>`, `throttle:<bytes/s>`,
+/// `fail_after:<bytes>`, `offline`, `status:<code>`. Malformed entries are skipped.
+fn parse_sim_spec(spec: &str) -> HashMap<String, NetCondition> {
+    let mut out = HashMap::new();
+    for entry in spec.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+        let Some((host, opts)) = entry.split_once('=') else {
+            continue;
+        };
+        let mut cond = NetCondition::default();
+        for opt in opts.split(',').map(str::trim) {
+            let (key, value) = opt.split_once(':').unwrap_or((opt, ""));
+            match (key, value.parse::<u64>().ok()) {
+                ("offline", _) => cond.offline = true,
+                ("latency", Some(ms)) => cond.latency = Duration::from_millis(ms),
+                ("throttle", Some(bps)) if bps > 0 => cond.throttle_bps = Some(bps),
+                ("fail_after", Some(n)) => cond.fail_after_bytes = Some(n as usize),
+                ("status", Some(code)) => cond.canned = Some((code as u16, Vec::new())),
+                _ => log::warn!("MONARCH_NET_SIM: ignoring '{}' for {}", opt, host),
+            }
+        }
+        out.insert(host.trim().to_lowercase(), cond);
+    }
+    out
+}
+
+/// Install (or replace) the condition for `host` ("*" for every host).
+pub fn simulate(host: &str, condition: NetCondition) {
+    if let Ok(mut map) = CONDITIONS.write() {
+        map.insert(host.to_lowercase(), condition);
+    }
+}
+
+pub fn clear_simulation(host: &str) {
+    if let Ok(mut map) = CONDITIONS.write() {
+        map.remove(&host.to_lowercase());
+    }
+}
+
+fn condition_for(host: &str) -> Option<NetCondition> {
+    let map = CONDITIONS.read().ok()?;
+    if map.is_empty() {
+        return None;
+    }
+    map.get(&host.to_lowercase())
+        .or_else(|| map.get("*"))
+        .cloned()
+}
+
+/// Default client for API calls.
+pub fn client(timeout: Duration) -> reqwest::Client {
+    builder(timeout)
+        .build()
+        .unwrap_or_else(|_| reqwest::Client::new())
+}
+
+/// Start of every client: callers with special needs add to this rather than starting
+/// from `reqwest::Client::builder()`, so the network settings still apply.
+pub fn builder(timeout: Duration) -> reqwest::ClientBuilder {
+    reqwest::Client::builder()
+        .user_agent(concat!("MonARCH-Store/", env!("CARGO_PKG_VERSION")))
+        .timeout(timeout)
+        .proxy(
+            reqwest::Proxy::custom(crate::network_settings::proxy_for)
+                .no_proxy(reqwest::NoProxy::from_env()),
+        )
+        .dns_resolver(Arc::new(SettingsResolver))
+}
+
+/// The system resolver, minus IPv6 addresses while the settings say IPv4 only.
+struct SettingsResolver;
+
+impl reqwest::dns::Resolve for SettingsResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        Box::pin(resolve_host(name.as_str().to_string()))
+    }
+}
+
+async fn resolve_host(
+    host: String,
+) -> Result<reqwest::dns::Addrs, Box<dyn std::error::Error + Send + Sync>> {
+    if crate::connectivity::is_offline() && host != "localhost" {
+        return Err(format!("{} not resolved: offline", host).into());
+    }
+    let ipv4_only = crate::network_settings::current().ipv4_only;
+    let addrs: Vec<std::net::SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+        .await?
+        .filter(|a| !ipv4_only || a.is_ipv4())
+        .collect();
+    if addrs.is_empty() {
+        return Err(format!("{} has no IPv4 address (IPv4-only is on)", host).into());
+    }
+    Ok(Box::new(addrs.into_iter()))
+}
+
+/// When the next body chunk may be handed out under the rate limit, across all requests.
+static RATE_LIMIT_NEXT: Lazy<tokio::sync::Mutex<tokio::time::Instant>> =
+    Lazy::new(|| tokio::sync::Mutex::new(tokio::time::Instant::now()));
+
+/// Wait until `bytes` more fit into the rate limit, if there is one. [`fetch`] calls this
+/// per chunk; code streaming a response itself calls it too.
+pub async fn rate_limit(bytes: usize) {
+    if let Some(kbps) = crate::network_settings::current().rate_limit_kbps {
+        pace(bytes, u64::from(kbps) * 1024).await;
+    }
+}
+
+async fn pace(bytes: usize, bps: u64) {
+    let ready_at = {
+        let mut next = RATE_LIMIT_NEXT.lock().await;
+        let start = (*next).max(tokio::time::Instant::now());
+        *next = start + Duration::from_secs_f64(bytes as f64 / bps as f64);
+        *next
+    };
+    tokio::time::sleep_until(ready_at).await;
+}
+
+/// A fully read response.
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: reqwest::StatusCode,
+    /// Empty for simulated responses.
+    pub headers: reqwest::header::HeaderMap,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    pub fn is_success(&self) -> bool {
+        self.status.is_success()
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    pub fn json<T: serde::de::DeserializeOwned>(&self) -> Result<T, String> {
+        serde_json::from_slice(&self.body).map_err(|e| e.to_string())
+    }
+}
+
+/// Applies the rate limit, and simulated throttling and fail-after, to a body as it arrives.
+struct BodyShaper {
+    condition: Option<NetCondition>,
+    received: usize,
+}
+
+impl BodyShaper {
+    async fn accept(&mut self, body: &mut Vec<u8>, chunk: &[u8], url: &str) -> Result<(), String> {
+        rate_limit(chunk.len()).await;
+        let Some(cond) = &self.condition else {
+            body.extend_from_slice(chunk);
+            return Ok(());
+        };
+        let mut chunk = chunk;
+        if let Some(limit) = cond.fail_after_bytes {
+            if self.received + chunk.len() > limit {
+                chunk = &chunk[..limit.saturating_sub(self.received)];
+                body.extend_from_slice(chunk);
+                return Err(format!(
+                    "error decoding response body for url ({}): simulated connection reset after {} bytes",
+                    url, limit
+                ));
+            }
+        }
+        if let Some(bps) = cond.throttle_bps {
+            let secs = chunk.len() as f64 / bps as f64;
+            tokio::time::sleep(Duration::from_secs_f64(secs)).await;
+        }
+        self.received += chunk.len();
+        body.extend_from_slice(chunk);
+        Ok(())
+    }
+}
+
+/// Reads like reqwest's send errors, which callers already treat as "network down".
+fn offline_error(url: &str) -> String {
+    format!("error sending request for url ({}): offline mode", url)
+}
+
+/// Send a request and read the whole body, honouring any simulated condition for its host.
+/// Errors read like reqwest's so callers' error handling is exercised unchanged.
+pub async fn fetch(request: reqwest::RequestBuilder) -> Result<HttpResponse, String> {
+    let (client, request) = request.build_split();
+    let request = request.map_err(|e| e.to_string())?;
+    let url = request.url().to_string();
+    if crate::connectivity::is_offline() {
+        return Err(offline_error(&url));
+    }
+    let condition = request.url().host_str().and_then(condition_for);
+
+    if let Some(cond) = &condition {
+        if cond.offline {
+            return Err(format!(
+                "error sending request for url ({}): simulated network unreachable",
+                url
+            ));
+        }
+        tokio::time::sleep(cond.latency).await;
+    }
+    let mut shaper = BodyShaper {
+        condition: condition.clone(),
+        received: 0,
+    };
+    let mut body = Vec::new();
+
+    if let Some((status, canned)) = condition.and_then(|c| c.canned) {
+        let status = reqwest::StatusCode::from_u16(status).map_err(|e| e.to_string())?;
+        for chunk in canned.chunks(CANNED_CHUNK) {
+            shaper.accept(&mut body, chunk, &url).await?;
+        }
+        return Ok(HttpResponse {
+            status,
+            headers: Default::default(),
+            body,
+        });
+    }
+
+    let mut resp = client.execute(request).await.map_err(|e| {
+        if e.is_connect() {
+            crate::connectivity::report_unreachable();
+        }
+        e.to_string()
+    })?;
+    let status = resp.status();
+    let headers = resp.headers().clone();
+    while let Some(chunk) = resp.chunk().await.map_err(|e| e.to_string())? {
+        shaper.accept(&mut body, &chunk, &url).await?;
+    }
+    Ok(HttpResponse {
+        status,
+        headers,
+        body,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sim_spec() {
+        let map = parse_sim_spec(
+            "aur.archlinux.org=latency:1500,fail_after:2048; Flathub.org=offline;*=throttle:4096;bad",
+        );
+        assert_eq!(map.len(), 3);
+        let aur = &map["aur.archlinux.org"];
+        assert_eq!(aur.latency, Duration::from_millis(1500));
+        assert_eq!(aur.fail_after_bytes, Some(2048));
+        assert!(map["flathub.org"].offline);
+        assert_eq!(map["*"].throttle_bps, Some(4096));
+    }
+
+    #[tokio::test]
+    async fn test_simulated_conditions_without_network() {
+        let client = client(Duration::from_secs(5));
+
+        simulate(
+            "canned.monarch.invalid",
+            NetCondition::respond(200, "[1,2,3]"),
+        );
+        let resp = fetch(client.get("https://canned.monarch.invalid/list"))
+            .await
+            .unwrap();
+        assert!(resp.is_success());
+        assert_eq!(resp.json::<Vec<u32>>().unwrap(), vec![1, 2, 3]);
+
+        simulate("down.monarch.invalid", NetCondition::offline());
+        let err = fetch(client.get("https://down.monarch.invalid/"))
+            .await
+            .unwrap_err();
+        assert!(err.contains("error sending request"));
+
+        simulate(
+            "flaky.monarch.invalid",
+            NetCondition {
+                fail_after_bytes: Some(20_000),
+                ..NetCondition::respond(200, vec![b'x'; 50_000])
+            },
+        );
+        let err = fetch(client.get("https://flaky.monarch.invalid/big"))
+            .await
+            .unwrap_err();
+        assert!(err.contains("after 20000 bytes"));
+
+        // 32 KiB at 64 KiB/s: two canned chunks, ~0.5 s in total
+        simulate(
+            "slow.monarch.invalid",
+            NetCondition {
+                throttle_bps: Some(64 * 1024),
+                latency: Duration::from_millis(100),
+                ..NetCondition::respond(503, vec![0u8; 32 * 1024])
+            },
+        );
+        let started = std::time::Instant::now();
+        let resp = fetch(client.get("https://slow.monarch.invalid/"))
+            .await
+            .unwrap();
+        assert_eq!(resp.status.as_u16(), 503);
+        assert_eq!(resp.body.len(), 32 * 1024);
+        assert!(started.elapsed() >= Duration::from_millis(550));
+
+        clear_simulation("canned.monarch.invalid");
+        assert!(condition_for("canned.monarch.invalid").is_none());
+    }
+}
@@ -0,0 +1,311 @@
+//! App icons cached on disk in ~/.cache/monarch-store/icon-cache, one PNG per app and size.
+//! AppStream and Flathub icons are remote URLs, inline data URIs or local files, often SVG;
+//! each is fetched once, rasterized (SVG via `rsvg-convert`), scaled down to one of
+//! [`ICON_SIZES`] and then served as an asset-protocol URL, so the webview neither refetches
+//! remote icons nor receives megabytes of base64 per listing. Files are keyed by app id and
+//! evicted like screenshots (`screenshot_cache::plan_eviction`).
+
+use base64::prelude::*;
+use futures::StreamExt;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+use tauri::State;
+
+/// Sizes icons are stored at; requests are rounded up to the next one.
+pub const ICON_SIZES: [u32; 3] = [48, 64, 128];
+const DEFAULT_SIZE: u32 = 64;
+const MAX_CACHE_BYTES: u64 = 64 * 1024 * 1024;
+const MAX_AGE_SECS: u64 = 90 * 86400;
+/// No icon is this big; anything larger is not worth decoding.
+const MAX_SOURCE_BYTES: usize = 4 * 1024 * 1024;
+/// Icons fetched at once by a batch.
+const BATCH_CONCURRENCY: usize = 8;
+
+/// Typed response for get_package_icon / get_icons_batch.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct CachedIcon {
+    /// Asset-protocol URL for `<img src>`.
+    pub url: String,
+    pub path: String,
+    pub size: u32,
+}
+
+pub fn get_icon_cache_dir() -> PathBuf {
+    crate::metadata::get_cache_dir().join("icon-cache")
+}
+
+/// Smallest stored size that is at least `requested`.
+pub fn bucket_size(requested: Option<u32>) -> u32 {
+    let requested = requested.unwrap_or(DEFAULT_SIZE);
+    ICON_SIZES
+        .iter()
+        .copied()
+        .find(|s| *s >= requested)
+        .unwrap_or(ICON_SIZES[ICON_SIZES.len() - 1])
+}
+
+/// App ids and package names as file names: anything outside [A-Za-z0-9._-] becomes '_'.
+fn cache_key(app_id: &str) -> String {
+    app_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn cached_path(app_id: &str, size: u32) -> PathBuf {
+    get_icon_cache_dir().join(format!("{}-{}.png", cache_key(app_id), size))
+}
+
+/// What Tauri's `convertFileSrc` makes of `path` on Linux.
+pub fn asset_url(path: &Path) -> String {
+    let mut url = String::from("asset://localhost/");
+    for byte in path.to_string_lossy().bytes() {
+        if byte.is_ascii_alphanumeric() || b"-_.!~*'()".contains(&byte) {
+            url.push(byte as char);
+        } else {
+            url.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    url
+}
+
+fn cached_icon(path: PathBuf, size: u32) -> CachedIcon {
+    CachedIcon {
+        url: asset_url(&path),
+        path: path.to_string_lossy().into_owned(),
+        size,
+    }
+}
+
+/// Already cached: no work beyond a stat.
+pub fn lookup(app_id: &str, size: u32) -> Option<CachedIcon> {
+    let path = cached_path(app_id, size);
+    path.exists().then(|| {
+        crate::screenshot_cache::touch(&path);
+        cached_icon(path, size)
+    })
+}
+
+/// Raw bytes of an icon as metadata hands it out: URL, data URI or path.
+async fn load_source(source: &str) -> Result<Vec<u8>, String> {
+    let bytes = if source.starts_with("https://") || source.starts_with("http://") {
+        let client = crate::http_client::client(Duration::from_secs(15));
+        let resp = crate::http_client::fetch(client.get(source)).await?;
+        if !resp.is_success() {
+            return Err(format!("Icon download failed: {}", resp.status));
+        }
+        resp.body
+    } else if let Some(data) = source.strip_prefix("data:") {
+        let (_, encoded) = data
+            .split_once(";base64,")
+            .ok_or_else(|| "Unsupported data URI".to_string())?;
+        BASE64_STANDARD
+            .decode(encoded)
+            .map_err(|e| format!("Bad icon data URI: {}", e))?
+    } else {
+        let path = source.strip_prefix("file://").unwrap_or(source);
+        if !path.starts_with('/') {
+            return Err(format!("Not an icon source: {}", source));
+        }
+        tokio::fs::read(path).await.map_err(|e| e.to_string())?
+    };
+    if bytes.len() > MAX_SOURCE_BYTES {
+        return Err("Icon is too large to cache".to_string());
+    }
+    Ok(bytes)
+}
+
+fn is_svg(bytes: &[u8]) -> bool {
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(1024)]).to_lowercase();
+    head.contains("<svg")
+}
+
+fn rasterize_svg(svg: &[u8], size: u32) -> Result<Vec<u8>, String> {
+    let mut child = Command::new("rsvg-convert")
+        .args(["--width", &size.to_string(), "--height", &size.to_string()])
+        .args(["--keep-aspect-ratio", "--format", "png"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("rsvg-convert not available ({}); install librsvg", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(svg).map_err(|e| e.to_string())?;
+    }
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    if !output.status.success() || output.stdout.is_empty() {
+        return Err("rsvg-convert could not render the icon".to_string());
+    }
+    Ok(output.stdout)
+}
+
+/// PNG of at most `size`x`size`; smaller bitmaps are kept as they are.
+fn to_png(bytes: &[u8], size: u32) -> Result<Vec<u8>, String> {
+    let raster = if is_svg(bytes) {
+        rasterize_svg(bytes, size)?
+    } else {
+        bytes.to_vec()
+    };
+    let mut img =
+        image::load_from_memory(&raster).map_err(|e| format!("Failed to decode icon: {}", e))?;
+    if img.width() > size || img.height() > size {
+        img = img.resize(size, size, image::imageops::FilterType::Lanczos3);
+    }
+    let mut out = std::io::Cursor::new(Vec::new());
+    img.write_to(&mut out, image::ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode icon: {}", e))?;
+    Ok(out.into_inner())
+}
+
+/// Cached icon for `app_id`, converting `source` on first use.
+pub async fn get(app_id: &str, source: &str, size: u32) -> Result<CachedIcon, String> {
+    if let Some(hit) = lookup(app_id, size) {
+        return Ok(hit);
+    }
+    let bytes = load_source(source).await?;
+    let png = tokio::task::spawn_blocking(move || to_png(&bytes, size))
+        .await
+        .map_err(|e| format!("Task join error: {}", e))??;
+
+    let dir = get_icon_cache_dir();
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = cached_path(app_id, size);
+    // Write-then-rename so a reader never sees half an icon
+    let tmp = path.with_extension("part");
+    std::fs::write(&tmp, png).map_err(|e| e.to_string())?;
+    std::fs::rename(&tmp, &path).map_err(|e| e.to_string())?;
+    tokio::task::spawn_blocking(move || {
+        crate::screenshot_cache::evict(&dir, MAX_CACHE_BYTES, MAX_AGE_SECS)
+    });
+    Ok(cached_icon(path, size))
+}
+
+/// Package name -> (cache key, icon source) from AppStream; the key is the app id when known.
+fn resolve(
+    state_meta: &crate::metadata::MetadataState,
+    names: &[String],
+) -> Vec<(String, String, Option<String>)> {
+    let Ok(loader) = state_meta.0.lock() else {
+        return Vec::new();
+    };
+    names
+        .iter()
+        .map(|name| {
+            let key = loader.find_app_id(name).unwrap_or_else(|| name.clone());
+            (name.clone(), key, loader.find_icon_heuristic(name))
+        })
+        .collect()
+}
+
+/// Icons for many packages at once, keyed by package name; packages without one are left
+/// out. Missing icons are fetched concurrently.
+pub async fn get_many(
+    state_meta: &crate::metadata::MetadataState,
+    names: &[String],
+    size: u32,
+) -> HashMap<String, CachedIcon> {
+    let mut icons = HashMap::new();
+    let mut missing = Vec::new();
+    for (name, key, source) in resolve(state_meta, names) {
+        match lookup(&key, size) {
+            Some(hit) => {
+                icons.insert(name, hit);
+            }
+            None => {
+                if let Some(source) = source {
+                    missing.push((name, key, source));
+                }
+            }
+        }
+    }
+    let fetched: Vec<(String, Result<CachedIcon, String>)> = futures::stream::iter(missing)
+        .map(|(name, key, source)| async move { (name, get(&key, &source, size).await) })
+        .buffer_unordered(BATCH_CONCURRENCY)
+        .collect()
+        .await;
+    for (name, result) in fetched {
+        match result {
+            Ok(icon) => {
+                icons.insert(name, icon);
+            }
+            Err(e) => log::debug!("No cached icon for {}: {}", name, e),
+        }
+    }
+    icons
+}
+
+/// Cached icons for package names, in one call instead of one per row.
+#[tauri::command]
+pub async fn get_icons_batch(
+    state_meta: State<'_, crate::metadata::MetadataState>,
+    names: Vec<String>,
+    size: Option<u32>,
+) -> Result<HashMap<String, CachedIcon>, String> {
+    Ok(get_many(state_meta.inner(), &names, bucket_size(size)).await)
+}
+
+/// Bytes currently used by the icon cache; reported by get_cache_size.
+pub fn cache_size() -> u64 {
+    crate::screenshot_cache::scan(&get_icon_cache_dir())
+        .iter()
+        .map(|e| e.size)
+        .sum()
+}
+
+/// Drop every cached icon; part of clear_cache.
+pub fn clear() {
+    let _ = std::fs::remove_dir_all(get_icon_cache_dir());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_icon_keys_sizes_and_urls() {
+        assert_eq!(bucket_size(None), 64);
+        assert_eq!(bucket_size(Some(32)), 48);
+        assert_eq!(bucket_size(Some(100)), 128);
+        assert_eq!(bucket_size(Some(512)), 128);
+
+        assert_eq!(cache_key("org.videolan.VLC"), "org.videolan.VLC");
+        assert_eq!(cache_key("../etc/passwd"), ".._etc_passwd");
+
+        assert_eq!(
+            asset_url(Path::new(
+                "/home/a b/.cache/monarch-store/icon-cache/vlc-64.png"
+            )),
+            "asset://localhost/%2Fhome%2Fa%20b%2F.cache%2Fmonarch-store%2Ficon-cache%2Fvlc-64.png"
+        );
+
+        assert!(is_svg(
+            b"<?xml version=\"1.0\"?>\n<svg xmlns=\"http://www.w3.org/2000/svg\"/>"
+        ));
+        assert!(!is_svg(b"\x89PNG\r\n\x1a\n"));
+    }
+
+    #[test]
+    fn test_to_png_scales_down_only() {
+        let encode = |w, h| {
+            let mut out = std::io::Cursor::new(Vec::new());
+            image::DynamicImage::new_rgba8(w, h)
+                .write_to(&mut out, image::ImageFormat::Png)
+                .unwrap();
+            out.into_inner()
+        };
+        let big = image::load_from_memory(&to_png(&encode(256, 128), 64).unwrap()).unwrap();
+        assert_eq!((big.width(), big.height()), (64, 32));
+        let small = image::load_from_memory(&to_png(&encode(32, 32), 64).unwrap()).unwrap();
+        assert_eq!((small.width(), small.height()), (32, 32));
+    }
+}
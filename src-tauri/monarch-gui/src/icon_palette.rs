@@ -0,0 +1,184 @@
+//! Dominant colors of an app icon, for tinting the details page header (as GNOME Software
+//! does). The icon comes from `icon_cache`; its palette is a median cut over the opaque
+//! pixels, color-thief style, and is saved next to the icon as `<icon>.palette.json` so each
+//! icon is only analysed once.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+/// Colors returned, most common first.
+const PALETTE_SIZE: usize = 5;
+/// Icon size analysed; plenty for a palette and already cached for lists.
+const ANALYSIS_SIZE: u32 = 64;
+/// Pixels more transparent than this are background.
+const MIN_ALPHA: u8 = 125;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+struct StoredPalette {
+    colors: Vec<String>,
+}
+
+/// One median-cut box.
+struct ColorBox {
+    pixels: Vec<[u8; 3]>,
+}
+
+impl ColorBox {
+    fn widest_channel(&self) -> (usize, u8) {
+        (0..3)
+            .map(|c| {
+                let (min, max) = self.pixels.iter().fold((u8::MAX, u8::MIN), |(lo, hi), p| {
+                    (lo.min(p[c]), hi.max(p[c]))
+                });
+                (c, max.saturating_sub(min))
+            })
+            .max_by_key(|(_, range)| *range)
+            .unwrap_or((0, 0))
+    }
+
+    fn average(&self) -> [u8; 3] {
+        let n = self.pixels.len().max(1) as u64;
+        let mut sum = [0u64; 3];
+        for p in &self.pixels {
+            for (total, value) in sum.iter_mut().zip(p) {
+                *total += u64::from(*value);
+            }
+        }
+        [(sum[0] / n) as u8, (sum[1] / n) as u8, (sum[2] / n) as u8]
+    }
+}
+
+/// Opaque pixels of an image, leaving out near-white ones (icon backgrounds and highlights).
+fn opaque_pixels(img: &image::RgbaImage) -> Vec<[u8; 3]> {
+    img.pixels()
+        .filter(|p| p[3] >= MIN_ALPHA)
+        .map(|p| [p[0], p[1], p[2]])
+        .filter(|p| !(p[0] > 250 && p[1] > 250 && p[2] > 250))
+        .collect()
+}
+
+/// Up to `count` representative colors, the most common first.
+pub fn median_cut(pixels: Vec<[u8; 3]>, count: usize) -> Vec<[u8; 3]> {
+    if pixels.is_empty() || count == 0 {
+        return Vec::new();
+    }
+    let mut boxes = vec![ColorBox { pixels }];
+    while boxes.len() < count {
+        // Split the most populous box that still has a spread
+        let Some(index) = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.pixels.len() > 1 && b.widest_channel().1 > 0)
+            .max_by_key(|(_, b)| b.pixels.len())
+            .map(|(i, _)| i)
+        else {
+            break;
+        };
+        let mut chosen = boxes.swap_remove(index);
+        let (channel, _) = chosen.widest_channel();
+        chosen.pixels.sort_unstable_by_key(|p| p[channel]);
+        // Cut at the median, moved to where the value changes so equal colors stay together
+        let pivot = chosen.pixels[chosen.pixels.len() / 2][channel];
+        let mut cut = chosen.pixels.partition_point(|p| p[channel] < pivot);
+        if cut == 0 {
+            cut = chosen.pixels.partition_point(|p| p[channel] <= pivot);
+        }
+        let upper = chosen.pixels.split_off(cut);
+        boxes.push(chosen);
+        boxes.push(ColorBox { pixels: upper });
+    }
+    boxes.sort_by_key(|b| std::cmp::Reverse(b.pixels.len()));
+    boxes.iter().map(ColorBox::average).collect()
+}
+
+pub fn to_hex(color: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+}
+
+fn palette_path(icon: &Path) -> PathBuf {
+    PathBuf::from(format!("{}.palette.json", icon.display()))
+}
+
+/// Saved palette, if it is at least as new as the icon.
+fn load_saved(icon: &Path) -> Option<Vec<String>> {
+    let path = palette_path(icon);
+    let saved = std::fs::metadata(&path).ok()?.modified().ok()?;
+    let icon_modified = std::fs::metadata(icon).ok()?.modified().ok()?;
+    if saved < icon_modified {
+        return None;
+    }
+    let stored: StoredPalette = serde_json::from_slice(&std::fs::read(&path).ok()?).ok()?;
+    Some(stored.colors)
+}
+
+fn analyse(icon: &Path) -> Result<Vec<String>, String> {
+    let img = image::open(icon)
+        .map_err(|e| format!("Failed to decode icon: {}", e))?
+        .to_rgba8();
+    let colors: Vec<String> = median_cut(opaque_pixels(&img), PALETTE_SIZE)
+        .into_iter()
+        .map(to_hex)
+        .collect();
+    if let Ok(json) = serde_json::to_vec(&StoredPalette {
+        colors: colors.clone(),
+    }) {
+        let _ = std::fs::write(palette_path(icon), json);
+    }
+    Ok(colors)
+}
+
+/// Hex colors of the app's icon, most common first; empty when the app has no icon.
+/// `app_id` may also be a package name.
+#[tauri::command]
+pub async fn extract_dominant_colors(
+    state_meta: State<'_, crate::metadata::MetadataState>,
+    app_id: String,
+) -> Result<Vec<String>, String> {
+    let pkg_name = state_meta
+        .inner()
+        .0
+        .lock()
+        .map_err(|e| e.to_string())?
+        .resolve_package_name(&app_id);
+    let names = [pkg_name.clone()];
+    let Some(icon) = crate::icon_cache::get_many(state_meta.inner(), &names, ANALYSIS_SIZE)
+        .await
+        .remove(&pkg_name)
+    else {
+        return Ok(Vec::new());
+    };
+    let icon = PathBuf::from(icon.path);
+    if let Some(colors) = load_saved(&icon) {
+        return Ok(colors);
+    }
+    tokio::task::spawn_blocking(move || analyse(&icon))
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_median_cut_orders_by_population() {
+        // 3/4 orange, 1/4 blue, a transparent corner and white highlights that don't count
+        let mut img = image::RgbaImage::new(8, 8);
+        for (x, y, px) in img.enumerate_pixels_mut() {
+            *px = match (x, y) {
+                (0, 0) => image::Rgba([0, 0, 0, 0]),
+                (1, 0) => image::Rgba([255, 255, 255, 255]),
+                (_, y) if y < 6 => image::Rgba([240, 120, 20, 255]),
+                _ => image::Rgba([30, 60, 200, 255]),
+            };
+        }
+        let pixels = opaque_pixels(&img);
+        assert_eq!(pixels.len(), 62);
+        let colors: Vec<String> = median_cut(pixels, 5).into_iter().map(to_hex).collect();
+        // Only two distinct colors, so only two boxes
+        assert_eq!(colors, vec!["#f07814", "#1e3cc8"]);
+
+        assert!(median_cut(Vec::new(), 5).is_empty());
+    }
+}
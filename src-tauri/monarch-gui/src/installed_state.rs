@@ -0,0 +1,109 @@
+//! Installed packages (name → version) read from the local DB directory, cached so listing
+//! commands can mark every result `installed` / `update_available` in the same round trip
+//! instead of the frontend asking per package. The state watcher swaps in a fresh snapshot
+//! after each transaction, ours or external; helper runs drop it in case the watcher isn't
+//! running.
+
+use crate::models::Package;
+use once_cell::sync::Lazy;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+static SNAPSHOT: Lazy<RwLock<Option<Arc<HashMap<String, String>>>>> =
+    Lazy::new(|| RwLock::new(None));
+
+/// The current snapshot, read from disk if there is none.
+pub fn snapshot() -> Arc<HashMap<String, String>> {
+    if let Some(current) = SNAPSHOT.read().ok().and_then(|s| s.clone()) {
+        return current;
+    }
+    let fresh = Arc::new(crate::state_watcher::read_local_db());
+    if let Ok(mut slot) = SNAPSHOT.write() {
+        *slot = Some(fresh.clone());
+    }
+    fresh
+}
+
+pub fn replace(versions: HashMap<String, String>) {
+    if let Ok(mut slot) = SNAPSHOT.write() {
+        *slot = Some(Arc::new(versions));
+    }
+}
+
+/// The next reader goes back to disk.
+pub fn invalidate() {
+    if let Ok(mut slot) = SNAPSHOT.write() {
+        *slot = None;
+    }
+}
+
+/// Set `installed` and `update_available` on repo and AUR results (and their alternatives).
+/// Flatpak results keep what the Flatpak side reported.
+pub fn annotate(packages: &mut [Package]) {
+    annotate_with(packages, &snapshot());
+}
+
+fn annotate_with(packages: &mut [Package], installed: &HashMap<String, String>) {
+    for pkg in packages {
+        if pkg.source.source_type != "flatpak" {
+            let local = installed.get(&pkg.name);
+            pkg.installed = local.is_some();
+            // "latest" is a placeholder for listings that never looked the version up
+            pkg.update_available = local.is_some_and(|v| {
+                !pkg.version.is_empty()
+                    && pkg.version != "latest"
+                    && crate::versions::compare(&pkg.version, v) == Ordering::Greater
+            });
+        }
+        if let Some(alternatives) = pkg.alternatives.as_mut() {
+            annotate_with(alternatives, installed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PackageSource;
+
+    fn pkg(name: &str, version: &str, source: PackageSource) -> Package {
+        Package {
+            name: name.to_string(),
+            version: version.to_string(),
+            source,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_annotate() {
+        let installed: HashMap<String, String> = [("vlc", "3.0.20-1"), ("gimp", "2.10.38-1")]
+            .iter()
+            .map(|(n, v)| (n.to_string(), v.to_string()))
+            .collect();
+        let mut flatpak = pkg(
+            "org.gimp.GIMP",
+            "2.10.38",
+            PackageSource::new("flatpak", "flathub", "2.10.38", "Flathub"),
+        );
+        flatpak.installed = true;
+        let mut packages = vec![
+            Package {
+                alternatives: Some(vec![pkg("vlc", "3.0.21-1", PackageSource::chaotic())]),
+                ..pkg("vlc", "3.0.20-1", PackageSource::official())
+            },
+            pkg("gimp", "latest", PackageSource::official()),
+            pkg("htop", "3.3-1", PackageSource::official()),
+            flatpak,
+        ];
+        annotate_with(&mut packages, &installed);
+
+        assert!(packages[0].installed && !packages[0].update_available);
+        let alt = &packages[0].alternatives.as_ref().unwrap()[0];
+        assert!(alt.installed && alt.update_available);
+        assert!(packages[1].installed && !packages[1].update_available);
+        assert!(!packages[2].installed);
+        assert!(packages[3].installed);
+    }
+}
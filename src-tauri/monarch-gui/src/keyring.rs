@@ -0,0 +1,233 @@
+//! pacman keyring page: the keys in /etc/pacman.d/gnupg and how far pacman trusts each, plus
+//! lsign / import / delete / third-party refresh through the helper (Keyring). Listing only
+//! reads the public keyring, so it runs unprivileged like check_keyring_health.
+
+use crate::helper_client::{self, HelperCommand};
+use monarch_types::KeyringAction;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use tokio::process::Command;
+
+const PACMAN_GNUPG: &str = "/etc/pacman.d/gnupg";
+
+/// One public key in pacman's keyring.
+#[derive(Debug, Serialize, Clone, PartialEq, Default)]
+pub struct PacmanKey {
+    /// Long key id (last 16 hex digits of the fingerprint).
+    pub keyid: String,
+    pub fingerprint: String,
+    pub uids: Vec<String>,
+    /// Unix time.
+    pub created: Option<i64>,
+    pub expires: Option<i64>,
+    /// ultimate (master keys), full (signed by them, or lsigned), marginal, unknown, never,
+    /// expired, revoked, disabled or invalid.
+    pub validity: String,
+}
+
+fn validity_name(code: &str) -> &'static str {
+    match code {
+        "u" => "ultimate",
+        "f" => "full",
+        "m" => "marginal",
+        "n" => "never",
+        "e" => "expired",
+        "r" => "revoked",
+        "d" => "disabled",
+        "i" => "invalid",
+        _ => "unknown",
+    }
+}
+
+/// gpg uid fields escape `:` and non-printables as `\xNN`.
+fn unescape_uid(uid: &str) -> String {
+    let mut out = Vec::with_capacity(uid.len());
+    let bytes = uid.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' && bytes.get(i + 1) == Some(&b'x') {
+            if let Some(b) = uid
+                .get(i + 2..i + 4)
+                .and_then(|h| u8::from_str_radix(h, 16).ok())
+            {
+                out.push(b);
+                i += 4;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Keys from `gpg --with-colons --list-keys`. Subkey fingerprints are skipped.
+pub fn parse_colon_listing(text: &str) -> Vec<PacmanKey> {
+    let mut keys: Vec<PacmanKey> = Vec::new();
+    let mut in_primary = false;
+    for line in text.lines() {
+        let fields: Vec<&str> = line.split(':').collect();
+        let field = |i: usize| fields.get(i).copied().unwrap_or_default();
+        match field(0) {
+            "pub" => {
+                in_primary = true;
+                keys.push(PacmanKey {
+                    keyid: field(4).to_string(),
+                    created: field(5).parse().ok(),
+                    expires: field(6).parse().ok(),
+                    validity: validity_name(field(1)).to_string(),
+                    ..Default::default()
+                });
+            }
+            "sub" => in_primary = false,
+            "fpr" if in_primary => {
+                if let Some(key) = keys.last_mut() {
+                    if key.fingerprint.is_empty() {
+                        key.fingerprint = field(9).to_string();
+                    }
+                }
+            }
+            "uid" => {
+                if let Some(key) = keys.last_mut() {
+                    let uid = unescape_uid(field(9));
+                    if !uid.is_empty() {
+                        key.uids.push(uid);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    keys
+}
+
+/// Keys in pacman's keyring.
+#[tauri::command]
+pub async fn list_pacman_keys() -> Result<Vec<PacmanKey>, String> {
+    let output = Command::new("gpg")
+        .args([
+            "--homedir",
+            PACMAN_GNUPG,
+            "--no-permission-warning",
+            "--no-auto-check-trustdb",
+            "--with-colons",
+            "--fixed-list-mode",
+            "--list-keys",
+        ])
+        .env("LC_ALL", "C")
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run gpg: {}", e))?;
+    let keys = parse_colon_listing(&String::from_utf8_lossy(&output.stdout));
+    if keys.is_empty() && !output.status.success() {
+        return Err(format!(
+            "Could not read the pacman keyring: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(keys)
+}
+
+async fn run_keyring_action(
+    app: &AppHandle,
+    action: KeyringAction,
+    password: Option<String>,
+) -> Result<(), String> {
+    let _guard = crate::utils::PRIVILEGED_LOCK.lock().await;
+    let result =
+        helper_client::run_helper(app, HelperCommand::Keyring { action }, password, |msg| {
+            let _ = app.emit("repair-log", &msg.message);
+        })
+        .await?;
+    if result.success {
+        Ok(())
+    } else {
+        Err(result
+            .error
+            .unwrap_or_else(|| "Keyring operation failed".to_string()))
+    }
+}
+
+/// Trust a key that is already in the keyring (`pacman-key --lsign-key`), by fingerprint.
+#[tauri::command]
+pub async fn locally_sign_key(
+    app: AppHandle,
+    keyid: String,
+    password: Option<String>,
+) -> Result<(), String> {
+    run_keyring_action(&app, KeyringAction::LocallySign { keyid }, password).await
+}
+
+/// Fetch a key by fingerprint from `server` (keyserver.ubuntu.com by default) and trust it.
+#[tauri::command]
+pub async fn import_key_from_keyserver(
+    app: AppHandle,
+    keyid: String,
+    server: Option<String>,
+    password: Option<String>,
+) -> Result<(), String> {
+    let server = server.filter(|s| !s.trim().is_empty());
+    run_keyring_action(
+        &app,
+        KeyringAction::ImportFromKeyserver { keyid, server },
+        password,
+    )
+    .await
+}
+
+/// Delete a key. The distribution's master keys are refused by the helper.
+#[tauri::command]
+pub async fn remove_key(
+    app: AppHandle,
+    keyid: String,
+    password: Option<String>,
+) -> Result<(), String> {
+    run_keyring_action(&app, KeyringAction::Remove { keyid }, password).await
+}
+
+/// Re-import and trust the Chaotic-AUR and CachyOS signing keys (for the enabled ones).
+#[tauri::command]
+pub async fn refresh_third_party_keys(
+    app: AppHandle,
+    password: Option<String>,
+) -> Result<(), String> {
+    run_keyring_action(&app, KeyringAction::RefreshThirdParty, password).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_colon_listing() {
+        let listing = "\
+tru::1:1700000000:0:3:1:5
+pub:u:4096:1:76A5EF9054449A5C:1567000000:::u:::scSC::::::23::0:
+fpr:::::::::3E80CA1A8B89F69CBA57D98A76A5EF9054449A5C:
+uid:u::::1567000000::ABC::Pierre Schmitz (Arch Linux Master Key) <pierre@master-key.archlinux.org>::::::::::0:
+pub:f:4096:1:3056513887B78AEB:1600000000:1900000000::-:::scSC::::::23::0:
+fpr:::::::::EF925EA60F33D0CB85C44AD13056513887B78AEB:
+uid:f::::1600000000::DEF::Nicholas Guriev\\x3a chaotic <guriev@example.org>::::::::::0:
+sub:f:4096:1:AAAABBBBCCCCDDDD:1600000000::::::e::::::23:
+fpr:::::::::0000000000000000000000000000AAAABBBBCCCCDDDD:
+";
+        let keys = parse_colon_listing(listing);
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[0].keyid, "76A5EF9054449A5C");
+        assert_eq!(keys[0].validity, "ultimate");
+        assert_eq!(
+            keys[0].fingerprint,
+            "3E80CA1A8B89F69CBA57D98A76A5EF9054449A5C"
+        );
+        assert_eq!(keys[1].validity, "full");
+        assert_eq!(keys[1].expires, Some(1900000000));
+        assert_eq!(
+            keys[1].fingerprint,
+            "EF925EA60F33D0CB85C44AD13056513887B78AEB"
+        );
+        assert_eq!(
+            keys[1].uids,
+            vec!["Nicholas Guriev: chaotic <guriev@example.org>"]
+        );
+    }
+}
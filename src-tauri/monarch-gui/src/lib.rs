@@ -1,22 +1,58 @@
 pub(crate) mod alpm_progress;
 pub(crate) mod alpm_read;
+pub(crate) mod app_data;
 pub(crate) mod labels;
+pub(crate) mod license;
+pub(crate) mod license_prompt;
+pub(crate) mod local_api;
 pub(crate) mod aur_api;
+pub(crate) mod aur_build;
+pub(crate) mod catalog;
 pub(crate) mod chaotic_api;
 pub(crate) mod commands;
+pub(crate) mod connectivity;
+pub(crate) mod content_rating;
+pub(crate) mod dbus_service;
+pub(crate) mod digest;
 pub(crate) mod distro_context;
 pub(crate) mod error_classifier;
+pub(crate) mod eula;
 pub(crate) mod flathub_api;
+pub(crate) mod fwupd_api;
 pub(crate) mod helper_client;
+pub(crate) mod helper_rpc;
+pub(crate) mod history;
+pub(crate) mod home_feed;
+pub(crate) mod http_client;
+pub(crate) mod icon_cache;
+pub(crate) mod icon_palette;
+pub(crate) mod installed_state;
+pub(crate) mod keyring;
+pub(crate) mod maintenance;
 pub(crate) mod metadata;
+pub(crate) mod migration;
 pub(crate) mod models;
+pub(crate) mod network_settings;
+pub(crate) mod news_api;
 pub(crate) mod odrs_api;
+pub(crate) mod pkgbuild_cache;
 pub(crate) mod pkgstats_api;
+pub(crate) mod pty_runner;
 pub(crate) mod repair;
 pub(crate) mod repo_db;
+pub(crate) mod repo_desc;
 pub(crate) mod repo_manager;
+pub(crate) mod scheduler;
+pub(crate) mod screenshot_cache;
+pub(crate) mod search_index;
 pub(crate) mod scm_api;
+pub(crate) mod state_watcher;
+pub(crate) mod systemd;
+pub(crate) mod tray;
+pub(crate) mod update_conditions;
+pub(crate) mod update_policy;
 pub(crate) mod utils;
+pub(crate) mod versions;
 
 #[cfg(test)]
 mod tests;
@@ -29,6 +65,17 @@ pub struct ScmState(pub scm_api::ScmClient);
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // Before RepoManager::new() reads repos.json
+    migration::run();
+
+    // A monarch:// or appstream:// link goes to the instance that is already open
+    let launch_link = dbus_service::launch_url();
+    if let Some(url) = &launch_link {
+        if dbus_service::forward_to_running(url) {
+            return;
+        }
+    }
+
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_fs::init())
@@ -61,10 +108,22 @@ pub fn run() {
             metadata::AppStreamLoader::new(),
         )))
         .manage(ScmState(scm_api::ScmClient::new()))
+        .manage(search_index::SearchIndexState::default())
+        .manage(catalog::CatalogState::default())
         .manage(distro_context::get_distro_context()) // Operation True Identity: Shared Context
+        .manage(dbus_service::LaunchLink(std::sync::Mutex::new(launch_link)))
         .setup(|app| {
             let handle = app.handle().clone();
 
+            // Proxy / rate limit / IPv4-only before anything goes to the network
+            network_settings::load(app.handle());
+
+            // AppStream names and descriptions in the language picked in Settings
+            metadata::load_catalog_locale(app.handle());
+
+            // Offline mode setting + background reachability probes
+            connectivity::start(app.handle().clone());
+
             // v0.2.40: RUNTIME REQUIREMENT CHECK
             // Prevent silent crashes if the PKGBUILD failed us.
             let required_bins = vec!["git", "checkupdates", "pkexec"];
@@ -90,8 +149,39 @@ pub fn run() {
                 // metadata init is fine as it's separate
                 let state_meta = handle.state::<metadata::MetadataState>();
                 state_meta.init(24).await;
+
+                // Repos and AppStream are both in: build the local search index
+                search_index::refresh(&handle).await;
+                catalog::refresh(&handle).await;
             });
 
+            // Rebuild the search index and the catalog after every repo sync
+            search_index::start(app.handle().clone());
+            catalog::start(app.handle().clone());
+
+            // Background update checks (interval from settings.json, 0 = off)
+            scheduler::start(app.handle().clone());
+
+            // Daily disk snapshots and the opt-in weekly digest
+            digest::start(app.handle().clone());
+
+            // Localhost API for dashboards, if the user turned it on
+            local_api::start(app.handle().clone());
+
+            // org.monarchstore.Store on the session bus for scripts and other apps
+            dbus_service::start(app.handle().clone());
+
+            // Pick up pacman/yay runs from a terminal while the app is open
+            state_watcher::start(app.handle().clone());
+
+            // Home page: push trending/featured changes instead of frontend polling
+            home_feed::start(app.handle().clone());
+
+            // Tray icon: quick actions + pending-updates counter
+            if let Err(e) = tray::init(app.handle()) {
+                log::warn!("Failed to create tray icon: {}", e);
+            }
+
             // Phase 2: The Chameleon (Cross-DE GUI)
             // 2. Ghost Protocol: Wayland Detection
             if std::env::var("WAYLAND_DISPLAY").is_ok() {
@@ -146,37 +236,150 @@ pub fn run() {
             // Search Commands
             commands::search::search_aur,
             commands::search::search_packages,
+            commands::search::search_index_query,
             commands::search::get_packages_by_names,
             commands::search::get_chaotic_package_info,
             commands::search::get_chaotic_packages_batch,
+            commands::search::get_build_status,
             commands::search::get_trending,
             commands::search::get_package_variants,
+            commands::search::get_optimized_alternatives,
             commands::search::get_category_packages_paginated,
+            catalog::get_package_scores,
+            commands::collections::get_collections,
+            commands::groups::get_package_groups,
+            commands::groups::get_group_members,
+            commands::groups::install_package_group,
+            commands::kernel_manager::get_kernels,
+            commands::kernel_manager::install_kernel,
+            commands::kernel_manager::remove_kernel,
+            commands::hardware::get_hardware_report,
+            commands::hardware::install_recommended_drivers,
+            commands::distro_tools::get_distro_tools,
+            commands::distro_tools::install_distro_tools,
+            commands::local_file::preview_local_file,
+            commands::local_file::install_local_file,
+            commands::compare::compare_packages,
             // Package Commands
             commands::package::install_package,
             commands::package::uninstall_package,
+            commands::package::uninstall_packages,
             commands::package::get_essentials_list,
             commands::package::abort_installation,
+            commands::package::install_build_tools,
+            commands::package::import_source_pgp_key,
             commands::package::check_installed_status,
             commands::update::perform_system_update,
             commands::update::get_system_update_command,
             commands::update::check_updates,
             commands::update::apply_updates,
+            commands::upstream::get_upstream_releases,
+            commands::changelog::get_update_changelog,
+            commands::batch::get_last_batch_state,
+            commands::batch::resume_last_batch,
+            commands::rollback::get_available_versions,
+            commands::rollback::downgrade_package,
+            commands::backup::export_settings,
+            commands::backup::import_settings,
+            commands::pins::pin_package,
+            commands::pins::unpin_package,
+            commands::pins::get_pinned_packages,
+            commands::pins::get_held_updates,
+            commands::deferred::discard_deferred_install,
+            commands::deferred::download_for_later,
+            commands::deferred::list_deferred_installs,
+            commands::deferred::resume_deferred_install,
+            commands::import::import_package_list,
+            commands::import::execute_import_plan,
+            commands::trial::get_trial_support,
+            commands::trial::try_app,
+            commands::migration::get_migration_suggestions,
+            commands::migration::migrate_to_repo,
+            commands::migration::get_dropped_packages,
+            commands::migration::switch_to_aur,
+            commands::migration::reset_update_source,
+            commands::devel::get_devel_updates,
+            commands::devel::get_devel_update_settings,
+            commands::devel::set_devel_update_settings,
+            commands::queue::enqueue_install,
+            commands::queue::dequeue_install,
+            commands::queue::reorder_install_queue,
+            commands::queue::get_install_queue,
+            commands::queue::clear_finished_queue_items,
+            commands::rescue::get_rescue_operations,
+            commands::rescue::run_rescue_operation,
+            aur_build::get_aur_build_mode,
+            aur_build::set_aur_build_mode,
+            scheduler::get_update_check_interval,
+            scheduler::set_update_check_interval,
+            scheduler::get_last_update_check,
+            update_conditions::get_update_conditions,
+            update_conditions::set_update_conditions,
+            maintenance::get_maintenance_policy,
+            maintenance::set_maintenance_policy,
+            maintenance::get_maintenance_report,
+            maintenance::run_maintenance,
+            update_policy::get_update_policies,
+            update_policy::set_update_policies,
+            update_policy::classify_updates,
+            network_settings::get_network_settings,
+            network_settings::set_network_settings,
+            connectivity::get_connectivity,
+            connectivity::check_connectivity,
+            connectivity::set_offline_mode,
+            content_rating::get_content_rating,
+            content_rating::get_parental_controls,
+            content_rating::set_parental_controls,
+            digest::get_weekly_digest,
+            digest::is_weekly_digest_enabled,
+            digest::set_weekly_digest_enabled,
+            local_api::get_local_api_settings,
+            local_api::set_local_api_settings,
+            local_api::regenerate_local_api_token,
+            dbus_service::take_launch_link,
             commands::package::fetch_pkgbuild,
+            pkgbuild_cache::get_pkgbuild_diff,
+            commands::package::get_aur_comments,
+            eula::get_eula_prompt,
+            eula::accept_eula,
+            eula::get_eula_acknowledgements,
+            news_api::get_unread_news,
+            news_api::mark_news_read,
             commands::package::get_installed_packages,
             commands::package::check_for_updates,
             commands::package::check_reboot_required,
             commands::package::get_pacnew_warnings,
             commands::package::get_orphans,
             commands::package::remove_orphans,
+            commands::package::get_package_files,
+            commands::package::find_file_owner,
+            app_data::get_config_archives,
+            app_data::restore_app_config,
+            app_data::get_uninstall_leftovers,
+            app_data::remove_uninstall_leftovers,
+            commands::deps::get_dependency_tree,
+            commands::deps::get_reverse_dependencies,
+            commands::deps::preview_uninstall,
+            commands::duplicates::get_duplicate_installations,
+            commands::duplicates::consolidate_duplicates,
+            commands::storage::get_disk_usage_report,
+            history::get_transaction_history,
+            history::undo_removal,
+            history::get_config_diff,
+            history::restore_config_backup,
             commands::system::get_cache_size,
             commands::system::get_orphans_with_size,
             commands::system::set_parallel_downloads,
             commands::system::get_mirror_rank_tool,
             commands::system::rank_mirrors,
             commands::system::test_mirrors,
+            commands::mirrors::get_mirror_sources,
+            commands::mirrors::list_mirrors,
+            commands::mirrors::test_mirror_latency,
+            commands::mirrors::set_mirrorlist,
             commands::system::force_refresh_databases,
             repo_manager::check_repo_sync_status,
+            repo_manager::get_repo_health,
             // Package Commands
             // System Commands
             commands::system::get_system_info,
@@ -189,6 +392,8 @@ pub fn run() {
             commands::system::set_aur_enabled,
             commands::system::is_one_click_enabled,
             commands::system::set_one_click_enabled,
+            commands::system::get_privileged_agent_settings,
+            commands::system::set_privileged_agent_settings,
             commands::system::is_advanced_mode,
             commands::system::set_advanced_mode,
             commands::system::check_security_policy,
@@ -206,26 +411,54 @@ pub fn run() {
             commands::system::is_notifications_enabled,
             commands::system::set_notifications_enabled,
             commands::system::set_telemetry_enabled,
+            pkgstats_api::get_pkgstats_settings,
+            pkgstats_api::set_pkgstats_enabled,
+            pkgstats_api::submit_pkgstats,
             commands::system::is_sync_on_startup_enabled,
             commands::system::set_sync_on_startup_enabled,
             commands::system::check_and_clear_refresh_requested,
+            commands::system::is_run_in_background_enabled,
+            commands::system::set_run_in_background_enabled,
+            migration::get_migration_report,
+            commands::system::get_license_policy,
+            commands::system::set_license_policy,
+            license::get_license_report,
+            license_prompt::accept_license_prompt,
+            license_prompt::decline_license_prompt,
             // Utils Commands
             commands::utils::get_package_icon,
+            icon_cache::get_icons_batch,
+            icon_palette::extract_dominant_colors,
             commands::utils::clear_cache,
+            commands::utils::get_screenshot,
             commands::utils::launch_app,
             commands::utils::track_event,
             // External Module Commands (Pre-refactor)
+            metadata::get_catalog_locale,
             metadata::get_metadata,
             metadata::get_metadata_batch,
+            metadata::hydrate_packages,
+            metadata::set_catalog_locale,
             repair::check_system_health,
             repair::check_initialization_status,
             repair::clear_sync_db_health_cache,
             repair::get_last_sync_age_seconds,
             commands::reviews::submit_review,
             commands::reviews::get_local_reviews,
+            commands::reviews::get_my_reviews,
+            commands::reviews::update_review,
+            commands::reviews::delete_review,
+            commands::reviews::export_review_pack,
+            commands::reviews::import_review_pack,
+            commands::reviews::list_review_packs,
+            commands::reviews::remove_review_pack,
             odrs_api::get_app_rating,
             odrs_api::get_app_ratings_batch,
             odrs_api::get_app_reviews,
+            odrs_api::is_odrs_publish_enabled,
+            odrs_api::set_odrs_publish_enabled,
+            odrs_api::submit_odrs_review,
+            odrs_api::vote_odrs_review,
             repair::cancel_install,
             repair::repair_unlock_pacman,
             repair::check_keyring_health,
@@ -234,10 +467,20 @@ pub fn run() {
             repair::needs_startup_unlock,
             repair::unlock_pacman_if_stale,
             repair::clear_pacman_package_cache,
+            repair::clean_package_cache,
+            systemd::list_package_services,
+            systemd::enable_service,
+            systemd::start_service,
+            keyring::list_pacman_keys,
+            keyring::locally_sign_key,
+            keyring::import_key_from_keyserver,
+            keyring::remove_key,
+            keyring::refresh_third_party_keys,
             repair::fix_keyring_issues_alias,
             repair::clear_build_cache,
             repo_manager::apply_os_config,
             commands::system::emit_sync_progress,
+            alpm_progress::get_download_progress,
             // Identity Matrix Command
             distro_context::get_distro_context,
         ])
@@ -261,6 +504,21 @@ pub fn run() {
                     }
                 }
                 RunEvent::WindowEvent { label, event, .. } => {
+                    // Run-in-background: closing the main window hides it to the tray
+                    if let WindowEvent::CloseRequested { api, .. } = event {
+                        let keep_running = app_handle
+                            .state::<RepoManager>()
+                            .run_in_background
+                            .try_read()
+                            .map(|v| *v)
+                            .unwrap_or(false);
+                        if label == "main" && keep_running {
+                            api.prevent_close();
+                            if let Some(win) = app_handle.get_webview_window(label) {
+                                let _ = win.hide();
+                            }
+                        }
+                    }
                     if matches!(event, WindowEvent::Resized(_) | WindowEvent::Focused(_)) {
                         if let Ok(set) = windows_icon_set.lock() {
                             if !set.contains(label) {
@@ -279,6 +537,7 @@ pub fn run() {
                 }
                 RunEvent::Exit => {
                     log::info!("App exiting");
+                    tauri::async_runtime::block_on(helper_client::stop_agent());
                 }
                 _ => {}
             }
@@ -0,0 +1,267 @@
+//! License normalization (legacy Arch names -> SPDX ids), license families, the
+//! "hide proprietary" / "FOSS only" browse policy and the installed-packages license report.
+
+use crate::models::Package;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+pub const POLICY_ALL: &str = "all";
+pub const POLICY_HIDE_PROPRIETARY: &str = "hide_proprietary";
+pub const POLICY_FOSS_ONLY: &str = "foss_only";
+
+const PROPRIETARY: &str = "LicenseRef-proprietary";
+
+/// Map one license token to its SPDX identifier. Already-SPDX ids pass through unchanged.
+pub fn normalize_spdx(raw: &str) -> String {
+    let t = raw.trim().trim_matches(|c| c == '(' || c == ')').trim();
+    let lower = t.to_lowercase();
+    let mapped = match lower.as_str() {
+        "gpl" => "GPL-2.0-or-later",
+        "gpl2" => "GPL-2.0-only",
+        "gpl3" => "GPL-3.0-or-later",
+        "lgpl" => "LGPL-2.1-or-later",
+        "lgpl2.1" => "LGPL-2.1-only",
+        "lgpl3" => "LGPL-3.0-or-later",
+        "agpl" | "agpl3" => "AGPL-3.0-or-later",
+        "mpl" | "mpl2" => "MPL-2.0",
+        "apache" | "apache2" => "Apache-2.0",
+        "bsd" => "BSD-3-Clause",
+        "mit" => "MIT",
+        "zlib" => "Zlib",
+        "isc" => "ISC",
+        "python" => "PSF-2.0",
+        "perlartistic" => "Artistic-1.0-Perl",
+        "artistic2.0" => "Artistic-2.0",
+        "cddl" => "CDDL-1.0",
+        "epl" => "EPL-1.0",
+        "fdl" | "fdl1.3" => "GFDL-1.3-or-later",
+        "unlicense" => "Unlicense",
+        "cc0" => "CC0-1.0",
+        "public domain" | "publicdomain" | "custom:publicdomain" => "LicenseRef-Public-Domain",
+        "proprietary"
+        | "custom:proprietary"
+        | "unfree"
+        | "commercial"
+        | "eula"
+        | "licenseref-proprietary" => PROPRIETARY,
+        "custom" | "unknown" | "" => "LicenseRef-custom",
+        _ if lower.starts_with("custom:") => "LicenseRef-custom",
+        _ => return t.to_string(),
+    };
+    mapped.to_string()
+}
+
+/// Split an SPDX expression ("GPL-2.0-or-later OR MIT", "Apache-2.0 WITH LLVM-exception")
+/// into normalized license ids. Exceptions are dropped; they don't change the family.
+pub fn split_expression(expr: &str) -> Vec<String> {
+    expr.split(" OR ")
+        .flat_map(|p| p.split(" AND "))
+        .filter_map(|p| p.split(" WITH ").next())
+        .map(normalize_spdx)
+        .collect()
+}
+
+/// Family of a normalized SPDX id:
+/// "copyleft" | "weak-copyleft" | "permissive" | "public-domain" | "proprietary" | "unknown".
+pub fn license_family(spdx: &str) -> &'static str {
+    let starts = |prefixes: &[&str]| prefixes.iter().any(|p| spdx.starts_with(p));
+    if spdx == PROPRIETARY {
+        "proprietary"
+    } else if starts(&["GPL-", "AGPL-"]) {
+        "copyleft"
+    } else if starts(&["LGPL-", "MPL-", "EPL-", "CDDL-", "GFDL-"]) {
+        "weak-copyleft"
+    } else if starts(&["Unlicense", "CC0-", "LicenseRef-Public-Domain", "WTFPL"]) {
+        "public-domain"
+    } else if starts(&[
+        "MIT",
+        "BSD-",
+        "0BSD",
+        "Apache-",
+        "ISC",
+        "Zlib",
+        "PSF-",
+        "Artistic-",
+        "BSL-",
+        "X11",
+        "OFL-",
+    ]) {
+        "permissive"
+    } else {
+        "unknown"
+    }
+}
+
+/// Rewrite `pkg.license` in place as a flat list of SPDX ids.
+pub fn normalize_package(pkg: &mut Package) {
+    if let Some(licenses) = pkg.license.take() {
+        let mut out: Vec<String> = Vec::new();
+        for l in licenses.iter().flat_map(|l| split_expression(l)) {
+            if !out.contains(&l) {
+                out.push(l);
+            }
+        }
+        pkg.license = Some(out);
+    }
+}
+
+/// Whether a (normalized) package is visible under `policy`.
+/// FOSS-only also hides packages with missing or unrecognized license data.
+pub fn passes_policy(pkg: &Package, policy: &str) -> bool {
+    let families: Vec<&str> = pkg
+        .license
+        .as_deref()
+        .unwrap_or_default()
+        .iter()
+        .map(|l| license_family(l))
+        .collect();
+    match policy {
+        POLICY_HIDE_PROPRIETARY => !families.contains(&"proprietary"),
+        POLICY_FOSS_ONLY => {
+            !families.is_empty()
+                && families
+                    .iter()
+                    .all(|f| !matches!(*f, "proprietary" | "unknown"))
+        }
+        _ => true,
+    }
+}
+
+/// Normalize licenses and drop packages the policy hides.
+pub fn apply_policy(packages: &mut Vec<Package>, policy: &str) {
+    for p in packages.iter_mut() {
+        normalize_package(p);
+    }
+    if policy != POLICY_ALL {
+        packages.retain(|p| passes_policy(p, policy));
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct LicenseUsage {
+    pub license: String,
+    pub packages: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct LicenseFamilyGroup {
+    pub family: String,
+    pub package_count: usize,
+    pub licenses: Vec<LicenseUsage>,
+}
+
+/// Typed response for get_license_report
+#[derive(Debug, Serialize, Clone)]
+pub struct LicenseReport {
+    pub total_packages: usize,
+    pub families: Vec<LicenseFamilyGroup>,
+}
+
+fn build_report(mut packages: Vec<Package>) -> LicenseReport {
+    let total_packages = packages.len();
+    // family -> license -> packages
+    let mut tree: BTreeMap<&'static str, BTreeMap<String, BTreeSet<String>>> = BTreeMap::new();
+    for pkg in packages.iter_mut() {
+        normalize_package(pkg);
+        let licenses = pkg
+            .license
+            .clone()
+            .filter(|l| !l.is_empty())
+            .unwrap_or_else(|| vec!["LicenseRef-custom".to_string()]);
+        for l in licenses {
+            tree.entry(license_family(&l))
+                .or_default()
+                .entry(l)
+                .or_default()
+                .insert(pkg.name.clone());
+        }
+    }
+
+    let families = tree
+        .into_iter()
+        .map(|(family, licenses)| {
+            let distinct: BTreeSet<&String> = licenses.values().flatten().collect();
+            LicenseFamilyGroup {
+                family: family.to_string(),
+                package_count: distinct.len(),
+                licenses: licenses
+                    .into_iter()
+                    .map(|(license, pkgs)| LicenseUsage {
+                        license,
+                        packages: pkgs.into_iter().collect(),
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+
+    LicenseReport {
+        total_packages,
+        families,
+    }
+}
+
+/// Installed packages grouped by license family, for compliance overviews.
+#[tauri::command]
+pub async fn get_license_report() -> Result<LicenseReport, String> {
+    let installed = tokio::task::spawn_blocking(crate::alpm_read::get_installed_packages_native)
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?;
+    Ok(build_report(installed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pkg(name: &str, licenses: &[&str]) -> Package {
+        Package {
+            name: name.to_string(),
+            license: Some(licenses.iter().map(|s| s.to_string()).collect()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_normalize_legacy_and_expressions() {
+        assert_eq!(normalize_spdx("GPL3"), "GPL-3.0-or-later");
+        assert_eq!(normalize_spdx("custom:proprietary"), PROPRIETARY);
+        assert_eq!(normalize_spdx("MIT"), "MIT");
+        assert_eq!(
+            split_expression("(GPL-2.0-or-later OR MIT) AND Apache-2.0 WITH LLVM-exception"),
+            vec!["GPL-2.0-or-later", "MIT", "Apache-2.0"]
+        );
+    }
+
+    #[test]
+    fn test_policy_filters() {
+        let mut list = vec![
+            pkg("vlc", &["GPL2"]),
+            pkg("steam", &["custom:proprietary"]),
+            pkg("weird", &["custom:weird"]),
+            Package {
+                name: "nolicense".to_string(),
+                ..Default::default()
+            },
+        ];
+        let mut hidden = list.clone();
+        apply_policy(&mut hidden, POLICY_HIDE_PROPRIETARY);
+        assert_eq!(hidden.len(), 3);
+
+        apply_policy(&mut list, POLICY_FOSS_ONLY);
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].name, "vlc");
+    }
+
+    #[test]
+    fn test_report_groups_by_family() {
+        let report = build_report(vec![
+            pkg("bash", &["GPL-3.0-or-later"]),
+            pkg("curl", &["MIT"]),
+            pkg("nvidia", &["custom"]),
+        ]);
+        assert_eq!(report.total_packages, 3);
+        let families: Vec<&str> = report.families.iter().map(|f| f.family.as_str()).collect();
+        assert_eq!(families, vec!["copyleft", "permissive", "unknown"]);
+    }
+}
@@ -0,0 +1,347 @@
+//! License questions asked by AUR builds. Some PKGBUILDs (nonfree fonts, drivers, SDKs)
+//! `read` a yes/no answer to their EULA during prepare()/build(); with stdin closed the
+//! read fails and the build dies, with stdin open it hangs forever. Build output is read
+//! in chunks so a prompt that has no newline yet is noticed once output pauses. License
+//! prompts pause the build and go to the frontend as "license-prompt" with the text printed
+//! before them; `accept_license_prompt` / `decline_license_prompt` answer on makepkg's
+//! stdin. Any other question gets end-of-file, as before.
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::process::ChildStdin;
+use tokio::sync::oneshot;
+
+/// Output has to be quiet this long before an unterminated line counts as a prompt.
+const PROMPT_IDLE: Duration = Duration::from_millis(1500);
+/// Unanswered license prompts are declined after this.
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+/// Output lines kept as the license text shown with a prompt.
+const CONTEXT_LINES: usize = 200;
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+static PENDING: Lazy<std::sync::Mutex<HashMap<String, oneshot::Sender<bool>>>> =
+    Lazy::new(|| std::sync::Mutex::new(HashMap::new()));
+
+/// Payload of "license-prompt".
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct LicensePrompt {
+    pub id: String,
+    pub package: String,
+    /// The question itself, e.g. "Do you accept the license? [y/N]".
+    pub prompt: String,
+    /// What the build printed before asking, usually the license.
+    pub text: String,
+}
+
+/// A line of build output, or the start of one that stopped mid-way.
+#[derive(Debug, PartialEq)]
+pub enum Output {
+    Line(String),
+    /// Output paused after this unterminated text: probably waiting for input.
+    Pending(String),
+}
+
+/// Line reader that also reports a trailing partial line once the stream goes quiet.
+pub struct PromptLines<R> {
+    reader: R,
+    buf: Vec<u8>,
+    idle: Duration,
+    reported: bool,
+}
+
+impl<R: AsyncRead + Unpin> PromptLines<R> {
+    pub fn new(reader: R) -> Self {
+        Self::with_idle(reader, PROMPT_IDLE)
+    }
+
+    fn with_idle(reader: R, idle: Duration) -> Self {
+        Self {
+            reader,
+            buf: Vec::new(),
+            idle,
+            reported: false,
+        }
+    }
+
+    pub async fn next(&mut self) -> std::io::Result<Option<Output>> {
+        let mut chunk = [0u8; 4096];
+        loop {
+            if let Some(pos) = self.buf.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = self.buf.drain(..=pos).collect();
+                self.reported = false;
+                let line = String::from_utf8_lossy(&line[..pos]);
+                return Ok(Some(Output::Line(line.trim_end_matches('\r').to_string())));
+            }
+            match tokio::time::timeout(self.idle, self.reader.read(&mut chunk)).await {
+                Ok(Ok(0)) if self.buf.is_empty() => return Ok(None),
+                Ok(Ok(0)) => {
+                    let rest = String::from_utf8_lossy(&std::mem::take(&mut self.buf)).into_owned();
+                    return Ok(Some(Output::Line(rest)));
+                }
+                Ok(Ok(n)) => {
+                    self.buf.extend_from_slice(&chunk[..n]);
+                    self.reported = false;
+                }
+                Ok(Err(e)) => return Err(e),
+                Err(_) if !self.buf.is_empty() && !self.reported => {
+                    self.reported = true;
+                    // Progress meters redraw with '\r'; only the last redraw is the prompt
+                    let text = String::from_utf8_lossy(&self.buf);
+                    let last = text.rsplit('\r').next().unwrap_or_default();
+                    return Ok(Some(Output::Pending(last.to_string())));
+                }
+                Err(_) => {}
+            }
+        }
+    }
+}
+
+/// Unterminated output that reads like a question.
+pub fn looks_like_prompt(text: &str) -> bool {
+    let t = text.trim().to_lowercase();
+    if t.is_empty() {
+        return false;
+    }
+    ["[y/n]", "(y/n)", "y/n", "yes/no", "(yes", "[yes"]
+        .iter()
+        .any(|p| t.contains(p))
+        || t.ends_with('?')
+        || t.ends_with(':')
+        || t.ends_with('>')
+}
+
+/// A question about a license: it mentions one, or it is a yes/no question right after
+/// license text.
+pub fn is_license_prompt(prompt: &str, context: &[String]) -> bool {
+    const WORDS: &[&str] = &["licen", "eula", "agree", "accept", "terms"];
+    let mentions = |s: &str| {
+        let s = s.to_lowercase();
+        WORDS.iter().any(|w| s.contains(w))
+    };
+    if !looks_like_prompt(prompt) {
+        return false;
+    }
+    mentions(prompt) || context.iter().rev().take(30).any(|l| mentions(l))
+}
+
+/// What to type to say yes or no: the spelled-out word when the prompt asks for it.
+pub fn answer_for(prompt: &str, accept: bool) -> &'static str {
+    let p = prompt.to_lowercase();
+    let wants_word = p.contains("yes/no")
+        || p.contains("(yes")
+        || p.contains("[yes")
+        || p.contains("type yes")
+        || p.contains("\"yes\"");
+    match (accept, wants_word) {
+        (true, true) => "yes\n",
+        (true, false) => "y\n",
+        (false, true) => "no\n",
+        (false, false) => "n\n",
+    }
+}
+
+/// makepkg's stdin plus recent output, shared by the readers of its stdout and stderr.
+pub struct BuildInput {
+    app: AppHandle,
+    package: String,
+    stdin: tokio::sync::Mutex<Option<ChildStdin>>,
+    context: std::sync::Mutex<VecDeque<String>>,
+    declined: AtomicBool,
+}
+
+impl BuildInput {
+    pub fn new(app: AppHandle, package: &str, stdin: Option<ChildStdin>) -> Arc<Self> {
+        Arc::new(Self {
+            app,
+            package: package.to_string(),
+            stdin: tokio::sync::Mutex::new(stdin),
+            context: std::sync::Mutex::new(VecDeque::new()),
+            declined: AtomicBool::new(false),
+        })
+    }
+
+    /// Keep a complete output line as possible license text.
+    pub fn record(&self, line: &str) {
+        if let Ok(mut context) = self.context.lock() {
+            if context.len() == CONTEXT_LINES {
+                context.pop_front();
+            }
+            context.push_back(line.to_string());
+        }
+    }
+
+    /// The user turned down a license during this build.
+    pub fn declined(&self) -> bool {
+        self.declined.load(Ordering::SeqCst)
+    }
+
+    /// Output stopped at `text`. Ask the user about license prompts; close stdin on any
+    /// other question so the build does not wait forever.
+    pub async fn on_pending(&self, text: &str) {
+        let context: Vec<String> = self
+            .context
+            .lock()
+            .map(|c| c.iter().cloned().collect())
+            .unwrap_or_default();
+        if is_license_prompt(text, &context) {
+            let accepted = self.ask(text, &context).await;
+            if !accepted {
+                self.declined.store(true, Ordering::SeqCst);
+            }
+            let mut stdin = self.stdin.lock().await;
+            if let Some(pipe) = stdin.as_mut() {
+                if pipe
+                    .write_all(answer_for(text, accepted).as_bytes())
+                    .await
+                    .is_err()
+                {
+                    *stdin = None;
+                }
+            }
+            if !accepted {
+                // Builds that ignore "no" would otherwise ask again or carry on
+                *stdin = None;
+            }
+        } else if looks_like_prompt(text) {
+            let _ = self.app.emit(
+                "install-output",
+                format!("Build asked \"{}\"; no answer given.", text.trim()),
+            );
+            *self.stdin.lock().await = None;
+        }
+    }
+
+    async fn ask(&self, prompt: &str, context: &[String]) -> bool {
+        let id = format!("license-{}", NEXT_ID.fetch_add(1, Ordering::SeqCst));
+        let (tx, rx) = oneshot::channel();
+        if let Ok(mut pending) = PENDING.lock() {
+            pending.insert(id.clone(), tx);
+        }
+        let _ = self.app.emit(
+            "install-output",
+            format!(
+                "{} asks to accept a license; waiting for your answer...",
+                self.package
+            ),
+        );
+        let _ = self.app.emit(
+            "license-prompt",
+            LicensePrompt {
+                id: id.clone(),
+                package: self.package.clone(),
+                prompt: prompt.trim().to_string(),
+                text: context.join("\n"),
+            },
+        );
+        let answer = tokio::time::timeout(ANSWER_TIMEOUT, rx).await;
+        if let Ok(mut pending) = PENDING.lock() {
+            pending.remove(&id);
+        }
+        let accepted = matches!(answer, Ok(Ok(true)));
+        let _ = self.app.emit(
+            "install-output",
+            if accepted {
+                format!("License for {} accepted.", self.package)
+            } else {
+                format!("License for {} declined; stopping the build.", self.package)
+            },
+        );
+        accepted
+    }
+}
+
+/// Forward one makepkg stream to "install-output" (with `prefix`), answering prompts.
+pub async fn pump<R: AsyncRead + Unpin>(
+    app: AppHandle,
+    stream: R,
+    input: Arc<BuildInput>,
+    prefix: &'static str,
+) {
+    let mut lines = PromptLines::new(stream);
+    while let Ok(Some(output)) = lines.next().await {
+        match output {
+            Output::Line(line) => {
+                input.record(&line);
+                let _ = app.emit("install-output", format!("{}{}", prefix, line));
+            }
+            Output::Pending(text) => input.on_pending(&text).await,
+        }
+    }
+}
+
+fn answer(id: &str, accept: bool) -> Result<(), String> {
+    let tx = PENDING
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(id)
+        .ok_or_else(|| "This license prompt is no longer waiting for an answer".to_string())?;
+    tx.send(accept)
+        .map_err(|_| "The build is no longer running".to_string())
+}
+
+#[tauri::command]
+pub async fn accept_license_prompt(id: String) -> Result<(), String> {
+    answer(&id, true)
+}
+
+#[tauri::command]
+pub async fn decline_license_prompt(id: String) -> Result<(), String> {
+    answer(&id, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompt_detection() {
+        let context = vec![
+            "END USER LICENSE AGREEMENT".to_string(),
+            "1. You may not redistribute...".to_string(),
+        ];
+        assert!(is_license_prompt("Do you accept the license? [y/N] ", &[]));
+        assert!(is_license_prompt("Continue? [y/n]", &context));
+        assert!(!is_license_prompt("Continue? [y/n]", &[]));
+        assert!(!is_license_prompt(" 45  12.3M   45 5.6M", &context));
+
+        assert!(looks_like_prompt("Enter a value:"));
+        assert!(!looks_like_prompt("==> Starting build()..."));
+
+        assert_eq!(answer_for("Accept? [y/N]", true), "y\n");
+        assert_eq!(answer_for("Type yes/no", true), "yes\n");
+        assert_eq!(answer_for("Accept the EULA (yes/no)?", false), "no\n");
+        assert!(answer("license-0", true).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_prompt_lines_reports_pending_text() {
+        let (mut tx, rx) = tokio::io::duplex(64);
+        let mut lines = PromptLines::with_idle(rx, Duration::from_millis(50));
+        tx.write_all(b"license text\nAccept? [y/N] ").await.unwrap();
+        assert_eq!(
+            lines.next().await.unwrap(),
+            Some(Output::Line("license text".into()))
+        );
+        assert_eq!(
+            lines.next().await.unwrap(),
+            Some(Output::Pending("Accept? [y/N] ".into()))
+        );
+        tx.write_all(b"\nbuilding\n").await.unwrap();
+        drop(tx);
+        assert_eq!(
+            lines.next().await.unwrap(),
+            Some(Output::Line("Accept? [y/N] ".into()))
+        );
+        assert_eq!(
+            lines.next().await.unwrap(),
+            Some(Output::Line("building".into()))
+        );
+        assert_eq!(lines.next().await.unwrap(), None);
+    }
+}
@@ -0,0 +1,429 @@
+//! Optional localhost HTTP API for dashboards (Home Assistant REST sensors, conky + curl).
+//! Off by default. It binds 127.0.0.1 only, and every request must carry
+//! `Authorization: Bearer <token>` with the token from settings. Endpoints:
+//!
+//! - `GET  /api/v1/status`: version, busy flag, pending update count, last check time
+//! - `GET  /api/v1/updates`: updates found by the last scheduled check
+//! - `GET  /api/v1/search?q=firefox`: repo packages matching the query
+//! - `POST /api/v1/check`: run an update check now (needs `allow_updates`)
+//! - `POST /api/v1/update`: start a system update, same as the Updates page
+//!   (needs `allow_updates`); 409 while unread news needs acknowledging
+//!
+//! Requests are tiny, so a hand-rolled HTTP/1.1 reader is enough: one request per
+//! connection, headers only, no bodies.
+
+use crate::repo_manager::RepoManager;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_store::StoreExt;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+const STORE_FILE: &str = "settings.json";
+const SETTINGS_KEY: &str = "local_api";
+const DEFAULT_PORT: u16 = 7341;
+/// Larger requests are cut off; nothing the API accepts comes close.
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_SEARCH_RESULTS: usize = 50;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LocalApiSettings {
+    pub enabled: bool,
+    pub port: u16,
+    /// Also accept POST /check and POST /update.
+    pub allow_updates: bool,
+    /// Bearer token; generated the first time the API is enabled.
+    pub token: String,
+}
+
+impl Default for LocalApiSettings {
+    fn default() -> Self {
+        LocalApiSettings {
+            enabled: false,
+            port: DEFAULT_PORT,
+            allow_updates: false,
+            token: String::new(),
+        }
+    }
+}
+
+/// Stop signal and accept loop of the running server.
+type Server = (
+    tokio::sync::oneshot::Sender<()>,
+    tauri::async_runtime::JoinHandle<()>,
+);
+
+/// A tokio mutex, so concurrent restarts take turns.
+static SERVER: Lazy<tokio::sync::Mutex<Option<Server>>> =
+    Lazy::new(|| tokio::sync::Mutex::new(None));
+
+#[derive(Debug, PartialEq)]
+struct Request {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    /// Lowercased names
+    headers: HashMap<String, String>,
+}
+
+fn read_settings(app: &AppHandle) -> LocalApiSettings {
+    app.store(STORE_FILE)
+        .ok()
+        .and_then(|s| s.get(SETTINGS_KEY))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+fn write_settings(app: &AppHandle, settings: &LocalApiSettings) -> Result<(), String> {
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    store.set(SETTINGS_KEY, json!(settings));
+    store.save().map_err(|e| e.to_string())
+}
+
+/// 32 hex characters from the kernel RNG.
+fn new_token() -> Result<String, String> {
+    let mut bytes = [0u8; 16];
+    std::fs::File::open("/dev/urandom")
+        .and_then(|mut f| std::io::Read::read_exact(&mut f, &mut bytes))
+        .map_err(|e| format!("Could not generate a token: {}", e))?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// `%XX` and `+` decoding for query values.
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+                match u8::from_str_radix(hex, 16) {
+                    Ok(b) => {
+                        out.push(b);
+                        i += 2;
+                    }
+                    Err(_) => out.push(b'%'),
+                }
+            }
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Request line and headers of one HTTP/1.x request; None if malformed or incomplete.
+fn parse_request(raw: &str) -> Option<Request> {
+    let head = raw.split("\r\n\r\n").next()?;
+    let mut lines = head.split("\r\n");
+    let mut parts = lines.next()?.split(' ');
+    let method = parts.next()?.to_string();
+    let target = parts.next()?;
+    if !parts.next()?.starts_with("HTTP/1.") {
+        return None;
+    }
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query = query
+        .split('&')
+        .filter(|kv| !kv.is_empty())
+        .map(|kv| {
+            let (k, v) = kv.split_once('=').unwrap_or((kv, ""));
+            (percent_decode(k), percent_decode(v))
+        })
+        .collect();
+    let headers = lines
+        .filter_map(|l| l.split_once(':'))
+        .map(|(k, v)| (k.trim().to_lowercase(), v.trim().to_string()))
+        .collect();
+    Some(Request {
+        method,
+        path: path.to_string(),
+        query,
+        headers,
+    })
+}
+
+/// Bearer token check, constant time over the token length. The Host check keeps DNS
+/// rebinding pages from talking to the API even if they learned the token.
+fn authorized(req: &Request, token: &str) -> bool {
+    let host_ok = req.headers.get("host").is_some_and(|h| {
+        let host = h.rsplit_once(':').map(|(h, _)| h).unwrap_or(h);
+        matches!(host, "127.0.0.1" | "localhost" | "[::1]")
+    });
+    let Some(given) = req
+        .headers
+        .get("authorization")
+        .and_then(|v| v.strip_prefix("Bearer "))
+    else {
+        return false;
+    };
+    host_ok
+        && !token.is_empty()
+        && given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+async fn handle(
+    app: &AppHandle,
+    req: &Request,
+    settings: &LocalApiSettings,
+) -> (u16, serde_json::Value) {
+    if !authorized(req, &settings.token) {
+        return (401, json!({ "error": "Missing or invalid token" }));
+    }
+    let last_check = crate::scheduler::get_last_update_check()
+        .await
+        .ok()
+        .flatten();
+
+    match (req.method.as_str(), req.path.as_str()) {
+        ("GET", "/api/v1/status") => (
+            200,
+            json!({
+                "version": env!("CARGO_PKG_VERSION"),
+                "busy": crate::utils::PRIVILEGED_LOCK.try_lock().is_err(),
+                "pending_updates": last_check.as_ref().map(|c| c.updates.len()),
+                "last_check": last_check.as_ref().map(|c| c.checked_at),
+                "updates_allowed": settings.allow_updates,
+            }),
+        ),
+        ("GET", "/api/v1/updates") => (
+            200,
+            json!({
+                "checked_at": last_check.as_ref().map(|c| c.checked_at),
+                "updates": last_check.map(|c| c.updates).unwrap_or_default(),
+            }),
+        ),
+        ("GET", "/api/v1/search") => {
+            let query = req.query.get("q").map(|q| q.trim()).unwrap_or("");
+            if query.len() < 2 {
+                return (400, json!({ "error": "q must be at least 2 characters" }));
+            }
+            let repo_manager = app.state::<RepoManager>();
+            let distro = app.state::<crate::distro_context::DistroContext>();
+            match repo_manager
+                .get_packages_matching(query, distro.inner())
+                .await
+            {
+                Ok((packages, _)) => {
+                    let results: Vec<serde_json::Value> = packages
+                        .into_iter()
+                        .take(MAX_SEARCH_RESULTS)
+                        .map(|p| {
+                            json!({
+                                "name": p.name,
+                                "version": p.version,
+                                "description": p.description,
+                                "repo": p.source.id,
+                                "installed": p.installed,
+                            })
+                        })
+                        .collect();
+                    (200, json!({ "results": results }))
+                }
+                Err(e) => (500, json!({ "error": e })),
+            }
+        }
+        ("POST", "/api/v1/check" | "/api/v1/update") if !settings.allow_updates => (
+            403,
+            json!({ "error": "Update triggers are disabled in MonArch settings" }),
+        ),
+        ("POST", "/api/v1/check" | "/api/v1/update")
+            if crate::utils::PRIVILEGED_LOCK.try_lock().is_err() =>
+        {
+            (409, json!({ "error": "Another operation is in progress" }))
+        }
+        ("POST", "/api/v1/check") => {
+            let handle = app.clone();
+            tauri::async_runtime::spawn(async move {
+                crate::scheduler::check_now(&handle).await;
+            });
+            (202, json!({ "started": "check" }))
+        }
+        ("POST", "/api/v1/update") => {
+            // Checks the news gate, then runs the update in the background
+            let state = app.state::<RepoManager>();
+            match crate::commands::update::perform_system_update(app.clone(), state, None).await {
+                Ok(_) => (202, json!({ "started": "update" })),
+                Err(e) if e.starts_with("NEWS_ACK_REQUIRED") => {
+                    let message = e.trim_start_matches("NEWS_ACK_REQUIRED:").trim();
+                    (409, json!({ "error": message, "news_ack_required": true }))
+                }
+                Err(e) => {
+                    log::warn!("Local API update failed: {}", e);
+                    (500, json!({ "error": e }))
+                }
+            }
+        }
+        _ => (404, json!({ "error": "Not found" })),
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        409 => "Conflict",
+        _ => "Internal Server Error",
+    }
+}
+
+async fn serve_connection(
+    app: AppHandle,
+    mut stream: tokio::net::TcpStream,
+    settings: LocalApiSettings,
+) {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 1024];
+    let read = tokio::time::timeout(READ_TIMEOUT, async {
+        while !buf.windows(4).any(|w| w == b"\r\n\r\n") && buf.len() < MAX_REQUEST_BYTES {
+            match stream.read(&mut chunk).await {
+                Ok(0) | Err(_) => break,
+                Ok(n) => buf.extend_from_slice(&chunk[..n]),
+            }
+        }
+    })
+    .await;
+    let (status, body) = match (read, parse_request(&String::from_utf8_lossy(&buf))) {
+        (Ok(()), Some(req)) => handle(&app, &req, &settings).await,
+        _ => (400, json!({ "error": "Malformed request" })),
+    };
+    let body = body.to_string();
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason(status),
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+/// (Re)start the server from the saved settings; stops it when disabled.
+async fn restart(app: &AppHandle) -> Result<(), String> {
+    let mut server = SERVER.lock().await;
+    if let Some((stop, running)) = server.take() {
+        let _ = stop.send(());
+        // The old listener has to be closed before the port can be bound again
+        let _ = running.await;
+    }
+    let settings = read_settings(app);
+    if !settings.enabled {
+        return Ok(());
+    }
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", settings.port))
+        .await
+        .map_err(|e| format!("Could not listen on 127.0.0.1:{}: {}", settings.port, e))?;
+    let (stop_tx, mut stop_rx) = tokio::sync::oneshot::channel();
+    log::info!("Local API listening on 127.0.0.1:{}", settings.port);
+
+    let app = app.clone();
+    let running = tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::select! {
+                _ = &mut stop_rx => break,
+                accepted = listener.accept() => {
+                    if let Ok((stream, _)) = accepted {
+                        tauri::async_runtime::spawn(serve_connection(app.clone(), stream, settings.clone()));
+                    }
+                }
+            }
+        }
+    });
+    *server = Some((stop_tx, running));
+    Ok(())
+}
+
+/// Start the API at launch if the user enabled it.
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = restart(&app).await {
+            log::warn!("Local API not started: {}", e);
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn get_local_api_settings(app: AppHandle) -> Result<LocalApiSettings, String> {
+    Ok(read_settings(&app))
+}
+
+/// Save the settings and apply them right away. A token is generated the first time the
+/// API is enabled. Ports below 1024 are refused.
+#[tauri::command]
+pub async fn set_local_api_settings(
+    app: AppHandle,
+    enabled: bool,
+    port: Option<u16>,
+    allow_updates: Option<bool>,
+) -> Result<LocalApiSettings, String> {
+    let mut settings = read_settings(&app);
+    settings.enabled = enabled;
+    if let Some(port) = port {
+        if port < 1024 {
+            return Err("Choose a port from 1024 to 65535".to_string());
+        }
+        settings.port = port;
+    }
+    if let Some(allow) = allow_updates {
+        settings.allow_updates = allow;
+    }
+    if settings.enabled && settings.token.is_empty() {
+        settings.token = new_token()?;
+    }
+    write_settings(&app, &settings)?;
+    restart(&app).await?;
+    Ok(settings)
+}
+
+/// Replace the token; clients using the old one get 401 from now on.
+#[tauri::command]
+pub async fn regenerate_local_api_token(app: AppHandle) -> Result<LocalApiSettings, String> {
+    let mut settings = read_settings(&app);
+    settings.token = new_token()?;
+    write_settings(&app, &settings)?;
+    restart(&app).await?;
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_request_and_auth() {
+        let raw = "GET /api/v1/search?q=visual+studio%20code&x HTTP/1.1\r\nHost: 127.0.0.1:7341\r\nAuthorization: Bearer abc123\r\n\r\n";
+        let req = parse_request(raw).unwrap();
+        assert_eq!(req.method, "GET");
+        assert_eq!(req.path, "/api/v1/search");
+        assert_eq!(req.query["q"], "visual studio code");
+        assert_eq!(req.query["x"], "");
+        assert!(authorized(&req, "abc123"));
+        assert!(!authorized(&req, "abc124"));
+        assert!(!authorized(&req, ""));
+
+        let rebound = parse_request(
+            "GET /api/v1/status HTTP/1.1\r\nHost: evil.example:7341\r\nAuthorization: Bearer abc123\r\n\r\n",
+        )
+        .unwrap();
+        assert!(!authorized(&rebound, "abc123"));
+
+        assert!(parse_request("garbage").is_none());
+        assert_eq!(percent_decode("100%"), "100%");
+    }
+}
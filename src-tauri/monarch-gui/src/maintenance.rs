@@ -0,0 +1,428 @@
+//! Maintenance policy: cleanup rules the scheduler applies in the background, such as
+//! "remove orphans weekly", "keep 2 versions of each cached package" or "clear the build
+//! cache once it passes 5 GiB". Each rule runs on its own interval. In dry-run mode a run
+//! only records what it would have cleaned. The last run of every rule is kept for
+//! get_maintenance_report. There are no rules until the user adds some.
+
+use monarch_types::disk_space::format_size;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+use tauri_plugin_store::StoreExt;
+
+const STORE_FILE: &str = "settings.json";
+const POLICY_KEY: &str = "maintenance_policy";
+const LOG_KEY: &str = "maintenance_log";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleInterval {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl RuleInterval {
+    fn secs(self) -> i64 {
+        match self {
+            RuleInterval::Daily => 24 * 3600,
+            RuleInterval::Weekly => 7 * 24 * 3600,
+            RuleInterval::Monthly => 30 * 24 * 3600,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum MaintenanceAction {
+    /// Uninstall dependencies nothing needs any more (`pacman -Rns $(pacman -Qtdq)`).
+    RemoveOrphans,
+    /// Keep this many versions of each package in the pacman cache, plus the installed one.
+    TrimPackageCache { keep: u32 },
+    /// Clear the AUR build cache once it is larger than this.
+    CleanBuildCache { over_bytes: u64 },
+}
+
+impl MaintenanceAction {
+    /// One rule per kind; runs are logged under it.
+    fn kind(&self) -> &'static str {
+        match self {
+            MaintenanceAction::RemoveOrphans => "remove_orphans",
+            MaintenanceAction::TrimPackageCache { .. } => "trim_package_cache",
+            MaintenanceAction::CleanBuildCache { .. } => "clean_build_cache",
+        }
+    }
+
+    /// Goes through the helper, which can mean an authentication prompt. Previewing a
+    /// cache trim still asks the helper what it would remove.
+    fn needs_helper(&self, dry_run: bool) -> bool {
+        match self {
+            MaintenanceAction::RemoveOrphans => !dry_run,
+            MaintenanceAction::TrimPackageCache { .. } => true,
+            MaintenanceAction::CleanBuildCache { .. } => false,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct MaintenanceRule {
+    #[serde(flatten)]
+    pub action: MaintenanceAction,
+    pub interval: RuleInterval,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct MaintenancePolicy {
+    pub rules: Vec<MaintenanceRule>,
+    /// Only report what the rules would clean.
+    pub dry_run: bool,
+}
+
+impl MaintenancePolicy {
+    fn validate(&self) -> Result<(), String> {
+        for (i, rule) in self.rules.iter().enumerate() {
+            if self.rules[..i]
+                .iter()
+                .any(|r| r.action.kind() == rule.action.kind())
+            {
+                return Err(format!("More than one {} rule", rule.action.kind()));
+            }
+            if let MaintenanceAction::CleanBuildCache { over_bytes: 0 } = rule.action {
+                return Err("The build cache limit must be above zero".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// One run of a rule.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RuleRun {
+    #[serde(flatten)]
+    pub action: MaintenanceAction,
+    pub ran_at: i64,
+    pub dry_run: bool,
+    /// Packages or paths removed, or that would have been.
+    pub items: Vec<String>,
+    pub bytes: u64,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct RuleStatus {
+    pub rule: MaintenanceRule,
+    pub last_run: Option<RuleRun>,
+    /// Unix seconds of the next background run.
+    pub next_run: i64,
+}
+
+/// Typed response for get_maintenance_report.
+#[derive(Debug, Serialize, Clone)]
+pub struct MaintenanceReport {
+    pub dry_run: bool,
+    pub rules: Vec<RuleStatus>,
+}
+
+fn read_policy(app: &AppHandle) -> MaintenancePolicy {
+    app.store(STORE_FILE)
+        .ok()
+        .and_then(|s| s.get(POLICY_KEY))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+fn read_log(app: &AppHandle) -> Vec<RuleRun> {
+    app.store(STORE_FILE)
+        .ok()
+        .and_then(|s| s.get(LOG_KEY))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+/// Replace each rule's previous run with the new one.
+fn record_runs(app: &AppHandle, runs: &[RuleRun]) {
+    let mut log = read_log(app);
+    log.retain(|old| !runs.iter().any(|r| r.action.kind() == old.action.kind()));
+    log.extend(runs.iter().cloned());
+    if let Ok(store) = app.store(STORE_FILE) {
+        store.set(LOG_KEY, json!(log));
+        let _ = store.save();
+    }
+}
+
+fn last_run<'a>(log: &'a [RuleRun], rule: &MaintenanceRule) -> Option<&'a RuleRun> {
+    log.iter().find(|r| r.action.kind() == rule.action.kind())
+}
+
+fn next_run(log: &[RuleRun], rule: &MaintenanceRule) -> i64 {
+    last_run(log, rule).map_or(0, |r| r.ran_at + rule.interval.secs())
+}
+
+/// Rules whose interval has passed since their last run, or that never ran.
+fn due_rules(policy: &MaintenancePolicy, log: &[RuleRun], now: i64) -> Vec<MaintenanceRule> {
+    policy
+        .rules
+        .iter()
+        .filter(|rule| next_run(log, rule) <= now)
+        .copied()
+        .collect()
+}
+
+async fn remove_orphans(
+    app: &AppHandle,
+    dry_run: bool,
+    password: Option<String>,
+) -> Result<(Vec<String>, u64), String> {
+    let orphans = tokio::task::spawn_blocking(crate::alpm_read::get_unrequired_orphans_native)
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?;
+    let bytes = orphans.iter().map(|(_, size)| size).sum();
+    let names: Vec<String> = orphans.into_iter().map(|(name, _)| name).collect();
+    if dry_run || names.is_empty() {
+        return Ok((names, bytes));
+    }
+
+    let result = crate::helper_client::run_helper(
+        app,
+        crate::helper_client::HelperCommand::ExecuteBatch {
+            manifest: crate::models::TransactionManifest {
+                remove_targets: names.clone(),
+                ..Default::default()
+            },
+            dry_run: false,
+        },
+        password,
+        |_| {},
+    )
+    .await?;
+    if !result.success {
+        return Err(result
+            .error
+            .unwrap_or_else(|| "Orphan removal failed".to_string()));
+    }
+    Ok((names, bytes))
+}
+
+fn clean_build_cache(over_bytes: u64, dry_run: bool) -> Result<(Vec<String>, u64), String> {
+    let Some(dir) = crate::repair::build_cache_dir().filter(|d| d.exists()) else {
+        return Ok((Vec::new(), 0));
+    };
+    let size = crate::app_data::path_size(&dir);
+    if size <= over_bytes {
+        return Ok((Vec::new(), 0));
+    }
+    if !dry_run {
+        std::fs::remove_dir_all(&dir)
+            .map_err(|e| format!("Failed to remove build cache: {}", e))?;
+    }
+    Ok((vec![dir.to_string_lossy().into_owned()], size))
+}
+
+/// The caller holds PRIVILEGED_LOCK: AUR builds run under it, so the build cache isn't
+/// cleared under a running build either.
+async fn run_rule(
+    app: &AppHandle,
+    action: MaintenanceAction,
+    dry_run: bool,
+    password: Option<String>,
+) -> RuleRun {
+    let outcome = match action {
+        MaintenanceAction::RemoveOrphans => remove_orphans(app, dry_run, password).await,
+        MaintenanceAction::TrimPackageCache { keep } => {
+            crate::repair::clean_package_cache_locked(app, keep, false, dry_run, password)
+                .await
+                .map(|report| {
+                    let files = report
+                        .removed
+                        .into_iter()
+                        .map(|f| format!("{}-{}", f.name, f.version))
+                        .collect();
+                    (files, report.reclaimed_bytes)
+                })
+        }
+        MaintenanceAction::CleanBuildCache { over_bytes } => {
+            tokio::task::spawn_blocking(move || clean_build_cache(over_bytes, dry_run))
+                .await
+                .unwrap_or_else(|e| Err(format!("Task join error: {}", e)))
+        }
+    };
+    let (items, bytes, error) = match outcome {
+        Ok((items, bytes)) => (items, bytes, None),
+        Err(e) => (Vec::new(), 0, Some(e)),
+    };
+    RuleRun {
+        action,
+        ran_at: chrono::Utc::now().timestamp(),
+        dry_run,
+        items,
+        bytes,
+        error,
+    }
+}
+
+/// Called by the scheduler every tick. Waits while another privileged operation runs, and
+/// leaves rules that need the helper due until it can run without asking for a password.
+pub(crate) async fn run_due(app: &AppHandle) {
+    let policy = read_policy(app);
+    let mut due = due_rules(&policy, &read_log(app), chrono::Utc::now().timestamp());
+    if due.is_empty() {
+        return;
+    }
+    let Ok(_guard) = crate::utils::PRIVILEGED_LOCK.try_lock() else {
+        return;
+    };
+    if !crate::helper_client::runs_unattended(app).await {
+        due.retain(|rule| !rule.action.needs_helper(policy.dry_run));
+    }
+
+    let mut runs = Vec::new();
+    for rule in due {
+        let run = run_rule(app, rule.action, policy.dry_run, None).await;
+        match &run.error {
+            Some(e) => log::warn!("Maintenance rule {} failed: {}", rule.action.kind(), e),
+            None => log::info!(
+                "Maintenance rule {}: {} item(s), {}{}",
+                rule.action.kind(),
+                run.items.len(),
+                format_size(run.bytes),
+                if run.dry_run { " (dry run)" } else { "" }
+            ),
+        }
+        runs.push(run);
+    }
+    record_runs(app, &runs);
+
+    let freed: u64 = runs
+        .iter()
+        .filter(|r| !r.dry_run && r.error.is_none())
+        .map(|r| r.bytes)
+        .sum();
+    if freed > 0 {
+        let repo_manager = app.state::<crate::repo_manager::RepoManager>();
+        if repo_manager.is_notifications_enabled().await {
+            let _ = app
+                .notification()
+                .builder()
+                .title("MonArch: Maintenance")
+                .body(format!("Cleanup freed {}", format_size(freed)))
+                .show();
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn get_maintenance_policy(app: AppHandle) -> Result<MaintenancePolicy, String> {
+    Ok(read_policy(&app))
+}
+
+#[tauri::command]
+pub async fn set_maintenance_policy(
+    app: AppHandle,
+    policy: MaintenancePolicy,
+) -> Result<MaintenancePolicy, String> {
+    policy.validate()?;
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    store.set(POLICY_KEY, json!(policy));
+    store.save().map_err(|e| e.to_string())?;
+    Ok(policy)
+}
+
+/// Each rule with its last run (what was, or in dry-run mode would have been, cleaned) and
+/// when it runs next.
+#[tauri::command]
+pub async fn get_maintenance_report(app: AppHandle) -> Result<MaintenanceReport, String> {
+    let policy = read_policy(&app);
+    let log = read_log(&app);
+    Ok(MaintenanceReport {
+        dry_run: policy.dry_run,
+        rules: policy
+            .rules
+            .iter()
+            .map(|rule| RuleStatus {
+                rule: *rule,
+                last_run: last_run(&log, rule).cloned(),
+                next_run: next_run(&log, rule),
+            })
+            .collect(),
+    })
+}
+
+/// Run every rule now, or with `dry_run` only preview them. Recorded like background runs.
+#[tauri::command]
+pub async fn run_maintenance(
+    app: AppHandle,
+    dry_run: bool,
+    password: Option<String>,
+) -> Result<Vec<RuleRun>, String> {
+    let policy = read_policy(&app);
+    let _guard = crate::utils::PRIVILEGED_LOCK.lock().await;
+    let mut runs = Vec::new();
+    for rule in &policy.rules {
+        runs.push(run_rule(&app, rule.action, dry_run, password.clone()).await);
+    }
+    record_runs(&app, &runs);
+    Ok(runs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(action: MaintenanceAction, ran_at: i64) -> RuleRun {
+        RuleRun {
+            action,
+            ran_at,
+            dry_run: false,
+            items: Vec::new(),
+            bytes: 0,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_policy_parsing_and_due_rules() {
+        let policy: MaintenancePolicy = serde_json::from_str(
+            r#"{"rules":[
+                {"action":"remove_orphans","interval":"weekly"},
+                {"action":"trim_package_cache","keep":2,"interval":"daily"},
+                {"action":"clean_build_cache","over_bytes":5368709120,"interval":"daily"}
+            ]}"#,
+        )
+        .unwrap();
+        assert!(policy.validate().is_ok());
+        assert!(!policy.dry_run);
+        assert_eq!(
+            policy.rules[1].action,
+            MaintenanceAction::TrimPackageCache { keep: 2 }
+        );
+
+        let day = RuleInterval::Daily.secs();
+        let now = 100 * day;
+        // Never ran: everything is due
+        assert_eq!(due_rules(&policy, &[], now).len(), 3);
+
+        let log = vec![
+            run(MaintenanceAction::RemoveOrphans, now - 2 * day),
+            run(MaintenanceAction::TrimPackageCache { keep: 3 }, now - day),
+        ];
+        let due: Vec<&str> = due_rules(&policy, &log, now)
+            .iter()
+            .map(|r| r.action.kind())
+            .collect();
+        assert_eq!(due, ["trim_package_cache", "clean_build_cache"]);
+
+        let twice = MaintenancePolicy {
+            rules: vec![policy.rules[0], policy.rules[0]],
+            dry_run: true,
+        };
+        assert!(twice.validate().is_err());
+
+        // Previews that read local state run unattended; cleanups wait for the helper
+        assert!(!MaintenanceAction::RemoveOrphans.needs_helper(true));
+        assert!(MaintenanceAction::RemoveOrphans.needs_helper(false));
+        assert!(MaintenanceAction::TrimPackageCache { keep: 2 }.needs_helper(true));
+        assert!(!MaintenanceAction::CleanBuildCache { over_bytes: 1 }.needs_helper(false));
+    }
+}
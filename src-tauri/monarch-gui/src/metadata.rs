@@ -3,12 +3,14 @@ use appstream::{enums::Icon, Collection, Component};
 use lazy_static::lazy_static;
 // use regex::Regex;
 use base64::prelude::*;
+use futures::StreamExt;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::io::Cursor;
 use std::path::PathBuf;
-use std::sync::Mutex;
-use tauri::State;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager, State};
+use tauri_plugin_store::StoreExt;
 
 /*
 lazy_static! {
@@ -33,14 +35,24 @@ pub struct AppMetadata {
     pub description: Option<String>,
 }
 
+/// Cheap to clone: the catalog and its indices are shared, so long readers (search index,
+/// package catalog) take a copy and let go of [`MetadataState`]'s lock.
+#[derive(Clone)]
 pub struct AppStreamLoader {
-    collection: Option<Collection>,
+    collection: Option<Arc<Collection>>,
     // Indices for O(1) lookup
-    category_index: HashMap<String, Vec<AppMetadata>>,
-    icon_index: HashMap<String, String>,
-    pkg_index: HashMap<String, AppMetadata>,
+    category_index: Arc<HashMap<String, Vec<AppMetadata>>>,
+    icon_index: Arc<HashMap<String, String>>,
+    pkg_index: Arc<HashMap<String, AppMetadata>>,
     // Optimizing "The Storm": Cache local filesystem icons to avoid 1500+ disk scans
-    local_icon_index: HashMap<String, String>,
+    local_icon_index: Arc<HashMap<String, String>>,
+    /// Translation keys to prefer for names and descriptions, best first (see
+    /// [`locale_candidates`]).
+    locale: Vec<String>,
+    /// OARS ratings by component id, read from the catalog files (`content_rating`).
+    content_ratings: Arc<HashMap<String, crate::content_rating::ContentRating>>,
+    /// Bumped whenever what lookups return changes; see [`Self::revision`].
+    revision: usize,
 }
 
 impl Default for AppStreamLoader {
@@ -53,10 +65,13 @@ impl AppStreamLoader {
     pub fn new() -> Self {
         let mut loader = Self {
             collection: None,
-            category_index: HashMap::new(),
-            icon_index: HashMap::new(),
-            pkg_index: HashMap::new(),
-            local_icon_index: HashMap::new(),
+            category_index: Arc::default(),
+            icon_index: Arc::default(),
+            pkg_index: Arc::default(),
+            local_icon_index: Arc::default(),
+            locale: locale_candidates(&system_locale()),
+            content_ratings: Arc::default(),
+            revision: 0,
         };
 
         // Pre-scan local icons (O(N) once, instead of O(N) * Requests)
@@ -79,8 +94,30 @@ impl AppStreamLoader {
     }
 
     pub fn set_collection(&mut self, col: Collection) {
-        self.collection = Some(col.clone());
+        let col = Arc::new(col);
         self.rebuild_indices(&col);
+        self.collection = Some(col);
+    }
+
+    /// Switch the language of names, summaries and descriptions; indices are rebuilt from
+    /// the loaded catalog.
+    pub fn set_locale(&mut self, locale: &str) {
+        self.locale = locale_candidates(locale);
+        if let Some(col) = self.collection.clone() {
+            self.rebuild_indices(&col);
+        }
+    }
+
+    pub fn set_content_ratings(
+        &mut self,
+        ratings: HashMap<String, crate::content_rating::ContentRating>,
+    ) {
+        self.content_ratings = Arc::new(ratings);
+        self.revision += 1;
+    }
+
+    pub fn content_rating(&self, app_id: &str) -> Option<crate::content_rating::ContentRating> {
+        self.content_ratings.get(app_id).cloned()
     }
 
     pub fn refresh_local_icon_index(&mut self) {
@@ -109,7 +146,7 @@ impl AppStreamLoader {
                 }
             }
         }
-        self.local_icon_index = index;
+        self.local_icon_index = Arc::new(index);
     }
 
     fn rebuild_indices(&mut self, col: &Collection) {
@@ -145,9 +182,16 @@ impl AppStreamLoader {
             }
         }
 
-        self.category_index = cat_idx;
-        self.icon_index = icon_idx;
-        self.pkg_index = pkg_idx;
+        self.category_index = Arc::new(cat_idx);
+        self.icon_index = Arc::new(icon_idx);
+        self.pkg_index = Arc::new(pkg_idx);
+        self.revision += 1;
+    }
+
+    /// Changes when a (re)load finishes, the ratings arrive or the locale switches: whatever
+    /// was built from the lookups (search index, catalog) is out of date.
+    pub fn revision(&self) -> usize {
+        self.revision
     }
 
     pub fn find_package(&self, pkg_name: &str) -> Option<AppMetadata> {
@@ -332,7 +376,7 @@ impl AppStreamLoader {
         // Iterate only if we must (slow-ish but cached)
         // Optimization: Only do this for short names or numbers like "2048"
         if pkg_name.chars().all(char::is_numeric) || pkg_name == "angband" {
-            for (key, icon) in &self.icon_index {
+            for (key, icon) in self.icon_index.iter() {
                 if key.contains(pkg_name) {
                     return Some(icon.clone());
                 }
@@ -420,19 +464,10 @@ impl AppStreamLoader {
         None
     }
 
+    #[allow(dead_code)]
     pub fn get_apps_by_category(&self, category: &str) -> Vec<AppMetadata> {
         let cat_lower = category.to_lowercase();
-        let query_key = match cat_lower.as_str() {
-            "utilities" => "utility",
-            "games" => "game",
-            "multimedia" => "audiovideo", // AudioVideo is XDG standard
-            "graphics" => "graphics",
-            "network" | "internet" => "network",
-            "office" | "productivity" => "office",
-            "development" | "develop" => "development",
-            "system" => "system",
-            k => k,
-        };
+        let query_key = category_key(&cat_lower);
 
         if let Some(res) = self.category_index.get(query_key) {
             return res.clone();
@@ -445,6 +480,32 @@ impl AppStreamLoader {
             .unwrap_or_default()
     }
 
+    /// (category key, package name) for every categorized app with a package.
+    pub fn category_members(&self) -> Vec<(String, String)> {
+        self.category_index
+            .iter()
+            .flat_map(|(category, apps)| {
+                apps.iter()
+                    .filter_map(|a| a.pkg_name.as_ref())
+                    .map(move |pkg| (category.clone(), pkg.to_lowercase()))
+            })
+            .collect()
+    }
+
+    /// Distinct apps per category, keyed like the category index ("game", "utility", ...).
+    pub fn category_counts(&self) -> HashMap<String, usize> {
+        self.category_index
+            .iter()
+            .map(|(category, apps)| {
+                let distinct: std::collections::HashSet<&str> = apps
+                    .iter()
+                    .map(|a| a.pkg_name.as_deref().unwrap_or(&a.app_id))
+                    .collect();
+                (category.clone(), distinct.len())
+            })
+            .collect()
+    }
+
     fn component_to_metadata(&self, component: &Component) -> AppMetadata {
         #[allow(unused_assignments)]
         // Sort icons by size (descending) to prefer higher resolution
@@ -521,28 +582,22 @@ impl AppStreamLoader {
         let maintainer = component
             .developer_name
             .as_ref()
-            .and_then(|d| d.0.values().next().cloned());
+            .and_then(|d| pick_translation(&d.0, &self.locale));
         let license = component.project_license.as_ref().map(|l| l.to_string());
         let description = component
             .description
             .as_ref()
-            .and_then(|d| d.0.values().next().cloned());
+            .and_then(|d| pick_translation(&d.0, &self.locale));
 
         let meta = AppMetadata {
-            name: component
-                .name
-                .0
-                .values()
-                .next()
-                .cloned()
-                .unwrap_or_default(),
+            name: pick_translation(&component.name.0, &self.locale).unwrap_or_default(),
             pkg_name: component.pkgname.clone(),
             icon_url,
             app_id: component.id.to_string(),
             summary: component
                 .summary
                 .as_ref()
-                .and_then(|s| s.0.values().next().cloned()),
+                .and_then(|s| pick_translation(&s.0, &self.locale)),
             screenshots: screenshots.clone(), // Clone here if needed or just move
             version,
             maintainer,
@@ -688,15 +743,15 @@ pub async fn download_and_cache_appstream(
 
     log::info!("Downloading Arch AppStream data...");
     let url = "https://archlinux.org/packages/extra/any/archlinux-appstream-data/download/";
-    let resp = reqwest::get(url).await.map_err(|e| e.to_string())?;
+    // Tens of MB; generous enough for a low rate limit
+    let client = crate::http_client::client(std::time::Duration::from_secs(1800));
+    let resp = crate::http_client::fetch(client.get(url)).await?;
 
-    if !resp.status().is_success() {
-        return Err(format!("Failed to download AppStream: {}", resp.status()));
+    if !resp.is_success() {
+        return Err(format!("Failed to download AppStream: {}", resp.status));
     }
 
-    let bytes = resp.bytes().await.map_err(|e| e.to_string())?;
-
-    let cursor = Cursor::new(bytes);
+    let cursor = Cursor::new(resp.body);
     let decoder = zstd::stream::read::Decoder::new(cursor).map_err(|e| e.to_string())?;
     let mut archive = tar::Archive::new(decoder);
     let mut found_xml = false;
@@ -748,6 +803,249 @@ pub async fn download_and_cache_appstream(
     Err("Could not find extra.xml.gz in package".to_string())
 }
 
+/// Where a catalog came from. When several describe the same component id the lowest rank
+/// wins and the others only fill fields it lacks (icons, screenshots, texts, pkgname). Flathub never
+/// adds components of its own: its ids are Flatpak apps, not repo packages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CatalogRank {
+    /// Shipped by the running derivative (cachyos*.xml.gz on CachyOS, ...).
+    Distro,
+    Arch,
+    /// Any other catalog installed under the system AppStream dirs.
+    Other,
+    Flathub,
+}
+
+/// Catalogs (and their cached icons) that appstream-data packages install.
+const SYSTEM_CATALOG_DIRS: &[(&str, &str)] = &[
+    ("/usr/share/swcatalog/xml", "/usr/share/swcatalog/icons"),
+    ("/usr/share/app-info/xmls", "/usr/share/app-info/icons"),
+];
+const ARCH_CATALOG_NAMES: &[&str] = &["core", "extra", "community", "multilib", "archlinux"];
+const FLATHUB_APPSTREAM_URL: &str = "https://dl.flathub.org/repo/appstream/x86_64/appstream.xml.gz";
+
+/// Rank of a system catalog by file name: `<distro>*` is the distro's own, Arch's are named
+/// after its repos.
+pub fn rank_system_catalog(file_name: &str, distro_id: &str) -> CatalogRank {
+    let stem = file_name
+        .trim_end_matches(".gz")
+        .trim_end_matches(".xml")
+        .to_lowercase();
+    if distro_id != "arch" && !distro_id.is_empty() && stem.starts_with(distro_id) {
+        CatalogRank::Distro
+    } else if ARCH_CATALOG_NAMES.contains(&stem.as_str()) {
+        CatalogRank::Arch
+    } else {
+        CatalogRank::Other
+    }
+}
+
+/// Merge catalogs by component id following `CatalogRank`. None if no catalog adds
+/// components (e.g. only Flathub loaded).
+pub fn merge_catalogs(mut catalogs: Vec<(CatalogRank, Collection)>) -> Option<Collection> {
+    catalogs.sort_by_key(|(rank, _)| *rank);
+    let mut merged: Option<Collection> = None;
+    let mut by_id: HashMap<String, usize> = HashMap::new();
+
+    for (rank, col) in catalogs {
+        if merged.is_none() && rank == CatalogRank::Flathub {
+            break;
+        }
+        let target = merged.get_or_insert_with(|| Collection {
+            components: Vec::new(),
+            ..col.clone()
+        });
+        for component in col.components {
+            let id = component.id.to_string();
+            match by_id.get(&id) {
+                Some(&i) => {
+                    let existing = &mut target.components[i];
+                    if existing.icons.is_empty() {
+                        existing.icons = component.icons;
+                    }
+                    if existing.screenshots.is_empty() {
+                        existing.screenshots = component.screenshots;
+                    }
+                    if existing.summary.is_none() {
+                        existing.summary = component.summary;
+                    }
+                    if existing.description.is_none() {
+                        existing.description = component.description;
+                    }
+                    if existing.pkgname.is_none() {
+                        existing.pkgname = component.pkgname;
+                    }
+                }
+                None if rank != CatalogRank::Flathub => {
+                    by_id.insert(id, target.components.len());
+                    target.components.push(component);
+                }
+                None => {}
+            }
+        }
+    }
+    merged
+}
+
+/// Read a (possibly gzipped) catalog, sanitize it like the Arch one and parse it from a
+/// copy in `work_dir`.
+fn load_catalog_file(path: &std::path::Path, work_dir: &std::path::Path) -> Option<Collection> {
+    use std::io::Read;
+    let raw = std::fs::read(path).ok()?;
+    let mut xml = String::new();
+    if path.extension().is_some_and(|e| e == "gz") {
+        flate2::read::GzDecoder::new(raw.as_slice())
+            .read_to_string(&mut xml)
+            .ok()?;
+    } else {
+        xml = String::from_utf8(raw).ok()?;
+    }
+    let name = path.file_name()?.to_string_lossy().replace(".gz", "");
+    let copy = work_dir.join(name);
+    std::fs::write(&copy, sanitize_xml(&xml)).ok()?;
+    match Collection::from_path(copy.clone()) {
+        Ok(col) => Some(col),
+        Err(e) => {
+            log::warn!("Skipping AppStream catalog {:?}: {}", path, e);
+            let _ = std::fs::remove_file(&copy);
+            None
+        }
+    }
+}
+
+/// Copy the cached icons a system catalog references into the icons dir, where
+/// `component_to_metadata` resolves them (system catalogs keep them per origin and size).
+fn import_cached_icons(col: &Collection, origin: &str, icons_root: &str) {
+    let icons_dir = get_icons_dir();
+    for icon in col.components.iter().flat_map(|c| c.icons.iter()) {
+        let Icon::Cached {
+            path,
+            width,
+            height,
+            ..
+        } = icon
+        else {
+            continue;
+        };
+        let Some(file_name) = path.file_name() else {
+            continue;
+        };
+        let target = icons_dir.join(file_name);
+        if target.exists() {
+            continue;
+        }
+        let size = format!("{}x{}", width.unwrap_or(64), height.unwrap_or(64));
+        let source = PathBuf::from(icons_root)
+            .join(origin)
+            .join(size)
+            .join(file_name);
+        if source.exists() {
+            let _ = std::fs::copy(&source, &target);
+        }
+    }
+}
+
+/// Catalogs installed on the system, ranked for `distro_id`. Arch's own are skipped when
+/// the downloaded Arch catalog is already loaded.
+pub fn load_system_catalogs(
+    cache_dir: &std::path::Path,
+    distro_id: &str,
+    skip_arch: bool,
+) -> Vec<(CatalogRank, Collection)> {
+    let work_dir = cache_dir.join("catalogs");
+    let _ = std::fs::create_dir_all(&work_dir);
+    let _ = std::fs::create_dir_all(get_icons_dir());
+    let mut catalogs = Vec::new();
+    for (xml_dir, icons_root) in SYSTEM_CATALOG_DIRS {
+        let Ok(entries) = std::fs::read_dir(xml_dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            if !(file_name.ends_with(".xml") || file_name.ends_with(".xml.gz")) {
+                continue;
+            }
+            let rank = rank_system_catalog(&file_name, distro_id);
+            if skip_arch && rank == CatalogRank::Arch {
+                continue;
+            }
+            if let Some(col) = load_catalog_file(&entry.path(), &work_dir) {
+                let origin = col.origin.clone().unwrap_or_else(|| {
+                    file_name
+                        .trim_end_matches(".gz")
+                        .trim_end_matches(".xml")
+                        .to_string()
+                });
+                import_cached_icons(&col, &origin, icons_root);
+                log::info!(
+                    "Loaded AppStream catalog {} ({} components, {:?})",
+                    file_name,
+                    col.components.len(),
+                    rank
+                );
+                catalogs.push((rank, col));
+            }
+        }
+    }
+    catalogs
+}
+
+/// Flathub's catalog, used to fill in icons and screenshots for repo packages that are also
+/// on Flathub. Cached as flathub.xml and refreshed on the same interval as Arch's.
+pub async fn download_flathub_appstream(
+    interval_hours: u64,
+    base_dir: &std::path::Path,
+) -> Result<PathBuf, String> {
+    let target_path = base_dir.join("flathub.xml");
+    let fresh = tokio::fs::metadata(&target_path)
+        .await
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.elapsed().ok())
+        .is_some_and(|age| age.as_secs() < interval_hours * 3600);
+    if fresh {
+        return Ok(target_path);
+    }
+
+    log::info!("Downloading Flathub AppStream data...");
+    let client = crate::http_client::client(std::time::Duration::from_secs(120));
+    let resp = crate::http_client::fetch(client.get(FLATHUB_APPSTREAM_URL)).await?;
+    if !resp.is_success() {
+        return Err(format!(
+            "Failed to download Flathub AppStream: {}",
+            resp.status
+        ));
+    }
+    // Tens of MB once decompressed: decode, sanitize and write off the async runtime
+    tokio::task::spawn_blocking(move || -> Result<PathBuf, String> {
+        let mut xml = String::new();
+        std::io::Read::read_to_string(
+            &mut flate2::read::GzDecoder::new(resp.body.as_slice()),
+            &mut xml,
+        )
+        .map_err(|e| e.to_string())?;
+        std::fs::write(&target_path, sanitize_xml(&xml)).map_err(|e| e.to_string())?;
+        Ok(target_path)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// The category index key for a store category name ("games" -> "game").
+pub fn category_key(cat_lower: &str) -> &str {
+    match cat_lower {
+        "utilities" => "utility",
+        "games" => "game",
+        "multimedia" => "audiovideo", // AudioVideo is XDG standard
+        "graphics" => "graphics",
+        "network" | "internet" => "network",
+        "office" | "productivity" => "office",
+        "development" | "develop" => "development",
+        "system" => "system",
+        k => k,
+    }
+}
+
 pub fn get_cache_dir() -> PathBuf {
     dirs::cache_dir()
         .unwrap_or_else(|| PathBuf::from("/tmp"))
@@ -758,9 +1056,175 @@ pub fn get_icons_dir() -> PathBuf {
     get_cache_dir().join("icons")
 }
 
+const STORE_FILE: &str = "settings.json";
+/// Catalog language chosen in Settings; absent means the system locale.
+const CATALOG_LOCALE_KEY: &str = "catalog_locale";
+
+/// The user's locale as gettext sees it: LC_ALL, then LC_MESSAGES, then LANG.
+pub fn system_locale() -> String {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|l| !l.is_empty())
+        .unwrap_or_else(|| "C".to_string())
+}
+
+/// AppStream translation keys for a POSIX locale, best first: "pt_BR.UTF-8" gives
+/// ["pt_BR", "pt"], "sr_RS@latin" gives ["sr_RS@latin", "sr_RS", "sr@latin", "sr"].
+pub fn locale_candidates(locale: &str) -> Vec<String> {
+    let (base, modifier) = match locale.split_once('@') {
+        Some((base, modifier)) => (base, Some(modifier)),
+        None => (locale, None),
+    };
+    let tag = base.split('.').next().unwrap_or_default().replace('-', "_");
+    let lang = tag.split('_').next().unwrap_or_default().to_string();
+    if lang.is_empty() || tag == "C" || tag == "POSIX" {
+        return Vec::new();
+    }
+    let mut keys = vec![tag];
+    if keys[0] != lang {
+        keys.push(lang);
+    }
+    let mut candidates = Vec::new();
+    for key in keys {
+        if let Some(modifier) = modifier {
+            candidates.push(format!("{}@{}", key, modifier));
+        }
+        candidates.push(key);
+    }
+    candidates
+}
+
+/// The translation for the first matching candidate, else the untranslated ("C") string.
+/// Catalogs without a "C" entry fall back to the first key in order, so the choice does
+/// not change between runs.
+pub fn pick_translation(
+    strings: &HashMap<String, String>,
+    candidates: &[String],
+) -> Option<String> {
+    candidates
+        .iter()
+        .map(String::as_str)
+        .chain(std::iter::once("C"))
+        .find_map(|key| strings.get(key))
+        .or_else(|| strings.iter().min_by(|a, b| a.0.cmp(b.0)).map(|(_, v)| v))
+        .cloned()
+}
+
+fn valid_locale(locale: &str) -> bool {
+    !locale.is_empty()
+        && locale.len() <= 32
+        && locale
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '@'))
+}
+
+fn stored_catalog_locale(app: &AppHandle) -> Option<String> {
+    app.store(STORE_FILE)
+        .ok()?
+        .get(CATALOG_LOCALE_KEY)?
+        .as_str()
+        .map(str::to_string)
+        .filter(|l| valid_locale(l))
+}
+
+/// Apply the catalog language saved in Settings; called once at startup, before the
+/// catalogs are loaded.
+pub(crate) fn load_catalog_locale(app: &AppHandle) {
+    if let Some(locale) = stored_catalog_locale(app) {
+        log::info!("AppStream catalog language: {}", locale);
+        if let Ok(mut loader) = app.state::<MetadataState>().0.lock() {
+            loader.set_locale(&locale);
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct CatalogLocale {
+    /// Locale the catalog is shown in.
+    pub locale: String,
+    /// Set in Settings rather than taken from the system.
+    pub custom: bool,
+}
+
+#[tauri::command]
+pub async fn get_catalog_locale(app: AppHandle) -> Result<CatalogLocale, String> {
+    Ok(match stored_catalog_locale(&app) {
+        Some(locale) => CatalogLocale {
+            locale,
+            custom: true,
+        },
+        None => CatalogLocale {
+            locale: system_locale(),
+            custom: false,
+        },
+    })
+}
+
+/// Show app names, summaries and descriptions in `locale` (e.g. "de_DE", "pt_BR"); `None`
+/// goes back to the system locale. Strings without a translation stay in English. The
+/// search index and catalog are rebuilt so listings change language too.
+#[tauri::command]
+pub async fn set_catalog_locale(
+    app: AppHandle,
+    state: State<'_, MetadataState>,
+    locale: Option<String>,
+) -> Result<CatalogLocale, String> {
+    let locale = locale
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty());
+    if let Some(l) = &locale {
+        if !valid_locale(l) {
+            return Err(format!("Invalid locale '{}'", l));
+        }
+    }
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    match &locale {
+        Some(l) => store.set(CATALOG_LOCALE_KEY, serde_json::json!(l)),
+        None => {
+            store.delete(CATALOG_LOCALE_KEY);
+        }
+    }
+    store.save().map_err(|e| e.to_string())?;
+
+    let effective = locale.clone().unwrap_or_else(system_locale);
+    // Re-indexing every component takes a while: do it on a copy, off the runtime, and
+    // swap it in unless the loader changed meanwhile
+    let loader = state.loader().ok_or("AppStream data is unavailable")?;
+    let started_from = loader.revision();
+    let rebuild_locale = effective.clone();
+    let rebuilt = tokio::task::spawn_blocking(move || {
+        let mut loader = loader;
+        loader.set_locale(&rebuild_locale);
+        loader
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    {
+        let mut current = state.0.lock().map_err(|e| e.to_string())?;
+        if current.revision() == started_from {
+            *current = rebuilt;
+        } else {
+            // A catalog load finished in between; index that one instead
+            current.set_locale(&effective);
+        }
+    }
+    crate::search_index::refresh(&app).await;
+    crate::catalog::refresh(&app).await;
+    Ok(CatalogLocale {
+        locale: effective,
+        custom: locale.is_some(),
+    })
+}
+
 pub struct MetadataState(pub Mutex<AppStreamLoader>);
 
 impl MetadataState {
+    /// A copy of the loader to read without holding the lock; cheap, the data is shared.
+    pub fn loader(&self) -> Option<AppStreamLoader> {
+        self.0.lock().ok().map(|l| l.clone())
+    }
+
     pub async fn init(&self, interval_hours: u64) {
         // Run on all platforms (Linux/macOS) to ensure consistent cache
         let cache_dir = get_cache_dir();
@@ -769,12 +1233,12 @@ impl MetadataState {
             return;
         }
 
+        let mut catalogs = Vec::new();
         match download_and_cache_appstream(interval_hours, &cache_dir).await {
             Ok(path) => match Collection::from_path(path.clone()) {
                 Ok(col) => {
                     log::info!("Loaded AppStream data from {:?}", path);
-                    let mut loader = self.0.lock().expect("MetadataState lock poisoned");
-                    loader.set_collection(col);
+                    catalogs.push((CatalogRank::Arch, col));
                 }
                 Err(e) => {
                     log::warn!(
@@ -793,6 +1257,60 @@ impl MetadataState {
                 log::error!("Failed to download AppStream: {}", e);
             }
         }
+
+        // Derivative repos (Manjaro, CachyOS, EndeavourOS, ...) ship their own catalogs
+        let distro_id = crate::distro_context::DistroContext::new()
+            .id_str()
+            .to_string();
+        let have_arch = !catalogs.is_empty();
+        let system_dir = cache_dir.clone();
+        match tokio::task::spawn_blocking(move || {
+            load_system_catalogs(&system_dir, &distro_id, have_arch)
+        })
+        .await
+        {
+            Ok(system) => catalogs.extend(system),
+            Err(e) => log::warn!("Failed to load system AppStream catalogs: {}", e),
+        }
+
+        match download_flathub_appstream(interval_hours, &cache_dir).await {
+            Ok(path) => {
+                let parsed = tokio::task::spawn_blocking({
+                    let path = path.clone();
+                    move || Collection::from_path(path).map_err(|e| e.to_string())
+                })
+                .await
+                .unwrap_or_else(|e| Err(format!("Task join error: {}", e)));
+                match parsed {
+                    Ok(col) => catalogs.push((CatalogRank::Flathub, col)),
+                    Err(e) => {
+                        log::warn!("Failed to parse Flathub AppStream data: {}", e);
+                        let _ = tokio::fs::remove_file(&path).await;
+                    }
+                }
+            }
+            Err(e) => log::warn!("Failed to download Flathub AppStream: {}", e),
+        }
+
+        if let Some(merged) = merge_catalogs(catalogs) {
+            log::info!(
+                "AppStream catalogs merged: {} components",
+                merged.components.len()
+            );
+            let mut loader = self.0.lock().expect("MetadataState lock poisoned");
+            loader.set_collection(merged);
+        }
+
+        match tokio::task::spawn_blocking(move || crate::content_rating::index_catalogs(&cache_dir))
+            .await
+        {
+            Ok(ratings) => {
+                log::info!("AppStream content ratings: {} apps", ratings.len());
+                let mut loader = self.0.lock().expect("MetadataState lock poisoned");
+                loader.set_content_ratings(ratings);
+            }
+            Err(e) => log::warn!("Failed to read AppStream content ratings: {}", e),
+        }
     }
 }
 
@@ -811,8 +1329,7 @@ pub async fn fetch_og_image(url: &str) -> Option<String> {
     }
 
     // Use a short timeout
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(3))
+    let client = crate::http_client::builder(std::time::Duration::from_secs(3))
         .build()
         .ok()?;
 
@@ -898,6 +1415,141 @@ pub async fn get_metadata_batch(
     Ok(results)
 }
 
+/// Upper bound per hydrate_packages call; lists page through in chunks.
+const MAX_HYDRATE_BATCH: usize = 200;
+/// Flathub lookups in flight at once for a hydrate_packages call.
+const HYDRATE_FLATHUB_CONCURRENCY: usize = 8;
+
+/// Subset of card metadata; only the fields asked for in `fields` are filled.
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct HydratedPackage {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub app_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub screenshots: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rating: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rating_count: Option<u32>,
+}
+
+/// Batch replacement for per-card get_metadata calls. `fields` is any of
+/// "name", "summary", "icon", "screenshots", "rating". AppStream indexes are read under a
+/// single lock; Flathub is only consulted for requested media that AppStream lacks, and
+/// ratings go out as one ODRS batch.
+#[tauri::command]
+pub async fn hydrate_packages(
+    state: State<'_, MetadataState>,
+    flathub_state: State<'_, crate::flathub_api::FlathubApiClient>,
+    pkg_names: Vec<String>,
+    fields: Vec<String>,
+) -> Result<HashMap<String, HydratedPackage>, String> {
+    if pkg_names.len() > MAX_HYDRATE_BATCH {
+        return Err(format!(
+            "Too many packages ({}); hydrate at most {} per call",
+            pkg_names.len(),
+            MAX_HYDRATE_BATCH
+        ));
+    }
+    let want = |f: &str| fields.iter().any(|x| x == f);
+    let (want_name, want_summary, want_icon, want_shots, want_rating) = (
+        want("name"),
+        want("summary"),
+        want("icon"),
+        want("screenshots"),
+        want("rating"),
+    );
+
+    // 1. AppStream + local icon index, one pass under one lock
+    let mut results: HashMap<String, HydratedPackage> = HashMap::new();
+    {
+        let loader = state.0.lock().map_err(|_| "MetadataState lock poisoned")?;
+        for pkg_name in &pkg_names {
+            let meta = loader.find_package(pkg_name).or_else(|| {
+                let base_name = crate::utils::strip_package_suffix(pkg_name);
+                (base_name != pkg_name.as_str())
+                    .then(|| loader.find_package(base_name))
+                    .flatten()
+            });
+            let mut out = HydratedPackage {
+                app_id: meta.as_ref().map(|m| m.app_id.clone()),
+                ..Default::default()
+            };
+            if let Some(m) = meta {
+                if want_name {
+                    out.name = Some(m.name);
+                }
+                if want_summary {
+                    out.summary = m.summary;
+                }
+                if want_icon {
+                    out.icon = m.icon_url;
+                }
+                if want_shots && !m.screenshots.is_empty() {
+                    out.screenshots = Some(m.screenshots);
+                }
+            }
+            if want_icon && out.icon.is_none() {
+                out.icon = loader.find_icon_heuristic(pkg_name);
+            }
+            results.insert(pkg_name.clone(), out);
+        }
+    }
+
+    // 2. Flathub only for requested media still missing (its client caches lookups)
+    let missing: Vec<String> = results
+        .iter()
+        .filter(|(_, h)| (want_icon && h.icon.is_none()) || (want_shots && h.screenshots.is_none()))
+        .map(|(name, _)| name.clone())
+        .collect();
+    let flathub = flathub_state.inner();
+    let fetched: Vec<_> = futures::stream::iter(missing)
+        .map(|name| async move {
+            let meta = flathub.get_metadata_for_package(&name).await;
+            (name, meta)
+        })
+        .buffer_unordered(HYDRATE_FLATHUB_CONCURRENCY)
+        .collect()
+        .await;
+    for (name, meta) in fetched {
+        let (Some(meta), Some(out)) = (meta, results.get_mut(&name)) else {
+            continue;
+        };
+        let enriched = crate::flathub_api::flathub_to_app_metadata(&meta, &name);
+        if out.app_id.as_ref().map_or(true, |id| !id.contains('.')) {
+            out.app_id = Some(enriched.app_id);
+        }
+        if want_icon && out.icon.is_none() {
+            out.icon = enriched.icon_url;
+        }
+        if want_shots && out.screenshots.is_none() && !enriched.screenshots.is_empty() {
+            out.screenshots = Some(enriched.screenshots);
+        }
+    }
+
+    // 3. Ratings in one ODRS batch
+    if want_rating {
+        let app_ids: Vec<String> = results.values().filter_map(|h| h.app_id.clone()).collect();
+        let ratings = crate::odrs_api::get_app_ratings_batch(app_ids)
+            .await
+            .unwrap_or_default();
+        for out in results.values_mut() {
+            if let Some(r) = out.app_id.as_ref().and_then(|id| ratings.get(id)) {
+                out.rating = r.average();
+                out.rating_count = Some(r.total);
+            }
+        }
+    }
+
+    Ok(results)
+}
+
 #[tauri::command]
 pub async fn get_metadata(
     state: State<'_, MetadataState>,
@@ -1043,3 +1695,155 @@ pub async fn get_metadata_core(
 }
 
 // Health logic successfully moved to repair.rs
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn catalog(name: &str, xml: &str) -> Collection {
+        let dir = std::env::temp_dir().join(format!("monarch-appstream-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, xml).unwrap();
+        Collection::from_path(path).unwrap()
+    }
+
+    #[test]
+    fn test_catalog_ranking_and_merge() {
+        assert_eq!(
+            rank_system_catalog("cachyos-v3.xml.gz", "cachyos"),
+            CatalogRank::Distro
+        );
+        assert_eq!(
+            rank_system_catalog("extra.xml.gz", "cachyos"),
+            CatalogRank::Arch
+        );
+        assert_eq!(
+            rank_system_catalog("extra.xml.gz", "arch"),
+            CatalogRank::Arch
+        );
+        assert_eq!(
+            rank_system_catalog("endeavouros.xml", "manjaro"),
+            CatalogRank::Other
+        );
+
+        let arch = catalog(
+            "extra.xml",
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<components version="0.14" origin="extra">
+  <component type="desktop-application">
+    <id>org.example.Viewer</id>
+    <pkgname>viewer</pkgname>
+    <name>Viewer</name>
+    <summary>From Arch</summary>
+  </component>
+</components>"#,
+        );
+        let distro = catalog(
+            "cachyos.xml",
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<components version="0.14" origin="cachyos">
+  <component type="desktop-application">
+    <id>org.example.Viewer</id>
+    <pkgname>viewer</pkgname>
+    <name>Viewer</name>
+    <summary>From CachyOS</summary>
+  </component>
+  <component type="desktop-application">
+    <id>org.cachyos.Hello</id>
+    <pkgname>cachyos-hello</pkgname>
+    <name>CachyOS Hello</name>
+  </component>
+</components>"#,
+        );
+        let flathub = catalog(
+            "flathub.xml",
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<components version="0.8" origin="flathub">
+  <component type="desktop-application">
+    <id>org.example.Viewer</id>
+    <name>Viewer</name>
+    <icon type="remote" width="128" height="128">https://dl.flathub.org/media/viewer.png</icon>
+    <screenshots>
+      <screenshot type="default"><image type="source">https://example.org/viewer.png</image></screenshot>
+    </screenshots>
+  </component>
+  <component type="desktop-application">
+    <id>org.example.FlatpakOnly</id>
+    <name>Flatpak Only</name>
+  </component>
+</components>"#,
+        );
+
+        assert!(merge_catalogs(vec![(CatalogRank::Flathub, flathub.clone())]).is_none());
+
+        let merged = merge_catalogs(vec![
+            (CatalogRank::Flathub, flathub),
+            (CatalogRank::Arch, arch),
+            (CatalogRank::Distro, distro),
+        ])
+        .unwrap();
+        let ids: Vec<String> = merged.components.iter().map(|c| c.id.to_string()).collect();
+        assert_eq!(ids, vec!["org.example.Viewer", "org.cachyos.Hello"]);
+
+        // The distro's text wins; Flathub fills in the icon and screenshot nobody else had
+        let loader = {
+            let mut l = AppStreamLoader {
+                collection: None,
+                category_index: Arc::default(),
+                icon_index: Arc::default(),
+                pkg_index: Arc::default(),
+                local_icon_index: Arc::default(),
+                locale: Vec::new(),
+                content_ratings: Arc::default(),
+                revision: 0,
+            };
+            l.set_collection(merged);
+            l
+        };
+        let viewer = loader.find_package("viewer").unwrap();
+        assert_eq!(viewer.summary.as_deref(), Some("From CachyOS"));
+        assert_eq!(
+            viewer.icon_url.as_deref(),
+            Some("https://dl.flathub.org/media/viewer.png")
+        );
+        assert_eq!(viewer.screenshots, vec!["https://example.org/viewer.png"]);
+        assert!(loader.find_package("cachyos-hello").is_some());
+    }
+
+    #[test]
+    fn test_locale_candidates_and_translation_pick() {
+        assert_eq!(locale_candidates("pt_BR.UTF-8"), vec!["pt_BR", "pt"]);
+        assert_eq!(locale_candidates("de"), vec!["de"]);
+        assert_eq!(locale_candidates("en-GB"), vec!["en_GB", "en"]);
+        assert_eq!(
+            locale_candidates("sr_RS.UTF-8@latin"),
+            vec!["sr_RS@latin", "sr_RS", "sr@latin", "sr"]
+        );
+        assert!(locale_candidates("C.UTF-8").is_empty());
+        assert!(locale_candidates("POSIX").is_empty());
+
+        let strings: HashMap<String, String> = [("C", "Viewer"), ("pt", "Visualizador")]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let pick = |locale: &str| pick_translation(&strings, &locale_candidates(locale));
+        assert_eq!(pick("pt_BR.UTF-8").as_deref(), Some("Visualizador"));
+        assert_eq!(pick("fr_FR.UTF-8").as_deref(), Some("Viewer"));
+        assert_eq!(pick("C").as_deref(), Some("Viewer"));
+
+        let untagged: HashMap<String, String> = [("fr", "Visionneuse"), ("de", "Betrachter")]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        assert_eq!(
+            pick_translation(&untagged, &[]).as_deref(),
+            Some("Betrachter")
+        );
+        assert!(pick_translation(&HashMap::new(), &[]).is_none());
+
+        assert!(valid_locale("sr_RS.UTF-8@latin"));
+        assert!(!valid_locale("../../etc"));
+        assert!(!valid_locale(""));
+    }
+}
@@ -0,0 +1,255 @@
+//! One-time import of settings left by older MonArch builds (the pre-split backend that
+//! stored config and caches under other directory names), so upgrading keeps repo toggles
+//! and preferences and doesn't leave a second, dead cache behind.
+//!
+//! Runs before RepoManager::new() reads ~/.config/monarch-store/repos.json. Never overwrites
+//! a current config; imported legacy files are renamed to `*.migrated` rather than deleted.
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const CURRENT_DIR: &str = "monarch-store";
+const MARKER: &str = ".legacy-migration-v1";
+
+/// Config directories used by earlier builds (Tauri identifier and older crate names).
+const LEGACY_CONFIG_DIRS: &[&str] = &["com.monarch.store", "monarch_store", "monarch"];
+/// Settings file names those builds wrote, in preference order.
+const LEGACY_CONFIG_FILES: &[&str] = &["repos.json", "config.json", "settings.json"];
+/// Caches from earlier builds; everything in them is re-downloadable. (~/.cache/com.monarch.store
+/// is not listed: the current WebKit webview keeps its cache there.)
+const LEGACY_CACHE_DIRS: &[&str] = &["monarch_store"];
+
+/// Settings keys carried over, with the spellings older builds used for them.
+const SETTING_KEYS: &[(&str, &[&str])] = &[
+    ("aur_enabled", &["aur_enabled", "aurEnabled", "enable_aur"]),
+    (
+        "one_click_enabled",
+        &["one_click_enabled", "oneClickEnabled", "one_click"],
+    ),
+    ("advanced_mode", &["advanced_mode", "advancedMode"]),
+    (
+        "telemetry_enabled",
+        &["telemetry_enabled", "telemetryEnabled", "telemetry"],
+    ),
+    (
+        "notifications_enabled",
+        &[
+            "notifications_enabled",
+            "notificationsEnabled",
+            "notifications",
+        ],
+    ),
+    (
+        "sync_on_startup_enabled",
+        &[
+            "sync_on_startup_enabled",
+            "syncOnStartup",
+            "sync_on_startup",
+        ],
+    ),
+];
+
+/// What the migration did on this start, for the settings page.
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct MigrationReport {
+    pub imported_from: Option<String>,
+    pub imported_repos: usize,
+    pub imported_settings: Vec<String>,
+    pub removed_caches: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+static LAST_REPORT: once_cell::sync::Lazy<Mutex<Option<MigrationReport>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(None));
+
+/// Repo toggles from the shapes older builds saved: `[{name, enabled}]` under "repos",
+/// or a `{name: bool}` map under "repos" / "enabled_repos".
+fn legacy_repo_toggles(legacy: &Value) -> Vec<(String, bool)> {
+    let field = legacy.get("repos").or_else(|| legacy.get("enabled_repos"));
+    match field {
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(|r| {
+                Some((
+                    r.get("name")?.as_str()?.to_string(),
+                    r.get("enabled").and_then(Value::as_bool).unwrap_or(true),
+                ))
+            })
+            .collect(),
+        Some(Value::Object(map)) => map
+            .iter()
+            .filter_map(|(name, v)| Some((name.clone(), v.as_bool()?)))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Convert a legacy settings document into the current repos.json shape. None when there
+/// is nothing recognisable to import.
+fn convert_legacy_config(legacy: &Value) -> Option<(Value, usize, Vec<String>)> {
+    let mut out = Map::new();
+    let mut settings = Vec::new();
+    for (key, aliases) in SETTING_KEYS {
+        if let Some(v) = aliases
+            .iter()
+            .find_map(|a| legacy.get(*a).and_then(Value::as_bool))
+        {
+            out.insert(key.to_string(), Value::Bool(v));
+            settings.push(key.to_string());
+        }
+    }
+
+    // RepoManager only takes `enabled` from saved repos (url/source come from pacman.conf)
+    let toggles = legacy_repo_toggles(legacy);
+    let repos: Vec<Value> = toggles
+        .iter()
+        .filter(|(name, _)| crate::utils::validate_package_name(name).is_ok())
+        .map(|(name, enabled)| {
+            serde_json::json!({
+                "name": name,
+                "url": "",
+                "source": { "source_type": "repo", "id": name, "version": "", "label": "" },
+                "enabled": enabled,
+            })
+        })
+        .collect();
+    if repos.is_empty() && settings.is_empty() {
+        return None;
+    }
+    let imported_repos = repos.len();
+    out.insert("repos".to_string(), Value::Array(repos));
+    Some((Value::Object(out), imported_repos, settings))
+}
+
+fn find_legacy_config(config_root: &Path) -> Option<(PathBuf, Value)> {
+    LEGACY_CONFIG_DIRS
+        .iter()
+        .flat_map(|dir| {
+            LEGACY_CONFIG_FILES
+                .iter()
+                .map(move |f| config_root.join(dir).join(f))
+        })
+        .find_map(|path| {
+            let value = serde_json::from_str(&std::fs::read_to_string(&path).ok()?).ok()?;
+            Some((path, value))
+        })
+}
+
+fn import_config(config_root: &Path, report: &mut MigrationReport) {
+    let target = config_root.join(CURRENT_DIR).join("repos.json");
+    if target.exists() {
+        return;
+    }
+    let Some((path, legacy)) = find_legacy_config(config_root) else {
+        return;
+    };
+    let Some((converted, repos, settings)) = convert_legacy_config(&legacy) else {
+        return;
+    };
+    let written = std::fs::create_dir_all(config_root.join(CURRENT_DIR)).and_then(|_| {
+        let json = serde_json::to_string_pretty(&converted).map_err(std::io::Error::other)?;
+        std::fs::write(&target, json)
+    });
+    match written {
+        Ok(()) => {
+            let mut backup = path.clone().into_os_string();
+            backup.push(".migrated");
+            if let Err(e) = std::fs::rename(&path, &backup) {
+                report
+                    .errors
+                    .push(format!("Failed to retire {}: {}", path.display(), e));
+            }
+            report.imported_from = Some(path.display().to_string());
+            report.imported_repos = repos;
+            report.imported_settings = settings;
+        }
+        Err(e) => report
+            .errors
+            .push(format!("Failed to write {}: {}", target.display(), e)),
+    }
+}
+
+fn remove_legacy_caches(cache_root: &Path, report: &mut MigrationReport) {
+    for dir in LEGACY_CACHE_DIRS {
+        let path = cache_root.join(dir);
+        // Never follow a symlink out of ~/.cache
+        let is_dir = std::fs::symlink_metadata(&path)
+            .map(|m| m.is_dir())
+            .unwrap_or(false);
+        if !is_dir {
+            continue;
+        }
+        match std::fs::remove_dir_all(&path) {
+            Ok(()) => report.removed_caches.push(path.display().to_string()),
+            Err(e) => report
+                .errors
+                .push(format!("Failed to remove {}: {}", path.display(), e)),
+        }
+    }
+}
+
+/// Import legacy config and drop legacy caches once per user. Call before RepoManager::new().
+pub fn run() {
+    let (Some(config_root), Some(cache_root)) = (dirs::config_dir(), dirs::cache_dir()) else {
+        return;
+    };
+    let marker = config_root.join(CURRENT_DIR).join(MARKER);
+    if marker.exists() {
+        return;
+    }
+
+    let mut report = MigrationReport::default();
+    import_config(&config_root, &mut report);
+    remove_legacy_caches(&cache_root, &mut report);
+
+    if report.imported_from.is_some() || !report.removed_caches.is_empty() {
+        log::info!("Legacy migration: {:?}", report);
+    }
+    for e in &report.errors {
+        log::warn!("Legacy migration: {}", e);
+    }
+    // Retry next start if something failed
+    if report.errors.is_empty() {
+        let _ = std::fs::create_dir_all(config_root.join(CURRENT_DIR));
+        let _ = std::fs::write(&marker, chrono::Utc::now().to_rfc3339());
+    }
+    if let Ok(mut last) = LAST_REPORT.lock() {
+        *last = Some(report);
+    }
+}
+
+/// What the legacy migration did on this start (None if it had already run before).
+#[tauri::command]
+pub async fn get_migration_report() -> Result<Option<MigrationReport>, String> {
+    Ok(LAST_REPORT.lock().map(|r| r.clone()).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_legacy_config() {
+        let legacy = serde_json::json!({
+            "aurEnabled": true,
+            "telemetry": false,
+            "repos": [
+                { "name": "chaotic-aur", "enabled": true },
+                { "name": "cachyos", "enabled": false },
+                { "name": "../etc", "enabled": true }
+            ]
+        });
+        let (converted, repos, settings) = convert_legacy_config(&legacy).unwrap();
+        assert_eq!(repos, 2);
+        assert_eq!(settings, vec!["aur_enabled", "telemetry_enabled"]);
+        assert_eq!(converted["aur_enabled"], Value::Bool(true));
+        assert_eq!(converted["repos"][1]["name"], "cachyos");
+        assert_eq!(converted["repos"][1]["enabled"], Value::Bool(false));
+
+        let map_shape = serde_json::json!({ "enabled_repos": { "chaotic-aur": true } });
+        assert_eq!(convert_legacy_config(&map_shape).unwrap().1, 1);
+        assert!(convert_legacy_config(&serde_json::json!({ "theme": "dark" })).is_none());
+    }
+}
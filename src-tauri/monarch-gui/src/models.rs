@@ -101,10 +101,28 @@ pub struct Package {
     pub make_depends: Option<Vec<String>>,
     pub is_featured: Option<bool>,
     pub installed: bool,
+    /// Installed, and this listing's version is newer.
+    #[serde(default)]
+    pub update_available: bool,
     pub download_size: Option<u64>,
     pub installed_size: Option<u64>,
     pub alternatives: Option<Vec<Package>>,
     pub available_sources: Option<Vec<PackageSource>>, // For consolidated search results
+    pub optimized_variants: Option<Vec<OptimizedVariant>>,
+    /// OARS age rating from AppStream, when the app has one.
+    #[serde(default)]
+    pub content_rating: Option<crate::content_rating::ContentRating>,
+}
+
+/// A CPU-optimized build of a package in an enabled repo (cachyos-v3, cachyos-extra-znver4, ...).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OptimizedVariant {
+    pub repo: String,
+    /// v3, v4 or znver4
+    pub level: String,
+    pub version: String,
+    /// This CPU can run the build.
+    pub supported: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -118,20 +136,75 @@ pub struct PackageVariant {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct UpdateItem {
     pub name: String,
+    /// Shown instead of `name` when that is an opaque id (firmware device ids).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
     pub current_version: String,
     pub new_version: String,
     pub source: PackageSource, // "official", "aur", "flatpak"
     pub size: Option<u64>,
     pub icon: Option<String>,
+    /// Candidate is older than what's installed (e.g. AUR behind a locally built package).
+    #[serde(default)]
+    pub downgrade: bool,
 }
 
+/// check_updates result: one section per backend, checked in parallel.
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
-pub struct TransactionManifest {
-    pub update_system: bool,          // Should we run -Syu?
-    pub refresh_db: bool,             // Should we run -Sy?
-    pub clear_cache: bool,            // Should we run -Sc?
-    pub remove_lock: bool,            // Should we remove pacman lock?
-    pub install_targets: Vec<String>, // List of repo packages
-    pub remove_targets: Vec<String>,  // List of packages to remove
-    pub local_paths: Vec<String>,     // List of pre-built AUR packages (.pkg.tar.zst) to install
+pub struct UpdateSet {
+    pub repo: Vec<UpdateItem>,
+    pub aur: Vec<UpdateItem>,
+    pub flatpak: Vec<UpdateItem>,
+    pub firmware: Vec<UpdateItem>,
+    /// Backend ("repo", "aur", "flatpak", "firmware") → why its check failed. The other
+    /// sections are still valid.
+    #[serde(default)]
+    pub errors: std::collections::BTreeMap<String, String>,
+}
+
+impl UpdateSet {
+    pub fn len(&self) -> usize {
+        self.repo.len() + self.aur.len() + self.flatpak.len() + self.firmware.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Sections in the order apply_updates runs them: repos, AUR, Flatpak, firmware.
+    pub fn into_items(self) -> Vec<UpdateItem> {
+        let mut items = self.repo;
+        items.extend(self.aur);
+        items.extend(self.flatpak);
+        items.extend(self.firmware);
+        items
+    }
+
+    /// Put each item in its backend's section by `source.source_type`.
+    pub fn from_items(items: Vec<UpdateItem>) -> Self {
+        let mut set = UpdateSet::default();
+        for item in items {
+            match item.source.source_type.as_str() {
+                "aur" => set.aur.push(item),
+                "flatpak" => set.flatpak.push(item),
+                "firmware" => set.firmware.push(item),
+                _ => set.repo.push(item),
+            }
+        }
+        set
+    }
+}
+
+pub use monarch_types::TransactionManifest;
+
+/// One node of a dependency tree (forward or reverse).
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct DependencyNode {
+    pub name: String,
+    pub version: Option<String>,
+    pub kind: String, // "root" | "depends" | "optdepends" | "makedepends" | "required_by" | "optional_for"
+    pub description: Option<String>, // optdepends reason, e.g. "for PDF export"
+    pub installed: bool,
+    pub satisfied_by: Option<String>, // Providing package when it differs from `name`
+    pub children: Vec<DependencyNode>,
 }
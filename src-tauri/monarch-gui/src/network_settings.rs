@@ -0,0 +1,163 @@
+//! Network settings (proxy, download rate limit, IPv4-only) from settings.json. The GUI's
+//! HTTP clients look them up per request through `http_client`, so changes apply without a
+//! restart. The helper keeps its own copy for package downloads, pushed with
+//! SetNetworkOptions when the settings are saved. Child processes (git, makepkg, flatpak)
+//! only honor the proxy, which is set on each command through [`proxy_env`]; the process
+//! environment is never changed.
+
+use monarch_types::NetworkOptions;
+use once_cell::sync::Lazy;
+use serde_json::json;
+use std::sync::RwLock;
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+const STORE_FILE: &str = "settings.json";
+const NETWORK_KEY: &str = "network";
+
+const PROXY_VARS: &[&str] = &[
+    "http_proxy",
+    "https_proxy",
+    "all_proxy",
+    "HTTP_PROXY",
+    "HTTPS_PROXY",
+    "ALL_PROXY",
+];
+
+static CURRENT: Lazy<RwLock<NetworkOptions>> = Lazy::new(|| RwLock::new(NetworkOptions::default()));
+
+pub fn current() -> NetworkOptions {
+    CURRENT.read().map(|c| c.clone()).unwrap_or_default()
+}
+
+/// Proxy for a GUI request to `url`: the configured one, else the system's proxy variables.
+/// Loopback never goes through a proxy.
+pub fn proxy_for(url: &reqwest::Url) -> Option<reqwest::Url> {
+    if matches!(url.host_str(), Some("localhost" | "127.0.0.1" | "[::1]")) {
+        return None;
+    }
+    let proxy = current().proxy.or_else(|| system_proxy(url.scheme()))?;
+    reqwest::Url::parse(&proxy).ok()
+}
+
+fn system_proxy(scheme: &str) -> Option<String> {
+    let wanted: &[&str] = if scheme == "https" {
+        &["https_proxy", "HTTPS_PROXY", "all_proxy", "ALL_PROXY"]
+    } else {
+        &["http_proxy", "HTTP_PROXY", "all_proxy", "ALL_PROXY"]
+    };
+    wanted
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|v| !v.is_empty()))
+}
+
+/// Proxy variables for a child process that goes online: the configured proxy under every
+/// standard name, or nothing when none is set (the child inherits the system's variables).
+pub fn proxy_env() -> Vec<(&'static str, String)> {
+    match current().proxy {
+        Some(proxy) => PROXY_VARS.iter().map(|var| (*var, proxy.clone())).collect(),
+        None => Vec::new(),
+    }
+}
+
+fn apply(options: NetworkOptions) {
+    if let Ok(mut slot) = CURRENT.write() {
+        *slot = options;
+    }
+}
+
+fn read_options(app: &AppHandle) -> NetworkOptions {
+    app.store(STORE_FILE)
+        .ok()
+        .and_then(|s| s.get(NETWORK_KEY))
+        .and_then(|v| serde_json::from_value::<NetworkOptions>(v).ok())
+        .map(NetworkOptions::normalized)
+        .filter(|o| o.validate().is_ok())
+        .unwrap_or_default()
+}
+
+/// At startup, before the first request.
+pub(crate) fn load(app: &AppHandle) {
+    let options = read_options(app);
+    if !options.is_default() {
+        log::info!(
+            "Network settings: proxy {}, rate limit {:?} KiB/s, IPv4 only {}",
+            if options.proxy.is_some() { "on" } else { "off" },
+            options.rate_limit_kbps,
+            options.ipv4_only
+        );
+    }
+    apply(options);
+}
+
+#[tauri::command]
+pub async fn get_network_settings(app: AppHandle) -> Result<NetworkOptions, String> {
+    Ok(read_options(&app))
+}
+
+/// Save and apply the settings, then hand them to the helper for package downloads. The
+/// GUI side is saved even when the helper step fails (e.g. authorization cancelled).
+#[tauri::command]
+pub async fn set_network_settings(
+    app: AppHandle,
+    settings: NetworkOptions,
+    password: Option<String>,
+) -> Result<NetworkOptions, String> {
+    let settings = settings.normalized();
+    settings.validate()?;
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    store.set(NETWORK_KEY, json!(settings));
+    store.save().map_err(|e| e.to_string())?;
+    apply(settings.clone());
+
+    if !crate::helper_client::supports("SetNetworkOptions").await {
+        log::warn!("Installed helper predates SetNetworkOptions; package downloads ignore the network settings");
+        return Ok(settings);
+    }
+    let _guard = crate::utils::PRIVILEGED_LOCK.lock().await;
+    let result = crate::helper_client::run_helper(
+        &app,
+        crate::helper_client::HelperCommand::SetNetworkOptions {
+            options: settings.clone(),
+        },
+        password,
+        |_| {},
+    )
+    .await?;
+    if !result.success {
+        return Err(format!(
+            "Saved for the app, but package downloads keep the previous settings: {}",
+            result
+                .error
+                .unwrap_or_else(|| "authorization cancelled?".to_string())
+        ));
+    }
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proxy_for() {
+        if let Ok(mut slot) = CURRENT.write() {
+            slot.proxy = Some("http://proxy.corp.example:3128".to_string());
+        }
+        let remote = reqwest::Url::parse("https://aur.archlinux.org/rpc/").unwrap();
+        assert_eq!(
+            proxy_for(&remote).map(|u| u.to_string()),
+            Some("http://proxy.corp.example:3128/".to_string())
+        );
+        let local = reqwest::Url::parse("http://127.0.0.1:8080/").unwrap();
+        assert_eq!(proxy_for(&local), None);
+        let env = proxy_env();
+        assert_eq!(env.len(), PROXY_VARS.len());
+        assert!(env
+            .iter()
+            .all(|(_, value)| value == "http://proxy.corp.example:3128"));
+        if let Ok(mut slot) = CURRENT.write() {
+            *slot = NetworkOptions::default();
+        }
+    }
+}
@@ -0,0 +1,393 @@
+//! Distro news shown before system updates (the informant workflow): Arch Linux news RSS,
+//! plus Manjaro announcements on Manjaro. Items published since the last system upgrade that
+//! the user hasn't marked read are "unread"; unread items that announce manual intervention
+//! block perform_system_update until acknowledged.
+
+use crate::distro_context::{get_distro_context, DistroId};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use tauri::{AppHandle, Manager};
+
+const ARCH_NEWS_URL: &str = "https://archlinux.org/feeds/news/";
+const MANJARO_ANNOUNCEMENTS_URL: &str = "https://forum.manjaro.org/c/announcements/11.rss";
+const CACHE_TTL_SECS: i64 = 60 * 60;
+/// Without any upgrade on record (fresh install, log rotated) only recent news counts as unread.
+const DEFAULT_LOOKBACK_SECS: i64 = 14 * 24 * 60 * 60;
+const SUMMARY_MAX_CHARS: usize = 400;
+const MAX_READ_LINKS: usize = 500;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct NewsItem {
+    /// "arch" or "manjaro"
+    pub source: String,
+    pub title: String,
+    pub link: String,
+    /// Unix seconds
+    pub published: i64,
+    pub summary: String,
+    /// The post asks for manual intervention; updating blindly may break the system.
+    pub breaking: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct NewsCache {
+    fetched_at: i64,
+    distro: String,
+    items: Vec<NewsItem>,
+}
+
+static ITEM_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<item>(.*?)</item>").expect("valid regex ITEM_RE"));
+static TAG_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<[^>]*>").expect("valid regex TAG_RE"));
+static SPACE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\s+").expect("valid regex SPACE_RE"));
+static PACMAN_LOG_TS_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\[([^\]]+)\]").expect("valid regex PACMAN_LOG_TS_RE"));
+
+fn tag_content<'a>(item: &'a str, tag: &str) -> Option<&'a str> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let start = item.find(&open)? + open.len();
+    let len = item[start..].find(&close)?;
+    let raw = item[start..start + len].trim();
+    Some(
+        raw.strip_prefix("<![CDATA[")
+            .and_then(|s| s.strip_suffix("]]>"))
+            .unwrap_or(raw),
+    )
+}
+
+fn decode_entities(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let Some(semi) = rest.bytes().take(10).position(|b| b == b';') else {
+            out.push('&');
+            rest = &rest[1..];
+            continue;
+        };
+        let decoded = match &rest[1..semi] {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            e if e.starts_with("#x") || e.starts_with("#X") => u32::from_str_radix(&e[2..], 16)
+                .ok()
+                .and_then(char::from_u32),
+            e if e.starts_with('#') => e[1..].parse().ok().and_then(char::from_u32),
+            _ => None,
+        };
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Descriptions are entity-escaped HTML: decode, drop the markup, collapse whitespace.
+fn plain_text(html: &str) -> String {
+    let decoded = decode_entities(html);
+    let no_tags = TAG_RE.replace_all(&decoded, " ");
+    // Second pass: text that was double-escaped (&amp;lt;) only becomes a tag/entity now
+    let text = decode_entities(&no_tags);
+    SPACE_RE.replace_all(text.trim(), " ").to_string()
+}
+
+fn truncate_chars(s: &str, max: usize) -> String {
+    match s.char_indices().nth(max) {
+        Some((idx, _)) => format!("{}…", s[..idx].trim_end()),
+        None => s.to_string(),
+    }
+}
+
+/// Arch marks these posts in the title ("... requires manual intervention"); Manjaro
+/// announcements put it in the body.
+fn is_breaking(title: &str, body: &str) -> bool {
+    let text = format!("{} {}", title, body).to_lowercase();
+    [
+        "manual intervention",
+        "intervention required",
+        "requires intervention",
+        "action required",
+    ]
+    .iter()
+    .any(|k| text.contains(k))
+}
+
+fn parse_rss(xml: &str, source: &str) -> Vec<NewsItem> {
+    ITEM_RE
+        .captures_iter(xml)
+        .filter_map(|cap| {
+            let item = cap.get(1)?.as_str();
+            let title = plain_text(tag_content(item, "title")?);
+            let link = decode_entities(tag_content(item, "link")?);
+            if title.is_empty() || !link.starts_with("https://") {
+                return None;
+            }
+            let published = tag_content(item, "pubDate")
+                .and_then(|d| chrono::DateTime::parse_from_rfc2822(d.trim()).ok())
+                .map(|d| d.timestamp())?;
+            let body = plain_text(tag_content(item, "description").unwrap_or(""));
+            Some(NewsItem {
+                source: source.to_string(),
+                breaking: is_breaking(&title, &body),
+                summary: truncate_chars(&body, SUMMARY_MAX_CHARS),
+                title,
+                link,
+                published,
+            })
+        })
+        .collect()
+}
+
+/// Time of the last full system upgrade in pacman.log. pacman logs "starting full system
+/// upgrade"; the helper drives ALPM directly, so its upgrades are recorded by
+/// record_system_upgrade instead and the caller takes the later of the two.
+fn last_upgrade_from_log(log: &str) -> Option<i64> {
+    log.lines()
+        .rev()
+        .find(|l| l.contains("starting full system upgrade"))
+        .and_then(|l| PACMAN_LOG_TS_RE.captures(l))
+        .and_then(|c| crate::history::parse_log_timestamp(c.get(1)?.as_str()))
+}
+
+fn unread_items(
+    items: &[NewsItem],
+    last_upgrade: Option<i64>,
+    read_links: &[String],
+    now: i64,
+) -> Vec<NewsItem> {
+    let since = last_upgrade.unwrap_or(now - DEFAULT_LOOKBACK_SECS);
+    let mut unread: Vec<NewsItem> = items
+        .iter()
+        .filter(|i| i.published > since && !read_links.contains(&i.link))
+        .cloned()
+        .collect();
+    unread.sort_by_key(|i| std::cmp::Reverse(i.published));
+    unread
+}
+
+fn cache_path() -> PathBuf {
+    crate::metadata::get_cache_dir().join("news.json")
+}
+
+fn last_upgrade_path() -> PathBuf {
+    crate::metadata::get_cache_dir().join("last_system_upgrade_at")
+}
+
+fn read_links_path(app: &AppHandle) -> PathBuf {
+    let path = app
+        .path()
+        .app_data_dir()
+        .unwrap_or_else(|_| PathBuf::from("."));
+    let _ = std::fs::create_dir_all(&path);
+    path.join("news-read.json")
+}
+
+fn load_read_links(app: &AppHandle) -> Vec<String> {
+    std::fs::read_to_string(read_links_path(app))
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+/// Called after a successful system update so news read during it isn't shown again.
+pub fn record_system_upgrade() {
+    let path = last_upgrade_path();
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    let _ = std::fs::write(path, chrono::Utc::now().timestamp().to_string());
+}
+
+fn last_system_upgrade() -> Option<i64> {
+    let recorded = std::fs::read_to_string(last_upgrade_path())
+        .ok()
+        .and_then(|s| s.trim().parse::<i64>().ok());
+    let logged = std::fs::read_to_string("/var/log/pacman.log")
+        .ok()
+        .and_then(|l| last_upgrade_from_log(&l));
+    recorded.max(logged)
+}
+
+async fn fetch_feed(
+    client: &reqwest::Client,
+    url: &str,
+    source: &str,
+) -> Result<Vec<NewsItem>, String> {
+    let body = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("{}: {}", url, e))?
+        .text()
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(parse_rss(&body, source))
+}
+
+/// All feed items for this distro, from the cache when fresh. A failed fetch falls back to
+/// the stale cache so being offline never blocks an update by itself.
+async fn fetch_news(force: bool) -> Vec<NewsItem> {
+    let distro = get_distro_context();
+    let distro_id = distro.id_str().to_string();
+    let cached: Option<NewsCache> = std::fs::read_to_string(cache_path())
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .filter(|c: &NewsCache| c.distro == distro_id);
+    let now = chrono::Utc::now().timestamp();
+    if let Some(c) = &cached {
+        if !force && now - c.fetched_at < CACHE_TTL_SECS {
+            return c.items.clone();
+        }
+    }
+
+    let client = match crate::http_client::builder(std::time::Duration::from_secs(10))
+        .user_agent("MonARCH-Store")
+        .build()
+    {
+        Ok(c) => c,
+        Err(_) => return cached.map(|c| c.items).unwrap_or_default(),
+    };
+    let mut feeds = vec![(ARCH_NEWS_URL, "arch")];
+    if distro.id == DistroId::Manjaro {
+        feeds.push((MANJARO_ANNOUNCEMENTS_URL, "manjaro"));
+    }
+    let mut items = Vec::new();
+    for (url, source) in feeds {
+        match fetch_feed(&client, url, source).await {
+            Ok(mut feed) => items.append(&mut feed),
+            Err(e) => {
+                log::warn!("News feed fetch failed: {}", e);
+                return cached.map(|c| c.items).unwrap_or_default();
+            }
+        }
+    }
+
+    let cache = NewsCache {
+        fetched_at: now,
+        distro: distro_id,
+        items: items.clone(),
+    };
+    if let Ok(json) = serde_json::to_string(&cache) {
+        let _ = std::fs::create_dir_all(crate::metadata::get_cache_dir());
+        let _ = std::fs::write(cache_path(), json);
+    }
+    items
+}
+
+/// Unread breaking news; perform_system_update refuses to start while this is non-empty.
+pub async fn pending_breaking_news(app: &AppHandle) -> Vec<NewsItem> {
+    let items = fetch_news(false).await;
+    let read = load_read_links(app);
+    let last_upgrade = tokio::task::spawn_blocking(last_system_upgrade)
+        .await
+        .ok()
+        .flatten();
+    unread_items(&items, last_upgrade, &read, chrono::Utc::now().timestamp())
+        .into_iter()
+        .filter(|i| i.breaking)
+        .collect()
+}
+
+/// News published since the last system upgrade that hasn't been marked read, newest first.
+#[tauri::command]
+pub async fn get_unread_news(app: AppHandle, force: Option<bool>) -> Result<Vec<NewsItem>, String> {
+    let items = fetch_news(force.unwrap_or(false)).await;
+    let read = load_read_links(&app);
+    let last_upgrade = tokio::task::spawn_blocking(last_system_upgrade)
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?;
+    Ok(unread_items(
+        &items,
+        last_upgrade,
+        &read,
+        chrono::Utc::now().timestamp(),
+    ))
+}
+
+/// Acknowledge news items (by link) so they no longer block or show as unread.
+#[tauri::command]
+pub async fn mark_news_read(app: AppHandle, links: Vec<String>) -> Result<(), String> {
+    let mut read = load_read_links(&app);
+    for link in links {
+        if !read.contains(&link) {
+            read.push(link);
+        }
+    }
+    if read.len() > MAX_READ_LINKS {
+        read.drain(..read.len() - MAX_READ_LINKS);
+    }
+    let content = serde_json::to_string_pretty(&read).map_err(|e| e.to_string())?;
+    std::fs::write(read_links_path(&app), content).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARCH_FEED: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<rss version="2.0"><channel><title>Arch Linux: Recent news updates</title>
+<item><title>Manual intervention for pacman 7.0.0 and local repositories required</title><link>https://archlinux.org/news/manual-intervention-for-pacman-700-and-local-repositories-required/</link><description>&lt;p&gt;With the release of &lt;a href="https://gitlab.archlinux.org/pacman/pacman/-/blob/master/NEWS?ref_type=heads"&gt;version 7.0.0&lt;/a&gt; pacman has added support for downloading packages as a separate user &amp;amp; more.&lt;/p&gt;</description><dc:creator xmlns:dc="http://purl.org/dc/elements/1.1/">Morten Linderud</dc:creator><pubDate>Sat, 14 Sep 2024 11:50:33 +0000</pubDate><guid isPermaLink="false">tag:archlinux.org,2024-09-14:/news/manual-intervention-for-pacman-700-and-local-repositories-required/</guid></item>
+<item><title><![CDATA[Valkey to replace Redis in the [extra] Repository]]></title><link>https://archlinux.org/news/valkey-to-replace-redis-in-the-extra-repository/</link><description>&lt;p&gt;Valkey will replace Redis.&lt;/p&gt;</description><pubDate>Tue, 15 Apr 2025 09:00:00 +0000</pubDate></item>
+<item><title>No date</title><link>https://archlinux.org/news/x/</link></item>
+</channel></rss>"#;
+
+    #[test]
+    fn test_parse_rss_and_flag_manual_intervention() {
+        let items = parse_rss(ARCH_FEED, "arch");
+        assert_eq!(items.len(), 2);
+        assert!(items[0].breaking);
+        assert_eq!(items[0].published, 1726314633);
+        assert!(items[0]
+            .summary
+            .starts_with("With the release of version 7.0.0 pacman"));
+        assert!(items[0].summary.ends_with("separate user & more."));
+        assert_eq!(
+            items[1].title,
+            "Valkey to replace Redis in the [extra] Repository"
+        );
+        assert!(!items[1].breaking);
+    }
+
+    #[test]
+    fn test_unread_since_last_upgrade() {
+        let log = "\
+[2024-09-01T10:00:00+0000] [PACMAN] Running 'pacman -Syu'
+[2024-09-01T10:00:00+0000] [PACMAN] starting full system upgrade
+[2024-09-20T08:00:00+0000] [PACMAN] Running 'pacman -S vim'
+";
+        let last = last_upgrade_from_log(log);
+        assert_eq!(last, Some(1725184800));
+
+        let items = parse_rss(ARCH_FEED, "arch");
+        let now = 1750000000;
+        let unread = unread_items(&items, last, &[], now);
+        assert_eq!(unread.len(), 2);
+        assert_eq!(unread[0].title, items[1].title, "newest first");
+
+        let read = vec![items[0].link.clone()];
+        let unread = unread_items(&items, last, &read, now);
+        assert_eq!(unread.len(), 1);
+        assert!(!unread[0].breaking);
+
+        // Nothing on record: only the lookback window counts
+        assert!(unread_items(&items, None, &[], now).is_empty());
+    }
+}
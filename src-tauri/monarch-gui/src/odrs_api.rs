@@ -1,5 +1,15 @@
+use crate::http_client;
 use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
 use std::collections::HashMap;
+use std::time::Duration;
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+const ODRS_API: &str = "https://odrs.gnome.org/1.0/reviews/api";
+const STORE_FILE: &str = "settings.json";
+/// Off until the user turns it on: publishing sends the review and a reviewer hash to ODRS.
+const PUBLISH_KEY: &str = "odrs_publish_enabled";
 
 /// JSON does not support NaN/Infinity; ensure f64 is finite before sending to frontend.
 fn sanitize_f64(v: Option<f64>) -> Option<f64> {
@@ -23,9 +33,24 @@ pub struct OdrsRating {
     pub score: Option<f64>, // ODRS returns 'score' (average) sometimes or we calc it
 }
 
+impl OdrsRating {
+    /// Average stars (1-5): the server's score if present, else computed from the histogram.
+    pub fn average(&self) -> Option<f64> {
+        if let Some(score) = sanitize_f64(self.score) {
+            return Some(score);
+        }
+        if self.total == 0 {
+            return None;
+        }
+        let sum = self.star1 + 2 * self.star2 + 3 * self.star3 + 4 * self.star4 + 5 * self.star5;
+        Some(sum as f64 / self.total as f64)
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Review {
     pub review_id: Option<u64>,
+    #[serde(default)]
     pub app_id: String,
     pub user_display: Option<String>,
     pub summary: Option<String>,
@@ -35,6 +60,12 @@ pub struct Review {
     pub version: Option<String>,
     pub distro: Option<String>,
     pub locale: Option<String>,
+    /// Key the server hands out with /fetch results; required to submit or vote for this app.
+    #[serde(default, skip_serializing)]
+    pub user_skey: Option<String>,
+    /// Reviewer hash; /fetch results carry it, so the user's own review can be found.
+    #[serde(default, skip_serializing)]
+    pub user_hash: Option<String>,
 }
 
 // Fetch basic rating summary
@@ -42,8 +73,7 @@ pub struct Review {
 pub async fn get_app_rating(app_id: String) -> Result<Option<OdrsRating>, String> {
     let url = format!("https://odrs.gnome.org/1.0/reviews/api/ratings/{}", app_id);
 
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(3))
+    let client = http_client::builder(std::time::Duration::from_secs(3))
         .build()
         .map_err(|e| e.to_string())?;
 
@@ -88,13 +118,22 @@ pub async fn get_app_ratings_batch(
     Ok(map)
 }
 
+/// Rating histograms of every app ODRS knows, for the catalog's "top rated" sort.
+pub async fn fetch_all_ratings() -> Result<HashMap<String, OdrsRating>, String> {
+    let client = http_client::client(Duration::from_secs(30));
+    let resp = http_client::fetch(client.get(format!("{}/ratings", ODRS_API))).await?;
+    if !resp.is_success() {
+        return Err(format!("ODRS returned {}", resp.status));
+    }
+    Ok(resp.json::<OdrsResponse>()?.ratings)
+}
+
 // Fetch detailed reviews
 #[tauri::command]
 pub async fn get_app_reviews(app_id: String) -> Result<Vec<Review>, String> {
     let url = format!("https://odrs.gnome.org/1.0/reviews/api/app/{}", app_id);
 
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(3))
+    let client = http_client::builder(std::time::Duration::from_secs(3))
         .build()
         .map_err(|e| e.to_string())?;
 
@@ -124,3 +163,286 @@ pub async fn get_app_reviews(app_id: String) -> Result<Vec<Review>, String> {
     }
     Ok(reviews)
 }
+
+/// Anonymous reviewer id derived from the local reviewer identity, so reviews and votes from
+/// this user stay linked without identifying the person or the machine.
+fn user_hash(app: &AppHandle) -> Result<String, String> {
+    crate::commands::reviews::identity_key(app).map(|key| hash_identity(&key))
+}
+
+fn hash_identity(key: &str) -> String {
+    let digest = Sha1::digest(format!("monarch-odrs[{}]", key).as_bytes());
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn publish_enabled(app: &AppHandle) -> bool {
+    app.store(STORE_FILE)
+        .ok()
+        .and_then(|s| s.get(PUBLISH_KEY))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+#[tauri::command]
+pub async fn is_odrs_publish_enabled(app: AppHandle) -> Result<bool, String> {
+    Ok(publish_enabled(&app))
+}
+
+/// Opt in or out of sharing new reviews with ODRS.
+#[tauri::command]
+pub async fn set_odrs_publish_enabled(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    store.set(PUBLISH_KEY, serde_json::json!(enabled));
+    store.save().map_err(|e| e.to_string())
+}
+
+fn check_vote(vote: &str) -> Result<(), String> {
+    if matches!(vote, "upvote" | "downvote" | "dismiss" | "report") {
+        Ok(())
+    } else {
+        Err(format!("Unknown review vote '{}'", vote))
+    }
+}
+
+fn odrs_locale() -> String {
+    std::env::var("LANG")
+        .ok()
+        .and_then(|l| l.split('.').next().map(str::to_string))
+        .filter(|l| !l.is_empty())
+        .unwrap_or_else(|| "C".to_string())
+}
+
+/// ODRS answers writes with `{"success": bool, "msg": ...}`, often with a 400 on failure.
+#[derive(Debug, Deserialize)]
+struct OdrsStatus {
+    #[serde(default)]
+    success: bool,
+    msg: Option<String>,
+}
+
+async fn post_odrs(endpoint: &str, payload: serde_json::Value) -> Result<(), String> {
+    let client = http_client::client(Duration::from_secs(10));
+    let url = format!("{}/{}", ODRS_API, endpoint);
+    let resp = http_client::fetch(client.post(&url).json(&payload)).await?;
+    let status: OdrsStatus = resp
+        .json()
+        .map_err(|_| format!("ODRS {} returned {}", endpoint, resp.status))?;
+    if status.success && resp.is_success() {
+        Ok(())
+    } else {
+        Err(status
+            .msg
+            .unwrap_or_else(|| format!("ODRS {} returned {}", endpoint, resp.status)))
+    }
+}
+
+/// Reviews of `app_id` as `user_hash` sees them; `limit` 0 asks for all. Every item carries
+/// the key the server issued for (user_hash, app_id), including the placeholder sent for
+/// unreviewed apps; writes are only accepted with it.
+async fn fetch_for_user(
+    app_id: &str,
+    user_hash: &str,
+    version: &str,
+    limit: u32,
+) -> Result<Vec<Review>, String> {
+    let client = http_client::client(Duration::from_secs(10));
+    let payload = serde_json::json!({
+        "app_id": app_id,
+        "user_hash": user_hash,
+        "locale": odrs_locale(),
+        "distro": crate::distro_context::DistroContext::new().pretty_name,
+        "version": version,
+        "limit": limit,
+    });
+    let resp =
+        http_client::fetch(client.post(format!("{}/fetch", ODRS_API)).json(&payload)).await?;
+    if !resp.is_success() {
+        return Err(format!("ODRS fetch returned {}", resp.status));
+    }
+    resp.json()
+}
+
+fn user_skey(reviews: &[Review], app_id: &str) -> Result<String, String> {
+    reviews
+        .iter()
+        .find_map(|r| r.user_skey.clone())
+        .ok_or_else(|| format!("ODRS did not issue a review key for {}", app_id))
+}
+
+async fn fetch_user_skey(app_id: &str, user_hash: &str, version: &str) -> Result<String, String> {
+    let reviews = fetch_for_user(app_id, user_hash, version, 1).await?;
+    user_skey(&reviews, app_id)
+}
+
+async fn publish(
+    app: &AppHandle,
+    app_id: &str,
+    stars: u32,
+    summary: &str,
+    description: &str,
+    user_display: &str,
+    version: &str,
+) -> Result<(), String> {
+    if !(1..=5).contains(&stars) {
+        return Err("Rating must be between 1 and 5 stars".to_string());
+    }
+    if summary.trim().is_empty() {
+        return Err("A review needs a summary".to_string());
+    }
+    let user_hash = user_hash(app)?;
+    let user_skey = fetch_user_skey(app_id, &user_hash, version).await?;
+    post_odrs(
+        "submit",
+        serde_json::json!({
+            "app_id": app_id,
+            "user_hash": user_hash,
+            "user_skey": user_skey,
+            "user_display": user_display,
+            "locale": odrs_locale(),
+            "distro": crate::distro_context::DistroContext::new().pretty_name,
+            "version": version,
+            "rating": stars * 20,
+            "summary": summary.trim(),
+            "description": description.trim(),
+        }),
+    )
+    .await
+}
+
+/// Publish a review to ODRS. `rating` is 1-5 stars (ODRS stores 0-100). Refused unless the
+/// user opted in with set_odrs_publish_enabled. `local_id` is the saved copy of the review,
+/// marked as published so later edits and deletes reach ODRS too.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn submit_odrs_review(
+    app: AppHandle,
+    app_id: String,
+    rating: u32,
+    summary: String,
+    description: String,
+    user_display: String,
+    version: Option<String>,
+    local_id: Option<String>,
+) -> Result<(), String> {
+    if !publish_enabled(&app) {
+        return Err("Publishing reviews to ODRS is turned off".to_string());
+    }
+    let version = version.unwrap_or_else(|| "unknown".to_string());
+    publish(
+        &app,
+        &app_id,
+        rating,
+        &summary,
+        &description,
+        &user_display,
+        &version,
+    )
+    .await?;
+    match local_id {
+        Some(id) => crate::commands::reviews::mark_published(&app, &id),
+        None => Ok(()),
+    }
+}
+
+/// Take the user's review of `app_id` off ODRS. Nothing to do when it is already gone.
+pub(crate) async fn remove_review(app: &AppHandle, app_id: &str) -> Result<(), String> {
+    let user_hash = user_hash(app)?;
+    let reviews = fetch_for_user(app_id, &user_hash, "unknown", 0).await?;
+    let Some(review_id) = own_review_id(&reviews, &user_hash) else {
+        return Ok(());
+    };
+    post_odrs(
+        "remove",
+        serde_json::json!({
+            "app_id": app_id,
+            "user_hash": user_hash,
+            "user_skey": user_skey(&reviews, app_id)?,
+            "review_id": review_id,
+        }),
+    )
+    .await
+}
+
+fn own_review_id(reviews: &[Review], user_hash: &str) -> Option<u64> {
+    reviews
+        .iter()
+        .find(|r| r.user_hash.as_deref() == Some(user_hash))
+        .and_then(|r| r.review_id)
+}
+
+/// Replace the user's ODRS review with an edited local copy. ODRS has no edit call and
+/// keeps one review per user and app, so the old one is removed first.
+pub(crate) async fn replace_review(
+    app: &AppHandle,
+    review: &crate::commands::reviews::LocalReview,
+) -> Result<(), String> {
+    remove_review(app, &review.app_id).await?;
+    publish(
+        app,
+        &review.app_id,
+        review.rating / 20,
+        &review.summary,
+        &review.description,
+        &review.user_display,
+        "unknown",
+    )
+    .await
+}
+
+/// Vote on someone else's review: "upvote", "downvote", "dismiss" (not helpful to me) or
+/// "report" (abuse).
+#[tauri::command]
+pub async fn vote_odrs_review(
+    app: AppHandle,
+    app_id: String,
+    review_id: u64,
+    vote: String,
+) -> Result<(), String> {
+    check_vote(&vote)?;
+    let user_hash = user_hash(&app)?;
+    let user_skey = fetch_user_skey(&app_id, &user_hash, "unknown").await?;
+    post_odrs(
+        &vote,
+        serde_json::json!({
+            "app_id": app_id,
+            "user_hash": user_hash,
+            "user_skey": user_skey,
+            "review_id": review_id,
+        }),
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_hash_is_stable_sha1() {
+        let a = hash_identity("0123456789abcdef");
+        assert_eq!(a.len(), 40);
+        assert_eq!(a, hash_identity("0123456789abcdef"));
+        assert_ne!(a, hash_identity("fedcba9876543210"));
+    }
+
+    #[test]
+    fn test_own_review_found_by_user_hash() {
+        let reviews: Vec<Review> = serde_json::from_str(
+            r#"[
+                {"review_id": 7, "app_id": "org.gimp.GIMP", "user_hash": "other", "user_skey": "k"},
+                {"review_id": 9, "app_id": "org.gimp.GIMP", "user_hash": "mine", "user_skey": "k"}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(own_review_id(&reviews, "mine"), Some(9));
+        assert_eq!(own_review_id(&reviews, "nobody"), None);
+        assert_eq!(user_skey(&reviews, "org.gimp.GIMP").unwrap(), "k");
+    }
+
+    #[test]
+    fn test_vote_rejects_unknown_kind() {
+        assert!(check_vote("report").is_ok());
+        let err = check_vote("delete").unwrap_err();
+        assert!(err.contains("Unknown review vote"));
+    }
+}
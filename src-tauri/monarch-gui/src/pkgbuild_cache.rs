@@ -0,0 +1,238 @@
+//! PKGBUILDs of locally built AUR packages, kept in ~/.local/share/monarch/pkgbuilds.json,
+//! so the next rebuild can show a unified diff against the current AUR HEAD.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+const CONTEXT_LINES: usize = 3;
+/// Beyond this the LCS table gets large; such PKGBUILDs are shown as a full replace.
+const MAX_DIFF_LINES: usize = 4000;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct BuiltPkgbuild {
+    hash: String,
+    content: String,
+    built_at: i64,
+}
+
+/// Typed response for get_pkgbuild_diff
+#[derive(Debug, Serialize, Clone)]
+pub struct PkgbuildDiff {
+    pub pkg_name: String,
+    pub built_hash: Option<String>, // None if never built through MonArch
+    pub built_at: Option<i64>,
+    pub current_hash: String,
+    pub changed: bool,
+    pub diff: String, // Unified diff, empty when unchanged or no local record
+}
+
+fn cache_path() -> Option<PathBuf> {
+    Some(
+        dirs::data_local_dir()?
+            .join("monarch")
+            .join("pkgbuilds.json"),
+    )
+}
+
+fn load_cache() -> HashMap<String, BuiltPkgbuild> {
+    cache_path()
+        .and_then(|p| std::fs::read_to_string(p).ok())
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+/// FNV-1a, hex. Stable across Rust releases, unlike DefaultHasher.
+fn content_hash(content: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for b in content.as_bytes() {
+        hash ^= *b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// Remember the PKGBUILD a package was just built from. Failures are logged, never fatal.
+pub(crate) fn record_built_pkgbuild(pkg_name: &str, content: &str) {
+    let Some(path) = cache_path() else {
+        return;
+    };
+    let mut cache = load_cache();
+    cache.insert(
+        pkg_name.to_string(),
+        BuiltPkgbuild {
+            hash: content_hash(content),
+            content: content.to_string(),
+            built_at: chrono::Utc::now().timestamp(),
+        },
+    );
+    let result = path
+        .parent()
+        .map(std::fs::create_dir_all)
+        .unwrap_or(Ok(()))
+        .and_then(|_| {
+            let json = serde_json::to_string(&cache).map_err(std::io::Error::other)?;
+            std::fs::write(&path, json)
+        });
+    if let Err(e) = result {
+        log::warn!("Failed to record PKGBUILD for {}: {}", pkg_name, e);
+    }
+}
+
+enum Op {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Line ops turning `old` into `new` (LCS backtrack).
+fn diff_ops(old: &[&str], new: &[&str]) -> Vec<(Op, usize, usize)> {
+    let (n, m) = (old.len(), new.len());
+    let mut ops = Vec::new();
+    if n > MAX_DIFF_LINES || m > MAX_DIFF_LINES {
+        ops.extend((0..n).map(|i| (Op::Delete, i, 0)));
+        ops.extend((0..m).map(|j| (Op::Insert, n, j)));
+        return ops;
+    }
+    // lcs[i][j] = LCS length of old[i..] and new[j..]
+    let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && old[i] == new[j] {
+            ops.push((Op::Equal, i, j));
+            i += 1;
+            j += 1;
+        } else if j < m && (i == n || lcs[i][j + 1] > lcs[i + 1][j]) {
+            ops.push((Op::Insert, i, j));
+            j += 1;
+        } else {
+            ops.push((Op::Delete, i, j));
+            i += 1;
+        }
+    }
+    ops
+}
+
+/// Unified diff (3 lines of context) between two texts.
+fn unified_diff(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let ops = diff_ops(&old_lines, &new_lines);
+
+    let changes: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, (op, _, _))| !matches!(op, Op::Equal))
+        .map(|(k, _)| k)
+        .collect();
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    let mut out = format!("--- {}\n+++ {}\n", old_label, new_label);
+    let mut idx = 0;
+    while idx < changes.len() {
+        // Grow the hunk while the next change is within 2*context of the current end
+        let start = changes[idx].saturating_sub(CONTEXT_LINES);
+        let mut last = changes[idx];
+        while idx + 1 < changes.len() && changes[idx + 1] - last <= 2 * CONTEXT_LINES {
+            idx += 1;
+            last = changes[idx];
+        }
+        let end = (last + CONTEXT_LINES + 1).min(ops.len());
+        idx += 1;
+
+        let hunk = &ops[start..end];
+        let old_count = hunk.iter().filter(|o| !matches!(o.0, Op::Insert)).count();
+        let new_count = hunk.iter().filter(|o| !matches!(o.0, Op::Delete)).count();
+        let (_, old_start, new_start) = hunk[0];
+        // Unified format: 1-based start; an empty side reports the line before it
+        let old_pos = if old_count == 0 {
+            old_start
+        } else {
+            old_start + 1
+        };
+        let new_pos = if new_count == 0 {
+            new_start
+        } else {
+            new_start + 1
+        };
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_pos, old_count, new_pos, new_count
+        ));
+        for (op, i, j) in hunk {
+            match op {
+                Op::Equal => out.push_str(&format!(" {}\n", old_lines[*i])),
+                Op::Delete => out.push_str(&format!("-{}\n", old_lines[*i])),
+                Op::Insert => out.push_str(&format!("+{}\n", new_lines[*j])),
+            }
+        }
+    }
+    out
+}
+
+/// What changed in an AUR package's PKGBUILD since MonArch last built it.
+#[tauri::command]
+pub async fn get_pkgbuild_diff(pkg_name: String) -> Result<PkgbuildDiff, String> {
+    crate::utils::validate_package_name(&pkg_name)?;
+    let current = crate::commands::package::fetch_pkgbuild(pkg_name.clone()).await?;
+    let current_hash = content_hash(&current);
+
+    let built = load_cache().remove(&pkg_name);
+    let (changed, diff) = match &built {
+        Some(b) if b.hash != current_hash => (
+            true,
+            unified_diff(
+                &b.content,
+                &current,
+                &format!("{}/PKGBUILD (built)", pkg_name),
+                &format!("{}/PKGBUILD (AUR HEAD)", pkg_name),
+            ),
+        ),
+        _ => (false, String::new()),
+    };
+
+    Ok(PkgbuildDiff {
+        pkg_name,
+        built_hash: built.as_ref().map(|b| b.hash.clone()),
+        built_at: built.map(|b| b.built_at),
+        current_hash,
+        changed,
+        diff,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_diff_hunks() {
+        let old = "pkgname=foo\npkgver=1.0\npkgrel=1\na\nb\nc\nd\ne\nf\ng\nsource=(x)\n";
+        let new = "pkgname=foo\npkgver=1.1\npkgrel=1\na\nb\nc\nd\ne\nf\ng\nsource=(y)\n";
+        let diff = unified_diff(old, new, "old", "new");
+        assert_eq!(
+            diff,
+            "--- old\n+++ new\n\
+             @@ -1,5 +1,5 @@\n pkgname=foo\n-pkgver=1.0\n+pkgver=1.1\n pkgrel=1\n a\n b\n\
+             @@ -8,4 +8,4 @@\n e\n f\n g\n-source=(x)\n+source=(y)\n"
+        );
+        assert!(unified_diff(old, old, "old", "new").is_empty());
+    }
+
+    #[test]
+    fn test_content_hash_is_stable() {
+        assert_eq!(content_hash(""), "cbf29ce484222325");
+        assert_ne!(content_hash("pkgver=1"), content_hash("pkgver=2"));
+    }
+}
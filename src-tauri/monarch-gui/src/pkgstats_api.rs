@@ -1,10 +1,31 @@
+//! pkgstats.archlinux.de: the popularity list behind the home feed, and opt-in submission of
+//! this system's package list (the same report the `pkgstats` CLI sends). Submission only
+//! runs while both the pkgstats opt-in and the telemetry toggle are on, at most weekly.
+
 use crate::models;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_store::StoreExt;
 
 const PKGSTATS_API_URL: &str = "https://pkgstats.archlinux.de/api/packages";
+const PKGSTATS_SUBMIT_URL: &str = "https://pkgstats.archlinux.de/api/submit";
+
+const STORE_FILE: &str = "settings.json";
+const ENABLED_KEY: &str = "pkgstats_enabled";
+const LAST_SUBMITTED_KEY: &str = "pkgstats_last_submitted";
+/// The pkgstats CLI's own timer is weekly; the server rate-limits more frequent reports.
+const SUBMIT_INTERVAL_SECS: i64 = 7 * 86400;
+
+/// pkgstats pages at most this many packages per request.
+const POPULARITY_PAGE: u32 = 10000;
+/// Past the top packages the scores are a fraction of a percent and don't order anything.
+const POPULARITY_MAX: u32 = 50000;
 
 #[derive(Debug, Deserialize)]
 struct PkgStatsResponse {
+    #[serde(default)]
+    total: u32,
     #[serde(rename = "packagePopularities")]
     package_popularities: Vec<PkgStatsPackage>,
 }
@@ -18,12 +39,8 @@ struct PkgStatsPackage {
 pub async fn fetch_top_packages(limit: u32) -> Result<Vec<models::Package>, String> {
     let url = format!("{}?limit={}&sort=popularity", PKGSTATS_API_URL, limit);
 
-    let response = reqwest::get(&url)
-        .await
-        .map_err(|e| e.to_string())?
-        .json::<PkgStatsResponse>()
-        .await
-        .map_err(|e| e.to_string())?;
+    let client = crate::http_client::client(std::time::Duration::from_secs(30));
+    let response: PkgStatsResponse = crate::http_client::fetch(client.get(&url)).await?.json()?;
 
     let packages = response
         .package_popularities
@@ -57,3 +74,256 @@ pub async fn fetch_top_packages(limit: u32) -> Result<Vec<models::Package>, Stri
 
     Ok(packages)
 }
+
+/// Popularity of every package pkgstats ranks (up to POPULARITY_MAX), for the catalog's
+/// "popular" sort.
+pub async fn fetch_popularity() -> Result<Vec<(String, f64)>, String> {
+    let client = crate::http_client::client(std::time::Duration::from_secs(30));
+    let mut scores = Vec::new();
+    loop {
+        let url = format!(
+            "{}?limit={}&offset={}&sort=popularity",
+            PKGSTATS_API_URL,
+            POPULARITY_PAGE,
+            scores.len()
+        );
+        let resp = crate::http_client::fetch(client.get(&url)).await?;
+        if !resp.is_success() {
+            return Err(format!("pkgstats returned {}", resp.status));
+        }
+        let page: PkgStatsResponse = resp.json()?;
+        let count = page.package_popularities.len();
+        scores.extend(
+            page.package_popularities
+                .into_iter()
+                .map(|p| (p.name, p.popularity as f64)),
+        );
+        let fetched = scores.len() as u32;
+        if count == 0 || fetched >= page.total || fetched >= POPULARITY_MAX {
+            return Ok(scores);
+        }
+    }
+}
+
+/// Submission body, format version 3.
+#[derive(Debug, Serialize, PartialEq)]
+struct Submission {
+    version: &'static str,
+    system: Architecture,
+    os: Architecture,
+    pacman: PacmanReport,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+struct Architecture {
+    architecture: String,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+struct PacmanReport {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mirror: Option<String>,
+    packages: Vec<String>,
+}
+
+/// Typed response for get_pkgstats_settings.
+#[derive(Debug, Serialize, Clone)]
+pub struct PkgstatsSettings {
+    pub enabled: bool,
+    /// Unix seconds of the last successful submission.
+    pub last_submitted: Option<i64>,
+    /// Submissions also need the telemetry toggle.
+    pub telemetry_enabled: bool,
+}
+
+/// CPU level as pkgstats names it (x86_64_v3, ...); other architectures as-is.
+fn system_architecture() -> String {
+    if std::env::consts::ARCH != "x86_64" {
+        return std::env::consts::ARCH.to_string();
+    }
+    if crate::utils::is_cpu_v4_compatible() {
+        "x86_64_v4".to_string()
+    } else if crate::utils::is_cpu_v3_compatible() {
+        "x86_64_v3".to_string()
+    } else {
+        "x86_64".to_string()
+    }
+}
+
+/// The mirror's base URL: pacman-conf prints `core`'s server with $repo/$arch expanded.
+fn mirror_base(core_server: &str, arch: &str) -> Option<String> {
+    let base = core_server.strip_suffix(&format!("core/os/{}", arch))?;
+    let scheme_ok = base.starts_with("https://") || base.starts_with("http://");
+    (scheme_ok && base.ends_with('/')).then(|| base.to_string())
+}
+
+async fn core_mirror() -> Option<String> {
+    let output = tokio::process::Command::new("pacman-conf")
+        .args(["--repo", "core", "Server"])
+        .output()
+        .await
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    mirror_base(stdout.lines().next()?.trim(), std::env::consts::ARCH)
+}
+
+/// Packages from the sync repos only: locally built and AUR packages are not reported.
+fn build_submission(
+    installed: Vec<String>,
+    foreign: &[String],
+    system_arch: String,
+    mirror: Option<String>,
+) -> Submission {
+    let mut packages: Vec<String> = installed
+        .into_iter()
+        .filter(|name| !foreign.contains(name))
+        .collect();
+    packages.sort();
+    packages.dedup();
+    Submission {
+        version: "3",
+        system: Architecture {
+            architecture: system_arch,
+        },
+        os: Architecture {
+            architecture: std::env::consts::ARCH.to_string(),
+        },
+        pacman: PacmanReport { mirror, packages },
+    }
+}
+
+fn read_settings(app: &AppHandle) -> (bool, Option<i64>) {
+    let store = app.store(STORE_FILE).ok();
+    let get = |key: &str| store.as_ref().and_then(|s| s.get(key));
+    (
+        get(ENABLED_KEY).and_then(|v| v.as_bool()).unwrap_or(false),
+        get(LAST_SUBMITTED_KEY).and_then(|v| v.as_i64()),
+    )
+}
+
+async fn telemetry_enabled(app: &AppHandle) -> bool {
+    app.state::<crate::repo_manager::RepoManager>()
+        .is_telemetry_enabled()
+        .await
+}
+
+/// Post the package list; returns how many packages were reported.
+async fn submit(app: &AppHandle) -> Result<usize, String> {
+    let (installed, foreign) = tokio::task::spawn_blocking(|| {
+        let installed: Vec<String> = crate::alpm_read::get_installed_packages_native()
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        let foreign: Vec<String> = crate::alpm_read::get_foreign_installed_packages()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        (installed, foreign)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?;
+    let system_arch = tokio::task::spawn_blocking(system_architecture)
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?;
+
+    let submission = build_submission(installed, &foreign, system_arch, core_mirror().await);
+    if submission.pacman.packages.is_empty() {
+        return Err("No packages to report".to_string());
+    }
+    let count = submission.pacman.packages.len();
+    let client = crate::http_client::client(std::time::Duration::from_secs(30));
+    let resp =
+        crate::http_client::fetch(client.post(PKGSTATS_SUBMIT_URL).json(&submission)).await?;
+    if !resp.is_success() {
+        return Err(format!("pkgstats rejected the submission: {}", resp.status));
+    }
+
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    store.set(LAST_SUBMITTED_KEY, json!(chrono::Utc::now().timestamp()));
+    store.save().map_err(|e| e.to_string())?;
+    log::info!("Submitted {} packages to pkgstats", count);
+    Ok(count)
+}
+
+/// Called on every scheduler tick; submits when opted in and the last report is a week old.
+pub(crate) async fn submit_if_due(app: &AppHandle) {
+    let (enabled, last) = read_settings(app);
+    if !enabled || !telemetry_enabled(app).await {
+        return;
+    }
+    let now = chrono::Utc::now().timestamp();
+    if last.is_some_and(|t| now - t < SUBMIT_INTERVAL_SECS) {
+        return;
+    }
+    if let Err(e) = submit(app).await {
+        log::warn!("pkgstats submission failed: {}", e);
+    }
+}
+
+#[tauri::command]
+pub async fn get_pkgstats_settings(app: AppHandle) -> Result<PkgstatsSettings, String> {
+    let (enabled, last_submitted) = read_settings(&app);
+    Ok(PkgstatsSettings {
+        enabled,
+        last_submitted,
+        telemetry_enabled: telemetry_enabled(&app).await,
+    })
+}
+
+#[tauri::command]
+pub async fn set_pkgstats_enabled(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    store.set(ENABLED_KEY, json!(enabled));
+    store.save().map_err(|e| e.to_string())
+}
+
+/// Submit now (the "Send now" button). Refused unless opted in and telemetry is on.
+#[tauri::command]
+pub async fn submit_pkgstats(app: AppHandle) -> Result<usize, String> {
+    let (enabled, _) = read_settings(&app);
+    if !enabled {
+        return Err("Turn on package statistics sharing first".to_string());
+    }
+    if !telemetry_enabled(&app).await {
+        return Err("Enable telemetry to share package statistics".to_string());
+    }
+    submit(&app).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_submission_excludes_foreign_and_strips_mirror() {
+        assert_eq!(
+            mirror_base("https://geo.mirror.pkgbuild.com/core/os/x86_64", "x86_64"),
+            Some("https://geo.mirror.pkgbuild.com/".to_string())
+        );
+        assert_eq!(
+            mirror_base("file:///srv/repo/core/os/x86_64", "x86_64"),
+            None
+        );
+        assert_eq!(
+            mirror_base("https://example.org/arch/$repo", "x86_64"),
+            None
+        );
+
+        let submission = build_submission(
+            vec![
+                "vlc".into(),
+                "pacman".into(),
+                "yay-bin".into(),
+                "linux".into(),
+            ],
+            &["yay-bin".to_string()],
+            "x86_64_v3".to_string(),
+            None,
+        );
+        assert_eq!(submission.pacman.packages, vec!["linux", "pacman", "vlc"]);
+        let body = serde_json::to_value(&submission).unwrap();
+        assert_eq!(body["version"], "3");
+        assert_eq!(body["system"]["architecture"], "x86_64_v3");
+        assert!(body["pacman"].get("mirror").is_none());
+    }
+}
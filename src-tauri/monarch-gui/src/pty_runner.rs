@@ -0,0 +1,341 @@
+//! Run a privileged command (pkexec/sudo pacman) inside a pseudo-terminal.
+//!
+//! Why a PTY: pacman only draws progress and asks questions when attached to a terminal, and
+//! the GUI cannot signal a root process directly. Writing ^C to the PTY master makes the
+//! kernel deliver SIGINT to the foreground process group regardless of its uid, so cancel
+//! behaves like Ctrl-C in a terminal (pacman releases db.lck cleanly).
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::io::{Read, Write};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+/// ^C as typed on a terminal.
+const INTERRUPT: &[u8] = b"\x03";
+/// After ^C, how long pacman gets to clean up before we fall back to SIGKILL.
+const INTERRUPT_GRACE: Duration = Duration::from_secs(10);
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+pub struct PtyOptions {
+    /// Hard limit for the whole command.
+    pub timeout: Duration,
+    /// No output at all for this long counts as hung.
+    pub idle_timeout: Duration,
+    /// Output ended in an unanswered question for this long: give up instead of deadlocking.
+    pub prompt_timeout: Duration,
+}
+
+impl Default for PtyOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30 * 60),
+            idle_timeout: Duration::from_secs(10 * 60),
+            prompt_timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct PtyOutcome {
+    pub success: bool,
+    pub lines: Vec<String>,
+    /// Set when we stopped the command (cancel, timeout, stuck prompt).
+    pub stopped: Option<String>,
+}
+
+/// Master side of the running command, so abort_installation can interrupt it.
+static ACTIVE_MASTER: Lazy<Mutex<Option<Arc<std::fs::File>>>> = Lazy::new(|| Mutex::new(None));
+static CANCEL_REQUESTED: Lazy<Mutex<bool>> = Lazy::new(|| Mutex::new(false));
+
+static ANSI_RE: LazyLock<Regex> = LazyLock::new(|| {
+    // CSI sequences, OSC sequences (BEL or ST terminated), and lone two-byte escapes
+    Regex::new(r"\x1b\[[0-9;?]*[ -/]*[@-~]|\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)|\x1b[@-Z\\-_]")
+        .expect("valid regex ANSI_RE")
+});
+
+/// Remove terminal control sequences and keep only what a `\r` redraw left visible.
+pub fn clean_line(raw: &str) -> String {
+    let stripped = ANSI_RE.replace_all(raw, "");
+    let visible = stripped
+        .trim_end_matches(['\r', '\n'])
+        .rsplit('\r')
+        .next()
+        .unwrap_or("");
+    visible
+        .chars()
+        .filter(|c| !c.is_control() || *c == '\t')
+        .collect::<String>()
+        .trim_end()
+        .to_string()
+}
+
+/// A trailing partial line that is waiting for an answer ("[Y/n]", "Enter a selection", ...).
+pub fn looks_like_prompt(partial: &str) -> bool {
+    let t = partial.trim_end();
+    let lower = t.to_lowercase();
+    t.ends_with("[Y/n]")
+        || t.ends_with("[y/N]")
+        || lower.contains("enter a selection")
+        || lower.contains("enter a number")
+        || (lower.contains("password") && t.ends_with(':'))
+        || (t.ends_with('?') && lower.starts_with("::"))
+}
+
+/// Ask the running PTY command to stop, like pressing Ctrl-C. Returns false if none is running.
+pub fn interrupt_active() -> bool {
+    let master = ACTIVE_MASTER.lock().ok().and_then(|m| m.clone());
+    match master {
+        Some(file) => {
+            if let Ok(mut c) = CANCEL_REQUESTED.lock() {
+                *c = true;
+            }
+            let _ = (&*file).write_all(INTERRUPT);
+            true
+        }
+        None => false,
+    }
+}
+
+fn open_pty() -> Result<(OwnedFd, OwnedFd), String> {
+    let mut master: libc::c_int = -1;
+    let mut slave: libc::c_int = -1;
+    // Wide enough that pacman doesn't wrap its progress lines
+    let size = libc::winsize {
+        ws_row: 40,
+        ws_col: 160,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+    let rc = unsafe {
+        libc::openpty(
+            &mut master,
+            &mut slave,
+            std::ptr::null_mut(),
+            std::ptr::null(),
+            &size,
+        )
+    };
+    if rc != 0 {
+        return Err(format!(
+            "openpty failed: {}",
+            std::io::Error::last_os_error()
+        ));
+    }
+    let (master, slave) = unsafe { (OwnedFd::from_raw_fd(master), OwnedFd::from_raw_fd(slave)) };
+
+    // No echo: a sudo password written to the master must never come back as output.
+    // ISIG stays on so ^C still becomes SIGINT.
+    unsafe {
+        let mut attrs: libc::termios = std::mem::zeroed();
+        if libc::tcgetattr(slave.as_raw_fd(), &mut attrs) == 0 {
+            attrs.c_lflag &= !(libc::ECHO | libc::ECHONL);
+            libc::tcsetattr(slave.as_raw_fd(), libc::TCSANOW, &attrs);
+        }
+    }
+    Ok((master, slave))
+}
+
+/// Run `binary args` on a fresh PTY, calling `on_line` for every cleaned output line.
+/// `stdin_data` (e.g. a sudo password line) is written once after spawn.
+pub async fn run_in_pty<F>(
+    binary: &str,
+    args: &[String],
+    stdin_data: Option<String>,
+    options: PtyOptions,
+    on_line: F,
+) -> Result<PtyOutcome, String>
+where
+    F: Fn(&str) + Send + 'static,
+{
+    let (master, slave) = open_pty()?;
+
+    let mut cmd = tokio::process::Command::new(binary);
+    cmd.args(args)
+        .env("TERM", "dumb")
+        .env("LC_ALL", "C")
+        .stdin(std::process::Stdio::from(
+            slave.try_clone().map_err(|e| e.to_string())?,
+        ))
+        .stdout(std::process::Stdio::from(
+            slave.try_clone().map_err(|e| e.to_string())?,
+        ))
+        .stderr(std::process::Stdio::from(slave));
+    // New session with the PTY as controlling terminal, so ^C and hangup reach the whole job
+    unsafe {
+        cmd.pre_exec(|| {
+            if libc::setsid() == -1 {
+                return Err(std::io::Error::last_os_error());
+            }
+            if libc::ioctl(0, libc::TIOCSCTTY as _, 0) == -1 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to spawn {}: {}", binary, e))?;
+    // `cmd` holds the last slave copies; drop it so EOF/EIO arrives once the child exits
+    drop(cmd);
+
+    let master = Arc::new(std::fs::File::from(master));
+    if let Ok(mut active) = ACTIVE_MASTER.lock() {
+        *active = Some(master.clone());
+    }
+    if let Ok(mut c) = CANCEL_REQUESTED.lock() {
+        *c = false;
+    }
+    if let Some(data) = stdin_data {
+        let _ = (&*master).write_all(data.as_bytes());
+    }
+
+    // Reader thread: master reads block; EIO means every slave fd is closed
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Vec<u8>>();
+    let reader = master.try_clone().map_err(|e| e.to_string())?;
+    std::thread::spawn(move || {
+        let mut reader = reader;
+        let mut buf = [0u8; 4096];
+        loop {
+            match reader.read(&mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => {
+                    if tx.send(buf[..n].to_vec()).is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    });
+
+    let started = Instant::now();
+    let mut last_output = Instant::now();
+    let mut pending = String::new();
+    let mut lines = Vec::new();
+    let mut stopped: Option<String> = None;
+    let mut interrupted_at: Option<Instant> = None;
+    let mut reader_done = false;
+
+    let emit = |line: &str, lines: &mut Vec<String>| {
+        let clean = clean_line(line);
+        if !clean.is_empty() {
+            on_line(&clean);
+            lines.push(clean);
+        }
+    };
+
+    let status = loop {
+        tokio::select! {
+            chunk = rx.recv(), if !reader_done => {
+                match chunk {
+                    Some(bytes) => {
+                        last_output = Instant::now();
+                        pending.push_str(&String::from_utf8_lossy(&bytes));
+                        while let Some(pos) = pending.find('\n') {
+                            let line: String = pending.drain(..=pos).collect();
+                            emit(&line, &mut lines);
+                        }
+                    }
+                    None => reader_done = true,
+                }
+            }
+            status = child.wait() => {
+                break status.map_err(|e| e.to_string())?;
+            }
+            _ = tokio::time::sleep(POLL_INTERVAL) => {
+                let cancel = CANCEL_REQUESTED.lock().map(|c| *c).unwrap_or(false);
+                let reason = if cancel {
+                    Some("Cancelled by user".to_string())
+                } else if started.elapsed() > options.timeout {
+                    Some(format!("Timed out after {} minutes", options.timeout.as_secs() / 60))
+                } else if looks_like_prompt(&pending) && last_output.elapsed() > options.prompt_timeout {
+                    Some(format!("Stopped: waiting for input that cannot be answered ({})", clean_line(&pending)))
+                } else if last_output.elapsed() > options.idle_timeout {
+                    Some(format!("No output for {} minutes", options.idle_timeout.as_secs() / 60))
+                } else {
+                    None
+                };
+                if let (Some(reason), None) = (&reason, &stopped) {
+                    stopped = Some(reason.clone());
+                }
+                if stopped.is_some() {
+                    match interrupted_at {
+                        None => {
+                            let _ = (&*master).write_all(INTERRUPT);
+                            interrupted_at = Some(Instant::now());
+                        }
+                        Some(t) if t.elapsed() > INTERRUPT_GRACE => {
+                            // ^C was ignored; SIGKILL only works if we own the process (sudo
+                            // keeps our uid in the real id, pkexec does not)
+                            let _ = child.start_kill();
+                            match tokio::time::timeout(INTERRUPT_GRACE, child.wait()).await {
+                                Ok(status) => break status.map_err(|e| e.to_string())?,
+                                Err(_) => {
+                                    if let Ok(mut active) = ACTIVE_MASTER.lock() {
+                                        active.take();
+                                    }
+                                    return Err(format!(
+                                        "{}, but the process did not exit. Use the Repair tool if pacman stays locked.",
+                                        stopped.unwrap_or_default()
+                                    ));
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+    };
+
+    // Drain what the child wrote right before exiting (the reader ends on EIO)
+    while !reader_done {
+        match tokio::time::timeout(Duration::from_millis(500), rx.recv()).await {
+            Ok(Some(bytes)) => pending.push_str(&String::from_utf8_lossy(&bytes)),
+            _ => reader_done = true,
+        }
+    }
+    for line in pending.split('\n') {
+        emit(line, &mut lines);
+    }
+    if let Ok(mut active) = ACTIVE_MASTER.lock() {
+        active.take();
+    }
+    // ^C usually ends the child before the next poll tick notices the request
+    if stopped.is_none() && CANCEL_REQUESTED.lock().map(|c| *c).unwrap_or(false) {
+        stopped = Some("Cancelled by user".to_string());
+    }
+
+    Ok(PtyOutcome {
+        success: status.success() && stopped.is_none(),
+        lines,
+        stopped,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_line_strips_ansi_and_redraws() {
+        assert_eq!(
+            clean_line("\x1b[1m:: \x1b[0mSynchronizing package databases...\r\n"),
+            ":: Synchronizing package databases..."
+        );
+        assert_eq!(
+            clean_line(" core  10%\r core  55%\r core 100%\r\n"),
+            " core 100%"
+        );
+        assert_eq!(clean_line("\x1b]0;title\x07done"), "done");
+    }
+
+    #[test]
+    fn test_prompt_detection() {
+        assert!(looks_like_prompt(":: Proceed with installation? [Y/n] "));
+        assert!(looks_like_prompt("Enter a selection (default=all): "));
+        assert!(looks_like_prompt("[sudo] password for user: "));
+        assert!(!looks_like_prompt("(1/3) removing foo"));
+    }
+}
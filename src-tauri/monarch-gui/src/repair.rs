@@ -213,11 +213,15 @@ async fn run_privileged(
     }
 }
 
-/// App Store–style cancel: create cancel file so the helper exits, wait for it, then clear db lock.
+/// App Store–style cancel: a cancel request for the run the helper session is executing, or
+/// the cancel file for older helpers and the agent; wait for the helper to exit, then clear
+/// the db lock.
 #[tauri::command]
 pub async fn cancel_install(app: AppHandle) -> Result<(), String> {
     const CANCEL_FILE: &str = "/var/tmp/monarch-cancel";
-    std::fs::write(CANCEL_FILE, "1").map_err(|e| format!("Could not request cancel: {}", e))?;
+    if crate::helper_rpc::cancel_active().await == 0 {
+        std::fs::write(CANCEL_FILE, "1").map_err(|e| format!("Could not request cancel: {}", e))?;
+    }
     tokio::time::sleep(tokio::time::Duration::from_millis(1500)).await;
     let _ = repair_unlock_pacman(app, None).await;
     Ok(())
@@ -240,6 +244,7 @@ pub async fn repair_unlock_pacman(app: AppHandle, password: Option<String>) -> R
                     remove_lock: true,
                     ..Default::default()
                 },
+                dry_run: false,
             },
             None,
         )
@@ -689,6 +694,7 @@ pub async fn unlock_pacman_if_stale(
                 remove_lock: true,
                 ..Default::default()
             },
+            dry_run: false,
         },
         password,
     )
@@ -698,39 +704,116 @@ pub async fn unlock_pacman_if_stale(
 }
 
 /// Clear the pacman package cache on disk (/var/cache/pacman/pkg) via the Helper.
-/// `keep`: number of versions to keep per package (0 = remove all). Helper may not yet honor this.
+/// `keep`: number of versions to keep per package (0 = remove all but the installed ones).
 #[tauri::command]
 pub async fn clear_pacman_package_cache(app: AppHandle, keep: Option<u32>) -> Result<(), String> {
-    let _keep = keep.unwrap_or(0);
-    let mut rx = crate::helper_client::invoke_helper(
-        &app,
-        crate::helper_client::HelperCommand::ExecuteBatch {
-            manifest: crate::models::TransactionManifest {
-                clear_cache: true,
-                ..Default::default()
-            },
+    clean_package_cache(app, keep.unwrap_or(0), false, false, None)
+        .await
+        .map(|_| ())
+}
+
+/// paccache-style cleanup: keep the `keep` newest versions of each cached package and always
+/// the installed one; with `uninstalled_only`, installed packages keep all their files.
+/// `dry_run` only reports what would go and how much space that frees; it is computed here
+/// from the readable cache and local DB, without the helper or authorization.
+#[tauri::command]
+pub async fn clean_package_cache(
+    app: AppHandle,
+    keep: u32,
+    uninstalled_only: bool,
+    dry_run: bool,
+    password: Option<String>,
+) -> Result<monarch_types::CacheCleanReport, String> {
+    if dry_run {
+        return preview_package_cache(keep, uninstalled_only).await;
+    }
+    let _guard = crate::utils::PRIVILEGED_LOCK.lock().await;
+    clean_package_cache_locked(&app, keep, uninstalled_only, dry_run, password).await
+}
+
+/// What CleanPackageCache would remove, planned with the helper's retention rules.
+async fn preview_package_cache(
+    keep: u32,
+    uninstalled_only: bool,
+) -> Result<monarch_types::CacheCleanReport, String> {
+    use monarch_types::cache_gc;
+    tokio::task::spawn_blocking(move || {
+        let policy = cache_gc::RetentionPolicy {
+            keep,
+            keep_installed: true,
+            uninstalled_only,
+        };
+        let (removed, kept) = cache_gc::plan(
+            cache_gc::scan(std::path::Path::new(cache_gc::CACHE_PKG_DIR)),
+            &crate::state_watcher::read_local_db(),
+            policy,
+            crate::versions::compare,
+        );
+        monarch_types::CacheCleanReport {
+            dry_run: true,
+            reclaimed_bytes: removed.iter().map(|e| e.size).sum(),
+            removed: removed
+                .into_iter()
+                .map(|e| monarch_types::CachedPackageFile {
+                    name: e.name,
+                    version: e.version,
+                    path: e.path.to_string_lossy().into_owned(),
+                    size: e.size,
+                })
+                .collect(),
+            kept: kept.len(),
+        }
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))
+}
+
+/// clean_package_cache for a caller that already holds PRIVILEGED_LOCK.
+pub(crate) async fn clean_package_cache_locked(
+    app: &AppHandle,
+    keep: u32,
+    uninstalled_only: bool,
+    dry_run: bool,
+    password: Option<String>,
+) -> Result<monarch_types::CacheCleanReport, String> {
+    if dry_run {
+        return preview_package_cache(keep, uninstalled_only).await;
+    }
+    let mut report = None;
+    let result = crate::helper_client::run_helper(
+        app,
+        crate::helper_client::HelperCommand::CleanPackageCache {
+            keep,
+            uninstalled_only,
+            dry_run,
+        },
+        password,
+        |msg| {
+            if msg.event_type.as_deref() == Some("cache_clean_report") {
+                report = serde_json::from_str(&msg.message).ok();
+            } else {
+                let _ = app.emit("install-output", &msg.message);
+            }
         },
-        None,
     )
     .await?;
-    let mut last = None;
-    while let Some(msg) = rx.recv().await {
-        last = Some(msg);
-    }
-    if let Some(m) = last {
-        if m.progress == 0 && m.message.starts_with("Error:") {
-            return Err(m.message);
-        }
+    if !result.success {
+        return Err(result
+            .error
+            .unwrap_or_else(|| "Package cache cleanup failed".to_string()));
     }
-    Ok(())
+    report.ok_or_else(|| "The helper did not report the cleanup".to_string())
+}
+
+/// The native builder cache (~/.cache/monarch/build).
+pub(crate) fn build_cache_dir() -> Option<std::path::PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("monarch").join("build"))
 }
 
-/// Clear the native builder cache (~/.cache/monarch/build).
+/// Clear the native builder cache.
 #[tauri::command]
 pub async fn clear_build_cache() -> Result<(), String> {
-    if let Some(mut cache_dir) = dirs::cache_dir() {
-        cache_dir.push("monarch");
-        cache_dir.push("build");
+    if let Some(cache_dir) = build_cache_dir() {
         if cache_dir.exists() {
             std::fs::remove_dir_all(&cache_dir)
                 .map_err(|e| format!("Failed to remove build cache: {}", e))?;
@@ -1,14 +1,31 @@
 use crate::models::{Package, PackageSource};
 use flate2::read::GzDecoder;
+use once_cell::sync::Lazy;
 use reqwest::Client;
-use std::io::Read;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
 use tar::Archive;
+use tokio::io::AsyncWriteExt;
 
-// Struct removed.
+/// Repos are parsed side by side, but no more at once than there are cores: each parse holds
+/// a decompressor window (xz's can be 64 MB) on top of the packages it has read so far. This
+/// bounds the per-repo parallelism on the blocking pool, in place of a rayon pool.
+static PARSE_SLOTS: Lazy<tokio::sync::Semaphore> = Lazy::new(|| {
+    let cores = std::thread::available_parallelism().map_or(2, |n| n.get());
+    tokio::sync::Semaphore::new(cores)
+});
 
 #[async_trait::async_trait]
 pub trait RepoClient: Send + Sync {
     async fn fetch_bytes(&self, url: &str) -> Result<Vec<u8>, String>;
+
+    /// Download `url` into `dest`. Clients that can stream write it as it arrives.
+    async fn download(&self, url: &str, dest: &Path) -> Result<(), String> {
+        let data = self.fetch_bytes(url).await?;
+        tokio::fs::write(dest, data)
+            .await
+            .map_err(|e| format!("Write error: {}", e))
+    }
 }
 
 pub struct RealRepoClient {
@@ -17,9 +34,8 @@ pub struct RealRepoClient {
 
 impl RealRepoClient {
     pub fn new() -> Self {
-        let client = Client::builder()
+        let client = crate::http_client::builder(std::time::Duration::from_secs(30))
             .user_agent("Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/121.0.0.0 Safari/537.36")
-            .timeout(std::time::Duration::from_secs(30))
             .http1_only()
             .tcp_keepalive(Some(std::time::Duration::from_secs(60)))
             .pool_idle_timeout(Some(std::time::Duration::from_secs(60)))
@@ -59,6 +75,34 @@ impl RepoClient for RealRepoClient {
             Err(e) => Err(format!("Request error: {}", e)),
         }
     }
+
+    async fn download(&self, url: &str, dest: &Path) -> Result<(), String> {
+        let mut resp = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| format!("Request error: {}", e))?;
+        if !resp.status().is_success() {
+            return Err(format!("HTTP {}", resp.status()));
+        }
+        let mut file = tokio::fs::File::create(dest)
+            .await
+            .map_err(|e| format!("Write error: {}", e))?;
+        while let Some(chunk) = resp
+            .chunk()
+            .await
+            .map_err(|e| format!("Bytes error: {}", e))?
+        {
+            crate::http_client::rate_limit(chunk.len()).await;
+            file.write_all(&chunk)
+                .await
+                .map_err(|e| format!("Write error: {}", e))?;
+        }
+        file.flush()
+            .await
+            .map_err(|e| format!("Write error: {}", e))
+    }
 }
 
 pub async fn fetch_repo_packages<C: RepoClient>(
@@ -95,10 +139,7 @@ pub async fn fetch_repo_packages<C: RepoClient>(
         false
     };
 
-    let bytes = if is_fresh {
-        // Load from disk
-        std::fs::read(&cache_path).map_err(|e| e.to_string())?
-    } else {
+    if !is_fresh {
         // Download with Fallback
         let mut mirrors_to_try = vec![mirror_url.to_string()];
 
@@ -157,15 +198,15 @@ pub async fn fetch_repo_packages<C: RepoClient>(
             }
         }
 
+        // Streamed to a .part file so a failed download never replaces the last good DB
+        let part_path = cache_path.with_extension("db.part");
         let mut accumulated_errors = Vec::new();
-        let mut success_data = None;
-
-        for (i, url) in mirrors_to_try.iter().enumerate() {
-            if i > 0 {}
+        let mut downloaded = false;
 
-            match client.fetch_bytes(url).await {
-                Ok(data) => {
-                    success_data = Some(data);
+        for url in &mirrors_to_try {
+            match client.download(url, &part_path).await {
+                Ok(()) => {
+                    downloaded = true;
                     break;
                 }
                 Err(e) => {
@@ -175,150 +216,112 @@ pub async fn fetch_repo_packages<C: RepoClient>(
             }
         }
 
-        match success_data {
-            Some(data) => {
-                // Save to cache
-                let _ = std::fs::write(&cache_path, &data);
-                data
-            }
-            Option::None => {
-                // FALLBACK: Try to use stale cache if download failed
-                if cache_path.exists() {
-                    log::warn!("Network sync failed for {}. Using stale cache.", repo_name);
-                    std::fs::read(&cache_path).map_err(|e| e.to_string())?
-                } else {
-                    return Err(format!(
-                        "All mirrors failed for {}. Errors: [{}]",
-                        repo_name,
-                        accumulated_errors.join("; ")
-                    ));
-                }
+        if downloaded {
+            tokio::fs::rename(&part_path, &cache_path)
+                .await
+                .map_err(|e| e.to_string())?;
+        } else {
+            let _ = tokio::fs::remove_file(&part_path).await;
+            // FALLBACK: Try to use stale cache if download failed
+            if cache_path.exists() {
+                log::warn!("Network sync failed for {}. Using stale cache.", repo_name);
+            } else {
+                return Err(format!(
+                    "All mirrors failed for {}. Errors: [{}]",
+                    repo_name,
+                    accumulated_errors.join("; ")
+                ));
             }
         }
-    };
-
-    // Decompress bytes (bytes is Vec<u8> or Bytes)
-    let _bytes_len = bytes.len(); // Moved outside closure capture
+    }
 
     // CPU-bound parsing moved to blocking thread to avoid stalling async runtime
-    let packages = tokio::task::spawn_blocking(move || {
-        // Detect compression based on magic bytes
-        let reader: Box<dyn Read + Send> = if bytes.starts_with(&[0x1f, 0x8b]) {
-            Box::new(GzDecoder::new(&bytes[..]))
-        } else if bytes.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
-            match zstd::stream::read::Decoder::new(&bytes[..]) {
-                Ok(d) => Box::new(d),
-                Err(e) => return Err(e.to_string()),
-            }
-        } else if bytes.starts_with(&[0xfd, 0x37, 0x7a, 0x58]) {
-            Box::new(xz2::read::XzDecoder::new(&bytes[..]))
-        } else {
-            Box::new(&bytes[..])
-        };
-
-        let mut archive = Archive::new(reader);
-        let mut packages = Vec::new();
-
-        // Iterate over archive
-        // Note: archive.entries() does I/O reading the tar headers
-        let entries = archive.entries().map_err(|e| e.to_string())?;
+    let _slot = PARSE_SLOTS.acquire().await.map_err(|e| e.to_string())?;
+    tokio::task::spawn_blocking(move || parse_repo_db(&cache_path, source))
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+}
 
-        for file in entries {
-            let mut file = file.map_err(|e| e.to_string())?;
-            let path = file.path().map_err(|e| e.to_string())?.into_owned();
+/// Packages of a repo DB file, read as a stream: the file is decompressed as the tar walks
+/// it, and only the current desc entry is held in memory.
+pub fn parse_repo_db(path: &Path, source: PackageSource) -> Result<Vec<Package>, String> {
+    let file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut buffered = BufReader::new(file);
+    // Detect compression based on magic bytes
+    let magic: Vec<u8> = buffered
+        .fill_buf()
+        .map_err(|e| e.to_string())?
+        .iter()
+        .take(4)
+        .copied()
+        .collect();
+    let reader: Box<dyn Read> = if magic.starts_with(&[0x1f, 0x8b]) {
+        Box::new(GzDecoder::new(buffered))
+    } else if magic.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+        Box::new(zstd::stream::read::Decoder::with_buffer(buffered).map_err(|e| e.to_string())?)
+    } else if magic.starts_with(&[0xfd, 0x37, 0x7a, 0x58]) {
+        Box::new(xz2::read::XzDecoder::new(buffered))
+    } else {
+        Box::new(buffered)
+    };
 
-            if path.file_name().and_then(|n| n.to_str()) == Some("desc") {
-                let mut content = String::new();
-                if file.read_to_string(&mut content).is_ok() {
-                    if let Some(pkg) = parse_desc(&content, source.clone()) {
-                        packages.push(pkg);
-                    }
+    let mut archive = Archive::new(reader);
+    let mut packages = Vec::new();
+    let mut content = String::new();
+
+    // Note: archive.entries() does I/O reading the tar headers
+    let entries = archive.entries().map_err(|e| e.to_string())?;
+
+    for file in entries {
+        let file = file.map_err(|e| e.to_string())?;
+        let is_desc = file
+            .path()
+            .map_err(|e| e.to_string())?
+            .file_name()
+            .and_then(|n| n.to_str())
+            == Some("desc");
+
+        // Oversized entries are skipped unread
+        let limit = crate::repo_desc::MAX_DESC_LEN as u64;
+        if is_desc && file.size() <= limit {
+            content.clear();
+            if file.take(limit).read_to_string(&mut content).is_ok() {
+                if let Some(pkg) = parse_desc(&content, source.clone()) {
+                    packages.push(pkg);
                 }
             }
         }
-        Ok(packages)
-    })
-    .await
-    .map_err(|e| format!("Task join error: {}", e))??;
-
+    }
     Ok(packages)
 }
 
 fn parse_desc(content: &str, source: PackageSource) -> Option<Package> {
-    let mut lines = content.lines();
-
-    let mut name = None;
-    let mut version = None;
-    let mut desc = None;
-    let mut url = None;
-    let mut last_modified = None;
-    let mut license = Vec::new();
-    let mut provides: Option<Vec<String>> = None;
-
-    while let Some(line) = lines.next() {
-        match line.trim() {
-            "%NAME%" => name = lines.next().map(|s| s.to_string()),
-            "%VERSION%" => version = lines.next().map(|s| s.to_string()),
-            "%DESC%" => desc = lines.next().map(|s| s.to_string()),
-            "%URL%" => url = lines.next().map(|s| s.to_string()),
-            "%BUILDDATE%" => {
-                if let Some(s) = lines.next() {
-                    last_modified = s.parse::<i64>().ok();
-                }
-            }
-            "%LICENSE%" => {
-                for l in lines.by_ref() {
-                    if l.is_empty() {
-                        break;
-                    }
-                    license.push(l.to_string());
-                }
-            }
-            "%PROVIDES%" => {
-                let mut p_list = Vec::new();
-                for l in lines.by_ref() {
-                    if l.is_empty() {
-                        break;
-                    }
-                    // Strip version info (e.g., "sh=1.0" -> "sh")
-                    let clean_name = l.split_once('=').map(|(n, _)| n).unwrap_or(l);
-                    p_list.push(clean_name.to_string());
-                }
-                provides = Some(p_list);
-            }
-            _ => {}
-        }
-    }
-
-    if let (Some(name), Some(version)) = (name, version) {
-        Some(Package {
-            name,
-            display_name: None,
-            version,
-            description: desc.unwrap_or_default(),
-            source,
-            maintainer: None,
-            license: Some(license),
-            url,
-            last_modified, // Populated from %BUILDDATE%
-            first_submitted: None,
-            out_of_date: None,
-            keywords: None,
-            num_votes: None,
-            icon: None,
-            screenshots: None,
-            provides,
-            app_id: None,
-            is_optimized: None,
-            depends: None,
-            make_depends: None,
-            is_featured: None,
-            installed: false,
-            ..Default::default()
-        })
-    } else {
-        None
-    }
+    let entry = crate::repo_desc::parse_desc_entry(content)?;
+    Some(Package {
+        name: entry.name,
+        display_name: None,
+        version: entry.version,
+        description: entry.description.unwrap_or_default(),
+        source,
+        maintainer: None,
+        license: Some(entry.licenses),
+        url: entry.url,
+        last_modified: entry.build_date, // Populated from %BUILDDATE%
+        first_submitted: None,
+        out_of_date: None,
+        keywords: None,
+        num_votes: None,
+        icon: None,
+        screenshots: None,
+        provides: entry.provides,
+        app_id: None,
+        is_optimized: None,
+        depends: None,
+        make_depends: None,
+        is_featured: None,
+        installed: false,
+        ..Default::default()
+    })
 }
 
 // ----------------------
@@ -402,6 +405,64 @@ mod tests {
         assert!(pkgs.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_fetch_repo_packages_streams_compressed_db() {
+        let mut tar = tar::Builder::new(Vec::new());
+        for (path, body) in [
+            (
+                "vlc-git-4.0-1/desc",
+                "%NAME%\nvlc-git\n\n%VERSION%\n4.0-1\n\n%DESC%\nMedia player\n",
+            ),
+            ("vlc-git-4.0-1/files", "%FILES%\nusr/bin/vlc\n"),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(body.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            tar.append_data(&mut header, path, body.as_bytes()).unwrap();
+        }
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        std::io::Write::write_all(&mut gz, &tar.into_inner().unwrap()).unwrap();
+
+        let mock_client = MockRepoClient::new();
+        let url = "https://example.com/chaotic-aur.db";
+        mock_client.mock_response(url, gz.finish().unwrap());
+        let temp_dir = tempfile::tempdir().unwrap();
+
+        let pkgs = fetch_repo_packages(
+            &mock_client,
+            url,
+            "chaotic-aur",
+            PackageSource::chaotic(),
+            temp_dir.path(),
+            true,
+            0,
+        )
+        .await
+        .unwrap();
+        assert_eq!(pkgs.len(), 1);
+        assert_eq!(pkgs[0].name, "vlc-git");
+        assert_eq!(pkgs[0].version, "4.0-1");
+        // Downloaded into place, no partial file left behind
+        assert!(temp_dir.path().join("chaotic-aur.db").exists());
+        assert!(!temp_dir.path().join("chaotic-aur.db.part").exists());
+
+        // Offline afterwards: the saved DB is parsed again
+        mock_client.mock_error(url, "Connection Timeout");
+        let again = fetch_repo_packages(
+            &mock_client,
+            url,
+            "chaotic-aur",
+            PackageSource::chaotic(),
+            temp_dir.path(),
+            true,
+            0,
+        )
+        .await
+        .unwrap();
+        assert_eq!(again.len(), 1);
+    }
+
     #[tokio::test]
     async fn test_fetch_repo_all_mirrors_fail() {
         let mock_client = MockRepoClient::new();
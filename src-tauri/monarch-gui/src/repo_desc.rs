@@ -0,0 +1,73 @@
+//! Parser for the `desc` entries inside repo `.db` tarballs (`%NAME%`, `%VERSION%`, ...).
+//! The tarballs come from third-party mirrors, so this is std-only and panic-free; the fuzz
+//! crate (src-tauri/fuzz) includes this file directly.
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DescEntry {
+    pub name: String,
+    pub version: String,
+    pub description: Option<String>,
+    pub url: Option<String>,
+    pub build_date: Option<i64>,
+    pub licenses: Vec<String>,
+    pub provides: Option<Vec<String>>, // Names only, version constraints stripped
+}
+
+/// A `desc` file longer than this is not a real package entry.
+pub const MAX_DESC_LEN: usize = 1 << 20;
+
+/// Parse one `desc` file. None unless it has a usable %NAME% and %VERSION%.
+pub fn parse_desc_entry(content: &str) -> Option<DescEntry> {
+    if content.len() > MAX_DESC_LEN {
+        return None;
+    }
+    let mut lines = content.lines();
+    let mut entry = DescEntry::default();
+
+    while let Some(line) = lines.next() {
+        match line.trim() {
+            "%NAME%" => entry.name = lines.next().unwrap_or("").trim().to_string(),
+            "%VERSION%" => entry.version = lines.next().unwrap_or("").trim().to_string(),
+            "%DESC%" => entry.description = lines.next().map(|s| s.to_string()),
+            "%URL%" => entry.url = lines.next().map(|s| s.to_string()),
+            "%BUILDDATE%" => {
+                if let Some(s) = lines.next() {
+                    entry.build_date = s.trim().parse::<i64>().ok();
+                }
+            }
+            "%LICENSE%" => {
+                for l in lines.by_ref() {
+                    if l.is_empty() {
+                        break;
+                    }
+                    entry.licenses.push(l.to_string());
+                }
+            }
+            "%PROVIDES%" => {
+                let mut p_list = Vec::new();
+                for l in lines.by_ref() {
+                    if l.is_empty() {
+                        break;
+                    }
+                    // Strip version info (e.g., "sh=1.0" -> "sh")
+                    let clean_name = l.split_once('=').map(|(n, _)| n).unwrap_or(l);
+                    p_list.push(clean_name.to_string());
+                }
+                entry.provides = Some(p_list);
+            }
+            _ => {}
+        }
+    }
+
+    // Names end up in paths and helper commands; don't let a hostile mirror smuggle one in
+    let name_ok = !entry.name.is_empty()
+        && !entry.name.starts_with(['.', '-'])
+        && entry
+            .name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "@._+-".contains(c));
+    if !name_ok || entry.version.is_empty() {
+        return None;
+    }
+    Some(entry)
+}
@@ -31,6 +31,19 @@ struct StoredConfig {
     /// Sync repositories when the app starts (default on); when off, no trigger_repo_sync on startup.
     #[serde(default = "default_sync_on_startup")]
     sync_on_startup_enabled: bool,
+    /// Browse/search license filter: "all" | "hide_proprietary" | "foss_only".
+    #[serde(default = "default_license_policy")]
+    license_policy: String,
+    /// Keep running in the tray when the main window is closed.
+    #[serde(default)]
+    run_in_background: bool,
+    /// Parental controls: hide apps whose OARS rating needs an older age; None = off.
+    #[serde(default)]
+    max_content_age: Option<u8>,
+}
+
+fn default_license_policy() -> String {
+    crate::license::POLICY_ALL.to_string()
 }
 
 fn default_sync_on_startup() -> bool {
@@ -41,10 +54,19 @@ fn default_notifications_enabled() -> bool {
     true // Default to enabled
 }
 
+/// One consistent view of the repo caches. Readers take an `Arc` of the whole index, so a
+/// running sync builds the next one on the side and never exposes a half-updated mix.
+#[derive(Default)]
+pub struct RepoIndex {
+    /// Bumped on every swap; search results carry it so the UI can tell data refreshed.
+    pub generation: u64,
+    pub repos: HashMap<String, Arc<Vec<Package>>>,
+}
+
 #[derive(Clone)]
 pub struct RepoManager {
-    // Map RepoName -> List of Packages
-    pub cache: Arc<RwLock<HashMap<String, Vec<Package>>>>,
+    // Current index (RepoName -> List of Packages), swapped atomically
+    cache: Arc<RwLock<Arc<RepoIndex>>>,
     repos: Arc<RwLock<Vec<RepoConfig>>>,
     pub aur_enabled: Arc<RwLock<bool>>,
     pub one_click_enabled: Arc<RwLock<bool>>,
@@ -52,6 +74,48 @@ pub struct RepoManager {
     pub telemetry_enabled: Arc<RwLock<bool>>,
     pub notifications_enabled: Arc<RwLock<bool>>,
     pub sync_on_startup_enabled: Arc<RwLock<bool>>,
+    pub license_policy: Arc<RwLock<String>>,
+    pub run_in_background: Arc<RwLock<bool>>,
+    pub max_content_age: Arc<RwLock<Option<u8>>>,
+}
+
+/// Repos that carry builds for a raised x86-64 level or a specific CPU (cachyos-v3,
+/// cachyos-core-v4, cachyos-extra-znver4, ...).
+pub fn is_optimized_repo(repo_name: &str) -> bool {
+    repo_name.contains("-v3") || repo_name.contains("-v4") || repo_name.contains("-znver4")
+}
+
+/// The CPU level an optimized repo builds for.
+pub fn optimization_level(repo_name: &str) -> Option<&'static str> {
+    if repo_name.contains("-znver4") {
+        Some("znver4")
+    } else if repo_name.contains("-v4") {
+        Some("v4")
+    } else if repo_name.contains("-v3") {
+        Some("v3")
+    } else {
+        None
+    }
+}
+
+/// Preference among optimized levels: Zen 4 over x86-64-v4 over x86-64-v3.
+pub fn optimization_rank(level: &str) -> u8 {
+    match level {
+        "znver4" => 3,
+        "v4" => 2,
+        "v3" => 1,
+        _ => 0,
+    }
+}
+
+/// Whether this machine's CPU can run builds for `level`.
+pub fn cpu_supports_level(level: &str) -> bool {
+    match level {
+        "znver4" => crate::utils::is_cpu_znver4_compatible(),
+        "v4" => crate::utils::is_cpu_v4_compatible(),
+        "v3" => crate::utils::is_cpu_v3_compatible(),
+        _ => false,
+    }
 }
 
 // Helper for Intelligent Priority Sorting (Granular Optimization Ranking)
@@ -169,6 +233,9 @@ impl RepoManager {
         let mut initial_telemetry = false;
         let mut initial_notifications = true; // Default to enabled
         let mut initial_sync_on_startup = true;
+        let mut initial_license_policy = default_license_policy();
+        let mut initial_run_in_background = false;
+        let mut initial_max_content_age = None;
 
         let config_file = config_path.join("repos.json");
 
@@ -182,6 +249,9 @@ impl RepoManager {
                     initial_telemetry = saved_config.telemetry_enabled;
                     initial_notifications = saved_config.notifications_enabled;
                     initial_sync_on_startup = saved_config.sync_on_startup_enabled;
+                    initial_license_policy = saved_config.license_policy;
+                    initial_run_in_background = saved_config.run_in_background;
+                    initial_max_content_age = saved_config.max_content_age;
 
                     // Merge saved repo enabled states
                     for saved_repo in saved_config.repos {
@@ -199,7 +269,7 @@ impl RepoManager {
         }
 
         Self {
-            cache: Arc::new(RwLock::new(HashMap::new())),
+            cache: Arc::new(RwLock::new(Arc::new(RepoIndex::default()))),
             repos: Arc::new(RwLock::new(initial_repos)),
             aur_enabled: Arc::new(RwLock::new(initial_aur)),
             one_click_enabled: Arc::new(RwLock::new(initial_one_click)),
@@ -207,29 +277,31 @@ impl RepoManager {
             telemetry_enabled: Arc::new(RwLock::new(initial_telemetry)),
             notifications_enabled: Arc::new(RwLock::new(initial_notifications)),
             sync_on_startup_enabled: Arc::new(RwLock::new(initial_sync_on_startup)),
+            license_policy: Arc::new(RwLock::new(initial_license_policy)),
+            run_in_background: Arc::new(RwLock::new(initial_run_in_background)),
+            max_content_age: Arc::new(RwLock::new(initial_max_content_age)),
+        }
+    }
+
+    async fn stored_config(&self) -> StoredConfig {
+        StoredConfig {
+            repos: self.repos.read().await.clone(),
+            aur_enabled: *self.aur_enabled.read().await,
+            one_click_enabled: *self.one_click_enabled.read().await,
+            advanced_mode: *self.advanced_mode.read().await,
+            telemetry_enabled: *self.telemetry_enabled.read().await,
+            notifications_enabled: *self.notifications_enabled.read().await,
+            sync_on_startup_enabled: *self.sync_on_startup_enabled.read().await,
+            license_policy: self.license_policy.read().await.clone(),
+            run_in_background: *self.run_in_background.read().await,
+            max_content_age: *self.max_content_age.read().await,
         }
     }
 
     async fn save_config_async(&self) {
-        let repos = self.repos.read().await.clone();
-        let aur = *self.aur_enabled.read().await;
-        let one_click = *self.one_click_enabled.read().await;
-        let advanced = *self.advanced_mode.read().await;
-        let telemetry = *self.telemetry_enabled.read().await;
-        let notifications = *self.notifications_enabled.read().await;
-        let sync_on_startup = *self.sync_on_startup_enabled.read().await;
+        let config = self.stored_config().await;
 
         tokio::task::spawn_blocking(move || {
-            let config = StoredConfig {
-                repos,
-                aur_enabled: aur,
-                one_click_enabled: one_click,
-                advanced_mode: advanced,
-                telemetry_enabled: telemetry,
-                notifications_enabled: notifications,
-                sync_on_startup_enabled: sync_on_startup,
-            };
-
             let config_path = dirs::config_dir()
                 .unwrap_or_else(|| std::path::PathBuf::from("."))
                 .join("monarch-store");
@@ -242,6 +314,60 @@ impl RepoManager {
         });
     }
 
+    /// Current repos.json contents, for a settings backup.
+    pub async fn export_config(&self) -> Result<Vec<u8>, String> {
+        serde_json::to_vec_pretty(&self.stored_config().await).map_err(|e| e.to_string())
+    }
+
+    /// Restore a backed-up repos.json: the toggles, and the enabled state of the repos this
+    /// machine also has (official repos stay enabled). Repo states go through
+    /// set_repo_family_state, so CPU-gated repos only come back where this CPU runs them;
+    /// the caller applies the result with one apply_os_config. Returns how many repos
+    /// matched.
+    pub async fn import_config(
+        &self,
+        app: &tauri::AppHandle,
+        data: &[u8],
+    ) -> Result<usize, String> {
+        let saved: StoredConfig = serde_json::from_slice(data)
+            .map_err(|e| format!("Repository settings are malformed: {}", e))?;
+        *self.aur_enabled.write().await = saved.aur_enabled;
+        *self.one_click_enabled.write().await = saved.one_click_enabled;
+        *self.advanced_mode.write().await = saved.advanced_mode;
+        *self.telemetry_enabled.write().await = saved.telemetry_enabled;
+        *self.notifications_enabled.write().await = saved.notifications_enabled;
+        *self.sync_on_startup_enabled.write().await = saved.sync_on_startup_enabled;
+        *self.license_policy.write().await = saved.license_policy;
+        *self.run_in_background.write().await = saved.run_in_background;
+        *self.max_content_age.write().await = saved.max_content_age;
+
+        // Same firewall as set_repo_state
+        let chaotic_blocked = !saved.advanced_mode
+            && matches!(
+                crate::distro_context::get_distro_context()
+                    .capabilities
+                    .chaotic_aur_support,
+                crate::distro_context::ChaoticSupport::Blocked
+            );
+        let current = self.get_all_repos().await;
+        let mut matched = 0;
+        for saved_repo in saved.repos {
+            let Some(r) = current.iter().find(|r| r.name == saved_repo.name) else {
+                continue;
+            };
+            matched += 1;
+            if r.source == PackageSource::official()
+                || (saved_repo.enabled && r.name == "chaotic-aur" && chaotic_blocked)
+            {
+                continue;
+            }
+            self.set_repo_family_state(app, &r.name, saved_repo.enabled, true)
+                .await?;
+        }
+        self.save_config_async().await;
+        Ok(matched)
+    }
+
     pub async fn set_aur_enabled(&self, _app: &tauri::AppHandle, enabled: bool) {
         let mut w = self.aur_enabled.write().await;
         *w = enabled;
@@ -297,6 +423,39 @@ impl RepoManager {
         *self.sync_on_startup_enabled.read().await
     }
 
+    pub async fn set_license_policy(&self, policy: &str) {
+        let mut w = self.license_policy.write().await;
+        *w = policy.to_string();
+        drop(w);
+        self.save_config_async().await;
+    }
+
+    pub async fn get_license_policy(&self) -> String {
+        self.license_policy.read().await.clone()
+    }
+
+    pub async fn set_max_content_age(&self, max_age: Option<u8>) {
+        let mut w = self.max_content_age.write().await;
+        *w = max_age;
+        drop(w);
+        self.save_config_async().await;
+    }
+
+    pub async fn get_max_content_age(&self) -> Option<u8> {
+        *self.max_content_age.read().await
+    }
+
+    pub async fn set_run_in_background(&self, enabled: bool) {
+        let mut w = self.run_in_background.write().await;
+        *w = enabled;
+        drop(w);
+        self.save_config_async().await;
+    }
+
+    pub async fn is_run_in_background(&self) -> bool {
+        *self.run_in_background.read().await
+    }
+
     pub async fn set_notifications_enabled(&self, enabled: bool) {
         let mut w = self.notifications_enabled.write().await;
         *w = enabled;
@@ -317,6 +476,47 @@ impl RepoManager {
         self.repos.read().await.clone()
     }
 
+    /// Repos to hand the helper for a transaction: every enabled repo plus the system
+    /// repos, so dependencies resolve from core/extra even if the UI state is stale.
+    pub async fn transaction_repos(&self) -> Vec<String> {
+        let mut repos: Vec<String> = self
+            .repos
+            .read()
+            .await
+            .iter()
+            .filter(|r| r.enabled)
+            .map(|r| r.name.clone())
+            .collect();
+        for sys in ["core", "extra", "community", "multilib"] {
+            if !repos.iter().any(|r| r == sys) {
+                repos.push(sys.to_string());
+            }
+        }
+        repos
+    }
+
+    /// The current index. Cheap; hold it for as long as one query needs a stable view.
+    pub async fn snapshot(&self) -> Arc<RepoIndex> {
+        self.cache.read().await.clone()
+    }
+
+    pub async fn index_generation(&self) -> u64 {
+        self.cache.read().await.generation
+    }
+
+    /// Build the next index from the current one and publish it in a single swap.
+    async fn swap_index(
+        &self,
+        update: impl FnOnce(&mut HashMap<String, Arc<Vec<Package>>>),
+    ) -> u64 {
+        let mut current = self.cache.write().await;
+        let mut repos = current.repos.clone();
+        update(&mut repos);
+        let generation = current.generation + 1;
+        *current = Arc::new(RepoIndex { generation, repos });
+        generation
+    }
+
     pub async fn load_initial_cache(&self) {
         let repos = self.repos.read().await;
         // Only load enabled or required repos
@@ -345,29 +545,31 @@ impl RepoManager {
                 if !path.exists() {
                     return None;
                 }
-                match std::fs::read(&path) {
-                    Ok(_) => {
-                        let client = crate::repo_db::RealRepoClient::new();
-                        match crate::repo_db::fetch_repo_packages(
-                            &client, &r.url, &r.name, r.source, &c_dir, false, 999999,
-                        )
-                        .await
-                        {
-                            Ok(pkgs) => Some((r.name, pkgs)),
-                            Err(_) => None,
-                        }
-                    }
+                // A fresh-enough cache is parsed straight from disk, never downloaded here
+                let client = crate::repo_db::RealRepoClient::new();
+                match crate::repo_db::fetch_repo_packages(
+                    &client, &r.url, &r.name, r.source, &c_dir, false, 999999,
+                )
+                .await
+                {
+                    Ok(pkgs) => Some((r.name, pkgs)),
                     Err(_) => None,
                 }
             }));
         }
 
+        let mut loaded = Vec::new();
         for handle in handles {
-            if let Ok(Some((name, pkgs))) = handle.await {
-                let mut cache = self.cache.write().await;
-                cache.insert(name, pkgs);
+            if let Ok(Some(repo)) = handle.await {
+                loaded.push(repo);
             }
         }
+        self.swap_index(|repos| {
+            for (name, pkgs) in loaded {
+                repos.insert(name, Arc::new(pkgs));
+            }
+        })
+        .await;
     }
 
     pub async fn sync_all(
@@ -462,28 +664,34 @@ impl RepoManager {
             }));
         }
 
-        let mut results = Vec::new();
+        // Searches keep using the previous index until every repo is in, then see all of it
+        let mut synced = Vec::new();
         for handle in handles {
-            match handle.await {
-                Ok(Ok((name, pkgs))) => {
-                    let mut cache = self.cache.write().await;
-                    let val = if pkgs.len() > 0 { pkgs } else { Vec::new() };
-                    cache.insert(name.clone(), val);
-                    results.push(format!("Synced {} from {}", 0, name)); // Simplified logging
-                }
-                _ => {}
+            if let Ok(Ok(repo)) = handle.await {
+                synced.push(repo);
             }
         }
+        let generation = self
+            .swap_index(|repos| {
+                for (name, pkgs) in synced {
+                    repos.insert(name, Arc::new(pkgs));
+                }
+            })
+            .await;
+        if let Some(ref a) = app {
+            let _ = a.emit("repo-index-updated", generation);
+        }
         Ok("Sync Complete".to_string())
     }
 
     /// Search for packages in the local cache matching the query string.
     /// This uses regex for case-insensitive partial matching on name and description.
+    /// Returns the matches together with the generation of the index they came from.
     pub async fn get_packages_matching(
         &self,
         query: &str,
         distro: &crate::distro_context::DistroContext,
-    ) -> Result<Vec<Package>, String> {
+    ) -> Result<(Vec<Package>, u64), String> {
         let query_parts: Vec<String> = query.split_whitespace().map(|s| s.to_string()).collect();
         let query_regexes: Vec<regex::Regex> = query_parts
             .iter()
@@ -495,14 +703,14 @@ impl RepoManager {
             })
             .collect();
 
+        let index = self.snapshot().await;
         if query_regexes.is_empty() {
-            return Ok(Vec::new());
+            return Ok((Vec::new(), index.generation));
         }
 
-        let cache = self.cache.read().await;
         let mut results = Vec::new();
-        for (repo_name, pkgs) in cache.iter() {
-            for pkg in pkgs {
+        for (repo_name, pkgs) in index.repos.iter() {
+            for pkg in pkgs.iter() {
                 let mut all_match = true;
                 for re in &query_regexes {
                     // Search name and description
@@ -515,11 +723,12 @@ impl RepoManager {
                 if all_match {
                     let mut p = pkg.clone();
                     p.source = PackageSource::from_repo_name(repo_name, &p.version, distro);
+                    p.is_optimized = Some(is_optimized_repo(repo_name));
                     results.push(p);
                 }
             }
         }
-        Ok(results)
+        Ok((results, index.generation))
     }
 
     // MODULAR APPLY LOGIC — pass password so one prompt covers all helper invokes
@@ -528,8 +737,15 @@ impl RepoManager {
         app: &tauri::AppHandle,
         password: Option<String>,
     ) -> Result<(), String> {
-        let repos = self.repos.read().await;
-        drop(repos);
+        // Enabled repos whose keyring is missing get it set up in the same helper run
+        let keyring_repos: Vec<String> = self
+            .repos
+            .read()
+            .await
+            .iter()
+            .filter(|r| r.enabled)
+            .map(|r| r.name.clone())
+            .collect();
         // 1. Refactor note: Traditional "Repo Injection" is deprecated.
         // We no longer modify pacman.conf or manage .conf files directly via HelperCommand::{WriteFiles, RemoveFiles}.
         // The application now uses Host Detection to respect system-provided repositories.
@@ -541,8 +757,10 @@ impl RepoManager {
             HelperCommand::ExecuteBatch {
                 manifest: crate::models::TransactionManifest {
                     refresh_db: true,
+                    keyring_repos,
                     ..Default::default()
                 },
+                dry_run: false,
             },
             password,
         )
@@ -584,8 +802,10 @@ impl RepoManager {
 
         // Instant UI Update: Clear from cache if disabled
         if !enabled {
-            let mut cache = self.cache.write().await;
-            cache.remove(name);
+            self.swap_index(|repos| {
+                repos.remove(name);
+            })
+            .await;
         }
 
         self.save_config_async().await;
@@ -643,10 +863,12 @@ impl RepoManager {
 
         // Instant UI Update: Batch clear
         if !affected_repos.is_empty() {
-            let mut cache = self.cache.write().await;
-            for name in affected_repos {
-                cache.remove(&name);
-            }
+            self.swap_index(|repos| {
+                for name in &affected_repos {
+                    repos.remove(name);
+                }
+            })
+            .await;
         }
 
         self.save_config_async().await;
@@ -676,13 +898,13 @@ impl RepoManager {
                 .map(|r| r.name.clone())
                 .collect()
         };
-        let cache = self.cache.read().await;
+        let index = self.snapshot().await;
         let mut results: Vec<(Package, u8, String)> = Vec::new();
         let cpu_v3 = crate::utils::is_cpu_v3_compatible();
         let cpu_v4 = crate::utils::is_cpu_v4_compatible();
         let distro = crate::distro_context::get_distro_context();
 
-        for (repo_name, pkgs) in cache.iter() {
+        for (repo_name, pkgs) in index.repos.iter() {
             if !enabled.contains(repo_name) {
                 continue;
             }
@@ -709,7 +931,10 @@ impl RepoManager {
         results.sort_by(|(pkg_a, level_a, _), (pkg_b, level_b, _)| {
             let rank_a = calculate_package_rank(pkg_a, *level_a, &distro);
             let rank_b = calculate_package_rank(pkg_b, *level_b, &distro);
-            rank_a.cmp(&rank_b)
+            // Same tier (e.g. two third-party repos): prefer the newer build
+            rank_a
+                .cmp(&rank_b)
+                .then_with(|| crate::versions::newest_first(&pkg_a.version, &pkg_b.version))
         });
 
         results
@@ -721,6 +946,32 @@ impl RepoManager {
             .collect()
     }
 
+    /// CPU-optimized builds of `name` in the enabled repos: the ones this CPU runs first,
+    /// best level first, so the first supported entry is the build to offer.
+    pub async fn get_optimized_variants(&self, name: &str) -> Vec<crate::models::OptimizedVariant> {
+        let mut variants: Vec<crate::models::OptimizedVariant> = self
+            .get_all_packages_with_repos(name)
+            .await
+            .into_iter()
+            .filter_map(|(p, repo)| {
+                let level = optimization_level(&repo)?;
+                Some(crate::models::OptimizedVariant {
+                    supported: cpu_supports_level(level),
+                    level: level.to_string(),
+                    version: p.version,
+                    repo,
+                })
+            })
+            .collect();
+        variants.sort_by(|a, b| {
+            b.supported
+                .cmp(&a.supported)
+                .then_with(|| optimization_rank(&b.level).cmp(&optimization_rank(&a.level)))
+                .then_with(|| a.repo.cmp(&b.repo))
+        });
+        variants
+    }
+
     #[allow(dead_code)]
     pub async fn get_all_packages(&self, name: &str) -> Vec<Package> {
         self.get_all_packages_with_repos(name)
@@ -750,13 +1001,13 @@ impl RepoManager {
                 .map(|r| r.name.clone())
                 .collect()
         };
-        let cache = self.cache.read().await;
+        let index = self.snapshot().await;
 
-        for (repo_name, repo_pkgs) in cache.iter() {
+        for (repo_name, repo_pkgs) in index.repos.iter() {
             if !enabled.contains(repo_name) {
                 continue;
             }
-            for pkg in repo_pkgs {
+            for pkg in repo_pkgs.iter() {
                 if let Some(provides) = &pkg.provides {
                     if provides.iter().any(|p| p == name) {
                         results.push((pkg.clone(), repo_name.clone()));
@@ -780,11 +1031,11 @@ impl RepoManager {
                 .map(|r| r.name.clone())
                 .collect()
         };
-        let cache = self.cache.read().await;
+        let index = self.snapshot().await;
         let names_set: std::collections::HashSet<&str> = names.iter().map(|s| s.as_str()).collect();
-        for (repo_name, pkgs) in cache.iter() {
+        for (repo_name, pkgs) in index.repos.iter() {
             if enabled.contains(repo_name) {
-                for pkg in pkgs {
+                for pkg in pkgs.iter() {
                     if names_set.contains(pkg.name.as_str()) {
                         results.push(pkg.clone());
                     }
@@ -795,8 +1046,9 @@ impl RepoManager {
     }
 
     pub async fn get_package_counts(&self) -> HashMap<String, usize> {
-        let cache = self.cache.read().await;
-        cache
+        self.snapshot()
+            .await
+            .repos
             .iter()
             .map(|(name, pkgs)| (name.clone(), pkgs.len()))
             .collect()
@@ -870,6 +1122,89 @@ mod tests {
             calculate_package_rank(&p_cachy, 1, &distro)
                 < calculate_package_rank(&p_cachy, 0, &distro)
         );
+
+        // The picker's "best" build ranks by level, not by repo order
+        let mut levels: Vec<&str> = ["cachyos-v3", "cachyos-extra-znver4", "cachyos-v4"]
+            .iter()
+            .filter_map(|r| optimization_level(r))
+            .collect();
+        levels.sort_by_key(|l| std::cmp::Reverse(optimization_rank(l)));
+        assert_eq!(levels, vec!["znver4", "v4", "v3"]);
+    }
+
+    #[tokio::test]
+    async fn test_index_swap_keeps_snapshots_consistent() {
+        let manager = RepoManager::new();
+        let start = manager.index_generation().await;
+        let pkg = |name: &str| Package {
+            name: name.to_string(),
+            ..make_test_pkg(PackageSource::official())
+        };
+
+        manager
+            .swap_index(|repos| {
+                repos.insert("core".into(), Arc::new(vec![pkg("old-core")]));
+                repos.insert("extra".into(), Arc::new(vec![pkg("old-extra")]));
+            })
+            .await;
+        let before = manager.snapshot().await;
+
+        // A sync publishes every repo at once; the earlier snapshot never sees part of it
+        let generation = manager
+            .swap_index(|repos| {
+                repos.insert("core".into(), Arc::new(vec![pkg("new-core")]));
+                repos.insert("extra".into(), Arc::new(vec![pkg("new-extra")]));
+            })
+            .await;
+        assert_eq!(generation, start + 2);
+        assert_eq!(before.generation, start + 1);
+        assert_eq!(before.repos["core"][0].name, "old-core");
+        assert_eq!(before.repos["extra"][0].name, "old-extra");
+
+        let distro = crate::distro_context::DistroContext::new();
+        let (found, found_generation) =
+            manager.get_packages_matching("new", &distro).await.unwrap();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found_generation, generation);
+    }
+
+    #[test]
+    fn test_repo_health_conf_and_warnings() {
+        let (server, siglevel) = parse_repo_conf(
+            "Server = https://cdn-mirror.chaotic.cx/$repo/$arch\n\
+             Server = https://geo-mirror.chaotic.cx/$repo/$arch\n\
+             SigLevel = Required\n\
+             SigLevel = DatabaseOptional\n\
+             Usage = All\n",
+        );
+        assert_eq!(
+            server.as_deref(),
+            Some("https://cdn-mirror.chaotic.cx/$repo/$arch")
+        );
+        assert_eq!(siglevel, "Required DatabaseOptional");
+
+        let now = 1_700_000_000;
+        let mut health = RepoHealth {
+            name: "chaotic-aur".to_string(),
+            last_sync: Some(now - 9 * 86400),
+            upstream_modified: Some(now - 3600),
+            mirror: server,
+            mirror_reachable: true,
+            signature: "valid".to_string(),
+            ..Default::default()
+        };
+        let warnings = health_warnings(&health, now);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains("chaotic-aur DB is 9 days old"));
+        assert!(warnings[1].contains("newer DB"));
+
+        health.last_sync = Some(now - 600);
+        health.upstream_modified = Some(now - 3600);
+        assert!(health_warnings(&health, now).is_empty());
+
+        health.mirror_reachable = false;
+        health.signature = "invalid".to_string();
+        assert_eq!(health_warnings(&health, now).len(), 2);
     }
 }
 
@@ -893,6 +1228,142 @@ pub async fn check_repo_sync_status(
     Ok(status)
 }
 
+/// Sync DBs older than this are reported as stale.
+const STALE_DB_DAYS: i64 = 7;
+/// Upstream this much newer than the local DB means the last sync didn't pick it up.
+const UPSTREAM_AHEAD_SECS: i64 = 24 * 3600;
+
+/// Typed response for get_repo_health: one entry per repo in pacman.conf.
+#[derive(Debug, Serialize, Clone, PartialEq, Default)]
+pub struct RepoHealth {
+    pub name: String,
+    /// When the local sync DB was last written (Unix seconds); None if never synced.
+    pub last_sync: Option<i64>,
+    /// Last-Modified of the DB on the first mirror.
+    pub upstream_modified: Option<i64>,
+    /// First Server for the repo, `$repo`/`$arch` expanded.
+    pub mirror: Option<String>,
+    pub mirror_reachable: bool,
+    pub package_count: usize,
+    /// SigLevel from pacman.conf, empty when the repo uses the global default.
+    pub siglevel: String,
+    /// "valid", "unsigned", "invalid" or "missing" (see alpm_read::SyncDbState).
+    pub signature: String,
+    pub warnings: Vec<String>,
+}
+
+/// First Server and the SigLevel from `pacman-conf --repo <name>` output.
+fn parse_repo_conf(output: &str) -> (Option<String>, String) {
+    let mut server = None;
+    let mut siglevel = Vec::new();
+    for (key, value) in output.lines().filter_map(|l| l.split_once('=')) {
+        let value = value.trim();
+        match key.trim() {
+            "Server" if server.is_none() && !value.is_empty() => server = Some(value.to_string()),
+            "SigLevel" if !value.is_empty() => siglevel.push(value.to_string()),
+            _ => {}
+        }
+    }
+    (server, siglevel.join(" "))
+}
+
+/// What's wrong with a repo, in words a user can act on.
+fn health_warnings(health: &RepoHealth, now: i64) -> Vec<String> {
+    let mut warnings = Vec::new();
+    match health.last_sync {
+        None => warnings.push(format!(
+            "{} has never been synced; installs from it will fail until you refresh",
+            health.name
+        )),
+        Some(synced) => {
+            let days = (now - synced) / 86400;
+            if days >= STALE_DB_DAYS {
+                warnings.push(format!(
+                    "{} DB is {} days old; packages it lists may 404 on install",
+                    health.name, days
+                ));
+            }
+            if let Some(upstream) = health.upstream_modified {
+                if upstream - synced > UPSTREAM_AHEAD_SECS {
+                    warnings.push(format!(
+                        "{} mirror has a newer DB than the local copy; refresh to pick it up",
+                        health.name
+                    ));
+                }
+            }
+        }
+    }
+    if health.mirror.is_none() {
+        warnings.push(format!("{} has no Server configured", health.name));
+    } else if !health.mirror_reachable {
+        warnings.push(format!("{} mirror is unreachable", health.name));
+    }
+    if health.signature == "invalid" {
+        warnings.push(format!(
+            "{} DB signature does not verify; check the repo's keyring",
+            health.name
+        ));
+    }
+    warnings
+}
+
+async fn probe_repo_mirror(client: &reqwest::Client, health: &mut RepoHealth) {
+    let Some(mirror) = &health.mirror else {
+        return;
+    };
+    let url = format!("{}/{}.db", mirror.trim_end_matches('/'), health.name);
+    if let Ok(resp) = crate::http_client::fetch(client.head(&url)).await {
+        health.mirror_reachable = resp.is_success();
+        health.upstream_modified = resp
+            .headers
+            .get(reqwest::header::LAST_MODIFIED)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| chrono::DateTime::parse_from_rfc2822(v).ok())
+            .map(|d| d.timestamp());
+    }
+}
+
+/// Per-repo health: local DB age, upstream Last-Modified, mirror reachability, package
+/// count and signature state. Explains most "404 on install" reports (a stale sync DB
+/// pointing at package versions the mirrors have dropped).
+#[tauri::command]
+pub async fn get_repo_health() -> Result<Vec<RepoHealth>, String> {
+    let states = tokio::task::spawn_blocking(crate::alpm_read::get_sync_db_states_native)
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?;
+
+    let mut report = Vec::with_capacity(states.len());
+    for state in states {
+        let conf = tokio::process::Command::new("pacman-conf")
+            .args(["--repo", &state.name])
+            .output()
+            .await
+            .map(|o| String::from_utf8_lossy(&o.stdout).into_owned())
+            .unwrap_or_default();
+        let (server, siglevel) = parse_repo_conf(&conf);
+        report.push(RepoHealth {
+            mirror: server.map(|s| {
+                s.replace("$repo", &state.name)
+                    .replace("$arch", std::env::consts::ARCH)
+            }),
+            siglevel,
+            last_sync: state.db_mtime,
+            package_count: state.package_count,
+            signature: state.signature,
+            name: state.name,
+            ..Default::default()
+        });
+    }
+
+    let client = crate::http_client::client(std::time::Duration::from_secs(6));
+    futures::future::join_all(report.iter_mut().map(|h| probe_repo_mirror(&client, h))).await;
+    let now = chrono::Utc::now().timestamp();
+    for health in report.iter_mut() {
+        health.warnings = health_warnings(health, now);
+    }
+    Ok(report)
+}
+
 // Trigger DB refresh to reflect system config. No repo injection; we read from ALPM.
 #[tauri::command]
 pub async fn apply_os_config(
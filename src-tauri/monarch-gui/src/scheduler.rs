@@ -0,0 +1,224 @@
+//! Background update checks. Runs the helper's CheckUpdatesSafe (plus AUR) on a user-set
+//! interval, caches the result and notifies when updates appear that weren't seen before.
+//! Updates whose update_policy is "auto" are then applied. Checks are held on low battery
+//! or a metered connection (see update_conditions). The same loop sends the opt-in
+//! pkgstats report and runs the maintenance rules when they are due.
+
+use crate::commands::package::PendingUpdate;
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+use tauri_plugin_store::StoreExt;
+
+const STORE_FILE: &str = "settings.json";
+const INTERVAL_KEY: &str = "update_check_interval_minutes";
+/// 6 hours; 0 disables scheduled checks.
+const DEFAULT_INTERVAL_MINUTES: u64 = 360;
+const MIN_INTERVAL_MINUTES: u64 = 30;
+/// Let startup (cache load, metadata init) settle before the first check.
+const STARTUP_DELAY_SECS: u64 = 120;
+/// How often the loop wakes to re-read the interval setting.
+const TICK_SECS: u64 = 60;
+
+/// Result of the most recent scheduled check.
+#[derive(Debug, Serialize, Clone)]
+pub struct ScheduledCheckResult {
+    pub checked_at: i64,
+    pub updates: Vec<PendingUpdate>,
+    pub new_count: usize, // Updates not present in the previous check
+}
+
+static LAST_RESULT: once_cell::sync::Lazy<Mutex<Option<ScheduledCheckResult>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(None));
+
+fn read_interval(app: &AppHandle) -> u64 {
+    app.store(STORE_FILE)
+        .ok()
+        .and_then(|s| s.get(INTERVAL_KEY))
+        .and_then(|v| v.as_u64())
+        .unwrap_or(DEFAULT_INTERVAL_MINUTES)
+}
+
+/// How many entries of `current` (name + target version) were not in `previous`.
+fn newly_seen(previous: &[PendingUpdate], current: &[PendingUpdate]) -> usize {
+    current
+        .iter()
+        .filter(|u| {
+            // An AUR entry behind a local build isn't worth a notification
+            !u.downgrade
+                && !previous
+                    .iter()
+                    .any(|p| p.name == u.name && p.new_version == u.new_version)
+        })
+        .count()
+}
+
+async fn run_check(app: &AppHandle) {
+    let updates = {
+        // Don't race an install/update the user started; try again next tick. Held for the
+        // whole check (it runs the helper) and released before automatic updates take it.
+        let Ok(_guard) = crate::utils::PRIVILEGED_LOCK.try_lock() else {
+            log::info!("Scheduled update check skipped: privileged operation in progress");
+            return;
+        };
+        match crate::commands::package::check_for_updates(
+            app.clone(),
+            app.state::<crate::metadata::MetadataState>(),
+        )
+        .await
+        {
+            Ok(u) => u,
+            Err(e) => {
+                log::warn!("Scheduled update check failed: {}", e);
+                return;
+            }
+        }
+    };
+
+    let previous = LAST_RESULT
+        .lock()
+        .ok()
+        .and_then(|g| g.as_ref().map(|r| r.updates.clone()))
+        .unwrap_or_default();
+    let result = ScheduledCheckResult {
+        checked_at: chrono::Utc::now().timestamp(),
+        new_count: newly_seen(&previous, &updates),
+        updates,
+    };
+    if let Ok(mut last) = LAST_RESULT.lock() {
+        *last = Some(result.clone());
+    }
+
+    let _ = app.emit("scheduled-update-check", &result);
+
+    // Same cadence: notice AUR packages a binary repo has started shipping
+    crate::commands::migration::refresh(app).await;
+
+    // Categories/packages set to "auto" are applied now; the rest wait for the user
+    crate::update_policy::apply_auto_updates(app, &result.updates).await;
+
+    if result.new_count > 0 {
+        let repo_manager = app.state::<crate::repo_manager::RepoManager>();
+        if repo_manager.is_notifications_enabled().await {
+            let _ = app
+                .notification()
+                .builder()
+                .title("MonArch: Updates Available")
+                .body(format!(
+                    "{} update(s) ready to install ({} new)",
+                    result.updates.len(),
+                    result.new_count
+                ))
+                .show();
+        }
+    }
+}
+
+/// Run a check right away (tray "Check for updates"); results go out on the usual event.
+pub(crate) async fn check_now(app: &AppHandle) {
+    run_check(app).await;
+}
+
+/// Drop cached updates for packages changed outside a check; returns the pruned result
+/// when anything was removed.
+pub(crate) fn forget_updates(names: &[String]) -> Option<ScheduledCheckResult> {
+    let mut last = LAST_RESULT.lock().ok()?;
+    let result = last.as_mut()?;
+    let before = result.updates.len();
+    result.updates.retain(|u| !names.contains(&u.name));
+    if result.updates.len() == before {
+        return None;
+    }
+    result.new_count = 0;
+    Some(result.clone())
+}
+
+/// Spawn the scheduler loop. Lives for the whole process, independent of any window.
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_secs(STARTUP_DELAY_SECS)).await;
+        let mut last_run: Option<std::time::Instant> = None;
+        let mut held: Option<String> = None;
+        loop {
+            let interval = read_interval(&app);
+            let due = interval > 0
+                && last_run
+                    .map(|t| t.elapsed().as_secs() >= interval * 60)
+                    .unwrap_or(true);
+            if due {
+                // On low battery or a metered connection, wait; retried every tick
+                match crate::update_conditions::current_hold_reason(&app).await {
+                    Some(reason) => {
+                        if held.as_ref() != Some(&reason) {
+                            log::info!("Scheduled update check deferred: {}", reason);
+                        }
+                        held = Some(reason);
+                    }
+                    None => {
+                        held = None;
+                        run_check(&app).await;
+                        last_run = Some(std::time::Instant::now());
+                    }
+                }
+            }
+            // Opt-in, weekly; independent of the update check interval
+            crate::pkgstats_api::submit_if_due(&app).await;
+            // Cleanup rules run on their own intervals
+            crate::maintenance::run_due(&app).await;
+            tokio::time::sleep(std::time::Duration::from_secs(TICK_SECS)).await;
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn get_update_check_interval(app: AppHandle) -> Result<u64, String> {
+    Ok(read_interval(&app))
+}
+
+/// Set the interval in minutes (0 = off). Values below 30 minutes are raised to 30.
+#[tauri::command]
+pub async fn set_update_check_interval(app: AppHandle, minutes: u64) -> Result<u64, String> {
+    let minutes = if minutes == 0 {
+        0
+    } else {
+        minutes.max(MIN_INTERVAL_MINUTES)
+    };
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    store.set(INTERVAL_KEY, serde_json::json!(minutes));
+    store.save().map_err(|e| e.to_string())?;
+    Ok(minutes)
+}
+
+/// Cached result of the last background check (None until the first one ran).
+#[tauri::command]
+pub async fn get_last_update_check() -> Result<Option<ScheduledCheckResult>, String> {
+    Ok(LAST_RESULT.lock().ok().and_then(|g| g.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn upd(name: &str, new_version: &str) -> PendingUpdate {
+        PendingUpdate {
+            name: name.to_string(),
+            old_version: "1".to_string(),
+            new_version: new_version.to_string(),
+            repo: "official".to_string(),
+            downgrade: false,
+        }
+    }
+
+    #[test]
+    fn test_newly_seen_counts_new_versions_only() {
+        let previous = vec![upd("firefox", "2"), upd("vlc", "3")];
+        let current = vec![upd("firefox", "2"), upd("vlc", "4"), upd("gimp", "3")];
+        assert_eq!(newly_seen(&previous, &current), 2);
+        assert_eq!(newly_seen(&current, &current), 0);
+
+        let mut older = upd("yay-git", "12.0-1");
+        older.downgrade = true;
+        assert_eq!(newly_seen(&previous, &[older]), 0);
+    }
+}
@@ -1,8 +1,9 @@
 // use log::{info, warn};
+use regex::Regex;
 use reqwest::header::USER_AGENT;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::Mutex;
+use std::sync::{LazyLock, Mutex};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScmMetadata {
@@ -35,12 +36,206 @@ struct GithubLicense {
     name: Option<String>,
 }
 
+/// One upstream release (or bare tag), as listed by get_upstream_releases.
+#[derive(Debug, Clone, Serialize)]
+pub struct UpstreamRelease {
+    pub tag: String,
+    /// The tag reduced to a pkgver ("v1.2.3" -> "1.2.3"); None when it isn't a version.
+    pub version: Option<String>,
+    pub name: Option<String>,
+    /// Release page (notes) or tag page.
+    pub url: String,
+    pub published_at: Option<String>,
+    /// Release notes (Markdown); None for bare tags.
+    pub body: Option<String>,
+    /// Marked as pre-release upstream, or the tag says alpha/beta/rc/nightly.
+    pub prerelease: bool,
+}
+
+#[derive(Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    name: Option<String>,
+    html_url: String,
+    published_at: Option<String>,
+    body: Option<String>,
+    #[serde(default)]
+    prerelease: bool,
+    #[serde(default)]
+    draft: bool,
+}
+
+#[derive(Deserialize)]
+struct GitlabRelease {
+    tag_name: String,
+    name: Option<String>,
+    released_at: Option<String>,
+    description: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TagEntry {
+    name: String,
+}
+
+/// A repository on a forge we know how to ask for releases.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScmRepo {
+    GitHub { owner: String, repo: String },
+    GitLab { path: String },
+}
+
+pub fn parse_scm_url(url: &str) -> Option<ScmRepo> {
+    let clean = url
+        .trim()
+        .trim_end_matches('/')
+        .trim_end_matches(".git")
+        .replace("http://", "https://");
+    if let Some(rest) = clean.split("github.com/").nth(1) {
+        let mut parts = rest.split('/');
+        let owner = parts.next().filter(|s| !s.is_empty())?;
+        let repo = parts.next().filter(|s| !s.is_empty())?;
+        return Some(ScmRepo::GitHub {
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+        });
+    }
+    if let Some(rest) = clean.split("gitlab.com/").nth(1) {
+        // Group paths can be nested; "/-/" starts a GitLab sub-page
+        let path = rest
+            .split("/-/")
+            .next()
+            .unwrap_or(rest)
+            .trim_end_matches('/');
+        if path.contains('/') {
+            return Some(ScmRepo::GitLab {
+                path: path.to_string(),
+            });
+        }
+    }
+    None
+}
+
+/// Reduce a release tag to a pkgver-like string: drop "v", "release-", "<pkgname>-" style
+/// prefixes and turn `_` separators into dots. None unless it then starts with a digit.
+pub fn normalize_tag(tag: &str, pkgname: &str) -> Option<String> {
+    let mut t = tag.trim().to_lowercase();
+    let base = crate::utils::strip_package_suffix(pkgname).to_lowercase();
+    for prefix in [
+        format!("{}-", base),
+        format!("{}_", base),
+        base.clone(),
+        "release-".to_string(),
+        "release_".to_string(),
+        "version-".to_string(),
+    ] {
+        if let Some(rest) = t.strip_prefix(&prefix) {
+            t = rest.to_string();
+            break;
+        }
+    }
+    let t = t.trim_start_matches('v').trim_start_matches('-');
+    if !t.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    let t = if t.contains('.') {
+        t.to_string()
+    } else {
+        t.replace('_', ".")
+    };
+    // pkgver may not contain '-'; upstream "1.2.0-rc1" is pacman's "1.2.0rc1"
+    Some(t.replace('-', ""))
+}
+
+/// Whether a tag names a pre-release. Markers must be a whole token, split at separators and
+/// letter/digit boundaries: "1.0-rc1" and "2.0beta" are, "predator-1.0" and "src-2" aren't.
+pub fn is_prerelease_tag(tag: &str) -> bool {
+    const MARKERS: [&str; 8] = [
+        "alpha", "beta", "rc", "pre", "nightly", "dev", "canary", "preview",
+    ];
+    let lower = tag.to_lowercase();
+    let mut token = String::new();
+    let mut chars = lower.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_alphanumeric() {
+            token.push(c);
+        }
+        let ends_token = match chars.peek() {
+            Some(next) => {
+                !c.is_alphanumeric()
+                    || !next.is_alphanumeric()
+                    || c.is_ascii_digit() != next.is_ascii_digit()
+            }
+            None => true,
+        };
+        if ends_token && !token.is_empty() {
+            if MARKERS.contains(&token.as_str()) {
+                return true;
+            }
+            token.clear();
+        }
+    }
+    false
+}
+
+/// A commit in an Arch package's packaging repository.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackagingCommit {
+    pub short_id: String,
+    pub title: String,
+    pub message: Option<String>,
+    pub author_name: Option<String>,
+    pub created_at: Option<String>,
+    pub web_url: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct GitlabCompare {
+    #[serde(default)]
+    commits: Vec<PackagingCommit>,
+}
+
+const ARCH_GITLAB: &str = "https://gitlab.archlinux.org";
+
+static PLUS_SUFFIX_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"([a-zA-Z0-9]+)\+([a-zA-Z]+)").expect("valid regex"));
+static INVALID_PATH_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[^a-zA-Z0-9_\-\.]").expect("valid regex"));
+static REPEATED_SEP_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[_\-]{2,}").expect("valid regex"));
+static MARKDOWN_IMAGE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"!\[.*?\]\((.*?)\)"#).expect("valid regex"));
+
+/// Project name of a pkgbase on gitlab.archlinux.org, using the same rules as devtools'
+/// pkgctl ("gtk2+extra" -> "gtk2-extra", "libc++" -> "libcplusplus", "tree" -> "unix-tree").
+pub fn arch_gitlab_project(pkgbase: &str) -> String {
+    let name = PLUS_SUFFIX_RE.replace_all(pkgbase, "$1-$2");
+    let name = name.replace('+', "plus");
+    let name = INVALID_PATH_RE.replace_all(&name, "-");
+    let name = REPEATED_SEP_RE.replace_all(&name, "-");
+    if name == "tree" {
+        "unix-tree".to_string()
+    } else {
+        name.into_owned()
+    }
+}
+
+/// Git tag Arch uses for a package version; tags can't hold the epoch's ':'.
+pub fn arch_version_tag(version: &str) -> String {
+    version.replace(':', "-")
+}
+
+/// Upstream releases are re-read at most hourly (GitHub allows 60 anonymous calls an hour).
+const RELEASES_TTL_SECS: u64 = 3600;
+/// A failed lookup (rate limit, missing repo) is remembered this long before retrying.
+const FAILED_RELEASES_TTL_SECS: u64 = 600;
+const RELEASES_PER_PAGE: u32 = 15;
+
 /// Extract image URLs from Markdown content
 fn extract_markdown_images(content: &str, base_raw_url: &str) -> Vec<String> {
-    let re = regex::Regex::new(r#"!\[.*?\]\((.*?)\)"#).unwrap();
     let mut images = Vec::new();
 
-    for cap in re.captures_iter(content) {
+    for cap in MARKDOWN_IMAGE_RE.captures_iter(content) {
         if let Some(url) = cap.get(1) {
             let url_str = url.as_str().trim();
 
@@ -88,6 +283,7 @@ fn extract_markdown_images(content: &str, base_raw_url: &str) -> Vec<String> {
 
 pub struct ScmClient {
     cache: Mutex<HashMap<String, Option<ScmMetadata>>>,
+    releases: Mutex<HashMap<String, (std::time::Instant, Result<Vec<UpstreamRelease>, String>)>>,
     client: reqwest::Client,
 }
 
@@ -101,9 +297,9 @@ impl ScmClient {
     pub fn new() -> Self {
         Self {
             cache: Mutex::new(HashMap::new()),
-            client: reqwest::Client::builder()
+            releases: Mutex::new(HashMap::new()),
+            client: crate::http_client::builder(std::time::Duration::from_secs(5))
                 .user_agent("MonARCH-Store/1.0")
-                .timeout(std::time::Duration::from_secs(5))
                 .build()
                 .unwrap_or_default(),
         }
@@ -140,6 +336,177 @@ impl ScmClient {
         if let Ok(mut cache) = self.cache.lock() {
             cache.clear();
         }
+        if let Ok(mut releases) = self.releases.lock() {
+            releases.clear();
+        }
+    }
+
+    /// Releases of the repo behind `url`, newest first as the forge lists them. Repos that
+    /// only push tags get their tags instead. `pkgname` helps strip "<name>-" tag prefixes.
+    pub async fn fetch_releases(
+        &self,
+        url: &str,
+        pkgname: &str,
+    ) -> Result<Vec<UpstreamRelease>, String> {
+        let repo = parse_scm_url(url)
+            .ok_or_else(|| format!("{} is not a GitHub or GitLab project", url))?;
+        let key = format!("{:?}", repo);
+        if let Ok(cache) = self.releases.lock() {
+            if let Some((at, releases)) = cache.get(&key) {
+                let ttl = if releases.is_ok() {
+                    RELEASES_TTL_SECS
+                } else {
+                    FAILED_RELEASES_TTL_SECS
+                };
+                if at.elapsed().as_secs() < ttl {
+                    return releases.clone();
+                }
+            }
+        }
+
+        let releases = match &repo {
+            ScmRepo::GitHub { owner, repo } => {
+                self.fetch_github_releases(owner, repo, pkgname).await
+            }
+            ScmRepo::GitLab { path } => self.fetch_gitlab_releases(path, pkgname).await,
+        };
+        if let Ok(mut cache) = self.releases.lock() {
+            cache.insert(key, (std::time::Instant::now(), releases.clone()));
+        }
+        releases
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T, String> {
+        let resp = crate::http_client::fetch(self.client.get(url)).await?;
+        if !resp.is_success() {
+            return Err(format!("{} returned {}", url, resp.status));
+        }
+        resp.json()
+    }
+
+    async fn fetch_github_releases(
+        &self,
+        owner: &str,
+        repo: &str,
+        pkgname: &str,
+    ) -> Result<Vec<UpstreamRelease>, String> {
+        let api = format!("https://api.github.com/repos/{}/{}", owner, repo);
+        let releases: Vec<GithubRelease> = self
+            .get_json(&format!("{}/releases?per_page={}", api, RELEASES_PER_PAGE))
+            .await?;
+        if !releases.is_empty() {
+            return Ok(releases
+                .into_iter()
+                .filter(|r| !r.draft)
+                .map(|r| UpstreamRelease {
+                    version: normalize_tag(&r.tag_name, pkgname),
+                    prerelease: r.prerelease || is_prerelease_tag(&r.tag_name),
+                    tag: r.tag_name,
+                    name: r.name.filter(|n| !n.is_empty()),
+                    url: r.html_url,
+                    published_at: r.published_at,
+                    body: r.body.filter(|b| !b.trim().is_empty()),
+                })
+                .collect());
+        }
+        let tags: Vec<TagEntry> = self
+            .get_json(&format!("{}/tags?per_page={}", api, RELEASES_PER_PAGE))
+            .await?;
+        Ok(tags
+            .into_iter()
+            .map(|t| UpstreamRelease {
+                version: normalize_tag(&t.name, pkgname),
+                prerelease: is_prerelease_tag(&t.name),
+                url: format!(
+                    "https://github.com/{}/{}/releases/tag/{}",
+                    owner, repo, t.name
+                ),
+                tag: t.name,
+                name: None,
+                published_at: None,
+                body: None,
+            })
+            .collect())
+    }
+
+    /// Packaging commits of an Arch pkgbase between two released versions, newest first.
+    pub async fn fetch_arch_commits(
+        &self,
+        pkgbase: &str,
+        old_version: &str,
+        new_version: &str,
+    ) -> Result<Vec<PackagingCommit>, String> {
+        let project = format!(
+            "archlinux/packaging/packages/{}",
+            arch_gitlab_project(pkgbase)
+        );
+        // The project path is one segment ('/' as %2F); tags like "1.0+r5-1" need encoding too
+        let mut url = reqwest::Url::parse(ARCH_GITLAB).map_err(|e| e.to_string())?;
+        url.path_segments_mut()
+            .map_err(|_| format!("{} can't take a path", ARCH_GITLAB))?
+            .extend(["api", "v4", "projects", &project, "repository", "compare"]);
+        url.query_pairs_mut()
+            .append_pair("from", &arch_version_tag(old_version))
+            .append_pair("to", &arch_version_tag(new_version));
+        let compare: GitlabCompare = self.get_json(url.as_str()).await?;
+        // GitLab lists compare commits oldest first
+        let mut commits = compare.commits;
+        commits.reverse();
+        for commit in &mut commits {
+            if commit.web_url.is_none() {
+                commit.web_url = Some(format!(
+                    "{}/{}/-/commit/{}",
+                    ARCH_GITLAB, project, commit.short_id
+                ));
+            }
+        }
+        Ok(commits)
+    }
+
+    async fn fetch_gitlab_releases(
+        &self,
+        path: &str,
+        pkgname: &str,
+    ) -> Result<Vec<UpstreamRelease>, String> {
+        let api = format!(
+            "https://gitlab.com/api/v4/projects/{}",
+            path.replace('/', "%2F")
+        );
+        let releases: Vec<GitlabRelease> = self
+            .get_json(&format!("{}/releases?per_page={}", api, RELEASES_PER_PAGE))
+            .await?;
+        if !releases.is_empty() {
+            return Ok(releases
+                .into_iter()
+                .map(|r| UpstreamRelease {
+                    version: normalize_tag(&r.tag_name, pkgname),
+                    prerelease: is_prerelease_tag(&r.tag_name),
+                    url: format!("https://gitlab.com/{}/-/releases/{}", path, r.tag_name),
+                    tag: r.tag_name,
+                    name: r.name.filter(|n| !n.is_empty()),
+                    published_at: r.released_at,
+                    body: r.description.filter(|b| !b.trim().is_empty()),
+                })
+                .collect());
+        }
+        let tags: Vec<TagEntry> = self
+            .get_json(&format!(
+                "{}/repository/tags?per_page={}",
+                api, RELEASES_PER_PAGE
+            ))
+            .await?;
+        Ok(tags
+            .into_iter()
+            .map(|t| UpstreamRelease {
+                version: normalize_tag(&t.name, pkgname),
+                prerelease: is_prerelease_tag(&t.name),
+                url: format!("https://gitlab.com/{}/-/tags/{}", path, t.name),
+                tag: t.name,
+                name: None,
+                published_at: None,
+                body: None,
+            })
+            .collect())
     }
 
     async fn fetch_github(&self, url: &str) -> Option<ScmMetadata> {
@@ -0,0 +1,300 @@
+//! On-demand screenshot cache in ~/.cache/monarch-store/screenshots. Images are downloaded
+//! the first time a details page asks for them; thumbnails are generated locally and served
+//! as data URIs, full-size images as paths for the asset protocol. Files are keyed by the
+//! SHA-1 of their URL and evicted least-recently-used past `MAX_CACHE_BYTES` or `MAX_AGE_SECS`.
+
+use base64::prelude::*;
+use serde::Serialize;
+use sha1::{Digest, Sha1};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime};
+
+/// Width the gallery renders thumbnails at; height follows the aspect ratio.
+const THUMBNAIL_WIDTH: u32 = 400;
+const THUMBNAIL_MAX_HEIGHT: u32 = 400;
+const MAX_CACHE_BYTES: u64 = 256 * 1024 * 1024;
+const MAX_AGE_SECS: u64 = 30 * 86400;
+/// Anything larger is not a screenshot worth keeping.
+const MAX_DOWNLOAD_BYTES: usize = 20 * 1024 * 1024;
+
+/// Suffix for in-flight writes; every download or thumbnail gets its own temp name.
+static WRITE_SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// Typed response for get_screenshot.
+#[derive(Debug, Serialize, Clone)]
+pub struct CachedScreenshot {
+    /// Absolute path inside the cache; pass to convertFileSrc for the asset protocol.
+    pub path: String,
+    /// Set for thumbnails, which are small enough to inline.
+    pub data_uri: Option<String>,
+}
+
+pub fn get_screenshots_dir() -> PathBuf {
+    crate::metadata::get_cache_dir().join("screenshots")
+}
+
+fn cache_key(url: &str) -> String {
+    Sha1::digest(url.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Mark a cache hit so LRU eviction keeps it.
+pub(crate) fn touch(path: &Path) {
+    if let Ok(file) = std::fs::File::options().append(true).open(path) {
+        let _ = file.set_modified(SystemTime::now());
+    }
+}
+
+/// Unique sibling of `path` to write into before renaming over it.
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(
+        ".{}-{}.part",
+        std::process::id(),
+        WRITE_SEQ.fetch_add(1, Ordering::Relaxed)
+    ));
+    path.with_file_name(name)
+}
+
+fn find_original(dir: &Path, key: &str) -> Option<PathBuf> {
+    std::fs::read_dir(dir).ok()?.flatten().find_map(|e| {
+        let name = e.file_name().to_string_lossy().into_owned();
+        (name.starts_with(key) && !name.contains(".thumb.") && !name.ends_with(".part"))
+            .then(|| e.path())
+    })
+}
+
+async fn download(url: &str, dir: &Path, key: &str) -> Result<PathBuf, String> {
+    if !url.starts_with("https://") && !url.starts_with("http://") {
+        return Err(format!("Not a remote screenshot: {}", url));
+    }
+    let client = crate::http_client::client(Duration::from_secs(30));
+    let resp = crate::http_client::fetch(client.get(url)).await?;
+    if !resp.is_success() {
+        return Err(format!("Screenshot download failed: {}", resp.status));
+    }
+    if resp.body.len() > MAX_DOWNLOAD_BYTES {
+        return Err("Screenshot is too large to cache".to_string());
+    }
+    let format = image::guess_format(&resp.body)
+        .map_err(|_| "Downloaded file is not an image".to_string())?;
+    let ext = format.extensions_str().first().copied().unwrap_or("img");
+
+    tokio::fs::create_dir_all(dir)
+        .await
+        .map_err(|e| e.to_string())?;
+    let path = dir.join(format!("{}.{}", key, ext));
+    // Write-then-rename so a reader never sees half an image. Concurrent downloads of the
+    // same URL each write their own temp file; the last rename wins with identical bytes.
+    let tmp = temp_path(&path);
+    if let Err(e) = tokio::fs::write(&tmp, &resp.body).await {
+        let _ = tokio::fs::remove_file(&tmp).await;
+        return Err(e.to_string());
+    }
+    if let Err(e) = tokio::fs::rename(&tmp, &path).await {
+        let _ = tokio::fs::remove_file(&tmp).await;
+        return Err(e.to_string());
+    }
+    Ok(path)
+}
+
+/// Cached original for `key`, bumped for LRU; None on a miss.
+fn lookup(dir: &Path, key: &str) -> Option<PathBuf> {
+    let path = find_original(dir, key)?;
+    touch(&path);
+    Some(path)
+}
+
+/// Existing thumbnail, bumped for LRU; false when it still has to be generated.
+fn lookup_thumbnail(thumb: &Path) -> bool {
+    let hit = thumb.exists();
+    if hit {
+        touch(thumb);
+    }
+    hit
+}
+
+fn make_thumbnail(original: &Path, thumb: &Path) -> Result<(), String> {
+    let img = image::open(original).map_err(|e| format!("Failed to decode screenshot: {}", e))?;
+    let small = img
+        .thumbnail(THUMBNAIL_WIDTH, THUMBNAIL_MAX_HEIGHT)
+        .to_rgb8();
+    let tmp = temp_path(thumb);
+    let written = small
+        .save_with_format(&tmp, image::ImageFormat::Jpeg)
+        .map_err(|e| format!("Failed to write thumbnail: {}", e))
+        .and_then(|_| std::fs::rename(&tmp, thumb).map_err(|e| e.to_string()));
+    if written.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    written
+}
+
+/// Cached copy of `url`, downloading it on first use. With `thumbnail` the result is a
+/// downscaled JPEG inlined as a data URI.
+pub async fn get(url: &str, thumbnail: bool) -> Result<CachedScreenshot, String> {
+    let dir = get_screenshots_dir();
+    let key = cache_key(url);
+
+    let (lookup_dir, lookup_key) = (dir.clone(), key.clone());
+    let cached = tokio::task::spawn_blocking(move || lookup(&lookup_dir, &lookup_key))
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?;
+    let original = match cached {
+        Some(path) => path,
+        None => {
+            let path = download(url, &dir, &key).await?;
+            let evict_dir = dir.clone();
+            tokio::task::spawn_blocking(move || evict(&evict_dir, MAX_CACHE_BYTES, MAX_AGE_SECS));
+            path
+        }
+    };
+
+    if !thumbnail {
+        return Ok(CachedScreenshot {
+            path: original.to_string_lossy().into_owned(),
+            data_uri: None,
+        });
+    }
+
+    let thumb = dir.join(format!("{}.thumb.jpg", key));
+    let (from, to) = (original.clone(), thumb.clone());
+    tokio::task::spawn_blocking(move || {
+        if lookup_thumbnail(&to) {
+            Ok(())
+        } else {
+            make_thumbnail(&from, &to)
+        }
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))??;
+    let bytes = tokio::fs::read(&thumb).await.map_err(|e| e.to_string())?;
+    Ok(CachedScreenshot {
+        path: thumb.to_string_lossy().into_owned(),
+        data_uri: Some(format!(
+            "data:image/jpeg;base64,{}",
+            BASE64_STANDARD.encode(&bytes)
+        )),
+    })
+}
+
+/// A cached file as seen by the eviction policy.
+#[derive(Debug, Clone)]
+pub struct CacheEntry {
+    pub path: PathBuf,
+    pub size: u64,
+    /// Seconds since last use (mtime is bumped on every hit).
+    pub age_secs: u64,
+}
+
+/// Files to delete: everything older than `max_age_secs`, then least recently used ones
+/// until the rest fits in `max_bytes`.
+pub fn plan_eviction(
+    mut entries: Vec<CacheEntry>,
+    max_bytes: u64,
+    max_age_secs: u64,
+) -> Vec<PathBuf> {
+    entries.sort_by_key(|e| std::cmp::Reverse(e.age_secs));
+    let mut total: u64 = entries.iter().map(|e| e.size).sum();
+    let mut evicted = Vec::new();
+    for entry in entries {
+        if entry.age_secs > max_age_secs || total > max_bytes {
+            total -= entry.size;
+            evicted.push(entry.path);
+        }
+    }
+    evicted
+}
+
+pub(crate) fn scan(dir: &Path) -> Vec<CacheEntry> {
+    let now = SystemTime::now();
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|e| {
+            let meta = e.metadata().ok()?;
+            if !meta.is_file() {
+                return None;
+            }
+            let age_secs = meta
+                .modified()
+                .ok()
+                .and_then(|m| now.duration_since(m).ok())
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            Some(CacheEntry {
+                path: e.path(),
+                size: meta.len(),
+                age_secs,
+            })
+        })
+        .collect()
+}
+
+pub(crate) fn evict(dir: &Path, max_bytes: u64, max_age_secs: u64) {
+    let doomed = plan_eviction(scan(dir), max_bytes, max_age_secs);
+    if !doomed.is_empty() {
+        log::info!(
+            "Evicting {} cached files from {}",
+            doomed.len(),
+            dir.display()
+        );
+    }
+    for path in doomed {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// Bytes currently used by the screenshot cache; reported by get_cache_size.
+pub fn cache_size() -> u64 {
+    scan(&get_screenshots_dir()).iter().map(|e| e.size).sum()
+}
+
+/// Drop every cached screenshot and thumbnail; part of clear_cache.
+pub fn clear() {
+    let _ = std::fs::remove_dir_all(get_screenshots_dir());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, size: u64, age_secs: u64) -> CacheEntry {
+        CacheEntry {
+            path: PathBuf::from(name),
+            size,
+            age_secs,
+        }
+    }
+
+    #[test]
+    fn test_eviction_drops_expired_then_least_recently_used() {
+        let entries = vec![
+            entry("fresh", 40, 10),
+            entry("expired", 10, 100),
+            entry("old", 40, 50),
+            entry("mid", 40, 20),
+        ];
+        // 130 bytes: the expired one goes regardless, then the oldest until <= 80 remain
+        let evicted = plan_eviction(entries, 80, 60);
+        assert_eq!(
+            evicted,
+            vec![PathBuf::from("expired"), PathBuf::from("old")]
+        );
+
+        assert!(plan_eviction(vec![entry("a", 10, 1)], 80, 60).is_empty());
+        assert_ne!(cache_key("https://a/1.png"), cache_key("https://a/2.png"));
+        assert_eq!(cache_key("https://a/1.png").len(), 40);
+
+        let original = PathBuf::from("/cache/abc.png");
+        let (a, b) = (temp_path(&original), temp_path(&original));
+        assert_ne!(a, b);
+        assert!(a.to_string_lossy().ends_with(".part"));
+        assert!(a.starts_with("/cache"));
+    }
+}
@@ -0,0 +1,555 @@
+//! Local inverted index over every synced repo package plus its AppStream name/summary, so
+//! search-as-you-type answers in milliseconds without scanning all repos or waiting on
+//! AUR/Flathub. Rebuilt whenever the repo index swaps ("repo-index-updated") or AppStream
+//! data finishes loading; queries against a stale index rebuild it first.
+
+use crate::metadata::{AppMetadata, MetadataState};
+use crate::models::{Package, PackageSource};
+use crate::repo_manager::{RepoIndex, RepoManager};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use tauri::{AppHandle, Listener, Manager};
+
+/// Per-field weight of a term; a prefix hit counts half.
+const W_NAME: u8 = 40;
+const W_APP_NAME: u8 = 30;
+const W_APP_ID: u8 = 20;
+const W_TEXT: u8 = 5;
+pub const DEFAULT_LIMIT: usize = 50;
+
+/// Optional narrowing for search_index_query and search_packages; empty means everything.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SearchFilters {
+    /// Sync repo names (or source ids such as "cachyos") to restrict results to.
+    pub repos: Vec<String>,
+    pub limit: Option<usize>,
+    /// "repo", "aur", "flatpak"; a package matches if any of its sources does.
+    pub source_types: Vec<String>,
+    pub only_installed: bool,
+    pub only_not_installed: bool,
+    /// Same rule as the FOSS-only license policy, applied to this search only.
+    pub foss_only: bool,
+    /// Only builds from -v3/-v4/-znver4 repos.
+    pub optimized_only: bool,
+}
+
+impl SearchFilters {
+    pub fn wants_source(&self, source_type: &str) -> bool {
+        self.source_types.is_empty() || self.source_types.iter().any(|s| s == source_type)
+    }
+
+    pub fn needs_installed(&self) -> bool {
+        self.only_installed || self.only_not_installed
+    }
+
+    /// Whether `retain` can drop anything (the index query then fetches past `limit`).
+    pub fn is_post_filter(&self) -> bool {
+        !self.source_types.is_empty()
+            || self.needs_installed()
+            || self.foss_only
+            || self.optimized_only
+    }
+
+    pub fn matches(&self, pkg: &Package, installed: &HashSet<String>) -> bool {
+        let sources = pkg
+            .available_sources
+            .as_deref()
+            .unwrap_or(std::slice::from_ref(&pkg.source));
+        if !self.source_types.is_empty()
+            && !sources.iter().any(|s| self.wants_source(&s.source_type))
+        {
+            return false;
+        }
+        if !self.repos.is_empty()
+            && !sources
+                .iter()
+                .any(|s| self.repos.iter().any(|r| r == &s.id))
+        {
+            return false;
+        }
+        let is_installed = installed.contains(&pkg.name);
+        if (self.only_installed && !is_installed) || (self.only_not_installed && is_installed) {
+            return false;
+        }
+        if self.foss_only && !crate::license::passes_policy(pkg, crate::license::POLICY_FOSS_ONLY) {
+            return false;
+        }
+        !self.optimized_only || pkg.is_optimized == Some(true)
+    }
+
+    /// Drop packages that don't pass (licenses must already be normalized).
+    pub fn retain(&self, packages: &mut Vec<Package>, installed: &HashSet<String>) {
+        packages.retain(|p| self.matches(p, installed));
+    }
+}
+
+/// Names of installed packages, read only when the filters ask about installed state.
+pub async fn installed_names(filters: &SearchFilters) -> HashSet<String> {
+    if !filters.needs_installed() {
+        return HashSet::new();
+    }
+    crate::installed_state::snapshot().keys().cloned().collect()
+}
+
+struct Doc {
+    repo: String,
+    pos: usize,
+    name_lower: String,
+    display_name: Option<String>,
+}
+
+#[derive(Default)]
+pub struct SearchIndex {
+    /// (repo index generation, AppStream loader revision) the index was built from.
+    built_from: Option<(u64, usize)>,
+    repos: HashMap<String, Arc<Vec<Package>>>,
+    docs: Vec<Doc>,
+    /// Term -> (doc, weight); ordered so prefix lookups are a range scan.
+    terms: BTreeMap<String, Vec<(u32, u8)>>,
+    /// Terms that occur in a name or app id, with how many docs use them; the dictionary
+    /// for "did you mean" (description words make poor corrections).
+    name_terms: Vec<(String, usize)>,
+}
+
+/// Edits tolerated when correcting a word of this length.
+fn max_typos(len: usize) -> usize {
+    match len {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    }
+}
+
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(|t| t.to_lowercase())
+}
+
+impl SearchIndex {
+    pub fn build(
+        index: &RepoIndex,
+        appstream_revision: usize,
+        appstream: impl Fn(&str) -> Option<AppMetadata>,
+    ) -> Self {
+        let mut docs = Vec::new();
+        let mut weights: HashMap<String, HashMap<u32, u8>> = HashMap::new();
+        let mut add = |term: String, doc: u32, weight: u8| {
+            let w = weights.entry(term).or_default().entry(doc).or_insert(0);
+            *w = (*w).max(weight);
+        };
+
+        for (repo, pkgs) in index.repos.iter() {
+            for (pos, pkg) in pkgs.iter().enumerate() {
+                let doc = docs.len() as u32;
+                let meta = appstream(&pkg.name);
+                let name_lower = pkg.name.to_lowercase();
+
+                add(name_lower.clone(), doc, W_NAME);
+                for t in tokenize(&pkg.name) {
+                    add(t, doc, W_NAME);
+                }
+                for t in tokenize(&pkg.description).filter(|t| t.len() > 1) {
+                    add(t, doc, W_TEXT);
+                }
+                if let Some(m) = &meta {
+                    for t in tokenize(&m.name) {
+                        add(t, doc, W_APP_NAME);
+                    }
+                    for t in tokenize(&m.app_id) {
+                        add(t, doc, W_APP_ID);
+                    }
+                    for t in m
+                        .summary
+                        .iter()
+                        .flat_map(|s| tokenize(s))
+                        .filter(|t| t.len() > 1)
+                    {
+                        add(t, doc, W_TEXT);
+                    }
+                }
+
+                docs.push(Doc {
+                    repo: repo.clone(),
+                    pos,
+                    name_lower,
+                    display_name: meta.map(|m| m.name).filter(|n| !n.is_empty()),
+                });
+            }
+        }
+
+        let name_terms = weights
+            .iter()
+            .filter(|(_, postings)| postings.values().any(|w| *w >= W_APP_ID))
+            .map(|(term, postings)| (term.clone(), postings.len()))
+            .collect();
+        let terms = weights
+            .into_iter()
+            .map(|(term, postings)| (term, postings.into_iter().collect()))
+            .collect();
+        SearchIndex {
+            built_from: Some((index.generation, appstream_revision)),
+            repos: index.repos.clone(),
+            docs,
+            terms,
+            name_terms,
+        }
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.built_from.map(|(g, _)| g).unwrap_or(0)
+    }
+
+    /// Scores for one query token: best field weight per doc, prefix matches at half weight.
+    fn token_scores(&self, token: &str) -> HashMap<u32, u32> {
+        let mut scores: HashMap<u32, u32> = HashMap::new();
+        for (term, postings) in self.terms.range(token.to_string()..) {
+            if !term.starts_with(token) {
+                break;
+            }
+            let exact = term == token;
+            for &(doc, weight) in postings {
+                let w = if exact {
+                    weight as u32
+                } else {
+                    weight as u32 / 2
+                };
+                let s = scores.entry(doc).or_insert(0);
+                *s = (*s).max(w);
+            }
+        }
+        scores
+    }
+
+    fn has_term_prefix(&self, token: &str) -> bool {
+        self.terms
+            .range(token.to_string()..)
+            .next()
+            .is_some_and(|(term, _)| term.starts_with(token))
+    }
+
+    /// Closest name-like term within `max_typos(token)` edits; ties go to the more common one.
+    fn closest_term(&self, token: &str) -> Option<&str> {
+        let limit = max_typos(token.chars().count());
+        if limit == 0 {
+            return None;
+        }
+        self.name_terms
+            .iter()
+            .filter(|(term, _)| term.len().abs_diff(token.len()) <= limit)
+            .map(|(term, docs)| (strsim::damerau_levenshtein(token, term), *docs, term))
+            .filter(|(dist, _, _)| *dist <= limit)
+            .min_by(|a, b| a.0.cmp(&b.0).then_with(|| b.1.cmp(&a.1)))
+            .map(|(_, _, term)| term.as_str())
+    }
+
+    /// A corrected query when some word of `query` matches nothing in the index: adjacent
+    /// words that form a known name are joined ("libre office"), the rest are replaced by
+    /// the nearest name term ("fierfox"). None when the query already matches as typed.
+    pub fn suggest(&self, query: &str) -> Option<String> {
+        let tokens: Vec<String> = tokenize(query).collect();
+        let mut out: Vec<String> = Vec::new();
+        let mut changed = false;
+        let mut i = 0;
+        while i < tokens.len() {
+            if let Some(next) = tokens.get(i + 1) {
+                let joined = format!("{}{}", tokens[i], next);
+                if self.name_terms.iter().any(|(t, _)| *t == joined) {
+                    out.push(joined);
+                    changed = true;
+                    i += 2;
+                    continue;
+                }
+            }
+            let token = &tokens[i];
+            if self.has_term_prefix(token) {
+                out.push(token.clone());
+            } else if let Some(fixed) = self.closest_term(token) {
+                out.push(fixed.to_string());
+                changed = true;
+            } else {
+                out.push(token.clone());
+            }
+            i += 1;
+        }
+        changed.then(|| out.join(" "))
+    }
+
+    /// Ranked packages matching every word of `query`, one entry per package name (the
+    /// highest-priority repo wins).
+    pub fn query(
+        &self,
+        query: &str,
+        filters: &SearchFilters,
+        distro: &crate::distro_context::DistroContext,
+    ) -> Vec<Package> {
+        let query_lower = query.trim().to_lowercase();
+        let mut scores: Option<HashMap<u32, u32>> = None;
+        for token in tokenize(&query_lower) {
+            let hits = self.token_scores(&token);
+            scores = Some(match scores {
+                None => hits,
+                Some(prev) => prev
+                    .into_iter()
+                    .filter_map(|(doc, s)| hits.get(&doc).map(|h| (doc, s + h)))
+                    .collect(),
+            });
+        }
+        let Some(scores) = scores else {
+            return Vec::new();
+        };
+
+        let mut ranked: Vec<(u32, &Doc, Package)> = scores
+            .into_iter()
+            .filter_map(|(doc_id, score)| {
+                let doc = &self.docs[doc_id as usize];
+                let mut pkg = self.repos.get(&doc.repo)?.get(doc.pos)?.clone();
+                pkg.source = PackageSource::from_repo_name(&doc.repo, &pkg.version, distro);
+                pkg.is_optimized = Some(crate::repo_manager::is_optimized_repo(&doc.repo));
+                if !filters.repos.is_empty()
+                    && !filters
+                        .repos
+                        .iter()
+                        .any(|r| r == &doc.repo || r == &pkg.source.id)
+                {
+                    return None;
+                }
+                let mut bonus = 0;
+                if doc.name_lower == query_lower {
+                    bonus += 200;
+                } else if doc.name_lower.starts_with(&query_lower) {
+                    bonus += 50;
+                }
+                if doc
+                    .display_name
+                    .as_ref()
+                    .is_some_and(|n| n.to_lowercase() == query_lower)
+                {
+                    bonus += 150;
+                }
+                if pkg.display_name.is_none() {
+                    pkg.display_name = doc.display_name.clone();
+                }
+                Some((score + bonus, doc, pkg))
+            })
+            .collect();
+
+        ranked.sort_by(|(sa, da, pa), (sb, db, pb)| {
+            sb.cmp(sa)
+                .then_with(|| da.name_lower.len().cmp(&db.name_lower.len()))
+                .then_with(|| da.name_lower.cmp(&db.name_lower))
+                .then_with(|| pa.source.priority().cmp(&pb.source.priority()))
+        });
+
+        let mut seen = HashSet::new();
+        ranked
+            .into_iter()
+            .filter(|(_, doc, _)| seen.insert(doc.name_lower.clone()))
+            .take(if filters.is_post_filter() {
+                usize::MAX
+            } else {
+                filters.limit.unwrap_or(DEFAULT_LIMIT)
+            })
+            .map(|(_, _, pkg)| pkg)
+            .collect()
+    }
+}
+
+/// Managed state: the current index, replaced whole on rebuild.
+#[derive(Default)]
+pub struct SearchIndexState(tokio::sync::Mutex<Arc<SearchIndex>>);
+
+impl SearchIndexState {
+    /// The index for the current repo snapshot and AppStream data, rebuilding it if either
+    /// changed since the last build.
+    pub async fn current(&self, repo: &RepoManager, meta: &MetadataState) -> Arc<SearchIndex> {
+        let snapshot = repo.snapshot().await;
+        let mut guard = self.0.lock().await;
+        let appstream_revision = meta.0.lock().map(|l| l.revision()).unwrap_or_default();
+        if guard.built_from == Some((snapshot.generation, appstream_revision)) {
+            return guard.clone();
+        }
+
+        // Built off the runtime from a copy of the AppStream loader, so lookups elsewhere
+        // aren't held up; searches wait on `guard` and get the new index
+        let started = std::time::Instant::now();
+        let loader = meta.loader();
+        let build = tokio::task::spawn_blocking(move || {
+            SearchIndex::build(&snapshot, appstream_revision, |name| {
+                loader.as_ref().and_then(|l| l.find_package(name))
+            })
+        });
+        let rebuilt = match build.await {
+            Ok(rebuilt) => rebuilt,
+            Err(e) => {
+                log::warn!("Search index rebuild failed: {}", e);
+                return guard.clone();
+            }
+        };
+        log::info!(
+            "Search index rebuilt: {} packages, {} terms in {:?}",
+            rebuilt.docs.len(),
+            rebuilt.terms.len(),
+            started.elapsed()
+        );
+        *guard = Arc::new(rebuilt);
+        guard.clone()
+    }
+}
+
+/// Rebuild in the background after every repo sync so the next keystroke doesn't pay for it.
+pub fn start(app: AppHandle) {
+    let handle = app.clone();
+    app.listen("repo-index-updated", move |_| {
+        let handle = handle.clone();
+        tauri::async_runtime::spawn(async move { refresh(&handle).await });
+    });
+}
+
+pub async fn refresh(app: &AppHandle) {
+    let state = app.state::<SearchIndexState>();
+    state
+        .current(&app.state::<RepoManager>(), &app.state::<MetadataState>())
+        .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pkg(name: &str, description: &str) -> Package {
+        Package {
+            name: name.to_string(),
+            description: description.to_string(),
+            version: "1.0-1".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_index_ranking_prefix_and_filters() {
+        let mut repos = HashMap::new();
+        repos.insert(
+            "extra".to_string(),
+            Arc::new(vec![
+                pkg("firefox", "Fast, private web browser"),
+                pkg("firefox-developer-edition", "Developer build of Firefox"),
+                pkg("libreoffice-fresh", "Office suite"),
+                pkg("vlc", "Multi-platform media player"),
+            ]),
+        );
+        repos.insert(
+            "chaotic-aur".to_string(),
+            Arc::new(vec![pkg("firefox", "Fast, private web browser")]),
+        );
+        let repo_index = RepoIndex {
+            generation: 7,
+            repos,
+        };
+        let index = SearchIndex::build(&repo_index, 1, |name| {
+            (name == "libreoffice-fresh").then(|| AppMetadata {
+                name: "LibreOffice".to_string(),
+                pkg_name: Some(name.to_string()),
+                icon_url: None,
+                app_id: "org.libreoffice.LibreOffice".to_string(),
+                summary: Some("Documents and spreadsheets".to_string()),
+                screenshots: Vec::new(),
+                version: None,
+                maintainer: None,
+                license: None,
+                last_updated: None,
+                description: None,
+            })
+        });
+        assert_eq!(index.generation(), 7);
+        let distro = crate::distro_context::DistroContext::new();
+        let names = |q: &str, f: &SearchFilters| -> Vec<String> {
+            index
+                .query(q, f, &distro)
+                .into_iter()
+                .map(|p| p.name)
+                .collect()
+        };
+        let all = SearchFilters::default();
+
+        // Exact name first, deduplicated across repos; prefix matches still found
+        assert_eq!(
+            names("firefox", &all),
+            vec!["firefox", "firefox-developer-edition"]
+        );
+        assert_eq!(names("fire", &all)[0], "firefox");
+        // AppStream name and summary are searchable; every word must match
+        assert_eq!(names("libreoffice", &all), vec!["libreoffice-fresh"]);
+        assert_eq!(names("spreadsheets", &all), vec!["libreoffice-fresh"]);
+        assert!(names("firefox player", &all).is_empty());
+
+        // Typos and split names get a suggestion; correct queries don't
+        assert_eq!(index.suggest("fierfox").as_deref(), Some("firefox"));
+        assert_eq!(
+            index.suggest("libre office").as_deref(),
+            Some("libreoffice")
+        );
+        assert_eq!(index.suggest("firefox"), None);
+        assert_eq!(index.suggest("vcl"), None);
+
+        let chaotic_only = SearchFilters {
+            repos: vec!["chaotic-aur".to_string()],
+            ..Default::default()
+        };
+        let hits = index.query("firefox", &chaotic_only, &distro);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].source.id, "chaotic-aur");
+    }
+
+    #[test]
+    fn test_search_filters_match() {
+        let installed: HashSet<String> = ["vlc".to_string()].into_iter().collect();
+        let mut vlc = pkg("vlc", "");
+        vlc.source = PackageSource::new("repo", "extra", "1.0-1", "Official");
+        vlc.license = Some(vec!["GPL-2.0-or-later".to_string()]);
+        let mut steam = pkg("steam", "");
+        steam.source = PackageSource::new("repo", "cachyos", "1.0-1", "CachyOS");
+        steam.license = Some(vec!["custom:proprietary".to_string()]);
+        steam.is_optimized = Some(true);
+        steam.available_sources = Some(vec![
+            steam.source.clone(),
+            PackageSource::new("flatpak", "flathub", "latest", "Flatpak (Sandboxed)"),
+        ]);
+
+        let check = |f: SearchFilters| -> Vec<&str> {
+            [&vlc, &steam]
+                .into_iter()
+                .filter(|p| f.matches(p, &installed))
+                .map(|p| p.name.as_str())
+                .collect()
+        };
+        assert_eq!(check(SearchFilters::default()), vec!["vlc", "steam"]);
+        let installed_only = SearchFilters {
+            only_installed: true,
+            ..Default::default()
+        };
+        assert_eq!(check(installed_only), vec!["vlc"]);
+        let not_installed = SearchFilters {
+            only_not_installed: true,
+            ..Default::default()
+        };
+        assert_eq!(check(not_installed), vec!["steam"]);
+        let flatpak = SearchFilters {
+            source_types: vec!["flatpak".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(check(flatpak), vec!["steam"]);
+        let optimized = SearchFilters {
+            optimized_only: true,
+            ..Default::default()
+        };
+        assert_eq!(check(optimized), vec!["steam"]);
+        let foss = SearchFilters {
+            foss_only: true,
+            ..Default::default()
+        };
+        assert_eq!(check(foss), vec!["vlc"]);
+    }
+}
@@ -0,0 +1,351 @@
+//! Notices package changes made outside MonArch (pacman/yay in a terminal). An inotify watch
+//! on the local DB, the sync DBs and pacman.log wakes a background thread; once pacman has
+//! let go of its lock, the local DB is diffed against the last snapshot, cached update
+//! results for the touched packages are dropped and `system-state-changed` is emitted so the
+//! frontend refetches installed status, update counts and orphans. `packages-changed`
+//! carries just the new per-package state, so listings can patch their cards in place.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::os::unix::ffi::OsStrExt;
+use tauri::{AppHandle, Emitter};
+
+const LOCAL_DB: &str = "/var/lib/pacman/local";
+const SYNC_DIR: &str = "/var/lib/pacman/sync";
+const PACMAN_LOG: &str = "/var/log/pacman.log";
+const DB_LOCK: &str = "/var/lib/pacman/db.lck";
+/// Quiet period after the last event before diffing; a transaction touches many files.
+const SETTLE_MS: i32 = 1500;
+
+/// Payload of `system-state-changed`.
+#[derive(Debug, Serialize, Clone, Default, PartialEq)]
+pub struct SystemStateChange {
+    pub installed: Vec<String>,
+    pub removed: Vec<String>,
+    /// Version changed in either direction (upgrade or downgrade).
+    pub upgraded: Vec<String>,
+    pub sync_dbs_changed: bool,
+    /// false when MonArch itself was running a privileged operation at the time.
+    pub external: bool,
+}
+
+impl SystemStateChange {
+    fn is_empty(&self) -> bool {
+        self.installed.is_empty()
+            && self.removed.is_empty()
+            && self.upgraded.is_empty()
+            && !self.sync_dbs_changed
+    }
+
+    /// Packages whose cached update entries are no longer valid.
+    fn touched(&self) -> Vec<String> {
+        self.removed.iter().chain(&self.upgraded).cloned().collect()
+    }
+}
+
+/// One entry of `packages-changed`.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct PackageStateChange {
+    pub name: String,
+    /// The installed version now; None when it was removed.
+    pub version: Option<String>,
+}
+
+/// New state of every package the change installed, upgraded or removed.
+fn package_changes(
+    change: &SystemStateChange,
+    now: &HashMap<String, String>,
+) -> Vec<PackageStateChange> {
+    change
+        .installed
+        .iter()
+        .chain(&change.upgraded)
+        .chain(&change.removed)
+        .map(|name| PackageStateChange {
+            name: name.clone(),
+            version: now.get(name).cloned(),
+        })
+        .collect()
+}
+
+/// `name-pkgver-pkgrel` directory → (name, "pkgver-pkgrel").
+fn split_local_entry(entry: &str) -> Option<(&str, &str)> {
+    let rel_dash = entry.rfind('-')?;
+    let ver_dash = entry[..rel_dash].rfind('-')?;
+    if ver_dash == 0 {
+        return None;
+    }
+    Some((&entry[..ver_dash], &entry[ver_dash + 1..]))
+}
+
+/// Installed packages (name → version) straight from the local DB directory names.
+pub(crate) fn read_local_db() -> HashMap<String, String> {
+    let Ok(entries) = std::fs::read_dir(LOCAL_DB) else {
+        return HashMap::new();
+    };
+    entries
+        .flatten()
+        .filter(|e| e.file_type().map(|t| t.is_dir()).unwrap_or(false))
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().into_owned();
+            split_local_entry(&name).map(|(n, v)| (n.to_string(), v.to_string()))
+        })
+        .collect()
+}
+
+fn diff_local(
+    before: &HashMap<String, String>,
+    after: &HashMap<String, String>,
+) -> SystemStateChange {
+    let mut change = SystemStateChange::default();
+    for (name, version) in after {
+        match before.get(name) {
+            None => change.installed.push(name.clone()),
+            Some(old) if old != version => change.upgraded.push(name.clone()),
+            Some(_) => {}
+        }
+    }
+    change.removed = before
+        .keys()
+        .filter(|name| !after.contains_key(*name))
+        .cloned()
+        .collect();
+    change.installed.sort();
+    change.removed.sort();
+    change.upgraded.sort();
+    change
+}
+
+/// Minimal inotify wrapper; the fd is closed on drop.
+struct Inotify {
+    fd: libc::c_int,
+}
+
+impl Inotify {
+    fn new() -> Result<Self, String> {
+        let fd = unsafe { libc::inotify_init1(libc::IN_CLOEXEC) };
+        if fd < 0 {
+            return Err(format!(
+                "inotify_init1 failed: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+        Ok(Inotify { fd })
+    }
+
+    fn watch(&self, path: &str, mask: u32) -> Result<libc::c_int, String> {
+        let c_path = CString::new(std::path::Path::new(path).as_os_str().as_bytes())
+            .map_err(|e| e.to_string())?;
+        let wd = unsafe { libc::inotify_add_watch(self.fd, c_path.as_ptr(), mask) };
+        if wd < 0 {
+            return Err(format!(
+                "Cannot watch {}: {}",
+                path,
+                std::io::Error::last_os_error()
+            ));
+        }
+        Ok(wd)
+    }
+
+    /// Wait up to `timeout_ms` (-1 = forever) for events; returns the watch descriptors seen.
+    fn wait(&self, timeout_ms: i32) -> Result<Vec<libc::c_int>, String> {
+        let mut pfd = libc::pollfd {
+            fd: self.fd,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let ready = unsafe { libc::poll(&mut pfd, 1, timeout_ms) };
+        if ready < 0 {
+            let err = std::io::Error::last_os_error();
+            if err.kind() == std::io::ErrorKind::Interrupted {
+                return Ok(Vec::new());
+            }
+            return Err(format!("poll failed: {}", err));
+        }
+        if ready == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut buf = [0u8; 4096];
+        let n = unsafe { libc::read(self.fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len()) };
+        if n < 0 {
+            return Err(format!(
+                "inotify read failed: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+        let header = std::mem::size_of::<libc::inotify_event>();
+        let mut wds = Vec::new();
+        let mut offset = 0;
+        while offset + header <= n as usize {
+            let event = unsafe {
+                std::ptr::read_unaligned(buf.as_ptr().add(offset) as *const libc::inotify_event)
+            };
+            wds.push(event.wd);
+            offset += header + event.len as usize;
+        }
+        Ok(wds)
+    }
+}
+
+impl Drop for Inotify {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.fd);
+        }
+    }
+}
+
+/// Drop cached results that the change made stale, then tell the frontend.
+fn publish(app: &AppHandle, change: &SystemStateChange, now: &HashMap<String, String>) {
+    let touched = change.touched();
+    if !touched.is_empty() {
+        crate::commands::pins::forget_held_updates(&touched);
+        crate::commands::devel::forget_devel_updates(&touched);
+        if let Some(result) = crate::scheduler::forget_updates(&touched) {
+            // Keeps the tray counter in step
+            let _ = app.emit("scheduled-update-check", &result);
+        }
+    }
+    if change.sync_dbs_changed {
+        crate::repair::clear_sync_db_health_cache();
+    }
+    log::info!(
+        "System state changed ({}): +{} -{} ~{}{}",
+        if change.external {
+            "external"
+        } else {
+            "MonArch"
+        },
+        change.installed.len(),
+        change.removed.len(),
+        change.upgraded.len(),
+        if change.sync_dbs_changed {
+            ", sync DBs refreshed"
+        } else {
+            ""
+        }
+    );
+    let _ = app.emit("system-state-changed", change);
+    let packages = package_changes(change, now);
+    if !packages.is_empty() {
+        let _ = app.emit("packages-changed", &packages);
+    }
+}
+
+fn watch_loop(app: &AppHandle) -> Result<(), String> {
+    let inotify = Inotify::new()?;
+    inotify.watch(
+        LOCAL_DB,
+        libc::IN_CREATE | libc::IN_DELETE | libc::IN_MOVED_FROM | libc::IN_MOVED_TO,
+    )?;
+    let sync_wd = inotify
+        .watch(SYNC_DIR, libc::IN_CLOSE_WRITE | libc::IN_MOVED_TO)
+        .map_err(|e| log::warn!("{}", e))
+        .ok();
+    // pacman.log is appended as each transaction ends, which also wakes us if the local DB
+    // events overflowed the inotify queue
+    if let Err(e) = inotify.watch(PACMAN_LOG, libc::IN_MODIFY) {
+        log::warn!("{}", e);
+    }
+
+    let mut snapshot = read_local_db();
+    loop {
+        let mut sync_dirty = false;
+        let mut wds = inotify.wait(-1)?;
+        // PRIVILEGED_LOCK is held for the whole of a MonArch-initiated transaction. Sampled
+        // as events arrive: after the settle delay our own transaction has already let go.
+        let mut ours = crate::utils::PRIVILEGED_LOCK.try_lock().is_err();
+        // Let the transaction finish: wait for quiet and for pacman to drop its lock
+        loop {
+            sync_dirty |= sync_wd.is_some_and(|wd| wds.contains(&wd));
+            if !wds.is_empty() {
+                ours |= crate::utils::PRIVILEGED_LOCK.try_lock().is_err();
+            }
+            wds = inotify.wait(SETTLE_MS)?;
+            if wds.is_empty() && !std::path::Path::new(DB_LOCK).exists() {
+                break;
+            }
+        }
+
+        let previous = std::mem::replace(&mut snapshot, read_local_db());
+        crate::installed_state::replace(snapshot.clone());
+        let mut change = diff_local(&previous, &snapshot);
+        change.sync_dbs_changed = sync_dirty;
+        if change.is_empty() {
+            continue;
+        }
+        change.external = !ours;
+        crate::history::record_transaction(app, &previous, &snapshot, change.external);
+        publish(app, &change, &snapshot);
+    }
+}
+
+/// Spawn the watcher thread. Without a pacman DB (or inotify) it logs and gives up.
+pub fn start(app: AppHandle) {
+    let spawned = std::thread::Builder::new()
+        .name("monarch-state-watcher".into())
+        .spawn(move || {
+            if let Err(e) = watch_loop(&app) {
+                log::warn!("Package state watcher stopped: {}", e);
+            }
+        });
+    if let Err(e) = spawned {
+        log::warn!("Failed to start package state watcher: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_entry_split_and_diff() {
+        assert_eq!(
+            split_local_entry("python-requests-2.32.3-1"),
+            Some(("python-requests", "2.32.3-1"))
+        );
+        assert_eq!(
+            split_local_entry("mesa-1:24.1.2-1"),
+            Some(("mesa", "1:24.1.2-1"))
+        );
+        assert_eq!(split_local_entry("ALPM_DB_VERSION"), None);
+
+        let before: HashMap<String, String> =
+            [("vlc", "3.0.20-1"), ("gimp", "2.10-1"), ("tree", "2.1-1")]
+                .iter()
+                .map(|(n, v)| (n.to_string(), v.to_string()))
+                .collect();
+        let after: HashMap<String, String> =
+            [("vlc", "3.0.21-1"), ("tree", "2.1-1"), ("htop", "3.3-1")]
+                .iter()
+                .map(|(n, v)| (n.to_string(), v.to_string()))
+                .collect();
+        let change = diff_local(&before, &after);
+        assert_eq!(change.installed, vec!["htop"]);
+        assert_eq!(change.removed, vec!["gimp"]);
+        assert_eq!(change.upgraded, vec!["vlc"]);
+        assert_eq!(change.touched(), vec!["gimp", "vlc"]);
+        assert!(diff_local(&after, &after).is_empty());
+
+        let changes = package_changes(&change, &after);
+        assert_eq!(
+            changes,
+            vec![
+                PackageStateChange {
+                    name: "htop".into(),
+                    version: Some("3.3-1".into())
+                },
+                PackageStateChange {
+                    name: "vlc".into(),
+                    version: Some("3.0.21-1".into())
+                },
+                PackageStateChange {
+                    name: "gimp".into(),
+                    version: None
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,211 @@
+//! systemd units shipped by packages (docker, sshd, cups, ...). After an install the GUI is
+//! told which of the package's units are still disabled (`services-available`) so it can
+//! offer to enable them; enabling and starting go through the helper (ManageService).
+
+use crate::helper_client::{self, HelperCommand};
+use monarch_types::ServiceAction;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use tokio::process::Command;
+
+const SYSTEM_UNIT_DIR: &str = "/usr/lib/systemd/system/";
+const UNIT_SUFFIXES: &[&str] = &[".service", ".socket", ".timer", ".path"];
+
+/// One unit of a package and its current state.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct PackageService {
+    pub unit: String,
+    /// Description= from the unit file.
+    pub description: Option<String>,
+    /// `systemctl is-enabled` output: enabled, disabled, static, masked, ...
+    pub enabled: String,
+    pub active: bool,
+}
+
+/// Payload of the `services-available` event.
+#[derive(Debug, Serialize, Clone)]
+pub struct ServicesAvailable {
+    pub package: String,
+    pub services: Vec<PackageService>,
+}
+
+/// System units among a package's files. Templates (`foo@.service`) need an instance name
+/// and drop-ins live in subdirectories; neither is listed.
+pub fn service_units(files: &[String]) -> Vec<String> {
+    let mut units: Vec<String> = files
+        .iter()
+        .filter_map(|f| f.strip_prefix(SYSTEM_UNIT_DIR))
+        .filter(|name| !name.contains('/') && !name.contains("@."))
+        .filter(|name| UNIT_SUFFIXES.iter().any(|s| name.ends_with(s)))
+        .map(str::to_string)
+        .collect();
+    units.sort();
+    units
+}
+
+/// Description= of a unit file's [Unit] section.
+pub fn unit_description(contents: &str) -> Option<String> {
+    contents
+        .lines()
+        .map(str::trim)
+        .find_map(|l| l.strip_prefix("Description="))
+        .map(|d| d.trim().to_string())
+        .filter(|d| !d.is_empty())
+}
+
+async fn systemctl(args: &[&str], unit: &str) -> Option<String> {
+    let output = Command::new("systemctl")
+        .args(args)
+        .arg("--")
+        .arg(unit)
+        .output()
+        .await
+        .ok()?;
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+async fn service_state(unit: String) -> PackageService {
+    let description = tokio::fs::read_to_string(format!("{}{}", SYSTEM_UNIT_DIR, unit))
+        .await
+        .ok()
+        .and_then(|c| unit_description(&c));
+    let enabled = systemctl(&["is-enabled"], &unit)
+        .await
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| "unknown".to_string());
+    let active = systemctl(&["is-active"], &unit).await.as_deref() == Some("active");
+    PackageService {
+        unit,
+        description,
+        enabled,
+        active,
+    }
+}
+
+/// systemd units shipped by the installed package `pkg`, with their state.
+#[tauri::command]
+pub async fn list_package_services(pkg: String) -> Result<Vec<PackageService>, String> {
+    crate::utils::validate_package_name(&pkg)?;
+    let name = pkg.clone();
+    let files =
+        tokio::task::spawn_blocking(move || crate::alpm_read::get_package_files_native(&name))
+            .await
+            .map_err(|e| format!("Task join error: {}", e))?;
+    let Some((files, true)) = files else {
+        return Err(format!("{} is not installed", pkg));
+    };
+    let mut services = Vec::new();
+    for unit in service_units(&files) {
+        services.push(service_state(unit).await);
+    }
+    Ok(services)
+}
+
+async fn manage_service(
+    app: &AppHandle,
+    unit: String,
+    action: ServiceAction,
+    password: Option<String>,
+) -> Result<(), String> {
+    let _guard = crate::utils::PRIVILEGED_LOCK.lock().await;
+    let result = helper_client::run_helper(
+        app,
+        HelperCommand::ManageService { unit, action },
+        password,
+        |msg| {
+            let _ = app.emit("install-output", &msg.message);
+        },
+    )
+    .await?;
+    if result.success {
+        Ok(())
+    } else {
+        Err(result
+            .error
+            .unwrap_or_else(|| "systemctl failed".to_string()))
+    }
+}
+
+/// Enable `unit` at boot; with `now`, also start it.
+#[tauri::command]
+pub async fn enable_service(
+    app: AppHandle,
+    unit: String,
+    now: bool,
+    password: Option<String>,
+) -> Result<(), String> {
+    let action = if now {
+        ServiceAction::EnableNow
+    } else {
+        ServiceAction::Enable
+    };
+    manage_service(&app, unit, action, password).await
+}
+
+/// Start `unit` without enabling it.
+#[tauri::command]
+pub async fn start_service(
+    app: AppHandle,
+    unit: String,
+    password: Option<String>,
+) -> Result<(), String> {
+    manage_service(&app, unit, ServiceAction::Start, password).await
+}
+
+/// After `pkg` was installed: tell the GUI about units it shipped that are not enabled yet.
+pub fn offer_after_install(app: &AppHandle, pkg: &str) {
+    let app = app.clone();
+    let pkg = pkg.to_string();
+    tauri::async_runtime::spawn(async move {
+        let Ok(services) = list_package_services(pkg.clone()).await else {
+            return;
+        };
+        let services: Vec<PackageService> = services
+            .into_iter()
+            .filter(|s| s.enabled == "disabled")
+            .collect();
+        if !services.is_empty() {
+            let _ = app.emit(
+                "services-available",
+                ServicesAvailable {
+                    package: pkg,
+                    services,
+                },
+            );
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_service_units() {
+        let files: Vec<String> = [
+            "/usr/bin/dockerd",
+            "/usr/lib/systemd/system/docker.service",
+            "/usr/lib/systemd/system/docker.socket",
+            "/usr/lib/systemd/system/getty@.service",
+            "/usr/lib/systemd/system/sockets.target.wants/docker.socket",
+            "/usr/lib/systemd/system/docker.service.d/override.conf",
+            "/usr/lib/systemd/system/",
+            "/usr/lib/systemd/user/foo.service",
+            "/usr/lib/sysusers.d/docker.conf",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        assert_eq!(
+            service_units(&files),
+            vec!["docker.service", "docker.socket"]
+        );
+
+        assert_eq!(
+            unit_description("[Unit]\nDescription=Docker Application Container Engine\nAfter=network-online.target\n")
+                .as_deref(),
+            Some("Docker Application Container Engine")
+        );
+        assert_eq!(unit_description("[Unit]\nDescription=\n"), None);
+    }
+}
@@ -158,4 +158,58 @@ mod tests {
         let v2 = "1.0.0-2";
         assert_ne!(v1, v2);
     }
+
+    mod input_properties {
+        use crate::repo_desc::parse_desc_entry;
+        use crate::utils::validate_package_name;
+        use proptest::prelude::*;
+
+        proptest! {
+            #[test]
+            fn valid_arch_names_are_accepted(name in "[a-z0-9@_+][a-z0-9@._+-]{0,60}") {
+                prop_assert!(validate_package_name(&name).is_ok());
+            }
+
+            #[test]
+            fn shell_and_path_characters_are_rejected(
+                prefix in "[a-z]{0,8}",
+                bad in "[/ ;&|$`'\"\\*?<>(){}\n\t\0]",
+                suffix in "[a-z]{0,8}",
+            ) {
+                let name = format!("{}{}{}", prefix, bad, suffix);
+                prop_assert!(validate_package_name(&name).is_err());
+            }
+
+            /// Whatever the input, an accepted name is safe as a path component and an argv entry.
+            #[test]
+            fn accepted_names_cannot_escape_or_become_flags(name in any::<String>()) {
+                if validate_package_name(&name).is_ok() {
+                    prop_assert!(!name.is_empty());
+                    prop_assert!(!name.starts_with('-') && !name.starts_with('.'));
+                    prop_assert!(!name.contains('/') && name.is_ascii());
+                }
+            }
+
+            #[test]
+            fn desc_parser_only_returns_valid_entries(content in any::<String>()) {
+                if let Some(entry) = parse_desc_entry(&content) {
+                    prop_assert!(validate_package_name(&entry.name).is_ok());
+                    prop_assert!(!entry.version.is_empty());
+                }
+            }
+
+            #[test]
+            fn desc_parser_roundtrips_generated_entries(
+                name in "[a-z0-9][a-z0-9._+-]{0,30}",
+                version in "[0-9]{1,3}\\.[0-9]{1,3}-[0-9]",
+                desc in "[ -~]{0,60}",
+            ) {
+                let content = format!("%NAME%\n{}\n\n%VERSION%\n{}\n\n%DESC%\n{}\n", name, version, desc);
+                let entry = parse_desc_entry(&content).expect("well-formed desc");
+                prop_assert_eq!(entry.name, name);
+                prop_assert_eq!(entry.version, version);
+                prop_assert_eq!(entry.description, Some(desc));
+            }
+        }
+    }
 }
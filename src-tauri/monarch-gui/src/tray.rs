@@ -0,0 +1,109 @@
+//! System tray: quick actions plus a pending-updates counter fed by the same
+//! "scheduled-update-check" / "update-complete" events the main window listens to.
+
+use tauri::menu::{Menu, MenuItem, PredefinedMenuItem};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Emitter, Listener, Manager, Wry};
+
+const TRAY_ID: &str = "monarch-tray";
+
+/// Show, unminimize and focus the main window (it may have been hidden by run-in-background).
+pub(crate) fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+fn set_pending_count(app: &AppHandle, item: &MenuItem<Wry>, count: usize) {
+    let label = match count {
+        0 => "No pending updates".to_string(),
+        1 => "1 update pending".to_string(),
+        n => format!("{} updates pending", n),
+    };
+    let _ = item.set_text(&label);
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let _ = tray.set_tooltip(Some(format!("MonArch Store — {}", label)));
+    }
+}
+
+pub fn init(app: &AppHandle) -> tauri::Result<()> {
+    let pending = MenuItem::with_id(app, "pending", "No pending updates", false, None::<&str>)?;
+    let open = MenuItem::with_id(app, "open", "Open MonArch", true, None::<&str>)?;
+    let check = MenuItem::with_id(app, "check", "Check for updates", true, None::<&str>)?;
+    let update = MenuItem::with_id(app, "update", "Update system", true, None::<&str>)?;
+    let digest = MenuItem::with_id(app, "digest", "Weekly digest", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+    let menu = Menu::with_items(
+        app,
+        &[
+            &pending,
+            &PredefinedMenuItem::separator(app)?,
+            &open,
+            &check,
+            &update,
+            &digest,
+            &PredefinedMenuItem::separator(app)?,
+            &quit,
+        ],
+    )?;
+
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .menu(&menu)
+        .tooltip("MonArch Store")
+        .on_menu_event(|app, event| match event.id().as_ref() {
+            "open" => show_main_window(app),
+            "check" => {
+                let handle = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    crate::scheduler::check_now(&handle).await;
+                });
+            }
+            "update" => {
+                // The Updates page owns the update flow (password prompt, progress UI)
+                show_main_window(app);
+                let _ = app.emit("tray-action", "update-system");
+            }
+            "digest" => {
+                show_main_window(app);
+                let _ = app.emit("tray-action", "open-digest");
+            }
+            "quit" => app.exit(0),
+            _ => {}
+        })
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                show_main_window(tray.app_handle());
+            }
+        });
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+
+    let handle = app.clone();
+    let item = pending.clone();
+    app.listen("scheduled-update-check", move |event| {
+        if let Ok(result) = serde_json::from_str::<serde_json::Value>(event.payload()) {
+            let count = result["updates"].as_array().map(|a| a.len()).unwrap_or(0);
+            set_pending_count(&handle, &item, count);
+        }
+    });
+
+    let handle = app.clone();
+    app.listen("update-complete", move |event| {
+        if let Ok(result) = serde_json::from_str::<serde_json::Value>(event.payload()) {
+            if result["success"].as_bool() == Some(true) {
+                set_pending_count(&handle, &pending, 0);
+            }
+        }
+    });
+
+    Ok(())
+}
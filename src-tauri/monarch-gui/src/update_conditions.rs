@@ -0,0 +1,222 @@
+//! Power and network conditions for big downloads. On battery below a threshold (UPower)
+//! or on a metered connection (NetworkManager's Metered property), the scheduler holds its
+//! background sync and auto updates until things improve, and the Updates page warns
+//! before a full system update. A missing service raises no objection: a machine without
+//! UPower runs on mains, and without NetworkManager nothing is known about metering.
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+use tokio::sync::OnceCell;
+
+const STORE_FILE: &str = "settings.json";
+const CONDITIONS_KEY: &str = "update_conditions";
+
+const UPOWER: &str = "org.freedesktop.UPower";
+const UPOWER_PATH: &str = "/org/freedesktop/UPower";
+/// UPower's combined view of all batteries.
+const DISPLAY_DEVICE_PATH: &str = "/org/freedesktop/UPower/devices/DisplayDevice";
+const NETWORK_MANAGER: &str = "org.freedesktop.NetworkManager";
+const NETWORK_MANAGER_PATH: &str = "/org/freedesktop/NetworkManager";
+
+static SYSTEM_BUS: OnceCell<zbus::Connection> = OnceCell::const_new();
+
+/// When big updates should wait. Stored in settings.json.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(default)]
+pub struct UpdateConditions {
+    /// Hold background work while on battery below `min_battery_percent`.
+    pub defer_on_battery: bool,
+    pub min_battery_percent: u8,
+    /// Hold background work on a metered connection (hotspot, capped plan).
+    pub defer_on_metered: bool,
+}
+
+impl Default for UpdateConditions {
+    fn default() -> Self {
+        Self {
+            defer_on_battery: true,
+            min_battery_percent: 50,
+            defer_on_metered: true,
+        }
+    }
+}
+
+/// What UPower and NetworkManager report right now.
+#[derive(Debug, Serialize, Clone, PartialEq, Default)]
+pub struct PowerNetworkState {
+    pub on_battery: bool,
+    /// Combined charge of the batteries, if there are any.
+    pub battery_percent: Option<f64>,
+    pub metered: bool,
+}
+
+/// Payload of get_update_conditions / set_update_conditions.
+#[derive(Debug, Serialize, Clone)]
+pub struct UpdateConditionsInfo {
+    pub conditions: UpdateConditions,
+    pub state: PowerNetworkState,
+    /// Why big updates should wait right now, if they should.
+    pub hold_reason: Option<String>,
+}
+
+fn read_conditions(app: &AppHandle) -> UpdateConditions {
+    app.store(STORE_FILE)
+        .ok()
+        .and_then(|s| s.get(CONDITIONS_KEY))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+/// Why `state` should hold big updates under `conditions`, if it should.
+pub fn hold_reason(conditions: &UpdateConditions, state: &PowerNetworkState) -> Option<String> {
+    if conditions.defer_on_battery && state.on_battery {
+        match state.battery_percent {
+            Some(p) if p < conditions.min_battery_percent as f64 => {
+                return Some(format!(
+                    "On battery at {:.0}% (below {}%)",
+                    p, conditions.min_battery_percent
+                ));
+            }
+            Some(_) => {}
+            None => return Some("On battery power".to_string()),
+        }
+    }
+    if conditions.defer_on_metered && state.metered {
+        return Some("On a metered connection".to_string());
+    }
+    None
+}
+
+async fn property<T>(
+    conn: &zbus::Connection,
+    destination: &'static str,
+    path: &'static str,
+    interface: &'static str,
+    name: &str,
+) -> Option<T>
+where
+    T: TryFrom<zbus::zvariant::OwnedValue>,
+    T::Error: Into<zbus::Error>,
+{
+    let proxy = zbus::Proxy::new(conn, destination, path, interface)
+        .await
+        .ok()?;
+    proxy.get_property(name).await.ok()
+}
+
+/// Ask UPower and NetworkManager; anything unanswered counts as mains power and unmetered.
+pub async fn read_state() -> PowerNetworkState {
+    let Ok(conn) = SYSTEM_BUS.get_or_try_init(zbus::Connection::system).await else {
+        return PowerNetworkState::default();
+    };
+    let on_battery = property::<bool>(conn, UPOWER, UPOWER_PATH, UPOWER, "OnBattery")
+        .await
+        .unwrap_or(false);
+    let device = "org.freedesktop.UPower.Device";
+    let battery_percent =
+        match property::<bool>(conn, UPOWER, DISPLAY_DEVICE_PATH, device, "IsPresent").await {
+            Some(true) => {
+                property::<f64>(conn, UPOWER, DISPLAY_DEVICE_PATH, device, "Percentage").await
+            }
+            _ => None,
+        };
+    // NMMetered: 1 yes, 3 guessed yes (phone hotspot); 0 unknown, 2 no, 4 guessed no
+    let metered = property::<u32>(
+        conn,
+        NETWORK_MANAGER,
+        NETWORK_MANAGER_PATH,
+        NETWORK_MANAGER,
+        "Metered",
+    )
+    .await
+    .is_some_and(|m| m == 1 || m == 3);
+    PowerNetworkState {
+        on_battery,
+        battery_percent,
+        metered,
+    }
+}
+
+/// Why background syncs and auto updates should wait right now, if they should.
+pub(crate) async fn current_hold_reason(app: &AppHandle) -> Option<String> {
+    hold_reason(&read_conditions(app), &read_state().await)
+}
+
+async fn info(conditions: UpdateConditions) -> UpdateConditionsInfo {
+    let state = read_state().await;
+    UpdateConditionsInfo {
+        hold_reason: hold_reason(&conditions, &state),
+        conditions,
+        state,
+    }
+}
+
+#[tauri::command]
+pub async fn get_update_conditions(app: AppHandle) -> Result<UpdateConditionsInfo, String> {
+    Ok(info(read_conditions(&app)).await)
+}
+
+#[tauri::command]
+pub async fn set_update_conditions(
+    app: AppHandle,
+    mut conditions: UpdateConditions,
+) -> Result<UpdateConditionsInfo, String> {
+    conditions.min_battery_percent = conditions.min_battery_percent.min(100);
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    store.set(
+        CONDITIONS_KEY,
+        serde_json::to_value(conditions).map_err(|e| e.to_string())?,
+    );
+    store.save().map_err(|e| e.to_string())?;
+    Ok(info(conditions).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hold_reason() {
+        let conditions = UpdateConditions::default();
+        let mains = PowerNetworkState::default();
+        assert_eq!(hold_reason(&conditions, &mains), None);
+
+        let low = PowerNetworkState {
+            on_battery: true,
+            battery_percent: Some(23.4),
+            metered: false,
+        };
+        assert_eq!(
+            hold_reason(&conditions, &low).as_deref(),
+            Some("On battery at 23% (below 50%)")
+        );
+        let charged = PowerNetworkState {
+            battery_percent: Some(80.0),
+            ..low.clone()
+        };
+        assert_eq!(hold_reason(&conditions, &charged), None);
+
+        let hotspot = PowerNetworkState {
+            metered: true,
+            ..mains
+        };
+        assert_eq!(
+            hold_reason(&conditions, &hotspot).as_deref(),
+            Some("On a metered connection")
+        );
+
+        let relaxed = UpdateConditions {
+            defer_on_battery: false,
+            defer_on_metered: false,
+            ..conditions
+        };
+        assert_eq!(hold_reason(&relaxed, &low), None);
+        assert_eq!(hold_reason(&relaxed, &hotspot), None);
+
+        // Missing fields take the defaults
+        let old: UpdateConditions = serde_json::from_str(r#"{"defer_on_metered":false}"#).unwrap();
+        assert!(old.defer_on_battery);
+        assert_eq!(old.min_battery_percent, 50);
+    }
+}
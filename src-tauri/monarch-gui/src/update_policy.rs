@@ -0,0 +1,355 @@
+//! Per-category update policies: auto-apply some kinds of updates in the background (e.g.
+//! browsers, security-critical libraries), hold others (kernels, drivers) for the user.
+//! Categories come from package names; per-package overrides win over the category. Every
+//! category defaults to "notify", which is the behaviour without policies.
+//!
+//! Official repo updates can only be applied together (a partial -Syu is unsupported), so
+//! the background updater applies them only when every pending repo update is "auto"; one
+//! held kernel keeps the whole repo set for manual confirmation. AUR and Flatpak updates are
+//! applied per package. Downgrades and firmware are never applied automatically.
+//!
+//! The decision is made on the scheduler's read-only check (CheckUpdatesSafe syncs a
+//! throwaway copy of the DBs); the live DBs are only refreshed by the full upgrade itself,
+//! never by a bare -Sy.
+
+use crate::commands::package::PendingUpdate;
+use crate::models::{PackageSource, UpdateItem, UpdateSet};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+use tauri_plugin_store::StoreExt;
+
+const STORE_FILE: &str = "settings.json";
+const POLICIES_KEY: &str = "update_policies";
+
+const KERNEL_FLAVORS: &[&str] = &[
+    "linux",
+    "linux-lts",
+    "linux-zen",
+    "linux-hardened",
+    "linux-rt",
+    "linux-rt-lts",
+];
+const DRIVER_PREFIXES: &[&str] = &[
+    "nvidia",
+    "lib32-nvidia",
+    "opencl-nvidia",
+    "mesa",
+    "lib32-mesa",
+    "vulkan-",
+    "lib32-vulkan-",
+    "xf86-video-",
+    "broadcom-wl",
+];
+/// Matched anywhere in the lowercased name, so Flatpak ids (org.mozilla.firefox) count too.
+const BROWSER_NAMES: &[&str] = &[
+    "firefox",
+    "chromium",
+    "google-chrome",
+    "com.google.chrome",
+    "brave",
+    "librewolf",
+    "vivaldi",
+    "floorp",
+    "zen-browser",
+    "microsoft-edge",
+    "torbrowser",
+];
+const SECURITY_PACKAGES: &[&str] = &[
+    "openssl",
+    "openssh",
+    "sudo",
+    "opendoas",
+    "gnupg",
+    "gnutls",
+    "nss",
+    "ca-certificates",
+    "ca-certificates-mozilla",
+    "curl",
+    "polkit",
+    "pam",
+    "xz",
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdatePolicy {
+    /// Applied by the background updater.
+    Auto,
+    /// Listed and notified; the user applies it.
+    #[default]
+    Notify,
+    /// Never applied in the background, and flagged on the Updates page.
+    Hold,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateCategory {
+    Browsers,
+    Security,
+    Kernels,
+    Drivers,
+    Other,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct UpdatePolicies {
+    #[serde(default)]
+    pub categories: HashMap<UpdateCategory, UpdatePolicy>,
+    /// Package name → policy, overriding its category.
+    #[serde(default)]
+    pub packages: HashMap<String, UpdatePolicy>,
+}
+
+impl UpdatePolicies {
+    pub fn policy_for(&self, name: &str) -> (UpdateCategory, UpdatePolicy) {
+        let category = classify(name);
+        let policy = self
+            .packages
+            .get(name)
+            .or_else(|| self.categories.get(&category))
+            .copied()
+            .unwrap_or_default();
+        (category, policy)
+    }
+
+    fn any_auto(&self) -> bool {
+        self.categories
+            .values()
+            .chain(self.packages.values())
+            .any(|p| *p == UpdatePolicy::Auto)
+    }
+}
+
+/// Typed response for classify_updates.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct PolicyAnnotation {
+    pub name: String,
+    pub category: UpdateCategory,
+    pub policy: UpdatePolicy,
+}
+
+pub fn classify(name: &str) -> UpdateCategory {
+    let lower = name.to_lowercase();
+    let base = lower.strip_suffix("-headers").unwrap_or(&lower);
+    if KERNEL_FLAVORS.contains(&base)
+        || base.starts_with("linux-cachyos")
+        || lower.starts_with("linux-firmware")
+    {
+        UpdateCategory::Kernels
+    } else if lower.ends_with("-dkms") || DRIVER_PREFIXES.iter().any(|p| lower.starts_with(p)) {
+        UpdateCategory::Drivers
+    } else if BROWSER_NAMES.iter().any(|b| lower.contains(b)) {
+        UpdateCategory::Browsers
+    } else if SECURITY_PACKAGES.contains(&lower.as_str()) {
+        UpdateCategory::Security
+    } else {
+        UpdateCategory::Other
+    }
+}
+
+/// The updates the background updater may apply on its own (see the module docs).
+fn auto_targets(set: &UpdateSet, policies: &UpdatePolicies) -> Vec<UpdateItem> {
+    let is_auto = |u: &UpdateItem| policies.policy_for(&u.name).1 == UpdatePolicy::Auto;
+    let mut targets = Vec::new();
+    let repo: Vec<&UpdateItem> = set.repo.iter().filter(|u| !u.downgrade).collect();
+    if !repo.is_empty() && repo.iter().all(|u| is_auto(u)) {
+        targets.extend(repo.into_iter().cloned());
+    }
+    targets.extend(
+        set.aur
+            .iter()
+            .chain(&set.flatpak)
+            .filter(|u| !u.downgrade && is_auto(u))
+            .cloned(),
+    );
+    targets
+}
+
+/// The scheduler's check result as update items; repo and AUR entries only.
+fn pending_items(pending: &[PendingUpdate]) -> Vec<UpdateItem> {
+    pending
+        .iter()
+        .map(|u| UpdateItem {
+            name: u.name.clone(),
+            display_name: None,
+            current_version: u.old_version.clone(),
+            new_version: u.new_version.clone(),
+            source: if u.repo == "aur" {
+                PackageSource::aur()
+            } else {
+                PackageSource::official()
+            },
+            size: None,
+            icon: None,
+            downgrade: u.downgrade,
+        })
+        .collect()
+}
+
+pub(crate) fn read_policies(app: &AppHandle) -> UpdatePolicies {
+    app.store(STORE_FILE)
+        .ok()
+        .and_then(|s| s.get(POLICIES_KEY))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+/// Called by the scheduler with the result of its check. Repo updates go through one
+/// full system upgrade (refresh and sysupgrade together), so nothing here leaves the
+/// system partially upgraded. Skipped when applying would show an authentication prompt.
+pub(crate) async fn apply_auto_updates(app: &AppHandle, pending: &[PendingUpdate]) {
+    let policies = read_policies(app);
+    if !policies.any_auto() {
+        return;
+    }
+    if !crate::news_api::pending_breaking_news(app).await.is_empty() {
+        log::info!("Automatic updates skipped: unread breaking news needs acknowledging");
+        return;
+    }
+    if crate::utils::PRIVILEGED_LOCK.try_lock().is_err() {
+        log::info!("Automatic updates skipped: privileged operation in progress");
+        return;
+    }
+    if !crate::helper_client::runs_unattended(app).await {
+        log::info!("Automatic updates skipped: applying them would need authentication");
+        return;
+    }
+
+    let mut items = pending_items(pending);
+    match crate::flathub_api::get_updates().await {
+        Ok(flatpaks) => items.extend(flatpaks),
+        Err(e) => log::warn!("Automatic Flatpak updates skipped: {}", e),
+    }
+    let set = UpdateSet::from_items(items);
+    let targets = auto_targets(&set, &policies);
+    if targets.is_empty() {
+        return;
+    }
+    let names: Vec<String> = targets.iter().map(|t| t.name.clone()).collect();
+    log::info!("Applying automatic updates: {}", names.join(", "));
+
+    let result = crate::commands::update::apply_updates_impl(app.clone(), targets, None).await;
+    let (title, body) = match &result {
+        Ok(_) => (
+            "MonArch: Updates Applied",
+            format!("Automatically updated {}", names.join(", ")),
+        ),
+        Err(e) => ("MonArch: Automatic Update Failed", e.clone()),
+    };
+    let repo_manager = app.state::<crate::repo_manager::RepoManager>();
+    if repo_manager.is_notifications_enabled().await {
+        let _ = app.notification().builder().title(title).body(body).show();
+    }
+}
+
+#[tauri::command]
+pub async fn get_update_policies(app: AppHandle) -> Result<UpdatePolicies, String> {
+    Ok(read_policies(&app))
+}
+
+#[tauri::command]
+pub async fn set_update_policies(
+    app: AppHandle,
+    policies: UpdatePolicies,
+) -> Result<UpdatePolicies, String> {
+    for name in policies.packages.keys() {
+        crate::utils::validate_package_name(name)?;
+    }
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    store.set(POLICIES_KEY, json!(policies));
+    store.save().map_err(|e| e.to_string())?;
+    Ok(policies)
+}
+
+/// Category and effective policy of each name, for grouping the Updates page.
+#[tauri::command]
+pub async fn classify_updates(
+    app: AppHandle,
+    names: Vec<String>,
+) -> Result<Vec<PolicyAnnotation>, String> {
+    let policies = read_policies(&app);
+    Ok(names
+        .into_iter()
+        .map(|name| {
+            let (category, policy) = policies.policy_for(&name);
+            PolicyAnnotation {
+                name,
+                category,
+                policy,
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(name: &str, source: PackageSource) -> UpdateItem {
+        UpdateItem {
+            name: name.to_string(),
+            display_name: None,
+            current_version: "1".to_string(),
+            new_version: "2".to_string(),
+            source,
+            size: None,
+            icon: None,
+            downgrade: false,
+        }
+    }
+
+    #[test]
+    fn test_classify_and_auto_targets() {
+        let pending = vec![PendingUpdate {
+            name: "yay".to_string(),
+            old_version: "12.3-1".to_string(),
+            new_version: "12.4-1".to_string(),
+            repo: "aur".to_string(),
+            downgrade: false,
+        }];
+        assert_eq!(pending_items(&pending)[0].source.source_type, "aur");
+
+        assert_eq!(classify("linux-zen-headers"), UpdateCategory::Kernels);
+        assert_eq!(classify("linux-firmware-intel"), UpdateCategory::Kernels);
+        assert_eq!(classify("linux-api-headers"), UpdateCategory::Other);
+        assert_eq!(classify("nvidia-dkms"), UpdateCategory::Drivers);
+        assert_eq!(classify("org.mozilla.firefox"), UpdateCategory::Browsers);
+        assert_eq!(classify("openssl"), UpdateCategory::Security);
+        assert_eq!(classify("vlc"), UpdateCategory::Other);
+
+        let policies = UpdatePolicies {
+            categories: HashMap::from([
+                (UpdateCategory::Browsers, UpdatePolicy::Auto),
+                (UpdateCategory::Security, UpdatePolicy::Auto),
+                (UpdateCategory::Kernels, UpdatePolicy::Hold),
+            ]),
+            packages: HashMap::from([("vlc".to_string(), UpdatePolicy::Auto)]),
+        };
+        assert_eq!(policies.policy_for("vlc").1, UpdatePolicy::Auto);
+        assert_eq!(policies.policy_for("gimp").1, UpdatePolicy::Notify);
+
+        let mut set = UpdateSet {
+            repo: vec![
+                item("firefox", PackageSource::official()),
+                item("openssl", PackageSource::official()),
+                item("linux", PackageSource::official()),
+            ],
+            aur: vec![item("librewolf-bin", PackageSource::aur())],
+            flatpak: vec![item("org.gimp.GIMP", PackageSource::official())],
+            ..Default::default()
+        };
+        // The held kernel keeps the whole repo upgrade back; the AUR browser still goes
+        let names = |t: Vec<UpdateItem>| t.into_iter().map(|u| u.name).collect::<Vec<_>>();
+        assert_eq!(names(auto_targets(&set, &policies)), vec!["librewolf-bin"]);
+
+        set.repo.pop();
+        assert_eq!(
+            names(auto_targets(&set, &policies)),
+            vec!["firefox", "openssl", "librewolf-bin"]
+        );
+    }
+}
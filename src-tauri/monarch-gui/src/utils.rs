@@ -102,7 +102,8 @@ pub fn to_pretty_name(pkg_name: &str) -> String {
 }
 
 lazy_static::lazy_static! {
-    static ref VALIDATE_RE: regex::Regex = regex::Regex::new(r"^[a-zA-Z0-9@._+\-]+$").expect("valid package name regex");
+    // pacman's rule: [a-zA-Z0-9@._+-], not starting with '-' or '.' (so never "..", never a flag)
+    static ref VALIDATE_RE: regex::Regex = regex::Regex::new(r"^[a-zA-Z0-9@_+][a-zA-Z0-9@._+\-]*$").expect("valid package name regex");
 }
 
 static VALIDATE_CACHE: once_cell::sync::Lazy<moka::sync::Cache<String, Result<(), String>>> =
@@ -135,6 +136,7 @@ pub fn validate_package_name(name: &str) -> Result<(), String> {
 
 use crate::models;
 
+#[allow(dead_code)]
 pub fn sort_packages_by_relevance(packages: &mut [models::Package], query: &str) {
     let q_lower = query.to_lowercase();
     let common_apps = [
@@ -697,10 +699,7 @@ pub async fn run_pacman_command_transparent(
     use crate::distro_context::DistroContext;
     use crate::distro_context::DistroId;
     use crate::error_classifier::ClassifiedError;
-    use std::sync::Arc;
     use tauri::Emitter;
-    use tokio::io::{AsyncBufReadExt, BufReader};
-    use tokio::sync::Mutex;
 
     // 1. Manjaro Safety Guard (Protocol v0.3.5 Phase 4)
     let distro = DistroContext::new();
@@ -725,72 +724,35 @@ pub async fn run_pacman_command_transparent(
     // Acquire global lock to serialize privileged prompts
     let _guard = PRIVILEGED_LOCK.lock().await;
 
-    let mut child = tokio::process::Command::new(binary)
-        .args(&args)
-        .stdin(std::process::Stdio::piped())
-        .stdout(std::process::Stdio::piped())
-        .stderr(std::process::Stdio::piped())
-        .spawn()
-        .map_err(|e| format!("Failed to spawn pacman command: {}", e))?;
-
-    // 3. Handle password if using sudo
-    if let Some(pwd) = password {
-        if let Some(mut s) = child.stdin.take() {
-            let _ =
-                tokio::io::AsyncWriteExt::write_all(&mut s, format!("{}\n", pwd).as_bytes()).await;
-        }
-    }
-
-    // 4. Stream Output with Error Collection
-    // Collect stderr for error classification
-    let error_buffer: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
-
+    // 3. Run on a PTY: clean streamed output, Ctrl-C style cancel (abort_installation),
+    // and a timeout instead of hanging on a prompt nobody can answer
     let app_clone = app.clone();
-    let stdout = child
-        .stdout
-        .take()
-        .ok_or_else(|| "Failed to capture stdout (was not piped)".to_string())?;
-    tokio::spawn(async move {
-        let mut reader = BufReader::new(stdout).lines();
-        while let Ok(Some(line)) = reader.next_line().await {
+    let outcome = crate::pty_runner::run_in_pty(
+        &binary,
+        &args,
+        password.map(|pwd| format!("{}\n", pwd)),
+        crate::pty_runner::PtyOptions::default(),
+        move |line| {
             let _ = app_clone.emit("install-output", line);
-        }
-    });
+        },
+    )
+    .await?;
 
-    let app_clone = app.clone();
-    let stderr = child
-        .stderr
-        .take()
-        .ok_or_else(|| "Failed to capture stderr (was not piped)".to_string())?;
-    let error_buffer_clone = error_buffer.clone();
-    tokio::spawn(async move {
-        let mut reader = BufReader::new(stderr).lines();
-        while let Ok(Some(line)) = reader.next_line().await {
-            // Store for later classification
-            {
-                let mut buf = error_buffer_clone.lock().await;
-                buf.push(line.clone());
-            }
-            let _ = app_clone.emit("install-output", format!("ERROR: {}", line));
-        }
-    });
-
-    // 5. Wait for completion
-    let status = child.wait().await.map_err(|e| e.to_string())?;
+    if outcome.success {
+        return Ok(());
+    }
+    if let Some(reason) = outcome.stopped {
+        let _ = app.emit("install-output", format!("--- {} ---", reason));
+        return Err(reason);
+    }
 
-    if status.success() {
-        Ok(())
+    // 4. Classify the error and emit structured event for UI recovery actions
+    // (stdout and stderr share the terminal, so classify everything pacman printed)
+    let combined_output = outcome.lines.join("\n");
+    if let Some(classified) = ClassifiedError::from_output(&combined_output) {
+        let _ = app.emit("install-error-classified", &classified);
+        Err(format!("{}: {}", classified.title, classified.description))
     } else {
-        // 6. Classify the error and emit structured event for UI recovery actions
-        let errors = error_buffer.lock().await;
-        let combined_output = errors.join("\n");
-
-        if let Some(classified) = ClassifiedError::from_output(&combined_output) {
-            // Emit structured error event for the UI to show recovery options
-            let _ = app.emit("install-error-classified", &classified);
-            Err(format!("{}: {}", classified.title, classified.description))
-        } else {
-            Err("Pacman operation failed. Check logs for details.".to_string())
-        }
+        Err("Pacman operation failed. Check logs for details.".to_string())
     }
 }
@@ -0,0 +1,58 @@
+//! Package version comparison. Everything goes through libalpm's vercmp so epochs
+//! (`1:2.0-1` > `3.0-1`), pkgrel-only bumps (`2.0-1` → `2.0-2`) and pre-release tags
+//! (`1.0rc1` < `1.0`) order the way pacman orders them.
+
+use serde::Serialize;
+use std::cmp::Ordering;
+
+/// pacman's ordering of two `[epoch:]pkgver[-pkgrel]` strings.
+pub fn compare(a: &str, b: &str) -> Ordering {
+    // alpm::vercmp goes through a C string; a NUL byte can only come from a bad remote
+    if a.contains('\0') || b.contains('\0') {
+        return a.cmp(b);
+    }
+    alpm::vercmp(a, b)
+}
+
+/// For sort_by: highest version first.
+pub fn newest_first(a: &str, b: &str) -> Ordering {
+    compare(b, a)
+}
+
+/// What moving from `installed` to `candidate` would do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VersionChange {
+    Upgrade,
+    Downgrade,
+    Same,
+}
+
+pub fn classify(installed: &str, candidate: &str) -> VersionChange {
+    match compare(candidate, installed) {
+        Ordering::Greater => VersionChange::Upgrade,
+        Ordering::Less => VersionChange::Downgrade,
+        Ordering::Equal => VersionChange::Same,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_epoch_pkgrel_and_prerelease_ordering() {
+        assert_eq!(classify("2.0-1", "2.0-2"), VersionChange::Upgrade);
+        assert_eq!(classify("3.0-1", "1:2.0-1"), VersionChange::Upgrade);
+        assert_eq!(classify("1:2.0-1", "3.0-1"), VersionChange::Downgrade);
+        assert_eq!(classify("1.10-1", "1.9-1"), VersionChange::Downgrade);
+        assert_eq!(classify("1.0rc1-1", "1.0-1"), VersionChange::Upgrade);
+        assert_eq!(classify("1:2.0-1", "1:2.0-1"), VersionChange::Same);
+        // String inequality would call these different versions
+        assert_eq!(classify("2.0-1", "0:2.0-1"), VersionChange::Same);
+
+        let mut versions = vec!["1.9-1", "1:0.1-1", "1.10-1", "1.10-2"];
+        versions.sort_by(|a, b| newest_first(a, b));
+        assert_eq!(versions, vec!["1:0.1-1", "1.10-2", "1.10-1", "1.9-1"]);
+    }
+}
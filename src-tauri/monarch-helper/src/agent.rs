@@ -0,0 +1,357 @@
+//! Optional long-lived privileged agent (`monarch-helper --agent <config file>`). The GUI
+//! starts it through pkexec once; it binds a Unix socket only the invoking user can open,
+//! forks into the background and runs each request's command the way a one-shot helper
+//! would, so batches and retries don't ask for authentication again. Requests must come from
+//! that user (SO_PEERCRED) and carry the token from the config file. The agent exits after
+//! the idle timeout, on Shutdown, or when the user cancels a running command.
+
+use crate::{logger, outcome, progress, self_healer};
+use monarch_types::{
+    agent_socket_path, exit_code, AgentCall, AgentConfig, AgentRequest, AlpmProgressEvent,
+    HelperCommand, AGENT_SOCKET_DIR,
+};
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::time::{Duration, Instant};
+
+/// How often the accept loop looks at the idle timeout.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// A client that connects but doesn't send its request in time is dropped.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Shorter timeouts would make the agent pointless for a batch with a long build in between.
+const MIN_IDLE_TIMEOUT_SECS: u64 = 30;
+const MIN_TOKEN_LEN: usize = 32;
+
+fn event(event_type: &str, message: &str) -> String {
+    serde_json::to_string(&AlpmProgressEvent {
+        event_type: event_type.to_string(),
+        package: None,
+        percent: None,
+        downloaded: None,
+        total: None,
+        speed_bps: None,
+        eta_seconds: None,
+        message: message.to_string(),
+    })
+    .unwrap_or_default()
+}
+
+fn read_config(path: &str, owner: u32) -> Result<AgentConfig, String> {
+    let meta = std::fs::metadata(path).map_err(|e| format!("Agent config {}: {}", path, e))?;
+    // Same rule as command files: only a file of the invoking user is trusted
+    if meta.uid() != owner {
+        return Err("Agent config must be owned by the invoking user".to_string());
+    }
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    // It holds the token; nobody else gets to read it later
+    let _ = std::fs::remove_file(path);
+    let config: AgentConfig =
+        serde_json::from_str(text.trim()).map_err(|e| format!("Invalid agent config: {}", e))?;
+    if config.token.len() < MIN_TOKEN_LEN {
+        return Err("Agent token is too short".to_string());
+    }
+    Ok(config)
+}
+
+/// Bind the owner's socket, readable and writable by the owner only.
+fn bind(owner: u32) -> Result<(UnixListener, String), String> {
+    std::fs::create_dir_all(AGENT_SOCKET_DIR)
+        .map_err(|e| format!("Failed to create {}: {}", AGENT_SOCKET_DIR, e))?;
+    let _ = std::fs::set_permissions(AGENT_SOCKET_DIR, std::fs::Permissions::from_mode(0o755));
+    let path = agent_socket_path(owner);
+    // Left by an earlier agent; if that one is still alive it idles out on its own
+    let _ = std::fs::remove_file(&path);
+    let listener =
+        UnixListener::bind(&path).map_err(|e| format!("Failed to bind {}: {}", path, e))?;
+    let c_path = std::ffi::CString::new(path.clone()).map_err(|e| e.to_string())?;
+    if unsafe { libc::chown(c_path.as_ptr(), owner, libc::gid_t::MAX) } != 0 {
+        let _ = std::fs::remove_file(&path);
+        return Err(format!("Failed to hand {} to UID {}", path, owner));
+    }
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))
+        .map_err(|e| e.to_string())?;
+    Ok((listener, path))
+}
+
+fn peer_uid(stream: &UnixStream) -> Option<u32> {
+    let mut cred = libc::ucred {
+        pid: 0,
+        uid: 0,
+        gid: 0,
+    };
+    let mut len = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+    let rc = unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            &mut cred as *mut libc::ucred as *mut libc::c_void,
+            &mut len,
+        )
+    };
+    (rc == 0).then_some(cred.uid)
+}
+
+/// Constant-time comparison, so response timing says nothing about the token.
+pub fn token_matches(expected: &str, given: &str) -> bool {
+    expected.len() == given.len()
+        && expected
+            .bytes()
+            .zip(given.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+/// Entry point of `--agent`. Prints an `agent_ready` event and returns in the foreground
+/// process (which pkexec waits for) while a forked child keeps serving.
+pub fn start(config_path: &str) -> i32 {
+    if unsafe { libc::geteuid() } != 0 {
+        logger::error("The privileged agent must run as root");
+        return exit_code::NOT_ROOT;
+    }
+    let ready = crate::calling_uid()
+        .ok_or_else(|| "The privileged agent must be started through pkexec".to_string())
+        .and_then(|owner| {
+            let config = read_config(config_path, owner)?;
+            let (listener, socket) = bind(owner)?;
+            Ok((owner, config, listener, socket))
+        });
+    let (owner, config, listener, socket) = match ready {
+        Ok(ready) => ready,
+        Err(e) => {
+            logger::error(&format!("Privileged agent not started: {}", e));
+            println!("{}", event("progress", &format!("Error: {}", e)));
+            return exit_code::FAILED;
+        }
+    };
+
+    // Nothing buffered may be written twice by parent and child
+    let _ = std::io::stdout().flush();
+    match unsafe { libc::fork() } {
+        -1 => {
+            let _ = std::fs::remove_file(&socket);
+            println!("{}", event("progress", "Error: Failed to fork the agent"));
+            return exit_code::FAILED;
+        }
+        0 => {}
+        _ => {
+            println!("{}", event("agent_ready", &socket));
+            let _ = std::io::stdout().flush();
+            return exit_code::SUCCESS;
+        }
+    }
+
+    unsafe { libc::setsid() };
+    // Let go of pkexec's pipes so the GUI sees EOF; from here output goes to the helper log
+    match crate::redirect_streams() {
+        Ok(ipc_pipe) => drop(ipc_pipe),
+        Err(e) => logger::warn(&format!("Agent could not redirect output: {}", e)),
+    }
+    if let Ok(null) = std::fs::File::open("/dev/null") {
+        unsafe { libc::dup2(null.as_raw_fd(), 0) };
+    }
+    progress::init(std::io::sink());
+    logger::info(&format!(
+        "Privileged agent for UID {} listening on {}",
+        owner, socket
+    ));
+
+    let _pid_guard = crate::PidFileGuard;
+    let _ = std::fs::write(crate::HELPER_PID_FILE, std::process::id().to_string());
+    crate::spawn_cancel_watcher();
+    let bound = std::fs::metadata(&socket).map(|m| m.ino()).ok();
+    serve(listener, &config, owner);
+    // A newer agent may have replaced the socket; only remove our own
+    if std::fs::metadata(&socket).map(|m| m.ino()).ok() == bound {
+        let _ = std::fs::remove_file(&socket);
+    }
+    exit_code::SUCCESS
+}
+
+/// What the accept loop should do after a connection.
+enum Next {
+    Continue,
+    Run(HelperCommand, UnixStream),
+    Stop,
+}
+
+fn serve(listener: UnixListener, config: &AgentConfig, owner: u32) {
+    let idle = Duration::from_secs(config.idle_timeout_secs.max(MIN_IDLE_TIMEOUT_SECS));
+    if let Err(e) = listener.set_nonblocking(true) {
+        logger::error(&format!("Agent socket setup failed: {}", e));
+        return;
+    }
+    let mut last_request = Instant::now();
+    // Commands run on a worker so pings are still answered during a long transaction
+    let mut worker: Option<std::thread::JoinHandle<()>> = None;
+    loop {
+        if worker.as_ref().is_some_and(|w| w.is_finished()) {
+            if let Some(w) = worker.take() {
+                let _ = w.join();
+            }
+            last_request = Instant::now();
+        }
+        match listener.accept() {
+            Ok((stream, _)) => {
+                last_request = Instant::now();
+                match handle(stream, config, owner, worker.is_some()) {
+                    Next::Continue => {}
+                    Next::Run(cmd, stream) => {
+                        worker = Some(std::thread::spawn(move || run(cmd, stream)));
+                    }
+                    Next::Stop => break,
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                if worker.is_none() && last_request.elapsed() >= idle {
+                    logger::info("Privileged agent idle; exiting");
+                    break;
+                }
+                std::thread::sleep(POLL_INTERVAL);
+            }
+            Err(e) => {
+                logger::warn(&format!("Agent accept failed: {}", e));
+                std::thread::sleep(POLL_INTERVAL);
+            }
+        }
+    }
+    // A Shutdown during a transaction lets it finish rather than leaving the DB locked
+    if let Some(w) = worker {
+        let _ = w.join();
+    }
+}
+
+/// Serve one connection; a `Run` is handed back to be executed off the accept loop.
+fn handle(mut stream: UnixStream, config: &AgentConfig, owner: u32, busy: bool) -> Next {
+    let _ = stream.set_nonblocking(false);
+    match peer_uid(&stream) {
+        Some(uid) if uid == owner || uid == 0 => {}
+        uid => {
+            logger::warn(&format!("Agent refused a connection from UID {:?}", uid));
+            return Next::Continue;
+        }
+    }
+
+    let _ = stream.set_read_timeout(Some(REQUEST_TIMEOUT));
+    let mut line = String::new();
+    if BufReader::new(&stream).read_line(&mut line).is_err() {
+        return Next::Continue;
+    }
+    let request: AgentRequest = match serde_json::from_str(line.trim()) {
+        Ok(request) => request,
+        Err(e) => {
+            let message = format!("Error: Invalid agent request: {}", e);
+            let _ = writeln!(stream, "{}", event("progress", &message));
+            return Next::Continue;
+        }
+    };
+    if !token_matches(&config.token, &request.token) {
+        logger::warn("Agent refused a request with a wrong token");
+        let _ = writeln!(stream, "{}", event("agent_denied", "Wrong agent token"));
+        return Next::Continue;
+    }
+
+    match request.call {
+        AgentCall::Ping => {
+            let pid = std::process::id().to_string();
+            let _ = writeln!(stream, "{}", event("agent_ready", &pid));
+            Next::Continue
+        }
+        AgentCall::Shutdown => {
+            logger::info("Privileged agent stopped by the GUI");
+            Next::Stop
+        }
+        AgentCall::Run(_) if busy => {
+            let message = "Error: The privileged agent is busy with another operation";
+            let _ = writeln!(stream, "{}", event("agent_busy", message));
+            Next::Continue
+        }
+        AgentCall::Run(cmd) => {
+            let _ = stream.set_read_timeout(None);
+            Next::Run(cmd, stream)
+        }
+    }
+}
+
+/// Run `cmd` with its progress and final `transaction_result` going to `stream`.
+fn run(cmd: HelperCommand, stream: UnixStream) {
+    progress::set_output(stream);
+    execute(cmd);
+    progress::set_output(std::io::sink());
+}
+
+/// Run `cmd` as a one-shot helper would, up to and including its `transaction_result`
+/// event, on the current progress output. Shared with `--rpc`.
+pub fn execute(cmd: HelperCommand) -> monarch_types::TransactionResult {
+    let work = move || match crate::init_alpm() {
+        Ok(mut alpm) => crate::execute_command(cmd, &mut alpm),
+        Err(e) => {
+            outcome::fail(&e.to_string());
+            crate::emit_progress(0, &format!("Error: {}", e));
+        }
+    };
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(work));
+    if let Err(payload) = result {
+        let msg = crate::panic_message(payload);
+        logger::panic_msg(&msg);
+        outcome::fail(&msg);
+        crate::emit_progress(0, &format!("Error: {}", msg));
+        if std::path::Path::new(self_healer::DB_LOCK_PATH).exists() {
+            let _ = std::fs::remove_file(self_healer::DB_LOCK_PATH);
+        }
+    }
+    // Sends the result and waits until it is written
+    outcome::finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_agent_token_and_events() {
+        let token = "a".repeat(64);
+        assert!(token_matches(&token, &"a".repeat(64)));
+        assert!(!token_matches(&token, &"a".repeat(63)));
+        assert!(!token_matches(&token, &format!("{}b", "a".repeat(63))));
+
+        let ready: AlpmProgressEvent =
+            serde_json::from_str(&event("agent_ready", "/run/monarch-store/agent-1000.sock"))
+                .unwrap();
+        assert_eq!(ready.event_type, "agent_ready");
+        assert!(!ready.is_error());
+    }
+
+    #[test]
+    fn test_agent_answers_ping_while_busy() {
+        let owner = unsafe { libc::getuid() };
+        let config = AgentConfig {
+            token: "t".repeat(MIN_TOKEN_LEN),
+            idle_timeout_secs: MIN_IDLE_TIMEOUT_SECS,
+        };
+        let reply = |call: AgentCall| {
+            let (mut client, server) = UnixStream::pair().unwrap();
+            let request = AgentRequest {
+                token: config.token.clone(),
+                call,
+            };
+            writeln!(client, "{}", serde_json::to_string(&request).unwrap()).unwrap();
+            let next = handle(server, &config, owner, true);
+            let mut line = String::new();
+            BufReader::new(&client).read_line(&mut line).unwrap();
+            let event: AlpmProgressEvent = serde_json::from_str(line.trim()).unwrap();
+            (next, event.event_type)
+        };
+
+        let (next, event_type) = reply(AgentCall::Ping);
+        assert!(matches!(next, Next::Continue));
+        assert_eq!(event_type, "agent_ready");
+        let (next, event_type) = reply(AgentCall::Run(HelperCommand::AlpmSync {
+            enabled_repos: Vec::new(),
+        }));
+        assert!(matches!(next, Next::Continue));
+        assert_eq!(event_type, "agent_busy");
+    }
+}
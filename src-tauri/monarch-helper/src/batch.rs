@@ -0,0 +1,268 @@
+//! ExecuteBatch as a list of steps with a checkpoint in [`BATCH_STATE_PATH`], rewritten
+//! before and after every step. A batch cut short (power loss mid-upgrade, a crash, a
+//! failed step) leaves its state behind: the GUI reads the file to show what happened and
+//! ResumeBatch runs the steps that did not finish. Dry runs are not checkpointed.
+
+use alpm::Alpm;
+use monarch_types::{
+    AlpmProgressEvent, BatchState, BatchStep, BatchStepStatus, TransactionManifest,
+    BATCH_STATE_PATH,
+};
+use std::path::Path;
+
+use crate::keyring::{self, ThirdPartyRepo};
+use crate::{cache_gc, emit_progress, logger, outcome, progress, transactions};
+
+fn now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Write the checkpoint. It only names packages, so it is world-readable for the GUI.
+fn save(state: &BatchState) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = Path::new(BATCH_STATE_PATH);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(state).map_err(|e| e.to_string())?;
+    let tmp = format!("{}.tmp", BATCH_STATE_PATH);
+    std::fs::write(&tmp, json).map_err(|e| format!("Failed to write {}: {}", tmp, e))?;
+    std::fs::set_permissions(&tmp, std::fs::Permissions::from_mode(0o644))
+        .map_err(|e| format!("Failed to set permissions on {}: {}", tmp, e))?;
+    // Durable before the step it describes starts changing the system
+    if let Ok(file) = std::fs::File::open(&tmp) {
+        let _ = file.sync_all();
+    }
+    std::fs::rename(&tmp, BATCH_STATE_PATH)
+        .map_err(|e| format!("Failed to update {}: {}", BATCH_STATE_PATH, e))
+}
+
+/// The last batch's checkpoint, if there is a readable one.
+pub fn load() -> Option<BatchState> {
+    let content = std::fs::read_to_string(BATCH_STATE_PATH).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(state) => Some(state),
+        Err(e) => {
+            logger::warn(&format!("Ignoring {}: {}", BATCH_STATE_PATH, e));
+            None
+        }
+    }
+}
+
+/// Record a step's status; a checkpoint that can't be written is logged, not fatal. The GUI
+/// gets the step list in a `batch_state` event, so it can follow a resume as it runs.
+fn checkpoint(
+    state: &mut BatchState,
+    step: BatchStep,
+    status: BatchStepStatus,
+    error: Option<String>,
+) {
+    state.set_status(step, status, error, now());
+    if let Err(e) = save(state) {
+        logger::warn(&format!("Batch checkpoint not saved: {}", e));
+    }
+    let event = AlpmProgressEvent {
+        event_type: "batch_state".to_string(),
+        package: None,
+        percent: None,
+        downloaded: None,
+        total: None,
+        speed_bps: None,
+        eta_seconds: None,
+        message: serde_json::to_string(&state.steps).unwrap_or_else(|_| "[]".to_string()),
+    };
+    if let Ok(json) = serde_json::to_string(&event) {
+        progress::send_progress_line(json);
+    }
+}
+
+/// Run a new batch.
+pub fn execute(alpm: &mut Alpm, manifest: TransactionManifest, dry_run: bool) {
+    // Operation "Silent Guard": Execute all steps under ONE lock acquisition
+    // Dry run: maintenance steps are skipped and each step below is prepared on its
+    // own against the current system, so step 4 does not see step 3's removals.
+
+    // 0a. Remove Stale Lock (Pre-transaction maintenance). A held lock stays; the steps
+    // below then fail on it with pacman's own message.
+    if manifest.remove_lock && !dry_run {
+        if let Err(e) = crate::remove_lock() {
+            logger::warn(&e);
+        }
+    }
+
+    // 0b. Clear Cache (Maintenance)
+    if manifest.clear_cache && !dry_run {
+        let clear_all = cache_gc::RetentionPolicy {
+            keep: 0,
+            keep_installed: false,
+            uninstalled_only: false,
+        };
+        let _ = cache_gc::run(alpm, clear_all, false);
+    }
+
+    let missing_keyrings = keyring::missing_keyrings(alpm, &manifest.keyring_repos);
+    let state = BatchState::new(manifest, !missing_keyrings.is_empty(), now());
+    if state.steps.is_empty() {
+        emit_progress(100, "Transaction successful (No actions required)");
+        return;
+    }
+    run(alpm, state, dry_run);
+}
+
+/// Finish the batch left in the checkpoint. Steps already done are not repeated; packages
+/// a failed removal did take out and local packages whose file is gone are left alone.
+pub fn resume(alpm: &mut Alpm) {
+    let Some(mut state) = load().filter(|s| !s.is_complete()) else {
+        emit_progress(100, "Nothing to resume: the last batch finished.");
+        return;
+    };
+    logger::info(&format!(
+        "Resuming batch from {} ({} step(s) left)",
+        state.started_at,
+        state.remaining().len()
+    ));
+    // A helper killed mid-commit leaves pacman's lock behind; one still held means another
+    // package manager, agent or helper is busy, and resuming now would race it.
+    if let Err(e) = crate::remove_lock() {
+        outcome::fail(&e);
+        emit_progress(0, &format!("Error: {}", e));
+        return;
+    }
+    state.resumed += 1;
+    let manifest = &mut state.manifest;
+    manifest
+        .remove_targets
+        .retain(|name| alpm.localdb().pkg(name.as_str()).is_ok());
+    let (present, gone): (Vec<String>, Vec<String>) = manifest
+        .local_paths
+        .drain(..)
+        .partition(|path| Path::new(path).exists());
+    manifest.local_paths = present;
+    if !gone.is_empty() {
+        outcome::skip(
+            &gone,
+            "Not attempted: the built package file no longer exists",
+        );
+    }
+    run(alpm, state, false);
+}
+
+/// Targets of the steps after `step`, which a failure of `step` leaves unattempted.
+fn later_targets(state: &BatchState, step: BatchStep) -> Vec<String> {
+    let m = &state.manifest;
+    match step {
+        BatchStep::Keyrings | BatchStep::RefreshDb | BatchStep::Upgrade => m
+            .remove_targets
+            .iter()
+            .chain(&m.install_targets)
+            .cloned()
+            .collect(),
+        BatchStep::Remove => m.install_targets.clone(),
+        BatchStep::Install | BatchStep::InstallLocal => Vec::new(),
+    }
+}
+
+fn run(alpm: &mut Alpm, mut state: BatchState, dry_run: bool) {
+    let mut missing_keyrings = keyring::missing_keyrings(alpm, &state.manifest.keyring_repos);
+    for step in state.remaining() {
+        if !dry_run {
+            checkpoint(&mut state, step, BatchStepStatus::Running, None);
+        }
+        let result = run_step(alpm, &state.manifest, step, &mut missing_keyrings, dry_run);
+        if let Err(e) = result {
+            // A failed step stops the batch; the targets of the steps after it are
+            // reported as skipped rather than failed.
+            outcome::fail(&e);
+            let reason = format!("Not attempted: {} failed", step.label());
+            outcome::skip(&later_targets(&state, step), &reason);
+            if !dry_run {
+                checkpoint(&mut state, step, BatchStepStatus::Failed, Some(e.clone()));
+            }
+            emit_progress(0, &format!("Error {}: {}", step.label(), e));
+            return;
+        }
+        if !dry_run {
+            checkpoint(&mut state, step, BatchStepStatus::Done, None);
+        }
+    }
+
+    if dry_run {
+        emit_progress(100, "Batch dry run complete. Nothing was changed.");
+    } else {
+        emit_progress(100, "Batch Transaction Complete");
+    }
+}
+
+fn run_step(
+    alpm: &mut Alpm,
+    manifest: &TransactionManifest,
+    step: BatchStep,
+    missing_keyrings: &mut Vec<&'static ThirdPartyRepo>,
+    dry_run: bool,
+) -> Result<(), String> {
+    match step {
+        // Third-party keyrings: a newly enabled repo's DB and keyring package only verify
+        // once its signing key is trusted. Then refresh, and install and populate the
+        // keyrings before anything from those repos. The install upgrades the system in
+        // the same transaction (sync_first), as installing right after a refresh without
+        // it is a partial upgrade.
+        BatchStep::Keyrings => {
+            if missing_keyrings.is_empty() {
+                return Ok(());
+            }
+            if !dry_run {
+                keyring::trust_signing_keys(missing_keyrings)?;
+            }
+            transactions::force_refresh_sync_dbs(alpm)?;
+            let keyring_pkgs: Vec<String> = missing_keyrings
+                .iter()
+                .map(|r| r.keyring_package.to_string())
+                .collect();
+            transactions::execute_alpm_install(
+                keyring_pkgs,
+                true,
+                None,
+                None,
+                &transactions::TransactionPhase::Full,
+                dry_run,
+                alpm,
+            )?;
+            if !dry_run {
+                keyring::populate(missing_keyrings)?;
+            }
+            missing_keyrings.clear();
+            Ok(())
+        }
+        BatchStep::RefreshDb => transactions::force_refresh_sync_dbs(alpm),
+        BatchStep::Upgrade => transactions::execute_alpm_upgrade(None, dry_run, alpm),
+        // Steps below run one after another under this process's single ALPM handle, so
+        // the lock is held for the whole batch.
+        BatchStep::Remove if manifest.remove_targets.is_empty() => Ok(()),
+        BatchStep::Remove => transactions::execute_alpm_uninstall(
+            manifest.remove_targets.clone(),
+            true,
+            dry_run,
+            alpm,
+        ),
+        // sync_first false because the refresh is its own step
+        BatchStep::Install => transactions::execute_alpm_install(
+            manifest.install_targets.clone(),
+            false,
+            None,
+            None,
+            &transactions::TransactionPhase::Full,
+            dry_run,
+            alpm,
+        ),
+        BatchStep::InstallLocal if manifest.local_paths.is_empty() => Ok(()),
+        // Built AUR packages
+        BatchStep::InstallLocal => {
+            transactions::execute_alpm_install_files(manifest.local_paths.clone(), dry_run, alpm)
+        }
+    }
+}
@@ -0,0 +1,206 @@
+//! Package cache garbage collection (CleanPackageCache). The retention plan comes from
+//! `monarch_types::cache_gc`; this side deletes. Signatures go with their package; `.part`
+//! downloads are left to the transaction code.
+
+use alpm::Alpm;
+pub use monarch_types::cache_gc::RetentionPolicy;
+use monarch_types::cache_gc::{plan, scan, CACHE_PKG_DIR};
+use monarch_types::{AlpmProgressEvent, CacheCleanReport, CachedPackageFile};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::logger;
+
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    if a.contains('\0') || b.contains('\0') {
+        return a.cmp(b);
+    }
+    alpm::vercmp(a, b)
+}
+
+/// Run the cleanup on the cache directory and report it as a `cache_clean_report` event.
+pub fn run(
+    alpm: &Alpm,
+    policy: RetentionPolicy,
+    dry_run: bool,
+) -> Result<CacheCleanReport, String> {
+    // pacman may be downloading into the cache right now
+    if Path::new(crate::self_healer::DB_LOCK_PATH).exists() {
+        return Err("The package database is locked by another process".to_string());
+    }
+    let installed: HashMap<String, String> = alpm
+        .localdb()
+        .pkgs()
+        .iter()
+        .map(|p| (p.name().to_string(), p.version().to_string()))
+        .collect();
+    crate::emit_progress(10, "Scanning package cache...");
+    let (removed, kept) = plan(
+        scan(Path::new(CACHE_PKG_DIR)),
+        &installed,
+        policy,
+        compare_versions,
+    );
+
+    let mut report = CacheCleanReport {
+        dry_run,
+        kept: kept.len(),
+        ..Default::default()
+    };
+    for entry in removed {
+        if !dry_run {
+            if let Err(e) = std::fs::remove_file(&entry.path) {
+                logger::warn(&format!("Could not remove {}: {}", entry.path.display(), e));
+                report.kept += 1;
+                continue;
+            }
+            let mut sig = entry.path.clone().into_os_string();
+            sig.push(".sig");
+            let _ = std::fs::remove_file(sig);
+        }
+        report.reclaimed_bytes += entry.size;
+        report.removed.push(CachedPackageFile {
+            name: entry.name,
+            version: entry.version,
+            path: entry.path.to_string_lossy().into_owned(),
+            size: entry.size,
+        });
+    }
+
+    let event = AlpmProgressEvent {
+        event_type: "cache_clean_report".to_string(),
+        package: None,
+        percent: None,
+        downloaded: None,
+        total: Some(report.reclaimed_bytes),
+        speed_bps: None,
+        eta_seconds: None,
+        message: serde_json::to_string(&report).unwrap_or_default(),
+    };
+    if let Ok(json) = serde_json::to_string(&event) {
+        crate::progress::send_progress_line(json);
+    }
+    let mib = report.reclaimed_bytes as f64 / (1024.0 * 1024.0);
+    crate::emit_progress(
+        100,
+        &if dry_run {
+            format!(
+                "{} cached package(s) could be removed, freeing {:.1} MiB",
+                report.removed.len(),
+                mib
+            )
+        } else {
+            format!(
+                "Removed {} cached package(s), freed {:.1} MiB",
+                report.removed.len(),
+                mib
+            )
+        },
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use monarch_types::cache_gc::{parse_package_filename, CacheEntry};
+    use std::path::PathBuf;
+
+    fn entry(name: &str, version: &str) -> CacheEntry {
+        CacheEntry {
+            name: name.to_string(),
+            version: version.to_string(),
+            arch: "x86_64".to_string(),
+            path: PathBuf::from(format!("{}-{}-x86_64.pkg.tar.zst", name, version)),
+            size: 10,
+        }
+    }
+
+    fn names(entries: &[CacheEntry]) -> Vec<String> {
+        let mut v: Vec<String> = entries
+            .iter()
+            .map(|e| format!("{}-{}", e.name, e.version))
+            .collect();
+        v.sort();
+        v
+    }
+
+    #[test]
+    fn test_cache_retention() {
+        assert_eq!(
+            parse_package_filename("lib32-glibc-2.40+r16-1-x86_64.pkg.tar.zst"),
+            Some(("lib32-glibc".into(), "2.40+r16-1".into(), "x86_64".into()))
+        );
+        assert_eq!(
+            parse_package_filename("vim-2:9.1.0-1-x86_64.pkg.tar.xz"),
+            Some(("vim".into(), "2:9.1.0-1".into(), "x86_64".into()))
+        );
+        assert_eq!(
+            parse_package_filename("vim-9.1-1-x86_64.pkg.tar.zst.sig"),
+            None
+        );
+        assert_eq!(
+            parse_package_filename("vim-9.1-1-x86_64.pkg.tar.zst.part"),
+            None
+        );
+        assert_eq!(parse_package_filename("x86_64.pkg.tar.zst"), None);
+
+        let entries = vec![
+            entry("vim", "9.1.0-1"),
+            entry("vim", "9.1.2-1"),
+            entry("vim", "9.0.0-3"),
+            entry("vim", "1:8.0-1"),
+            entry("gone", "1.0-1"),
+            entry("gone", "1.1-1"),
+        ];
+        let installed = HashMap::from([("vim".to_string(), "9.1.0-1".to_string())]);
+
+        // keep 1: the newest vim (an epoch sorts ahead) plus the installed one
+        let (removed, kept) = plan(
+            entries.clone(),
+            &installed,
+            RetentionPolicy {
+                keep: 1,
+                keep_installed: true,
+                uninstalled_only: false,
+            },
+            compare_versions,
+        );
+        assert_eq!(
+            names(&kept),
+            vec!["gone-1.1-1", "vim-1:8.0-1", "vim-9.1.0-1"]
+        );
+        assert_eq!(
+            names(&removed),
+            vec!["gone-1.0-1", "vim-9.0.0-3", "vim-9.1.2-1"]
+        );
+
+        // uninstalled only: vim is left alone
+        let (removed, _) = plan(
+            entries.clone(),
+            &installed,
+            RetentionPolicy {
+                keep: 0,
+                keep_installed: true,
+                uninstalled_only: true,
+            },
+            compare_versions,
+        );
+        assert_eq!(names(&removed), vec!["gone-1.0-1", "gone-1.1-1"]);
+
+        // clearing everything
+        let (removed, kept) = plan(
+            entries,
+            &installed,
+            RetentionPolicy {
+                keep: 0,
+                keep_installed: false,
+                uninstalled_only: false,
+            },
+            compare_versions,
+        );
+        assert_eq!(removed.len(), 6);
+        assert!(kept.is_empty());
+    }
+}
@@ -0,0 +1,68 @@
+//! Config file restores (RestoreConfigFile). Only files listed in the backup array of an
+//! installed package can be written, and only from a copy staged in /tmp/monarch-install, so
+//! the GUI can't use this to overwrite arbitrary system files.
+
+use alpm::Alpm;
+use std::os::unix::fs::PermissionsExt;
+
+use crate::logger;
+
+const ALLOWED_SOURCE_PREFIX: &str = "/tmp/monarch-install";
+
+/// Absolute, normalized path (no `..`, `.` or empty components).
+pub fn valid_target(path: &str) -> bool {
+    path.starts_with('/')
+        && path.len() <= 4096
+        && path[1..]
+            .split('/')
+            .all(|c| !c.is_empty() && c != "." && c != "..")
+        && !path.chars().any(|c| c.is_control())
+}
+
+/// The installed package whose backup array lists `path`.
+fn backup_owner(alpm: &Alpm, path: &str) -> Option<String> {
+    let relative = path.strip_prefix('/')?;
+    alpm.localdb()
+        .pkgs()
+        .iter()
+        .find(|pkg| pkg.backup().iter().any(|b| b.name() == relative))
+        .map(|pkg| pkg.name().to_string())
+}
+
+/// Replace `path` with the staged `source`, keeping the current file as `<path>.monarch-bak`
+/// and its permissions on the restored one.
+pub fn restore_config_file(alpm: &Alpm, path: &str, source: &str) -> Result<(), String> {
+    if !valid_target(path) {
+        return Err(format!("Invalid config path: {}", path));
+    }
+    let owner = backup_owner(alpm, path)
+        .ok_or_else(|| format!("{} is not a config file of an installed package", path))?;
+
+    let prefix = std::fs::canonicalize(ALLOWED_SOURCE_PREFIX)
+        .unwrap_or_else(|_| std::path::PathBuf::from(ALLOWED_SOURCE_PREFIX));
+    let source = std::fs::canonicalize(source)
+        .map_err(|_| format!("Path not found or invalid: {}", source))?;
+    if !source.starts_with(&prefix) {
+        return Err(format!(
+            "Unauthorized source for RestoreConfigFile (only {}/ allowed)",
+            ALLOWED_SOURCE_PREFIX
+        ));
+    }
+
+    let mode = std::fs::metadata(path)
+        .map(|m| m.permissions().mode())
+        .unwrap_or(0o644);
+    if std::path::Path::new(path).exists() {
+        let backup = format!("{}.monarch-bak", path);
+        std::fs::copy(path, &backup).map_err(|e| format!("Failed to back up {}: {}", path, e))?;
+    }
+    // Write-then-rename so nothing reads a half-written file
+    let tmp = format!("{}.monarch-tmp", path);
+    std::fs::copy(&source, &tmp).map_err(|e| format!("Failed to write {}: {}", tmp, e))?;
+    std::fs::set_permissions(&tmp, std::fs::Permissions::from_mode(mode))
+        .map_err(|e| format!("Failed to set permissions on {}: {}", tmp, e))?;
+    std::fs::rename(&tmp, path).map_err(|e| format!("Failed to update {}: {}", path, e))?;
+    let _ = std::fs::remove_file(&source);
+    logger::info(&format!("Restored {} (owned by {})", path, owner));
+    Ok(())
+}
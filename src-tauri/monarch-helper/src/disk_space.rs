@@ -0,0 +1,81 @@
+//! Free-space preflight between prepare and commit. libalpm's own check only runs once
+//! the packages are downloaded, and a cache that fills up mid-download leaves partial
+//! files behind. Here the prepared transaction's download size is checked against the
+//! cache's filesystem and its net installed size against the root's (the two add up when
+//! they share one), and a transaction that does not fit is released untouched.
+
+use alpm::Alpm;
+use monarch_types::disk_space::{filesystem, shortfall_message, SpaceCheck};
+use std::path::Path;
+
+use crate::logger;
+
+const CACHE_PKG_DIR: &str = "/var/cache/pacman/pkg";
+
+/// Bytes still to download, and the growth of the installed size (new packages and
+/// upgrades minus what they replace and what is removed).
+fn requirements(alpm: &Alpm) -> (u64, u64) {
+    let mut download = 0u64;
+    let mut installed = 0i64;
+    for pkg in alpm.trans_add() {
+        download += pkg.download_size().max(0) as u64;
+        installed += pkg.isize();
+        if let Ok(old) = alpm.localdb().pkg(pkg.name()) {
+            installed -= old.isize();
+        }
+    }
+    for pkg in alpm.trans_remove() {
+        installed -= pkg.isize();
+    }
+    (download, installed.max(0) as u64)
+}
+
+/// Where the prepared transaction writes and how much, one entry per filesystem.
+fn checks(alpm: &Alpm, download_only: bool) -> Vec<SpaceCheck> {
+    let (download, installed) = requirements(alpm);
+    let cache = alpm
+        .cachedirs()
+        .iter()
+        .next()
+        .unwrap_or(CACHE_PKG_DIR)
+        .to_string();
+    let mut wanted = vec![(cache, download)];
+    if !download_only {
+        wanted.push((alpm.root().to_string(), installed));
+    }
+
+    let mut checks: Vec<(u64, SpaceCheck)> = Vec::new();
+    for (path, required) in wanted {
+        let Some(fs) = filesystem(Path::new(&path)) else {
+            logger::warn(&format!("Disk space preflight: cannot stat {}", path));
+            continue;
+        };
+        match checks.iter_mut().find(|(d, _)| *d == fs.dev) {
+            Some((_, check)) => check.required += required,
+            None => checks.push((
+                fs.dev,
+                SpaceCheck {
+                    path,
+                    required,
+                    available: fs.available,
+                    tmpfs: fs.tmpfs,
+                },
+            )),
+        }
+    }
+    checks.into_iter().map(|(_, check)| check).collect()
+}
+
+/// Refuse a prepared transaction that does not fit. The transaction is released, so a
+/// batch can go on to its next step.
+pub fn preflight(alpm: &mut Alpm, download_only: bool) -> Result<(), String> {
+    let checks = checks(alpm, download_only);
+    match shortfall_message(&checks) {
+        None => Ok(()),
+        Some(msg) => {
+            logger::warn(&msg);
+            let _ = alpm.trans_release();
+            Err(msg)
+        }
+    }
+}
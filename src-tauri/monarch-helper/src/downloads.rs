@@ -0,0 +1,428 @@
+//! Mirror failover and resumable package downloads. libalpm already tries each server of a
+//! repo in turn for every file and resumes the `.part` files it leaves in the cache. What it
+//! doesn't do is go round again once every server failed, which on a flaky mirror is enough
+//! to fail the whole transaction, or say which mirror it is using. `commit_with_failover`
+//! fills both gaps; the `.part` files are kept between rounds (and between runs) so a retry
+//! picks up where the last attempt stopped. A mirror that keeps failing gets its
+//! mirrorlist re-ranked, after which the transaction is tried once more.
+
+use alpm::Alpm;
+use monarch_types::AlpmProgressEvent;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use crate::logger;
+use crate::mirrors;
+use crate::progress;
+
+/// Rounds over the server lists before a download failure is final.
+pub const MIRROR_ROUNDS: usize = 3;
+
+/// Pause between rounds, so a mirror that dropped out briefly can come back.
+const ROUND_DELAY: Duration = Duration::from_secs(2);
+
+/// Partial downloads older than this are not worth resuming.
+const PARTIAL_MAX_AGE: Duration = Duration::from_secs(3 * 24 * 60 * 60);
+
+const CACHE_PKG_DIR: &str = "/var/cache/pacman/pkg";
+
+/// Failed files from one mirror within a commit before its mirrorlist is re-ranked.
+pub const MIRROR_STRIKES: usize = 3;
+
+/// Failures per mirror host since the commit started, and the first host to reach
+/// [`MIRROR_STRIKES`].
+struct MirrorStrikes {
+    counts: Vec<(String, usize)>,
+    struck: Option<String>,
+}
+
+impl MirrorStrikes {
+    const fn new() -> Self {
+        Self {
+            counts: Vec::new(),
+            struck: None,
+        }
+    }
+
+    fn record(&mut self, host: &str) {
+        let count = match self.counts.iter_mut().find(|(h, _)| h == host) {
+            Some((_, count)) => {
+                *count += 1;
+                *count
+            }
+            None => {
+                self.counts.push((host.to_string(), 1));
+                1
+            }
+        };
+        if count >= MIRROR_STRIKES && self.struck.is_none() {
+            self.struck = Some(host.to_string());
+        }
+    }
+}
+
+static STRIKES: Mutex<MirrorStrikes> = Mutex::new(MirrorStrikes::new());
+
+fn emit(event_type: &str, repo: Option<&str>, message: String) {
+    let event = AlpmProgressEvent {
+        event_type: event_type.to_string(),
+        package: repo.map(str::to_string),
+        percent: None,
+        downloaded: None,
+        total: None,
+        speed_bps: None,
+        eta_seconds: None,
+        message,
+    };
+    if let Ok(json) = serde_json::to_string(&event) {
+        progress::send_progress_line(json);
+    }
+}
+
+/// Errors from trans_commit that mean files could not be fetched (as opposed to conflicts,
+/// bad signatures, ...), so another round over the mirrors may help.
+pub fn is_download_failure(err: &str) -> bool {
+    let lower = err.to_lowercase();
+    lower.contains("failed to retrieve some files")
+        || lower.contains("failed retrieving file")
+        || lower.contains("download library error")
+}
+
+/// Host of a server URL, for messages; the URL itself if it has none.
+pub fn mirror_host(url: &str) -> &str {
+    let rest = url.split_once("://").map(|(_, r)| r).unwrap_or(url);
+    match rest.split('/').next() {
+        Some(host) if !host.is_empty() => host,
+        _ => url,
+    }
+}
+
+/// libalpm's "failed retrieving file 'NAME' from HOST : REASON" -> (NAME, HOST).
+pub fn parse_failed_retrieval(msg: &str) -> Option<(&str, &str)> {
+    let rest = msg.trim().strip_prefix("failed retrieving file '")?;
+    let (file, rest) = rest.split_once("' from ")?;
+    let host = rest.split(" : ").next()?.trim();
+    if file.is_empty() || host.is_empty() {
+        return None;
+    }
+    Some((file, host))
+}
+
+/// Called with every libalpm log line: a mirror that failed for a file becomes a
+/// `mirror_failed` event (libalpm moves on to the repo's next server by itself).
+pub fn note_log_line(msg: &str) {
+    if let Some((file, host)) = parse_failed_retrieval(msg) {
+        logger::warn(msg.trim());
+        // The reason stays in the log; it often says "error", which the GUI reads as fatal
+        emit(
+            "mirror_failed",
+            None,
+            format!("{} failed for {}; trying the next mirror", host, file),
+        );
+        if let Ok(mut strikes) = STRIKES.lock() {
+            strikes.record(host);
+        }
+    }
+}
+
+/// Server list with the first entry moved to the back.
+pub fn rotated(servers: &[String]) -> Vec<String> {
+    let mut next = servers.to_vec();
+    if next.len() > 1 {
+        next.rotate_left(1);
+    }
+    next
+}
+
+/// Repos the prepared transaction downloads from.
+fn download_repos(alpm: &Alpm) -> Vec<String> {
+    let mut repos: Vec<String> = Vec::new();
+    for pkg in alpm.trans_add() {
+        if pkg.download_size() <= 0 {
+            continue;
+        }
+        if let Some(db) = pkg.db() {
+            if !repos.iter().any(|r| r == db.name()) {
+                repos.push(db.name().to_string());
+            }
+        }
+    }
+    repos
+}
+
+/// A `mirror` event per repo: the server its downloads start from.
+fn announce_mirrors(alpm: &Alpm, repos: &[String]) {
+    for db in alpm.syncdbs() {
+        if !repos.iter().any(|r| r == db.name()) {
+            continue;
+        }
+        if let Some(server) = db.servers().iter().next() {
+            emit(
+                "mirror",
+                Some(db.name()),
+                format!(
+                    "Downloading {} packages from {}",
+                    db.name(),
+                    mirror_host(server)
+                ),
+            );
+        }
+    }
+}
+
+/// Start each of `repos` that has more than one server on its next one.
+fn rotate_servers(alpm: &mut Alpm, repos: &[String]) {
+    for db in alpm.syncdbs_mut() {
+        if !repos.iter().any(|r| r == db.name()) {
+            continue;
+        }
+        let servers: Vec<String> = db.servers().iter().map(str::to_string).collect();
+        if servers.len() < 2 {
+            continue;
+        }
+        if let Err(e) = db.set_servers(rotated(&servers)) {
+            logger::warn(&format!(
+                "Could not reorder servers of {}: {}",
+                db.name(),
+                e
+            ));
+        }
+    }
+}
+
+/// Servers of a repo as pacman.conf now resolves them (`$repo` and `$arch` filled in).
+fn configured_servers(repo: &str) -> Vec<String> {
+    std::process::Command::new("pacman-conf")
+        .args(["--repo", repo])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| {
+            crate::pacman_conf::parse_repo_details(&String::from_utf8_lossy(&o.stdout)).servers
+        })
+        .unwrap_or_default()
+}
+
+/// Re-rank the mirrorlists behind the `repos` that use `host`, then load their new servers
+/// into the handle. A `mirror_switch` event per repo names the old and new mirror.
+/// Returns how many repos were switched.
+fn rerank_mirrors(alpm: &mut Alpm, repos: &[String], host: &str) -> usize {
+    let mut keys: Vec<&'static str> = Vec::new();
+    for db in alpm.syncdbs() {
+        if !repos.iter().any(|r| r == db.name())
+            || !db.servers().iter().any(|s| mirror_host(s) == host)
+        {
+            continue;
+        }
+        if let Some(key) = mirrors::mirrorlist_key(db.name()) {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+    }
+
+    let mut ranked: Vec<&'static str> = Vec::new();
+    for key in keys {
+        emit(
+            "progress",
+            None,
+            format!("{} keeps failing; re-ranking the {} mirrors...", host, key),
+        );
+        match mirrors::rerank_mirrorlist(key, host) {
+            Ok(tool) => {
+                logger::info(&format!("Re-ranked {} mirrors with {}", key, tool));
+                ranked.push(key);
+            }
+            Err(e) => logger::warn(&format!("Could not re-rank {} mirrors: {}", key, e)),
+        }
+    }
+
+    let mut switched = 0;
+    for db in alpm.syncdbs_mut() {
+        let name = db.name().to_string();
+        if !repos.contains(&name)
+            || !mirrors::mirrorlist_key(&name).is_some_and(|k| ranked.contains(&k))
+        {
+            continue;
+        }
+        let servers = configured_servers(&name);
+        let Some(first) = servers.first().cloned() else {
+            continue;
+        };
+        if let Err(e) = db.set_servers(servers) {
+            logger::warn(&format!("Could not reload servers of {}: {}", name, e));
+            continue;
+        }
+        switched += 1;
+        emit(
+            "mirror_switch",
+            Some(&name),
+            format!("Switched {} from {} to {}", name, host, mirror_host(&first)),
+        );
+    }
+    switched
+}
+
+/// trans_commit, going round the mirrors again (up to [`MIRROR_ROUNDS`] times in all) when
+/// it fails to fetch files. A download failure leaves the transaction prepared, so the same
+/// transaction is committed again; finished files are in the cache and partial ones resume.
+/// Once a mirror has failed [`MIRROR_STRIKES`] files, its mirrorlist is re-ranked with the
+/// installed ranking tool and the transaction gets one more try on the new mirrors.
+pub fn commit_with_failover(alpm: &mut Alpm) -> Result<(), String> {
+    let repos = download_repos(alpm);
+    announce_mirrors(alpm, &repos);
+    if let Ok(mut strikes) = STRIKES.lock() {
+        *strikes = MirrorStrikes::new();
+    }
+    let mut round = 1;
+    let mut reranked = false;
+    loop {
+        let err = match alpm.trans_commit() {
+            Ok(_) => return Ok(()),
+            Err(e) => e.to_string(),
+        };
+        if reranked || repos.is_empty() || !is_download_failure(&err) {
+            return Err(err);
+        }
+        let struck = STRIKES.lock().ok().and_then(|mut s| s.struck.take());
+        if let Some(host) = struck {
+            if rerank_mirrors(alpm, &repos, &host) > 0 {
+                reranked = true;
+                announce_mirrors(alpm, &repos);
+                continue;
+            }
+        }
+        if round >= MIRROR_ROUNDS {
+            return Err(err);
+        }
+        round += 1;
+        logger::warn(&format!(
+            "Download failed ({}); mirror round {} of {}",
+            err, round, MIRROR_ROUNDS
+        ));
+        // A repo with a single server retries it; that still helps when it only hiccuped
+        rotate_servers(alpm, &repos);
+        emit(
+            "progress",
+            None,
+            format!(
+                "Some downloads failed; retrying from other mirrors ({} of {})...",
+                round, MIRROR_ROUNDS
+            ),
+        );
+        std::thread::sleep(ROUND_DELAY);
+        announce_mirrors(alpm, &repos);
+    }
+}
+
+/// Remove `.part` files too old to resume; recent ones stay for the next attempt.
+pub fn prune_partial_downloads() {
+    prune_partial_downloads_in(Path::new(CACHE_PKG_DIR), PARTIAL_MAX_AGE);
+}
+
+fn prune_partial_downloads_in(dir: &Path, max_age: Duration) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let now = SystemTime::now();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_file() || path.extension().is_none_or(|ext| ext != "part") {
+            continue;
+        }
+        let stale = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .is_none_or(|age| age >= max_age);
+        if stale && std::fs::remove_file(&path).is_ok() {
+            logger::trace(&format!(
+                "Removed stale partial download: {}",
+                path.display()
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failed_retrieval_and_hosts() {
+        assert_eq!(
+            parse_failed_retrieval(
+                "failed retrieving file 'vlc-3.0.21-1-x86_64.pkg.tar.zst' from mirror.example.org : Operation too slow\n"
+            ),
+            Some(("vlc-3.0.21-1-x86_64.pkg.tar.zst", "mirror.example.org"))
+        );
+        assert_eq!(parse_failed_retrieval("failed to commit transaction"), None);
+
+        assert_eq!(
+            mirror_host("https://geo.mirror.pkgbuild.com/extra/os/x86_64"),
+            "geo.mirror.pkgbuild.com"
+        );
+        assert_eq!(mirror_host("file:///srv/repo"), "file:///srv/repo");
+
+        assert!(is_download_failure("failed to retrieve some files"));
+        assert!(!is_download_failure("conflicting files"));
+
+        let servers = vec!["https://a".to_string(), "https://b".to_string()];
+        assert_eq!(rotated(&servers), vec!["https://b", "https://a"]);
+        assert_eq!(rotated(&servers[..1]), vec!["https://a"]);
+    }
+
+    #[test]
+    fn test_mirror_strikes_and_rerank_list() {
+        let mut strikes = MirrorStrikes::new();
+        strikes.record("bad.example.org");
+        strikes.record("other.example.org");
+        strikes.record("bad.example.org");
+        assert_eq!(strikes.struck, None);
+        strikes.record("bad.example.org");
+        strikes.record("other.example.org");
+        strikes.record("other.example.org");
+        assert_eq!(strikes.struck.as_deref(), Some("bad.example.org"));
+
+        assert_eq!(mirrors::mirrorlist_key("extra"), Some("arch"));
+        assert_eq!(
+            mirrors::mirrorlist_key("cachyos-core-v3"),
+            Some("cachyos-v3")
+        );
+        assert_eq!(mirrors::mirrorlist_key("my-local-repo"), None);
+
+        let ranked = "# Server list generated by rankmirrors\n\
+                      Server = https://bad.example.org/$repo/os/$arch\n\
+                      Server = https://good.example.org/$repo/os/$arch # 40ms\n\
+                      #Server = https://off.example.org/$repo/os/$arch\n\
+                      Server = https://good.example.org/$repo/os/$arch\n\
+                      Server = https://x.org/$repo\n[options]\n";
+        let servers = mirrors::demote_host(mirrors::parse_servers(ranked), "bad.example.org");
+        assert_eq!(
+            servers,
+            vec![
+                "https://good.example.org/$repo/os/$arch",
+                "https://x.org/$repo",
+                "https://bad.example.org/$repo/os/$arch"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_prune_partial_downloads() {
+        let dir = tempfile::tempdir().unwrap();
+        let partial = dir.path().join("vlc-3.0.21-1-x86_64.pkg.tar.zst.part");
+        let finished = dir.path().join("vlc-3.0.21-1-x86_64.pkg.tar.zst");
+        std::fs::write(&partial, b"half").unwrap();
+        std::fs::write(&finished, b"whole").unwrap();
+
+        prune_partial_downloads_in(dir.path(), Duration::from_secs(3600));
+        assert!(partial.exists());
+
+        prune_partial_downloads_in(dir.path(), Duration::ZERO);
+        assert!(!partial.exists());
+        assert!(finished.exists());
+    }
+}
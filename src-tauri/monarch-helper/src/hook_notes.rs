@@ -0,0 +1,166 @@
+//! Output of ALPM hooks and install scripts. libalpm hands every line they print to the
+//! event callback and otherwise only writes it to pacman.log, so "you must reboot" or
+//! mkinitcpio's missing-firmware warnings never reached the user. Lines are logged, the
+//! notable ones collected, and after each commit sent as a `post_transaction_notes` event.
+
+use alpm::{Alpm, Event, PackageOperation};
+use monarch_types::{AlpmProgressEvent, NoteKind, PostTransactionNote};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use crate::{logger, progress};
+
+/// Notes kept per commit; a hook that warns about every module should not flood the dialog.
+const MAX_NOTES: usize = 30;
+
+#[derive(Default)]
+struct Collected {
+    /// Hook or package whose output is coming in.
+    source: Option<String>,
+    notes: Vec<PostTransactionNote>,
+    /// Missing firmware modules per source, reported as one note each.
+    firmware: BTreeMap<String, Vec<String>>,
+}
+
+static COLLECTED: Mutex<Option<Collected>> = Mutex::new(None);
+
+fn with_collected<R>(f: impl FnOnce(&mut Collected) -> R) -> R {
+    let mut guard = COLLECTED.lock().unwrap_or_else(|e| e.into_inner());
+    f(guard.get_or_insert_with(Collected::default))
+}
+
+/// What a line is about, if it is worth showing.
+pub fn classify(line: &str) -> Option<NoteKind> {
+    let lower = line.trim().trim_start_matches("==>").trim().to_lowercase();
+    if lower.contains("firmware")
+        && ["missing", "warning", "failed", "error", "not found"]
+            .iter()
+            .any(|w| lower.contains(w))
+    {
+        Some(NoteKind::Firmware)
+    } else if lower.contains("reboot")
+        || [
+            "restart your system",
+            "restart the system",
+            "restart your computer",
+        ]
+        .iter()
+        .any(|w| lower.contains(w))
+    {
+        Some(NoteKind::Reboot)
+    } else if lower.starts_with("error") {
+        Some(NoteKind::Error)
+    } else if lower.starts_with("warning") {
+        Some(NoteKind::Warning)
+    } else {
+        None
+    }
+}
+
+/// Module named by mkinitcpio's "Possibly missing firmware for module: 'ast'".
+pub fn missing_firmware_module(line: &str) -> Option<String> {
+    let (head, module) = line.rsplit_once(':')?;
+    if !head.to_lowercase().contains("missing firmware for module") {
+        return None;
+    }
+    let module = module.trim().trim_matches(|c| c == '\'' || c == '"');
+    (!module.is_empty()).then(|| module.to_string())
+}
+
+fn record(line: &str) {
+    let line = line.trim_end();
+    logger::info(&format!("[scriptlet] {}", line));
+    let Some(kind) = classify(line) else {
+        return;
+    };
+    with_collected(|c| {
+        let source = c
+            .source
+            .clone()
+            .unwrap_or_else(|| "Install scripts".to_string());
+        if let Some(module) = missing_firmware_module(line) {
+            let modules = c.firmware.entry(source).or_default();
+            if !modules.contains(&module) {
+                modules.push(module);
+            }
+            return;
+        }
+        let message = line.trim().trim_start_matches("==>").trim().to_string();
+        if c.notes.len() < MAX_NOTES && !c.notes.iter().any(|n| n.message == message) {
+            c.notes.push(PostTransactionNote {
+                kind,
+                source,
+                message,
+            });
+        }
+    });
+}
+
+/// Collect hook and scriptlet output from now on.
+pub fn install(alpm: &mut Alpm) {
+    alpm.set_event_cb((), |event, _: &mut ()| match event.event() {
+        Event::ScriptletInfo(info) => record(info.line()),
+        Event::HookRunStart(hook) => {
+            let source = hook.desc().unwrap_or(hook.name()).to_string();
+            with_collected(|c| c.source = Some(source));
+        }
+        Event::PackageOperationStart(op) => {
+            let name = match op.operation() {
+                PackageOperation::Install(pkg) | PackageOperation::Remove(pkg) => pkg.name(),
+                PackageOperation::Upgrade(_, pkg)
+                | PackageOperation::Reinstall(_, pkg)
+                | PackageOperation::Downgrade(_, pkg) => pkg.name(),
+            };
+            let source = name.to_string();
+            with_collected(|c| c.source = Some(source));
+        }
+        Event::HookRunDone(_) | Event::PackageOperationDone(_) => {
+            with_collected(|c| c.source = None);
+        }
+        _ => {}
+    });
+}
+
+/// The notes collected since the last call, missing firmware first.
+pub fn take() -> Vec<PostTransactionNote> {
+    let collected = COLLECTED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .take()
+        .unwrap_or_default();
+    collected
+        .firmware
+        .into_iter()
+        .map(|(source, modules)| PostTransactionNote {
+            kind: NoteKind::Firmware,
+            source,
+            message: format!(
+                "Possibly missing firmware for module{}: {}",
+                if modules.len() == 1 { "" } else { "s" },
+                modules.join(", ")
+            ),
+        })
+        .chain(collected.notes)
+        .collect()
+}
+
+/// Send what the last commit's hooks and scripts had to say, if anything.
+pub fn emit() {
+    let notes = take();
+    if notes.is_empty() {
+        return;
+    }
+    let event = AlpmProgressEvent {
+        event_type: "post_transaction_notes".to_string(),
+        package: None,
+        percent: None,
+        downloaded: None,
+        total: None,
+        speed_bps: None,
+        eta_seconds: None,
+        message: serde_json::to_string(&notes).unwrap_or_else(|_| "[]".to_string()),
+    };
+    if let Ok(json) = serde_json::to_string(&event) {
+        progress::send_progress_line(json);
+    }
+}
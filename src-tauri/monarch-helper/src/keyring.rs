@@ -0,0 +1,312 @@
+//! pacman keyring management (Keyring): lsign, import from a keyserver, delete, and refresh
+//! the signing keys of chaotic-aur and CachyOS. Each action is one pacman-key call with its
+//! output forwarded to the GUI log. The distribution's master keys can't be deleted here.
+//!
+//! ExecuteBatch's `keyring_repos` step also lives here: a third-party repo whose keyring
+//! package is missing gets its signing key trusted before the DB refresh, and the keyring
+//! installed and populated after it.
+
+use alpm::Alpm;
+use monarch_types::KeyringAction;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use crate::logger;
+
+const DEFAULT_KEYSERVER: &str = "hkps://keyserver.ubuntu.com";
+
+/// A third-party repo that signs with its own key and ships it as a keyring package.
+pub struct ThirdPartyRepo {
+    /// Sync DB names containing this belong to the repo (cachyos, cachyos-v3, ...).
+    pub db: &'static str,
+    pub label: &'static str,
+    /// Full fingerprint: a short id can collide with a key anyone can upload.
+    pub fingerprint: &'static str,
+    pub keyring_package: &'static str,
+    /// Keyring name for `pacman-key --populate`.
+    pub populate: &'static str,
+}
+
+const THIRD_PARTY_REPOS: &[ThirdPartyRepo] = &[
+    ThirdPartyRepo {
+        db: "chaotic",
+        label: "Chaotic-AUR",
+        fingerprint: "EF925EA60F33D0CB85C44AD13056513887B78AEB",
+        keyring_package: "chaotic-keyring",
+        populate: "chaotic",
+    },
+    ThirdPartyRepo {
+        db: "cachyos",
+        label: "CachyOS",
+        fingerprint: "882DCFE48E2051D48E2562ABF3B607488DB35A47",
+        keyring_package: "cachyos-keyring",
+        populate: "cachyos",
+    },
+];
+
+/// The third-party repo a sync DB belongs to, if any.
+pub fn third_party_repo(db_name: &str) -> Option<&'static ThirdPartyRepo> {
+    THIRD_PARTY_REPOS.iter().find(|r| db_name.contains(r.db))
+}
+
+/// Fingerprints of the master keys shipped by archlinux-keyring and friends.
+const TRUSTED_KEYRINGS_DIR: &str = "/usr/share/pacman/keyrings";
+/// Keyservers can take a while, but not forever.
+const PACMAN_KEY_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Uppercase hex without `0x` or spaces. Only a full 40-digit fingerprint is accepted: a
+/// short or long key id can be matched by a key anyone generates and uploads.
+pub fn normalize_keyid(keyid: &str) -> Result<String, String> {
+    let trimmed = keyid.trim();
+    let hex = trimmed
+        .strip_prefix("0x")
+        .or_else(|| trimmed.strip_prefix("0X"))
+        .unwrap_or(trimmed);
+    let hex: String = hex.chars().filter(|c| !c.is_whitespace()).collect();
+    if hex.len() == 40 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        Ok(hex.to_ascii_uppercase())
+    } else {
+        Err(format!(
+            "Invalid key: {} (expected the full 40-digit fingerprint)",
+            keyid
+        ))
+    }
+}
+
+/// `hkp://`, `hkps://` or a bare host (taken as hkps), with an optional port.
+pub fn normalize_keyserver(server: &str) -> Result<String, String> {
+    let server = server.trim().trim_end_matches('/');
+    let (scheme, host) = match server.split_once("://") {
+        Some((scheme @ ("hkp" | "hkps"), host)) => (scheme, host),
+        Some(_) => return Err(format!("Unsupported keyserver: {}", server)),
+        None => ("hkps", server),
+    };
+    let (name, port) = host.split_once(':').unwrap_or((host, ""));
+    let name_ok = !name.is_empty()
+        && !name.starts_with(['-', '.'])
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-');
+    let port_ok = port.is_empty() || port.parse::<u16>().is_ok();
+    if name_ok && port_ok {
+        Ok(format!("{}://{}", scheme, host))
+    } else {
+        Err(format!("Invalid keyserver: {}", server))
+    }
+}
+
+/// Fingerprints listed in `*-trusted` files (`FINGERPRINT:4:` lines).
+pub fn parse_trusted(contents: &str) -> Vec<String> {
+    contents
+        .lines()
+        .filter_map(|l| l.split(':').next())
+        .map(|f| f.trim().to_ascii_uppercase())
+        .filter(|f| f.len() == 40)
+        .collect()
+}
+
+fn is_master_key(keyid: &str) -> bool {
+    let Ok(entries) = std::fs::read_dir(TRUSTED_KEYRINGS_DIR) else {
+        return false;
+    };
+    entries
+        .flatten()
+        .filter(|e| e.file_name().to_string_lossy().ends_with("-trusted"))
+        .filter_map(|e| std::fs::read_to_string(e.path()).ok())
+        .flat_map(|c| parse_trusted(&c))
+        .any(|fpr| fpr == keyid)
+}
+
+fn pacman_key(args: &[&str]) -> Result<(), String> {
+    crate::emit_progress(20, &format!("pacman-key {}", args.join(" ")));
+    let child = Command::new("pacman-key")
+        .args(args)
+        .env("LC_ALL", "C")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run pacman-key: {}", e))?;
+    // wait_with_output drains both pipes while waiting, so a chatty --refresh-keys can't
+    // fill one and stall; the wait runs on a thread so it can still time out.
+    let pid = child.id();
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = tx.send(child.wait_with_output());
+    });
+    let output = match rx.recv_timeout(PACMAN_KEY_TIMEOUT) {
+        Ok(output) => output.map_err(|e| format!("Failed to run pacman-key: {}", e))?,
+        Err(_) => {
+            // The waiting thread reaps it
+            unsafe { libc::kill(pid as libc::pid_t, libc::SIGKILL) };
+            return Err(format!(
+                "pacman-key {} timed out after {} seconds",
+                args.first().copied().unwrap_or_default(),
+                PACMAN_KEY_TIMEOUT.as_secs()
+            ));
+        }
+    };
+    let status = output.status;
+    let out = String::from_utf8_lossy(&output.stdout);
+    let err = String::from_utf8_lossy(&output.stderr);
+    for line in out.lines().chain(err.lines()) {
+        if !line.trim().is_empty() {
+            crate::emit_progress(60, line.trim());
+        }
+    }
+    if status.success() {
+        Ok(())
+    } else {
+        let detail = err.lines().rev().find(|l| !l.trim().is_empty());
+        Err(match detail {
+            Some(line) => format!("pacman-key failed: {}", line.trim()),
+            None => format!("pacman-key exited with {}", status),
+        })
+    }
+}
+
+fn import_and_sign(keyid: &str, server: &str) -> Result<(), String> {
+    pacman_key(&["--keyserver", server, "--recv-keys", keyid])?;
+    pacman_key(&["--lsign-key", keyid])
+}
+
+pub fn run(alpm: &Alpm, action: KeyringAction) -> Result<String, String> {
+    match action {
+        KeyringAction::LocallySign { keyid } => {
+            let keyid = normalize_keyid(&keyid)?;
+            pacman_key(&["--lsign-key", &keyid])?;
+            Ok(format!("Key {} is now trusted", keyid))
+        }
+        KeyringAction::ImportFromKeyserver { keyid, server } => {
+            let keyid = normalize_keyid(&keyid)?;
+            let server = normalize_keyserver(server.as_deref().unwrap_or(DEFAULT_KEYSERVER))?;
+            import_and_sign(&keyid, &server)?;
+            Ok(format!("Imported and trusted key {}", keyid))
+        }
+        KeyringAction::Remove { keyid } => {
+            let keyid = normalize_keyid(&keyid)?;
+            if is_master_key(&keyid) {
+                return Err(format!(
+                    "{} is a master key of your distribution's keyring and can't be removed",
+                    keyid
+                ));
+            }
+            pacman_key(&["--delete", &keyid])?;
+            Ok(format!("Removed key {}", keyid))
+        }
+        KeyringAction::RefreshThirdParty => {
+            let enabled: Vec<&ThirdPartyRepo> = THIRD_PARTY_REPOS
+                .iter()
+                .filter(|r| alpm.syncdbs().iter().any(|d| d.name().contains(r.db)))
+                .collect();
+            if enabled.is_empty() {
+                return Ok("Neither Chaotic-AUR nor CachyOS is enabled".to_string());
+            }
+            let mut failed = Vec::new();
+            for repo in &enabled {
+                crate::emit_progress(10, &format!("Refreshing the {} signing key...", repo.label));
+                if let Err(e) = import_and_sign(repo.fingerprint, DEFAULT_KEYSERVER) {
+                    logger::warn(&format!("{} key refresh failed: {}", repo.label, e));
+                    failed.push(format!("{} ({})", repo.label, e));
+                }
+            }
+            if failed.is_empty() {
+                let repos: Vec<&str> = enabled.iter().map(|r| r.label).collect();
+                Ok(format!("Refreshed the {} keys", repos.join(" and ")))
+            } else {
+                Err(format!("Could not refresh: {}", failed.join(", ")))
+            }
+        }
+    }
+}
+
+/// Third-party repos among `repos` that pacman knows but whose keyring isn't installed.
+pub fn missing_keyrings(alpm: &Alpm, repos: &[String]) -> Vec<&'static ThirdPartyRepo> {
+    let mut missing: Vec<&'static ThirdPartyRepo> = Vec::new();
+    for name in repos {
+        let Some(repo) = third_party_repo(name) else {
+            continue;
+        };
+        let registered = alpm.syncdbs().iter().any(|d| d.name() == name);
+        let installed = alpm.localdb().pkg(repo.keyring_package).is_ok();
+        if registered && !installed && !missing.iter().any(|m| m.db == repo.db) {
+            missing.push(repo);
+        }
+    }
+    missing
+}
+
+/// Before the DB refresh: trust the signing keys, so the repo and its keyring verify.
+pub fn trust_signing_keys(repos: &[&ThirdPartyRepo]) -> Result<(), String> {
+    for repo in repos {
+        crate::emit_progress(5, &format!("Setting up the {} signing key...", repo.label));
+        import_and_sign(repo.fingerprint, DEFAULT_KEYSERVER)
+            .map_err(|e| format!("{} signing key: {}", repo.label, e))?;
+    }
+    Ok(())
+}
+
+/// After the keyring packages are installed.
+pub fn populate(repos: &[&ThirdPartyRepo]) -> Result<(), String> {
+    for repo in repos {
+        pacman_key(&["--populate", repo.populate])?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyring_input() {
+        assert_eq!(
+            normalize_keyid("0xef925ea60f33d0cb85c44ad13056513887b78aeb").as_deref(),
+            Ok("EF925EA60F33D0CB85C44AD13056513887B78AEB")
+        );
+        assert_eq!(
+            normalize_keyid("EF92 5EA6 0F33 D0CB 85C4  4AD1 3056 5138 87B7 8AEB").unwrap(),
+            "EF925EA60F33D0CB85C44AD13056513887B78AEB"
+        );
+        // Short and long key ids are spoofable
+        assert!(normalize_keyid("3056513887B78AEB").is_err());
+        assert!(normalize_keyid("1234567").is_err());
+        assert!(normalize_keyid("--delete").is_err());
+        assert!(normalize_keyid("3056513887B78AEG").is_err());
+
+        assert_eq!(
+            normalize_keyserver("keyserver.ubuntu.com").as_deref(),
+            Ok("hkps://keyserver.ubuntu.com")
+        );
+        assert_eq!(
+            normalize_keyserver("hkp://keys.openpgp.org:11371/").as_deref(),
+            Ok("hkp://keys.openpgp.org:11371")
+        );
+        assert!(normalize_keyserver("http://evil.example").is_err());
+        assert!(normalize_keyserver("--homedir").is_err());
+        assert!(normalize_keyserver("host:99999").is_err());
+
+        assert_eq!(
+            third_party_repo("cachyos-v3").map(|r| r.keyring_package),
+            Some("cachyos-keyring")
+        );
+        assert_eq!(
+            third_party_repo("chaotic-aur").map(|r| r.populate),
+            Some("chaotic")
+        );
+        assert!(third_party_repo("extra").is_none());
+        for repo in THIRD_PARTY_REPOS {
+            assert_eq!(repo.fingerprint.len(), 40);
+            assert_eq!(
+                normalize_keyid(repo.fingerprint).as_deref(),
+                Ok(repo.fingerprint)
+            );
+        }
+
+        let trusted = "3E80CA1A8B89F69CBA57D98A76A5EF9054449A5C:4:\n# comment\nshort:4:\n";
+        assert_eq!(
+            parse_trusted(trusted),
+            vec!["3E80CA1A8B89F69CBA57D98A76A5EF9054449A5C"]
+        );
+    }
+}
@@ -1,13 +1,30 @@
+mod agent;
 mod alpm_errors;
+mod batch;
+mod cache_gc;
+mod config_restore;
+mod disk_space;
+mod downloads;
+mod hook_notes;
+mod keyring;
 mod logger;
+mod mirrors;
+mod network;
+mod outcome;
+mod pacman_conf;
+mod pins;
 mod progress;
+mod rescue;
+mod rpc;
 mod safe_transaction;
 mod self_healer;
+mod services;
 mod transactions;
 
 #[cfg(test)]
 mod command_tests {
     use super::HelperCommand;
+    use monarch_types::{ResolvedPackage, TransactionPhase};
     use serde_json;
     use std::io::Write;
     use tempfile::NamedTempFile;
@@ -24,6 +41,8 @@ mod command_tests {
             ],
             cpu_optimization: Some("v3".to_string()),
             target_repo: None,
+            phase: Default::default(),
+            dry_run: false,
         };
 
         let json = serde_json::to_string(&cmd).expect("Should serialize");
@@ -41,12 +60,16 @@ mod command_tests {
                 enabled_repos,
                 cpu_optimization,
                 target_repo,
+                phase,
+                dry_run,
             } => {
                 assert_eq!(packages.len(), 2);
                 assert!(sync_first);
                 assert_eq!(enabled_repos.len(), 3);
                 assert_eq!(cpu_optimization, Some("v3".to_string()));
                 assert_eq!(target_repo, None);
+                assert!(phase.is_full());
+                assert!(!dry_run);
             }
             _ => panic!("Wrong command variant"),
         }
@@ -64,6 +87,8 @@ mod command_tests {
             ],
             cpu_optimization: Some("v3".to_string()),
             target_repo: None,
+            phase: Default::default(),
+            dry_run: false,
         };
 
         let json = serde_json::to_string(&cmd).expect("Should serialize");
@@ -106,6 +131,8 @@ mod command_tests {
             enabled_repos: vec!["core".to_string()],
             cpu_optimization: None,
             target_repo: None,
+            phase: Default::default(),
+            dry_run: false,
         };
 
         let json = serde_json::to_string(&cmd).expect("Should serialize");
@@ -124,48 +151,280 @@ mod command_tests {
             _ => panic!("Wrong variant"),
         }
     }
+
+    #[test]
+    fn test_dry_run_flag_defaults_off() {
+        // Command files written before the flag existed must still commit
+        let parsed: HelperCommand = serde_json::from_str(
+            r#"{"command":"AlpmUpgrade","payload":{"packages":null,"enabled_repos":["core"]}}"#,
+        )
+        .expect("Should parse");
+        assert!(matches!(
+            parsed,
+            HelperCommand::AlpmUpgrade {
+                dry_run: false,
+                phase: TransactionPhase::Full,
+                ..
+            }
+        ));
+
+        let parsed: HelperCommand = serde_json::from_str(
+            r#"{"command":"ExecuteBatch","payload":{"manifest":{"update_system":false,"refresh_db":false,"clear_cache":false,"remove_lock":false,"install_targets":[],"remove_targets":["vlc"],"local_paths":[]},"dry_run":true}}"#,
+        )
+        .expect("Should parse");
+        match parsed {
+            HelperCommand::ExecuteBatch { manifest, dry_run } => {
+                assert!(dry_run);
+                assert_eq!(manifest.remove_targets, vec!["vlc".to_string()]);
+            }
+            _ => panic!("Wrong variant"),
+        }
+    }
+
+    #[test]
+    fn test_commit_phase_detects_changed_versions() {
+        use crate::transactions::phase_mismatch;
+        let pkg = |name: &str, version: &str| ResolvedPackage {
+            name: name.to_string(),
+            version: version.to_string(),
+        };
+        let downloaded = vec![pkg("firefox", "131.0-1"), pkg("nss", "3.104-1")];
+        assert!(phase_mismatch(&downloaded, &downloaded).is_empty());
+
+        let now = vec![pkg("firefox", "131.0.2-1"), pkg("icu", "75.1-1")];
+        assert_eq!(
+            phase_mismatch(&downloaded, &now),
+            vec![
+                "firefox is now 131.0.2-1 (downloaded 131.0-1)",
+                "nss is no longer part of the transaction",
+                "icu 75.1-1 was not downloaded",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hook_output_classification() {
+        use crate::hook_notes::{classify, missing_firmware_module};
+        use monarch_types::NoteKind;
+        let line = "==> WARNING: Possibly missing firmware for module: 'qla2xxx'";
+        assert_eq!(classify(line), Some(NoteKind::Firmware));
+        assert_eq!(missing_firmware_module(line), Some("qla2xxx".to_string()));
+        assert_eq!(
+            classify("(3/5) Updating linux initcpios... you must reboot to use the new kernel"),
+            Some(NoteKind::Reboot)
+        );
+        assert_eq!(
+            classify("==> ERROR: module not found: 'nvidia'"),
+            Some(NoteKind::Error)
+        );
+        assert_eq!(
+            classify("warning: /etc/pacman.conf installed as /etc/pacman.conf.pacnew"),
+            Some(NoteKind::Warning)
+        );
+        assert_eq!(
+            classify("==> Building image from preset: /etc/mkinitcpio.d/linux.preset: 'default'"),
+            None
+        );
+        assert_eq!(
+            missing_firmware_module("==> WARNING: module not found: 'ast'"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_download_filename_to_package() {
+        use crate::progress::package_from_filename;
+        assert_eq!(
+            package_from_filename("firefox-130.0-1-x86_64.pkg.tar.zst"),
+            "firefox"
+        );
+        assert_eq!(
+            package_from_filename("lib32-mesa-1:24.2.1-1-x86_64.pkg.tar.zst"),
+            "lib32-mesa"
+        );
+        assert_eq!(package_from_filename("core.db"), "core.db");
+    }
+
+    #[test]
+    fn test_pins_dropin() {
+        use crate::pins::{parse_ignorepkg, render_dropin, PINS_DROPIN};
+        let pins = vec!["linux".to_string(), "mesa".to_string()];
+        let dropin = render_dropin(&pins);
+        assert_eq!(parse_ignorepkg(&dropin), pins);
+        assert!(parse_ignorepkg(&render_dropin(&[])).is_empty());
+
+        // Lives with the repo drop-ins and sets its own section
+        assert!(PINS_DROPIN.starts_with("/etc/pacman.d/monarch/"));
+        assert!(dropin.lines().any(|l| l == "[options]"));
+    }
+
+    #[test]
+    fn test_mirrorlist_validation_and_render() {
+        use crate::mirrors::{mirrorlist_path, render_mirrorlist, valid_server};
+        assert_eq!(mirrorlist_path("arch"), Some("/etc/pacman.d/mirrorlist"));
+        assert_eq!(mirrorlist_path("../../etc/passwd"), None);
+
+        assert!(valid_server(
+            "https://geo.mirror.pkgbuild.com/$repo/os/$arch"
+        ));
+        assert!(valid_server(
+            "http://mirror.example.org/cachyos/repo/$arch/$repo"
+        ));
+        assert!(!valid_server("ftp://mirror.example.org/$repo"));
+        assert!(!valid_server("https:///$repo"));
+        assert!(!valid_server("https://a.org/$repo\nXferCommand = /bin/sh"));
+        assert!(!valid_server("https://a.org/$repo\n[options]"));
+        assert!(!valid_server("https://a.org/$repo # comment"));
+
+        let servers = vec![
+            "https://a.org/$repo/os/$arch".to_string(),
+            "https://b.org/$repo/os/$arch".to_string(),
+        ];
+        let rendered = render_mirrorlist("arch", &servers);
+        let lines: Vec<&str> = rendered
+            .lines()
+            .filter(|l| l.starts_with("Server"))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "Server = https://a.org/$repo/os/$arch",
+                "Server = https://b.org/$repo/os/$arch"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_config_restore_target_validation() {
+        use crate::config_restore::valid_target;
+        assert!(valid_target("/etc/mkinitcpio.conf"));
+        assert!(valid_target("/etc/pacman.d/mirrorlist"));
+        assert!(!valid_target("etc/pacman.conf"));
+        assert!(!valid_target("/etc/../root/.ssh/authorized_keys"));
+        assert!(!valid_target("/etc//pacman.conf"));
+        assert!(!valid_target("/etc/./pacman.conf"));
+        assert!(!valid_target("/"));
+    }
+
+    #[test]
+    fn test_rescue_kernel_detection_and_conf_backup() {
+        use crate::rescue::{is_kernel_image, pick_conf_backup};
+        assert!(is_kernel_image("usr/lib/modules/6.9.1-arch1-1/vmlinuz"));
+        assert!(is_kernel_image("usr/lib/modules/6.9.1-1-cachyos/vmlinuz"));
+        assert!(!is_kernel_image(
+            "usr/lib/modules/6.9.1-arch1-1/kernel/fs/ext4.ko.zst"
+        ));
+        assert!(!is_kernel_image("usr/lib/modules//vmlinuz"));
+        assert!(!is_kernel_image("boot/vmlinuz-linux"));
+
+        let entries = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            pick_conf_backup(&entries(&[
+                "pacman.conf",
+                "pacman.conf.bak.parallel.1700000000",
+                "pacman.conf.bak.parallel.1710000000",
+                "pacman.conf.bak.parallel.junk",
+            ])),
+            Some("/etc/pacman.conf.bak.parallel.1710000000".to_string())
+        );
+        assert_eq!(
+            pick_conf_backup(&entries(&[
+                "pacman.conf.bak.parallel.1710000000",
+                "pacman.conf.monarch-bak"
+            ])),
+            Some("/etc/pacman.conf.monarch-bak".to_string())
+        );
+        assert_eq!(pick_conf_backup(&entries(&["pacman.conf.pacnew"])), None);
+    }
+
+    #[test]
+    fn test_download_speed_and_eta() {
+        use crate::progress::speed_and_eta;
+        assert_eq!(speed_and_eta(0, 1000, 1.0), (None, None));
+        assert_eq!(speed_and_eta(500, 1000, 1.0), (Some(500), Some(1)));
+        assert_eq!(speed_and_eta(1000, 1000, 2.0), (Some(500), None));
+    }
+
+    #[test]
+    fn test_transaction_result_outcomes() {
+        use crate::outcome;
+        use monarch_types::{exit_code, DryRunAction, DryRunChange, PackageOutcome};
+        let change = |action, name: &str| DryRunChange {
+            action,
+            name: name.to_string(),
+            old_version: None,
+            new_version: Some("1.0-1".to_string()),
+            download_size: 0,
+            installed_size: 0,
+        };
+
+        // Batch: vlc (and a dependency) committed, then the install step failed
+        outcome::begin(&["vlc".into(), "gimp".into(), "krita".into()], false);
+        outcome::record(&[
+            change(DryRunAction::Install, "vlc"),
+            change(DryRunAction::Install, "libvlc"),
+        ]);
+        outcome::record_failed(
+            &[change(DryRunAction::Upgrade, "gimp")],
+            "conflicting files",
+        );
+        outcome::skip(&["krita".into()], "Not attempted");
+        outcome::fail("failed to commit transaction (conflicting files)");
+        let result = outcome::finish();
+        assert!(!result.success);
+        assert_eq!(result.exit_code, exit_code::PARTIAL);
+        assert_eq!(result.outcome_of("libvlc"), Some(PackageOutcome::Installed));
+        assert_eq!(result.outcome_of("gimp"), Some(PackageOutcome::Failed));
+        assert_eq!(result.outcome_of("krita"), Some(PackageOutcome::Skipped));
+
+        // Nothing to upgrade: the requested package is skipped, not failed
+        outcome::begin(&["vlc".into()], false);
+        let result = outcome::finish();
+        assert!(result.success);
+        assert_eq!(result.outcome_of("vlc"), Some(PackageOutcome::Skipped));
+
+        outcome::begin(&[], false);
+        outcome::fail("unable to lock database");
+        assert_eq!(outcome::finish().exit_code, exit_code::DB_LOCKED);
+
+        // No command parsed at all
+        assert_eq!(outcome::finish().exit_code, exit_code::INVALID_COMMAND);
+    }
+
+    #[test]
+    fn test_pacman_conf_output_parsing() {
+        use crate::pacman_conf::{parse_repo_details, parse_repo_list, RepoSigLevel, RepoUsage};
+        assert_eq!(
+            parse_repo_list("core\nextra\n\n../../etc/passwd\ncore\nmy repo\n"),
+            vec!["core", "extra"]
+        );
+
+        let details = parse_repo_details(
+            "Server = https://mirror.example/$repo/os/$arch # fast\n\
+             Server=https://other.example/$repo\n\
+             SigLevel = PackageOptional\n\
+             Usage = Sync\n\
+             Usage = Search\n",
+        );
+        assert_eq!(
+            details.servers,
+            vec![
+                "https://mirror.example/$repo/os/$arch",
+                "https://other.example/$repo"
+            ]
+        );
+        assert_eq!(details.siglevel, RepoSigLevel::PackageOptional);
+        assert!(details.usage.sync && details.usage.search && !details.usage.install);
+        assert_eq!(parse_repo_details("").usage, RepoUsage::ALL);
+    }
 }
 
 use alpm::Question;
 use alpm::{Alpm, SigLevel};
-use serde::{Deserialize, Serialize};
-use std::io::{self, BufRead};
 #[cfg(unix)]
 use std::os::unix::fs::MetadataExt;
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
-#[serde(tag = "command", content = "payload")]
-pub enum HelperCommand {
-    // ✅ NEW: Full ALPM Transactions
-    AlpmInstall {
-        packages: Vec<String>,
-        sync_first: bool,
-        enabled_repos: Vec<String>,
-        cpu_optimization: Option<String>,
-        target_repo: Option<String>,
-    },
-    // ✅ NEW: Atomic Batch Transaction (Operation Silent Guard)
-    ExecuteBatch {
-        manifest: transactions::TransactionManifest,
-    },
-    CheckUpdatesSafe {
-        enabled_repos: Vec<String>,
-    },
-    AlpmUninstall {
-        packages: Vec<String>,
-        remove_deps: bool,
-    },
-    AlpmUpgrade {
-        packages: Option<Vec<String>>,
-        enabled_repos: Vec<String>,
-    },
-    AlpmSync {
-        enabled_repos: Vec<String>,
-    },
-    AlpmInstallFiles {
-        paths: Vec<String>,
-    },
-}
+pub use monarch_types::HelperCommand;
 
 // Struct for legacy or simple progress messages if ever needed again
 // #[derive(Debug, Serialize)]
@@ -181,6 +440,8 @@ fn emit_progress(progress: u32, message: &str) {
         percent: Some(progress as u8),
         downloaded: None,
         total: None,
+        speed_bps: None,
+        eta_seconds: None,
         message: message.to_string(),
     };
     if let Ok(json) = serde_json::to_string(&event) {
@@ -198,6 +459,8 @@ fn emit_classified_error(e: &str) {
         percent: None,
         downloaded: None,
         total: None,
+        speed_bps: None,
+        eta_seconds: None,
         message,
     };
     if let Ok(json) = serde_json::to_string(&event) {
@@ -230,7 +493,8 @@ impl Drop for PidFileGuard {
 }
 
 /// Spawn a thread that watches for CANCEL_FILE. When the GUI creates it (user clicked Cancel),
-/// we remove it and exit so the install stops and the lock is released.
+/// we remove it, report what was committed so far and exit so the install stops and the lock
+/// is released.
 fn spawn_cancel_watcher() {
     std::thread::spawn(|| {
         let cancel_path = std::path::Path::new(CANCEL_FILE);
@@ -240,7 +504,7 @@ fn spawn_cancel_watcher() {
                 let _ = std::fs::remove_file(cancel_path);
                 let _ = std::fs::remove_file(HELPER_PID_FILE);
                 logger::info("Cancel requested by user; exiting.");
-                std::process::exit(0);
+                std::process::exit(outcome::cancel().exit_code);
             }
         }
     });
@@ -298,7 +562,7 @@ fn run() -> Result<(), Box<dyn std::error::Error>> {
                 euid
             );
             logger::error(&msg);
-            std::process::exit(125);
+            std::process::exit(monarch_types::exit_code::NOT_ROOT);
         }
     }
 
@@ -320,51 +584,7 @@ fn run() -> Result<(), Box<dyn std::error::Error>> {
         logger::trace("Could not write PID file (non-fatal)");
     }
     spawn_cancel_watcher();
-    let mut alpm = Alpm::new("/", "/var/lib/pacman")?;
-
-    // Phase 4: Performance - Set Parallel Downloads
-    let _ = alpm.set_parallel_downloads(5);
-
-    // App Store grade: auto-answer questions (NOCONFIRM behavior) so GUI never hangs
-    alpm.set_question_cb((), |question, _: &mut ()| match question.question() {
-        Question::SelectProvider(mut q) => {
-            q.set_index(0);
-            logger::trace("Auto-resolved provider conflict: chose option 1 (repository default)");
-        }
-        Question::Replace(q) => {
-            q.set_replace(true);
-            logger::trace("Auto-resolved replace: chose to replace");
-        }
-        Question::ImportKey(mut q) => q.set_import(true),
-        Question::InstallIgnorepkg(mut q) => {
-            logger::warn("IgnorePkg respected: skipping requested upgrade for ignored package.");
-            q.set_install(false);
-        }
-        Question::RemovePkgs(mut q) => q.set_skip(false),
-        Question::Conflict(mut q) => q.set_remove(false),
-        Question::Corrupted(mut q) => q.set_remove(true),
-    });
-
-    // Set log callback to suppress noise (set_log_cb(data, FnMut(LogLevel, &str, &mut T))
-    alpm.set_log_cb((), |level, msg, _: &mut ()| {
-        if level.bits() >= alpm::LogLevel::WARNING.bits() {
-            logger::warn(&format!("[ALPM {:?}] {}", level, msg));
-        }
-    });
-
-    // Improved Repository Registration: Use pacman-conf to get accurate DB locations and servers
-    if let Err(e) = register_repositories(&mut alpm) {
-        emit_progress(
-            0,
-            &format!(
-                "Warning: Failed to register repositories via pacman-conf: {}",
-                e
-            ),
-        );
-        // Fail gracefully if pacman-conf fails
-    }
-    // Remove any syncdb that has no servers (avoids "no servers configured for repository" during sync)
-    remove_syncdbs_with_no_servers(&mut alpm);
+    let mut alpm = init_alpm()?;
 
     let args: Vec<String> = std::env::args().collect();
     logger::info(&format!(
@@ -401,38 +621,6 @@ fn run() -> Result<(), Box<dyn std::error::Error>> {
         logger::info("MONARCH_CMD_JSON not found in environment");
     }
 
-    // Fallback: Check for file path in environment variable (backup when env var JSON fails)
-    if let Ok(file_path) = std::env::var("MONARCH_CMD_FILE") {
-        logger::info(&format!(
-            "Found command file path in MONARCH_CMD_FILE: {}",
-            file_path
-        ));
-        if let Ok(json_str) = std::fs::read_to_string(&file_path) {
-            let trimmed = json_str.trim();
-            if !trimmed.is_empty() {
-                logger::info(&format!("Read {} bytes from command file", trimmed.len()));
-                match serde_json::from_str::<HelperCommand>(trimmed) {
-                    Ok(cmd) => {
-                        logger::info("Successfully parsed command from file");
-                        let _ = std::fs::remove_file(&file_path);
-                        execute_command(cmd, &mut alpm);
-                        logger::info("monarch-helper exiting normally");
-                        return Ok(());
-                    }
-                    Err(e) => {
-                        logger::error(&format!("Failed to parse command from file: {}", e));
-                        emit_progress(
-                            0,
-                            &format!("Error: Invalid JSON command in file {}: {}", file_path, e),
-                        );
-                        let _ = std::fs::remove_file(&file_path);
-                        return Ok(());
-                    }
-                }
-            }
-        }
-    }
-
     if args.len() > 1 {
         // Handle command: args[1] may be path to temp JSON file (GUI/Update flow) or inline JSON (repair/AUR PACMAN wrapper).
         // Try reading as file first for any path-like arg (handles /tmp and /var/tmp).
@@ -635,79 +823,112 @@ fn run() -> Result<(), Box<dyn std::error::Error>> {
             }
         }
     } else {
-        // Primary path for GUI (pkexec with no args): command is sent as a single JSON line on stdin.
-        // BUT: If MONARCH_CMD_FILE is set, we're in password mode (sudo -S) and stdin contains the password, not the command.
-        // In that case, we should have already read from the file above. If we get here, something went wrong.
-        if std::env::var("MONARCH_CMD_FILE").is_ok() {
-            logger::error(
-                "MONARCH_CMD_FILE is set but command file read failed. This should not happen.",
-            );
-            emit_progress(
-                0,
-                "Error: Command file was specified but could not be read. Check permissions and ensure the file exists.",
-            );
-            return Ok(());
+        // The GUI uses --rpc; a one-shot run takes its command from argv or MONARCH_CMD_JSON
+        emit_progress(
+            0,
+            "Error: No command given. Pass a command file or JSON command as the first argument, set MONARCH_CMD_JSON, or use --rpc.",
+        );
+    }
+
+    logger::info("monarch-helper exiting normally");
+    Ok(())
+}
+
+/// ALPM handle set up for a command: repos from pacman.conf, auto-answered questions, pinned
+/// packages ignored. The agent makes a fresh one per request so no state leaks between runs.
+fn init_alpm() -> Result<Alpm, Box<dyn std::error::Error>> {
+    init_alpm_at(transactions::DB_PATH)
+}
+
+/// [`init_alpm`] on another database directory, e.g. a dry run's scratch copy.
+fn init_alpm_at(dbpath: &str) -> Result<Alpm, Box<dyn std::error::Error>> {
+    let mut alpm = Alpm::new("/", dbpath)?;
+
+    // Phase 4: Performance - Set Parallel Downloads
+    let _ = alpm.set_parallel_downloads(5);
+
+    // Proxy / rate limit / IPv4-only from the Network settings
+    network::apply(&mut alpm);
+
+    // App Store grade: auto-answer questions (NOCONFIRM behavior) so GUI never hangs
+    alpm.set_question_cb((), |question, _: &mut ()| match question.question() {
+        Question::SelectProvider(mut q) => {
+            q.set_index(0);
+            logger::trace("Auto-resolved provider conflict: chose option 1 (repository default)");
+        }
+        Question::Replace(q) => {
+            q.set_replace(true);
+            logger::trace("Auto-resolved replace: chose to replace");
+        }
+        Question::ImportKey(mut q) => q.set_import(true),
+        Question::InstallIgnorepkg(mut q) => {
+            logger::warn("IgnorePkg respected: skipping requested upgrade for ignored package.");
+            q.set_install(false);
         }
+        Question::RemovePkgs(mut q) => q.set_skip(false),
+        Question::Conflict(mut q) => q.set_remove(false),
+        Question::Corrupted(mut q) => q.set_remove(true),
+    });
 
-        // No file path set, so we're in pkexec mode: command is on stdin (not password)
-        logger::info("Reading command from stdin (pkexec mode, no password)");
-        let stdin = io::stdin();
-        for line in stdin.lock().lines() {
-            if let Ok(line) = line {
-                let trimmed = line.trim();
-                if trimmed.is_empty() {
-                    continue; // Skip empty lines
-                }
-                // CRITICAL: Validate that input looks like JSON before attempting to parse
-                // Reject raw strings (e.g., repo names like "cachyos") that aren't JSON
-                if !trimmed.starts_with('{') || !trimmed.ends_with('}') {
-                    logger::error(&format!(
-                        "Received non-JSON input on stdin: {:?}",
-                        trimmed.chars().take(80).collect::<String>()
-                    ));
-                    emit_progress(
-                        0,
-                        &format!(
-                            "Error: Invalid input on stdin. Expected JSON command, got raw string: {:?}. This may indicate a serialization bug in the GUI.",
-                            trimmed.chars().take(80).collect::<String>()
-                        ),
-                    );
-                    continue; // Skip this line and try next
-                }
-                match serde_json::from_str::<HelperCommand>(trimmed) {
-                    Ok(cmd) => execute_command(cmd, &mut alpm),
-                    Err(e) => {
-                        let err_str = e.to_string();
-                        let is_outdated_helper = err_str.contains("unknown variant")
-                            && (trimmed.contains("AlpmInstall")
-                                || trimmed.contains("AlpmUpgrade")
-                                || trimmed.contains("AlpmUninstall"));
-                        if is_outdated_helper {
-                            emit_progress(
-                                0,
-                                "Error: The installed monarch-helper is outdated and does not support ALPM install/update. Please update monarch-store: run 'pacman -Syu monarch-store' or reinstall the package so the helper matches this app version.",
-                            );
-                        } else {
-                            let preview: String = trimmed.chars().take(80).collect();
-                            emit_progress(
-                                0,
-                                &format!(
-                                    "Error: Failed to parse command JSON: {}. Payload preview: {:?}",
-                                    err_str, preview
-                                ),
-                            );
-                        }
-                    }
-                }
-            }
+    // Set log callback to suppress noise (set_log_cb(data, FnMut(LogLevel, &str, &mut T))
+    alpm.set_log_cb((), |level, msg, _: &mut ()| {
+        downloads::note_log_line(msg);
+        if level.bits() >= alpm::LogLevel::WARNING.bits() {
+            logger::warn(&format!("[ALPM {:?}] {}", level, msg));
         }
+    });
+    // Hook and install-script output, for the post-transaction notes
+    hook_notes::install(&mut alpm);
+
+    // Improved Repository Registration: Use pacman-conf to get accurate DB locations and servers
+    if let Err(e) = register_repositories(&mut alpm) {
+        emit_progress(
+            0,
+            &format!(
+                "Warning: Failed to register repositories via pacman-conf: {}",
+                e
+            ),
+        );
+        // Fail gracefully if pacman-conf fails
     }
+    // Remove any syncdb that has no servers (avoids "no servers configured for repository" during sync)
+    remove_syncdbs_with_no_servers(&mut alpm);
 
-    logger::info("monarch-helper exiting normally");
-    Ok(())
+    // Pinned packages (and any user IgnorePkg) must be skipped by sysupgrade
+    pins::load_ignored_packages(&mut alpm);
+
+    Ok(alpm)
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "Helper crashed (unknown panic)".to_string()
+    }
 }
 
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    // Handshake: unprivileged and before ALPM, so the GUI can ask without an auth prompt.
+    if args.get(1).map(String::as_str) == Some("--capabilities") {
+        println!(
+            "{}",
+            serde_json::to_string(&capabilities()).unwrap_or_default()
+        );
+        std::process::exit(monarch_types::exit_code::SUCCESS);
+    }
+    // The GUI's transport: JSON-RPC on the socketpair it passed as stdin and stdout
+    if args.get(1).map(String::as_str) == Some("--rpc") {
+        std::process::exit(rpc::serve());
+    }
+    if args.get(1).map(String::as_str) == Some("--agent") {
+        let config = args.get(2).map(String::as_str).unwrap_or_default();
+        std::process::exit(agent::start(config));
+    }
+
     let result = std::panic::catch_unwind(|| {
         run().map_err(|e| {
             logger::error(&e.to_string());
@@ -717,17 +938,13 @@ fn main() {
     match result {
         Ok(Ok(())) => {}
         Ok(Err(e)) => {
+            outcome::fail(&e);
             emit_progress(0, &format!("Error: {}", e));
         }
         Err(panic_payload) => {
-            let msg = if let Some(s) = panic_payload.downcast_ref::<&str>() {
-                s.to_string()
-            } else if let Some(s) = panic_payload.downcast_ref::<String>() {
-                s.clone()
-            } else {
-                "Helper crashed (unknown panic)".to_string()
-            };
+            let msg = panic_message(panic_payload);
             logger::panic_msg(&msg);
+            outcome::fail(&msg);
             emit_progress(0, &format!("Error: {}", msg));
             // Release ALPM lock on panic so a zombie lockfile doesn't break the system.
             if std::path::Path::new(self_healer::DB_LOCK_PATH).exists() {
@@ -735,6 +952,8 @@ fn main() {
             }
         }
     }
+    let summary = outcome::finish();
+    std::process::exit(summary.exit_code);
 }
 
 /// Ensure we can lock the DB.
@@ -792,6 +1011,7 @@ where
             } else {
                 emit_progress(10, "Keys reset. Retrying operation...");
                 if let Err(retry_e) = action() {
+                    outcome::fail(&retry_e);
                     emit_classified_error(&retry_e);
                     emit_progress(0, &format!("Error (Persistent): {}", retry_e));
                 } else {
@@ -802,12 +1022,77 @@ where
         }
 
         // If not sig error or repair failed/didn't help: emit both legacy line and structured error for GUI recovery UI
+        outcome::fail(&e);
         emit_classified_error(&e);
         emit_progress(0, &format!("Error: {}", e));
     }
 }
 
+/// What this helper build understands, for `--capabilities` and GetCapabilities.
+fn capabilities() -> monarch_types::HelperCapabilities {
+    monarch_types::HelperCapabilities::current(env!("CARGO_PKG_VERSION"))
+}
+
+/// Package names the caller asked for, so the final result can report the ones no step
+/// touched.
+fn requested_packages(cmd: &HelperCommand) -> Vec<String> {
+    match cmd {
+        HelperCommand::AlpmInstall { packages, .. }
+        | HelperCommand::AlpmUninstall { packages, .. } => packages.clone(),
+        HelperCommand::AlpmUpgrade { packages, .. } => packages.clone().unwrap_or_default(),
+        HelperCommand::ExecuteBatch { manifest, .. } => manifest
+            .remove_targets
+            .iter()
+            .chain(&manifest.install_targets)
+            .cloned()
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
 fn execute_command(cmd: HelperCommand, alpm: &mut Alpm) {
+    let dry_run = match &cmd {
+        HelperCommand::AlpmInstall { dry_run, .. }
+        | HelperCommand::AlpmUninstall { dry_run, .. }
+        | HelperCommand::AlpmUpgrade { dry_run, .. }
+        | HelperCommand::ExecuteBatch { dry_run, .. }
+        | HelperCommand::CleanPackageCache { dry_run, .. } => *dry_run,
+        _ => false,
+    };
+    outcome::begin(&requested_packages(&cmd), dry_run);
+
+    // Dry runs may refresh the sync DBs (sync_first, the upgrade, a batch's refresh step).
+    // They do so on a scratch copy so the live DBs never move ahead of the installed
+    // packages, which would leave the system partially upgraded.
+    let _scratch_dir;
+    let mut scratch_alpm;
+    let alpm = if dry_run
+        && matches!(
+            cmd,
+            HelperCommand::AlpmInstall { .. }
+                | HelperCommand::AlpmUpgrade { .. }
+                | HelperCommand::ExecuteBatch { .. }
+        ) {
+        let scratch = transactions::scratch_dbpath().and_then(|dir| {
+            let handle = init_alpm_at(&dir.path().to_string_lossy()).map_err(|e| e.to_string())?;
+            Ok((dir, handle))
+        });
+        match scratch {
+            Ok((dir, handle)) => {
+                _scratch_dir = dir;
+                scratch_alpm = handle;
+                &mut scratch_alpm
+            }
+            Err(e) => {
+                outcome::fail(&e);
+                emit_progress(0, &format!("Error: {}", e));
+                return;
+            }
+        }
+    } else {
+        alpm
+    };
+
     match cmd {
         // ✅ NEW: Full ALPM Transactions
         HelperCommand::AlpmInstall {
@@ -816,6 +1101,8 @@ fn execute_command(cmd: HelperCommand, alpm: &mut Alpm) {
             enabled_repos: _,
             cpu_optimization,
             target_repo,
+            phase,
+            dry_run,
         } => {
             execute_with_healing(|| {
                 if let Err(e) = ensure_db_ready() {
@@ -826,6 +1113,8 @@ fn execute_command(cmd: HelperCommand, alpm: &mut Alpm) {
                     sync_first,
                     cpu_optimization.clone(),
                     target_repo.clone(),
+                    &phase,
+                    dry_run,
                     alpm,
                 )
             });
@@ -838,16 +1127,21 @@ fn execute_command(cmd: HelperCommand, alpm: &mut Alpm) {
         HelperCommand::AlpmUninstall {
             packages,
             remove_deps,
+            dry_run,
         } => {
             // Uninstall usually doesn't involve signatures, but db lock might need check.
             // We can use simple execution or healing if we suspect DB lock issues?
             // Prompt only requested self-healing for "Invalid Signature". Uninstall won't verify sigs.
             if let Err(e) = ensure_db_ready() {
+                outcome::fail(&e);
                 emit_classified_error(&e);
                 emit_progress(0, &e);
                 return;
             }
-            if let Err(e) = transactions::execute_alpm_uninstall(packages, remove_deps, alpm) {
+            if let Err(e) =
+                transactions::execute_alpm_uninstall(packages, remove_deps, dry_run, alpm)
+            {
+                outcome::fail(&e);
                 emit_classified_error(&e);
                 emit_progress(0, &format!("Error: {}", e));
             }
@@ -855,12 +1149,26 @@ fn execute_command(cmd: HelperCommand, alpm: &mut Alpm) {
         HelperCommand::AlpmUpgrade {
             packages,
             enabled_repos: _,
+            phase,
+            dry_run,
         } => {
             execute_with_healing(|| {
                 if let Err(e) = ensure_db_ready() {
                     return Err(e);
                 }
-                let mut trans = safe_transaction::SafeUpdateTransaction::new(alpm);
+                // SafeUpdateTransaction relies on the caller for fresh DBs; a download for
+                // later has no caller that synced, so it is a -Syuw of its own. The commit
+                // phase must not re-sync.
+                if phase == transactions::TransactionPhase::DownloadOnly {
+                    return transactions::execute_alpm_download_upgrade(
+                        packages.clone(),
+                        dry_run,
+                        alpm,
+                    );
+                }
+                let mut trans = safe_transaction::SafeUpdateTransaction::new(alpm)
+                    .with_phase(phase.clone())
+                    .with_dry_run(dry_run);
                 if let Some(targets) = packages.clone() {
                     trans = trans.with_targets(targets);
                 }
@@ -898,97 +1206,105 @@ fn execute_command(cmd: HelperCommand, alpm: &mut Alpm) {
                 if allowed_paths.is_empty() {
                     return Err("Error: No valid paths for AlpmInstallFiles".to_string());
                 }
-                transactions::execute_alpm_install_files(allowed_paths, alpm)
+                transactions::execute_alpm_install_files(allowed_paths, false, alpm)
             });
         }
-        HelperCommand::ExecuteBatch { manifest } => {
-            // Operation "Silent Guard": Execute all steps under ONE lock acquisition
-
-            // 0a. Remove Stale Lock (Pre-transaction maintenance)
-            if manifest.remove_lock {
-                let _ = remove_lock(); // Special logic in remove_lock handles safety
-            }
-
-            // 0b. Clear Cache (Maintenance)
-            if manifest.clear_cache {
-                let _ = clear_cache(alpm, 0); // Clear everything
-            }
-
-            // 1. Refresh DB
-            if manifest.refresh_db {
-                if let Err(e) = transactions::force_refresh_sync_dbs(alpm) {
-                    emit_progress(0, &format!("Error refreshing databases: {}", e));
-                    return;
+        HelperCommand::SetPinnedPackages { packages } => {
+            match pins::set_pinned_packages(&packages) {
+                Ok(()) => emit_progress(100, "Pinned packages updated"),
+                Err(e) => {
+                    outcome::fail(&e);
+                    emit_classified_error(&e);
+                    emit_progress(0, &format!("Error: {}", e));
                 }
             }
-
-            // 2. System Upgrade
-            if manifest.update_system {
-                if let Err(e) = transactions::execute_alpm_upgrade(None, alpm) {
-                    emit_progress(0, &format!("Error upgrading system: {}", e));
-                    return;
+        }
+        HelperCommand::SetMirrorlist { repo, servers } => {
+            match mirrors::set_mirrorlist(&repo, &servers) {
+                Ok(()) => emit_progress(100, "Mirrorlist updated"),
+                Err(e) => {
+                    outcome::fail(&e);
+                    emit_classified_error(&e);
+                    emit_progress(0, &format!("Error: {}", e));
                 }
             }
-
-            // 3. Remove Targets
-            if !manifest.remove_targets.is_empty() {
-                if let Err(e) = transactions::execute_alpm_uninstall(
-                    manifest.remove_targets.clone(),
-                    true,
-                    alpm,
-                ) {
-                    emit_progress(0, &format!("Error removing packages: {}", e));
-                    return;
+        }
+        HelperCommand::RestoreConfigFile { path, source } => {
+            match config_restore::restore_config_file(alpm, &path, &source) {
+                Ok(()) => emit_progress(100, &format!("Restored {}", path)),
+                Err(e) => {
+                    outcome::fail(&e);
+                    emit_classified_error(&e);
+                    emit_progress(0, &format!("Error: {}", e));
                 }
             }
-
-            // 4. Install Targets (Repo + Local)
-            // Note: ALPM allows installing repo pkgs and local files in one transaction?
-            // The current helper functions are separate. We can call them sequentially since we hold the lock.
-            // The main() function holds the ALPM handle which implies the lock is held (if configured).
-            // Actually ALPM lock matches the handle lifetime or transaction lifetime.
-            // Our helper is short-lived process. One invocation = one ALPM instance = one lock.
-            // So calling these sequentially IS atomic regarding the lock.
-
-            let mut installed_anything = false;
-
-            if !manifest.install_targets.is_empty() {
-                // sync_first false because we handled it in step 1 if needed
-                // cpu strictness default (None)
-                if let Err(e) = transactions::execute_alpm_install(
-                    manifest.install_targets.clone(),
-                    false,
-                    None,
-                    None,
-                    alpm,
-                ) {
-                    emit_progress(0, &format!("Error installing repo packages: {}", e));
-                    return;
-                }
-                installed_anything = true;
+        }
+        HelperCommand::Rescue { action } => match rescue::run(action, alpm) {
+            Ok(summary) => emit_progress(100, &summary),
+            Err(e) => {
+                outcome::fail(&e);
+                emit_classified_error(&e);
+                emit_progress(0, &format!("Error: {}", e));
             }
-
-            // 4b. Install Local Files (Built AUR packages)
-            if !manifest.local_paths.is_empty() {
-                if let Err(e) =
-                    transactions::execute_alpm_install_files(manifest.local_paths.clone(), alpm)
-                {
-                    emit_progress(0, &format!("Error installing local packages: {}", e));
-                    return;
+        },
+        HelperCommand::CleanPackageCache {
+            keep,
+            uninstalled_only,
+            dry_run,
+        } => {
+            let policy = cache_gc::RetentionPolicy {
+                keep,
+                keep_installed: true,
+                uninstalled_only,
+            };
+            if let Err(e) = cache_gc::run(alpm, policy, dry_run) {
+                outcome::fail(&e);
+                emit_progress(0, &format!("Error: {}", e));
+            }
+        }
+        HelperCommand::ManageService { unit, action } => match services::run(alpm, &unit, action) {
+            Ok(summary) => emit_progress(100, &summary),
+            Err(e) => {
+                outcome::fail(&e);
+                emit_progress(0, &format!("Error: {}", e));
+            }
+        },
+        HelperCommand::SetNetworkOptions { options } => {
+            match network::set_network_options(&options) {
+                Ok(()) => emit_progress(100, "Network settings updated"),
+                Err(e) => {
+                    outcome::fail(&e);
+                    emit_progress(0, &format!("Error: {}", e));
                 }
-                installed_anything = true;
             }
-
-            if !installed_anything
-                && !manifest.update_system
-                && !manifest.refresh_db
-                && manifest.remove_targets.is_empty()
-            {
-                emit_progress(100, "Transaction successful (No actions required)");
-            } else {
-                emit_progress(100, "Batch Transaction Complete");
+        }
+        HelperCommand::GetCapabilities => {
+            let event = transactions::AlpmProgressEvent {
+                event_type: "capabilities".to_string(),
+                package: None,
+                percent: None,
+                downloaded: None,
+                total: None,
+                speed_bps: None,
+                eta_seconds: None,
+                message: serde_json::to_string(&capabilities()).unwrap_or_default(),
+            };
+            if let Ok(json) = serde_json::to_string(&event) {
+                progress::send_progress_line(json);
+            }
+        }
+        HelperCommand::Keyring { action } => match keyring::run(alpm, action) {
+            Ok(summary) => emit_progress(100, &summary),
+            Err(e) => {
+                outcome::fail(&e);
+                emit_classified_error(&e);
+                emit_progress(0, &format!("Error: {}", e));
             }
+        },
+        HelperCommand::ExecuteBatch { manifest, dry_run } => {
+            batch::execute(alpm, manifest, dry_run);
         }
+        HelperCommand::ResumeBatch => batch::resume(alpm),
     }
 }
 
@@ -1035,12 +1351,7 @@ fn register_repositories(alpm: &mut Alpm) -> Result<(), Box<dyn std::error::Erro
         .into());
     }
 
-    let repo_list_str = String::from_utf8_lossy(&output.stdout);
-    let repo_names: Vec<&str> = repo_list_str
-        .lines()
-        .map(|s| s.trim())
-        .filter(|s| !s.is_empty())
-        .collect();
+    let repo_names = pacman_conf::parse_repo_list(&String::from_utf8_lossy(&output.stdout));
 
     emit_progress(
         5,
@@ -1048,6 +1359,7 @@ fn register_repositories(alpm: &mut Alpm) -> Result<(), Box<dyn std::error::Erro
     );
 
     for repo_name in repo_names {
+        let repo_name = repo_name.as_str();
         logger::trace(&format!("Querying details for repo: {}", repo_name));
         // 2. Get details for each repo
         let details_out = Command::new("pacman-conf")
@@ -1060,65 +1372,26 @@ fn register_repositories(alpm: &mut Alpm) -> Result<(), Box<dyn std::error::Erro
             continue;
         }
 
-        let details_str = String::from_utf8_lossy(&details_out.stdout);
-        let mut servers = Vec::new();
-        let mut siglevel = SigLevel::USE_DEFAULT;
-        let mut usage = alpm::Usage::ALL;
-
-        for line in details_str.lines() {
-            let line = line.trim();
-            if line.contains("Server = ") || line.contains("Server=") {
-                let val = if line.contains("Server = ") {
-                    line.splitn(2, "Server = ").nth(1).unwrap_or("")
-                } else {
-                    line.splitn(2, "Server=").nth(1).unwrap_or("")
-                }
-                .trim();
-
-                let server = val.split('#').next().unwrap_or(val).trim();
-                if !server.is_empty() {
-                    servers.push(server.to_string());
-                }
-            } else if line.contains("SigLevel = ") || line.contains("SigLevel=") {
-                let val = if line.contains("SigLevel = ") {
-                    line.splitn(2, "SigLevel = ").nth(1).unwrap_or("")
-                } else {
-                    line.splitn(2, "SigLevel=").nth(1).unwrap_or("")
-                }
-                .trim();
-                let val_lower = val.to_lowercase();
-                if val_lower.contains("never") {
-                    siglevel = SigLevel::NONE;
-                } else if val_lower.contains("taroptional") || val_lower.contains("packageoptional")
-                {
-                    siglevel = SigLevel::PACKAGE_OPTIONAL;
-                } else if val_lower.contains("required") {
-                    siglevel = SigLevel::USE_DEFAULT;
-                }
-            } else if line.contains("Usage = ") || line.contains("Usage=") {
-                let val = if line.contains("Usage = ") {
-                    line.splitn(2, "Usage = ").nth(1).unwrap_or("")
-                } else {
-                    line.splitn(2, "Usage=").nth(1).unwrap_or("")
-                }
-                .trim();
-
-                if val.contains("Sync") {
-                    usage |= alpm::Usage::SYNC;
-                }
-                if val.contains("Search") {
-                    usage |= alpm::Usage::SEARCH;
-                }
-                if val.contains("Install") {
-                    usage |= alpm::Usage::INSTALL;
-                }
-                if val.contains("Upgrade") {
-                    usage |= alpm::Usage::UPGRADE;
-                }
-                if val.contains("All") {
-                    usage = alpm::Usage::ALL;
-                }
-            }
+        let details =
+            pacman_conf::parse_repo_details(&String::from_utf8_lossy(&details_out.stdout));
+        let servers = details.servers;
+        let siglevel = match details.siglevel {
+            pacman_conf::RepoSigLevel::Never => SigLevel::NONE,
+            pacman_conf::RepoSigLevel::PackageOptional => SigLevel::PACKAGE_OPTIONAL,
+            pacman_conf::RepoSigLevel::Default => SigLevel::USE_DEFAULT,
+        };
+        let mut usage = alpm::Usage::empty();
+        if details.usage.sync {
+            usage |= alpm::Usage::SYNC;
+        }
+        if details.usage.search {
+            usage |= alpm::Usage::SEARCH;
+        }
+        if details.usage.install {
+            usage |= alpm::Usage::INSTALL;
+        }
+        if details.usage.upgrade {
+            usage |= alpm::Usage::UPGRADE;
         }
 
         if !servers.is_empty() {
@@ -1160,38 +1433,15 @@ fn register_repositories(alpm: &mut Alpm) -> Result<(), Box<dyn std::error::Erro
     Ok(())
 }
 
-fn clear_cache(_alpm: &mut Alpm, _keep: u32) -> Result<(), String> {
-    emit_progress(0, "Clearing package cache...");
-    let cache_dir = "/var/cache/pacman/pkg";
-    if let Ok(entries) = std::fs::read_dir(cache_dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path
-                .extension()
-                .map(|s| s == "zst" || s == "xz")
-                .unwrap_or(false)
-            {
-                let _ = std::fs::remove_file(path);
-            }
-        }
-    }
-    emit_progress(100, "Cache cleared");
-    Ok(())
-}
-
 fn remove_lock() -> Result<(), String> {
     let lock_path = "/var/lib/pacman/db.lck";
     if std::path::Path::new(lock_path).exists() {
-        // SECURITY: Check if pacman is running first
-        let is_running = std::process::Command::new("pgrep")
-            .arg("-x")
-            .arg("pacman")
-            .status()
-            .map(|s| s.success())
-            .unwrap_or(false);
-
-        if is_running {
-            return Err("Cannot remove lock: Pacman process is active.".to_string());
+        // SECURITY: Only a lock nobody holds open is stale
+        if let Some((pid, name)) = self_healer::db_lock_holder() {
+            return Err(format!(
+                "Cannot remove lock: it is held by {} (pid {}).",
+                name, pid
+            ));
         }
 
         std::fs::remove_file(lock_path).map_err(|e| e.to_string())?;
@@ -0,0 +1,195 @@
+//! Mirrorlist writes (SetMirrorlist). Only the known mirrorlist files can be targeted and
+//! every line is rebuilt from validated URLs, so the GUI can't inject other pacman options.
+//! Also the automatic re-rank the download failover falls back on (see downloads.rs).
+
+use crate::logger;
+use std::path::Path;
+use std::process::Command;
+
+/// Repo key → mirrorlist file. Keys match what the GUI sends (see commands::mirrors).
+pub fn mirrorlist_path(repo: &str) -> Option<&'static str> {
+    match repo {
+        "arch" => Some("/etc/pacman.d/mirrorlist"),
+        "cachyos" => Some("/etc/pacman.d/cachyos-mirrorlist"),
+        "cachyos-v3" => Some("/etc/pacman.d/cachyos-v3-mirrorlist"),
+        "cachyos-v4" => Some("/etc/pacman.d/cachyos-v4-mirrorlist"),
+        "chaotic-aur" => Some("/etc/pacman.d/chaotic-mirrorlist"),
+        "endeavouros" => Some("/etc/pacman.d/endeavouros-mirrorlist"),
+        _ => None,
+    }
+}
+
+/// Sync DB name → repo key, the same mapping the GUI uses for its mirror pages.
+pub fn mirrorlist_key(db: &str) -> Option<&'static str> {
+    let db = db.to_lowercase();
+    let key = match db.as_str() {
+        "core" | "extra" | "multilib" | "core-testing" | "extra-testing" | "multilib-testing" => {
+            "arch"
+        }
+        d if d.starts_with("cachyos") && (d.ends_with("-v4") || d.contains("znver4")) => {
+            "cachyos-v4"
+        }
+        d if d.starts_with("cachyos") && d.ends_with("-v3") => "cachyos-v3",
+        d if d.starts_with("cachyos") => "cachyos",
+        d if d.starts_with("chaotic") => "chaotic-aur",
+        "endeavouros" => "endeavouros",
+        _ => return None,
+    };
+    Some(key)
+}
+
+/// http(s) URL with a host and nothing that could end the `Server = ` line early.
+pub fn valid_server(url: &str) -> bool {
+    let Some(rest) = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+    else {
+        return false;
+    };
+    let host = rest.split('/').next().unwrap_or("");
+    !host.is_empty()
+        && url.len() <= 512
+        && !url
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control() || c == '#' || c == '\\')
+}
+
+pub fn render_mirrorlist(repo: &str, servers: &[String]) -> String {
+    let mut out = format!(
+        "# {} mirrorlist, written by MonArch Store.\n# Order is priority: pacman tries the first server first.\n\n",
+        repo
+    );
+    for server in servers {
+        out.push_str(&format!("Server = {}\n", server));
+    }
+    out
+}
+
+/// Replace the mirrorlist for `repo`, keeping the previous one as `<path>.monarch-bak`.
+pub fn set_mirrorlist(repo: &str, servers: &[String]) -> Result<(), String> {
+    let path = mirrorlist_path(repo).ok_or_else(|| format!("Unknown mirrorlist repo: {}", repo))?;
+    if servers.is_empty() {
+        return Err("Refusing to write an empty mirrorlist".to_string());
+    }
+    if let Some(bad) = servers.iter().find(|s| !valid_server(s)) {
+        return Err(format!("Invalid mirror URL: {}", bad));
+    }
+
+    if std::path::Path::new(path).exists() {
+        let backup = format!("{}.monarch-bak", path);
+        std::fs::copy(path, &backup).map_err(|e| format!("Failed to back up {}: {}", path, e))?;
+    }
+    // Write-then-rename so pacman never reads a half-written list
+    let tmp = format!("{}.monarch-tmp", path);
+    std::fs::write(&tmp, render_mirrorlist(repo, servers))
+        .map_err(|e| format!("Failed to write {}: {}", tmp, e))?;
+    std::fs::rename(&tmp, path).map_err(|e| format!("Failed to update {}: {}", path, e))?;
+    logger::info(&format!("Wrote {} servers to {}", servers.len(), path));
+    Ok(())
+}
+
+fn installed(program: &str) -> bool {
+    ["/usr/bin", "/usr/local/bin"]
+        .iter()
+        .any(|dir| Path::new(dir).join(program).exists())
+}
+
+/// The tool `rank_mirrors` would use for `key`: pacman-mirrors on Manjaro, reflector for
+/// Arch, otherwise rate-mirrors. Manjaro's own list is never handed to reflector.
+pub fn rank_tool(key: &str) -> Option<&'static str> {
+    let manjaro = Path::new("/etc/manjaro-release").exists();
+    if key == "arch" && manjaro {
+        return installed("pacman-mirrors").then_some("pacman-mirrors");
+    }
+    if key == "arch" && installed("reflector") {
+        return Some("reflector");
+    }
+    installed("rate-mirrors").then_some("rate-mirrors")
+}
+
+/// `rate-mirrors` subcommand for a repo key.
+fn rate_mirrors_target(key: &str) -> &'static str {
+    match key {
+        "arch" => "arch",
+        "chaotic-aur" => "chaotic-aur",
+        "endeavouros" => "endeavouros",
+        _ => "cachyos",
+    }
+}
+
+/// Valid `Server = ` URLs from ranking tool output, in order and without duplicates.
+pub fn parse_servers(text: &str) -> Vec<String> {
+    let mut servers: Vec<String> = Vec::new();
+    for line in text.lines() {
+        let Some(value) = line
+            .trim()
+            .strip_prefix("Server")
+            .and_then(|rest| rest.trim_start().strip_prefix('='))
+        else {
+            continue;
+        };
+        let Some(url) = value.split_whitespace().next() else {
+            continue;
+        };
+        if valid_server(url) && !servers.iter().any(|s| s == url) {
+            servers.push(url.to_string());
+        }
+    }
+    servers
+}
+
+/// `servers` with those on `host` moved to the end, so a ranking that still rates the
+/// failing mirror well doesn't start from it again.
+pub fn demote_host(servers: Vec<String>, host: &str) -> Vec<String> {
+    let (bad, mut good): (Vec<String>, Vec<String>) = servers
+        .into_iter()
+        .partition(|s| crate::downloads::mirror_host(s) == host);
+    good.extend(bad);
+    good
+}
+
+/// Re-rank the mirrorlist for `key` with [`rank_tool`], putting `avoid_host` last.
+/// Returns the tool that was used.
+pub fn rerank_mirrorlist(key: &str, avoid_host: &str) -> Result<&'static str, String> {
+    let tool = rank_tool(key).ok_or_else(|| {
+        format!(
+            "No mirror ranking tool for {} (install reflector or rate-mirrors)",
+            key
+        )
+    })?;
+    let mut cmd = Command::new(tool);
+    match tool {
+        // Writes /etc/pacman.d/mirrorlist itself, same as rank_mirrors
+        "pacman-mirrors" => cmd.args(["-f", "5"]),
+        "reflector" => cmd.args(["--protocol", "https", "--latest", "20", "--sort", "rate"]),
+        _ => cmd.args([
+            "--allow-root",
+            "--protocol",
+            "https",
+            rate_mirrors_target(key),
+        ]),
+    };
+    let output = cmd
+        .output()
+        .map_err(|e| format!("Could not run {}: {}", tool, e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{} failed: {}",
+            tool,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    if tool == "pacman-mirrors" {
+        return Ok(tool);
+    }
+
+    let servers = demote_host(
+        parse_servers(&String::from_utf8_lossy(&output.stdout)),
+        avoid_host,
+    );
+    if servers.is_empty() {
+        return Err(format!("{} returned no mirrors", tool));
+    }
+    set_mirrorlist(key, &servers)?;
+    Ok(tool)
+}
@@ -0,0 +1,221 @@
+//! Network options for package downloads (SetNetworkOptions). The GUI keeps its own copy
+//! for its HTTP clients; the helper keeps this root-owned one, so only an authorized user
+//! can point root's downloads at a proxy.
+//!
+//! A proxy alone reaches libalpm's curl through the usual proxy variables, which keeps
+//! parallel downloads and per-byte progress. libalpm has no knob for a rate limit or for
+//! IPv4-only, so with either set the files are fetched by the `curl` binary through the
+//! fetch callback instead: one at a time, resuming `.part` files just like libalpm does.
+
+use alpm::{Alpm, FetchResult};
+use monarch_types::NetworkOptions;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::downloads;
+use crate::logger;
+
+pub const NETWORK_CONF: &str = "/var/lib/monarch/network.json";
+
+const PROXY_VARS: &[&str] = &[
+    "http_proxy",
+    "https_proxy",
+    "ftp_proxy",
+    "all_proxy",
+    "HTTP_PROXY",
+    "HTTPS_PROXY",
+    "ALL_PROXY",
+];
+
+/// Whether the proxy variables are ours, so clearing the proxy in a long-lived session
+/// doesn't remove variables sudo passed through.
+static PROXY_FROM_OPTIONS: AtomicBool = AtomicBool::new(false);
+
+/// Validate and save the options for later transactions. The file can hold proxy
+/// credentials, so it is root-only.
+pub fn set_network_options(options: &NetworkOptions) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let options = options.clone().normalized();
+    options.validate()?;
+    let path = Path::new(NETWORK_CONF);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(&options).map_err(|e| e.to_string())?;
+    let tmp = format!("{}.tmp", NETWORK_CONF);
+    std::fs::write(&tmp, json).map_err(|e| format!("Failed to write {}: {}", tmp, e))?;
+    std::fs::set_permissions(&tmp, std::fs::Permissions::from_mode(0o600))
+        .map_err(|e| format!("Failed to restrict {}: {}", tmp, e))?;
+    std::fs::rename(&tmp, NETWORK_CONF)
+        .map_err(|e| format!("Failed to update {}: {}", NETWORK_CONF, e))?;
+    logger::info(&format!(
+        "Network options set: proxy {}, rate limit {:?} KiB/s, IPv4 only {}",
+        if options.proxy.is_some() { "on" } else { "off" },
+        options.rate_limit_kbps,
+        options.ipv4_only
+    ));
+    Ok(())
+}
+
+/// The saved options; the defaults if there are none or they don't validate.
+pub fn load() -> NetworkOptions {
+    let options = std::fs::read_to_string(NETWORK_CONF)
+        .ok()
+        .and_then(|c| serde_json::from_str::<NetworkOptions>(&c).ok())
+        .unwrap_or_default()
+        .normalized();
+    match options.validate() {
+        Ok(()) => options,
+        Err(e) => {
+            logger::warn(&format!("Ignoring {}: {}", NETWORK_CONF, e));
+            NetworkOptions::default()
+        }
+    }
+}
+
+/// Apply the saved options to a fresh ALPM handle.
+pub fn apply(alpm: &mut Alpm) {
+    let options = load();
+    match &options.proxy {
+        Some(proxy) => {
+            for var in PROXY_VARS {
+                std::env::set_var(var, proxy);
+            }
+            PROXY_FROM_OPTIONS.store(true, Ordering::SeqCst);
+        }
+        None => {
+            if PROXY_FROM_OPTIONS.swap(false, Ordering::SeqCst) {
+                for var in PROXY_VARS {
+                    std::env::remove_var(var);
+                }
+            }
+        }
+    }
+    if options.rate_limit_kbps.is_some() || options.ipv4_only {
+        logger::info("Rate limit / IPv4-only set: downloading with the curl binary");
+        alpm.set_fetch_cb(options, |url, dir, force, options| {
+            fetch_with_curl(url, Path::new(dir), force, options)
+        });
+    }
+}
+
+/// Arguments for fetching `url` into `part`, resuming it, and only if newer than `newer_than`.
+/// The proxy comes from the environment ([`apply`]) so its credentials stay out of `ps`.
+pub fn curl_args(
+    url: &str,
+    part: &Path,
+    newer_than: Option<&Path>,
+    options: &NetworkOptions,
+) -> Vec<String> {
+    let mut args: Vec<String> = [
+        "--fail",
+        "--location",
+        "--silent",
+        "--show-error",
+        "--connect-timeout",
+        "15",
+        // Give up on a stalled transfer like libalpm does (under 1 byte/s for 30 s)
+        "--speed-limit",
+        "1",
+        "--speed-time",
+        "30",
+        "--continue-at",
+        "-",
+    ]
+    .iter()
+    .map(|a| a.to_string())
+    .collect();
+    if let Some(kbps) = options.rate_limit_kbps {
+        args.push("--limit-rate".to_string());
+        args.push(format!("{}k", kbps));
+    }
+    if options.ipv4_only {
+        args.push("--ipv4".to_string());
+    }
+    if let Some(existing) = newer_than {
+        args.push("--time-cond".to_string());
+        args.push(existing.display().to_string());
+    }
+    args.push("--output".to_string());
+    args.push(part.display().to_string());
+    args.push(url.to_string());
+    args
+}
+
+fn fetch_with_curl(url: &str, dir: &Path, force: bool, options: &NetworkOptions) -> FetchResult {
+    let Some(name) = url.rsplit('/').next().filter(|n| !n.is_empty()) else {
+        return FetchResult::Err;
+    };
+    let dest = dir.join(name);
+    let part = PathBuf::from(format!("{}.part", dest.display()));
+    if force {
+        let _ = std::fs::remove_file(&part);
+    }
+    let newer_than = (!force && dest.exists()).then_some(dest.as_path());
+    logger::trace(&format!("curl fetch: {}", url));
+    let status = Command::new("curl")
+        .args(curl_args(url, &part, newer_than, options))
+        .status();
+    match status {
+        Ok(s) if s.success() => {
+            // With --time-cond and nothing newer curl writes no file
+            if !part.exists() {
+                return FetchResult::FileExists;
+            }
+            match std::fs::rename(&part, &dest) {
+                Ok(()) => FetchResult::Ok,
+                Err(e) => {
+                    logger::warn(&format!("Could not move {}: {}", part.display(), e));
+                    FetchResult::Err
+                }
+            }
+        }
+        other => {
+            let reason = match other {
+                Ok(s) => format!("curl exited with {}", s),
+                Err(e) => format!("could not run curl: {}", e),
+            };
+            let host = downloads::mirror_host(url);
+            // Same line libalpm logs, so the failover events read the same
+            downloads::note_log_line(&format!(
+                "failed retrieving file '{}' from {} : {}",
+                name, host, reason
+            ));
+            FetchResult::Err
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_curl_args() {
+        let options = NetworkOptions {
+            proxy: Some("http://proxy.example:3128".into()),
+            rate_limit_kbps: Some(256),
+            ipv4_only: true,
+        };
+        let url = "https://mirror.example/extra/os/x86_64/vlc-3.0.21-1-x86_64.pkg.tar.zst";
+        let part = Path::new("/var/cache/pacman/pkg/vlc-3.0.21-1-x86_64.pkg.tar.zst.part");
+        let args = curl_args(url, part, None, &options);
+        let joined = args.join(" ");
+        assert!(joined.contains("--continue-at - "));
+        assert!(joined.contains("--limit-rate 256k"));
+        assert!(joined.contains("--ipv4"));
+        assert!(!joined.contains("proxy.example"));
+        assert!(!joined.contains("--time-cond"));
+        assert_eq!(args.last().map(String::as_str), Some(url));
+
+        let db = Path::new("/var/lib/pacman/sync/extra.db");
+        let args = curl_args(url, part, Some(db), &NetworkOptions::default());
+        assert!(args
+            .join(" ")
+            .contains("--time-cond /var/lib/pacman/sync/extra.db"));
+        assert!(!args.contains(&"--ipv4".to_string()));
+    }
+}
@@ -0,0 +1,211 @@
+//! Per-package outcome of the command being run. Transactions record what they committed
+//! (or, in a dry run, would commit); `finish` reports it once as the final
+//! `transaction_result` event and picks the exit code.
+
+use crate::alpm_errors::{classify_alpm_error, ErrorKind};
+use crate::progress;
+use monarch_types::{
+    exit_code, AlpmProgressEvent, DryRunChange, PackageOutcome, PackageResult, TransactionResult,
+};
+use std::sync::Mutex;
+
+#[derive(Default)]
+struct State {
+    /// Set once a command was parsed; without one the run ends as an invalid command.
+    started: bool,
+    dry_run: bool,
+    requested: Vec<String>,
+    packages: Vec<PackageResult>,
+    error: Option<String>,
+}
+
+static STATE: Mutex<Option<State>> = Mutex::new(None);
+
+fn with_state<R>(f: impl FnOnce(&mut State) -> R) -> R {
+    let mut guard = STATE.lock().unwrap_or_else(|e| e.into_inner());
+    f(guard.get_or_insert_with(State::default))
+}
+
+fn push(state: &mut State, result: PackageResult) {
+    // A later step (e.g. the retry after a self-repair) wins over an earlier record
+    state.packages.retain(|p| p.name != result.name);
+    state.packages.push(result);
+}
+
+/// A command was parsed. `requested` are the packages the caller named; those no step
+/// touched end up skipped, or failed if the run failed.
+pub fn begin(requested: &[String], dry_run: bool) {
+    with_state(|s| {
+        s.started = true;
+        s.dry_run = dry_run;
+        s.requested.extend(requested.iter().cloned());
+    });
+}
+
+/// Changes that were committed, or would be in a dry run.
+pub fn record(changes: &[DryRunChange]) {
+    record_as(changes, None);
+}
+
+/// Changes that were downloaded into the cache but not installed.
+pub fn record_downloaded(changes: &[DryRunChange]) {
+    record_as(changes, Some(PackageOutcome::Downloaded));
+}
+
+fn record_as(changes: &[DryRunChange], outcome: Option<PackageOutcome>) {
+    with_state(|s| {
+        for c in changes {
+            push(
+                s,
+                PackageResult {
+                    name: c.name.clone(),
+                    outcome: outcome.unwrap_or_else(|| PackageOutcome::from_action(c.action)),
+                    old_version: c.old_version.clone(),
+                    new_version: c.new_version.clone(),
+                    reason: None,
+                },
+            );
+        }
+    });
+}
+
+/// Changes whose commit failed with `reason`.
+pub fn record_failed(changes: &[DryRunChange], reason: &str) {
+    with_state(|s| {
+        for c in changes {
+            push(
+                s,
+                PackageResult {
+                    name: c.name.clone(),
+                    outcome: PackageOutcome::Failed,
+                    old_version: c.old_version.clone(),
+                    new_version: c.new_version.clone(),
+                    reason: Some(reason.to_string()),
+                },
+            );
+        }
+    });
+}
+
+/// Packages that were not attempted, e.g. because an earlier batch step failed.
+pub fn skip(names: &[String], reason: &str) {
+    with_state(|s| {
+        for name in names {
+            if s.packages.iter().any(|p| &p.name == name) {
+                continue;
+            }
+            push(
+                s,
+                PackageResult {
+                    name: name.clone(),
+                    outcome: PackageOutcome::Skipped,
+                    old_version: None,
+                    new_version: None,
+                    reason: Some(reason.to_string()),
+                },
+            );
+        }
+    });
+}
+
+/// The run failed. Only the first error is kept; later ones are usually fallout.
+pub fn fail(error: &str) {
+    with_state(|s| {
+        if s.error.is_none() {
+            s.error = Some(error.to_string());
+        }
+    });
+}
+
+/// Build the result from what was recorded. Requested packages no step touched are failed
+/// with the run's error, or skipped when the run succeeded (already up to date).
+fn summarize(mut state: State) -> TransactionResult {
+    for name in std::mem::take(&mut state.requested) {
+        if state.packages.iter().any(|p| p.name == name) {
+            continue;
+        }
+        let (outcome, reason) = match &state.error {
+            Some(e) => (PackageOutcome::Failed, e.clone()),
+            None => (PackageOutcome::Skipped, "Nothing to do".to_string()),
+        };
+        push(
+            &mut state,
+            PackageResult {
+                name,
+                outcome,
+                old_version: None,
+                new_version: None,
+                reason: Some(reason),
+            },
+        );
+    }
+
+    let exit_code = match &state.error {
+        _ if !state.started => exit_code::INVALID_COMMAND,
+        None => exit_code::SUCCESS,
+        Some(e) if classify_alpm_error(e).kind == ErrorKind::DatabaseLocked => exit_code::DB_LOCKED,
+        Some(_)
+            if !state.dry_run
+                && state.packages.iter().any(|p| {
+                    !matches!(p.outcome, PackageOutcome::Failed | PackageOutcome::Skipped)
+                }) =>
+        {
+            exit_code::PARTIAL
+        }
+        Some(_) => exit_code::FAILED,
+    };
+    let error = match (&state.error, state.started) {
+        (None, false) => Some("No valid command was received".to_string()),
+        (e, _) => e.clone(),
+    };
+    TransactionResult {
+        success: exit_code == exit_code::SUCCESS,
+        dry_run: state.dry_run,
+        packages: state.packages,
+        error,
+        exit_code,
+    }
+}
+
+/// Emit the `transaction_result` event. The helper exits with its `exit_code`.
+pub fn finish() -> TransactionResult {
+    let result = summarize(take_state());
+    emit(&result);
+    result
+}
+
+/// The user cancelled: report what was done so far, with [`exit_code::CANCELLED`].
+pub fn cancel() -> TransactionResult {
+    fail("Cancelled by user");
+    let mut result = summarize(take_state());
+    result.success = false;
+    result.exit_code = exit_code::CANCELLED;
+    emit(&result);
+    result
+}
+
+fn take_state() -> State {
+    STATE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .take()
+        .unwrap_or_default()
+}
+
+fn emit(result: &TransactionResult) {
+    let event = AlpmProgressEvent {
+        event_type: "transaction_result".to_string(),
+        package: None,
+        percent: None,
+        downloaded: None,
+        total: None,
+        speed_bps: None,
+        eta_seconds: None,
+        message: serde_json::to_string(result).unwrap_or_default(),
+    };
+    if let Ok(json) = serde_json::to_string(&event) {
+        progress::send_progress_line(json);
+    }
+    // The process exits right after; let the writer thread get the line out first
+    progress::drain(std::time::Duration::from_secs(2));
+}
@@ -0,0 +1,117 @@
+//! Parsing of `pacman-conf --repo-list` / `pacman-conf --repo <name>` output.
+//!
+//! Runs as root on text produced from user-editable config, so it is std-only and
+//! panic-free; the fuzz crate (src-tauri/fuzz) includes this file directly.
+
+/// Signature policy for a repo, reduced to what the helper maps onto ALPM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepoSigLevel {
+    Default,
+    PackageOptional,
+    Never,
+}
+
+/// `Usage =` values; a repo without any Usage line is usable for everything.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RepoUsage {
+    pub sync: bool,
+    pub search: bool,
+    pub install: bool,
+    pub upgrade: bool,
+}
+
+impl RepoUsage {
+    pub const ALL: RepoUsage = RepoUsage {
+        sync: true,
+        search: true,
+        install: true,
+        upgrade: true,
+    };
+    const NONE: RepoUsage = RepoUsage {
+        sync: false,
+        search: false,
+        install: false,
+        upgrade: false,
+    };
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoDetails {
+    pub servers: Vec<String>,
+    pub siglevel: RepoSigLevel,
+    pub usage: RepoUsage,
+}
+
+/// Repo names are used for sync DB file names (/var/lib/pacman/sync/<name>.db).
+pub fn is_valid_repo_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "._+-".contains(c))
+}
+
+/// Names from `pacman-conf --repo-list`; anything that isn't a plausible repo name is dropped.
+pub fn parse_repo_list(output: &str) -> Vec<String> {
+    let mut repos: Vec<String> = Vec::new();
+    for name in output.lines().map(str::trim) {
+        if is_valid_repo_name(name) && !repos.iter().any(|r| r == name) {
+            repos.push(name.to_string());
+        }
+    }
+    repos
+}
+
+/// `Key = value` (or `Key=value`) with the value's trailing `# comment` removed.
+fn key_value(line: &str) -> Option<(&str, &str)> {
+    let (key, value) = line.split_once('=')?;
+    let value = value.split('#').next().unwrap_or("").trim();
+    Some((key.trim(), value))
+}
+
+/// Servers, SigLevel and Usage from `pacman-conf --repo <name>`. pacman-conf prints list
+/// values one per line, so repeated keys accumulate.
+pub fn parse_repo_details(output: &str) -> RepoDetails {
+    let mut servers = Vec::new();
+    let mut siglevel = RepoSigLevel::Default;
+    let mut usage: Option<RepoUsage> = None;
+
+    for (key, value) in output.lines().filter_map(|l| key_value(l.trim())) {
+        match key {
+            "Server" if !value.is_empty() && !value.chars().any(char::is_whitespace) => {
+                servers.push(value.to_string());
+            }
+            "SigLevel" => {
+                let lower = value.to_lowercase();
+                if lower.contains("never") {
+                    siglevel = RepoSigLevel::Never;
+                } else if lower.contains("taroptional") || lower.contains("packageoptional") {
+                    siglevel = RepoSigLevel::PackageOptional;
+                } else if lower.contains("required") {
+                    siglevel = RepoSigLevel::Default;
+                }
+            }
+            "Usage" => {
+                let u = usage.get_or_insert(RepoUsage::NONE);
+                for word in value.split_whitespace() {
+                    match word {
+                        "Sync" => u.sync = true,
+                        "Search" => u.search = true,
+                        "Install" => u.install = true,
+                        "Upgrade" => u.upgrade = true,
+                        "All" => *u = RepoUsage::ALL,
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    RepoDetails {
+        servers,
+        siglevel,
+        usage: usage.unwrap_or(RepoUsage::ALL),
+    }
+}
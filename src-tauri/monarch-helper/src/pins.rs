@@ -0,0 +1,91 @@
+//! Package pins: IgnorePkg entries in a MonArch-managed pacman drop-in under
+//! /etc/pacman.d/monarch/. The helper loads the list into ALPM at startup, so its own runs
+//! (AlpmUpgrade / ExecuteBatch) always skip pinned packages. pacman.conf is never edited:
+//! `pacman -Syu` in a terminal honors pins only if the user adds
+//! `Include = /etc/pacman.d/monarch/*.conf` to it.
+
+use alpm::Alpm;
+
+use crate::logger;
+
+const DROPIN_DIR: &str = "/etc/pacman.d/monarch";
+pub const PINS_DROPIN: &str = "/etc/pacman.d/monarch/pins.conf";
+
+/// The drop-in names its own section, so it means the same wherever a user Includes it.
+pub fn render_dropin(packages: &[String]) -> String {
+    let mut out = String::from(
+        "# Managed by MonArch Store (pinned packages). Changes here are overwritten;\n\
+         # pin/unpin packages from the app instead.\n\
+         [options]\n",
+    );
+    if !packages.is_empty() {
+        out.push_str(&format!("IgnorePkg = {}\n", packages.join(" ")));
+    }
+    out
+}
+
+/// IgnorePkg values from a pacman config snippet.
+pub fn parse_ignorepkg(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.starts_with('#'))
+        .filter_map(|l| l.split_once('='))
+        .filter(|(k, _)| k.trim() == "IgnorePkg")
+        .flat_map(|(_, v)| v.split_whitespace().map(String::from))
+        .collect()
+}
+
+fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "@._+-".contains(c))
+}
+
+/// Replace the pinned set.
+pub fn set_pinned_packages(packages: &[String]) -> Result<(), String> {
+    if let Some(bad) = packages.iter().find(|p| !valid_name(p)) {
+        return Err(format!("Invalid package name for pin: {}", bad));
+    }
+    std::fs::create_dir_all(DROPIN_DIR)
+        .map_err(|e| format!("Failed to create {}: {}", DROPIN_DIR, e))?;
+    // Write-then-rename so pacman never reads a half-written drop-in
+    let tmp = format!("{}.monarch-tmp", PINS_DROPIN);
+    std::fs::write(&tmp, render_dropin(packages))
+        .map_err(|e| format!("Failed to write {}: {}", tmp, e))?;
+    std::fs::rename(&tmp, PINS_DROPIN)
+        .map_err(|e| format!("Failed to write {}: {}", PINS_DROPIN, e))?;
+    logger::info(&format!("Pinned packages set: {:?}", packages));
+    Ok(())
+}
+
+/// Register IgnorePkg from pacman.conf (via pacman-conf, which follows Includes) and from
+/// our drop-in directly, since pacman.conf may not Include it.
+pub fn load_ignored_packages(alpm: &mut Alpm) {
+    let mut ignored: Vec<String> = std::process::Command::new("pacman-conf")
+        .arg("IgnorePkg")
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| {
+            String::from_utf8_lossy(&o.stdout)
+                .split_whitespace()
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default();
+    if let Ok(content) = std::fs::read_to_string(PINS_DROPIN) {
+        ignored.extend(parse_ignorepkg(&content));
+    }
+    ignored.sort();
+    ignored.dedup();
+    for name in &ignored {
+        if let Err(e) = alpm.add_ignorepkg(name.as_str()) {
+            logger::warn(&format!("Failed to ignore {}: {}", name, e));
+        }
+    }
+    if !ignored.is_empty() {
+        logger::info(&format!("IgnorePkg: {}", ignored.join(" ")));
+    }
+}
@@ -1,34 +1,183 @@
-//! Single writer thread for all progress output to IPC (original stdout).
+//! Single writer thread for all progress output to IPC (original stdout, or the client
+//! connection in agent mode).
 //! Prevents ALPM download callback (or main) from blocking on stdout and stalling the download.
 
 use crossbeam_channel::{bounded, Sender};
-use std::fs::File;
 use std::io::Write;
-use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
 
 static SENDER: OnceLock<Sender<String>> = OnceLock::new();
+/// Where the writer thread puts lines; the agent swaps it per connection.
+static OUTPUT: Mutex<Option<Box<dyn Write + Send>>> = Mutex::new(None);
+/// Lines queued and lines written, so `drain` knows when the pipe has caught up.
+static QUEUED: AtomicU64 = AtomicU64::new(0);
+static WRITTEN: AtomicU64 = AtomicU64::new(0);
 
 /// Initialize the progress system with the IPC output stream (the original stdout).
 /// This must be called BEFORE any progress messages are sent.
-pub fn init(mut ipc_pipe: File) {
+pub fn init(ipc_pipe: impl Write + Send + 'static) {
+    set_output(ipc_pipe);
     let (tx, rx) = bounded::<String>(256);
     std::thread::spawn(move || {
         while let Ok(line) = rx.recv() {
-            let _ = writeln!(ipc_pipe, "{}", line);
-            // Unbuffered write to ensure GUI gets it immediately
-            let _ = ipc_pipe.flush();
+            let mut output = OUTPUT.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(out) = output.as_mut() {
+                let _ = writeln!(out, "{}", line);
+                // Unbuffered write to ensure GUI gets it immediately
+                let _ = out.flush();
+            }
+            WRITTEN.fetch_add(1, Ordering::SeqCst);
         }
     });
     let _ = SENDER.set(tx);
 }
 
+/// Send later lines to `out`. Call `drain` first so queued lines reach the old output.
+pub fn set_output(out: impl Write + Send + 'static) {
+    *OUTPUT.lock().unwrap_or_else(|e| e.into_inner()) = Some(Box::new(out));
+}
+
 /// Send a single JSON progress line to the GUI. Non-blocking; drops if channel is full.
 pub fn send_progress_line(line: String) {
     if let Some(tx) = SENDER.get() {
-        let _ = tx.try_send(line);
+        if tx.try_send(line).is_ok() {
+            QUEUED.fetch_add(1, Ordering::SeqCst);
+        }
     } else {
         // Fallback if not initialized (should not happen in prod, but maybe in tests)
         // Just print to stderr so it's visible in logs at least
         eprintln!("[Pre-Init Progress]: {}", line);
     }
 }
+
+/// Block until every queued line is written or `timeout` passes. Call before exiting, which
+/// would otherwise kill the writer thread with lines still in the channel.
+pub fn drain(timeout: std::time::Duration) {
+    let deadline = std::time::Instant::now() + timeout;
+    while WRITTEN.load(Ordering::SeqCst) < QUEUED.load(Ordering::SeqCst)
+        && std::time::Instant::now() < deadline
+    {
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+}
+
+/// Minimum interval between progress events for the same file, so a fast mirror
+/// does not flood the 256-slot channel (excess lines are dropped, not queued).
+const DL_EMIT_INTERVAL: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// One throttled download update ready to be serialized as an AlpmProgressEvent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DownloadUpdate {
+    pub event_type: &'static str,
+    pub package: String,
+    pub percent: Option<u8>,
+    pub downloaded: u64,
+    pub total: u64,
+    pub speed_bps: Option<u64>,
+    pub eta_seconds: Option<u64>,
+}
+
+struct FileState {
+    started: std::time::Instant,
+    last_emit: Option<std::time::Instant>,
+}
+
+/// Per-file state for ALPM's download callback. Lives as the callback's user data.
+#[derive(Default)]
+pub struct DownloadTracker {
+    files: std::collections::HashMap<String, FileState>,
+}
+
+impl DownloadTracker {
+    pub fn start(&mut self, filename: &str) -> Option<DownloadUpdate> {
+        self.files.insert(
+            filename.to_string(),
+            FileState {
+                started: std::time::Instant::now(),
+                last_emit: None,
+            },
+        );
+        Some(DownloadUpdate {
+            event_type: "download_start",
+            package: package_from_filename(filename),
+            percent: Some(0),
+            downloaded: 0,
+            total: 0,
+            speed_bps: None,
+            eta_seconds: None,
+        })
+    }
+
+    pub fn progress(
+        &mut self,
+        filename: &str,
+        downloaded: u64,
+        total: u64,
+    ) -> Option<DownloadUpdate> {
+        let now = std::time::Instant::now();
+        let state = self.files.entry(filename.to_string()).or_insert(FileState {
+            started: now,
+            last_emit: None,
+        });
+        if let Some(last) = state.last_emit {
+            if now.duration_since(last) < DL_EMIT_INTERVAL {
+                return None;
+            }
+        }
+        state.last_emit = Some(now);
+        let elapsed = now.duration_since(state.started).as_secs_f64();
+        let (speed_bps, eta_seconds) = speed_and_eta(downloaded, total, elapsed);
+        Some(DownloadUpdate {
+            event_type: "download_progress",
+            package: package_from_filename(filename),
+            percent: (total > 0).then(|| ((downloaded.min(total) * 100) / total) as u8),
+            downloaded,
+            total,
+            speed_bps,
+            eta_seconds,
+        })
+    }
+
+    pub fn complete(&mut self, filename: &str, total: u64) -> Option<DownloadUpdate> {
+        self.files.remove(filename);
+        Some(DownloadUpdate {
+            event_type: "download_complete",
+            package: package_from_filename(filename),
+            percent: Some(100),
+            downloaded: total,
+            total,
+            speed_bps: None,
+            eta_seconds: Some(0),
+        })
+    }
+}
+
+/// Average speed since start and remaining time. None until there is enough data.
+pub fn speed_and_eta(downloaded: u64, total: u64, elapsed_secs: f64) -> (Option<u64>, Option<u64>) {
+    if elapsed_secs <= 0.0 || downloaded == 0 {
+        return (None, None);
+    }
+    let speed = downloaded as f64 / elapsed_secs;
+    let eta = if total > downloaded && speed > 0.0 {
+        Some(((total - downloaded) as f64 / speed).ceil() as u64)
+    } else {
+        None
+    };
+    (Some(speed as u64), eta)
+}
+
+/// "firefox-130.0-1-x86_64.pkg.tar.zst" -> "firefox"; "core.db" -> "core.db".
+pub fn package_from_filename(filename: &str) -> String {
+    let Some(idx) = filename.find(".pkg.tar") else {
+        return filename.to_string();
+    };
+    let stem = &filename[..idx];
+    // Strip the trailing pkgver-pkgrel-arch segments.
+    let parts: Vec<&str> = stem.rsplitn(4, '-').collect();
+    if parts.len() == 4 {
+        parts[3].to_string()
+    } else {
+        stem.to_string()
+    }
+}
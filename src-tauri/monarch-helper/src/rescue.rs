@@ -0,0 +1,263 @@
+//! Rescue toolkit (Rescue): repairs for a system whose last update broke boot-adjacent
+//! components. Each action is its own helper run; the GUI describes the risk and asks for
+//! confirmation first. The local DB and pacman.conf are only ever restored from copies
+//! MonArch made itself, and whatever they replace is kept next to them as `*.monarch-broken`.
+
+use alpm::Alpm;
+use monarch_types::RescueAction;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::logger;
+use crate::transactions;
+
+pub const LOCAL_DB: &str = "/var/lib/pacman/local";
+pub const LOCAL_DB_BACKUP: &str = "/var/lib/pacman/local.monarch-bak";
+/// Generations of the local DB backup: the newest at LOCAL_DB_BACKUP, older ones at
+/// LOCAL_DB_BACKUP.1, .2, ...
+const LOCAL_DB_BACKUPS_KEPT: usize = 3;
+const DB_LOCK: &str = "/var/lib/pacman/db.lck";
+const PACMAN_CONF: &str = "/etc/pacman.conf";
+pub const PACMAN_CONF_BACKUP: &str = "/etc/pacman.conf.monarch-bak";
+/// Timestamped copies written by older releases before changing ParallelDownloads.
+const LEGACY_CONF_BACKUP_PREFIX: &str = "pacman.conf.bak.parallel.";
+
+/// Reinstalled along with the kernels when installed. Only packages whose files the boot
+/// path reads; systemd-boot lives in systemd and is left alone.
+const BOOT_PACKAGES: &[&str] = &[
+    "linux-firmware",
+    "intel-ucode",
+    "amd-ucode",
+    "mkinitcpio",
+    "dracut",
+    "grub",
+    "limine",
+    "refind",
+    "syslinux",
+    "efibootmgr",
+];
+
+/// Set while a rescue action runs: its transactions must not push the pre-breakage backup
+/// out of the rotation.
+static RESCUING: AtomicBool = AtomicBool::new(false);
+
+pub fn run(action: RescueAction, alpm: &mut Alpm) -> Result<String, String> {
+    RESCUING.store(true, Ordering::SeqCst);
+    let result = match action {
+        RescueAction::RegenerateInitramfs => regenerate_initramfs(),
+        RescueAction::ReinstallBootPackages => reinstall_boot_packages(alpm),
+        RescueAction::RestoreLocalDb => restore_local_db(),
+        RescueAction::RestorePacmanConf => restore_pacman_conf(),
+    };
+    RESCUING.store(false, Ordering::SeqCst);
+    result
+}
+
+/// Run a repair tool, forwarding its output to the GUI log line by line.
+fn run_tool(program: &str, args: &[&str]) -> Result<(), String> {
+    crate::emit_progress(10, &format!("Running {} {}", program, args.join(" ")));
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    for line in stdout.lines().chain(stderr.lines()) {
+        if !line.trim().is_empty() {
+            crate::emit_progress(50, line);
+        }
+    }
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!("{} exited with {}", program, output.status))
+    }
+}
+
+fn regenerate_initramfs() -> Result<String, String> {
+    if std::path::Path::new("/usr/bin/mkinitcpio").exists() {
+        run_tool("mkinitcpio", &["-P"])?;
+    } else if std::path::Path::new("/usr/bin/dracut").exists() {
+        run_tool("dracut", &["--regenerate-all", "--force"])?;
+    } else {
+        return Err("Neither mkinitcpio nor dracut is installed".to_string());
+    }
+    Ok("Initramfs images regenerated".to_string())
+}
+
+/// A kernel package ships its image as usr/lib/modules/<version>/vmlinuz; matching on that
+/// instead of names also catches third-party kernels (linux-cachyos, linux-xanmod, ...).
+pub fn is_kernel_image(path: &str) -> bool {
+    path.strip_prefix("usr/lib/modules/")
+        .and_then(|rest| rest.split_once('/'))
+        .is_some_and(|(version, file)| !version.is_empty() && file == "vmlinuz")
+}
+
+fn reinstall_boot_packages(alpm: &mut Alpm) -> Result<String, String> {
+    let names: Vec<String> = alpm
+        .localdb()
+        .pkgs()
+        .iter()
+        .filter(|pkg| {
+            BOOT_PACKAGES.contains(&pkg.name())
+                || pkg
+                    .files()
+                    .files()
+                    .iter()
+                    .any(|f| is_kernel_image(f.name()))
+        })
+        .map(|pkg| pkg.name().to_string())
+        .collect();
+    if names.is_empty() {
+        return Err("No kernel or bootloader packages are installed".to_string());
+    }
+    let reinstalled = transactions::execute_alpm_reinstall(&names, alpm)?;
+
+    // The grub package only ships the tools; regenerate the menu for the reinstalled kernels
+    if reinstalled.iter().any(|n| n == "grub")
+        && std::path::Path::new("/boot/grub/grub.cfg").exists()
+    {
+        run_tool("grub-mkconfig", &["-o", "/boot/grub/grub.cfg"])?;
+    }
+    Ok(format!("Reinstalled {}", reinstalled.join(", ")))
+}
+
+fn ensure_unlocked() -> Result<(), String> {
+    if std::path::Path::new(DB_LOCK).exists() {
+        return Err(format!(
+            "{} exists; unlock the package database first",
+            DB_LOCK
+        ));
+    }
+    Ok(())
+}
+
+fn copy_tree(from: &str, to: &str) -> Result<(), String> {
+    let status = Command::new("cp")
+        .args(["-a", "--", from, to])
+        .status()
+        .map_err(|e| format!("Failed to run cp: {}", e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("Copying {} to {} failed", from, to))
+    }
+}
+
+fn backup_generation(n: usize) -> String {
+    if n == 0 {
+        LOCAL_DB_BACKUP.to_string()
+    } else {
+        format!("{}.{}", LOCAL_DB_BACKUP, n)
+    }
+}
+
+/// Keep a copy of the local DB as it was before a transaction commits; called from
+/// `transactions::commit_recorded`. Earlier copies move down one generation, the oldest is
+/// dropped. The new copy is complete before anything rotates, so a failed copy leaves the
+/// previous ones as they were. Skipped during a rescue.
+pub fn backup_local_db() -> Result<(), String> {
+    if RESCUING.load(Ordering::SeqCst) {
+        logger::info("Rescue in progress; keeping the existing local database backups");
+        return Ok(());
+    }
+    let tmp = format!("{}.monarch-tmp", LOCAL_DB_BACKUP);
+    let _ = std::fs::remove_dir_all(&tmp);
+    copy_tree(LOCAL_DB, &tmp)?;
+    let _ = std::fs::remove_dir_all(backup_generation(LOCAL_DB_BACKUPS_KEPT - 1));
+    for n in (0..LOCAL_DB_BACKUPS_KEPT - 1).rev() {
+        let from = backup_generation(n);
+        if std::path::Path::new(&from).exists() {
+            std::fs::rename(&from, backup_generation(n + 1))
+                .map_err(|e| format!("Failed to rotate {}: {}", from, e))?;
+        }
+    }
+    std::fs::rename(&tmp, LOCAL_DB_BACKUP)
+        .map_err(|e| format!("Failed to store {}: {}", LOCAL_DB_BACKUP, e))
+}
+
+fn restore_local_db() -> Result<String, String> {
+    ensure_unlocked()?;
+    if !std::path::Path::new(LOCAL_DB_BACKUP)
+        .join("ALPM_DB_VERSION")
+        .exists()
+    {
+        return Err(format!(
+            "No usable local database backup at {}",
+            LOCAL_DB_BACKUP
+        ));
+    }
+    let tmp = format!("{}.monarch-tmp", LOCAL_DB);
+    let broken = format!("{}.monarch-broken", LOCAL_DB);
+    let _ = std::fs::remove_dir_all(&tmp);
+    copy_tree(LOCAL_DB_BACKUP, &tmp)?;
+    let _ = std::fs::remove_dir_all(&broken);
+    if std::path::Path::new(LOCAL_DB).exists() {
+        std::fs::rename(LOCAL_DB, &broken)
+            .map_err(|e| format!("Failed to move {} aside: {}", LOCAL_DB, e))?;
+    }
+    std::fs::rename(&tmp, LOCAL_DB)
+        .map_err(|e| format!("Failed to restore {}: {}", LOCAL_DB, e))?;
+    logger::info(&format!(
+        "Restored {} from {} (previous copy in {})",
+        LOCAL_DB, LOCAL_DB_BACKUP, broken
+    ));
+    Ok(format!(
+        "Package database restored; the replaced one is in {}",
+        broken
+    ))
+}
+
+/// The backup to restore: MonArch's own copy, else the newest legacy timestamped one.
+pub fn pick_conf_backup(etc_entries: &[String]) -> Option<String> {
+    let own = PACMAN_CONF_BACKUP.rsplit('/').next().unwrap_or_default();
+    if etc_entries.iter().any(|e| e == own) {
+        return Some(PACMAN_CONF_BACKUP.to_string());
+    }
+    etc_entries
+        .iter()
+        .filter_map(|e| {
+            let stamp: u64 = e.strip_prefix(LEGACY_CONF_BACKUP_PREFIX)?.parse().ok()?;
+            Some((stamp, e))
+        })
+        .max_by_key(|(stamp, _)| *stamp)
+        .map(|(_, e)| format!("/etc/{}", e))
+}
+
+fn restore_pacman_conf() -> Result<String, String> {
+    ensure_unlocked()?;
+    let entries: Vec<String> = std::fs::read_dir("/etc")
+        .map_err(|e| format!("Failed to read /etc: {}", e))?
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.file_name().into_string().ok())
+        .collect();
+    let backup = pick_conf_backup(&entries)
+        .ok_or_else(|| "MonArch has no pacman.conf backup on this system".to_string())?;
+
+    // Only put back a file pacman itself can parse
+    let check = Command::new("pacman-conf")
+        .args(["--config", &backup, "--repo-list"])
+        .output()
+        .map_err(|e| format!("Failed to run pacman-conf: {}", e))?;
+    if !check.status.success()
+        || crate::pacman_conf::parse_repo_list(&String::from_utf8_lossy(&check.stdout)).is_empty()
+    {
+        return Err(format!("{} is not a valid pacman.conf", backup));
+    }
+
+    let broken = format!("{}.monarch-broken", PACMAN_CONF);
+    if std::path::Path::new(PACMAN_CONF).exists() {
+        std::fs::copy(PACMAN_CONF, &broken)
+            .map_err(|e| format!("Failed to back up {}: {}", PACMAN_CONF, e))?;
+    }
+    // Write-then-rename so pacman never reads a half-written config
+    let tmp = format!("{}.monarch-tmp", PACMAN_CONF);
+    std::fs::copy(&backup, &tmp).map_err(|e| format!("Failed to write {}: {}", tmp, e))?;
+    std::fs::rename(&tmp, PACMAN_CONF)
+        .map_err(|e| format!("Failed to update {}: {}", PACMAN_CONF, e))?;
+    logger::info(&format!("Restored {} from {}", PACMAN_CONF, backup));
+    Ok(format!(
+        "pacman.conf restored from {}; the replaced one is in {}",
+        backup, broken
+    ))
+}
@@ -0,0 +1,372 @@
+//! `monarch-helper --rpc`: JSON-RPC over the socket the GUI hands us as stdin and stdout
+//! (see `monarch_types::rpc`). A reader thread takes requests off the socket and answers
+//! `cancel` and `capabilities` right away; commands queue up and run one at a time on the
+//! main thread in the order they arrived, each with its events sent as notifications
+//! carrying its request id. The GUI keeps one session open across operations, so a batch of
+//! installs and removals asks for authentication once and never races itself for the lock.
+
+use crate::{agent, logger, progress};
+use monarch_types::rpc::{
+    error_code, RpcCall, RpcMessage, RpcNotification, RpcRequest, RpcResponse, RpcResult,
+    READY_EVENT,
+};
+use monarch_types::{exit_code, AlpmProgressEvent, HelperCommand};
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::io::{AsRawFd, FromRawFd};
+use std::os::unix::net::UnixStream;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long a queued command waits for another package manager to release the DB lock
+/// before it runs anyway (and fails with the usual lock error).
+const LOCK_WAIT: Duration = Duration::from_secs(300);
+const LOCK_POLL: Duration = Duration::from_secs(1);
+
+fn event(event_type: &str, message: &str) -> AlpmProgressEvent {
+    AlpmProgressEvent {
+        event_type: event_type.to_string(),
+        package: None,
+        percent: None,
+        downloaded: None,
+        total: None,
+        speed_bps: None,
+        eta_seconds: None,
+        message: message.to_string(),
+    }
+}
+
+/// Our end of the socketpair. Responses come from both threads, so whole lines are written
+/// under the lock.
+#[derive(Clone)]
+struct Channel(Arc<Mutex<UnixStream>>);
+
+impl Channel {
+    fn send(&self, message: &RpcMessage) {
+        let Ok(line) = serde_json::to_string(message) else {
+            return;
+        };
+        let mut stream = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let _ = writeln!(stream, "{}", line);
+        let _ = stream.flush();
+    }
+
+    fn respond(&self, response: RpcResponse) {
+        self.send(&RpcMessage::Response(response));
+    }
+}
+
+/// Progress output while request `id` runs: each event line becomes an `event` notification.
+struct EventFramer {
+    id: u64,
+    channel: Channel,
+    pending: Vec<u8>,
+}
+
+impl Write for EventFramer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        while let Some(end) = self.pending.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            match serde_json::from_str::<AlpmProgressEvent>(line.trim()) {
+                Ok(event) => self
+                    .channel
+                    .send(&RpcMessage::Notification(RpcNotification::event(
+                        self.id, event,
+                    ))),
+                Err(_) => logger::warn(&format!("Dropped a non-event progress line: {}", line)),
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Which requests are queued or running.
+#[derive(Default)]
+struct Session {
+    running: Option<u64>,
+    queued: Vec<u64>,
+    cancelled: HashSet<u64>,
+}
+
+type SharedSession = Arc<Mutex<Session>>;
+
+fn lock(session: &SharedSession) -> std::sync::MutexGuard<'_, Session> {
+    session.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Entry point of `--rpc`. Returns the exit code once the GUI closed the socket or sent
+/// `shutdown` and every queued command ran.
+pub fn serve() -> i32 {
+    if unsafe { libc::geteuid() } != 0 {
+        logger::error("monarch-helper --rpc must run as root");
+        return exit_code::NOT_ROOT;
+    }
+    // Keep a private copy of the socket and give fd 0 to /dev/null, so scriptlets and hooks
+    // cannot read requests off it; redirect_streams moves fds 1 and 2 to the log.
+    let fd = unsafe { libc::dup(0) };
+    if fd < 0 {
+        logger::error("--rpc: stdin is not usable");
+        return exit_code::FAILED;
+    }
+    let socket = unsafe { UnixStream::from_raw_fd(fd) };
+    if let Ok(null) = std::fs::File::open("/dev/null") {
+        unsafe { libc::dup2(null.as_raw_fd(), 0) };
+    }
+    match crate::redirect_streams() {
+        Ok(ipc_pipe) => drop(ipc_pipe),
+        Err(e) => {
+            logger::error(&format!("--rpc: {}", e));
+            return exit_code::FAILED;
+        }
+    }
+    let reader = match socket.try_clone() {
+        Ok(reader) => reader,
+        Err(e) => {
+            logger::error(&format!("--rpc: {}", e));
+            return exit_code::FAILED;
+        }
+    };
+    progress::init(std::io::sink());
+    match crate::calling_uid() {
+        Some(uid) => logger::info(&format!("monarch-helper serving RPC (invoker UID={})", uid)),
+        None => logger::info("monarch-helper serving RPC"),
+    }
+
+    let _pid_guard = crate::PidFileGuard;
+    let _ = std::fs::write(crate::HELPER_PID_FILE, std::process::id().to_string());
+    let channel = Channel(Arc::new(Mutex::new(socket)));
+    let session = SharedSession::default();
+    spawn_cancel_watcher(channel.clone(), session.clone());
+
+    channel.send(&RpcMessage::Notification(RpcNotification::event(
+        0,
+        event(READY_EVENT, &std::process::id().to_string()),
+    )));
+
+    let (tx, rx) = crossbeam_channel::unbounded::<(u64, HelperCommand)>();
+    {
+        let channel = channel.clone();
+        let session = session.clone();
+        std::thread::spawn(move || read_requests(reader, &channel, &session, tx));
+    }
+
+    for (id, cmd) in rx {
+        {
+            let mut s = lock(&session);
+            s.queued.retain(|q| *q != id);
+            if s.cancelled.remove(&id) {
+                drop(s);
+                channel.respond(RpcResponse::err(
+                    id,
+                    error_code::CANCELLED,
+                    "Cancelled before it started",
+                ));
+                continue;
+            }
+            s.running = Some(id);
+        }
+        logger::info(&format!("RPC request {}: {}", id, cmd.name()));
+        progress::set_output(EventFramer {
+            id,
+            channel: channel.clone(),
+            pending: Vec::new(),
+        });
+        wait_for_db_lock();
+        let result = agent::execute(cmd);
+        progress::set_output(std::io::sink());
+        lock(&session).running = None;
+        channel.respond(RpcResponse::ok(id, RpcResult::Transaction(result)));
+    }
+    logger::info("monarch-helper RPC session ended");
+    exit_code::SUCCESS
+}
+
+/// Answer requests until `shutdown` or EOF; commands go to the main thread through `tx`.
+fn read_requests(
+    reader: UnixStream,
+    channel: &Channel,
+    session: &SharedSession,
+    tx: crossbeam_channel::Sender<(u64, HelperCommand)>,
+) {
+    for line in BufReader::new(reader).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let request: RpcRequest = match serde_json::from_str(line.trim()) {
+            Ok(request) => request,
+            Err(e) => {
+                let code = if serde_json::from_str::<serde_json::Value>(line.trim()).is_ok() {
+                    error_code::INVALID_REQUEST
+                } else {
+                    error_code::PARSE_ERROR
+                };
+                logger::error(&format!("Invalid RPC request: {}", e));
+                channel.respond(RpcResponse::err(0, code, &e.to_string()));
+                continue;
+            }
+        };
+        let id = request.id;
+        match request.call {
+            RpcCall::Run(cmd) => {
+                let ahead = {
+                    let mut s = lock(session);
+                    let ahead = s.queued.len() + usize::from(s.running.is_some());
+                    s.queued.push(id);
+                    ahead
+                };
+                if ahead > 0 {
+                    let message = format!(
+                        "Queued behind {} other operation{}",
+                        ahead,
+                        if ahead == 1 { "" } else { "s" }
+                    );
+                    channel.send(&RpcMessage::Notification(RpcNotification::event(
+                        id,
+                        event("queued", &message),
+                    )));
+                }
+                let _ = tx.send((id, cmd));
+            }
+            RpcCall::Cancel { id: target } => {
+                let mut s = lock(session);
+                if s.running == Some(target) {
+                    drop(s);
+                    channel.respond(RpcResponse::ok(id, RpcResult::Acknowledged(true)));
+                    cancel_running(channel, session);
+                } else if s.queued.contains(&target) {
+                    s.cancelled.insert(target);
+                    drop(s);
+                    channel.respond(RpcResponse::ok(id, RpcResult::Acknowledged(true)));
+                } else {
+                    drop(s);
+                    channel.respond(RpcResponse::ok(id, RpcResult::Acknowledged(false)));
+                }
+            }
+            RpcCall::Capabilities => channel.respond(RpcResponse::ok(
+                id,
+                RpcResult::Capabilities(crate::capabilities()),
+            )),
+            RpcCall::Shutdown => {
+                channel.respond(RpcResponse::ok(id, RpcResult::Acknowledged(true)));
+                break;
+            }
+        }
+    }
+    // Dropping `tx` lets the main thread finish the queue and exit
+}
+
+/// Commands of one session never overlap, but pacman or another front end may take the lock
+/// between them. Wait for it (reporting that we do) rather than fail the next command.
+fn wait_for_db_lock() {
+    let lock_path = std::path::Path::new(crate::self_healer::DB_LOCK_PATH);
+    let deadline = Instant::now() + LOCK_WAIT;
+    let mut announced = false;
+    // libalpm leaves the lock file empty, which is_db_lock_stale counts as stale; a running
+    // pacman is the better sign of a live holder
+    while lock_path.exists()
+        && (crate::self_healer::is_pacman_running() || !crate::self_healer::is_db_lock_stale())
+    {
+        if Instant::now() >= deadline {
+            logger::warn("Gave up waiting for the package database lock");
+            return;
+        }
+        if !announced {
+            let line = serde_json::to_string(&event(
+                "waiting_for_lock",
+                "Waiting for another package manager to finish…",
+            ))
+            .unwrap_or_default();
+            progress::send_progress_line(line);
+            announced = true;
+        }
+        std::thread::sleep(LOCK_POLL);
+    }
+}
+
+/// There is no safe way to stop ALPM mid-transaction, so this does what the cancel file
+/// always did: report the running command as cancelled and exit. Queued commands never ran.
+fn cancel_running(channel: &Channel, session: &SharedSession) -> ! {
+    logger::info("Cancel requested by user; exiting.");
+    let _ = std::fs::remove_file(crate::HELPER_PID_FILE);
+    let (running, queued) = {
+        let s = lock(session);
+        (s.running, s.queued.clone())
+    };
+    // Sends the cancelled transaction_result as an event of the running request
+    let result = crate::outcome::cancel();
+    if let Some(id) = running {
+        channel.respond(RpcResponse::ok(id, RpcResult::Transaction(result)));
+    }
+    for id in queued {
+        channel.respond(RpcResponse::err(
+            id,
+            error_code::ABORTED,
+            "The helper session was cancelled before this command ran",
+        ));
+    }
+    std::process::exit(exit_code::CANCELLED);
+}
+
+/// The GUI's cancel button still writes the cancel file; treat it like a `cancel` of the
+/// running request.
+fn spawn_cancel_watcher(channel: Channel, session: SharedSession) {
+    std::thread::spawn(move || {
+        let cancel_path = std::path::Path::new(crate::CANCEL_FILE);
+        loop {
+            std::thread::sleep(std::time::Duration::from_millis(500));
+            if cancel_path.exists() {
+                let _ = std::fs::remove_file(cancel_path);
+                if lock(&session).running.is_some() {
+                    cancel_running(&channel, &session);
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_framer() {
+        let (ours, theirs) = UnixStream::pair().unwrap();
+        let mut framer = EventFramer {
+            id: 3,
+            channel: Channel(Arc::new(Mutex::new(ours))),
+            pending: Vec::new(),
+        };
+        // progress writes the line and its newline separately
+        write!(
+            framer,
+            "{}",
+            r#"{"event_type":"progress","package":null,"percent":5,"downloaded":null,"total":null,"message":"Syncing"}"#
+        )
+        .unwrap();
+        writeln!(framer).unwrap();
+        writeln!(framer, "not json").unwrap();
+        drop(framer);
+
+        let lines: Vec<String> = BufReader::new(theirs)
+            .lines()
+            .map_while(Result::ok)
+            .collect();
+        assert_eq!(lines.len(), 1);
+        match serde_json::from_str::<RpcMessage>(&lines[0]).unwrap() {
+            RpcMessage::Notification(n) => {
+                assert_eq!(n.params.id, 3);
+                assert_eq!(n.params.event.message, "Syncing");
+            }
+            other => panic!("Expected an event notification, got {:?}", other),
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use crate::logger;
+use crate::transactions::{self, TransactionPhase};
 use alpm::{Alpm, TransFlag};
 use std::path::Path;
 
@@ -7,6 +8,8 @@ use std::path::Path;
 pub struct SafeUpdateTransaction<'a> {
     alpm: &'a mut Alpm,
     target_packages: Vec<String>,
+    phase: TransactionPhase,
+    dry_run: bool,
 }
 
 impl<'a> SafeUpdateTransaction<'a> {
@@ -14,6 +17,8 @@ impl<'a> SafeUpdateTransaction<'a> {
         Self {
             alpm,
             target_packages: Vec::new(),
+            phase: TransactionPhase::Full,
+            dry_run: false,
         }
     }
 
@@ -22,6 +27,18 @@ impl<'a> SafeUpdateTransaction<'a> {
         self
     }
 
+    /// Download only, or commit an earlier download after re-checking versions.
+    pub fn with_phase(mut self, phase: TransactionPhase) -> Self {
+        self.phase = phase;
+        self
+    }
+
+    /// Stop after prepare and report the change set instead of committing.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
     /// Execute the transaction with strict -Syu enforcement.
     pub fn execute(&mut self) -> Result<(), String> {
         logger::info("SafeUpdateTransaction: Initializing Iron Core protocol...");
@@ -42,7 +59,7 @@ impl<'a> SafeUpdateTransaction<'a> {
         logger::info("Ensuring database consistency...");
 
         // 3. Initialize Transaction
-        alpm.trans_init(TransFlag::ALL_DEPS)
+        alpm.trans_init(TransFlag::ALL_DEPS | transactions::phase_flags(&self.phase))
             .map_err(|e| e.to_string())?;
 
         // 4. Resolve Targets (Find first, then add)
@@ -104,10 +121,23 @@ impl<'a> SafeUpdateTransaction<'a> {
         alpm.trans_prepare()
             .map_err(|e| format!("Transaction Prepare failed: {}", e))?;
 
+        if self.dry_run {
+            logger::info("Dry run: releasing prepared transaction without committing.");
+            return transactions::finish_dry_run(alpm);
+        }
+        crate::disk_space::preflight(alpm, self.phase == TransactionPhase::DownloadOnly)?;
+        let downloaded = transactions::check_phase(alpm, &self.phase)?;
+
         logger::info("Committing transaction...");
-        alpm.trans_commit()
+        transactions::commit_recorded(alpm, downloaded.is_some())
             .map_err(|e| format!("Transaction Commit failed: {}", e))?;
 
+        if let Some(changes) = downloaded {
+            logger::info("Download-only phase finished; commit deferred.");
+            transactions::emit_downloads_ready(&changes);
+            return Ok(());
+        }
+
         logger::info("Atomic Update Protocol completed successfully.");
         Ok(())
     }
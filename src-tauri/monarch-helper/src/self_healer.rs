@@ -11,7 +11,7 @@ pub const DB_LOCK_PATH: &str = "/var/lib/pacman/db.lck";
 const STALE_LOCK_AGE_SECS: u64 = 600; // 10 minutes
 
 /// Returns true if a pacman process is currently running.
-fn is_pacman_running() -> bool {
+pub fn is_pacman_running() -> bool {
     std::process::Command::new("pgrep")
         .args(["-x", "pacman"])
         .output()
@@ -19,6 +19,34 @@ fn is_pacman_running() -> bool {
         .unwrap_or(false)
 }
 
+/// The process holding pacman's lock, with its name. libalpm keeps db.lck open for as long
+/// as it holds the lock, so this finds pacman, pamac, PackageKit and other helper or agent
+/// processes alike, where a `pgrep pacman` would miss all but the first.
+pub fn db_lock_holder() -> Option<(u32, String)> {
+    let own = std::process::id();
+    std::fs::read_dir("/proc")
+        .ok()?
+        .flatten()
+        .filter_map(|e| e.file_name().to_str()?.parse::<u32>().ok())
+        .filter(|pid| *pid != own)
+        .find(|pid| {
+            std::fs::read_dir(format!("/proc/{}/fd", pid))
+                .map(|fds| {
+                    fds.flatten().any(|fd| {
+                        std::fs::read_link(fd.path())
+                            .is_ok_and(|target| target == std::path::Path::new(DB_LOCK_PATH))
+                    })
+                })
+                .unwrap_or(false)
+        })
+        .map(|pid| {
+            let name = std::fs::read_to_string(format!("/proc/{}/comm", pid))
+                .map(|c| c.trim().to_string())
+                .unwrap_or_default();
+            (pid, name)
+        })
+}
+
 /// Returns true if the lock file exists and is safe to remove:
 /// - PID in file is dead, OR
 /// - Lock file is older than 10 minutes AND no pacman process is running.
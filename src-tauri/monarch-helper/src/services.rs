@@ -0,0 +1,106 @@
+//! systemd units of installed packages (ManageService). Only units a package ships in
+//! /usr/lib/systemd/system can be enabled or started, so the GUI can't be used to turn on
+//! arbitrary units; instances of a template (`getty@tty2.service`) go by the template.
+
+use alpm::Alpm;
+use monarch_types::ServiceAction;
+use std::process::Command;
+
+/// As stored in the local DB: no leading slash.
+const SYSTEM_UNIT_DIR: &str = "usr/lib/systemd/system/";
+const UNIT_SUFFIXES: &[&str] = &[".service", ".socket", ".timer", ".path"];
+const MAX_UNIT_LEN: usize = 256;
+
+pub fn is_valid_unit_name(unit: &str) -> bool {
+    unit.len() <= MAX_UNIT_LEN
+        && !unit.starts_with(['-', '.', '@'])
+        && UNIT_SUFFIXES
+            .iter()
+            .any(|s| unit.len() > s.len() && unit.ends_with(s))
+        && unit
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '@' | '.' | '_' | '-' | ':'))
+}
+
+/// The unit file that provides `unit`: itself, or its template for an instance.
+pub fn unit_file_for(unit: &str) -> String {
+    match unit.split_once('@') {
+        Some((prefix, rest)) if !rest.starts_with('.') => {
+            let suffix = rest.rfind('.').map_or("", |i| &rest[i..]);
+            format!("{}@{}", prefix, suffix)
+        }
+        _ => unit.to_string(),
+    }
+}
+
+/// Name of the installed package that ships `unit`.
+fn owning_package(alpm: &Alpm, unit: &str) -> Option<String> {
+    let path = format!("{}{}", SYSTEM_UNIT_DIR, unit_file_for(unit));
+    alpm.localdb()
+        .pkgs()
+        .iter()
+        .find(|p| p.files().files().iter().any(|f| f.name() == path))
+        .map(|p| p.name().to_string())
+}
+
+pub fn run(alpm: &Alpm, unit: &str, action: ServiceAction) -> Result<String, String> {
+    if !is_valid_unit_name(unit) {
+        return Err(format!("Invalid unit name: {}", unit));
+    }
+    let Some(pkg) = owning_package(alpm, unit) else {
+        return Err(format!("{} is not shipped by an installed package", unit));
+    };
+    let (args, done): (&[&str], &str) = match action {
+        ServiceAction::Enable => (&["enable"], "enabled"),
+        ServiceAction::EnableNow => (&["enable", "--now"], "enabled and started"),
+        ServiceAction::Start => (&["start"], "started"),
+    };
+    crate::emit_progress(
+        20,
+        &format!("systemctl {} {} (from {})", args.join(" "), unit, pkg),
+    );
+    let output = Command::new("systemctl")
+        .args(args)
+        .arg("--")
+        .arg(unit)
+        .output()
+        .map_err(|e| format!("Failed to run systemctl: {}", e))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    for line in stdout.lines().chain(stderr.lines()) {
+        if !line.trim().is_empty() {
+            crate::emit_progress(60, line);
+        }
+    }
+    if output.status.success() {
+        Ok(format!("{} {}", unit, done))
+    } else {
+        let detail = stderr.lines().rev().find(|l| !l.trim().is_empty());
+        Err(match detail {
+            Some(line) => format!("systemctl could not manage {}: {}", unit, line.trim()),
+            None => format!("systemctl exited with {}", output.status),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unit_names() {
+        assert!(is_valid_unit_name("docker.socket"));
+        assert!(is_valid_unit_name("cups.service"));
+        assert!(is_valid_unit_name("getty@tty2.service"));
+        assert!(is_valid_unit_name("paccache.timer"));
+        assert!(!is_valid_unit_name(".service"));
+        assert!(!is_valid_unit_name("--now.service"));
+        assert!(!is_valid_unit_name("../../etc/foo.service"));
+        assert!(!is_valid_unit_name("multi-user.target"));
+        assert!(!is_valid_unit_name("sshd"));
+
+        assert_eq!(unit_file_for("getty@tty2.service"), "getty@.service");
+        assert_eq!(unit_file_for("getty@.service"), "getty@.service");
+        assert_eq!(unit_file_for("sshd.service"), "sshd.service");
+    }
+}
@@ -1,44 +1,16 @@
 use crate::alpm_errors::classify_alpm_error;
 use crate::logger;
+use crate::outcome;
 use crate::progress;
 use alpm::{Alpm, SigLevel, TransFlag};
-use serde::{Deserialize, Serialize};
 
-/// Minimum free space (200 MB) below which we warn the user before prepare.
-const LOW_DISK_SPACE_THRESHOLD_B: u64 = 200 * 1024 * 1024;
+pub use monarch_types::{
+    AlpmProgressEvent, DryRunAction, DryRunChange, ResolvedPackage, TransactionManifest,
+    TransactionPhase,
+};
 
-#[cfg(unix)]
-fn free_space_bytes(path: &std::path::Path) -> Option<u64> {
-    use std::ffi::CString;
-    use std::os::unix::ffi::OsStrExt;
-    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
-    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
-    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
-        return None;
-    }
-    Some(stat.f_bavail as u64 * stat.f_frsize as u64)
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct AlpmProgressEvent {
-    pub event_type: String,
-    pub package: Option<String>,
-    pub percent: Option<u8>,
-    pub downloaded: Option<u64>,
-    pub total: Option<u64>,
-    pub message: String,
-}
-
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
-pub struct TransactionManifest {
-    pub update_system: bool,          // Should we run -Syu?
-    pub refresh_db: bool,             // Should we run -Sy?
-    pub clear_cache: bool,            // Should we run -Sc?
-    pub remove_lock: bool,            // Should we remove pacman lock?
-    pub install_targets: Vec<String>, // List of repo packages
-    pub remove_targets: Vec<String>,  // List of packages to remove
-    pub local_paths: Vec<String>,     // List of pre-built AUR packages (.pkg.tar.zst) to install
-}
+/// pacman's database directory.
+pub const DB_PATH: &str = "/var/lib/pacman";
 
 fn emit_progress_event(event: AlpmProgressEvent) {
     if let Ok(json) = serde_json::to_string(&event) {
@@ -53,29 +25,206 @@ fn emit_simple_progress(percent: u8, message: &str) {
         percent: Some(percent),
         downloaded: None,
         total: None,
+        speed_bps: None,
+        eta_seconds: None,
         message: message.to_string(),
     });
 }
 
-const CACHE_PKG_DIR: &str = "/var/cache/pacman/pkg";
+/// What the prepared transaction in `alpm` would change.
+fn collect_changes(alpm: &Alpm) -> Vec<DryRunChange> {
+    let mut changes = Vec::new();
+    for pkg in alpm.trans_add() {
+        let old = alpm.localdb().pkg(pkg.name()).ok();
+        let action = match old {
+            None => DryRunAction::Install,
+            Some(o) => match pkg.version().partial_cmp(o.version()) {
+                Some(std::cmp::Ordering::Greater) => DryRunAction::Upgrade,
+                Some(std::cmp::Ordering::Less) => DryRunAction::Downgrade,
+                _ => DryRunAction::Reinstall,
+            },
+        };
+        changes.push(DryRunChange {
+            action,
+            name: pkg.name().to_string(),
+            old_version: old.map(|o| o.version().to_string()),
+            new_version: Some(pkg.version().to_string()),
+            download_size: pkg.download_size().max(0) as u64,
+            installed_size: pkg.isize().max(0) as u64,
+        });
+    }
+    for pkg in alpm.trans_remove() {
+        changes.push(DryRunChange {
+            action: DryRunAction::Remove,
+            name: pkg.name().to_string(),
+            old_version: Some(pkg.version().to_string()),
+            new_version: None,
+            download_size: 0,
+            installed_size: pkg.isize().max(0) as u64,
+        });
+    }
+    changes
+}
 
-fn cleanup_partial_downloads() {
-    let dir = std::path::Path::new(CACHE_PKG_DIR);
-    if let Ok(entries) = std::fs::read_dir(dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.is_file() {
-                if let Some(ext) = path.extension() {
-                    if ext == "part" {
-                        let _ = std::fs::remove_file(&path);
-                        logger::trace(&format!("Cleaned partial download: {}", path.display()));
-                    }
-                }
+/// Report a prepared transaction as `dry_run_change` events and release it. Nothing is
+/// downloaded or committed.
+pub fn finish_dry_run(alpm: &mut Alpm) -> Result<(), String> {
+    let changes = collect_changes(alpm);
+    alpm.trans_release().map_err(|e| e.to_string())?;
+    outcome::record(&changes);
+
+    let removals = changes
+        .iter()
+        .filter(|c| c.action == DryRunAction::Remove)
+        .count();
+    for change in &changes {
+        emit_progress_event(AlpmProgressEvent {
+            event_type: "dry_run_change".to_string(),
+            package: Some(change.name.clone()),
+            percent: None,
+            downloaded: None,
+            total: Some(change.download_size),
+            speed_bps: None,
+            eta_seconds: None,
+            message: serde_json::to_string(change).map_err(|e| e.to_string())?,
+        });
+    }
+    emit_simple_progress(
+        100,
+        &format!(
+            "Dry run complete: {} to install or upgrade, {} to remove. Nothing was changed.",
+            changes.len() - removals,
+            removals
+        ),
+    );
+    Ok(())
+}
+
+/// Extra trans_init flags for `phase`.
+pub fn phase_flags(phase: &TransactionPhase) -> TransFlag {
+    match phase {
+        TransactionPhase::DownloadOnly => TransFlag::DOWNLOAD_ONLY,
+        _ => TransFlag::NONE,
+    }
+}
+
+/// Differences between the set downloaded earlier and what the transaction resolves to now,
+/// one line per package. Empty when they match.
+pub fn phase_mismatch(expected: &[ResolvedPackage], resolved: &[ResolvedPackage]) -> Vec<String> {
+    let mut diffs = Vec::new();
+    for want in expected {
+        match resolved.iter().find(|r| r.name == want.name) {
+            None => diffs.push(format!(
+                "{} is no longer part of the transaction",
+                want.name
+            )),
+            Some(r) if r.version != want.version => diffs.push(format!(
+                "{} is now {} (downloaded {})",
+                want.name, r.version, want.version
+            )),
+            Some(_) => {}
+        }
+    }
+    for r in resolved {
+        if !expected.iter().any(|e| e.name == r.name) {
+            diffs.push(format!("{} {} was not downloaded", r.name, r.version));
+        }
+    }
+    diffs
+}
+
+/// Run between prepare and commit. A Commit phase must resolve to the set that was
+/// downloaded; otherwise the transaction is released and nothing changes. Returns the change
+/// set to report after a DownloadOnly commit.
+pub fn check_phase(
+    alpm: &mut Alpm,
+    phase: &TransactionPhase,
+) -> Result<Option<Vec<DryRunChange>>, String> {
+    match phase {
+        TransactionPhase::Full => Ok(None),
+        TransactionPhase::DownloadOnly => Ok(Some(collect_changes(alpm))),
+        TransactionPhase::Commit { expected } => {
+            let resolved: Vec<ResolvedPackage> = collect_changes(alpm)
+                .into_iter()
+                .filter_map(|c| {
+                    c.new_version.map(|version| ResolvedPackage {
+                        name: c.name,
+                        version,
+                    })
+                })
+                .collect();
+            let diffs = phase_mismatch(expected, &resolved);
+            if diffs.is_empty() {
+                return Ok(None);
+            }
+            let _ = alpm.trans_release();
+            emit_progress_event(AlpmProgressEvent {
+                event_type: "stale_download".to_string(),
+                package: None,
+                percent: None,
+                downloaded: None,
+                total: None,
+                speed_bps: None,
+                eta_seconds: None,
+                message: diffs.join("; "),
+            });
+            Err(format!(
+                "Packages changed since download; download again. {}",
+                diffs.join("; ")
+            ))
+        }
+    }
+}
+
+/// Commit the prepared transaction, recording each package's outcome for the final result.
+/// A DownloadOnly commit installs nothing; `emit_downloads_ready` records that one.
+/// Before a real commit the local DB is copied for the rescue toolkit (see rescue.rs).
+pub fn commit_recorded(alpm: &mut Alpm, download_only: bool) -> Result<(), String> {
+    let changes = collect_changes(alpm);
+    if !download_only {
+        if let Err(e) = crate::rescue::backup_local_db() {
+            logger::warn(&format!("Local database backup failed: {}", e));
+        }
+    }
+    let result = crate::downloads::commit_with_failover(alpm);
+    // Hooks may have run even when the commit failed part-way
+    crate::hook_notes::emit();
+    match result {
+        Ok(()) => {
+            if !download_only {
+                outcome::record(&changes);
             }
+            Ok(())
+        }
+        Err(msg) => {
+            outcome::record_failed(&changes, &msg);
+            Err(msg)
         }
     }
 }
 
+/// After a DownloadOnly commit: the packages now waiting in the cache.
+pub fn emit_downloads_ready(changes: &[DryRunChange]) {
+    outcome::record_downloaded(changes);
+    emit_progress_event(AlpmProgressEvent {
+        event_type: "downloads_ready".to_string(),
+        package: None,
+        percent: None,
+        downloaded: None,
+        total: Some(changes.iter().map(|c| c.download_size).sum()),
+        speed_bps: None,
+        eta_seconds: None,
+        message: serde_json::to_string(changes).unwrap_or_else(|_| "[]".to_string()),
+    });
+    emit_simple_progress(
+        100,
+        &format!(
+            "Download complete: {} package(s) ready to install.",
+            changes.len()
+        ),
+    );
+}
+
 fn is_corrupt_db_error(err: &str) -> bool {
     err.contains("Unrecognized archive format") || err.contains("could not open database")
 }
@@ -112,10 +261,35 @@ fn check_db_freshness(alpm: &Alpm) -> bool {
     false
 }
 
+/// A throwaway database directory for a dry run: the real local DB (linked, read-only use)
+/// and a copy of the sync DBs, so refreshing them leaves the live ones untouched.
+pub fn scratch_dbpath() -> Result<tempfile::TempDir, String> {
+    let dir = tempfile::Builder::new()
+        .prefix("monarch-dryrun")
+        .tempdir()
+        .map_err(|e| format!("Could not create a scratch database directory: {}", e))?;
+    let db_path = std::path::Path::new(DB_PATH);
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(db_path.join("local"), dir.path().join("local"))
+        .map_err(|e| format!("Could not link the local database: {}", e))?;
+    let sync = dir.path().join("sync");
+    std::fs::create_dir(&sync).map_err(|e| e.to_string())?;
+    // Copied rather than linked: libalpm replaces the files it refreshes in place
+    if let Ok(entries) = std::fs::read_dir(db_path.join("sync")) {
+        for entry in entries.flatten() {
+            if entry.path().is_file() {
+                let _ = std::fs::copy(entry.path(), sync.join(entry.file_name()));
+            }
+        }
+    }
+    Ok(dir)
+}
+
 pub fn force_refresh_sync_dbs(alpm: &mut Alpm) -> Result<(), String> {
     emit_simple_progress(5, "Force refreshing sync databases...");
-    let sync_dir = std::path::Path::new("/var/lib/pacman/sync");
-    if let Ok(entries) = std::fs::read_dir(sync_dir) {
+    // The handle's own directory: a dry run's scratch copy must not clear the live DBs
+    let sync_dir = std::path::Path::new(alpm.dbpath()).join("sync");
+    if let Ok(entries) = std::fs::read_dir(&sync_dir) {
         for entry in entries.flatten() {
             let path = entry.path();
             if path.is_file() {
@@ -175,10 +349,15 @@ pub fn execute_alpm_install(
     sync_first: bool,
     _cpu_optimization: Option<String>,
     target_repo: Option<String>,
+    phase: &TransactionPhase,
+    dry_run: bool,
     alpm: &mut Alpm,
 ) -> Result<(), String> {
-    if let Err(e) = ensure_keyrings_updated(alpm) {
-        logger::warn(&format!("Keyring pre-flight failed: {}", e));
+    // The keyring refresh installs packages, so a dry run resolves with the keys it has
+    if !dry_run {
+        if let Err(e) = ensure_keyrings_updated(alpm) {
+            logger::warn(&format!("Keyring pre-flight failed: {}", e));
+        }
     }
 
     emit_simple_progress(5, "Initializing transaction...");
@@ -222,7 +401,7 @@ pub fn execute_alpm_install(
         ));
     }
 
-    alpm.trans_init(TransFlag::ALL_DEPS)
+    alpm.trans_init(TransFlag::ALL_DEPS | phase_flags(phase))
         .map_err(|e| e.to_string())?;
 
     for pkg in &found_packages {
@@ -250,41 +429,29 @@ pub fn execute_alpm_install(
 
     setup_progress_callbacks(alpm)?;
 
-    // Pre-flight: warn if package cache or root is low on space (premium app-store UX)
-    #[cfg(unix)]
-    {
-        let cache_path = std::path::Path::new(CACHE_PKG_DIR);
-        if let Some(free) =
-            free_space_bytes(cache_path).or_else(|| free_space_bytes(std::path::Path::new("/")))
-        {
-            if free < LOW_DISK_SPACE_THRESHOLD_B {
-                let mb = free / (1024 * 1024);
-                emit_simple_progress(
-                    38,
-                    &format!(
-                        "Low disk space (~{} MB free). Installation may fail if cache is full.",
-                        mb
-                    ),
-                );
-            }
-        }
-    }
-
     emit_simple_progress(40, "Preparing transaction...");
     alpm.trans_prepare().map_err(|e| {
         let msg = format!("Transaction preparation failed: {}", e);
-        cleanup_partial_downloads();
+        crate::downloads::prune_partial_downloads();
         msg
     })?;
 
+    if dry_run {
+        return finish_dry_run(alpm);
+    }
+    crate::disk_space::preflight(alpm, *phase == TransactionPhase::DownloadOnly)?;
+    let downloaded = check_phase(alpm, phase)?;
+
     emit_simple_progress(50, "Downloading packages...");
-    match alpm.trans_commit() {
-        Ok(_) => {
-            emit_simple_progress(100, "Installation complete!");
+    match commit_recorded(alpm, downloaded.is_some()) {
+        Ok(()) => {
+            match downloaded {
+                Some(changes) => emit_downloads_ready(&changes),
+                None => emit_simple_progress(100, "Installation complete!"),
+            }
             Ok(())
         }
-        Err(e) => {
-            let msg = e.to_string();
+        Err(msg) => {
             let classified = classify_alpm_error(&msg);
             emit_progress_event(AlpmProgressEvent {
                 event_type: "error".to_string(),
@@ -292,6 +459,8 @@ pub fn execute_alpm_install(
                 percent: None,
                 downloaded: None,
                 total: None,
+                speed_bps: None,
+                eta_seconds: None,
                 message: serde_json::to_string(&classified).unwrap_or(msg.clone()),
             });
             Err(msg)
@@ -353,6 +522,8 @@ pub fn execute_alpm_check_updates_safe(_alpm: &mut Alpm) {
                             percent: None,
                             downloaded: None,
                             total: None,
+                            speed_bps: None,
+                            eta_seconds: None,
                             message: format!(
                                 "Update available: {} {} -> {}",
                                 name, parts[1], new_ver
@@ -374,6 +545,7 @@ pub fn execute_alpm_check_updates_safe(_alpm: &mut Alpm) {
 pub fn execute_alpm_uninstall(
     packages: Vec<String>,
     remove_deps: bool,
+    dry_run: bool,
     alpm: &mut Alpm,
 ) -> Result<(), String> {
     let flags = if remove_deps {
@@ -394,24 +566,30 @@ pub fn execute_alpm_uninstall(
     setup_progress_callbacks(alpm)?;
     alpm.trans_prepare().map_err(|e| e.to_string())?;
 
-    emit_simple_progress(50, "Removing packages...");
-    match alpm.trans_commit() {
-        Ok(_) => {
-            emit_simple_progress(100, "Uninstallation complete!");
-            Ok(())
-        }
-        Err(e) => Err(e.to_string()),
+    if dry_run {
+        return finish_dry_run(alpm);
     }
+
+    emit_simple_progress(50, "Removing packages...");
+    commit_recorded(alpm, false)?;
+    emit_simple_progress(100, "Uninstallation complete!");
+    Ok(())
 }
 
-pub fn execute_alpm_upgrade(packages: Option<Vec<String>>, alpm: &mut Alpm) -> Result<(), String> {
+pub fn execute_alpm_upgrade(
+    packages: Option<Vec<String>>,
+    dry_run: bool,
+    alpm: &mut Alpm,
+) -> Result<(), String> {
     if packages.is_some() {
         logger::info(
             "AlpmUpgrade with package list: doing full system upgrade (Arch does not support partial upgrades).",
         );
     }
 
-    ensure_keyrings_updated(alpm)?;
+    if !dry_run {
+        ensure_keyrings_updated(alpm)?;
+    }
 
     // RETRY LOOP: Scoped manually to avoid borrow checker issues
     let mut retry_needed = false;
@@ -452,19 +630,21 @@ pub fn execute_alpm_upgrade(packages: Option<Vec<String>>, alpm: &mut Alpm) -> R
                 ));
                 retry_needed = true;
             } else {
-                cleanup_partial_downloads();
+                crate::downloads::prune_partial_downloads();
                 return Err(format!("Transaction preparation failed: {}", msg));
             }
+        } else if dry_run {
+            return finish_dry_run(alpm);
         } else if !retry_needed {
             // Success path (only if no error)
+            crate::disk_space::preflight(alpm, false)?;
             emit_simple_progress(50, "Upgrading system...");
-            match alpm.trans_commit() {
-                Ok(_) => {
+            match commit_recorded(alpm, false) {
+                Ok(()) => {
                     emit_simple_progress(100, "System upgrade complete!");
                     return Ok(());
                 }
-                Err(e) => {
-                    let msg = e.to_string();
+                Err(msg) => {
                     let classified = classify_alpm_error(&msg);
                     emit_progress_event(AlpmProgressEvent {
                         event_type: "error".to_string(),
@@ -472,6 +652,8 @@ pub fn execute_alpm_upgrade(packages: Option<Vec<String>>, alpm: &mut Alpm) -> R
                         percent: None,
                         downloaded: None,
                         total: None,
+                        speed_bps: None,
+                        eta_seconds: None,
                         message: serde_json::to_string(&classified).unwrap_or(msg.clone()),
                     });
                     return Err(msg);
@@ -514,24 +696,30 @@ pub fn execute_alpm_upgrade(packages: Option<Vec<String>>, alpm: &mut Alpm) -> R
 
         if let Some(msg) = prepare_err {
             let _ = alpm.trans_release();
-            cleanup_partial_downloads();
+            crate::downloads::prune_partial_downloads();
             return Err(format!("Transaction preparation failed (Retry): {}", msg));
         }
 
+        if dry_run {
+            return finish_dry_run(alpm);
+        }
+
+        crate::disk_space::preflight(alpm, false)?;
         emit_simple_progress(50, "Upgrading system...");
-        match alpm.trans_commit() {
-            Ok(_) => {
+        match commit_recorded(alpm, false) {
+            Ok(()) => {
                 emit_simple_progress(100, "System upgrade complete!");
                 return Ok(());
             }
-            Err(e) => {
-                let msg = e.to_string();
+            Err(msg) => {
                 emit_progress_event(AlpmProgressEvent {
                     event_type: "error".to_string(),
                     package: None,
                     percent: None,
                     downloaded: None,
                     total: None,
+                    speed_bps: None,
+                    eta_seconds: None,
                     message: msg.clone(),
                 });
                 return Err(msg);
@@ -542,8 +730,67 @@ pub fn execute_alpm_upgrade(packages: Option<Vec<String>>, alpm: &mut Alpm) -> R
     Ok(())
 }
 
-pub fn execute_alpm_install_files(paths: Vec<String>, alpm: &mut Alpm) -> Result<(), String> {
-    ensure_keyrings_updated(alpm)?;
+/// `pacman -Syuw [targets]`: refresh, then download the full upgrade (and `targets`) into
+/// the cache without installing it. The resolved set goes out in `downloads_ready`.
+pub fn execute_alpm_download_upgrade(
+    targets: Option<Vec<String>>,
+    dry_run: bool,
+    alpm: &mut Alpm,
+) -> Result<(), String> {
+    emit_simple_progress(5, "Synchronizing databases...");
+    alpm.syncdbs_mut()
+        .update(false)
+        .map_err(|e| format!("Database sync failed: {}", e))?;
+
+    setup_progress_callbacks(alpm)?;
+    emit_simple_progress(10, "Calculating upgrades...");
+    alpm.trans_init(TransFlag::ALL_DEPS | TransFlag::DOWNLOAD_ONLY)
+        .map_err(|e| e.to_string())?;
+    if let Err(e) = alpm.sync_sysupgrade(false) {
+        let _ = alpm.trans_release();
+        return Err(e.to_string());
+    }
+    let targets = targets.unwrap_or_default();
+    let found = lookup_packages(alpm, &targets, &None);
+    if found.len() != targets.len() {
+        let _ = alpm.trans_release();
+        return Err(format!(
+            "Package(s) not found in enabled repositories: {:?}",
+            targets
+        ));
+    }
+    for pkg in found {
+        // Already queued by the sysupgrade when it's an upgrade
+        let _ = alpm.trans_add_pkg(pkg);
+    }
+
+    emit_simple_progress(20, "Preparing transaction...");
+    if let Err(e) = alpm.trans_prepare() {
+        let msg = format!("Transaction preparation failed: {}", e);
+        let _ = alpm.trans_release();
+        crate::downloads::prune_partial_downloads();
+        return Err(msg);
+    }
+    if dry_run {
+        return finish_dry_run(alpm);
+    }
+    crate::disk_space::preflight(alpm, true)?;
+    let changes = collect_changes(alpm);
+
+    emit_simple_progress(50, "Downloading packages...");
+    commit_recorded(alpm, true)?;
+    emit_downloads_ready(&changes);
+    Ok(())
+}
+
+pub fn execute_alpm_install_files(
+    paths: Vec<String>,
+    dry_run: bool,
+    alpm: &mut Alpm,
+) -> Result<(), String> {
+    if !dry_run {
+        ensure_keyrings_updated(alpm)?;
+    }
     emit_simple_progress(5, "Initializing local install...");
 
     for path in paths {
@@ -555,10 +802,57 @@ pub fn execute_alpm_install_files(paths: Vec<String>, alpm: &mut Alpm) -> Result
 
     setup_progress_callbacks(alpm)?;
     alpm.trans_prepare().map_err(|e| e.to_string())?;
-    match alpm.trans_commit() {
-        Ok(_) => Ok(()),
-        Err(e) => Err(e.to_string()),
+    if dry_run {
+        return finish_dry_run(alpm);
+    }
+    crate::disk_space::preflight(alpm, false)?;
+    commit_recorded(alpm, false)
+}
+
+/// Reinstall installed packages from the sync DBs at their installed version. A package
+/// whose repo version differs is skipped: installing it alone would be a partial upgrade.
+/// Returns the names that were reinstalled.
+pub fn execute_alpm_reinstall(names: &[String], alpm: &mut Alpm) -> Result<Vec<String>, String> {
+    emit_simple_progress(5, "Initializing reinstall...");
+    let mut targets = Vec::new();
+    for name in names {
+        let Ok(local) = alpm.localdb().pkg(name.as_str()) else {
+            continue;
+        };
+        let sync = alpm
+            .syncdbs()
+            .iter()
+            .find_map(|db| db.pkg(name.as_str()).ok());
+        match sync {
+            Some(pkg) if pkg.version() == local.version() => targets.push(pkg),
+            Some(pkg) => emit_simple_progress(
+                10,
+                &format!(
+                    "Skipping {}: repo has {} (installed {}); run a full update instead",
+                    name,
+                    pkg.version(),
+                    local.version()
+                ),
+            ),
+            None => emit_simple_progress(10, &format!("Skipping {}: not in any repository", name)),
+        }
+    }
+    if targets.is_empty() {
+        return Err("None of the packages can be reinstalled from the repositories".to_string());
     }
+    let reinstalled: Vec<String> = targets.iter().map(|p| p.name().to_string()).collect();
+
+    alpm.trans_init(TransFlag::NONE)
+        .map_err(|e| e.to_string())?;
+    for pkg in targets {
+        alpm.trans_add_pkg(pkg).map_err(|e| e.to_string())?;
+    }
+    setup_progress_callbacks(alpm)?;
+    emit_simple_progress(40, "Preparing transaction...");
+    alpm.trans_prepare().map_err(|e| e.to_string())?;
+    emit_simple_progress(50, "Reinstalling packages...");
+    commit_recorded(alpm, false)?;
+    Ok(reinstalled)
 }
 
 pub fn execute_alpm_sync(repos: Vec<String>, alpm: &mut Alpm) -> Result<(), String> {
@@ -582,11 +876,34 @@ pub fn execute_alpm_sync(repos: Vec<String>, alpm: &mut Alpm) -> Result<(), Stri
 }
 
 fn setup_progress_callbacks(alpm: &mut Alpm) -> Result<(), String> {
-    // Callback signatures fixed for alpm 5.x
-    // Ignoring download events for now to simplify type checking
-    alpm.set_dl_cb((), move |_, _, _| {
-        // no-op for now to satisfy type checker
-    });
+    // Per-file download callback: feeds the tracker, which throttles and computes speed/ETA.
+    alpm.set_dl_cb(
+        progress::DownloadTracker::default(),
+        move |filename, event, tracker| {
+            let update = match event.event() {
+                alpm::DownloadEvent::Init(_) => tracker.start(filename),
+                alpm::DownloadEvent::Progress(p) => {
+                    tracker.progress(filename, p.downloaded.max(0) as u64, p.total.max(0) as u64)
+                }
+                alpm::DownloadEvent::Completed(c) => {
+                    tracker.complete(filename, c.total.max(0) as u64)
+                }
+                _ => None,
+            };
+            if let Some(u) = update {
+                emit_progress_event(AlpmProgressEvent {
+                    event_type: u.event_type.to_string(),
+                    package: Some(u.package),
+                    percent: u.percent,
+                    downloaded: Some(u.downloaded),
+                    total: Some(u.total),
+                    speed_bps: u.speed_bps,
+                    eta_seconds: u.eta_seconds,
+                    message: format!("Downloading {}", filename),
+                });
+            }
+        },
+    );
 
     // Progress Callback: FnMut(&mut Ctx, &str, i32, usize, usize, ?)
     // We cannot reliably access Progress enum (AddStart etc) due to API changes/version mismatch.
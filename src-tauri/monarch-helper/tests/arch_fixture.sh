@@ -0,0 +1,115 @@
+#!/bin/bash
+# Throwaway Arch root for the helper E2E tests (tests/e2e_transactions.rs).
+#
+#   arch_fixture.sh up   <name> <helper-binary>   create the root, mount the helper, sync DBs
+#   arch_fixture.sh exec <name> <cmd> [args...]   run a command as root inside it
+#   arch_fixture.sh down <name>                   destroy it
+#
+# Backend: MONARCH_E2E_BACKEND=podman (default when podman exists) or nspawn (needs root,
+# systemd-nspawn and pacstrap from arch-install-scripts). MONARCH_CMD_JSON, when set, is
+# forwarded into the root so `exec <name> monarch-helper` receives a command.
+
+set -euo pipefail
+
+STATE_DIR="${TMPDIR:-/tmp}/monarch-e2e"
+IMAGE="${MONARCH_E2E_IMAGE:-docker.io/archlinux/archlinux:base}"
+HELPER_IN_ROOT="/usr/local/bin/monarch-helper"
+
+backend() {
+    if [ -n "${MONARCH_E2E_BACKEND:-}" ]; then
+        echo "$MONARCH_E2E_BACKEND"
+    elif command -v podman >/dev/null 2>&1; then
+        echo podman
+    elif command -v systemd-nspawn >/dev/null 2>&1 && command -v pacstrap >/dev/null 2>&1; then
+        echo nspawn
+    else
+        echo "No backend: install podman, or systemd-nspawn + arch-install-scripts" >&2
+        exit 2
+    fi
+}
+
+# Everything the tests need inside the root, regardless of backend
+prepare_root() {
+    local name="$1"
+    run_in "$name" sh -c '
+        sed -i "s/^#DisableSandbox/DisableSandbox/" /etc/pacman.conf
+        pacman-key --init >/dev/null 2>&1
+        pacman-key --populate archlinux >/dev/null 2>&1
+        pacman -Sy --noconfirm >/dev/null
+        mkdir -p /tmp/monarch-install
+    '
+    # A helper linked against a different libalpm soname fails here, not mid-test
+    if run_in "$name" ldd "$HELPER_IN_ROOT" | grep -q "not found"; then
+        echo "Helper binary has unresolved libraries inside the Arch root:" >&2
+        run_in "$name" ldd "$HELPER_IN_ROOT" | grep "not found" >&2
+        exit 3
+    fi
+}
+
+run_in() {
+    local name="$1"
+    shift
+    case "$(cat "$STATE_DIR/$name/backend")" in
+        podman)
+            podman exec ${MONARCH_CMD_JSON:+-e MONARCH_CMD_JSON="$MONARCH_CMD_JSON"} \
+                "monarch-e2e-$name" "$@"
+            ;;
+        nspawn)
+            systemd-nspawn -q --register=no --console=pipe -D "$STATE_DIR/$name/root" \
+                --bind-ro="$(cat "$STATE_DIR/$name/helper"):$HELPER_IN_ROOT" \
+                ${MONARCH_CMD_JSON:+--setenv=MONARCH_CMD_JSON="$MONARCH_CMD_JSON"} \
+                "$@"
+            ;;
+    esac
+}
+
+cmd="${1:-}"
+name="${2:-}"
+if [ -z "$cmd" ] || [ -z "$name" ]; then
+    sed -n '2,11p' "$0" >&2
+    exit 2
+fi
+
+case "$cmd" in
+    up)
+        helper="$(realpath "${3:?helper binary path required}")"
+        mkdir -p "$STATE_DIR/$name"
+        echo "$helper" > "$STATE_DIR/$name/helper"
+        b="$(backend)"
+        echo "$b" > "$STATE_DIR/$name/backend"
+        case "$b" in
+            podman)
+                podman rm -f "monarch-e2e-$name" >/dev/null 2>&1 || true
+                podman run -d --name "monarch-e2e-$name" \
+                    -v "$helper:$HELPER_IN_ROOT:ro" \
+                    "$IMAGE" sleep infinity >/dev/null
+                ;;
+            nspawn)
+                mkdir -p "$STATE_DIR/$name/root"
+                pacstrap -c -K "$STATE_DIR/$name/root" base >/dev/null
+                ;;
+            *)
+                echo "Unknown backend: $b" >&2
+                exit 2
+                ;;
+        esac
+        prepare_root "$name"
+        ;;
+    exec)
+        shift 2
+        run_in "$name" "$@"
+        ;;
+    down)
+        if [ -f "$STATE_DIR/$name/backend" ]; then
+            case "$(cat "$STATE_DIR/$name/backend")" in
+                podman) podman rm -f "monarch-e2e-$name" >/dev/null 2>&1 || true ;;
+                nspawn) rm -rf --one-file-system "$STATE_DIR/$name/root" ;;
+            esac
+        fi
+        rm -rf "$STATE_DIR/$name"
+        ;;
+    *)
+        echo "Unknown command: $cmd" >&2
+        exit 2
+        ;;
+esac
@@ -0,0 +1,410 @@
+/// End-to-end transaction tests: the real helper binary runs AlpmInstall / AlpmUpgrade /
+/// AlpmUninstall / AlpmInstallFiles as root inside a throwaway Arch root (podman container or
+/// systemd-nspawn directory, see tests/arch_fixture.sh). Each test asserts on the JSON event
+/// stream the GUI would receive and on the final local DB (`pacman -Q`).
+///
+/// Needs network access and podman (or root + systemd-nspawn + pacstrap), so they are ignored
+/// by default. Run with:
+/// `cargo test -p monarch-helper --test e2e_transactions -- --ignored --test-threads=1`
+///
+/// The helper runs inside the Arch root, so it must be built against a compatible libalpm
+/// (an Arch host or the builder image). MONARCH_E2E_HELPER overrides the binary path.
+use monarch_types::{
+    exit_code, AlpmProgressEvent, DryRunAction, DryRunChange, HelperCommand, PackageOutcome,
+    ResolvedPackage, TransactionManifest, TransactionPhase, TransactionResult,
+};
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+/// Small, dependency-light package from [extra] that no base image ships.
+const TEST_PKG: &str = "tree";
+
+struct ArchFixture {
+    name: String,
+}
+
+fn fixture_script() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("arch_fixture.sh")
+}
+
+fn helper_binary() -> PathBuf {
+    std::env::var_os("MONARCH_E2E_HELPER")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_BIN_EXE_monarch-helper")))
+}
+
+impl ArchFixture {
+    fn up(test: &str) -> Self {
+        let name = format!("{}-{}", test, std::process::id());
+        let status = Command::new(fixture_script())
+            .args(["up", &name])
+            .arg(helper_binary())
+            .status()
+            .expect("Should run arch_fixture.sh");
+        assert!(status.success(), "Arch root fixture failed to start");
+        ArchFixture { name }
+    }
+
+    fn exec(&self, args: &[&str]) -> Output {
+        Command::new(fixture_script())
+            .args(["exec", &self.name])
+            .args(args)
+            .env_remove("MONARCH_CMD_JSON")
+            .output()
+            .expect("Should run command in the Arch root")
+    }
+
+    /// Run one helper command and collect every event it emits.
+    fn run_helper(&self, cmd: &HelperCommand) -> Vec<AlpmProgressEvent> {
+        self.run_helper_in(&["monarch-helper"], cmd)
+    }
+
+    /// Like `run_helper`, after a shell `setup` in the same invocation (nspawn gives every
+    /// invocation a fresh /dev/shm).
+    fn run_helper_after(&self, setup: &str, cmd: &HelperCommand) -> Vec<AlpmProgressEvent> {
+        let script = format!("{} && exec monarch-helper", setup);
+        self.run_helper_in(&["sh", "-c", &script], cmd)
+    }
+
+    fn run_helper_in(&self, argv: &[&str], cmd: &HelperCommand) -> Vec<AlpmProgressEvent> {
+        let json = serde_json::to_string(cmd).expect("Should serialize");
+        let out = Command::new(fixture_script())
+            .args(["exec", &self.name])
+            .args(argv)
+            .env("MONARCH_CMD_JSON", json)
+            .output()
+            .expect("Should run helper in the Arch root");
+        String::from_utf8_lossy(&out.stdout)
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| {
+                serde_json::from_str::<AlpmProgressEvent>(l)
+                    .unwrap_or_else(|e| panic!("Helper emitted a non-event line {:?}: {}", l, e))
+            })
+            .collect()
+    }
+
+    fn installed_version(&self, pkg: &str) -> Option<String> {
+        let out = self.exec(&["pacman", "-Q", pkg]);
+        if !out.status.success() {
+            return None;
+        }
+        String::from_utf8_lossy(&out.stdout)
+            .split_whitespace()
+            .nth(1)
+            .map(str::to_string)
+    }
+}
+
+impl Drop for ArchFixture {
+    fn drop(&mut self) {
+        let _ = Command::new(fixture_script())
+            .args(["down", &self.name])
+            .status();
+    }
+}
+
+fn errors(events: &[AlpmProgressEvent]) -> Vec<&str> {
+    events
+        .iter()
+        .filter(|e| e.event_type == "error" || e.message.starts_with("Error"))
+        .map(|e| e.message.as_str())
+        .collect()
+}
+
+fn dry_run_changes(events: &[AlpmProgressEvent]) -> Vec<DryRunChange> {
+    events
+        .iter()
+        .filter(|e| e.event_type == "dry_run_change")
+        .map(|e| serde_json::from_str(&e.message).expect("dry_run_change carries a DryRunChange"))
+        .collect()
+}
+
+/// The summary every run ends with.
+fn transaction_result(events: &[AlpmProgressEvent]) -> TransactionResult {
+    let last = events.last().expect("Helper emitted no events");
+    assert_eq!(last.event_type, "transaction_result");
+    serde_json::from_str(&last.message).expect("transaction_result carries a TransactionResult")
+}
+
+fn assert_succeeded(events: &[AlpmProgressEvent], done_message: &str) {
+    assert!(!events.is_empty(), "Helper emitted no events");
+    assert_eq!(errors(events), Vec::<&str>::new(), "Helper reported errors");
+    assert!(
+        events
+            .iter()
+            .any(|e| e.percent == Some(100) && e.message.contains(done_message)),
+        "No '{}' event in: {:#?}",
+        done_message,
+        events.iter().map(|e| &e.message).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+#[ignore = "needs podman or systemd-nspawn and network; see TESTING.md"]
+fn e2e_install_upgrade_uninstall_cycle() {
+    let root = ArchFixture::up("cycle");
+    assert_eq!(root.installed_version(TEST_PKG), None);
+
+    let events = root.run_helper(&HelperCommand::AlpmInstall {
+        packages: vec![TEST_PKG.to_string()],
+        sync_first: false,
+        enabled_repos: vec!["core".into(), "extra".into()],
+        cpu_optimization: None,
+        target_repo: Some("extra".into()),
+        phase: Default::default(),
+        dry_run: false,
+    });
+    assert_succeeded(&events, "Installation complete");
+    let result = transaction_result(&events);
+    assert!(result.success);
+    assert_eq!(result.outcome_of(TEST_PKG), Some(PackageOutcome::Installed));
+    // Package-level events carry the package name the GUI shows per row
+    assert!(events
+        .iter()
+        .any(|e| e.package.as_deref() == Some(TEST_PKG)));
+    assert!(root.installed_version(TEST_PKG).is_some());
+
+    // The image is a snapshot, so a full upgrade usually has real work to do
+    let events = root.run_helper(&HelperCommand::AlpmUpgrade {
+        packages: None,
+        enabled_repos: vec!["core".into(), "extra".into()],
+        phase: Default::default(),
+        dry_run: false,
+    });
+    assert_succeeded(&events, "System upgrade complete");
+    let pending = root.exec(&["pacman", "-Qu"]);
+    assert!(
+        String::from_utf8_lossy(&pending.stdout).trim().is_empty(),
+        "Upgrade left packages behind: {}",
+        String::from_utf8_lossy(&pending.stdout)
+    );
+
+    let events = root.run_helper(&HelperCommand::AlpmUninstall {
+        packages: vec![TEST_PKG.to_string()],
+        remove_deps: true,
+        dry_run: false,
+    });
+    assert_succeeded(&events, "Uninstallation complete");
+    assert_eq!(
+        transaction_result(&events).outcome_of(TEST_PKG),
+        Some(PackageOutcome::Removed)
+    );
+    assert_eq!(root.installed_version(TEST_PKG), None);
+}
+
+#[test]
+#[ignore = "needs podman or systemd-nspawn and network; see TESTING.md"]
+fn e2e_install_missing_package_reports_error_and_changes_nothing() {
+    let root = ArchFixture::up("missing");
+    let before = root.exec(&["pacman", "-Qq"]).stdout;
+
+    let events = root.run_helper(&HelperCommand::AlpmInstall {
+        packages: vec!["monarch-no-such-package".to_string()],
+        sync_first: false,
+        enabled_repos: vec!["core".into(), "extra".into()],
+        cpu_optimization: None,
+        target_repo: None,
+        phase: Default::default(),
+        dry_run: false,
+    });
+    assert!(
+        !errors(&events).is_empty(),
+        "Expected an error event, got: {:#?}",
+        events
+    );
+    assert!(!events
+        .iter()
+        .any(|e| e.message.contains("Installation complete")));
+    let result = transaction_result(&events);
+    assert!(!result.success);
+    assert_eq!(result.exit_code, exit_code::FAILED);
+    assert_eq!(
+        result.outcome_of("monarch-no-such-package"),
+        Some(PackageOutcome::Failed)
+    );
+    assert_eq!(root.exec(&["pacman", "-Qq"]).stdout, before);
+}
+
+#[test]
+#[ignore = "needs podman or systemd-nspawn and network; see TESTING.md"]
+fn e2e_install_files_and_batch() {
+    let root = ArchFixture::up("files");
+
+    // Fetch the archive the way an AUR build would leave one: a file in /tmp/monarch-install
+    let fetched = root.exec(&[
+        "pacman",
+        "-Sw",
+        "--noconfirm",
+        "--cachedir",
+        "/tmp/monarch-install",
+        TEST_PKG,
+    ]);
+    assert!(fetched.status.success(), "pacman -Sw failed");
+    let listing = root.exec(&["sh", "-c", "ls /tmp/monarch-install/*.pkg.tar.zst"]);
+    let path = String::from_utf8_lossy(&listing.stdout)
+        .lines()
+        .find(|l| l.contains(TEST_PKG))
+        .expect("Downloaded package archive")
+        .to_string();
+
+    let events = root.run_helper(&HelperCommand::AlpmInstallFiles { paths: vec![path] });
+    assert_eq!(
+        errors(&events),
+        Vec::<&str>::new(),
+        "Helper reported errors"
+    );
+    assert!(root.installed_version(TEST_PKG).is_some());
+
+    // Paths outside /tmp/monarch-install are refused before ALPM sees them
+    let events = root.run_helper(&HelperCommand::AlpmInstallFiles {
+        paths: vec!["/etc/passwd".into()],
+    });
+    assert!(errors(&events)
+        .iter()
+        .any(|m| m.contains("Unauthorized path")));
+
+    // One ExecuteBatch removing the package again under a single lock
+    let events = root.run_helper(&HelperCommand::ExecuteBatch {
+        manifest: TransactionManifest {
+            remove_targets: vec![TEST_PKG.to_string()],
+            ..Default::default()
+        },
+        dry_run: false,
+    });
+    assert_eq!(
+        errors(&events),
+        Vec::<&str>::new(),
+        "Helper reported errors"
+    );
+    assert_eq!(root.installed_version(TEST_PKG), None);
+}
+
+#[test]
+#[ignore = "needs podman or systemd-nspawn and network; see TESTING.md"]
+fn e2e_dry_run_reports_changes_and_commits_nothing() {
+    let root = ArchFixture::up("dryrun");
+    let before = root.exec(&["pacman", "-Q"]).stdout;
+
+    let events = root.run_helper(&HelperCommand::AlpmInstall {
+        packages: vec![TEST_PKG.to_string()],
+        sync_first: false,
+        enabled_repos: vec!["core".into(), "extra".into()],
+        cpu_optimization: None,
+        target_repo: Some("extra".into()),
+        phase: Default::default(),
+        dry_run: true,
+    });
+    assert_succeeded(&events, "Dry run complete");
+    let changes = dry_run_changes(&events);
+    let tree = changes
+        .iter()
+        .find(|c| c.name == TEST_PKG)
+        .expect("Dry run lists the requested package");
+    assert_eq!(tree.action, DryRunAction::Install);
+    assert_eq!(tree.old_version, None);
+    assert!(tree.new_version.is_some());
+
+    let events = root.run_helper(&HelperCommand::AlpmUpgrade {
+        packages: None,
+        enabled_repos: vec!["core".into(), "extra".into()],
+        phase: Default::default(),
+        dry_run: true,
+    });
+    assert_succeeded(&events, "Dry run complete");
+
+    let events = root.run_helper(&HelperCommand::ExecuteBatch {
+        manifest: TransactionManifest {
+            install_targets: vec![TEST_PKG.to_string()],
+            ..Default::default()
+        },
+        dry_run: true,
+    });
+    assert_succeeded(&events, "Batch dry run complete");
+    assert!(dry_run_changes(&events).iter().any(|c| c.name == TEST_PKG));
+
+    assert_eq!(root.exec(&["pacman", "-Q"]).stdout, before);
+}
+
+#[test]
+#[ignore = "needs podman or systemd-nspawn and network; see TESTING.md"]
+fn e2e_download_now_commit_later() {
+    let root = ArchFixture::up("deferred");
+    let install = |phase: TransactionPhase| HelperCommand::AlpmInstall {
+        packages: vec![TEST_PKG.to_string()],
+        sync_first: false,
+        enabled_repos: vec!["core".into(), "extra".into()],
+        cpu_optimization: None,
+        target_repo: Some("extra".into()),
+        phase,
+        dry_run: false,
+    };
+
+    let events = root.run_helper(&install(TransactionPhase::DownloadOnly));
+    assert_succeeded(&events, "Download complete");
+    let ready: Vec<DryRunChange> = events
+        .iter()
+        .find(|e| e.event_type == "downloads_ready")
+        .map(|e| serde_json::from_str(&e.message).expect("downloads_ready carries changes"))
+        .expect("No downloads_ready event");
+    assert_eq!(root.installed_version(TEST_PKG), None);
+    let cached = root.exec(&["sh", "-c", "ls /var/cache/pacman/pkg"]);
+    assert!(String::from_utf8_lossy(&cached.stdout).contains(TEST_PKG));
+
+    // A resume token whose versions no longer match is refused without changes
+    let stale = vec![ResolvedPackage {
+        name: TEST_PKG.to_string(),
+        version: "0.0.1-1".to_string(),
+    }];
+    let events = root.run_helper(&install(TransactionPhase::Commit { expected: stale }));
+    assert!(events.iter().any(|e| e.event_type == "stale_download"));
+    assert_eq!(root.installed_version(TEST_PKG), None);
+
+    let expected: Vec<ResolvedPackage> = ready
+        .iter()
+        .map(|c| ResolvedPackage {
+            name: c.name.clone(),
+            version: c.new_version.clone().expect("Installs carry a version"),
+        })
+        .collect();
+    let version = expected
+        .iter()
+        .find(|p| p.name == TEST_PKG)
+        .map(|p| p.version.clone());
+    let events = root.run_helper(&install(TransactionPhase::Commit { expected }));
+    assert_succeeded(&events, "Installation complete");
+    assert_eq!(root.installed_version(TEST_PKG), version);
+}
+
+#[test]
+#[ignore = "needs podman or systemd-nspawn and network; see TESTING.md"]
+fn e2e_upgrade_refuses_when_cache_is_full() {
+    let root = ArchFixture::up("diskfull");
+    let before = root.exec(&["pacman", "-Q"]).stdout;
+
+    // The package cache on a tmpfs with 1 MiB left, below the preflight's cushion
+    let fill_cache = "mkdir -p /dev/shm/pkg && rm -rf /var/cache/pacman/pkg \
+        && ln -s /dev/shm/pkg /var/cache/pacman/pkg \
+        && fallocate -l $(( $(df --output=avail -B1 /dev/shm | tail -n 1) - 1048576 )) \
+        /dev/shm/fill";
+    let events = root.run_helper_after(
+        fill_cache,
+        &HelperCommand::AlpmUpgrade {
+            packages: None,
+            enabled_repos: vec!["core".into(), "extra".into()],
+            phase: Default::default(),
+            dry_run: false,
+        },
+    );
+    assert!(
+        errors(&events)
+            .iter()
+            .any(|e| e.contains(monarch_types::disk_space::SHORTFALL_PREFIX)),
+        "No disk space error in: {:#?}",
+        errors(&events)
+    );
+    assert!(!transaction_result(&events).success);
+    assert_eq!(root.exec(&["pacman", "-Q"]).stdout, before);
+}
@@ -49,7 +49,7 @@ unset MONARCH_CMD_JSON
 
 # Test 3: Reject raw strings (no root needed)
 echo -e "${GREEN}Test 3: Reject Raw Strings${NC}"
-echo "cachyos" | $HELPER_BIN 2>&1 | grep -q "Invalid input on stdin" && \
+$HELPER_BIN cachyos 2>&1 | grep -q "Invalid command argument" && \
     echo -e "${GREEN}✓ Raw strings correctly rejected${NC}" || \
     (echo -e "${RED}✗ Raw strings not rejected${NC}" && exit 1)
 
@@ -0,0 +1,123 @@
+//! Package cache retention planning, shared by the helper's CleanPackageCache and the GUI's
+//! dry-run preview (which only needs read access to the cache and the local DB). Modelled
+//! on paccache: for each package (and architecture) keep the `keep` newest versions, and
+//! never the installed version unless the whole cache is being cleared. Versions are
+//! compared with the caller's vercmp (libalpm's in both binaries).
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+pub const CACHE_PKG_DIR: &str = "/var/cache/pacman/pkg";
+
+/// What a cleanup keeps.
+#[derive(Debug, Clone, Copy)]
+pub struct RetentionPolicy {
+    /// Newest versions kept per package.
+    pub keep: u32,
+    /// The installed version stays regardless of `keep`.
+    pub keep_installed: bool,
+    /// Only packages that are not installed lose files.
+    pub uninstalled_only: bool,
+}
+
+/// One package file in the cache.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheEntry {
+    pub name: String,
+    pub version: String,
+    pub arch: String,
+    pub path: PathBuf,
+    /// Package plus signature.
+    pub size: u64,
+}
+
+/// `name-pkgver-pkgrel-arch.pkg.tar[.ext]` -> (name, "pkgver-pkgrel", arch).
+pub fn parse_package_filename(file_name: &str) -> Option<(String, String, String)> {
+    let (stem, ext) = file_name.split_once(".pkg.tar")?;
+    if !matches!(
+        ext,
+        "" | ".zst" | ".xz" | ".gz" | ".bz2" | ".lz4" | ".lzo" | ".lz" | ".Z"
+    ) {
+        return None;
+    }
+    let mut parts = stem.rsplitn(4, '-');
+    let arch = parts.next()?;
+    let pkgrel = parts.next()?;
+    let pkgver = parts.next()?;
+    let name = parts.next()?;
+    if name.is_empty() || pkgver.is_empty() || pkgrel.is_empty() || arch.is_empty() {
+        return None;
+    }
+    Some((
+        name.to_string(),
+        format!("{}-{}", pkgver, pkgrel),
+        arch.to_string(),
+    ))
+}
+
+/// Split `entries` into (removed, kept) under `policy`; `installed` maps pkgname to the
+/// installed version and `vercmp` orders two versions the way pacman does.
+pub fn plan(
+    entries: Vec<CacheEntry>,
+    installed: &HashMap<String, String>,
+    policy: RetentionPolicy,
+    vercmp: fn(&str, &str) -> Ordering,
+) -> (Vec<CacheEntry>, Vec<CacheEntry>) {
+    let mut groups: BTreeMap<(String, String), Vec<CacheEntry>> = BTreeMap::new();
+    for e in entries {
+        groups
+            .entry((e.name.clone(), e.arch.clone()))
+            .or_default()
+            .push(e);
+    }
+
+    let (mut removed, mut kept) = (Vec::new(), Vec::new());
+    for ((name, _), mut files) in groups {
+        let installed_version = installed.get(&name);
+        if policy.uninstalled_only && installed_version.is_some() {
+            kept.extend(files);
+            continue;
+        }
+        files.sort_by(|a, b| vercmp(&b.version, &a.version));
+        for (i, file) in files.into_iter().enumerate() {
+            let is_installed = installed_version == Some(&file.version);
+            if (i as u32) < policy.keep || (policy.keep_installed && is_installed) {
+                kept.push(file);
+            } else {
+                removed.push(file);
+            }
+        }
+    }
+    (removed, kept)
+}
+
+/// Package files in `dir` (regular files with a parseable name), sizes including signatures.
+pub fn scan(dir: &Path) -> Vec<CacheEntry> {
+    let Ok(read) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    read.flatten()
+        .filter_map(|entry| {
+            // Regular files only: a symlink in the cache must not point the cleanup elsewhere
+            let meta = std::fs::symlink_metadata(entry.path()).ok()?;
+            if !meta.is_file() {
+                return None;
+            }
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            let (name, version, arch) = parse_package_filename(&file_name)?;
+            let sig = dir.join(format!("{}.sig", file_name));
+            let sig_size = std::fs::symlink_metadata(&sig)
+                .ok()
+                .filter(|m| m.is_file())
+                .map_or(0, |m| m.len());
+            Some(CacheEntry {
+                name,
+                version,
+                arch,
+                path: entry.path(),
+                size: meta.len() + sig_size,
+            })
+        })
+        .collect()
+}
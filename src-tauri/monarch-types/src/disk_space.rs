@@ -0,0 +1,100 @@
+//! Free-space preflight shared by the helper (package cache and root before a commit) and
+//! the GUI (the AUR build directory, often a small tmpfs). libalpm only notices a full
+//! disk while extracting, and makepkg halfway through a build; both leave a mess that
+//! looks like anything but a full disk. The check refuses up front with a message the
+//! error classifier maps to [`crate::errors::ErrorKind::DiskFull`].
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Kept free on top of what an operation needs; pacman's own check uses the same margin.
+pub const SPACE_CUSHION_B: u64 = 20 * 1024 * 1024;
+
+/// Starts every shortfall message; the error classifier keys on it.
+pub const SHORTFALL_PREFIX: &str = "Not enough free disk space";
+
+/// What an operation will write to one filesystem and what that filesystem has free.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SpaceCheck {
+    /// Directory the space is needed in (a mount point or a directory on one).
+    pub path: String,
+    pub required: u64,
+    pub available: u64,
+    /// The filesystem is a tmpfs, so its size is a share of RAM rather than disk.
+    #[serde(default)]
+    pub tmpfs: bool,
+}
+
+impl SpaceCheck {
+    pub fn is_short(&self) -> bool {
+        self.required.saturating_add(SPACE_CUSHION_B) > self.available
+    }
+}
+
+/// The filesystem a path lives on, as `statfs` reports it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Filesystem {
+    /// Device id, to add up needs of paths that share one filesystem.
+    pub dev: u64,
+    /// Bytes free for unprivileged users.
+    pub available: u64,
+    pub tmpfs: bool,
+}
+
+/// The filesystem holding `path`, or its nearest existing parent (a cache or build
+/// directory may not exist yet).
+pub fn filesystem(path: &Path) -> Option<Filesystem> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::MetadataExt;
+    let existing = path.ancestors().find(|p| p.exists())?;
+    let dev = std::fs::metadata(existing).ok()?.dev();
+    let c_path = CString::new(existing.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    Some(Filesystem {
+        dev,
+        available: stat.f_bavail * stat.f_bsize as u64,
+        tmpfs: stat.f_type == libc::TMPFS_MAGIC,
+    })
+}
+
+/// Bytes as B, KiB, MiB or GiB with one decimal.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// The error for the checks that fail, or `None` when everything fits.
+pub fn shortfall_message(checks: &[SpaceCheck]) -> Option<String> {
+    let short: Vec<String> = checks
+        .iter()
+        .filter(|c| c.is_short())
+        .map(|c| {
+            format!(
+                "{} needs {}, {} free{}",
+                c.path,
+                format_size(c.required.saturating_add(SPACE_CUSHION_B)),
+                format_size(c.available),
+                if c.tmpfs { " (tmpfs)" } else { "" }
+            )
+        })
+        .collect();
+    if short.is_empty() {
+        None
+    } else {
+        Some(format!("{}: {}", SHORTFALL_PREFIX, short.join("; ")))
+    }
+}
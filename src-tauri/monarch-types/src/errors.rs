@@ -0,0 +1,624 @@
+//! Classification of pacman, libalpm, makepkg and network errors into a typed kind and a
+//! recovery action the frontend can offer as a button. Shared so the helper's `error`
+//! events and the GUI's own errors (pacman output, AUR builds, install failures) carry the
+//! same codes.
+//!
+//! [`RecoveryAction`] serializes as `{"type": "...", "payload": ...}`. Helpers before this
+//! sent a bare string there, which the frontend still accepts.
+
+use crate::disk_space::SHORTFALL_PREFIX;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// What went wrong. The variant name is the stable error code.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Database is locked by another process
+    DatabaseLocked,
+    /// A sync database could not be read (truncated download, wrong format)
+    DatabaseCorrupt,
+    /// PGP signature verification failed
+    KeyringError,
+    /// Package not found in any repository
+    PackageNotFound,
+    /// A 32-bit (lib32-*) package was asked for while [multilib] is off
+    MultilibDisabled,
+    /// The sync databases name package files the mirrors no longer have
+    StaleDatabase,
+    /// Mirror/network issues
+    MirrorFailure,
+    /// No network at all: name resolution or routing fails
+    NetworkUnavailable,
+    /// Disk space insufficient
+    DiskFull,
+    /// Dependency conflict
+    DependencyConflict,
+    /// File conflict with existing package
+    FileConflict,
+    /// Corrupted package download
+    CorruptedPackage,
+    /// Permission denied
+    PermissionDenied,
+    /// makepkg, fakeroot, git or another base-devel tool is missing
+    MissingBuildTools,
+    /// makepkg could not verify a source signature: the maintainer's key is not imported
+    SourcePgpKey,
+    /// A downloaded source did not match the PKGBUILD's checksum
+    ChecksumMismatch,
+    /// A source file could not be downloaded by makepkg
+    SourceDownloadFailed,
+    /// makepkg could not install a build dependency
+    BuildDependencyMissing,
+    /// makepkg reported "An unknown error has occurred" (often toolchain/permissions)
+    MakepkgBuildFailure,
+    /// Generic/unknown error
+    Unknown,
+}
+
+/// Actions that can be taken to recover from an error
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", content = "payload")]
+pub enum RecoveryAction {
+    /// Remove the database lock file
+    UnlockDatabase,
+    /// Reset and repopulate the keyring
+    RepairKeyring,
+    /// Refresh mirrors with reflector or manual intervention
+    RefreshMirrors,
+    /// Force refresh sync databases
+    ForceRefreshDb,
+    /// Full system upgrade (-Syu) before trying again
+    SystemUpdate,
+    /// Enable the [multilib] repository
+    EnableMultilib,
+    /// Free up disk space
+    CleanCache,
+    /// Install base-devel and git
+    InstallBuildTools,
+    /// Import the PGP key with this fingerprint or ID into the user's keyring
+    ImportPgpKey(String),
+    /// Retry the operation
+    Retry,
+    /// Show manual resolution steps
+    ShowManualSteps(String),
+}
+
+/// Structured error with classification and suggested action
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ClassifiedError {
+    pub kind: ErrorKind,
+    pub title: String,
+    pub description: String,
+    #[serde(default)]
+    pub recovery_action: Option<RecoveryAction>,
+    pub raw_message: String,
+}
+
+impl fmt::Display for ClassifiedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.kind == ErrorKind::Unknown {
+            write!(f, "{}", self.description)
+        } else {
+            write!(f, "{}: {}", self.title, self.description)
+        }
+    }
+}
+
+/// Unclassified errors keep their message as the description, so `?` on a `String` error
+/// loses nothing.
+impl From<String> for ClassifiedError {
+    fn from(message: String) -> Self {
+        Self::classify(&message)
+    }
+}
+
+impl From<&str> for ClassifiedError {
+    fn from(message: &str) -> Self {
+        Self::classify(message)
+    }
+}
+
+/// The key makepkg could not verify a source with: `... unknown public key 1234ABCD)`.
+fn unknown_key(output: &str) -> Option<String> {
+    let lower = output.to_lowercase();
+    let start = lower.find("unknown public key")? + "unknown public key".len();
+    let key: String = output[start..]
+        .trim_start()
+        .chars()
+        .take_while(|c| c.is_ascii_hexdigit())
+        .collect();
+    (key.len() >= 8).then_some(key)
+}
+
+fn any(haystack: &str, needles: &[&str]) -> bool {
+    needles.iter().any(|n| haystack.contains(n))
+}
+
+impl ClassifiedError {
+    pub fn new(
+        kind: ErrorKind,
+        title: &str,
+        description: &str,
+        recovery_action: Option<RecoveryAction>,
+        raw_message: &str,
+    ) -> Self {
+        Self {
+            kind,
+            title: title.to_string(),
+            description: description.to_string(),
+            recovery_action,
+            raw_message: raw_message.to_string(),
+        }
+    }
+
+    /// Analyze pacman/makepkg output and classify the error; `None` if nothing matches.
+    pub fn from_output(output: &str) -> Option<Self> {
+        let c = Self::classify(output);
+        (c.kind != ErrorKind::Unknown).then_some(c)
+    }
+
+    /// Like [`from_output`](Self::from_output), falling back to [`ErrorKind::Unknown`]
+    /// with the message itself as the description.
+    pub fn classify(output: &str) -> Self {
+        let lower = output.to_lowercase();
+        let found = |kind, title: &str, description: &str, action| {
+            Self::new(kind, title, description, action, output)
+        };
+
+        // Database Lock Detection
+        if any(
+            &lower,
+            &["database is locked", "unable to lock database", "db.lck"],
+        ) {
+            return found(
+                ErrorKind::DatabaseLocked,
+                "Database Locked",
+                "Another package manager is running or a previous operation was interrupted.",
+                Some(RecoveryAction::UnlockDatabase),
+            );
+        }
+
+        // Unreadable sync database
+        if any(
+            &lower,
+            &[
+                "unrecognized archive format",
+                "could not open database",
+                "failed to open database",
+                "database not found",
+                "failed to initialize alpm",
+            ],
+        ) {
+            return found(
+                ErrorKind::DatabaseCorrupt,
+                "Package Database Issue",
+                "A package database could not be read. Downloading fresh copies usually fixes it.",
+                Some(RecoveryAction::ForceRefreshDb),
+            );
+        }
+
+        // Missing toolchain for AUR builds
+        if any(
+            &lower,
+            &[
+                "cannot find the fakeroot binary",
+                "cannot find the strip binary",
+                "makepkg: command not found",
+                "git: command not found",
+                "cannot find the git package needed",
+                "base-devel is not installed",
+            ],
+        ) {
+            return found(
+                ErrorKind::MissingBuildTools,
+                "Build Tools Missing",
+                "Building from the AUR needs base-devel and git.",
+                Some(RecoveryAction::InstallBuildTools),
+            );
+        }
+
+        // makepkg source signature: the PKGBUILD's validpgpkeys are not in the user's keyring
+        if lower.contains("one or more pgp signatures could not be verified")
+            || (lower.contains("unknown public key") && lower.contains("==>"))
+        {
+            return found(
+                ErrorKind::SourcePgpKey,
+                "Source Signature Not Verified",
+                "The upstream source is signed with a key you have not imported yet.",
+                unknown_key(output).map(RecoveryAction::ImportPgpKey),
+            );
+        }
+
+        if lower.contains("did not pass the validity check") {
+            return found(
+                ErrorKind::ChecksumMismatch,
+                "Source Checksum Mismatch",
+                "A downloaded source file does not match the checksum in the PKGBUILD. Upstream may have re-released it; wait for the AUR package to be updated.",
+                Some(RecoveryAction::Retry),
+            );
+        }
+
+        if lower.contains("failure while downloading")
+            || lower.contains("one or more files did not download")
+        {
+            return found(
+                ErrorKind::SourceDownloadFailed,
+                "Source Download Failed",
+                "makepkg could not download a source file. The upstream server may be down.",
+                Some(RecoveryAction::Retry),
+            );
+        }
+
+        if any(
+            &lower,
+            &[
+                "could not resolve all dependencies",
+                "failed to install missing dependencies",
+                "missing dependencies:",
+            ],
+        ) {
+            return found(
+                ErrorKind::BuildDependencyMissing,
+                "Build Dependencies Missing",
+                "A package needed to build this one could not be installed. It may come from the AUR or a repository that is not enabled.",
+                Some(RecoveryAction::ForceRefreshDb),
+            );
+        }
+
+        // Keyring/PGP Error Detection
+        if any(
+            &lower,
+            &[
+                "gpgme error",
+                "pgp signature",
+                "invalid or corrupted package (pgp",
+                "key could not be looked up",
+                "unknown public key",
+                "signature from",
+                "trust database",
+                "keyring is not writable",
+            ],
+        ) {
+            return found(
+                ErrorKind::KeyringError,
+                "Security Key Issue",
+                "Package signatures could not be verified. Your keyring may need to be refreshed.",
+                Some(RecoveryAction::RepairKeyring),
+            );
+        }
+
+        // 32-bit packages live in [multilib]
+        if lower.contains("target not found: lib32-")
+            || (lower.contains("multilib") && any(&lower, &["not found", "not enabled"]))
+        {
+            return found(
+                ErrorKind::MultilibDisabled,
+                "Multilib Repository Needed",
+                "This needs 32-bit libraries from the [multilib] repository, which is not enabled.",
+                Some(RecoveryAction::EnableMultilib),
+            );
+        }
+
+        // Package Not Found
+        if any(
+            &lower,
+            &[
+                "target not found",
+                "no results found",
+                "package not found",
+                "not found in any enabled repository",
+            ],
+        ) {
+            return found(
+                ErrorKind::PackageNotFound,
+                "Package Not Found",
+                "The package could not be found. It may have been renamed, removed, or your repositories need syncing.",
+                Some(RecoveryAction::ForceRefreshDb),
+            );
+        }
+
+        // No network at all
+        if any(
+            &lower,
+            &[
+                "could not resolve host",
+                "temporary failure in name resolution",
+                "network is unreachable",
+                "no route to host",
+                "offline:",
+            ],
+        ) {
+            return found(
+                ErrorKind::NetworkUnavailable,
+                "No Connection",
+                "The network is unreachable. Check your connection and try again.",
+                Some(RecoveryAction::Retry),
+            );
+        }
+
+        // A package file the mirror no longer has: the local databases are out of date
+        if (lower.contains("failed retrieving file") && lower.contains(".pkg.tar"))
+            || lower.contains("systemupdaterequired")
+        {
+            return found(
+                ErrorKind::StaleDatabase,
+                "System Update Required",
+                "Your package databases are older than the mirrors. Update the system, then try again.",
+                Some(RecoveryAction::SystemUpdate),
+            );
+        }
+
+        // Free-space preflight: name the filesystems that are short
+        if let Some(details) = output
+            .lines()
+            .find_map(|l| l.split_once(&format!("{}: ", SHORTFALL_PREFIX)))
+            .map(|(_, details)| details.trim())
+        {
+            // A tmpfs build directory is RAM, which clearing the package cache won't free
+            let action = if details.split("; ").all(|d| d.ends_with("(tmpfs)")) {
+                RecoveryAction::ShowManualSteps(
+                    "Free space in the build directory, or set TMPDIR to a directory on disk before starting MonARCH.".to_string(),
+                )
+            } else {
+                RecoveryAction::CleanCache
+            };
+            return found(
+                ErrorKind::DiskFull,
+                "Not Enough Disk Space",
+                &format!(
+                    "Nothing was changed. {}. Free some space and try again.",
+                    details
+                ),
+                Some(action),
+            );
+        }
+
+        // Disk Full
+        if any(
+            &lower,
+            &[
+                "no space left on device",
+                "not enough free disk space",
+                "too full",
+            ],
+        ) {
+            return found(
+                ErrorKind::DiskFull,
+                "Disk Full",
+                "Not enough disk space to complete the operation. Try clearing the package cache.",
+                Some(RecoveryAction::CleanCache),
+            );
+        }
+
+        // Mirror/Network Issues
+        if any(
+            &lower,
+            &[
+                "failed retrieving file",
+                "failed to synchronize",
+                "connection timed out",
+                "operation too slow",
+                "error downloading",
+                "ssl certificate problem",
+                "404",
+            ],
+        ) {
+            return found(
+                ErrorKind::MirrorFailure,
+                "Download Failed",
+                "Could not download packages from mirrors. Check your internet connection or try refreshing your mirror list.",
+                Some(RecoveryAction::RefreshMirrors),
+            );
+        }
+
+        // Dependency Conflicts
+        if any(
+            &lower,
+            &[
+                "conflicting dependencies",
+                "breaks dependency",
+                "satisfies dependency",
+                "unresolvable package conflicts",
+                "could not satisfy dependencies",
+                "unable to satisfy dependency",
+            ],
+        ) {
+            return found(
+                ErrorKind::DependencyConflict,
+                "Dependency Conflict",
+                "Package dependencies conflict with installed packages. Manual intervention may be required.",
+                Some(RecoveryAction::ShowManualSteps(
+                    "Review the conflicting packages and decide which to keep. You may need to remove one before installing the other.".to_string(),
+                )),
+            );
+        }
+
+        // File Conflicts
+        if lower.contains("exists in filesystem") || lower.contains("file conflict") {
+            return found(
+                ErrorKind::FileConflict,
+                "File Conflict",
+                "A file already exists on your system that would be overwritten. This usually happens when files were installed outside of pacman.",
+                Some(RecoveryAction::ShowManualSteps(
+                    "You can either: 1) Remove the conflicting file manually, or 2) Use --overwrite flag (advanced users only).".to_string(),
+                )),
+            );
+        }
+
+        // Corrupted Package
+        if any(
+            &lower,
+            &[
+                "corrupted package",
+                "failed integrity",
+                "invalid or corrupted package",
+            ],
+        ) {
+            return found(
+                ErrorKind::CorruptedPackage,
+                "Corrupted Download",
+                "A downloaded package was corrupted. This usually resolves by retrying.",
+                Some(RecoveryAction::Retry),
+            );
+        }
+
+        // Permission Denied
+        if lower.contains("permission denied") || lower.contains("operation not permitted") {
+            return found(
+                ErrorKind::PermissionDenied,
+                "Permission Denied",
+                "The operation requires administrator privileges.",
+                Some(RecoveryAction::Retry),
+            );
+        }
+
+        // makepkg "An unknown error has occurred" — often toolchain (base-devel), permissions, or stale build dir
+        if lower.contains("unknown error has occurred") {
+            return found(
+                ErrorKind::MakepkgBuildFailure,
+                "AUR Build Failed (Unknown Error)",
+                "makepkg reported an unknown error. Common causes: missing base-devel or git, wrong permissions in /tmp or cache, or a previous build run as root. Run the Permission Sanitizer script and ensure base-devel and git are installed.",
+                Some(RecoveryAction::ShowManualSteps(
+                    "Run: scripts/monarch-permission-sanitizer.sh (or ensure base-devel and git are installed; fix ownership of /tmp/monarch-install and ~/.cache/monarch-store).".to_string(),
+                )),
+            );
+        }
+
+        found(ErrorKind::Unknown, "Operation Failed", output.trim(), None)
+    }
+
+    /// Check if this error is recoverable automatically
+    pub fn is_auto_recoverable(&self) -> bool {
+        matches!(
+            self.kind,
+            ErrorKind::DatabaseLocked | ErrorKind::KeyringError | ErrorKind::CorruptedPackage
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kind(output: &str) -> ErrorKind {
+        ClassifiedError::classify(output).kind
+    }
+
+    #[test]
+    fn test_database_lock_detection() {
+        let output = "error: failed to init transaction (unable to lock database)";
+        let err = ClassifiedError::from_output(output).unwrap();
+        assert_eq!(err.kind, ErrorKind::DatabaseLocked);
+        assert_eq!(err.recovery_action, Some(RecoveryAction::UnlockDatabase));
+    }
+
+    #[test]
+    fn test_keyring_error_detection() {
+        let output = "error: package: signature from \"Developer\" is invalid";
+        assert_eq!(kind(output), ErrorKind::KeyringError);
+    }
+
+    #[test]
+    fn test_package_not_found_detection() {
+        assert_eq!(
+            kind("error: target not found: nonexistent-package"),
+            ErrorKind::PackageNotFound
+        );
+        assert_eq!(
+            kind("error: target not found: lib32-mesa"),
+            ErrorKind::MultilibDisabled
+        );
+    }
+
+    #[test]
+    fn test_mirror_failure_detection() {
+        let output = "error: failed retrieving file 'extra.db' from mirror.example.com : The requested URL returned error: 404";
+        assert_eq!(kind(output), ErrorKind::MirrorFailure);
+        let stale = "error: failed retrieving file 'firefox-131.0-1-x86_64.pkg.tar.zst' from mirror.example.com : The requested URL returned error: 404";
+        assert_eq!(kind(stale), ErrorKind::StaleDatabase);
+        assert_eq!(
+            kind("error: failed retrieving file 'core.db' from mirror.example.com : Could not resolve host: mirror.example.com"),
+            ErrorKind::NetworkUnavailable
+        );
+    }
+
+    #[test]
+    fn test_makepkg_error_detection() {
+        let pgp = "==> Verifying source file signatures with gpg...\n    foo-1.0.tar.gz ... FAILED (unknown public key 3B94A80E50A477C7)\n==> ERROR: One or more PGP signatures could not be verified!";
+        let err = ClassifiedError::classify(pgp);
+        assert_eq!(err.kind, ErrorKind::SourcePgpKey);
+        assert_eq!(
+            err.recovery_action,
+            Some(RecoveryAction::ImportPgpKey("3B94A80E50A477C7".to_string()))
+        );
+        assert_eq!(
+            kind("==> ERROR: One or more files did not pass the validity check!"),
+            ErrorKind::ChecksumMismatch
+        );
+        assert_eq!(
+            kind("==> ERROR: Cannot find the fakeroot binary."),
+            ErrorKind::MissingBuildTools
+        );
+        assert_eq!(
+            kind("==> ERROR: 'pacman' failed to install missing dependencies."),
+            ErrorKind::BuildDependencyMissing
+        );
+    }
+
+    #[test]
+    fn test_unknown_errors_keep_the_message() {
+        let err = ClassifiedError::from("Helper exited unexpectedly".to_string());
+        assert_eq!(err.kind, ErrorKind::Unknown);
+        assert_eq!(err.to_string(), "Helper exited unexpectedly");
+        assert!(ClassifiedError::from_output("all good").is_none());
+
+        let json = serde_json::to_value(ClassifiedError::classify(
+            "error: failed to commit transaction (not enough free disk space)",
+        ))
+        .unwrap();
+        assert_eq!(json["kind"], "DiskFull");
+        assert_eq!(json["recovery_action"]["type"], "CleanCache");
+    }
+
+    #[test]
+    fn test_space_preflight_names_the_filesystems() {
+        use crate::disk_space::{shortfall_message, SpaceCheck};
+
+        let gib = 1024 * 1024 * 1024;
+        let root = SpaceCheck {
+            path: "/".to_string(),
+            required: 3 * gib,
+            available: 2 * gib,
+            tmpfs: false,
+        };
+        let tmp = SpaceCheck {
+            path: "/tmp".to_string(),
+            required: gib,
+            available: 512 * 1024 * 1024,
+            tmpfs: true,
+        };
+        let roomy = SpaceCheck {
+            path: "/var/cache/pacman/pkg".to_string(),
+            required: gib,
+            available: 10 * gib,
+            tmpfs: false,
+        };
+        assert_eq!(shortfall_message(std::slice::from_ref(&roomy)), None);
+
+        let msg = shortfall_message(&[root, roomy, tmp.clone()]).unwrap();
+        assert_eq!(
+            msg,
+            "Not enough free disk space: / needs 3.0 GiB, 2.0 GiB free; /tmp needs 1.0 GiB, 512.0 MiB free (tmpfs)"
+        );
+        let err = ClassifiedError::classify(&msg);
+        assert_eq!(err.kind, ErrorKind::DiskFull);
+        assert!(err.description.contains("/tmp needs"));
+        assert_eq!(err.recovery_action, Some(RecoveryAction::CleanCache));
+
+        let build = ClassifiedError::classify(&shortfall_message(&[tmp]).unwrap());
+        assert!(matches!(
+            build.recovery_action,
+            Some(RecoveryAction::ShowManualSteps(_))
+        ));
+    }
+}
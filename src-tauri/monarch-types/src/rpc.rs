@@ -0,0 +1,214 @@
+//! JSON-RPC 2.0 between the GUI and `monarch-helper --rpc`.
+//!
+//! The GUI creates a socketpair and starts the helper through pkexec (or `sudo -S`) with its
+//! end as stdin and stdout, the only descriptors pkexec passes on. Each message is one JSON
+//! object on one line. Once running as root the helper sends a [`READY_EVENT`] notification
+//! with id 0; the GUI waits for it before its first request, so `sudo -S` reading the
+//! password off the same socket cannot swallow a request. The GUI then sends
+//! [`RpcRequest`]s (ids start at 1); the helper answers every request with
+//! an [`RpcResponse`] carrying the same id, and while a `run` request is executing it sends
+//! `event` [`RpcNotification`]s tagged with that id. Events are the same
+//! [`AlpmProgressEvent`]s a one-shot helper prints, `transaction_result` included.
+
+use crate::{AlpmProgressEvent, HelperCapabilities, HelperCommand, TransactionResult};
+use serde::{Deserialize, Serialize};
+
+pub const JSONRPC_VERSION: &str = "2.0";
+
+/// Method of the notifications that carry helper events.
+pub const EVENT_METHOD: &str = "event";
+
+/// `event_type` of the notification a freshly started helper sends before any request.
+pub const READY_EVENT: &str = "rpc_ready";
+
+/// `code` of an [`RpcError`]. The negative 32xxx range is JSON-RPC's own.
+pub mod error_code {
+    pub const PARSE_ERROR: i32 = -32700;
+    pub const INVALID_REQUEST: i32 = -32600;
+    /// The request was cancelled before it started.
+    pub const CANCELLED: i32 = -32000;
+    /// The session ended before the request ran.
+    pub const ABORTED: i32 = -32001;
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+pub enum RpcCall {
+    /// Run a command; answered with its [`TransactionResult`] once it finished.
+    Run(HelperCommand),
+    /// Cancel request `id`; answered with whether it was still pending or running.
+    /// Cancelling the running command ends the session, as the cancel file does.
+    Cancel { id: u64 },
+    /// Answered with the helper's [`HelperCapabilities`].
+    Capabilities,
+    /// Finish the current command, then exit. Closing the socket does the same.
+    Shutdown,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RpcRequest {
+    pub jsonrpc: String,
+    pub id: u64,
+    #[serde(flatten)]
+    pub call: RpcCall,
+}
+
+impl RpcRequest {
+    pub fn new(id: u64, call: RpcCall) -> Self {
+        RpcRequest {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id,
+            call,
+        }
+    }
+}
+
+/// `result` of a successful [`RpcResponse`]; which one depends on the request's method.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum RpcResult {
+    Capabilities(HelperCapabilities),
+    Transaction(TransactionResult),
+    Acknowledged(bool),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RpcError {
+    pub code: i32,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RpcResponse {
+    pub jsonrpc: String,
+    /// Id of the request answered; 0 when the request could not be parsed.
+    pub id: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<RpcResult>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcError>,
+}
+
+impl RpcResponse {
+    pub fn ok(id: u64, result: RpcResult) -> Self {
+        RpcResponse {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id,
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    pub fn err(id: u64, code: i32, message: &str) -> Self {
+        RpcResponse {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id,
+            result: None,
+            error: Some(RpcError {
+                code,
+                message: message.to_string(),
+            }),
+        }
+    }
+}
+
+/// Params of an `event` notification: one helper event of request `id`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RpcEvent {
+    pub id: u64,
+    pub event: AlpmProgressEvent,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RpcNotification {
+    pub jsonrpc: String,
+    pub method: String,
+    pub params: RpcEvent,
+}
+
+impl RpcNotification {
+    pub fn event(id: u64, event: AlpmProgressEvent) -> Self {
+        RpcNotification {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            method: EVENT_METHOD.to_string(),
+            params: RpcEvent { id, event },
+        }
+    }
+}
+
+/// Any line the helper writes on the socket.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum RpcMessage {
+    Response(RpcResponse),
+    Notification(RpcNotification),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exit_code;
+
+    #[test]
+    fn test_rpc_wire_format() {
+        let run = RpcRequest::new(
+            7,
+            RpcCall::Run(HelperCommand::AlpmSync {
+                enabled_repos: vec![],
+            }),
+        );
+        let json = serde_json::to_string(&run).unwrap();
+        assert_eq!(
+            json,
+            r#"{"jsonrpc":"2.0","id":7,"method":"run","params":{"command":"AlpmSync","payload":{"enabled_repos":[]}}}"#
+        );
+        assert_eq!(serde_json::from_str::<RpcRequest>(&json).unwrap(), run);
+
+        let cancel: RpcRequest =
+            serde_json::from_str(r#"{"jsonrpc":"2.0","id":8,"method":"cancel","params":{"id":7}}"#)
+                .unwrap();
+        assert_eq!(cancel.call, RpcCall::Cancel { id: 7 });
+        let caps: RpcRequest =
+            serde_json::from_str(r#"{"jsonrpc":"2.0","id":9,"method":"capabilities"}"#).unwrap();
+        assert_eq!(caps.call, RpcCall::Capabilities);
+
+        let done = RpcResponse::ok(
+            7,
+            RpcResult::Transaction(TransactionResult {
+                success: true,
+                exit_code: exit_code::SUCCESS,
+                ..Default::default()
+            }),
+        );
+        let cancelled = RpcResponse::err(7, error_code::CANCELLED, "Cancelled by user");
+        let acked = RpcResponse::ok(8, RpcResult::Acknowledged(true));
+        let capabilities = RpcResponse::ok(
+            9,
+            RpcResult::Capabilities(HelperCapabilities::current("0.4.0")),
+        );
+        let event = RpcNotification::event(
+            7,
+            AlpmProgressEvent {
+                event_type: "progress".to_string(),
+                package: None,
+                percent: Some(10),
+                downloaded: None,
+                total: None,
+                speed_bps: None,
+                eta_seconds: None,
+                message: "Syncing".to_string(),
+            },
+        );
+        for message in [
+            RpcMessage::Response(done),
+            RpcMessage::Response(cancelled),
+            RpcMessage::Response(acked),
+            RpcMessage::Response(capabilities),
+            RpcMessage::Notification(event),
+        ] {
+            let line = serde_json::to_string(&message).unwrap();
+            assert!(!line.contains('\n'));
+            assert_eq!(serde_json::from_str::<RpcMessage>(&line).unwrap(), message);
+        }
+    }
+}
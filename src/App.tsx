@@ -5,8 +5,8 @@ import { useFavorites } from './hooks/useFavorites';
 import Sidebar from './components/Sidebar';
 import SearchBar from './components/SearchBar';
 import InstallMonitor from './components/InstallMonitor';
-import { Package } from './components/PackageCard';
-import { PackageSource } from './types/alpm';
+import { Package, SearchResponse } from './components/PackageCard';
+import { AurBuildMode, PackageSource, SearchFilters } from './types/alpm';
 import TrendingSection from './components/TrendingSection';
 import HeroSection from './components/HeroSection';
 import PackageDetails from './pages/PackageDetailsFresh';
@@ -15,14 +15,20 @@ import CategoryView from './pages/CategoryView';
 import InstalledPage from './pages/InstalledPage';
 import UpdatesPage from './pages/UpdatesPage';
 import SettingsPage from './pages/SettingsPage';
+import DigestPage from './pages/DigestPage';
 import { useTheme } from './hooks/useTheme';
 import './App.css';
 import LoadingScreen from './components/LoadingScreen';
 import OnboardingModal from './components/OnboardingModal';
 import ErrorModal from './components/ErrorModal';
+import ServicesPromptModal from './components/ServicesPromptModal';
+import LicensePromptModal from './components/LicensePromptModal';
+import ResumeBatchModal from './components/ResumeBatchModal';
+import PostTransactionNotesModal from './components/PostTransactionNotesModal';
 import ConfirmationModal from './components/ConfirmationModal';
 import SearchPage from './pages/SearchPage';
 import { useSearchHistory } from './hooks/useSearchHistory';
+import { useLocalPackageDrop } from './hooks/useLocalPackageDrop';
 import HomePage from './pages/HomePage';
 import { ESSENTIALS_POOL } from './constants';
 import { listen } from '@tauri-apps/api/event';
@@ -31,6 +37,13 @@ import { useToast } from './context/ToastContext';
 import { useSessionPassword } from './context/useSessionPassword';
 import { useErrorService } from './context/ErrorContext';
 import TitleBar from './components/TitleBar';
+import { usePackagesChanged } from './hooks/usePackagesChanged';
+
+/** Payload of the `open-package` event (see dbus_service.rs). */
+interface PackageLink {
+  package: Package;
+  install: boolean;
+}
 
 const MOBILE_TABS = [
   { id: 'search', icon: Search, label: 'Search' },
@@ -87,11 +100,14 @@ function MobileNav({
 
 function App() {
   const [activeTab, setActiveTab] = useState('explore');
-  const [activeInstall, setActiveInstall] = useState<{ name: string; source: PackageSource; repoName?: string; mode: 'install' | 'uninstall' } | null>(null);
+  const [activeInstall, setActiveInstall] = useState<{ name: string; source: PackageSource; repoName?: string; buildMode?: AurBuildMode; mode: 'install' | 'uninstall' } | null>(null);
   const [viewAll, setViewAll] = useState<'essentials' | 'trending' | null>(null);
   const [showOnboarding, setShowOnboarding] = useState(false);
   const [searchQuery, setSearchQuery] = useState('');
+  const [searchSuggestion, setSearchSuggestion] = useState<string | null>(null);
+  const [searchFilters, setSearchFilters] = useState<SearchFilters>({});
   const [packages, setPackages] = useState<Package[]>([]);
+  usePackagesChanged(setPackages);
   const [selectedPackage, setSelectedPackage] = useState<Package | null>(null);
   const [preferredSource, setPreferredSource] = useState<string | undefined>(undefined);
   const [onboardingReason, setOnboardingReason] = useState<string | undefined>(undefined);
@@ -104,6 +120,19 @@ function App() {
   const [systemHealth, setSystemHealth] = useState<{ is_healthy: boolean, reasons: string[] } | null>(null);
   const scrollContainerRef = useRef<HTMLDivElement>(null);
   const searchRequestIdRef = useRef(0);
+  // Repo index generation of the results on screen; a newer one after a sync triggers a re-run
+  const resultsGenerationRef = useRef<number | null>(null);
+  const lastRecordedQueryRef = useRef<string | null>(null);
+  const [indexGeneration, setIndexGeneration] = useState(0);
+
+  useEffect(() => {
+    const unlisten = listen<number>('repo-index-updated', (event) => {
+      if (resultsGenerationRef.current !== null && event.payload !== resultsGenerationRef.current) {
+        setIndexGeneration(event.payload);
+      }
+    });
+    return () => { unlisten.then((fn) => fn()).catch(() => { }); };
+  }, []);
   const updateTimerRef = useRef<number | null>(null);
 
   const { addSearch } = useSearchHistory();
@@ -124,6 +153,21 @@ function App() {
   const errorService = useErrorService();
   const reducePasswordPrompts = useAppStore((s) => s.reducePasswordPrompts);
 
+  // Drag a downloaded .pkg.tar.zst onto the window to install it
+  const localDrop = useLocalPackageDrop((e) => showToast(e, 'error'));
+  const installDroppedPackage = async () => {
+    const pkg = localDrop.preview;
+    localDrop.clear();
+    if (!pkg) return;
+    try {
+      const pwd = await requestSessionPassword();
+      await invoke('install_local_file', { path: pkg.path, password: pwd });
+      showToast(`${pkg.name} ${pkg.version} installed.`, 'success');
+    } catch (e) {
+      errorService.reportError(e as Error | string);
+    }
+  };
+
   const [enabledRepos, setEnabledRepos] = useState<{ name: string; enabled: boolean; source: string }[]>([]);
 
   // Polkit rule pre-check at startup: flag immediately if missing so user isn't surprised at first install
@@ -143,6 +187,36 @@ function App() {
       .catch((e) => errorService.reportWarning(e as Error | string));
   }, [isRefreshing, showToast, errorService]);
 
+  // Tray "Weekly digest" (and the digest notification, which points there)
+  useEffect(() => {
+    const unlisten = listen<string>('tray-action', (event) => {
+      if (event.payload === 'open-digest') {
+        setSelectedPackage(null);
+        setSearchQuery('');
+        setActiveTab('digest');
+      }
+    });
+    return () => { unlisten.then((fn) => fn()).catch(() => { }); };
+  }, []);
+
+  // monarch:// and appstream:// links, and InstallPackage over DBus
+  useEffect(() => {
+    const openLink = (link: PackageLink | null) => {
+      if (!link) return;
+      setSearchQuery('');
+      setSelectedCategory(null);
+      setSelectedPackage(link.package);
+      if (link.install) {
+        showToast(`Another app asked to install ${link.package.name}. Review it and click Install to continue.`, 'info');
+      }
+    };
+    invoke<PackageLink | null>('take_launch_link')
+      .then(openLink)
+      .catch((e) => errorService.reportWarning(e as Error | string));
+    const unlisten = listen<PackageLink>('open-package', (event) => openLink(event.payload));
+    return () => { unlisten.then((fn) => fn()).catch(() => { }); };
+  }, [showToast, errorService]);
+
   // Global Update Listeners
   useEffect(() => {
     const unlistenProgress = listen<UpdateProgress>('update-progress', (event) => {
@@ -358,14 +432,31 @@ function App() {
     const search = async () => {
       if (!searchQuery) {
         setPackages([]);
+        setSearchSuggestion(null);
+        lastRecordedQueryRef.current = null;
         return;
       }
       setLoading(true);
       try {
-        const results = await invoke<Package[]>('search_packages', { query: searchQuery });
+        // Instant phase: local repo index; AUR/Flathub results replace these below
+        let fullResultsShown = false;
+        invoke<SearchResponse>('search_index_query', { query: searchQuery, filters: searchFilters })
+          .then(({ packages: instant }) => {
+            if (currentRequestId === searchRequestIdRef.current && !fullResultsShown && instant.length > 0) {
+              setPackages(instant);
+            }
+          })
+          .catch(() => { });
+        const { packages: results, index_generation, did_you_mean } = await invoke<SearchResponse>('search_packages', { query: searchQuery, filters: searchFilters });
         // Only update if this is still the latest request (prevents race conditions)
         if (currentRequestId !== searchRequestIdRef.current) return;
+        fullResultsShown = true;
         setPackages(results);
+        setSearchSuggestion(did_you_mean);
+        resultsGenerationRef.current = index_generation;
+        // A refresh after a sync re-runs the same query; record and track it once
+        if (lastRecordedQueryRef.current === searchQuery) return;
+        lastRecordedQueryRef.current = searchQuery;
         addSearch(searchQuery);
         invoke('track_event', {
           event: 'search',
@@ -388,7 +479,7 @@ function App() {
 
     const timeoutId = setTimeout(() => search(), 300);
     return () => clearTimeout(timeoutId);
-  }, [searchQuery, addSearch, errorService]);
+  }, [searchQuery, searchFilters, indexGeneration, addSearch, errorService]);
 
   const handleTabChange = (tab: string) => {
     if (tab === 'search') {
@@ -533,12 +624,12 @@ function App() {
             preferredSource={preferredSource}
             installInProgress={activeInstall !== null}
             activeInstallPackage={activeInstall}
-            onInstall={(p: { name: string; source: PackageSource | string; repoName?: string }) => {
+            onInstall={(p: { name: string; source: PackageSource | string; repoName?: string; buildMode?: AurBuildMode }) => {
               // Normalize string source to struct if needed (legacy fallback)
               const srcArgs = typeof p.source === 'string'
                 ? { source_type: 'repo', id: p.source, version: '', label: p.source.toUpperCase() } as PackageSource
                 : p.source;
-              setActiveInstall({ name: p.name, source: srcArgs, repoName: p.repoName, mode: 'install' });
+              setActiveInstall({ name: p.name, source: srcArgs, repoName: p.repoName, buildMode: p.buildMode, mode: 'install' });
             }}
             onUninstall={(p: { name: string; source: PackageSource | string; repoName?: string }) => {
               const srcArgs = typeof p.source === 'string'
@@ -581,6 +672,9 @@ function App() {
                       query={searchQuery}
                       onQueryChange={setSearchQuery}
                       packages={packages}
+                      suggestion={searchSuggestion}
+                      filters={searchFilters}
+                      onFiltersChange={setSearchFilters}
                       loading={loading}
                       onSelectPackage={setSelectedPackage}
                       enabledRepos={enabledRepos}
@@ -607,6 +701,8 @@ function App() {
                     </div>
                   ) : activeTab === 'updates' ? (
                     <UpdatesPage />
+                  ) : activeTab === 'digest' ? (
+                    <DigestPage />
                   ) : activeTab === 'settings' ? (
                     <SettingsPage
                       onRestartOnboarding={() => setShowOnboarding(true)}
@@ -656,6 +752,35 @@ function App() {
           }}
         />
       )}
+      {localDrop.preview && (
+        <ConfirmationModal
+          isOpen={!!localDrop.preview}
+          onClose={localDrop.clear}
+          onConfirm={installDroppedPackage}
+          title={`Install ${localDrop.preview.name} ${localDrop.preview.version}?`}
+          message={[
+            localDrop.preview.description ?? '',
+            localDrop.preview.installed_version
+              ? `Replaces installed version ${localDrop.preview.installed_version}.`
+              : '',
+            localDrop.preview.depends.length > 0
+              ? `Dependencies: ${localDrop.preview.depends.join(', ')}`
+              : '',
+            'This file does not come from a repository; only install packages you trust.',
+          ].filter(Boolean).join(' ')}
+          confirmLabel="Install"
+          variant="danger"
+        />
+      )}
+      {localDrop.dragging && (
+        <div className="fixed inset-0 z-50 pointer-events-none border-4 border-dashed border-accent/60 bg-app-bg/60 flex items-center justify-center">
+          <p className="text-lg font-bold">Drop a package file to install it</p>
+        </div>
+      )}
+      <ServicesPromptModal />
+      <LicensePromptModal />
+      <ResumeBatchModal />
+      <PostTransactionNotesModal />
       <ErrorModal />
     </div>
   );
@@ -0,0 +1,32 @@
+import { useEffect, useState, ImgHTMLAttributes } from 'react';
+import { invoke, convertFileSrc } from '@tauri-apps/api/core';
+import { CachedScreenshot as CachedScreenshotResult } from '../types/alpm';
+
+interface CachedScreenshotProps extends Omit<ImgHTMLAttributes<HTMLImageElement>, 'src'> {
+    url: string;
+    /** Load the downscaled copy (gallery) instead of the full image (lightbox). */
+    thumbnail?: boolean;
+}
+
+/** Screenshot served from the local cache; falls back to the remote URL if caching fails. */
+export default function CachedScreenshot({ url, thumbnail = false, ...imgProps }: CachedScreenshotProps) {
+    const [src, setSrc] = useState<string | null>(null);
+
+    useEffect(() => {
+        let cancelled = false;
+        setSrc(null);
+        invoke<CachedScreenshotResult>('get_screenshot', { url, thumbnail })
+            .then(res => {
+                if (!cancelled) setSrc(res.data_uri ?? convertFileSrc(res.path));
+            })
+            .catch(() => {
+                if (!cancelled) setSrc(url);
+            });
+        return () => { cancelled = true; };
+    }, [url, thumbnail]);
+
+    if (!src) {
+        return <div className={`${imgProps.className ?? ''} animate-pulse bg-white/5`} />;
+    }
+    return <img src={src} {...imgProps} />;
+}
@@ -4,8 +4,13 @@ import { motion } from 'framer-motion';
 interface CategoryGridProps {
     onSelectCategory: (category: string) => void;
     selectedCategoryId?: string | null;
+    /** From get_collections; keyed by lowercase AppStream category. */
+    counts?: Record<string, number>;
 }
 
+// Tile ids whose AppStream category key differs from the lowercased id
+const COUNT_KEYS: Record<string, string> = { Utilities: 'utility' };
+
 export interface CategoryData {
     id: string;
     label: string;
@@ -100,7 +105,7 @@ export const CATEGORIES: CategoryData[] = [
     },
 ];
 
-export default function CategoryGrid({ onSelectCategory, selectedCategoryId }: CategoryGridProps) {
+export default function CategoryGrid({ onSelectCategory, selectedCategoryId, counts }: CategoryGridProps) {
     const container = {
         hidden: { opacity: 0 },
         show: {
@@ -147,6 +152,11 @@ export default function CategoryGrid({ onSelectCategory, selectedCategoryId }: C
                                     <h3 className={`text-2xl font-bold text-slate-800 dark:text-white group-hover:${cat.color} transition-colors tracking-tight`}>
                                         {cat.label}
                                     </h3>
+                                    {counts?.[COUNT_KEYS[cat.id] ?? cat.id.toLowerCase()] ? (
+                                        <span className="text-xs font-bold text-slate-500 dark:text-white/40">
+                                            {counts[COUNT_KEYS[cat.id] ?? cat.id.toLowerCase()]} apps
+                                        </span>
+                                    ) : null}
                                 </div>
                                 <p className="text-slate-600 dark:text-indigo-100/60 text-sm leading-relaxed mb-4 font-medium max-w-[80%]">
                                     {cat.description}
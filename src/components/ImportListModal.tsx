@@ -0,0 +1,157 @@
+import { useState } from 'react';
+import { motion, AnimatePresence } from 'framer-motion';
+import { clsx } from 'clsx';
+import { ClipboardList, Loader2 } from 'lucide-react';
+import { invoke } from '@tauri-apps/api/core';
+import { useEscapeKey } from '../hooks/useEscapeKey';
+import { useFocusTrap } from '../hooks/useFocusTrap';
+import { useToast } from '../context/ToastContext';
+import { useErrorService } from '../context/ErrorContext';
+import { useSessionPassword } from '../context/useSessionPassword';
+import { useEula } from '../context/useEula';
+import { useAppStore } from '../store/internal_store';
+import { ImportPlan, ImportSource } from '../types/alpm';
+
+const SOURCE_LABELS: Record<ImportSource, { label: string; className: string }> = {
+    official: { label: 'Official', className: 'bg-blue-500/10 text-blue-500' },
+    chaotic: { label: 'Chaotic-AUR', className: 'bg-purple-500/10 text-purple-500' },
+    aur: { label: 'AUR build', className: 'bg-amber-500/10 text-amber-500' },
+    not_found: { label: 'Not found', className: 'bg-red-500/10 text-red-500' },
+    lookup_failed: { label: 'AUR lookup failed', className: 'bg-red-500/10 text-red-500' },
+};
+
+/** Paste a `pacman -Qqe` list (or a file path), review where each package comes from, install. */
+export default function ImportListModal({ isOpen, onClose }: { isOpen: boolean; onClose: () => void }) {
+    const [input, setInput] = useState('');
+    const [plan, setPlan] = useState<ImportPlan | null>(null);
+    const [busy, setBusy] = useState(false);
+    const { success, show } = useToast();
+    const errorService = useErrorService();
+    const { requestSessionPassword } = useSessionPassword();
+    const { confirmLicenses } = useEula();
+    const reducePasswordPrompts = useAppStore((s) => s.reducePasswordPrompts);
+    useEscapeKey(onClose, isOpen && !busy);
+    const focusTrapRef = useFocusTrap(isOpen);
+
+    if (!isOpen) return null;
+
+    const review = async () => {
+        setBusy(true);
+        try {
+            setPlan(await invoke<ImportPlan>('import_package_list', { textOrPath: input }));
+        } catch (e) {
+            errorService.reportError(e as Error | string);
+        } finally {
+            setBusy(false);
+        }
+    };
+
+    const install = async () => {
+        if (!plan) return;
+        setBusy(true);
+        try {
+            const repoTargets = await confirmLicenses(plan.repo_targets);
+            const aurTargets = await confirmLicenses(plan.aur_targets);
+            const declined = [...plan.repo_targets, ...plan.aur_targets]
+                .filter((name) => !repoTargets.includes(name) && !aurTargets.includes(name));
+            const pwd = reducePasswordPrompts ? await requestSessionPassword() : null;
+            const failed = repoTargets.length + aurTargets.length === 0 ? [] : await invoke<string[]>('execute_import_plan', {
+                repoTargets,
+                aurTargets,
+                password: pwd,
+            });
+            failed.push(...declined);
+            if (failed.length === 0) {
+                success(`Imported ${plan.repo_targets.length + plan.aur_targets.length} packages.`);
+            } else {
+                show(`Imported with problems. Not installed: ${failed.join(', ')}`, 'warning');
+            }
+            setPlan(null);
+            setInput('');
+            onClose();
+        } catch (e) {
+            errorService.reportError(e as Error | string);
+        } finally {
+            setBusy(false);
+        }
+    };
+
+    const targetCount = plan ? plan.repo_targets.length + plan.aur_targets.length : 0;
+
+    return (
+        <AnimatePresence>
+            <div className="fixed inset-0 z-50 flex items-center justify-center bg-black/60 backdrop-blur-sm">
+                <motion.div
+                    ref={focusTrapRef}
+                    initial={{ opacity: 0, scale: 0.9 }}
+                    animate={{ opacity: 1, scale: 1 }}
+                    exit={{ opacity: 0, scale: 0.9 }}
+                    className="w-full max-w-2xl max-h-[85vh] flex flex-col bg-app-card border border-app-border rounded-2xl shadow-2xl p-6 gap-4"
+                    role="dialog"
+                    aria-modal="true"
+                    aria-labelledby="import-list-title"
+                >
+                    <h3 id="import-list-title" className="text-xl font-bold text-app-fg flex items-center gap-2">
+                        <ClipboardList size={22} className="text-blue-500" />
+                        Import Package List
+                    </h3>
+
+                    {!plan ? (
+                        <>
+                            <p className="text-app-muted text-sm leading-relaxed">
+                                Paste the output of <code>pacman -Qqe</code> from another machine, or the path of a file that holds it. You can review everything before it is installed.
+                            </p>
+                            <textarea
+                                value={input}
+                                onChange={(e) => setInput(e.target.value)}
+                                placeholder={'firefox\nvlc\n...'}
+                                rows={10}
+                                className="w-full p-3 bg-black/5 dark:bg-black/20 border border-app-border rounded-xl text-app-fg font-mono text-sm focus:outline-none focus:ring-2 focus:ring-blue-500/50"
+                            />
+                        </>
+                    ) : (
+                        <>
+                            <p className="text-app-muted text-sm">
+                                {plan.repo_targets.length} from repositories, {plan.aur_targets.length} AUR builds, {plan.entries.filter(e => e.installed).length} already installed, {plan.not_found.length} not found{plan.lookup_failed.length > 0 && `, ${plan.lookup_failed.length} could not be looked up in the AUR (import again to retry)`}.
+                            </p>
+                            <div className="flex-1 overflow-y-auto custom-scrollbar space-y-1 min-h-0">
+                                {plan.entries.map((entry) => (
+                                    <div key={entry.name} className={clsx("flex items-center justify-between gap-3 px-3 py-1.5 rounded-lg text-sm", entry.installed && "opacity-50")}>
+                                        <span className="font-medium text-app-fg truncate">
+                                            {entry.name}
+                                            {entry.version && <span className="text-app-muted font-normal ml-2">{entry.version}</span>}
+                                        </span>
+                                        <span className={clsx("text-xs font-bold px-2 py-0.5 rounded-md shrink-0", SOURCE_LABELS[entry.source].className)}>
+                                            {entry.installed ? 'Installed' : entry.source === 'official' && entry.repo ? entry.repo : SOURCE_LABELS[entry.source].label}
+                                        </span>
+                                    </div>
+                                ))}
+                                {plan.invalid_lines.map((line) => (
+                                    <div key={line} className="px-3 py-1.5 text-sm text-app-muted line-through truncate">{line}</div>
+                                ))}
+                            </div>
+                        </>
+                    )}
+
+                    <div className="flex gap-3 w-full">
+                        <button
+                            onClick={plan ? () => setPlan(null) : onClose}
+                            disabled={busy}
+                            className="flex-1 py-2.5 rounded-xl border border-app-border text-app-fg hover:bg-app-subtle font-medium transition-colors disabled:opacity-50"
+                        >
+                            {plan ? 'Back' : 'Cancel'}
+                        </button>
+                        <button
+                            onClick={plan ? install : review}
+                            disabled={busy || (plan ? targetCount === 0 : !input.trim())}
+                            className="flex-1 py-2.5 rounded-xl text-white font-bold shadow-lg transition-all active:scale-95 btn-accent hover:opacity-90 disabled:opacity-50 flex items-center justify-center gap-2"
+                        >
+                            {busy && <Loader2 size={16} className="animate-spin" />}
+                            {plan ? `Install ${targetCount} packages` : 'Review'}
+                        </button>
+                    </div>
+                </motion.div>
+            </div>
+        </AnimatePresence>
+    );
+}
@@ -8,30 +8,32 @@ import { useEscapeKey } from '../hooks/useEscapeKey';
 import { useFocusTrap } from '../hooks/useFocusTrap';
 import { useAppStore } from '../store/internal_store';
 import { useSessionPassword } from '../context/useSessionPassword';
+import { useEula } from '../context/useEula';
 import { useErrorService } from '../context/ErrorContext';
 import { useToast } from '../context/ToastContext';
 
-import { PackageSource } from '../types/alpm';
+import { AurBuildMode, ConnectivityState, PackageSource } from '../types/alpm';
 
 interface InstallMonitorProps {
-    pkg: { name: string; source: PackageSource; repoName?: string; } | null;
+    pkg: { name: string; source: PackageSource; repoName?: string; buildMode?: AurBuildMode; } | null;
     onClose: () => void;
     mode?: 'install' | 'uninstall';
     onSuccess?: () => void;
 }
 
-// Matches the Rust AlpmClassifiedError (helper) and GUI error_classifier
+// Matches the Rust monarch_types::errors::ClassifiedError (helper and GUI)
 interface ClassifiedError {
     kind: string;
     title: string;
     description: string;
-    /** Helper sends string (e.g. "UnlockDatabase"); GUI classifier may send object */
+    /** `{ type, payload }`; older helpers send the action name as a string */
     recovery_action?: string | { type: string; payload?: string };
     raw_message: string;
 }
 
 export default function InstallMonitor({ pkg, onClose, mode = 'install', onSuccess }: InstallMonitorProps) {
     const { requestSessionPassword } = useSessionPassword();
+    const { confirmLicenses } = useEula();
     const errorService = useErrorService();
     const reducePasswordPrompts = useAppStore((s) => s.reducePasswordPrompts);
     const { show: showToast } = useToast();
@@ -180,6 +182,11 @@ export default function InstallMonitor({ pkg, onClose, mode = 'install', onSucce
                 case 'install_complete':
                     setProgressStatusThrottled(99, `Installed ${evt.package || 'package'}`);
                     break;
+                case 'mirror_switch':
+                    // The helper re-ranked a failing mirror and retries on the new one
+                    showToast(evt.message, 'info');
+                    setProgressStatusThrottled(progressStatusRef.current.target, evt.message);
+                    break;
                 case 'progress':
                     if (evt.percent !== undefined) {
                         setProgressStatusThrottled(evt.percent, evt.message);
@@ -299,7 +306,18 @@ export default function InstallMonitor({ pkg, onClose, mode = 'install', onSucce
     }, [pkg, reducePasswordPrompts, requestSessionPassword]);
 
     // Recovery action handlers
-    const handleRecoveryAction = async (action: string) => {
+    const handleRecoveryAction = async (action: string, payload?: string) => {
+        if (action === 'SystemUpdate') {
+            // The "Update & Install" path below already does the full upgrade first
+            setClassifiedError(null);
+            setUpdateRequired(true);
+            return;
+        }
+        if (action === 'ShowManualSteps') {
+            // Nothing to run for the user; the retry is theirs once the steps are done
+            setLogs(prev => [...prev, '\n--- MANUAL STEPS ---', payload ?? 'See the error details above.']);
+            return;
+        }
         setIsRecovering(true);
         setLogs(prev => [...prev, `\n--- RECOVERY: ${action.toUpperCase()} ---`]);
 
@@ -327,10 +345,31 @@ export default function InstallMonitor({ pkg, onClose, mode = 'install', onSucce
 
                 case 'CleanCache':
                     setLogs(prev => [...prev, 'Clearing package cache...']);
-                    await invoke('clear_cache', { keepVersions: 1 });
+                    await invoke('clean_package_cache', { keep: 0, uninstalledOnly: false, dryRun: false, password: pwd });
                     setLogs(prev => [...prev, '✓ Cache cleared']);
                     break;
 
+                case 'EnableMultilib':
+                    setLogs(prev => [...prev, 'Enabling the multilib repository...']);
+                    await invoke('toggle_repo_family', { family: 'multilib', enabled: true, password: pwd });
+                    await invoke('trigger_repo_sync', { forceRefresh: true });
+                    setLogs(prev => [...prev, '✓ multilib enabled']);
+                    break;
+
+                case 'InstallBuildTools':
+                    setLogs(prev => [...prev, 'Installing base-devel and git...']);
+                    await invoke('install_build_tools', { password: pwd });
+                    setLogs(prev => [...prev, '✓ Build tools installed']);
+                    break;
+
+                case 'ImportPgpKey': {
+                    if (!payload) throw new Error('No key ID in the build output');
+                    setLogs(prev => [...prev, `Importing source signing key ${payload}...`]);
+                    const imported = await invoke<string>('import_source_pgp_key', { keyId: payload });
+                    setLogs(prev => [...prev, `✓ ${imported}`]);
+                    break;
+                }
+
                 default:
                     setLogs(prev => [...prev, 'Preparing to retry...']);
             }
@@ -364,7 +403,22 @@ export default function InstallMonitor({ pkg, onClose, mode = 'install', onSucce
             case 'DiskFull':
                 return { icon: HardDrive, label: 'Clear Cache & Retry', color: 'bg-red-500 hover:bg-red-600' };
             case 'PackageNotFound':
+            case 'StaleDatabase':
+            case 'DatabaseCorrupt':
                 return { icon: RefreshCw, label: 'Refresh & Retry', color: 'bg-teal-500 hover:bg-teal-600' };
+            case 'MultilibDisabled':
+                return { icon: PackageIcon, label: 'Enable multilib & Retry', color: 'bg-teal-500 hover:bg-teal-600' };
+            case 'MissingBuildTools':
+                return { icon: PackageIcon, label: 'Install Build Tools & Retry', color: 'bg-teal-500 hover:bg-teal-600' };
+            case 'SourcePgpKey':
+                return { icon: Key, label: 'Import Key & Retry', color: 'bg-purple-500 hover:bg-purple-600' };
+            case 'NetworkUnavailable':
+                return { icon: Wifi, label: 'Retry', color: 'bg-blue-500 hover:bg-blue-600' };
+            case 'ShowManualSteps':
+            case 'DependencyConflict':
+            case 'FileConflict':
+            case 'MakepkgBuildFailure':
+                return { icon: Terminal, label: 'Show Manual Steps', color: 'bg-slate-500 hover:bg-slate-600' };
             default:
                 return { icon: RefreshCw, label: 'Retry', color: 'bg-blue-500 hover:bg-blue-600' };
         }
@@ -416,6 +470,11 @@ export default function InstallMonitor({ pkg, onClose, mode = 'install', onSucce
         setVisualProgress(0);
 
         try {
+            // Flagged proprietary packages (NVIDIA, Steam, ...) need the license accepted first
+            if (mode === 'install' && (await confirmLicenses([pkg.name])).length === 0) {
+                onClose();
+                return;
+            }
             const pwd = reducePasswordPrompts ? await requestSessionPassword() : null;
             if (mode === 'uninstall') {
                 await invoke('uninstall_package', {
@@ -430,16 +489,33 @@ export default function InstallMonitor({ pkg, onClose, mode = 'install', onSucce
                     setCommandPreview(`$ pacman -Rns --noconfirm ${pkg.name}`);
                 }
             } else {
+                // Offline: queue it instead; the queue starts it once the connection returns
+                const connectivity = await invoke<ConnectivityState>('get_connectivity').catch(() => null);
+                if (connectivity?.offline) {
+                    await invoke('enqueue_install', {
+                        name: pkg.name,
+                        source: pkg.source,
+                        repoName: pkg.repoName || null,
+                        buildMode: pkg.buildMode || null,
+                        password: pwd
+                    });
+                    showToast(`Offline: ${pkg.name} is queued and installs when the connection returns.`, 'info');
+                    onClose();
+                    return;
+                }
                 await invoke('install_package', {
                     name: pkg.name,
                     source: pkg.source,
                     password: pwd,
-                    repoName: pkg.repoName || null
+                    repoName: pkg.repoName || null,
+                    buildMode: pkg.buildMode || null
                 });
 
                 // Set Command Preview
                 if (pkg.source.source_type === 'aur') {
-                    setCommandPreview(`$ git clone https://aur.archlinux.org/${pkg.name}.git && makepkg -si`);
+                    setCommandPreview(pkg.buildMode === 'chroot'
+                        ? `$ git clone https://aur.archlinux.org/${pkg.name}.git && pkgctl build`
+                        : `$ git clone https://aur.archlinux.org/${pkg.name}.git && makepkg -si`);
                 } else if (pkg.source.source_type === 'flatpak') {
                     setCommandPreview(`$ flatpak install flathub ${pkg.name} -y`);
                 } else {
@@ -448,6 +524,14 @@ export default function InstallMonitor({ pkg, onClose, mode = 'install', onSucce
             }
             // The command is async spawned, completion comes via event
         } catch (e) {
+            // install_package rejects with a ClassifiedError; other commands with a string
+            if (typeof e === 'object' && e !== null && 'kind' in e) {
+                const classified = e as ClassifiedError;
+                setClassifiedError(classified);
+                setLogs((prev: string[]) => [...prev, `${classified.title}: ${classified.description}`]);
+                setStatus('error');
+                return;
+            }
             errorService.reportError(e as Error | string);
             setLogs((prev: string[]) => [...prev, `Error launching: ${e}`]);
             setStatus('error');
@@ -1054,15 +1138,17 @@ export default function InstallMonitor({ pkg, onClose, mode = 'install', onSucce
                                 {classifiedError.kind && (
                                     <div className="flex gap-2">
                                         {(() => {
-                                            const config = getRecoveryConfig(classifiedError.kind);
-                                            const RecoveryIcon = config.icon;
                                             // Use recovery_action (e.g. "UnlockDatabase") when backend sends it; else kind for retry
-                                            const action = typeof classifiedError.recovery_action === 'string'
-                                                ? classifiedError.recovery_action
-                                                : classifiedError.kind;
+                                            const recovery = classifiedError.recovery_action;
+                                            const action = typeof recovery === 'string'
+                                                ? recovery
+                                                : recovery?.type ?? classifiedError.kind;
+                                            const payload = typeof recovery === 'object' ? recovery.payload : undefined;
+                                            const config = getRecoveryConfig(action === 'ShowManualSteps' ? action : classifiedError.kind);
+                                            const RecoveryIcon = config.icon;
                                             return (
                                                 <button
-                                                    onClick={() => handleRecoveryAction(action)}
+                                                    onClick={() => handleRecoveryAction(action, payload)}
                                                     disabled={isRecovering}
                                                     className={clsx(
                                                         "flex-1 text-white font-bold py-3 rounded-xl flex items-center justify-center gap-2 shadow-lg transition-all active:scale-95",
@@ -0,0 +1,83 @@
+import { useEffect, useState } from 'react';
+import { motion, AnimatePresence } from 'framer-motion';
+import { FileText, Loader2 } from 'lucide-react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { useFocusTrap } from '../hooks/useFocusTrap';
+import { useErrorService } from '../context/ErrorContext';
+import { LicensePrompt } from '../types/alpm';
+
+/** An AUR build is waiting for a license answer; show the license and answer for the user. */
+export default function LicensePromptModal() {
+    const [prompt, setPrompt] = useState<LicensePrompt | null>(null);
+    const [busy, setBusy] = useState(false);
+    const errorService = useErrorService();
+    const focusTrapRef = useFocusTrap(!!prompt);
+
+    useEffect(() => {
+        const unlisten = listen<LicensePrompt>('license-prompt', (event) => setPrompt(event.payload));
+        return () => { unlisten.then((fn) => fn()).catch(() => { }); };
+    }, []);
+
+    if (!prompt) return null;
+
+    const respond = async (accept: boolean) => {
+        setBusy(true);
+        try {
+            await invoke(accept ? 'accept_license_prompt' : 'decline_license_prompt', { id: prompt.id });
+        } catch (e) {
+            errorService.reportError(e as Error | string);
+        } finally {
+            setBusy(false);
+            setPrompt(null);
+        }
+    };
+
+    return (
+        <AnimatePresence>
+            <div className="fixed inset-0 z-50 flex items-center justify-center bg-black/60 backdrop-blur-sm">
+                <motion.div
+                    ref={focusTrapRef}
+                    initial={{ opacity: 0, scale: 0.9 }}
+                    animate={{ opacity: 1, scale: 1 }}
+                    exit={{ opacity: 0, scale: 0.9 }}
+                    className="w-full max-w-2xl bg-app-card border border-app-border rounded-2xl shadow-2xl p-6 flex flex-col gap-4"
+                    role="dialog"
+                    aria-modal="true"
+                    aria-labelledby="license-prompt-title"
+                >
+                    <h3 id="license-prompt-title" className="text-xl font-bold text-app-fg flex items-center gap-2">
+                        <FileText size={22} className="text-amber-500" />
+                        {prompt.package} needs your agreement
+                    </h3>
+                    <p className="text-app-muted text-sm leading-relaxed">
+                        The build is paused until you answer. Declining stops the build; nothing is installed.
+                    </p>
+                    {prompt.text && (
+                        <pre className="max-h-80 overflow-auto whitespace-pre-wrap text-xs font-mono text-app-fg bg-app-subtle rounded-lg p-3 border border-app-border">
+                            {prompt.text}
+                        </pre>
+                    )}
+                    <p className="text-sm font-medium text-app-fg font-mono">{prompt.prompt}</p>
+                    <div className="flex gap-3 w-full">
+                        <button
+                            onClick={() => respond(false)}
+                            disabled={busy}
+                            className="flex-1 py-2.5 rounded-xl border border-app-border text-app-fg hover:bg-app-subtle font-medium transition-colors disabled:opacity-50"
+                        >
+                            Decline
+                        </button>
+                        <button
+                            onClick={() => respond(true)}
+                            disabled={busy}
+                            className="flex-1 py-2.5 rounded-xl text-white font-bold shadow-lg transition-all active:scale-95 btn-accent hover:opacity-90 disabled:opacity-50 flex items-center justify-center gap-2"
+                        >
+                            {busy && <Loader2 size={16} className="animate-spin" />}
+                            Accept
+                        </button>
+                    </div>
+                </motion.div>
+            </div>
+        </AnimatePresence>
+    );
+}
@@ -7,7 +7,7 @@ import { invoke } from '@tauri-apps/api/core';
 import { resolveIconUrl } from '../utils/iconHelper';
 import RepoBadge from './RepoBadge';
 
-import { PackageSource } from '../types/alpm';
+import { ContentRating, OptimizedVariant, PackageSource } from '../types/alpm';
 import { getBestSource, getAdditionalSourceCount } from '../utils/repoHelper';
 
 export interface Package {
@@ -32,6 +32,13 @@ export interface Package {
     is_featured?: boolean;
     available_sources?: PackageSource[];
     alternatives?: Package[];
+    optimized_variants?: OptimizedVariant[];
+    /** OARS age rating from AppStream, when the app has one. */
+    content_rating?: ContentRating | null;
+    /** Set by listing commands from the local package DB. */
+    installed?: boolean;
+    /** Installed, and this version is newer. */
+    update_available?: boolean;
 }
 
 interface PackageCardProps {
@@ -41,6 +48,14 @@ interface PackageCardProps {
     chaoticInfo?: ChaoticPackage | null;
 }
 
+/** search_packages result; index_generation changes whenever a repo sync swaps in new data. */
+export interface SearchResponse {
+    packages: Package[];
+    index_generation: number;
+    /** Corrected query when words of the typed one match nothing ("fierfox" -> "firefox"). */
+    did_you_mean: string | null;
+}
+
 export interface ChaoticPackage {
     id: number;
     pkgname: string;
@@ -183,6 +198,16 @@ const PackageCardInner: React.FC<PackageCardProps> = ({ pkg, onClick, skipMetada
                             ) : (
                                 <span className="text-[10px] text-slate-400 dark:text-white/40 font-mono">{displayPkg.version}</span>
                             )}
+                            {displayPkg.installed && (
+                                <span className={clsx(
+                                    "text-[10px] font-bold px-1.5 py-0.5 rounded",
+                                    displayPkg.update_available
+                                        ? "bg-amber-500/15 text-amber-600 dark:text-amber-400"
+                                        : "bg-green-500/15 text-green-600 dark:text-green-400"
+                                )}>
+                                    {displayPkg.update_available ? 'Update available' : 'Installed'}
+                                </span>
+                            )}
                         </div>
                     </div>
                 </div>
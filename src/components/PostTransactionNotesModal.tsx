@@ -0,0 +1,94 @@
+import { useEffect, useState } from 'react';
+import { motion, AnimatePresence } from 'framer-motion';
+import { clsx } from 'clsx';
+import { AlertTriangle, Cpu, ListChecks, LucideIcon, RotateCcw, XCircle } from 'lucide-react';
+import { listen } from '@tauri-apps/api/event';
+import { useEscapeKey } from '../hooks/useEscapeKey';
+import { useFocusTrap } from '../hooks/useFocusTrap';
+import { NoteKind, PostTransactionNote } from '../types/alpm';
+
+const KIND_ORDER: NoteKind[] = ['reboot', 'error', 'firmware', 'warning'];
+
+const KIND_STYLE: Record<NoteKind, { icon: LucideIcon; label: string; className: string }> = {
+    reboot: { icon: RotateCcw, label: 'Restart needed', className: 'text-blue-500' },
+    error: { icon: XCircle, label: 'Errors', className: 'text-red-500' },
+    firmware: { icon: Cpu, label: 'Firmware', className: 'text-amber-500' },
+    warning: { icon: AlertTriangle, label: 'Warnings', className: 'text-amber-500' },
+};
+
+/** Summary of what package hooks and install scripts reported (reboot needed, missing firmware, ...). */
+export default function PostTransactionNotesModal() {
+    const [notes, setNotes] = useState<PostTransactionNote[]>([]);
+    const close = () => setNotes([]);
+    useEscapeKey(close, notes.length > 0);
+    const focusTrapRef = useFocusTrap(notes.length > 0);
+
+    useEffect(() => {
+        // A batch commits several times; keep adding until the dialog is closed
+        const unlisten = listen<PostTransactionNote[]>('post-transaction-notes', (event) => {
+            setNotes((prev) => [
+                ...prev,
+                ...event.payload.filter((n) => !prev.some((p) => p.kind === n.kind && p.message === n.message)),
+            ]);
+        });
+        return () => { unlisten.then((fn) => fn()).catch(() => { }); };
+    }, []);
+
+    if (notes.length === 0) return null;
+
+    const needsReboot = notes.some((n) => n.kind === 'reboot');
+
+    return (
+        <AnimatePresence>
+            <div className="fixed inset-0 z-50 flex items-center justify-center bg-black/60 backdrop-blur-sm">
+                <motion.div
+                    ref={focusTrapRef}
+                    initial={{ opacity: 0, scale: 0.9 }}
+                    animate={{ opacity: 1, scale: 1 }}
+                    exit={{ opacity: 0, scale: 0.9 }}
+                    className="w-full max-w-2xl bg-app-card border border-app-border rounded-2xl shadow-2xl p-6 flex flex-col gap-4"
+                    role="dialog"
+                    aria-modal="true"
+                    aria-labelledby="post-transaction-notes-title"
+                >
+                    <h3 id="post-transaction-notes-title" className="text-xl font-bold text-app-fg flex items-center gap-2">
+                        <ListChecks size={22} className="text-blue-500" />
+                        Notes from the last update
+                    </h3>
+                    <p className="text-app-muted text-sm leading-relaxed">
+                        {needsReboot
+                            ? 'Some changes take effect after you restart the computer.'
+                            : 'Package scripts printed messages you may want to look at.'}
+                    </p>
+                    <div className="max-h-96 overflow-auto space-y-4">
+                        {KIND_ORDER.filter((kind) => notes.some((n) => n.kind === kind)).map((kind) => {
+                            const { icon: Icon, label, className } = KIND_STYLE[kind];
+                            return (
+                                <div key={kind} className="space-y-2">
+                                    <h4 className={clsx('text-sm font-bold flex items-center gap-2', className)}>
+                                        <Icon size={16} />
+                                        {label}
+                                    </h4>
+                                    <ul className="space-y-1">
+                                        {notes.filter((n) => n.kind === kind).map((n) => (
+                                            <li key={`${n.source}:${n.message}`} className="text-sm bg-app-subtle rounded-lg px-3 py-2 border border-app-border">
+                                                <p className="font-mono text-xs text-app-fg break-words">{n.message}</p>
+                                                <p className="text-xs text-app-muted mt-1">{n.source}</p>
+                                            </li>
+                                        ))}
+                                    </ul>
+                                </div>
+                            );
+                        })}
+                    </div>
+                    <button
+                        onClick={close}
+                        className="w-full py-2.5 rounded-xl text-white font-bold shadow-lg transition-all active:scale-95 btn-accent hover:opacity-90"
+                    >
+                        OK
+                    </button>
+                </motion.div>
+            </div>
+        </AnimatePresence>
+    );
+}
@@ -0,0 +1,149 @@
+import { useEffect, useState } from 'react';
+import { motion, AnimatePresence } from 'framer-motion';
+import { clsx } from 'clsx';
+import { History, Loader2 } from 'lucide-react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { useEscapeKey } from '../hooks/useEscapeKey';
+import { useFocusTrap } from '../hooks/useFocusTrap';
+import { useToast } from '../context/ToastContext';
+import { useErrorService } from '../context/ErrorContext';
+import { useSessionPassword } from '../context/useSessionPassword';
+import { useAppStore } from '../store/internal_store';
+import { BatchState, BatchStep, BatchStepState } from '../types/alpm';
+
+const STEP_LABELS: Record<BatchStep, string> = {
+    keyrings: 'Set up repository keys',
+    refresh_db: 'Refresh databases',
+    upgrade: 'Upgrade system',
+    remove: 'Remove packages',
+    install: 'Install packages',
+    install_local: 'Install built packages',
+};
+
+/** Batches older than this are not offered: the system has likely moved on since. */
+const OFFER_FOR_SECS = 7 * 24 * 3600;
+/** started_at of the batch the user chose to never resume. */
+const DISMISSED_KEY = 'monarch_resume_batch_dismissed';
+
+/** On launch, offer to finish a package operation that was cut off or stopped part-way. */
+export default function ResumeBatchModal() {
+    const [state, setState] = useState<BatchState | null>(null);
+    const [busy, setBusy] = useState(false);
+    const { success } = useToast();
+    const errorService = useErrorService();
+    const { requestSessionPassword } = useSessionPassword();
+    const reducePasswordPrompts = useAppStore((s) => s.reducePasswordPrompts);
+    const close = () => setState(null);
+    useEscapeKey(close, !!state && !busy);
+    const focusTrapRef = useFocusTrap(!!state);
+
+    useEffect(() => {
+        invoke<BatchState | null>('get_last_batch_state')
+            .then((s) => {
+                if (!s || s.steps.every((step) => step.status === 'done')) return;
+                if (Date.now() / 1000 - s.updated_at > OFFER_FOR_SECS) return;
+                if (localStorage.getItem(DISMISSED_KEY) === String(s.started_at)) return;
+                setState(s);
+            })
+            .catch(() => { });
+    }, []);
+
+    // The helper reports the step list as each step starts and ends
+    useEffect(() => {
+        if (!busy) return;
+        const unlisten = listen<BatchStepState[]>('batch-progress', (event) => {
+            setState((s) => (s ? { ...s, steps: event.payload } : s));
+        });
+        return () => { unlisten.then((f) => f()); };
+    }, [busy]);
+
+    if (!state) return null;
+
+    const interrupted = !busy && state.steps.some((s) => s.status === 'running');
+    const failed = state.steps.find((s) => s.status === 'failed');
+
+    const dismiss = () => {
+        localStorage.setItem(DISMISSED_KEY, String(state.started_at));
+        close();
+    };
+
+    const resume = async () => {
+        setBusy(true);
+        try {
+            const pwd = reducePasswordPrompts ? await requestSessionPassword() : null;
+            await invoke('resume_last_batch', { password: pwd });
+            success('The unfinished operation is complete.');
+            close();
+        } catch (e) {
+            errorService.reportError(e as Error | string);
+        } finally {
+            setBusy(false);
+        }
+    };
+
+    return (
+        <AnimatePresence>
+            <div className="fixed inset-0 z-50 flex items-center justify-center bg-black/60 backdrop-blur-sm">
+                <motion.div
+                    ref={focusTrapRef}
+                    initial={{ opacity: 0, scale: 0.9 }}
+                    animate={{ opacity: 1, scale: 1 }}
+                    exit={{ opacity: 0, scale: 0.9 }}
+                    className="w-full max-w-lg bg-app-card border border-app-border rounded-2xl shadow-2xl p-6 flex flex-col gap-4"
+                    role="dialog"
+                    aria-modal="true"
+                    aria-labelledby="resume-batch-title"
+                >
+                    <h3 id="resume-batch-title" className="text-xl font-bold text-app-fg flex items-center gap-2">
+                        <History size={22} className="text-amber-500" />
+                        Finish the last operation?
+                    </h3>
+                    <p className="text-app-muted text-sm leading-relaxed">
+                        {interrupted
+                            ? 'The last package operation was cut off before it finished, so the system may be partly updated.'
+                            : `The last package operation stopped early${failed?.error ? `: ${failed.error}` : '.'}`}
+                        {' '}Resuming runs the steps that did not complete; finished steps are not repeated.
+                    </p>
+                    <ul className="space-y-1 text-sm">
+                        {state.steps.map((s) => (
+                            <li key={s.step} className="flex items-center justify-between gap-3">
+                                <span className="text-app-fg">{STEP_LABELS[s.step]}</span>
+                                <span className={clsx(
+                                    'text-xs font-medium',
+                                    s.status === 'done' ? 'text-green-500' : s.status === 'failed' ? 'text-red-500' : 'text-amber-500',
+                                )}>
+                                    {s.status === 'done' ? 'Done' : s.status === 'failed' ? 'Failed' : s.status === 'running' ? (busy ? 'Running…' : 'Interrupted') : 'Not started'}
+                                </span>
+                            </li>
+                        ))}
+                    </ul>
+                    <div className="flex gap-3 w-full">
+                        <button
+                            onClick={dismiss}
+                            disabled={busy}
+                            className="flex-1 py-2.5 rounded-xl border border-app-border text-app-muted hover:bg-app-subtle font-medium transition-colors disabled:opacity-50"
+                        >
+                            Don't ask again
+                        </button>
+                        <button
+                            onClick={close}
+                            disabled={busy}
+                            className="flex-1 py-2.5 rounded-xl border border-app-border text-app-fg hover:bg-app-subtle font-medium transition-colors disabled:opacity-50"
+                        >
+                            Later
+                        </button>
+                        <button
+                            onClick={resume}
+                            disabled={busy}
+                            className="flex-1 py-2.5 rounded-xl text-white font-bold shadow-lg transition-all active:scale-95 btn-accent hover:opacity-90 disabled:opacity-50 flex items-center justify-center gap-2"
+                        >
+                            {busy && <Loader2 size={16} className="animate-spin" />}
+                            Resume
+                        </button>
+                    </div>
+                </motion.div>
+            </div>
+        </AnimatePresence>
+    );
+}
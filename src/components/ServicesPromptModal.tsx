@@ -0,0 +1,120 @@
+import { useEffect, useState } from 'react';
+import { motion, AnimatePresence } from 'framer-motion';
+import { clsx } from 'clsx';
+import { Cog, Loader2 } from 'lucide-react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { useEscapeKey } from '../hooks/useEscapeKey';
+import { useFocusTrap } from '../hooks/useFocusTrap';
+import { useToast } from '../context/ToastContext';
+import { useErrorService } from '../context/ErrorContext';
+import { useSessionPassword } from '../context/useSessionPassword';
+import { useAppStore } from '../store/internal_store';
+import { ServicesAvailable } from '../types/alpm';
+
+/** After an install that shipped disabled systemd units, offer to enable and start them. */
+export default function ServicesPromptModal() {
+    const [prompt, setPrompt] = useState<ServicesAvailable | null>(null);
+    const [selected, setSelected] = useState<Set<string>>(new Set());
+    const [busy, setBusy] = useState(false);
+    const { success } = useToast();
+    const errorService = useErrorService();
+    const { requestSessionPassword } = useSessionPassword();
+    const reducePasswordPrompts = useAppStore((s) => s.reducePasswordPrompts);
+    const close = () => setPrompt(null);
+    useEscapeKey(close, !!prompt && !busy);
+    const focusTrapRef = useFocusTrap(!!prompt);
+
+    useEffect(() => {
+        const unlisten = listen<ServicesAvailable>('services-available', (event) => {
+            setPrompt(event.payload);
+            setSelected(new Set(event.payload.services.map(s => s.unit)));
+        });
+        return () => { unlisten.then((fn) => fn()).catch(() => { }); };
+    }, []);
+
+    if (!prompt) return null;
+
+    const toggle = (unit: string) => {
+        const next = new Set(selected);
+        if (next.has(unit)) next.delete(unit); else next.add(unit);
+        setSelected(next);
+    };
+
+    const enable = async () => {
+        setBusy(true);
+        try {
+            const pwd = reducePasswordPrompts ? await requestSessionPassword() : null;
+            for (const unit of selected) {
+                await invoke('enable_service', { unit, now: true, password: pwd });
+            }
+            success(`Enabled ${[...selected].join(', ')}`);
+            close();
+        } catch (e) {
+            errorService.reportError(e as Error | string);
+        } finally {
+            setBusy(false);
+        }
+    };
+
+    return (
+        <AnimatePresence>
+            <div className="fixed inset-0 z-50 flex items-center justify-center bg-black/60 backdrop-blur-sm">
+                <motion.div
+                    ref={focusTrapRef}
+                    initial={{ opacity: 0, scale: 0.9 }}
+                    animate={{ opacity: 1, scale: 1 }}
+                    exit={{ opacity: 0, scale: 0.9 }}
+                    className="w-full max-w-lg bg-app-card border border-app-border rounded-2xl shadow-2xl p-6 flex flex-col gap-4"
+                    role="dialog"
+                    aria-modal="true"
+                    aria-labelledby="services-prompt-title"
+                >
+                    <h3 id="services-prompt-title" className="text-xl font-bold text-app-fg flex items-center gap-2">
+                        <Cog size={22} className="text-blue-500" />
+                        Enable services?
+                    </h3>
+                    <p className="text-app-muted text-sm leading-relaxed">
+                        {prompt.package} comes with background services that are not running yet. Enabled services start now and at every boot.
+                    </p>
+                    <div className="space-y-1">
+                        {prompt.services.map((service) => (
+                            <label
+                                key={service.unit}
+                                className={clsx("flex items-start gap-3 px-3 py-2 rounded-lg cursor-pointer hover:bg-app-subtle", !selected.has(service.unit) && "opacity-60")}
+                            >
+                                <input
+                                    type="checkbox"
+                                    checked={selected.has(service.unit)}
+                                    onChange={() => toggle(service.unit)}
+                                    className="mt-1"
+                                />
+                                <span className="min-w-0">
+                                    <span className="block font-medium text-app-fg font-mono text-sm">{service.unit}</span>
+                                    {service.description && <span className="block text-xs text-app-muted">{service.description}</span>}
+                                </span>
+                            </label>
+                        ))}
+                    </div>
+                    <div className="flex gap-3 w-full">
+                        <button
+                            onClick={close}
+                            disabled={busy}
+                            className="flex-1 py-2.5 rounded-xl border border-app-border text-app-fg hover:bg-app-subtle font-medium transition-colors disabled:opacity-50"
+                        >
+                            Not now
+                        </button>
+                        <button
+                            onClick={enable}
+                            disabled={busy || selected.size === 0}
+                            className="flex-1 py-2.5 rounded-xl text-white font-bold shadow-lg transition-all active:scale-95 btn-accent hover:opacity-90 disabled:opacity-50 flex items-center justify-center gap-2"
+                        >
+                            {busy && <Loader2 size={16} className="animate-spin" />}
+                            Enable & Start
+                        </button>
+                    </div>
+                </motion.div>
+            </div>
+        </AnimatePresence>
+    );
+}
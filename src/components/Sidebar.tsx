@@ -4,6 +4,14 @@ import { clsx } from 'clsx';
 import logoIcon from '../assets/logo.png';
 import { motion } from 'framer-motion';
 
+interface SystemStateChange {
+    installed: string[];
+    removed: string[];
+    upgraded: string[];
+    sync_dbs_changed: boolean;
+    external: boolean;
+}
+
 interface SidebarProps {
     activeTab: string;
     setActiveTab: (tab: string) => void;
@@ -37,11 +45,23 @@ const Sidebar: React.FC<SidebarProps> = ({ activeTab, setActiveTab }) => {
 
     useEffect(() => {
         // Check for updates to show notification badge
-        import('@tauri-apps/api/core').then(({ invoke }) => {
-            invoke('check_for_updates')
-                .then((updates) => setUpdateCount((updates as any[]).length))
-                .catch(() => { });
-        });
+        const refreshCount = () => {
+            import('@tauri-apps/api/core').then(({ invoke }) => {
+                invoke('check_for_updates')
+                    .then((updates) => setUpdateCount((updates as any[]).length))
+                    .catch(() => { });
+            });
+        };
+        refreshCount();
+
+        // Packages changed outside MonArch (pacman/yay in a terminal)
+        const unlisten = import('@tauri-apps/api/event').then(({ listen }) =>
+            listen<SystemStateChange>('system-state-changed', (event) => {
+                const { removed, upgraded, sync_dbs_changed } = event.payload;
+                if (removed.length || upgraded.length || sync_dbs_changed) refreshCount();
+            })
+        );
+        return () => { unlisten.then((fn) => fn()).catch(() => { }); };
     }, []);
 
     const tabs = [
@@ -14,12 +14,14 @@ import {
     Activity,
     ChevronDown,
     Eye,
-    EyeOff
+    EyeOff,
+    LifeBuoy
 } from "lucide-react";
 import { clsx } from 'clsx';
 import { useEscapeKey } from '../hooks/useEscapeKey';
 import { useFocusTrap } from '../hooks/useFocusTrap';
 import { useErrorService } from '../context/ErrorContext';
+import { RescueOperation } from '../types/alpm';
 
 interface HealthIssue {
     category: string;
@@ -41,6 +43,7 @@ export default function SystemHealthSection() {
     const [showPassword, setShowPassword] = useState(false);
     const [classifiedError, setClassifiedError] = useState<any | null>(null);
     const [isCheckingHealth, setIsCheckingHealth] = useState(true);
+    const [rescueOps, setRescueOps] = useState<RescueOperation[]>([]);
 
     useEscapeKey(() => setShowPasswordInput(false), showPasswordInput);
     const authModalRef = useFocusTrap(showPasswordInput);
@@ -53,6 +56,7 @@ export default function SystemHealthSection() {
             setTimeout(() => setIsCheckingHealth(false), 500);
         };
         runHealthCheck();
+        loadRescueOps();
         const unlisten = listen("repair-log", (event) => {
             setLogs((prev) => [...prev, event.payload as string]);
         });
@@ -74,6 +78,12 @@ export default function SystemHealthSection() {
         }
     };
 
+    const loadRescueOps = () => {
+        invoke<RescueOperation[]>("get_rescue_operations").then(setRescueOps).catch(() => { });
+    };
+
+    const pendingRescue = rescueOps.find(op => pendingAction === `rescue:${op.action}`);
+
     const checkHealth = async () => {
         try {
             const issues = await invoke<HealthIssue[]>("check_system_health");
@@ -134,11 +144,17 @@ export default function SystemHealthSection() {
                     break;
             }
 
+            if (pendingAction.startsWith("rescue:")) {
+                cmd = "run_rescue_operation";
+                args = { action: pendingAction.slice("rescue:".length) };
+            }
+
             if (cmd) {
                 await invoke(cmd, { password: password || null, ...args });
                 setLogs((p) => [...p, `>>> SUCCESS: ${pendingAction.toUpperCase()} COMPLETED.`]);
                 checkHealth();
                 checkLock();
+                loadRescueOps();
             }
         } catch (e) {
             errorService.reportError(e as Error | string);
@@ -261,6 +277,26 @@ export default function SystemHealthSection() {
                     </div>
                 </div>
 
+                {/* Rescue Toolkit: boot-adjacent repairs, each confirmed with its risk spelled out */}
+                {rescueOps.length > 0 && (
+                    <div className="pt-8 border-t border-app-border/30">
+                        <h3 className="text-sm font-black uppercase tracking-widest text-app-muted mb-2 px-2">Rescue Toolkit</h3>
+                        <p className="text-xs text-app-muted mb-4 px-2">For when the last update broke booting. Run one step at a time.</p>
+                        <div className="grid grid-cols-1 md:grid-cols-2 gap-4">
+                            {rescueOps.map(op => (
+                                <RepairButton
+                                    key={op.action}
+                                    icon={<LifeBuoy className={op.risk === 'high' ? "text-red-500" : op.risk === 'medium' ? "text-amber-500" : "text-emerald-500"} />}
+                                    title={op.title}
+                                    desc={op.note ? `${op.description} ${op.note}.` : op.description}
+                                    onClick={() => handleAction(`rescue:${op.action}`)}
+                                    loading={loading || !op.available}
+                                />
+                            ))}
+                        </div>
+                    </div>
+                )}
+
                 {/* Log Output (Collapsible for Cleanliness) */}
                 <div className="mt-4 space-y-4">
                     {classifiedError && (
@@ -305,7 +341,7 @@ export default function SystemHealthSection() {
                             </div>
                             <div>
                                 <h3 id="auth-modal-title" className="text-2xl font-black text-app-fg leading-tight">Authorize Task</h3>
-                                <p className="text-app-muted text-sm mt-1">MonARCH needs your permission to repair: <span className="text-app-fg font-mono font-bold uppercase">{pendingAction}</span></p>
+                                <p className="text-app-muted text-sm mt-1">MonARCH needs your permission to repair: <span className="text-app-fg font-mono font-bold uppercase">{pendingRescue?.title ?? pendingAction}</span></p>
                             </div>
                         </div>
 
@@ -315,6 +351,16 @@ export default function SystemHealthSection() {
                                 <p>This action will perform system-level changes. Entering your password here allows MonARCH to handle the permissions securely without multiple system prompts.</p>
                             </div>
 
+                            {pendingRescue && (
+                                <div className={clsx(
+                                    "flex start gap-4 p-4 rounded-2xl border",
+                                    pendingRescue.risk === 'high' ? "bg-red-500/10 border-red-500/20" : "bg-amber-500/10 border-amber-500/20"
+                                )}>
+                                    <AlertTriangle className={clsx("shrink-0 mt-0.5", pendingRescue.risk === 'high' ? "text-red-500" : "text-amber-500")} size={20} />
+                                    <p><span className="font-bold text-app-fg uppercase text-xs">{pendingRescue.risk} risk · </span>{pendingRescue.risk_description}</p>
+                                </div>
+                            )}
+
                             <div className="relative group/passwd">
                                 <input
                                     type={showPassword ? "text" : "password"}
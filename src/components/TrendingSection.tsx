@@ -1,5 +1,6 @@
 import { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import PackageCard, { Package, ChaoticPackage } from './PackageCard';
 import SkeletonCard from './SkeletonCard';
 import { useErrorService } from '../context/ErrorContext';
@@ -49,6 +50,16 @@ export default function TrendingSection({ title, onSelectPackage, filterIds, lim
         // eslint-disable-next-line react-hooks/exhaustive-deps
     }, [JSON.stringify(filterIds)]);
 
+    // The home feed poller pushes a fresh trending list when its inputs change
+    useEffect(() => {
+        if (filterIds && filterIds.length > 0) return;
+        const unlisten = listen<{ trending: Package[] }>('home-content-updated', (event) => {
+            setPackages(event.payload.trending);
+        });
+        return () => { unlisten.then((fn) => fn()).catch(() => { }); };
+        // eslint-disable-next-line react-hooks/exhaustive-deps
+    }, [JSON.stringify(filterIds)]);
+
     // Batch fetch chaotic info when packages load
     useEffect(() => {
         const fetchBatchInfo = async () => {
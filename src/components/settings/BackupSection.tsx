@@ -0,0 +1,66 @@
+import React from 'react';
+import { ArchiveRestore, Download, Upload } from 'lucide-react';
+import { invoke } from '@tauri-apps/api/core';
+import { useToast } from '../../context/ToastContext';
+import { useErrorService } from '../../context/ErrorContext';
+import { useSessionPassword } from '../../context/useSessionPassword';
+import { useAppStore } from '../../store/internal_store';
+import { SettingsImport } from '../../types/alpm';
+
+/** Export settings, repo choices and pins to one file; import them on another machine or after a reinstall. */
+export default function BackupSection() {
+    const { success, show } = useToast();
+    const errorService = useErrorService();
+    const { requestSessionPassword } = useSessionPassword();
+    const reducePasswordPrompts = useAppStore((s) => s.reducePasswordPrompts);
+    const [busy, setBusy] = React.useState(false);
+
+    const exportBackup = async () => {
+        setBusy(true);
+        try {
+            const path = await invoke<string | null>('export_settings');
+            if (path) success(`Settings saved to ${path}`);
+        } catch (e) {
+            errorService.reportError(e as Error | string);
+        } finally {
+            setBusy(false);
+        }
+    };
+
+    const importBackup = async () => {
+        setBusy(true);
+        try {
+            const pwd = reducePasswordPrompts ? await requestSessionPassword() : null;
+            const report = await invoke<SettingsImport | null>('import_settings', { password: pwd });
+            if (!report) return;
+            success(`Restored ${report.settings} settings, ${report.repos} repositories and ${report.pins} pins. Restart MonArch to apply everything.`);
+            report.warnings.forEach((w) => show(w, 'warning'));
+        } catch (e) {
+            errorService.reportError(e as Error | string);
+        } finally {
+            setBusy(false);
+        }
+    };
+
+    return (
+        <section className="space-y-4">
+            <h2 className="text-lg font-bold text-slate-900 dark:text-white flex items-center gap-2">
+                <ArchiveRestore size={20} className="text-blue-500" />
+                Backup &amp; Restore
+            </h2>
+            <div className="p-6 bg-app-card/50 dark:bg-white/5 border border-app-border rounded-2xl space-y-4">
+                <p className="text-sm text-slate-500 dark:text-white/50 leading-relaxed">
+                    Save your settings, repository choices and pinned packages to one file, and restore them on another machine or after a reinstall. Repositories this machine doesn't have are skipped. The file includes your proxy settings.
+                </p>
+                <div className="flex flex-wrap items-center gap-3">
+                    <button onClick={exportBackup} disabled={busy} className="px-3 py-2 rounded-lg bg-slate-100 dark:bg-white/10 hover:bg-slate-200 dark:hover:bg-white/20 text-sm font-bold flex items-center gap-2 disabled:opacity-50">
+                        <Upload size={16} /> Export
+                    </button>
+                    <button onClick={importBackup} disabled={busy} className="px-3 py-2 rounded-lg bg-slate-100 dark:bg-white/10 hover:bg-slate-200 dark:hover:bg-white/20 text-sm font-bold flex items-center gap-2 disabled:opacity-50">
+                        <Download size={16} /> Import
+                    </button>
+                </div>
+            </div>
+        </section>
+    );
+}
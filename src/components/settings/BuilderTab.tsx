@@ -1,9 +1,10 @@
 import React from 'react';
-import { Terminal, Trash2, Cpu, Zap, Info, ChevronDown } from 'lucide-react';
+import { Terminal, Trash2, Cpu, Zap, Info, ChevronDown, Box, Gauge, GitBranch, X } from 'lucide-react';
 import { clsx } from 'clsx';
 import { useAppStore } from '../../store/internal_store';
 import { useToast } from '../../context/ToastContext';
 import { invoke } from '@tauri-apps/api/core';
+import { AurBuildMode, AurBuildModeInfo, DevelUpdateSettings } from '../../types/alpm';
 
 export default function BuilderTab() {
     const {
@@ -13,6 +14,30 @@ export default function BuilderTab() {
     } = useAppStore();
     const { success, error } = useToast();
     const [isClearing, setIsClearing] = React.useState(false);
+    const [buildInfo, setBuildInfo] = React.useState<AurBuildModeInfo | null>(null);
+
+    const [devel, setDevel] = React.useState<DevelUpdateSettings | null>(null);
+
+    React.useEffect(() => {
+        invoke<AurBuildModeInfo>('get_aur_build_mode').then(setBuildInfo).catch(() => { });
+        invoke<DevelUpdateSettings>('get_devel_update_settings').then(setDevel).catch(() => { });
+    }, []);
+
+    const saveDevel = async (exclude: boolean, alwaysCheck: string[]) => {
+        try {
+            setDevel(await invoke<DevelUpdateSettings>('set_devel_update_settings', { exclude, alwaysCheck }));
+        } catch (e) {
+            error(String(e));
+        }
+    };
+
+    const saveBuildMode = async (mode: AurBuildMode, ccache: boolean) => {
+        try {
+            setBuildInfo(await invoke<AurBuildModeInfo>('set_aur_build_mode', { mode, ccache }));
+        } catch (e) {
+            error(String(e));
+        }
+    };
 
     const handleClearBuildCache = async () => {
         setIsClearing(true);
@@ -68,6 +93,114 @@ export default function BuilderTab() {
 
                     <div className="h-px bg-slate-100 dark:bg-white/5 w-full" />
 
+                    {/* Build Environment */}
+                    <div className="flex flex-col sm:flex-row sm:items-center justify-between gap-4">
+                        <div className="space-y-1">
+                            <h3 className="font-bold text-slate-900 dark:text-white flex items-center gap-2">
+                                <Box size={16} className="text-slate-400" />
+                                Build Environment
+                            </h3>
+                            <p className="text-sm text-slate-500 dark:text-white/50 max-w-md">
+                                {buildInfo && !buildInfo.devtools_available
+                                    ? "Install devtools to build AUR packages in an isolated clean chroot."
+                                    : "Clean chroot builds are reproducible and keep build dependencies off your system, but are slower."}
+                            </p>
+                        </div>
+                        <div className="relative shrink-0">
+                            <select
+                                value={buildInfo?.mode ?? 'host'}
+                                disabled={!buildInfo}
+                                onChange={(e) => buildInfo && saveBuildMode(e.target.value as AurBuildMode, buildInfo.ccache)}
+                                className="appearance-none bg-slate-100 dark:bg-white/5 border border-slate-200 dark:border-white/10 rounded-xl pl-4 pr-10 py-2.5 text-sm font-bold text-slate-900 dark:text-white focus:outline-none focus:ring-2 focus:ring-blue-500/40 transition-all cursor-pointer disabled:opacity-50"
+                            >
+                                <option value="host">Host (makepkg)</option>
+                                <option value="chroot" disabled={!buildInfo?.devtools_available}>Clean chroot (devtools)</option>
+                            </select>
+                            <div className="absolute right-4 top-1/2 -translate-y-1/2 pointer-events-none text-slate-400">
+                                <ChevronDown size={16} />
+                            </div>
+                        </div>
+                    </div>
+
+                    <div className="h-px bg-slate-100 dark:bg-white/5 w-full" />
+
+                    {/* ccache */}
+                    <div className="flex flex-col sm:flex-row sm:items-center justify-between gap-4">
+                        <div className="space-y-1">
+                            <h3 className="font-bold text-slate-900 dark:text-white flex items-center gap-2">
+                                <Gauge size={16} className="text-slate-400" />
+                                Compiler Cache (ccache)
+                            </h3>
+                            <p className="text-sm text-slate-500 dark:text-white/50 max-w-md">
+                                {buildInfo && !buildInfo.ccache_available
+                                    ? "Install ccache to speed up rebuilds of C/C++ packages."
+                                    : "Reuse compiled objects between host builds. Makes -git package rebuilds much faster."}
+                            </p>
+                        </div>
+                        <button
+                            onClick={() => buildInfo && saveBuildMode(buildInfo.mode, !buildInfo.ccache)}
+                            disabled={!buildInfo?.ccache_available}
+                            className={clsx(
+                                "relative w-14 h-8 rounded-full p-1 transition-all duration-300 focus:outline-none focus:ring-2 focus:ring-blue-500/50 shrink-0 disabled:opacity-50",
+                                buildInfo?.ccache ? "bg-blue-600 shadow-lg shadow-blue-600/20" : "bg-slate-200 dark:bg-white/10"
+                            )}
+                        >
+                            <div className={clsx(
+                                "w-6 h-6 bg-white rounded-full transition-transform duration-300 shadow-sm",
+                                buildInfo?.ccache ? "translate-x-6" : "translate-x-0"
+                            )} />
+                        </button>
+                    </div>
+
+                    <div className="h-px bg-slate-100 dark:bg-white/5 w-full" />
+
+                    {/* Development packages */}
+                    <div className="space-y-3">
+                        <div className="flex flex-col sm:flex-row sm:items-center justify-between gap-4">
+                            <div className="space-y-1">
+                                <h3 className="font-bold text-slate-900 dark:text-white flex items-center gap-2">
+                                    <GitBranch size={16} className="text-slate-400" />
+                                    Skip Development Packages
+                                </h3>
+                                <p className="text-sm text-slate-500 dark:text-white/50 max-w-md">
+                                    Leave -git, -svn and similar AUR packages out of routine updates. Their AUR version rarely matches a fresh build, so they show up as updates on every check.
+                                </p>
+                            </div>
+                            <button
+                                onClick={() => devel && saveDevel(!devel.exclude, devel.always_check)}
+                                disabled={!devel}
+                                className={clsx(
+                                    "relative w-14 h-8 rounded-full p-1 transition-all duration-300 focus:outline-none focus:ring-2 focus:ring-blue-500/50 shrink-0 disabled:opacity-50",
+                                    devel?.exclude ? "bg-blue-600 shadow-lg shadow-blue-600/20" : "bg-slate-200 dark:bg-white/10"
+                                )}
+                            >
+                                <div className={clsx(
+                                    "w-6 h-6 bg-white rounded-full transition-transform duration-300 shadow-sm",
+                                    devel?.exclude ? "translate-x-6" : "translate-x-0"
+                                )} />
+                            </button>
+                        </div>
+                        {devel?.exclude && devel.always_check.length > 0 && (
+                            <div className="flex flex-wrap items-center gap-2">
+                                <span className="text-xs text-slate-400 dark:text-white/30">Always checked:</span>
+                                {devel.always_check.map((name) => (
+                                    <span key={name} className="flex items-center gap-1 px-2 py-1 rounded-lg bg-slate-100 dark:bg-white/5 text-xs font-bold text-slate-700 dark:text-white/70">
+                                        {name}
+                                        <button
+                                            onClick={() => saveDevel(devel.exclude, devel.always_check.filter(n => n !== name))}
+                                            title={`Skip ${name} again`}
+                                            className="text-slate-400 hover:text-red-500"
+                                        >
+                                            <X size={12} />
+                                        </button>
+                                    </span>
+                                ))}
+                            </div>
+                        )}
+                    </div>
+
+                    <div className="h-px bg-slate-100 dark:bg-white/5 w-full" />
+
                     {/* Clean Build */}
                     <div className="flex flex-col sm:flex-row sm:items-center justify-between gap-4">
                         <div className="space-y-1">
@@ -0,0 +1,80 @@
+import React from 'react';
+import { Languages, Loader2 } from 'lucide-react';
+import { invoke } from '@tauri-apps/api/core';
+import { useToast } from '../../context/ToastContext';
+import { useErrorService } from '../../context/ErrorContext';
+import { CatalogLocale } from '../../types/alpm';
+
+/** Language of AppStream app names, summaries and descriptions (defaults to the system locale). */
+export default function CatalogLanguageSection() {
+    const { success } = useToast();
+    const errorService = useErrorService();
+    const [current, setCurrent] = React.useState<CatalogLocale | null>(null);
+    const [locale, setLocale] = React.useState('');
+    const [busy, setBusy] = React.useState(false);
+
+    const show = (value: CatalogLocale) => {
+        setCurrent(value);
+        setLocale(value.custom ? value.locale : '');
+    };
+
+    React.useEffect(() => {
+        invoke<CatalogLocale>('get_catalog_locale').then(show).catch(() => { });
+    }, []);
+
+    const save = async (value: string | null) => {
+        setBusy(true);
+        try {
+            const next = await invoke<CatalogLocale>('set_catalog_locale', { locale: value });
+            show(next);
+            success(next.custom ? `App descriptions now in ${next.locale}.` : 'App descriptions follow the system language.');
+        } catch (e) {
+            errorService.reportError(e as Error | string);
+        } finally {
+            setBusy(false);
+        }
+    };
+
+    return (
+        <section className="space-y-4">
+            <h2 className="text-lg font-bold text-slate-900 dark:text-white flex items-center gap-2">
+                <Languages size={20} className="text-indigo-500" />
+                App Descriptions Language
+            </h2>
+            <div className="p-6 bg-app-card/50 dark:bg-white/5 border border-app-border rounded-2xl space-y-4">
+                <p className="text-sm text-slate-500 dark:text-white/50 max-w-md leading-relaxed">
+                    Names, summaries and descriptions are shown in this language where the app provides a translation, English otherwise.
+                    {current && !current.custom && ` Currently the system language (${current.locale}).`}
+                </p>
+                <div className="flex items-center justify-between gap-6">
+                    <p className="text-sm text-slate-500 dark:text-white/50">Locale (e.g. de_DE, pt_BR)</p>
+                    <input
+                        value={locale}
+                        onChange={(e) => setLocale(e.target.value)}
+                        placeholder={current?.locale ?? 'System default'}
+                        className="w-48 text-sm px-3 py-2 rounded-lg bg-slate-100 dark:bg-white/5 border border-app-border"
+                    />
+                </div>
+                <div className="flex justify-end gap-2">
+                    {current?.custom && (
+                        <button
+                            onClick={() => save(null)}
+                            disabled={busy}
+                            className="px-4 py-2 rounded-xl bg-slate-100 dark:bg-white/5 text-sm font-bold text-slate-600 dark:text-white/70 hover:bg-slate-200 dark:hover:bg-white/10 disabled:opacity-50"
+                        >
+                            Use System Language
+                        </button>
+                    )}
+                    <button
+                        onClick={() => save(locale.trim() || null)}
+                        disabled={busy}
+                        className="px-4 py-2 rounded-xl bg-blue-600 text-white text-sm font-bold hover:bg-blue-500 disabled:opacity-50 flex items-center gap-2"
+                    >
+                        {busy && <Loader2 size={16} className="animate-spin" />}
+                        Save
+                    </button>
+                </div>
+            </div>
+        </section>
+    );
+}
@@ -0,0 +1,78 @@
+import React from 'react';
+import { Network, RefreshCw } from 'lucide-react';
+import { clsx } from 'clsx';
+import { invoke } from '@tauri-apps/api/core';
+import { useToast } from '../../context/ToastContext';
+import { LocalApiSettings } from '../../types/alpm';
+
+/** Opt-in localhost API for dashboards (Home Assistant, conky). */
+export default function LocalApiSection() {
+    const { success, error } = useToast();
+    const [settings, setSettings] = React.useState<LocalApiSettings | null>(null);
+
+    React.useEffect(() => {
+        invoke<LocalApiSettings>('get_local_api_settings').then(setSettings).catch(() => { });
+    }, []);
+
+    const save = async (enabled: boolean, allowUpdates: boolean) => {
+        try {
+            setSettings(await invoke<LocalApiSettings>('set_local_api_settings', { enabled, allowUpdates }));
+        } catch (e) {
+            error(String(e));
+        }
+    };
+
+    const regenerate = async () => {
+        try {
+            setSettings(await invoke<LocalApiSettings>('regenerate_local_api_token'));
+            success('New token generated. Update your dashboards.');
+        } catch (e) {
+            error(String(e));
+        }
+    };
+
+    if (!settings) return null;
+
+    const toggle = (on: boolean, onClick: () => void) => (
+        <button
+            onClick={onClick}
+            className={clsx(
+                "relative w-14 h-8 rounded-full p-1 transition-all duration-300 shrink-0",
+                on ? "bg-blue-600 shadow-lg shadow-blue-600/20" : "bg-slate-200 dark:bg-white/10"
+            )}
+        >
+            <div className={clsx("w-6 h-6 bg-white rounded-full transition-transform duration-300 shadow-sm", on ? "translate-x-6" : "translate-x-0")} />
+        </button>
+    );
+
+    return (
+        <section className="space-y-4">
+            <h2 className="text-lg font-bold text-slate-900 dark:text-white flex items-center gap-2">
+                <Network size={20} className="text-cyan-500" />
+                Local API
+            </h2>
+            <div className="p-6 bg-app-card/50 dark:bg-white/5 border border-app-border rounded-2xl space-y-4">
+                <div className="flex items-center justify-between gap-6">
+                    <p className="text-sm text-slate-500 dark:text-white/50 max-w-md leading-relaxed">
+                        Serve update status and search on <code>127.0.0.1:{settings.port}</code> for dashboards such as Home Assistant or conky. Requests need the token below.
+                    </p>
+                    {toggle(settings.enabled, () => save(!settings.enabled, settings.allow_updates))}
+                </div>
+                {settings.enabled && (
+                    <>
+                        <div className="flex items-center justify-between gap-6">
+                            <p className="text-sm text-slate-500 dark:text-white/50">Allow starting update checks and system updates</p>
+                            {toggle(settings.allow_updates, () => save(true, !settings.allow_updates))}
+                        </div>
+                        <div className="flex items-center gap-3">
+                            <code className="flex-1 text-xs p-2 rounded-lg bg-slate-100 dark:bg-white/5 select-all break-all">{settings.token}</code>
+                            <button onClick={regenerate} className="p-2 rounded-lg bg-slate-100 dark:bg-white/10 hover:bg-slate-200 dark:hover:bg-white/20" title="Generate a new token">
+                                <RefreshCw size={16} />
+                            </button>
+                        </div>
+                    </>
+                )}
+            </div>
+        </section>
+    );
+}
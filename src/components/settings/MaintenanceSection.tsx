@@ -0,0 +1,178 @@
+import React from 'react';
+import { Eye, Loader2, Sparkles } from 'lucide-react';
+import { clsx } from 'clsx';
+import { invoke } from '@tauri-apps/api/core';
+import { useToast } from '../../context/ToastContext';
+import { useSessionPassword } from '../../context/useSessionPassword';
+import { useAppStore } from '../../store/internal_store';
+import { MaintenanceAction, MaintenancePolicy, MaintenanceReport, MaintenanceRule, RuleInterval, RuleRun } from '../../types/alpm';
+
+const GIB = 1024 ** 3;
+
+const formatBytes = (bytes: number) => {
+    if (bytes >= GIB) return `${(bytes / GIB).toFixed(2)} GiB`;
+    if (bytes >= 1024 ** 2) return `${(bytes / 1024 ** 2).toFixed(1)} MiB`;
+    return `${(bytes / 1024).toFixed(0)} KiB`;
+};
+
+/** Rule kinds in display order, with the action a new rule of that kind starts with. */
+const KINDS: { kind: MaintenanceAction['action']; label: string; initial: MaintenanceAction }[] = [
+    { kind: 'remove_orphans', label: 'Remove orphaned dependencies', initial: { action: 'remove_orphans' } },
+    { kind: 'trim_package_cache', label: 'Trim the package cache', initial: { action: 'trim_package_cache', keep: 2 } },
+    { kind: 'clean_build_cache', label: 'Clear the build cache when large', initial: { action: 'clean_build_cache', over_bytes: 5 * GIB } },
+];
+
+const describeRun = (run: RuleRun) => {
+    const when = new Date(run.ran_at * 1000).toLocaleString();
+    if (run.error) return `${when}: failed, ${run.error}`;
+    if (run.items.length === 0) return `${when}: nothing to clean`;
+    return `${when}: ${run.dry_run ? 'would clean' : 'cleaned'} ${run.items.length} item(s), ${formatBytes(run.bytes)}`;
+};
+
+/** Cleanup rules the background scheduler applies: orphans, package cache, build cache. */
+export default function MaintenanceSection() {
+    const { success, error } = useToast();
+    const { requestSessionPassword } = useSessionPassword();
+    const reducePasswordPrompts = useAppStore((s) => s.reducePasswordPrompts);
+    const [policy, setPolicy] = React.useState<MaintenancePolicy | null>(null);
+    const [report, setReport] = React.useState<MaintenanceReport | null>(null);
+    const [previewing, setPreviewing] = React.useState(false);
+
+    const refreshReport = () => {
+        invoke<MaintenanceReport>('get_maintenance_report').then(setReport).catch(() => { });
+    };
+
+    React.useEffect(() => {
+        invoke<MaintenancePolicy>('get_maintenance_policy').then(setPolicy).catch(() => { });
+        refreshReport();
+    }, []);
+
+    const save = async (next: MaintenancePolicy) => {
+        try {
+            setPolicy(await invoke<MaintenancePolicy>('set_maintenance_policy', { policy: next }));
+            refreshReport();
+        } catch (e) {
+            error(String(e));
+        }
+    };
+
+    const ruleFor = (kind: string) => policy?.rules.find((r) => r.action === kind);
+
+    const setRule = (kind: string, rule: MaintenanceRule | null) => {
+        if (!policy) return;
+        const others = policy.rules.filter((r) => r.action !== kind);
+        save({ ...policy, rules: rule ? [...others, rule] : others });
+    };
+
+    const preview = async () => {
+        setPreviewing(true);
+        try {
+            const pwd = reducePasswordPrompts ? await requestSessionPassword() : null;
+            const runs = await invoke<RuleRun[]>('run_maintenance', { dryRun: true, password: pwd });
+            const bytes = runs.reduce((sum, r) => sum + r.bytes, 0);
+            success(`Cleanup would free ${formatBytes(bytes)}`);
+            refreshReport();
+        } catch (e) {
+            error(String(e));
+        } finally {
+            setPreviewing(false);
+        }
+    };
+
+    if (!policy) return null;
+
+    const toggle = (on: boolean, onClick: () => void) => (
+        <button
+            onClick={onClick}
+            className={clsx(
+                "relative w-14 h-8 rounded-full p-1 transition-all duration-300 shrink-0",
+                on ? "bg-blue-600 shadow-lg shadow-blue-600/20" : "bg-slate-200 dark:bg-white/10"
+            )}
+        >
+            <div className={clsx("w-6 h-6 bg-white rounded-full transition-transform duration-300 shadow-sm", on ? "translate-x-6" : "translate-x-0")} />
+        </button>
+    );
+
+    const inputClass = "w-20 text-sm px-3 py-2 rounded-lg bg-slate-100 dark:bg-white/5 border border-app-border";
+
+    return (
+        <section className="space-y-4">
+            <h2 className="text-lg font-bold text-slate-900 dark:text-white flex items-center gap-2">
+                <Sparkles size={20} className="text-blue-500" />
+                Automatic Maintenance
+            </h2>
+            <div className="p-6 bg-app-card/50 dark:bg-white/5 border border-app-border rounded-2xl space-y-5">
+                <p className="text-sm text-slate-500 dark:text-white/50 max-w-md leading-relaxed">
+                    Cleanup rules run in the background on their own schedule. In dry-run mode they only record what they would clean.
+                </p>
+                {KINDS.map(({ kind, label, initial }) => {
+                    const rule = ruleFor(kind);
+                    const status = report?.rules.find((s) => s.rule.action === kind);
+                    return (
+                        <div key={kind} className="space-y-2">
+                            <div className="flex items-center justify-between gap-4">
+                                <p className="text-sm font-bold text-slate-900 dark:text-white">{label}</p>
+                                {toggle(!!rule, () => setRule(kind, rule ? null : { ...initial, interval: 'weekly' }))}
+                            </div>
+                            {rule && (
+                                <div className="flex flex-wrap items-center gap-3 text-sm text-slate-500 dark:text-white/50">
+                                    <select
+                                        value={rule.interval}
+                                        onChange={(e) => setRule(kind, { ...rule, interval: e.target.value as RuleInterval })}
+                                        className="bg-slate-100 dark:bg-white/5 border border-app-border rounded-lg px-3 py-2 text-sm"
+                                    >
+                                        <option value="daily">Daily</option>
+                                        <option value="weekly">Weekly</option>
+                                        <option value="monthly">Monthly</option>
+                                    </select>
+                                    {rule.action === 'trim_package_cache' && (
+                                        <label className="flex items-center gap-2">
+                                            keep
+                                            <input
+                                                type="number"
+                                                min={0}
+                                                defaultValue={rule.keep}
+                                                onBlur={(e) => setRule(kind, { ...rule, keep: Math.max(0, parseInt(e.target.value, 10) || 0) })}
+                                                className={inputClass}
+                                            />
+                                            versions
+                                        </label>
+                                    )}
+                                    {rule.action === 'clean_build_cache' && (
+                                        <label className="flex items-center gap-2">
+                                            over
+                                            <input
+                                                type="number"
+                                                min={1}
+                                                defaultValue={Math.round(rule.over_bytes / GIB)}
+                                                onBlur={(e) => setRule(kind, { ...rule, over_bytes: Math.max(1, parseInt(e.target.value, 10) || 1) * GIB })}
+                                                className={inputClass}
+                                            />
+                                            GiB
+                                        </label>
+                                    )}
+                                </div>
+                            )}
+                            {status?.last_run && (
+                                <p className={clsx("text-xs", status.last_run.error ? "text-red-500" : "text-slate-400 dark:text-white/40")}>
+                                    Last run {describeRun(status.last_run)}
+                                </p>
+                            )}
+                        </div>
+                    );
+                })}
+                <div className="flex items-center justify-between gap-6 pt-2">
+                    <p className="text-sm text-slate-500 dark:text-white/50">Dry run (report only)</p>
+                    {toggle(policy.dry_run, () => save({ ...policy, dry_run: !policy.dry_run }))}
+                </div>
+                <button
+                    onClick={preview}
+                    disabled={previewing || policy.rules.length === 0}
+                    className="px-3 py-2 rounded-lg bg-slate-100 dark:bg-white/10 hover:bg-slate-200 dark:hover:bg-white/20 text-sm font-bold flex items-center gap-2 disabled:opacity-50"
+                >
+                    {previewing ? <Loader2 size={16} className="animate-spin" /> : <Eye size={16} />} Preview cleanup
+                </button>
+            </div>
+        </section>
+    );
+}
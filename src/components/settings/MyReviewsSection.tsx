@@ -0,0 +1,105 @@
+import React from 'react';
+import { Check, PenLine, Star, Trash2, X } from 'lucide-react';
+import { invoke } from '@tauri-apps/api/core';
+import { useToast } from '../../context/ToastContext';
+import { LocalReview } from '../../types/alpm';
+
+/** Reviews saved under this user's reviewer identity, with edit and delete. */
+export default function MyReviewsSection() {
+    const { error } = useToast();
+    const [reviews, setReviews] = React.useState<LocalReview[]>([]);
+    const [editing, setEditing] = React.useState<string | null>(null);
+    const [stars, setStars] = React.useState(5);
+    const [summary, setSummary] = React.useState('');
+
+    const refresh = () => {
+        invoke<LocalReview[]>('get_my_reviews').then(setReviews).catch(() => { });
+    };
+
+    React.useEffect(refresh, []);
+
+    const startEdit = (review: LocalReview) => {
+        setEditing(review.id);
+        setStars(Math.max(1, Math.round(review.rating / 20)));
+        setSummary(review.summary);
+    };
+
+    const saveEdit = async (review: LocalReview) => {
+        try {
+            await invoke('update_review', { id: review.id, rating: stars, summary, description: review.description });
+            setEditing(null);
+            refresh();
+        } catch (e) {
+            error(String(e));
+        }
+    };
+
+    const remove = async (review: LocalReview) => {
+        try {
+            await invoke('delete_review', { id: review.id });
+            refresh();
+        } catch (e) {
+            error(String(e));
+        }
+    };
+
+    if (reviews.length === 0) return null;
+
+    return (
+        <section className="space-y-4">
+            <h2 className="text-lg font-bold text-slate-900 dark:text-white flex items-center gap-2">
+                <PenLine size={20} className="text-amber-500" />
+                My Reviews
+            </h2>
+            <div className="p-6 bg-app-card/50 dark:bg-white/5 border border-app-border rounded-2xl">
+                <ul className="space-y-3">
+                    {reviews.map((review) => (
+                        <li key={review.id} className="flex items-center justify-between gap-3 text-sm">
+                            {editing === review.id ? (
+                                <div className="flex-1 flex flex-wrap items-center gap-2">
+                                    <div className="flex">
+                                        {[1, 2, 3, 4, 5].map((s) => (
+                                            <Star key={s} size={16} onClick={() => setStars(s)} fill={s <= stars ? '#fbbf24' : 'none'} className={s <= stars ? 'text-amber-400 cursor-pointer' : 'text-slate-400 cursor-pointer'} />
+                                        ))}
+                                    </div>
+                                    <input
+                                        value={summary}
+                                        onChange={(e) => setSummary(e.target.value)}
+                                        className="flex-1 min-w-[10rem] text-sm px-3 py-1.5 rounded-lg bg-slate-100 dark:bg-white/5 border border-black/5 dark:border-white/10"
+                                    />
+                                </div>
+                            ) : (
+                                <div className="min-w-0">
+                                    <span className="font-bold text-slate-900 dark:text-white">{review.app_id}</span>
+                                    <span className="text-amber-500"> · {'★'.repeat(Math.round(review.rating / 20))}</span>
+                                    <span className="block text-slate-500 dark:text-white/50 truncate">{review.summary}</span>
+                                </div>
+                            )}
+                            <div className="flex shrink-0">
+                                {editing === review.id ? (
+                                    <>
+                                        <button onClick={() => saveEdit(review)} disabled={!summary.trim()} className="p-2 rounded-lg text-green-500 hover:bg-green-500/10 disabled:opacity-50" title="Save">
+                                            <Check size={16} />
+                                        </button>
+                                        <button onClick={() => setEditing(null)} className="p-2 rounded-lg text-slate-500 hover:bg-slate-500/10" title="Cancel">
+                                            <X size={16} />
+                                        </button>
+                                    </>
+                                ) : (
+                                    <>
+                                        <button onClick={() => startEdit(review)} className="p-2 rounded-lg text-slate-500 hover:bg-slate-500/10" title="Edit review">
+                                            <PenLine size={16} />
+                                        </button>
+                                        <button onClick={() => remove(review)} className="p-2 rounded-lg text-red-500 hover:bg-red-500/10" title="Delete review">
+                                            <Trash2 size={16} />
+                                        </button>
+                                    </>
+                                )}
+                            </div>
+                        </li>
+                    ))}
+                </ul>
+            </div>
+        </section>
+    );
+}
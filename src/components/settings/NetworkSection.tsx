@@ -0,0 +1,141 @@
+import React from 'react';
+import { Globe, Loader2 } from 'lucide-react';
+import { clsx } from 'clsx';
+import { invoke } from '@tauri-apps/api/core';
+import { useToast } from '../../context/ToastContext';
+import { useErrorService } from '../../context/ErrorContext';
+import { useSessionPassword } from '../../context/useSessionPassword';
+import { useAppStore } from '../../store/internal_store';
+import { ConnectivityState, NetworkSettings } from '../../types/alpm';
+import { useConnectivity } from '../../hooks/useOnlineStatus';
+
+/** Proxy, bandwidth cap and IPv4-only for every download, the helper's package downloads included. */
+export default function NetworkSection() {
+    const { success } = useToast();
+    const errorService = useErrorService();
+    const { requestSessionPassword } = useSessionPassword();
+    const reducePasswordPrompts = useAppStore((s) => s.reducePasswordPrompts);
+    const [proxy, setProxy] = React.useState('');
+    const [rateLimit, setRateLimit] = React.useState('');
+    const [ipv4Only, setIpv4Only] = React.useState(false);
+    const [busy, setBusy] = React.useState(false);
+    const connectivity = useConnectivity();
+
+    const toggleOffline = async () => {
+        try {
+            const next = await invoke<ConnectivityState>('set_offline_mode', { enabled: !connectivity.forced });
+            if (next.forced) {
+                success('Offline mode on: cached data only, installs are queued.');
+            } else if (next.offline) {
+                success('Offline mode off, but the network is still unreachable.');
+            } else {
+                success('Back online.');
+            }
+        } catch (e) {
+            errorService.reportError(e as Error | string);
+        }
+    };
+
+    const show = (settings: NetworkSettings) => {
+        setProxy(settings.proxy ?? '');
+        setRateLimit(settings.rate_limit_kbps ? String(settings.rate_limit_kbps) : '');
+        setIpv4Only(settings.ipv4_only);
+    };
+
+    React.useEffect(() => {
+        invoke<NetworkSettings>('get_network_settings').then(show).catch(() => { });
+    }, []);
+
+    const save = async () => {
+        const limit = parseInt(rateLimit, 10);
+        const settings: NetworkSettings = {
+            proxy: proxy.trim() || null,
+            rate_limit_kbps: Number.isFinite(limit) && limit > 0 ? limit : null,
+            ipv4_only: ipv4Only,
+        };
+        setBusy(true);
+        try {
+            const pwd = reducePasswordPrompts ? await requestSessionPassword() : null;
+            show(await invoke<NetworkSettings>('set_network_settings', { settings, password: pwd }));
+            success('Network settings saved.');
+        } catch (e) {
+            errorService.reportError(e as Error | string);
+        } finally {
+            setBusy(false);
+        }
+    };
+
+    return (
+        <section className="space-y-4">
+            <h2 className="text-lg font-bold text-slate-900 dark:text-white flex items-center gap-2">
+                <Globe size={20} className="text-indigo-500" />
+                Network
+            </h2>
+            <div className="p-6 bg-app-card/50 dark:bg-white/5 border border-app-border rounded-2xl space-y-4">
+                <p className="text-sm text-slate-500 dark:text-white/50 max-w-md leading-relaxed">
+                    Used for store data, screenshots and package downloads. Leave the proxy empty to use the system's proxy variables. Saving asks for authorization so package downloads pick the settings up too.
+                </p>
+                <div className="flex items-center justify-between gap-6">
+                    <p className="text-sm text-slate-500 dark:text-white/50">HTTP(S) proxy</p>
+                    <input
+                        value={proxy}
+                        onChange={(e) => setProxy(e.target.value)}
+                        placeholder="http://proxy.example:3128"
+                        className="w-72 text-sm px-3 py-2 rounded-lg bg-slate-100 dark:bg-white/5 border border-app-border"
+                    />
+                </div>
+                <div className="flex items-center justify-between gap-6">
+                    <p className="text-sm text-slate-500 dark:text-white/50">Download limit (KiB/s, empty for none)</p>
+                    <input
+                        value={rateLimit}
+                        onChange={(e) => setRateLimit(e.target.value.replace(/[^0-9]/g, ''))}
+                        inputMode="numeric"
+                        placeholder="Unlimited"
+                        className="w-32 text-sm px-3 py-2 rounded-lg bg-slate-100 dark:bg-white/5 border border-app-border"
+                    />
+                </div>
+                <div className="flex items-center justify-between gap-6">
+                    <p className="text-sm text-slate-500 dark:text-white/50">Connect over IPv4 only</p>
+                    <button
+                        onClick={() => setIpv4Only(!ipv4Only)}
+                        className={clsx(
+                            "relative w-14 h-8 rounded-full p-1 transition-all duration-300 shrink-0",
+                            ipv4Only ? "bg-blue-600 shadow-lg shadow-blue-600/20" : "bg-slate-200 dark:bg-white/10"
+                        )}
+                    >
+                        <div className={clsx("w-6 h-6 bg-white rounded-full transition-transform duration-300 shadow-sm", ipv4Only ? "translate-x-6" : "translate-x-0")} />
+                    </button>
+                </div>
+                <div className="flex items-center justify-between gap-6">
+                    <div>
+                        <p className="text-sm text-slate-500 dark:text-white/50">Offline mode</p>
+                        <p className="text-xs text-slate-400 dark:text-white/30 max-w-md">
+                            {connectivity.offline && !connectivity.forced
+                                ? 'No network right now; the store is already working from its caches.'
+                                : 'Browse cached data only and queue installs until you turn it off.'}
+                        </p>
+                    </div>
+                    <button
+                        onClick={toggleOffline}
+                        className={clsx(
+                            "relative w-14 h-8 rounded-full p-1 transition-all duration-300 shrink-0",
+                            connectivity.forced ? "bg-amber-500 shadow-lg shadow-amber-500/20" : "bg-slate-200 dark:bg-white/10"
+                        )}
+                    >
+                        <div className={clsx("w-6 h-6 bg-white rounded-full transition-transform duration-300 shadow-sm", connectivity.forced ? "translate-x-6" : "translate-x-0")} />
+                    </button>
+                </div>
+                <div className="flex justify-end">
+                    <button
+                        onClick={save}
+                        disabled={busy}
+                        className="px-4 py-2 rounded-xl bg-blue-600 text-white text-sm font-bold hover:bg-blue-500 disabled:opacity-50 flex items-center gap-2"
+                    >
+                        {busy && <Loader2 size={16} className="animate-spin" />}
+                        Save
+                    </button>
+                </div>
+            </div>
+        </section>
+    );
+}
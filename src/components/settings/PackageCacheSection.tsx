@@ -0,0 +1,111 @@
+import React from 'react';
+import { HardDrive, Loader2 } from 'lucide-react';
+import { clsx } from 'clsx';
+import { invoke } from '@tauri-apps/api/core';
+import { useToast } from '../../context/ToastContext';
+import { useErrorService } from '../../context/ErrorContext';
+import { useSessionPassword } from '../../context/useSessionPassword';
+import { useAppStore } from '../../store/internal_store';
+import { CacheCleanReport } from '../../types/alpm';
+
+const KEEP_OPTIONS = [0, 1, 2, 3];
+
+const mib = (bytes: number) => (bytes / (1024 * 1024)).toFixed(1);
+
+/** paccache-style cleanup of /var/cache/pacman/pkg with a preview before anything is deleted. */
+export default function PackageCacheSection() {
+    const { success } = useToast();
+    const errorService = useErrorService();
+    const { requestSessionPassword } = useSessionPassword();
+    const reducePasswordPrompts = useAppStore((s) => s.reducePasswordPrompts);
+    const [keep, setKeep] = React.useState(3);
+    const [uninstalledOnly, setUninstalledOnly] = React.useState(false);
+    const [preview, setPreview] = React.useState<CacheCleanReport | null>(null);
+    const [busy, setBusy] = React.useState(false);
+
+    const run = async (dryRun: boolean) => {
+        setBusy(true);
+        try {
+            // The preview is computed without the helper, so it needs no password
+            const pwd = reducePasswordPrompts && !dryRun ? await requestSessionPassword() : null;
+            const report = await invoke<CacheCleanReport>('clean_package_cache', {
+                keep,
+                uninstalledOnly,
+                dryRun,
+                password: pwd,
+            });
+            if (dryRun) {
+                setPreview(report);
+            } else {
+                setPreview(null);
+                success(`Removed ${report.removed.length} cached packages, freed ${mib(report.reclaimed_bytes)} MiB.`);
+            }
+        } catch (e) {
+            errorService.reportError(e as Error | string);
+        } finally {
+            setBusy(false);
+        }
+    };
+
+    return (
+        <section className="space-y-4">
+            <h2 className="text-lg font-bold text-slate-900 dark:text-white flex items-center gap-2">
+                <HardDrive size={20} className="text-orange-500" />
+                Package Cache
+            </h2>
+            <div className="p-6 bg-app-card/50 dark:bg-white/5 border border-app-border rounded-2xl space-y-4">
+                <p className="text-sm text-slate-500 dark:text-white/50 max-w-md leading-relaxed">
+                    Downloaded packages pile up in /var/cache/pacman/pkg. Keep a few recent versions of each for downgrades and remove the rest. The installed version is always kept.
+                </p>
+                <div className="flex items-center justify-between gap-6">
+                    <p className="text-sm text-slate-500 dark:text-white/50">Versions to keep per package</p>
+                    <select
+                        value={keep}
+                        onChange={(e) => { setKeep(Number(e.target.value)); setPreview(null); }}
+                        className="text-sm p-2 rounded-lg bg-slate-100 dark:bg-white/10 text-slate-900 dark:text-white border border-app-border"
+                    >
+                        {KEEP_OPTIONS.map(n => (
+                            <option key={n} value={n}>{n === 0 ? 'Installed only' : n}</option>
+                        ))}
+                    </select>
+                </div>
+                <div className="flex items-center justify-between gap-6">
+                    <p className="text-sm text-slate-500 dark:text-white/50">Only packages that are no longer installed</p>
+                    <button
+                        onClick={() => { setUninstalledOnly(!uninstalledOnly); setPreview(null); }}
+                        className={clsx(
+                            "relative w-14 h-8 rounded-full p-1 transition-all duration-300 shrink-0",
+                            uninstalledOnly ? "bg-blue-600 shadow-lg shadow-blue-600/20" : "bg-slate-200 dark:bg-white/10"
+                        )}
+                    >
+                        <div className={clsx("w-6 h-6 bg-white rounded-full transition-transform duration-300 shadow-sm", uninstalledOnly ? "translate-x-6" : "translate-x-0")} />
+                    </button>
+                </div>
+                {preview && (
+                    <p className="text-sm text-slate-700 dark:text-white/70">
+                        {preview.removed.length === 0
+                            ? 'Nothing to remove.'
+                            : `${preview.removed.length} files would be removed, freeing ${mib(preview.reclaimed_bytes)} MiB. ${preview.kept} stay.`}
+                    </p>
+                )}
+                <div className="flex justify-end gap-3">
+                    <button
+                        onClick={() => run(true)}
+                        disabled={busy}
+                        className="px-4 py-2 rounded-xl text-sm font-bold bg-slate-100 dark:bg-white/10 text-slate-700 dark:text-white disabled:opacity-50"
+                    >
+                        Preview
+                    </button>
+                    <button
+                        onClick={() => run(false)}
+                        disabled={busy || (preview !== null && preview.removed.length === 0)}
+                        className="px-4 py-2 rounded-xl text-sm font-bold bg-orange-600 text-white disabled:opacity-50 flex items-center gap-2"
+                    >
+                        {busy && <Loader2 size={14} className="animate-spin" />}
+                        Clean Cache
+                    </button>
+                </div>
+            </div>
+        </section>
+    );
+}
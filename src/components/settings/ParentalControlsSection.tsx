@@ -0,0 +1,52 @@
+import React from 'react';
+import { Baby } from 'lucide-react';
+import { invoke } from '@tauri-apps/api/core';
+import { useToast } from '../../context/ToastContext';
+import { useErrorService } from '../../context/ErrorContext';
+
+const AGE_LIMITS = [3, 7, 12, 16, 18];
+
+/** Hide apps whose OARS content rating is above an age limit from search and browsing. */
+export default function ParentalControlsSection() {
+    const { success } = useToast();
+    const errorService = useErrorService();
+    const [maxAge, setMaxAge] = React.useState<number | null>(null);
+
+    React.useEffect(() => {
+        invoke<number | null>('get_parental_controls').then(setMaxAge).catch(() => { });
+    }, []);
+
+    const save = async (value: number | null) => {
+        try {
+            await invoke('set_parental_controls', { maxAge: value });
+            setMaxAge(value);
+            success(value === null ? 'Parental controls off.' : `Hiding apps rated above ${value}+.`);
+        } catch (e) {
+            errorService.reportError(e as Error | string);
+        }
+    };
+
+    return (
+        <section className="space-y-4">
+            <h2 className="text-lg font-bold text-slate-900 dark:text-white flex items-center gap-2">
+                <Baby size={20} className="text-amber-500" />
+                Parental Controls
+            </h2>
+            <div className="p-6 bg-app-card/50 dark:bg-white/5 border border-app-border rounded-2xl flex items-center justify-between gap-6">
+                <p className="text-sm text-slate-500 dark:text-white/50 max-w-md leading-relaxed">
+                    Hide apps whose content rating (OARS) is for older ages. Apps that publish no rating are still shown.
+                </p>
+                <select
+                    value={maxAge === null ? '' : String(maxAge)}
+                    onChange={(e) => save(e.target.value === '' ? null : parseInt(e.target.value, 10))}
+                    className="text-sm px-3 py-2 rounded-lg bg-slate-100 dark:bg-white/5 border border-app-border"
+                >
+                    <option value="">Off</option>
+                    {AGE_LIMITS.map((age) => (
+                        <option key={age} value={age}>Up to {age}+</option>
+                    ))}
+                </select>
+            </div>
+        </section>
+    );
+}
@@ -0,0 +1,73 @@
+import React from 'react';
+import { ShieldCheck } from 'lucide-react';
+import { clsx } from 'clsx';
+import { invoke } from '@tauri-apps/api/core';
+import { useToast } from '../../context/ToastContext';
+import { PrivilegedAgentSettings } from '../../types/alpm';
+
+const IDLE_TIMEOUTS = [
+    { secs: 300, label: '5 minutes' },
+    { secs: 600, label: '10 minutes' },
+    { secs: 1800, label: '30 minutes' },
+    { secs: 3600, label: '1 hour' },
+];
+
+/** Opt-in persistent privileged agent, so batches authenticate once. */
+export default function PrivilegedAgentSection() {
+    const { error } = useToast();
+    const [settings, setSettings] = React.useState<PrivilegedAgentSettings | null>(null);
+
+    React.useEffect(() => {
+        invoke<PrivilegedAgentSettings>('get_privileged_agent_settings').then(setSettings).catch(() => { });
+    }, []);
+
+    const save = async (next: PrivilegedAgentSettings) => {
+        try {
+            await invoke('set_privileged_agent_settings', { settings: next });
+            setSettings(next);
+        } catch (e) {
+            error(String(e));
+        }
+    };
+
+    if (!settings) return null;
+
+    return (
+        <section className="space-y-4">
+            <h2 className="text-lg font-bold text-slate-900 dark:text-white flex items-center gap-2">
+                <ShieldCheck size={20} className="text-emerald-500" />
+                Stay Authenticated
+            </h2>
+            <div className="p-6 bg-app-card/50 dark:bg-white/5 border border-app-border rounded-2xl space-y-4">
+                <div className="flex items-center justify-between gap-6">
+                    <p className="text-sm text-slate-500 dark:text-white/50 max-w-md leading-relaxed">
+                        After you authenticate once, keep a privileged agent running so installs, updates and retries don't ask again. Only your user can talk to it, and it stops when MonARCH closes or sits idle.
+                    </p>
+                    <button
+                        onClick={() => save({ ...settings, enabled: !settings.enabled })}
+                        className={clsx(
+                            "relative w-14 h-8 rounded-full p-1 transition-all duration-300 shrink-0",
+                            settings.enabled ? "bg-blue-600 shadow-lg shadow-blue-600/20" : "bg-slate-200 dark:bg-white/10"
+                        )}
+                    >
+                        <div className={clsx("w-6 h-6 bg-white rounded-full transition-transform duration-300 shadow-sm", settings.enabled ? "translate-x-6" : "translate-x-0")} />
+                    </button>
+                </div>
+                {settings.enabled && (
+                    <div className="flex items-center justify-between gap-6">
+                        <p className="text-sm text-slate-500 dark:text-white/50">Stop the agent after</p>
+                        <select
+                            value={settings.idle_timeout_secs}
+                            onChange={(e) => save({ ...settings, idle_timeout_secs: Number(e.target.value) })}
+                            className="text-sm p-2 rounded-lg bg-slate-100 dark:bg-white/10 text-slate-900 dark:text-white border border-app-border"
+                        >
+                            {IDLE_TIMEOUTS.map(t => (
+                                <option key={t.secs} value={t.secs}>{t.label} idle</option>
+                            ))}
+                        </select>
+                    </div>
+                )}
+            </div>
+        </section>
+    );
+}
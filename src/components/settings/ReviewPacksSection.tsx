@@ -0,0 +1,93 @@
+import React from 'react';
+import { Download, MessageSquareQuote, Trash2, Upload } from 'lucide-react';
+import { invoke } from '@tauri-apps/api/core';
+import { useToast } from '../../context/ToastContext';
+import { ReviewPackInfo } from '../../types/alpm';
+
+/** GPG-signed review packs: export your own reviews, import curated ones. */
+export default function ReviewPacksSection() {
+    const { success, error } = useToast();
+    const [packs, setPacks] = React.useState<ReviewPackInfo[]>([]);
+    const [name, setName] = React.useState('');
+
+    const refresh = () => {
+        invoke<ReviewPackInfo[]>('list_review_packs').then(setPacks).catch(() => { });
+    };
+
+    React.useEffect(refresh, []);
+
+    const exportPack = async () => {
+        try {
+            const path = await invoke<string | null>('export_review_pack', { name });
+            if (path) success(`Review pack saved to ${path}`);
+        } catch (e) {
+            error(String(e));
+        }
+    };
+
+    const importPack = async () => {
+        try {
+            const info = await invoke<ReviewPackInfo | null>('import_review_pack');
+            if (info) {
+                success(`Imported ${info.review_count} reviews from "${info.origin.name}" (signed by ${info.origin.signer_uid})`);
+                refresh();
+            }
+        } catch (e) {
+            error(String(e));
+        }
+    };
+
+    const removePack = async (pack: ReviewPackInfo) => {
+        try {
+            await invoke('remove_review_pack', { name: pack.origin.name, signer: pack.origin.signer });
+            refresh();
+        } catch (e) {
+            error(String(e));
+        }
+    };
+
+    return (
+        <section className="space-y-4">
+            <h2 className="text-lg font-bold text-slate-900 dark:text-white flex items-center gap-2">
+                <MessageSquareQuote size={20} className="text-amber-500" />
+                Review Packs
+            </h2>
+            <div className="p-6 bg-app-card/50 dark:bg-white/5 border border-app-border rounded-2xl space-y-4">
+                <p className="text-sm text-slate-500 dark:text-white/50 leading-relaxed">
+                    Share your reviews as a GPG-signed pack, or import packs from people you trust. Imports need the signer's key in your GPG keyring.
+                </p>
+                <div className="flex flex-wrap items-center gap-3">
+                    <input
+                        type="text"
+                        placeholder="Pack name"
+                        value={name}
+                        onChange={(e) => setName(e.target.value)}
+                        className="flex-1 min-w-[10rem] text-sm px-3 py-2 rounded-lg bg-slate-100 dark:bg-white/5 border border-black/5 dark:border-white/10"
+                    />
+                    <button onClick={exportPack} disabled={!name.trim()} className="px-3 py-2 rounded-lg bg-slate-100 dark:bg-white/10 hover:bg-slate-200 dark:hover:bg-white/20 text-sm font-bold flex items-center gap-2 disabled:opacity-50">
+                        <Upload size={16} /> Export
+                    </button>
+                    <button onClick={importPack} className="px-3 py-2 rounded-lg bg-slate-100 dark:bg-white/10 hover:bg-slate-200 dark:hover:bg-white/20 text-sm font-bold flex items-center gap-2">
+                        <Download size={16} /> Import
+                    </button>
+                </div>
+                {packs.length > 0 && (
+                    <ul className="space-y-2">
+                        {packs.map((pack) => (
+                            <li key={`${pack.origin.signer}-${pack.origin.name}`} className="flex items-center justify-between gap-3 text-sm">
+                                <div className="min-w-0">
+                                    <span className="font-bold text-slate-900 dark:text-white">{pack.origin.name}</span>
+                                    <span className="text-slate-500 dark:text-white/50"> · {pack.review_count} reviews · {pack.origin.signer_uid}</span>
+                                    <code className="block text-xs text-slate-400 dark:text-white/30 truncate">{pack.origin.signer}</code>
+                                </div>
+                                <button onClick={() => removePack(pack)} className="p-2 rounded-lg text-red-500 hover:bg-red-500/10 shrink-0" title="Remove pack">
+                                    <Trash2 size={16} />
+                                </button>
+                            </li>
+                        ))}
+                    </ul>
+                )}
+            </div>
+        </section>
+    );
+}
@@ -0,0 +1,95 @@
+import React from 'react';
+import { BatteryWarning } from 'lucide-react';
+import { clsx } from 'clsx';
+import { invoke } from '@tauri-apps/api/core';
+import { useToast } from '../../context/ToastContext';
+import { UpdateConditions, UpdateConditionsInfo } from '../../types/alpm';
+
+/** Hold background syncs and auto updates on low battery or a metered connection. */
+export default function UpdateConditionsSection() {
+    const { error } = useToast();
+    const [info, setInfo] = React.useState<UpdateConditionsInfo | null>(null);
+    const [threshold, setThreshold] = React.useState('');
+
+    const show = (next: UpdateConditionsInfo) => {
+        setInfo(next);
+        setThreshold(String(next.conditions.min_battery_percent));
+    };
+
+    React.useEffect(() => {
+        invoke<UpdateConditionsInfo>('get_update_conditions').then(show).catch(() => { });
+    }, []);
+
+    const save = async (patch: Partial<UpdateConditions>) => {
+        if (!info) return;
+        try {
+            show(await invoke<UpdateConditionsInfo>('set_update_conditions', { conditions: { ...info.conditions, ...patch } }));
+        } catch (e) {
+            error(String(e));
+        }
+    };
+
+    const saveThreshold = () => {
+        const value = parseInt(threshold, 10);
+        if (!info || !Number.isFinite(value) || value === info.conditions.min_battery_percent) {
+            if (info) setThreshold(String(info.conditions.min_battery_percent));
+            return;
+        }
+        save({ min_battery_percent: Math.min(100, Math.max(0, value)) });
+    };
+
+    if (!info) return null;
+
+    const toggle = (on: boolean, onClick: () => void) => (
+        <button
+            onClick={onClick}
+            className={clsx(
+                "relative w-14 h-8 rounded-full p-1 transition-all duration-300 shrink-0",
+                on ? "bg-blue-600 shadow-lg shadow-blue-600/20" : "bg-slate-200 dark:bg-white/10"
+            )}
+        >
+            <div className={clsx("w-6 h-6 bg-white rounded-full transition-transform duration-300 shadow-sm", on ? "translate-x-6" : "translate-x-0")} />
+        </button>
+    );
+
+    return (
+        <section className="space-y-4">
+            <h2 className="text-lg font-bold text-slate-900 dark:text-white flex items-center gap-2">
+                <BatteryWarning size={20} className="text-amber-500" />
+                Update Conditions
+            </h2>
+            <div className="p-6 bg-app-card/50 dark:bg-white/5 border border-app-border rounded-2xl space-y-4">
+                <p className="text-sm text-slate-500 dark:text-white/50 max-w-md leading-relaxed">
+                    Background update checks and auto updates wait until conditions improve, and the Updates page warns before a full system update.
+                    {info.hold_reason && <span className="block mt-1 font-bold text-amber-600 dark:text-amber-400">Holding now: {info.hold_reason}.</span>}
+                </p>
+                <div className="flex items-center justify-between gap-6">
+                    <p className="text-sm text-slate-500 dark:text-white/50">Wait while on battery</p>
+                    {toggle(info.conditions.defer_on_battery, () => save({ defer_on_battery: !info.conditions.defer_on_battery }))}
+                </div>
+                <div className="flex items-center justify-between gap-6">
+                    <p className="text-sm text-slate-500 dark:text-white/50">
+                        Battery level below which to wait (%)
+                        {info.state.battery_percent !== null && ` · now ${Math.round(info.state.battery_percent)}%`}
+                    </p>
+                    <input
+                        value={threshold}
+                        onChange={(e) => setThreshold(e.target.value.replace(/[^0-9]/g, ''))}
+                        onBlur={saveThreshold}
+                        onKeyDown={(e) => e.key === 'Enter' && saveThreshold()}
+                        disabled={!info.conditions.defer_on_battery}
+                        inputMode="numeric"
+                        className="w-20 text-sm px-3 py-2 rounded-lg bg-slate-100 dark:bg-white/5 border border-app-border disabled:opacity-50"
+                    />
+                </div>
+                <div className="flex items-center justify-between gap-6">
+                    <p className="text-sm text-slate-500 dark:text-white/50">
+                        Wait on a metered connection
+                        {info.state.metered && ' · metered now'}
+                    </p>
+                    {toggle(info.conditions.defer_on_metered, () => save({ defer_on_metered: !info.conditions.defer_on_metered }))}
+                </div>
+            </div>
+        </section>
+    );
+}
@@ -0,0 +1,81 @@
+import React from 'react';
+import { ShieldCheck } from 'lucide-react';
+import { clsx } from 'clsx';
+import { invoke } from '@tauri-apps/api/core';
+import { useToast } from '../../context/ToastContext';
+import { UpdateCategory, UpdatePolicies, UpdatePolicy } from '../../types/alpm';
+
+const CATEGORIES: { id: UpdateCategory; label: string; hint: string }[] = [
+    { id: 'security', label: 'Security', hint: 'openssl, openssh, sudo, gnupg, ca-certificates…' },
+    { id: 'browsers', label: 'Browsers', hint: 'Firefox, Chromium, Brave, LibreWolf…' },
+    { id: 'kernels', label: 'Kernels', hint: 'linux, linux-lts, linux-zen, linux-firmware…' },
+    { id: 'drivers', label: 'Drivers', hint: 'NVIDIA, Mesa, Vulkan, DKMS modules…' },
+    { id: 'other', label: 'Everything else', hint: 'Applications and libraries' },
+];
+
+const POLICIES: { id: UpdatePolicy; label: string }[] = [
+    { id: 'auto', label: 'Auto' },
+    { id: 'notify', label: 'Notify' },
+    { id: 'hold', label: 'Hold' },
+];
+
+/** Per-category update policies enforced by the background updater. */
+export default function UpdatePoliciesSection() {
+    const { error } = useToast();
+    const [policies, setPolicies] = React.useState<UpdatePolicies | null>(null);
+
+    React.useEffect(() => {
+        invoke<UpdatePolicies>('get_update_policies').then(setPolicies).catch(() => { });
+    }, []);
+
+    const setPolicy = async (category: UpdateCategory, policy: UpdatePolicy) => {
+        if (!policies) return;
+        const next = { ...policies, categories: { ...policies.categories, [category]: policy } };
+        try {
+            setPolicies(await invoke<UpdatePolicies>('set_update_policies', { policies: next }));
+        } catch (e) {
+            error(String(e));
+        }
+    };
+
+    if (!policies) return null;
+
+    return (
+        <section className="space-y-4">
+            <h2 className="text-lg font-bold text-slate-900 dark:text-white flex items-center gap-2">
+                <ShieldCheck size={20} className="text-emerald-500" />
+                Update Policies
+            </h2>
+            <div className="p-6 bg-app-card/50 dark:bg-white/5 border border-app-border rounded-2xl space-y-4">
+                <p className="text-sm text-slate-500 dark:text-white/50 leading-relaxed">
+                    Auto updates are installed by the background check. Repository updates are installed together, so they only run automatically when every pending one is set to Auto. Held updates always wait for you.
+                </p>
+                {CATEGORIES.map((c) => {
+                    const current = policies.categories[c.id] ?? 'notify';
+                    return (
+                        <div key={c.id} className="flex items-center justify-between gap-6">
+                            <div>
+                                <p className="text-sm font-bold text-slate-900 dark:text-white">{c.label}</p>
+                                <p className="text-xs text-slate-500 dark:text-white/50">{c.hint}</p>
+                            </div>
+                            <div className="flex rounded-lg bg-slate-100 dark:bg-white/5 p-1 shrink-0">
+                                {POLICIES.map((p) => (
+                                    <button
+                                        key={p.id}
+                                        onClick={() => setPolicy(c.id, p.id)}
+                                        className={clsx(
+                                            "px-3 py-1 rounded-md text-xs font-bold transition-all",
+                                            current === p.id ? "bg-white dark:bg-white/20 shadow-sm text-slate-900 dark:text-white" : "text-slate-500 dark:text-white/50"
+                                        )}
+                                    >
+                                        {p.label}
+                                    </button>
+                                ))}
+                            </div>
+                        </div>
+                    );
+                })}
+            </div>
+        </section>
+    );
+}
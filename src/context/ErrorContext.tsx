@@ -9,7 +9,7 @@ import { friendlyError, FriendlyError } from '../utils/friendlyError';
 export type ErrorSeverity = 'info' | 'warning' | 'error' | 'critical';
 
 /**
- * Backend ClassifiedError structure (matches Rust error_classifier.rs)
+ * Backend ClassifiedError structure (matches Rust monarch_types::errors)
  */
 export interface ClassifiedError {
     kind: string;
@@ -0,0 +1,95 @@
+import { createContext, useState, useCallback, useRef, ReactNode } from 'react';
+import { FileText, ExternalLink } from 'lucide-react';
+import { invoke } from '@tauri-apps/api/core';
+import { openUrl } from '@tauri-apps/plugin-opener';
+import { useErrorService } from './ErrorContext';
+import { EulaPrompt } from '../types/alpm';
+
+interface EulaContextType {
+    /** Ask for every license agreement `names` still need; resolves to the names the user may install. */
+    confirmLicenses: (names: string[]) => Promise<string[]>;
+}
+
+export const EulaContext = createContext<EulaContextType | undefined>(undefined);
+
+export function EulaProvider({ children }: { children: ReactNode }) {
+    const errorService = useErrorService();
+    const [prompt, setPrompt] = useState<EulaPrompt | null>(null);
+    const resolveRef = useRef<((accepted: boolean) => void) | null>(null);
+
+    const ask = useCallback((p: EulaPrompt): Promise<boolean> => new Promise((resolve) => {
+        resolveRef.current = resolve;
+        setPrompt(p);
+    }), []);
+
+    const confirmLicenses = useCallback(async (names: string[]): Promise<string[]> => {
+        const allowed: string[] = [];
+        // One at a time: accepting a shared agreement (nvidia + nvidia-utils) clears the next prompt
+        for (const name of names) {
+            const pending = await invoke<EulaPrompt | null>('get_eula_prompt', { pkgName: name });
+            if (!pending) {
+                allowed.push(name);
+                continue;
+            }
+            if (!(await ask(pending))) continue;
+            try {
+                await invoke('accept_eula', { pkgName: name, eulaId: pending.eula_id });
+                allowed.push(name);
+            } catch (e) {
+                errorService.reportError(e as Error | string);
+            }
+        }
+        return allowed;
+    }, [ask, errorService]);
+
+    const answer = useCallback((accepted: boolean) => {
+        resolveRef.current?.(accepted);
+        resolveRef.current = null;
+        setPrompt(null);
+    }, []);
+
+    return (
+        <EulaContext.Provider value={{ confirmLicenses }}>
+            {children}
+            {prompt && (
+                <div className="fixed inset-0 z-50 flex items-center justify-center bg-black/80 backdrop-blur-sm p-4" role="dialog" aria-modal="true" aria-labelledby="eula-prompt-title">
+                    <div className="bg-app-card border border-app-border rounded-2xl shadow-2xl p-6 max-w-lg w-full space-y-4 animate-in zoom-in-95 duration-200">
+                        <div className="flex items-center gap-3">
+                            <div className="p-2 bg-amber-500/20 rounded-xl">
+                                <FileText size={24} className="text-amber-500" />
+                            </div>
+                            <div>
+                                <h3 id="eula-prompt-title" className="font-bold text-app-fg text-lg">{prompt.title}</h3>
+                                <p className="text-xs text-app-muted mt-0.5">Installing {prompt.package} requires accepting this agreement.</p>
+                            </div>
+                        </div>
+                        <p className="text-sm text-app-fg leading-relaxed">{prompt.summary}</p>
+                        <button
+                            type="button"
+                            onClick={() => openUrl(prompt.url).catch(() => { })}
+                            className="flex items-center gap-1.5 text-sm font-medium text-blue-500 hover:underline"
+                        >
+                            Read the full agreement <ExternalLink size={14} />
+                        </button>
+                        <div className="flex gap-3">
+                            <button
+                                type="button"
+                                onClick={() => answer(false)}
+                                className="flex-1 py-2.5 rounded-xl font-bold text-sm text-app-muted hover:text-app-fg hover:bg-app-fg/5 transition-colors"
+                            >
+                                Decline
+                            </button>
+                            <button
+                                type="button"
+                                onClick={() => answer(true)}
+                                className="flex-1 py-2.5 rounded-xl font-bold text-sm text-white bg-amber-500 hover:bg-amber-600 transition-colors"
+                            >
+                                Accept and install
+                            </button>
+                        </div>
+                    </div>
+                </div>
+            )}
+        </EulaContext.Provider>
+    );
+}
@@ -0,0 +1,8 @@
+import { useContext } from 'react';
+import { EulaContext } from './EulaContext';
+
+export function useEula() {
+    const ctx = useContext(EulaContext);
+    if (!ctx) throw new Error('useEula must be used within EulaProvider');
+    return ctx;
+}
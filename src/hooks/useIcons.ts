@@ -0,0 +1,30 @@
+import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import type { CachedIcon } from '../types/alpm';
+
+/**
+ * Icons for a list of packages in one get_icons_batch call: package name -> asset URL.
+ * Packages without an icon are missing from the map.
+ */
+export function useIcons(names: string[], size?: number): Record<string, string> {
+    const [icons, setIcons] = useState<Record<string, string>>({});
+    const key = names.join('\n');
+
+    useEffect(() => {
+        if (names.length === 0) {
+            setIcons({});
+            return;
+        }
+        let cancelled = false;
+        invoke<Record<string, CachedIcon>>('get_icons_batch', { names, size: size ?? null })
+            .then((found) => {
+                if (cancelled) return;
+                setIcons(Object.fromEntries(Object.entries(found).map(([name, icon]) => [name, icon.url])));
+            })
+            .catch(() => { });
+        return () => { cancelled = true; };
+        // eslint-disable-next-line react-hooks/exhaustive-deps
+    }, [key, size]);
+
+    return icons;
+}
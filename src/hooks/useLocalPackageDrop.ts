@@ -0,0 +1,53 @@
+import { useState, useEffect, useRef } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { getCurrentWebview } from '@tauri-apps/api/webview';
+
+// Matches LocalPackagePreview in commands/local_file.rs
+export interface LocalPackagePreview {
+    path: string;
+    name: string;
+    version: string;
+    description: string | null;
+    url: string | null;
+    arch: string;
+    installed_size: number | null;
+    packager: string | null;
+    depends: string[];
+    optdepends: string[];
+    conflicts: string[];
+    provides: string[];
+    installed_version: string | null;
+}
+
+const PACKAGE_FILE = /\.pkg\.tar(\.(zst|xz|gz))?$/;
+
+/** Previews a .pkg.tar.* file dropped onto the window; the caller confirms and installs it. */
+export function useLocalPackageDrop(onError: (e: string) => void) {
+    const [preview, setPreview] = useState<LocalPackagePreview | null>(null);
+    const [dragging, setDragging] = useState(false);
+    const onErrorRef = useRef(onError);
+    onErrorRef.current = onError;
+
+    useEffect(() => {
+        const unlisten = getCurrentWebview().onDragDropEvent((event) => {
+            const payload = event.payload;
+            if (payload.type === 'enter' || payload.type === 'over') {
+                setDragging(true);
+            } else if (payload.type === 'leave') {
+                setDragging(false);
+            } else if (payload.type === 'drop') {
+                setDragging(false);
+                const path = payload.paths.find((p) => PACKAGE_FILE.test(p));
+                if (!path) return;
+                invoke<LocalPackagePreview>('preview_local_file', { path })
+                    .then(setPreview)
+                    .catch((e) => onErrorRef.current(String(e)));
+            }
+        });
+        return () => {
+            unlisten.then(fn => fn()).catch(() => { });
+        };
+    }, []);
+
+    return { preview, dragging, clear: () => setPreview(null) };
+}
@@ -1,20 +1,39 @@
 import { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import type { ConnectivityState } from '../types/alpm';
 
-export function useOnlineStatus() {
-    const [isOnline, setIsOnline] = useState(navigator.onLine);
+/**
+ * The backend's view of connectivity: offline when the user switched offline mode on or
+ * when its probes reach nothing. Starts from navigator.onLine until the backend answers.
+ */
+export function useConnectivity(): ConnectivityState {
+    const [state, setState] = useState<ConnectivityState>({
+        offline: !navigator.onLine,
+        forced: false,
+        since: null,
+    });
 
     useEffect(() => {
-        const handleOnline = () => setIsOnline(true);
-        const handleOffline = () => setIsOnline(false);
-
-        window.addEventListener('online', handleOnline);
-        window.addEventListener('offline', handleOffline);
+        invoke<ConnectivityState>('get_connectivity').then(setState).catch(() => { });
+        const unlisten = listen<ConnectivityState>('connectivity-changed', (event) => setState(event.payload));
+        // The OS noticing first is a good moment to re-probe
+        const recheck = () => {
+            invoke<ConnectivityState>('check_connectivity').then(setState).catch(() => { });
+        };
+        window.addEventListener('online', recheck);
+        window.addEventListener('offline', recheck);
 
         return () => {
-            window.removeEventListener('online', handleOnline);
-            window.removeEventListener('offline', handleOffline);
+            unlisten.then((fn) => fn()).catch(() => { });
+            window.removeEventListener('online', recheck);
+            window.removeEventListener('offline', recheck);
         };
     }, []);
 
-    return isOnline;
+    return state;
+}
+
+export function useOnlineStatus() {
+    return !useConnectivity().offline;
 }
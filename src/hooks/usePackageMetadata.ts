@@ -15,24 +15,82 @@ export interface AppMetadata {
     description?: string;
 }
 
-// Global Singleton Cache with TTL
-export const metadataCache = new Map<string, { data: AppMetadata, timestamp: number }>();
+// Global Singleton Cache with TTL. `full` entries came from get_metadata; batch entries only
+// carry the card fields (name, summary, icon, screenshots, app id).
+export const metadataCache = new Map<string, { data: AppMetadata, timestamp: number, full?: boolean }>();
 const CACHE_TTL = 5 * 60 * 1000; // 5 minutes
 
 /**
- * Global Batch Executor
+ * Global Batch Executor: cards mounting in the same tick share one hydrate_packages call.
  */
+interface HydratedPackage {
+    app_id?: string;
+    name?: string;
+    summary?: string;
+    icon?: string;
+    screenshots?: string[];
+}
+
+const BATCH_DELAY_MS = 30;
+const MAX_BATCH = 200; // hydrate_packages limit per call
+const CARD_FIELDS = ['name', 'summary', 'icon', 'screenshots'];
+
+let pending = new Map<string, ((meta: HydratedPackage | null) => void)[]>();
+let flushTimer: ReturnType<typeof setTimeout> | null = null;
+
+async function flushBatch() {
+    flushTimer = null;
+    const batch = pending;
+    pending = new Map();
+    const names = [...batch.keys()];
+    for (let i = 0; i < names.length; i += MAX_BATCH) {
+        const chunk = names.slice(i, i + MAX_BATCH);
+        let results: Record<string, HydratedPackage> = {};
+        try {
+            results = await invoke<Record<string, HydratedPackage>>('hydrate_packages', {
+                pkgNames: chunk,
+                fields: CARD_FIELDS
+            });
+        } catch (err) {
+            console.warn('[Metadata] Batch failed:', err);
+        }
+        for (const name of chunk) {
+            const meta = results[name] ?? null;
+            batch.get(name)?.forEach(resolve => resolve(meta));
+        }
+    }
+}
+
+function hydrate(pkgName: string): Promise<HydratedPackage | null> {
+    return new Promise(resolve => {
+        pending.set(pkgName, [...(pending.get(pkgName) ?? []), resolve]);
+        if (!flushTimer) flushTimer = setTimeout(flushBatch, BATCH_DELAY_MS);
+    });
+}
 
+/** Batch result as AppMetadata, or null when it identified nothing. */
+function fromHydrated(pkgName: string, h: HydratedPackage | null): AppMetadata | null {
+    if (!h || (!h.app_id && !h.icon)) return null;
+    return {
+        name: h.name ?? pkgName,
+        pkg_name: pkgName,
+        icon_url: h.icon,
+        app_id: h.app_id ?? pkgName,
+        summary: h.summary,
+        screenshots: h.screenshots ?? []
+    };
+}
 
 /**
  * Hook for fetching package metadata (icons, IDs, screens).
- * Reverted to direct individual fetching to ensure Upstream URL is passed for fallback lookups.
- * (Batching removed to fix regression where URL was lost)
+ * Cards go through the batch command; packages it can't identify fall back to get_metadata,
+ * which also tries the upstream URL. `full` (details page) always uses get_metadata for
+ * maintainer, license and the rest.
  */
-export function usePackageMetadata(pkgName: string, upstreamUrl?: string, skip = false) {
+export function usePackageMetadata(pkgName: string, upstreamUrl?: string, skip = false, full = false) {
     const [metadata, setMetadata] = useState<AppMetadata | null>(() => {
         const cached = metadataCache.get(pkgName);
-        if (cached && (Date.now() - cached.timestamp < CACHE_TTL)) {
+        if (cached && (Date.now() - cached.timestamp < CACHE_TTL) && (cached.full || !full)) {
             return cached.data;
         }
         return null;
@@ -44,22 +102,31 @@ export function usePackageMetadata(pkgName: string, upstreamUrl?: string, skip =
 
         // 1. Check Cache
         const cached = metadataCache.get(pkgName);
-        if (cached && (Date.now() - cached.timestamp < CACHE_TTL)) {
+        if (cached && (Date.now() - cached.timestamp < CACHE_TTL) && (cached.full || !full)) {
             setMetadata(cached.data);
             return;
         }
 
-        // 2. Fetch Directly (No Batching)
+        // 2. Batch for cards, single lookup for details or when the batch found nothing
         let isMounted = true;
         setIsLoading(true);
 
-        invoke<AppMetadata>('get_metadata', {
+        const fetchSingle = () => invoke<AppMetadata>('get_metadata', {
             pkgName,
             upstreamUrl: upstreamUrl || null
-        })
-            .then(data => {
+        }).then(data => ({ data, full: true }));
+
+        const lookup = full
+            ? fetchSingle()
+            : hydrate(pkgName).then(h => {
+                const data = fromHydrated(pkgName, h);
+                return data ? { data, full: false } : fetchSingle();
+            });
+
+        lookup
+            .then(({ data, full: isFull }) => {
                 if (isMounted && data) {
-                    metadataCache.set(pkgName, { data, timestamp: Date.now() });
+                    metadataCache.set(pkgName, { data, timestamp: Date.now(), full: isFull });
                     setMetadata(data);
                 }
             })
@@ -73,7 +140,7 @@ export function usePackageMetadata(pkgName: string, upstreamUrl?: string, skip =
         return () => {
             isMounted = false;
         };
-    }, [pkgName, upstreamUrl, skip]);
+    }, [pkgName, upstreamUrl, skip, full]);
 
     return { metadata, isLoading };
 }
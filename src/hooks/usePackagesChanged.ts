@@ -0,0 +1,35 @@
+import { useEffect } from 'react';
+import type { Dispatch, SetStateAction } from 'react';
+import { listen } from '@tauri-apps/api/event';
+import type { Package } from '../components/PackageCard';
+import type { PackageStateChange } from '../types/alpm';
+
+function applyChange(pkg: Package, changes: Map<string, string | null>): Package {
+    const alternatives = pkg.alternatives?.map(a => applyChange(a, changes));
+    const sourceType = typeof pkg.source === 'string' ? pkg.source : pkg.source.source_type;
+    if (!changes.has(pkg.name) || sourceType === 'flatpak') {
+        return alternatives ? { ...pkg, alternatives } : pkg;
+    }
+    const version = changes.get(pkg.name);
+    return {
+        ...pkg,
+        alternatives,
+        installed: version != null,
+        // Installing exactly this version settles it; otherwise keep what the listing said
+        update_available: version != null && !!pkg.update_available && version !== pkg.version,
+    };
+}
+
+/**
+ * Keep the installed / update badges of a listing current when packages change on the
+ * system, whether MonARCH or pacman in a terminal changed them.
+ */
+export function usePackagesChanged(setPackages: Dispatch<SetStateAction<Package[]>>) {
+    useEffect(() => {
+        const unlisten = listen<PackageStateChange[]>('packages-changed', (event) => {
+            const changes = new Map(event.payload.map(c => [c.name, c.version]));
+            setPackages(prev => prev.map(p => applyChange(p, changes)));
+        });
+        return () => { unlisten.then((fn) => fn()).catch(() => { }); };
+    }, [setPackages]);
+}
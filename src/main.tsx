@@ -8,6 +8,7 @@ import { ToastProvider } from './context/ToastContext';
 import { ErrorProvider } from './context/ErrorContext';
 import { getErrorService } from './context/getErrorService';
 import { SessionPasswordProvider } from './context/SessionPasswordContext';
+import { EulaProvider } from './context/EulaContext';
 
 // Global uncaught errors: report to ErrorService when available (after ErrorProvider mounts)
 if (typeof window !== 'undefined') {
@@ -29,7 +30,9 @@ ReactDOM.createRoot(document.getElementById("root") as HTMLElement).render(
       <ToastProvider>
         <ErrorProvider>
           <SessionPasswordProvider>
-            {isScreenshotLoading ? <LoadingScreen /> : <App />}
+            <EulaProvider>
+              {isScreenshotLoading ? <LoadingScreen /> : <App />}
+            </EulaProvider>
           </SessionPasswordProvider>
         </ErrorProvider>
       </ToastProvider>
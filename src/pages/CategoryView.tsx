@@ -3,12 +3,14 @@ import { useInfiniteScroll } from '../hooks/useInfiniteScroll';
 import { ArrowLeft, LayoutGrid, Filter, Check, ChevronDown } from 'lucide-react';
 import clsx from 'clsx';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import PackageCard, { Package, ChaoticPackage } from '../components/PackageCard';
 import PackageCardSkeleton from '../components/PackageCardSkeleton';
 import EmptyState from '../components/EmptyState';
 import { CATEGORIES } from '../components/CategoryGrid';
 import { useErrorService } from '../context/ErrorContext';
 import { friendlyError } from '../utils/friendlyError';
+import { usePackagesChanged } from '../hooks/usePackagesChanged';
 
 // Multi-Select Dropdown Component
 
@@ -122,10 +124,11 @@ interface PaginatedResponse {
 const CategoryView: React.FC<CategoryViewProps> = ({ category, onBack, onSelectPackage }) => {
     const errorService = useErrorService();
     const [packages, setPackages] = useState<Package[]>([]);
+    usePackagesChanged(setPackages);
     const [totalPackages, setTotalPackages] = useState(0); // Track total available from backend
     const [loading, setLoading] = useState(true);
     const [initialLoad, setInitialLoad] = useState(true); // Track first load vs "load more"
-    const [sortBy, setSortBy] = useState<'featured' | 'name' | 'updated'>('featured');
+    const [sortBy, setSortBy] = useState<'featured' | 'name' | 'updated' | 'popular' | 'top_rated'>('featured');
     const [repoFilter, setRepoFilter] = useState<string[]>(['all']);
     const [page, setPage] = useState(1);
     const [hasMore, setHasMore] = useState(true);
@@ -184,8 +187,8 @@ const CategoryView: React.FC<CategoryViewProps> = ({ category, onBack, onSelectP
         try {
             const res = await invoke<PaginatedResponse>('get_category_packages_paginated', {
                 category,
-                repo_filter: repoFilter,
-                sort_by: sortBy,
+                repoFilter,
+                sortBy,
                 page: currentPage,
                 limit: LIMIT
             });
@@ -211,7 +214,7 @@ const CategoryView: React.FC<CategoryViewProps> = ({ category, onBack, onSelectP
                     return [...prev, ...uniqueNew];
                 });
             }
-            setHasMore(res.packages.length === LIMIT);
+            setHasMore(res.has_more);
             setError(null);
         } catch (e: unknown) {
             const raw = e instanceof Error ? e.message : String(e);
@@ -229,6 +232,13 @@ const CategoryView: React.FC<CategoryViewProps> = ({ category, onBack, onSelectP
         fetchApps(true);
     }, [category, repoFilter, sortBy]);
 
+    // Before the first repo sync there is no catalog to page through; reload once there is
+    useEffect(() => {
+        if (totalPackages > 0) return;
+        const unlisten = listen<number>('repo-index-updated', () => fetchApps(true));
+        return () => { unlisten.then((fn) => fn()).catch(() => { }); };
+    }, [totalPackages, fetchApps]);
+
     // 2. Load More when page increments (but NOT on page 1, which is handled by reset)
     useEffect(() => {
         if (page > 1) {
@@ -344,11 +354,13 @@ const CategoryView: React.FC<CategoryViewProps> = ({ category, onBack, onSelectP
                         <select
                             className="bg-app-subtle border border-app-border rounded-lg px-3 py-1.5 text-sm text-app-fg focus:outline-none focus:border-blue-500 transition-colors"
                             value={sortBy}
-                            onChange={(e) => setSortBy(e.target.value as 'featured' | 'name' | 'updated')}
+                            onChange={(e) => setSortBy(e.target.value as 'featured' | 'name' | 'updated' | 'popular' | 'top_rated')}
                         >
                             <option value="featured">Featured</option>
                             <option value="name">Name (A-Z)</option>
                             <option value="updated">Last Updated</option>
+                            <option value="popular">Most Popular</option>
+                            <option value="top_rated">Top Rated</option>
                         </select>
                     </div>
                 </div>
@@ -0,0 +1,157 @@
+import { useState, useEffect } from 'react';
+import { ArrowRight, HardDrive, Loader2, RefreshCw, ShieldCheck, Wrench } from 'lucide-react';
+import { clsx } from 'clsx';
+import { invoke } from '@tauri-apps/api/core';
+import { useErrorService } from '../context/ErrorContext';
+import { WeeklyDigest } from '../types/alpm';
+
+const formatBytes = (bytes: number) => {
+    const abs = Math.abs(bytes);
+    if (abs >= 1024 ** 3) return `${(bytes / 1024 ** 3).toFixed(2)} GiB`;
+    if (abs >= 1024 ** 2) return `${(bytes / 1024 ** 2).toFixed(1)} MiB`;
+    return `${(bytes / 1024).toFixed(0)} KiB`;
+};
+
+const formatDelta = (bytes: number) => (bytes > 0 ? '+' : '') + formatBytes(bytes);
+
+const formatDate = (secs: number) => new Date(secs * 1000).toLocaleDateString();
+
+export default function DigestPage() {
+    const [digest, setDigest] = useState<WeeklyDigest | null>(null);
+    const [loading, setLoading] = useState(true);
+    const [enabled, setEnabled] = useState(false);
+    const errorService = useErrorService();
+
+    const load = async () => {
+        setLoading(true);
+        try {
+            setDigest(await invoke<WeeklyDigest>('get_weekly_digest'));
+        } catch (e) {
+            errorService.reportError(e as Error | string);
+        } finally {
+            setLoading(false);
+        }
+    };
+
+    useEffect(() => {
+        load();
+        invoke<boolean>('is_weekly_digest_enabled').then(setEnabled).catch(() => { });
+    }, []);
+
+    const toggleEnabled = async () => {
+        try {
+            await invoke('set_weekly_digest_enabled', { enabled: !enabled });
+            setEnabled(!enabled);
+        } catch (e) {
+            errorService.reportError(e as Error | string);
+        }
+    };
+
+    return (
+        <div className="py-4 space-y-6">
+            <div className="flex flex-col sm:flex-row sm:items-center justify-between gap-3">
+                <div>
+                    <h2 className="text-2xl font-bold">Weekly Digest</h2>
+                    {digest && (
+                        <p className="text-sm text-app-muted">
+                            {formatDate(digest.period_start)} – {formatDate(digest.period_end)}
+                        </p>
+                    )}
+                </div>
+                <div className="flex items-center gap-3">
+                    <label className="flex items-center gap-2 text-sm cursor-pointer">
+                        <input type="checkbox" checked={enabled} onChange={toggleEnabled} />
+                        Notify me every week
+                    </label>
+                    <button
+                        onClick={load}
+                        disabled={loading}
+                        className="p-2 rounded-lg bg-app-subtle hover:bg-app-hover disabled:opacity-50"
+                        title="Refresh"
+                    >
+                        {loading ? <Loader2 size={16} className="animate-spin" /> : <RefreshCw size={16} />}
+                    </button>
+                </div>
+            </div>
+
+            {digest && (
+                <>
+                    <section className="p-4 rounded-xl bg-app-card border border-app-border">
+                        <h3 className="font-bold mb-2">
+                            {digest.updated.length} package{digest.updated.length !== 1 ? 's' : ''} updated
+                            <span className="text-app-muted font-normal text-sm">
+                                {' '}· {digest.installed_count} installed · {digest.removed_count} removed
+                            </span>
+                        </h3>
+                        <ul className="text-sm space-y-1 max-h-64 overflow-y-auto">
+                            {digest.updated.map((u) => (
+                                <li key={u.name} className="flex items-center gap-2">
+                                    <span className="font-medium">{u.name}</span>
+                                    <span className="text-app-muted">{u.old_version}</span>
+                                    <ArrowRight size={12} className="text-app-muted" />
+                                    <span className={clsx(u.action === 'downgraded' && 'text-amber-500')}>{u.new_version}</span>
+                                </li>
+                            ))}
+                        </ul>
+                    </section>
+
+                    <section className="p-4 rounded-xl bg-app-card border border-app-border">
+                        <h3 className="font-bold mb-2 flex items-center gap-2">
+                            <ShieldCheck size={18} className="text-emerald-500" />
+                            Security advisories fixed
+                        </h3>
+                        {!digest.advisories_checked ? (
+                            <p className="text-sm text-app-muted">The Arch security tracker could not be reached.</p>
+                        ) : digest.advisories_fixed.length === 0 ? (
+                            <p className="text-sm text-app-muted">None this week.</p>
+                        ) : (
+                            <ul className="text-sm space-y-1">
+                                {digest.advisories_fixed.map((a) => (
+                                    <li key={`${a.group}-${a.package}`}>
+                                        <span className="font-medium">{a.package} {a.fixed_version}</span>
+                                        <span className="text-app-muted"> · {a.severity} · {a.group}</span>
+                                        {a.issues.length > 0 && <span className="text-app-muted"> ({a.issues.join(', ')})</span>}
+                                    </li>
+                                ))}
+                            </ul>
+                        )}
+                    </section>
+
+                    <section className="p-4 rounded-xl bg-app-card border border-app-border">
+                        <h3 className="font-bold mb-2 flex items-center gap-2">
+                            <HardDrive size={18} />
+                            Disk space
+                        </h3>
+                        <p className="text-sm">
+                            Installed packages: {formatBytes(digest.disk.installed_bytes)}
+                            <span className="text-app-muted"> ({formatDelta(digest.disk.installed_delta)} this week)</span>
+                        </p>
+                        <p className="text-sm">
+                            Package cache: {formatBytes(digest.disk.cache_bytes)}
+                            <span className="text-app-muted"> ({formatDelta(digest.disk.cache_delta)} this week)</span>
+                        </p>
+                    </section>
+
+                    <section className="p-4 rounded-xl bg-app-card border border-app-border">
+                        <h3 className="font-bold mb-2 flex items-center gap-2">
+                            <Wrench size={18} />
+                            Needs attention
+                        </h3>
+                        {digest.maintenance.length === 0 ? (
+                            <p className="text-sm text-app-muted">Nothing to do.</p>
+                        ) : (
+                            <ul className="text-sm space-y-2">
+                                {digest.maintenance.map((m) => (
+                                    <li key={m.kind}>
+                                        <span className="font-medium block">{m.title}</span>
+                                        <span className="text-app-muted break-all">{m.detail}</span>
+                                    </li>
+                                ))}
+                            </ul>
+                        )}
+                    </section>
+                </>
+            )}
+        </div>
+    );
+}
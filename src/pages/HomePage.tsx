@@ -1,12 +1,14 @@
 import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
 import { Zap, AlertTriangle } from 'lucide-react';
 import TrendingSection from '../components/TrendingSection';
 import CategoryGrid from '../components/CategoryGrid';
 import { useSmartEssentials } from '../hooks/useSmartEssentials';
-import { useOnlineStatus } from '../hooks/useOnlineStatus'; // Vector 4: Offline Resilience
+import { useConnectivity } from '../hooks/useOnlineStatus'; // Vector 4: Offline Resilience
 import { useDistro } from '../hooks/useDistro';
 import { Package } from '../components/PackageCard';
 import { WifiOff } from 'lucide-react';
+import { CollectionsResponse } from '../types/alpm';
 
 interface HomePageProps {
     onSelectPackage: (pkg: Package) => void;
@@ -17,12 +19,20 @@ interface HomePageProps {
 export default function HomePage({ onSelectPackage, onSeeAll, onSelectCategory }: HomePageProps) {
     const { distro } = useDistro();
     const { essentials, loading } = useSmartEssentials();
-    const isOnline = useOnlineStatus();
+    const connectivity = useConnectivity();
+    const isOnline = !connectivity.offline;
     const [showAlphaNotice, setShowAlphaNotice] = useState(() => {
         if (typeof window === 'undefined') return true;
         return localStorage.getItem('monarch_alpha_notice_dismissed') !== 'true';
     });
     const [offlineDismissed, setOfflineDismissed] = useState(false);
+    const [collections, setCollections] = useState<CollectionsResponse | null>(null);
+
+    useEffect(() => {
+        invoke<CollectionsResponse>('get_collections')
+            .then(setCollections)
+            .catch(() => { });
+    }, []);
 
     useEffect(() => {
         if (typeof window === 'undefined') return;
@@ -90,8 +100,8 @@ export default function HomePage({ onSelectPackage, onSeeAll, onSelectCategory }
                             <WifiOff size={20} />
                         </div>
                         <div className="flex-1 space-y-1">
-                            <h3 className="font-bold text-amber-500 text-sm">No Internet Connection</h3>
-                            <p className="text-xs text-amber-500/80">You are browsing cached application data. Install and update actions may fail until connectivity returns.</p>
+                            <h3 className="font-bold text-amber-500 text-sm">{connectivity.forced ? 'Offline Mode' : 'No Internet Connection'}</h3>
+                            <p className="text-xs text-amber-500/80">You are browsing cached application data; search covers installed repositories only. Installs wait in the queue until {connectivity.forced ? 'offline mode is turned off' : 'connectivity returns'}.</p>
                         </div>
                         <button
                             type="button"
@@ -121,7 +131,27 @@ export default function HomePage({ onSelectPackage, onSeeAll, onSelectCategory }
                 variant="scroll"
             />
 
-            <CategoryGrid onSelectCategory={onSelectCategory} />
+            {collections?.collections.map((collection) => (
+                <section key={collection.id}>
+                    <div className="mb-4 px-2">
+                        <h2 className="text-xl font-bold text-slate-900 dark:text-white">{collection.title}</h2>
+                        <p className="text-xs text-slate-500 dark:text-app-muted">{collection.description}</p>
+                        {collection.skipped.length > 0 && (
+                            <p className="text-[11px] text-slate-400 dark:text-app-muted/70 mt-0.5" title={collection.skipped.join(', ')}>
+                                {collection.skipped.length} more not in your enabled sources
+                            </p>
+                        )}
+                    </div>
+                    <TrendingSection
+                        title=""
+                        filterIds={collection.packages}
+                        onSelectPackage={onSelectPackage}
+                        variant="scroll"
+                    />
+                </section>
+            ))}
+
+            <CategoryGrid onSelectCategory={onSelectCategory} counts={collections?.category_counts} />
         </div>
     );
 }
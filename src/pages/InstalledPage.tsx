@@ -1,14 +1,17 @@
 import { useState, useEffect } from 'react';
-import { Search, Trash2, Play, HardDrive, Calendar, Package as PackageIcon, Loader2 } from 'lucide-react';
+import { Search, Trash2, Play, HardDrive, Calendar, Package as PackageIcon, Loader2, ClipboardList } from 'lucide-react';
 import { motion, AnimatePresence } from 'framer-motion';
 import { clsx } from 'clsx';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import ConfirmationModal from '../components/ConfirmationModal';
+import ImportListModal from '../components/ImportListModal';
 import { useToast } from '../context/ToastContext';
 import { useErrorService } from '../context/ErrorContext';
 import { useSessionPassword } from '../context/useSessionPassword';
 import { useAppStore } from '../store/internal_store';
-import { Package } from '../components/PackageCard';
+import { Package, SearchResponse } from '../components/PackageCard';
+import { UninstallPreview } from '../types/alpm';
 
 interface InstalledApp {
     name: string;
@@ -46,16 +49,18 @@ export default function InstalledPage({ onSelectPackage }: { onSelectPackage: (p
     const [loading, setLoading] = useState(true);
     const [totalSize, setTotalSize] = useState('Calculating...');
 
-    const [confirmModal, setConfirmModal] = useState<{ isOpen: boolean; id: string; name: string } | null>(null);
+    const [confirmModal, setConfirmModal] = useState<{ isOpen: boolean; ids: string[]; name: string; preview?: UninstallPreview | null } | null>(null);
+    const [selected, setSelected] = useState<Set<string>>(new Set());
+    const [importOpen, setImportOpen] = useState(false);
     const { success } = useToast();
     const errorService = useErrorService();
     const { requestSessionPassword } = useSessionPassword();
     const reducePasswordPrompts = useAppStore((s) => s.reducePasswordPrompts);
 
-    // Fetch installed packages on mount
+    // Fetch installed packages on mount, and again when pacman changes them behind our back
     useEffect(() => {
-        const fetchInstalled = async () => {
-            setLoading(true);
+        const fetchInstalled = async (quiet = false) => {
+            if (!quiet) setLoading(true);
             try {
                 const packages = await invoke<InstalledApp[]>('get_installed_packages');
                 setApps(packages);
@@ -74,6 +79,8 @@ export default function InstalledPage({ onSelectPackage }: { onSelectPackage: (p
         };
 
         fetchInstalled();
+        const unlisten = listen('system-state-changed', () => fetchInstalled(true));
+        return () => { unlisten.then((fn) => fn()).catch(() => { }); };
     }, []);
 
     const filteredApps = apps.filter(app =>
@@ -81,18 +88,54 @@ export default function InstalledPage({ onSelectPackage }: { onSelectPackage: (p
         app.description.toLowerCase().includes(searchQuery.toLowerCase())
     );
 
-    const handleUninstall = (id: string, name: string) => {
-        setConfirmModal({ isOpen: true, id, name });
+    const handleUninstall = async (id: string, name: string) => {
+        // Show what the removal takes with it before asking
+        const preview = await invoke<UninstallPreview>('preview_uninstall', { pkg: id, removeDeps: true }).catch(() => null);
+        setConfirmModal({ isOpen: true, ids: [id], name, preview });
+    };
+
+    const toggleSelected = (id: string) => {
+        setSelected((prev) => {
+            const next = new Set(prev);
+            if (next.has(id)) next.delete(id); else next.add(id);
+            return next;
+        });
+    };
+
+    const handleUninstallSelected = () => {
+        const ids = [...selected];
+        if (ids.length === 0) return;
+        setConfirmModal({ isOpen: true, ids, name: `${ids.length} packages`, preview: null });
+    };
+
+    const uninstallMessage = (name: string | undefined, preview: UninstallPreview | null | undefined) => {
+        const ids = confirmModal?.ids ?? [];
+        const base = ids.length > 1
+            ? `Are you sure you want to remove ${ids.join(', ')}? This action cannot be undone.`
+            : `Are you sure you want to remove ${name}? This action cannot be undone.`;
+        if (!preview) return base;
+        const others = preview.packages.filter((p) => p.reason !== 'target').map((p) => p.name);
+        const parts = [base];
+        if (others.length > 0) parts.push(`Also removed: ${others.join(', ')}.`);
+        parts.push(`Frees ${(preview.reclaimed_bytes / 1024 / 1024).toFixed(1)} MiB.`);
+        parts.push(...preview.warnings);
+        return parts.join(' ');
     };
 
     const performUninstall = async () => {
         if (!confirmModal) return;
-        const { id, name } = confirmModal;
+        const { ids, name } = confirmModal;
 
         try {
             const pwd = reducePasswordPrompts ? await requestSessionPassword() : null;
-            await invoke('uninstall_package', { name: id, password: pwd });
-            setApps(apps.filter(a => a.name !== id));
+            if (ids.length === 1) {
+                await invoke('uninstall_package', { name: ids[0], password: pwd });
+            } else {
+                // One transaction for the whole selection: a single prompt and progress stream
+                await invoke<string[]>('uninstall_packages', { names: ids, password: pwd });
+            }
+            setApps(apps.filter(a => !ids.includes(a.name)));
+            setSelected(new Set());
             success(`${name} uninstalled successfully`);
         } catch (e) {
             errorService.reportError(e as Error | string);
@@ -115,7 +158,7 @@ export default function InstalledPage({ onSelectPackage }: { onSelectPackage: (p
                 onSelectPackage(results[0]);
             } else {
                 // Search as fallback
-                const searchResults = await invoke<Package[]>('search_packages', { query: app.name });
+                const { packages: searchResults } = await invoke<SearchResponse>('search_packages', { query: app.name });
                 const exactMatch = searchResults.find(p => p.name.toLowerCase() === app.name.toLowerCase());
                 if (exactMatch) {
                     onSelectPackage(exactMatch);
@@ -141,6 +184,23 @@ export default function InstalledPage({ onSelectPackage }: { onSelectPackage: (p
                             {loading ? 'Thinking...' : `${apps.length} packages • ${totalSize}`}
                         </p>
                     </div>
+                    <div className="flex items-center gap-2 shrink-0">
+                        <button
+                            onClick={() => setImportOpen(true)}
+                            className="h-8 px-3 rounded-lg bg-blue-500/10 hover:bg-blue-500/20 text-blue-500 dark:text-blue-400 border border-blue-500/10 hover:border-blue-500/30 font-bold text-xs flex items-center gap-1.5 transition-all active:scale-95"
+                            title="Install the packages from another machine's pacman -Qqe list"
+                        >
+                            <ClipboardList size={14} /> Import list
+                        </button>
+                        {selected.size > 0 && (
+                            <button
+                                onClick={handleUninstallSelected}
+                                className="h-8 px-3 rounded-lg bg-red-500/10 hover:bg-red-500/20 text-red-500 dark:text-red-400 border border-red-500/10 hover:border-red-500/30 font-bold text-xs flex items-center gap-1.5 transition-all active:scale-95 shrink-0"
+                            >
+                                <Trash2 size={14} /> Uninstall selected ({selected.size})
+                            </button>
+                        )}
+                    </div>
                 </div>
 
                 <div className="relative group mt-3">
@@ -180,6 +240,15 @@ export default function InstalledPage({ onSelectPackage }: { onSelectPackage: (p
                                     onClick={() => handleNavigation(app)}
                                     className="group bg-white dark:bg-app-card border border-black/5 dark:border-white/5 hover:border-black/10 dark:hover:border-white/20 rounded-xl transition-all overflow-hidden relative shadow-sm dark:shadow-md hover:shadow-lg hover:-translate-y-0.5 backdrop-blur-sm p-3 flex items-center gap-3 md:gap-4 cursor-pointer min-w-0"
                                 >
+                                    <input
+                                        type="checkbox"
+                                        checked={selected.has(app.name)}
+                                        onClick={(e) => e.stopPropagation()}
+                                        onChange={() => toggleSelected(app.name)}
+                                        className="shrink-0 accent-red-500"
+                                        aria-label={`Select ${app.name}`}
+                                    />
+
                                     {/* Icon */}
                                     <div className="w-11 h-11 rounded-xl bg-slate-50 dark:bg-black/20 border border-black/5 dark:border-white/5 flex items-center justify-center shrink-0 overflow-hidden relative shadow-inner p-1.5">
                                         <AppIcon appName={app.name} appIcon={app.icon} />
@@ -238,10 +307,11 @@ export default function InstalledPage({ onSelectPackage }: { onSelectPackage: (p
                 onClose={() => setConfirmModal(null)}
                 onConfirm={performUninstall}
                 title={`Uninstall ${confirmModal?.name}?`}
-                message={`Are you sure you want to remove ${confirmModal?.name}? This action cannot be undone.`}
+                message={uninstallMessage(confirmModal?.name, confirmModal?.preview)}
                 confirmLabel="Uninstall"
                 variant="danger"
             />
+            <ImportListModal isOpen={importOpen} onClose={() => setImportOpen(false)} />
         </div>
     );
 }
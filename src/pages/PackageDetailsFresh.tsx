@@ -1,22 +1,25 @@
 import { useState, useEffect, useRef } from 'react';
 import {
     ArrowLeft, Download, Play, Heart, Star, Code, X,
-    AlertTriangle, Trash2, User, Globe, Calendar,
+    AlertTriangle, Trash2, User, Globe, Calendar, Baby,
     ChevronRight, CheckCircle2,
-    Loader2, ShieldCheck, MessageSquare, Cpu, ChevronDown, RefreshCw
+    Loader2, ShieldCheck, MessageSquare, Cpu, ChevronDown, RefreshCw, History,
+    ThumbsUp, ThumbsDown, Flag, Hammer, Tag, FlaskConical, Zap
 } from 'lucide-react';
 import { motion, AnimatePresence } from 'framer-motion';
 import DOMPurify from 'dompurify'; // Vector 1: HTML Injection Fix
 import RepoSelector from '../components/RepoSelector';
 import RepoBadge from '../components/RepoBadge';
+import CachedScreenshot from '../components/CachedScreenshot';
 import { Package } from '../components/PackageCard';
-import { PackageSource } from '../types/alpm';
+import { AurBuildMode, AurBuildModeInfo, AvailableVersion, ChaoticBuildStatus, ContentRating, LocalReview, OptimizedVariant, PackageSource, TrialStatus, TrialSupport, UpstreamStatus } from '../types/alpm';
 import { invoke } from '@tauri-apps/api/core';
+import { openUrl } from '@tauri-apps/plugin-opener';
 import { listen, UnlistenFn } from '@tauri-apps/api/event';
 import { clsx } from 'clsx';
 import { resolveIconUrl } from '../utils/iconHelper';
 import { useFavorites } from '../hooks/useFavorites';
-import { submitReview } from '../services/reviewService';
+import { submitReview, submitOdrsReview, voteOdrsReview, OdrsVote, Review } from '../services/reviewService';
 import { useToast } from '../context/ToastContext';
 import { useErrorService } from '../context/ErrorContext';
 import archLogo from '../assets/arch-logo.svg';
@@ -26,6 +29,8 @@ import { compareVersions } from '../utils/versionHelper';
 import { useDistro } from '../hooks/useDistro';
 import { useEscapeKey } from '../hooks/useEscapeKey';
 import { useFocusTrap } from '../hooks/useFocusTrap';
+import { useSessionPassword } from '../context/useSessionPassword';
+import { useAppStore } from '../store/internal_store';
 
 // --- Types ---
 interface PackageDetailsProps {
@@ -36,7 +41,7 @@ interface PackageDetailsProps {
     installInProgress?: boolean;
     /** When set and name matches this pkg, show "Installing..." / "Uninstalling..." with spinner (no layout shift). */
     activeInstallPackage?: { name: string; mode: 'install' | 'uninstall' } | null;
-    onInstall: (p: { name: string; source: PackageSource | string; repoName?: string }) => void;
+    onInstall: (p: { name: string; source: PackageSource | string; repoName?: string; buildMode?: AurBuildMode }) => void;
     onUninstall: (p: { name: string; source: PackageSource | string; repoName?: string }) => void;
 }
 
@@ -61,12 +66,34 @@ interface InstallStatus {
 export default function PackageDetails({ pkg, onBack, preferredSource, installInProgress = false, activeInstallPackage = null, onInstall, onUninstall }: PackageDetailsProps) {
     const activeInstall = activeInstallPackage;
     // --- State & Hooks ---
-    const { metadata: fullMeta } = usePackageMetadata(pkg.name);
+    const { metadata: fullMeta } = usePackageMetadata(pkg.name, undefined, false, true);
     const { success } = useToast();
     const errorService = useErrorService();
     const { distro } = useDistro();
+    const { requestSessionPassword } = useSessionPassword();
+    const reducePasswordPrompts = useAppStore((s) => s.reducePasswordPrompts);
 
     const lookupId = pkg.app_id || fullMeta?.app_id || pkg.name;
+    // Reviews only go to ODRS under a real AppStream id
+    const odrsId = pkg.app_id || fullMeta?.app_id;
+
+    // Header tint from the icon's dominant color (GNOME Software style)
+    const [accentColor, setAccentColor] = useState<string | null>(null);
+    useEffect(() => {
+        let cancelled = false;
+        setAccentColor(null);
+        invoke<string[]>('extract_dominant_colors', { appId: lookupId })
+            .then((colors) => { if (!cancelled) setAccentColor(colors[0] ?? null); })
+            .catch(() => { });
+        return () => { cancelled = true; };
+    }, [lookupId]);
+
+    const [contentRating, setContentRating] = useState<ContentRating | null>(pkg.content_rating ?? null);
+    useEffect(() => {
+        invoke<ContentRating | null>('get_content_rating', { appId: lookupId })
+            .then(setContentRating)
+            .catch(() => { });
+    }, [lookupId]);
     const { reviews, summary: rating, refresh: refreshReviews } = usePackageReviews(pkg.name, lookupId);
 
     const [variants, setVariants] = useState<PackageVariant[]>([]);
@@ -83,6 +110,7 @@ export default function PackageDetails({ pkg, onBack, preferredSource, installIn
     const [reviewBody, setReviewBody] = useState('');
     const [reviewRating, setReviewRating] = useState(5);
     const [isSubmittingReview, setIsSubmittingReview] = useState(false);
+    const [odrsPublish, setOdrsPublish] = useState(false);
 
     // Pagination for reviews
     const [visibleReviewsCount, setVisibleReviewsCount] = useState(5);
@@ -98,8 +126,25 @@ export default function PackageDetails({ pkg, onBack, preferredSource, installIn
     const pkgbuildModalRef = useFocusTrap(showPkgbuild);
     const [pkgbuildContent, setPkgbuildContent] = useState<string | null>(null);
     const [pkgbuildLoading, setPkgbuildLoading] = useState(false);
+    // Per-install AUR build override; null = use the default from Settings → Builder
+    const [buildInfo, setBuildInfo] = useState<AurBuildModeInfo | null>(null);
+    const [chaoticBuild, setChaoticBuild] = useState<ChaoticBuildStatus | null>(null);
+    const [upstream, setUpstream] = useState<UpstreamStatus | null>(null);
+    const [optimized, setOptimized] = useState<OptimizedVariant[]>(pkg.optimized_variants || []);
+    const [buildOverride, setBuildOverride] = useState<AurBuildMode | null>(null);
+    const [trialSupport, setTrialSupport] = useState<TrialSupport | null>(null);
+    const [trial, setTrial] = useState<TrialStatus | null>(null);
     const [pkgbuildError, setPkgbuildError] = useState<string | null>(null);
 
+    // Rollback to an earlier version (pacman cache or Arch Linux Archive)
+    const [showRollback, setShowRollback] = useState(false);
+    useEscapeKey(() => setShowRollback(false), showRollback);
+    const rollbackModalRef = useFocusTrap(showRollback);
+    const [rollbackVersions, setRollbackVersions] = useState<AvailableVersion[] | null>(null);
+    const [rollbackError, setRollbackError] = useState<string | null>(null);
+    const [rollbackPin, setRollbackPin] = useState(true);
+    const [rollingBackTo, setRollingBackTo] = useState<string | null>(null);
+
     // Lightbox
     const [lightboxIndex, setLightboxIndex] = useState<number | null>(null);
     useEscapeKey(() => setLightboxIndex(null), lightboxIndex !== null);
@@ -113,6 +158,32 @@ export default function PackageDetails({ pkg, onBack, preferredSource, installIn
         reviewsRef.current?.scrollIntoView({ behavior: 'smooth', block: 'start' });
     };
 
+    const openRollback = () => {
+        const name = installedVariant?.actual_package_name || pkg.name;
+        setShowRollback(true);
+        setRollbackVersions(null);
+        setRollbackError(null);
+        invoke<AvailableVersion[]>('get_available_versions', { pkg: name })
+            .then(versions => setRollbackVersions(versions.filter(v => !v.installed)))
+            .catch(e => setRollbackError(String(e)));
+    };
+
+    const handleRollback = async (version: string) => {
+        const name = installedVariant?.actual_package_name || pkg.name;
+        setRollingBackTo(version);
+        try {
+            const pwd = reducePasswordPrompts ? await requestSessionPassword() : null;
+            const msg = await invoke<string>('downgrade_package', { pkg: name, version, pin: rollbackPin, password: pwd });
+            success(msg);
+            setShowRollback(false);
+            checkStatus();
+        } catch (e) {
+            setRollbackError(String(e));
+        } finally {
+            setRollingBackTo(null);
+        }
+    };
+
     // --- Effects ---
 
     // 1. Fetch Variants & Initial Selection (use available_sources from unified search when present)
@@ -190,17 +261,107 @@ export default function PackageDetails({ pkg, onBack, preferredSource, installIn
     useEffect(() => {
         checkStatus();
         const unlisten = listen('install-complete', () => checkStatus());
-        return () => { unlisten.then((f: UnlistenFn) => f()); };
+        const unlistenState = listen<{ installed: string[]; removed: string[]; upgraded: string[] }>(
+            'system-state-changed',
+            (event) => {
+                const { installed, removed, upgraded } = event.payload;
+                if ([...installed, ...removed, ...upgraded].includes(pkg.name)) checkStatus();
+            }
+        );
+        return () => {
+            unlisten.then((f: UnlistenFn) => f());
+            unlistenState.then((f: UnlistenFn) => f());
+        };
     }, [pkg.name, selectedSource, variants]);
 
 
     // --- Actions ---
 
+    const isAurSelected = typeof selectedSource === 'string' ? selectedSource === 'aur' : selectedSource.source_type === 'aur';
+
+    useEffect(() => {
+        if (!isAurSelected || buildInfo) return;
+        invoke<AurBuildModeInfo>('get_aur_build_mode').then(setBuildInfo).catch(() => { });
+    }, [isAurSelected]);
+
+    const isChaoticSelected = typeof selectedSource === 'string' ? selectedSource === 'chaotic' : selectedSource.id === 'chaotic-aur';
+
+    useEffect(() => {
+        setChaoticBuild(null);
+        if (!isChaoticSelected) return;
+        const name = variants.find(v => isSameSource(v.source, selectedSource))?.pkg_name || pkg.name;
+        invoke<ChaoticBuildStatus>('get_build_status', { pkgname: name }).then(setChaoticBuild).catch(() => { });
+    }, [isChaoticSelected, pkg.name, variants]);
+
+    useEffect(() => {
+        setUpstream(null);
+        invoke<UpstreamStatus>('get_upstream_releases', { pkg: pkg.name }).then(setUpstream).catch(() => { });
+    }, [pkg.name]);
+
+    // CachyOS builds for this CPU's x86-64 level (or Zen 4), offered next to the source picker
+    useEffect(() => {
+        setOptimized(pkg.optimized_variants || []);
+        invoke<OptimizedVariant[]>('get_optimized_alternatives', { pkg: pkg.name }).then(setOptimized).catch(() => { });
+    }, [pkg.name]);
+    // Sorted by the backend: supported builds first, best CPU level first
+    const bestOptimized = optimized.find(v => v.supported);
+    const cachyVariant = variants.find(v => typeof v.source !== 'string' && v.source.id === 'cachyos');
+    // All CachyOS repos share the "cachyos" source; pin the variant to the detected tier's repo
+    const selectOptimized = () => {
+        if (!cachyVariant || !bestOptimized) return;
+        setVariants(prev => prev.map(v => v === cachyVariant
+            ? { ...v, repo_name: bestOptimized.repo, version: bestOptimized.version }
+            : v));
+        setSelectedSource(cachyVariant.source);
+    };
+
+    // Try before install: the Flathub build in a throwaway installation, else an official
+    // repo package in a distrobox container
+    const flatpakVariant = variants.find(v => typeof v.source !== 'string' && v.source.source_type === 'flatpak');
+    const selectedSourceId = typeof selectedSource === 'string' ? selectedSource : selectedSource.id;
+    const trialId = trialSupport?.flatpak && flatpakVariant
+        ? (flatpakVariant.pkg_name || pkg.app_id || null)
+        : trialSupport?.distrobox && ['core', 'extra'].includes(selectedSourceId)
+            ? pkg.name
+            : null;
+    const trialActive = !!trial && ['preparing', 'running', 'cleaning'].includes(trial.state);
+
+    useEffect(() => {
+        invoke<TrialSupport>('get_trial_support').then(setTrialSupport).catch(() => { });
+        invoke<boolean>('is_odrs_publish_enabled').then(setOdrsPublish).catch(() => { });
+    }, []);
+
+    const toggleOdrsPublish = async (enabled: boolean) => {
+        try {
+            await invoke('set_odrs_publish_enabled', { enabled });
+            setOdrsPublish(enabled);
+        } catch (e) { errorService.reportError(e as Error | string); }
+    };
+
+    useEffect(() => {
+        setTrial(null);
+        if (!trialId) return;
+        const unlisten = listen<TrialStatus>('trial-status', (event) => {
+            if (event.payload.app_id !== trialId) return;
+            setTrial(event.payload);
+            if (event.payload.state === 'failed') errorService.reportError(`Trial failed: ${event.payload.message}`);
+        });
+        return () => { unlisten.then((fn) => fn()).catch(() => { }); };
+    }, [trialId]);
+
+    const handleTry = async () => {
+        if (!trialId) return;
+        try {
+            await invoke('try_app', { appId: trialId });
+        } catch (e) { errorService.reportError(e as Error | string); }
+    };
+
     const handleInstallClick = () => {
         onInstall({
             name: variants.find(v => v.source === selectedSource)?.pkg_name || pkg.name,
             source: selectedSource,
-            repoName: variants.find(v => v.source === selectedSource)?.repo_name
+            repoName: variants.find(v => v.source === selectedSource)?.repo_name,
+            buildMode: isAurSelected && buildOverride ? buildOverride : undefined
         });
     };
 
@@ -232,6 +393,24 @@ export default function PackageDetails({ pkg, onBack, preferredSource, installIn
             const fullComment = reviewTitle ? `${reviewTitle}\n\n${reviewBody}` : reviewBody;
 
             await submitReview(pkg.name, reviewRating, fullComment, "MonArch User");
+            const summary = reviewTitle || reviewBody.slice(0, 60);
+            // The user's own copy, listed under My Reviews for editing and deleting
+            const local = await invoke<LocalReview>('submit_review', {
+                appId: odrsId || pkg.name,
+                rating: reviewRating,
+                summary,
+                description: reviewBody,
+                userDisplay: "MonArch User",
+            }).catch(() => null);
+            // Share with the ODRS pool when the user opted in and the app has an AppStream id;
+            // the MonArch copy is already saved.
+            if (odrsId && odrsPublish) {
+                try {
+                    await submitOdrsReview(odrsId, reviewRating, summary, reviewBody, "MonArch User", pkg.version, local?.id);
+                } catch (e) {
+                    errorService.reportError(`Review saved, but publishing to ODRS failed: ${e}`);
+                }
+            }
             setShowReviewForm(false);
             setReviewTitle(''); setReviewBody('');
             await refreshReviews(); // Force refresh via hook wrapper
@@ -252,6 +431,16 @@ export default function PackageDetails({ pkg, onBack, preferredSource, installIn
         }
     };
 
+    const handleReviewVote = async (review: Review, vote: OdrsVote) => {
+        if (!review.appId || !Number.isInteger(review.id)) return;
+        try {
+            await voteOdrsReview(review.appId, review.id as number, vote);
+            success(vote === 'report' ? "Review reported to ODRS moderators" : "Thanks for your feedback");
+        } catch (e) {
+            errorService.reportError(e as Error | string);
+        }
+    };
+
     const fetchPkgbuild = async () => {
         setPkgbuildLoading(true);
         setPkgbuildError(null);
@@ -304,7 +493,14 @@ export default function PackageDetails({ pkg, onBack, preferredSource, installIn
             <div className="relative h-auto md:min-h-[350px] pt-16 pb-10 md:pb-20 flex items-start z-30">
                 {/* Background Gradient / Image */}
                 <div className="absolute inset-0 z-0 overflow-hidden">
-                    <div className="absolute inset-0 bg-gradient-to-b from-blue-900/40 to-app-bg z-10" />
+                    {accentColor ? (
+                        <div
+                            className="absolute inset-0 z-10 transition-colors duration-500"
+                            style={{ backgroundImage: `linear-gradient(to bottom, ${accentColor}66, var(--app-bg))` }}
+                        />
+                    ) : (
+                        <div className="absolute inset-0 bg-gradient-to-b from-blue-900/40 to-app-bg z-10" />
+                    )}
                     <div className="absolute inset-0 bg-[url('/grid.svg')] opacity-10" />
                     {screenshots.length > 0 && (
                         <div className="absolute inset-0 blur-3xl opacity-30 scale-110">
@@ -439,6 +635,17 @@ export default function PackageDetails({ pkg, onBack, preferredSource, installIn
                                             selectedSource={selectedSource}
                                             onChange={(s) => setSelectedSource(s as any)}
                                         />
+                                        {bestOptimized && (
+                                            <button
+                                                onClick={selectOptimized}
+                                                disabled={!cachyVariant}
+                                                className="mt-1.5 flex items-center gap-1.5 text-xs text-purple-500 hover:underline disabled:no-underline disabled:cursor-default"
+                                                title={`${bestOptimized.repo} • v${bestOptimized.version}`}
+                                            >
+                                                <Zap size={12} />
+                                                Optimized for your CPU ({bestOptimized.level === 'znver4' ? 'Zen 4' : `x86-64-${bestOptimized.level}`})
+                                            </button>
+                                        )}
                                     </div>
                                 )}
 
@@ -493,6 +700,18 @@ export default function PackageDetails({ pkg, onBack, preferredSource, installIn
                                                         );
                                                     })()}
 
+                                                    {/* Roll back Button (repo packages only; AUR builds are not archived) */}
+                                                    {!['aur', 'flatpak'].includes(typeof installedVariant?.source === 'object' ? installedVariant.source.source_type : String(installedVariant?.source || '').toLowerCase()) && (
+                                                        <button
+                                                            onClick={openRollback}
+                                                            disabled={installInProgress}
+                                                            className="h-14 px-6 bg-slate-100 hover:bg-slate-200 dark:bg-white/5 dark:hover:bg-white/10 text-slate-700 dark:text-white/80 border border-slate-200 dark:border-white/10 rounded-2xl font-bold active:scale-95 transition-all flex items-center justify-center gap-2 disabled:opacity-50 disabled:cursor-not-allowed"
+                                                            title="Install an earlier version"
+                                                        >
+                                                            <History size={20} /> Roll back
+                                                        </button>
+                                                    )}
+
                                                     {/* Uninstall Button */}
                                                     {(() => {
                                                         const isThisUninstalling = activeInstall?.name === (installedVariant?.actual_package_name || pkg.name) && activeInstall?.mode === 'uninstall';
@@ -553,7 +772,30 @@ export default function PackageDetails({ pkg, onBack, preferredSource, installIn
                                         <Heart size={24} className={isFav ? "fill-current" : ""} />
                                     </button>
 
-                                    {(typeof selectedSource === 'string' ? selectedSource === 'aur' : selectedSource.source_type === 'aur') && (
+                                    {trialId && !installedVariant?.installed && (
+                                        <button
+                                            onClick={handleTry}
+                                            disabled={trialActive}
+                                            className="h-14 px-5 rounded-2xl border border-slate-200 dark:border-white/10 bg-slate-100 dark:bg-white/5 flex items-center justify-center gap-2 text-sm font-bold text-slate-600 dark:text-white/70 hover:bg-slate-200 dark:hover:bg-white/10 transition-colors shrink-0 disabled:opacity-60"
+                                            title={trial?.message || "Run it in a temporary sandbox that is removed afterwards"}
+                                        >
+                                            {trialActive ? <Loader2 size={20} className="animate-spin" /> : <FlaskConical size={20} />}
+                                            {trial?.state === 'running' ? 'Trying…' : trial?.state === 'cleaning' ? 'Cleaning up…' : trialActive ? 'Preparing…' : 'Try'}
+                                        </button>
+                                    )}
+
+                                    {isAurSelected && buildInfo?.devtools_available && (
+                                        <select
+                                            value={buildOverride ?? buildInfo.mode}
+                                            onChange={(e) => setBuildOverride(e.target.value as AurBuildMode)}
+                                            title="Build environment for this install"
+                                            className="h-14 rounded-2xl border border-slate-200 dark:border-white/10 bg-slate-100 dark:bg-white/5 px-3 text-xs font-bold text-slate-600 dark:text-white/70 focus:outline-none focus:ring-2 focus:ring-blue-500/40 shrink-0 cursor-pointer"
+                                        >
+                                            <option value="host">Host build</option>
+                                            <option value="chroot">Clean chroot</option>
+                                        </select>
+                                    )}
+                                    {isAurSelected && (
                                         <button onClick={fetchPkgbuild} className="h-14 w-14 rounded-2xl border border-slate-200 dark:border-white/10 bg-slate-100 dark:bg-white/5 flex items-center justify-center text-slate-500 dark:text-white/50 hover:text-slate-900 dark:hover:text-white hover:bg-slate-200 dark:hover:bg-white/10 transition-colors shrink-0" title="View PKGBUILD">
                                             {pkgbuildLoading ? <Loader2 size={24} className="animate-spin" /> : <Code size={24} />}
                                         </button>
@@ -602,6 +844,59 @@ export default function PackageDetails({ pkg, onBack, preferredSource, installIn
                                 return null;
                             })()}
 
+                            {/* Chaotic-AUR builder state: explains a version lagging behind the AUR */}
+                            {chaoticBuild && (chaoticBuild.state === 'queued' || chaoticBuild.state === 'building' || chaoticBuild.state === 'failed') && (
+                                <div className="w-full flex items-center justify-between gap-4 p-3 rounded-xl bg-app-bg/50 border border-app-border backdrop-blur-sm">
+                                    <div className="flex items-center gap-3">
+                                        <div className={clsx("shrink-0", chaoticBuild.state === 'failed' ? "text-red-500" : "text-amber-500")}>
+                                            {chaoticBuild.state === 'building' ? <Loader2 size={20} className="animate-spin" /> : <Hammer size={20} />}
+                                        </div>
+                                        <div className="text-sm">
+                                            <span className="font-bold text-app-fg block">
+                                                {chaoticBuild.state === 'queued' ? "Queued on Chaotic-AUR" : chaoticBuild.state === 'building' ? "Building on Chaotic-AUR" : "Last Chaotic-AUR build failed"}
+                                            </span>
+                                            <span className="text-app-muted text-xs">
+                                                {chaoticBuild.state === 'failed'
+                                                    ? `The repository may lag behind the AUR until ${chaoticBuild.last_version ?? 'the package'} builds again.`
+                                                    : "A newer version is on its way to the repository."}
+                                            </span>
+                                        </div>
+                                    </div>
+                                    {chaoticBuild.log_url && (
+                                        <button onClick={() => openUrl(chaoticBuild.log_url!).catch(() => { })} className="px-4 py-2 bg-slate-100 dark:bg-white/10 hover:bg-slate-200 dark:hover:bg-white/20 text-xs font-bold rounded-lg transition-all whitespace-nowrap">
+                                            Build log
+                                        </button>
+                                    )}
+                                </div>
+                            )}
+
+                            {/* Upstream releases: repo version behind the project, or a beta/nightly channel */}
+                            {upstream && (upstream.lagging || upstream.latest_prerelease) && (
+                                <div className="w-full flex items-center justify-between gap-4 p-3 rounded-xl bg-app-bg/50 border border-app-border backdrop-blur-sm">
+                                    <div className="flex items-center gap-3">
+                                        <div className={clsx("shrink-0", upstream.lagging ? "text-amber-500" : "text-blue-500")}>
+                                            <Tag size={20} />
+                                        </div>
+                                        <div className="text-sm">
+                                            <span className="font-bold text-app-fg block">
+                                                {upstream.lagging
+                                                    ? `Upstream ${upstream.latest_stable?.version} is out`
+                                                    : `Beta ${upstream.latest_prerelease?.version ?? upstream.latest_prerelease?.tag} available upstream`}
+                                            </span>
+                                            <span className="text-app-muted text-xs">
+                                                {upstream.lagging
+                                                    ? `The packaged ${upstream.packaged_version} is out of date.`
+                                                    : "Not packaged yet; the stable release is current."}
+                                                {upstream.lagging && upstream.latest_prerelease && ` Beta: ${upstream.latest_prerelease.version ?? upstream.latest_prerelease.tag}.`}
+                                            </span>
+                                        </div>
+                                    </div>
+                                    <button onClick={() => openUrl((upstream.lagging ? upstream.latest_stable : upstream.latest_prerelease)!.url).catch(() => { })} className="px-4 py-2 bg-slate-100 dark:bg-white/10 hover:bg-slate-200 dark:hover:bg-white/20 text-xs font-bold rounded-lg transition-all whitespace-nowrap">
+                                        Release notes
+                                    </button>
+                                </div>
+                            )}
+
                         </motion.div>
                     </div>
                 </div>
@@ -648,6 +943,17 @@ export default function PackageDetails({ pkg, onBack, preferredSource, installIn
                                             <span className="text-[10px] md:text-xs text-app-muted flex items-center gap-1.5 md:gap-2 shrink-0"><ShieldCheck size={12} className="md:w-[14px] md:h-[14px] text-emerald-500" /> License</span>
                                             <span className="text-[10px] md:text-xs text-slate-900 dark:text-white font-medium truncate max-w-[80px] sm:max-w-[150px]">{fullMeta?.license || "Unknown"}</span>
                                         </div>
+                                        {contentRating && (
+                                            <div
+                                                className="px-3 py-2 md:px-4 md:py-3 flex items-center justify-between gap-2 md:gap-4"
+                                                title={Object.entries(contentRating.attributes).map(([id, level]) => `${id}: ${level}`).join('\n') || 'No objectionable content'}
+                                            >
+                                                <span className="text-[10px] md:text-xs text-app-muted flex items-center gap-1.5 md:gap-2 shrink-0"><Baby size={12} className="md:w-[14px] md:h-[14px] text-amber-500" /> Age Rating</span>
+                                                <span className="text-[10px] md:text-xs text-slate-900 dark:text-white font-medium whitespace-nowrap">
+                                                    {contentRating.min_age > 0 ? `${contentRating.min_age}+` : 'All ages'}
+                                                </span>
+                                            </div>
+                                        )}
                                         <div className="px-3 py-2 md:px-4 md:py-3 flex items-center justify-between gap-2 md:gap-4">
                                             <span className="text-[10px] md:text-xs text-app-muted flex items-center gap-1.5 md:gap-2 shrink-0"><Calendar size={12} className="md:w-[14px] md:h-[14px] text-purple-500" /> Updated</span>
                                             <span className="text-[10px] md:text-xs text-slate-900 dark:text-white font-medium whitespace-nowrap">
@@ -671,8 +977,9 @@ export default function PackageDetails({ pkg, onBack, preferredSource, installIn
                                                 onClick={() => setLightboxIndex(i)}
                                                 className="shrink-0 w-[400px] aspect-video rounded-2xl overflow-hidden bg-slate-100 dark:bg-black/20 border border-slate-200 dark:border-white/10 cursor-pointer snap-center shadow-xl"
                                             >
-                                                <img
-                                                    src={url}
+                                                <CachedScreenshot
+                                                    url={url}
+                                                    thumbnail
                                                     alt="Screenshot"
                                                     className="w-full h-full object-cover"
                                                     loading="lazy"
@@ -764,6 +1071,12 @@ export default function PackageDetails({ pkg, onBack, preferredSource, installIn
                                         </div>
                                         <input value={reviewTitle} onChange={e => setReviewTitle(e.target.value)} placeholder="Title (e.g. Works great!)" className="w-full bg-black/20 border border-white/10 rounded-xl p-4 mb-3 text-white focus:border-blue-500 outline-none transition-colors" />
                                         <textarea value={reviewBody} onChange={e => setReviewBody(e.target.value)} placeholder="Share your experience..." className="w-full bg-black/20 border border-white/10 rounded-xl p-4 mb-3 text-white focus:border-blue-500 outline-none transition-colors" rows={4} />
+                                        {odrsId && (
+                                            <label className="flex items-center gap-2 mb-3 text-sm text-zinc-400 cursor-pointer">
+                                                <input type="checkbox" checked={odrsPublish} onChange={e => toggleOdrsPublish(e.target.checked)} className="accent-blue-500" />
+                                                Also publish my reviews to ODRS, where GNOME Software and Discover users see them
+                                            </label>
+                                        )}
                                         <div className="flex justify-end gap-3">
                                             <button onClick={() => setShowReviewForm(false)} className="px-6 py-2 text-zinc-400 hover:text-white transition-colors">Cancel</button>
                                             <button
@@ -810,6 +1123,19 @@ export default function PackageDetails({ pkg, onBack, preferredSource, installIn
                                                 </div>
                                                 {/* We don't have a distinct separate title field in the interface unless we parse it. For now, showing comment. */}
                                                 <p className="text-app-fg/80 text-sm leading-relaxed whitespace-pre-line mt-3 pl-11">{review.comment}</p>
+                                                {review.source === 'odrs' && review.appId && Number.isInteger(review.id) && (
+                                                    <div className="flex items-center gap-3 mt-3 pl-11 text-app-muted">
+                                                        <button onClick={() => handleReviewVote(review, 'upvote')} className="flex items-center gap-1 text-xs hover:text-white transition-colors" title="Helpful">
+                                                            <ThumbsUp size={12} /> Helpful
+                                                        </button>
+                                                        <button onClick={() => handleReviewVote(review, 'downvote')} className="flex items-center gap-1 text-xs hover:text-white transition-colors" title="Not helpful">
+                                                            <ThumbsDown size={12} />
+                                                        </button>
+                                                        <button onClick={() => handleReviewVote(review, 'report')} className="flex items-center gap-1 text-xs hover:text-red-400 transition-colors ml-auto" title="Report abuse">
+                                                            <Flag size={12} /> Report
+                                                        </button>
+                                                    </div>
+                                                )}
                                             </div>
                                         ))
                                     )}
@@ -841,8 +1167,8 @@ export default function PackageDetails({ pkg, onBack, preferredSource, installIn
                         className="fixed inset-0 z-40 bg-black/95 backdrop-blur-xl flex items-center justify-center p-4"
                     >
                         <button onClick={() => setLightboxIndex(null)} className="absolute top-6 right-6 p-4 text-white/50 hover:text-white" aria-label="Close"><X size={32} /></button>
-                        <img
-                            src={screenshots[lightboxIndex]}
+                        <CachedScreenshot
+                            url={screenshots[lightboxIndex]}
                             className="max-h-[90vh] max-w-[90vw] rounded-lg shadow-2xl"
                             onClick={e => e.stopPropagation()}
                         />
@@ -852,6 +1178,67 @@ export default function PackageDetails({ pkg, onBack, preferredSource, installIn
 
             {/* PKGBUILD Modal */}
             <AnimatePresence>
+                {showRollback && (
+                    <motion.div
+                        initial={{ opacity: 0 }} animate={{ opacity: 1 }} exit={{ opacity: 0 }}
+                        className="fixed inset-0 z-50 bg-black/80 backdrop-blur-sm flex items-center justify-center p-4"
+                        onClick={() => !rollingBackTo && setShowRollback(false)}
+                    >
+                        <motion.div
+                            ref={rollbackModalRef}
+                            initial={{ scale: 0.9 }} animate={{ scale: 1 }} exit={{ scale: 0.9 }}
+                            onClick={e => e.stopPropagation()}
+                            className="bg-app-card w-full max-w-lg max-h-[70vh] rounded-2xl border border-white/10 flex flex-col overflow-hidden shadow-2xl"
+                            role="dialog"
+                            aria-modal="true"
+                            aria-labelledby="rollback-modal-title"
+                        >
+                            <div className="p-4 border-b border-white/10 flex justify-between items-center bg-white/5">
+                                <h3 id="rollback-modal-title" className="font-bold text-white flex items-center gap-2"><History size={20} className="text-blue-400" /> Roll back {installedVariant?.actual_package_name || pkg.name}</h3>
+                                <button onClick={() => setShowRollback(false)} disabled={!!rollingBackTo} aria-label="Close"><X size={24} className="text-white/50 hover:text-white" /></button>
+                            </div>
+                            <div className="flex-1 overflow-auto p-4 space-y-2">
+                                {rollbackError && (
+                                    <div className="flex items-start gap-2 text-red-400 text-sm p-3 rounded-xl bg-red-500/10 border border-red-500/20">
+                                        <AlertTriangle size={16} className="shrink-0 mt-0.5" /> <span>{rollbackError}</span>
+                                    </div>
+                                )}
+                                {rollbackVersions === null && !rollbackError ? (
+                                    <div className="py-10 flex flex-col items-center justify-center text-white/50 gap-4">
+                                        <Loader2 size={32} className="animate-spin text-blue-500" />
+                                        <p>Looking up earlier versions...</p>
+                                    </div>
+                                ) : rollbackVersions?.length === 0 ? (
+                                    <p className="py-6 text-center text-white/50">No earlier versions found in the pacman cache or the Arch Linux Archive.</p>
+                                ) : rollbackVersions?.map(v => (
+                                    <div key={v.filename} className="flex items-center justify-between gap-3 p-3 rounded-xl bg-white/5 border border-white/10">
+                                        <div className="min-w-0">
+                                            <div className="font-mono text-white truncate">{v.version}</div>
+                                            <span className={clsx(
+                                                "text-[10px] font-bold uppercase tracking-wider px-1.5 py-0.5 rounded",
+                                                v.source === 'cache' ? "bg-emerald-500/20 text-emerald-400" : "bg-blue-500/20 text-blue-400"
+                                            )}>
+                                                {v.source === 'cache' ? 'In cache' : 'Archive'}
+                                            </span>
+                                        </div>
+                                        <button
+                                            onClick={() => handleRollback(v.version)}
+                                            disabled={!!rollingBackTo || installInProgress}
+                                            className="shrink-0 px-4 py-2 rounded-xl bg-blue-600 hover:bg-blue-500 text-white text-sm font-bold flex items-center gap-2 disabled:opacity-50 disabled:cursor-not-allowed"
+                                        >
+                                            {rollingBackTo === v.version ? <Loader2 size={16} className="animate-spin" /> : <History size={16} />}
+                                            {rollingBackTo === v.version ? 'Installing…' : 'Install'}
+                                        </button>
+                                    </div>
+                                ))}
+                            </div>
+                            <label className="p-4 border-t border-white/10 flex items-center gap-2 text-sm text-white/70 cursor-pointer">
+                                <input type="checkbox" checked={rollbackPin} onChange={e => setRollbackPin(e.target.checked)} disabled={!!rollingBackTo} />
+                                Keep this version (pin it so updates skip it)
+                            </label>
+                        </motion.div>
+                    </motion.div>
+                )}
                 {showPkgbuild && (
                     <motion.div
                         initial={{ opacity: 0 }} animate={{ opacity: 1 }} exit={{ opacity: 0 }}
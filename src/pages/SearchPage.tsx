@@ -7,11 +7,18 @@ import PackageCard, { Package } from '../components/PackageCard';
 import SkeletonCard from '../components/SkeletonCard';
 import EmptyState from '../components/EmptyState';
 import { clsx } from 'clsx';
+import { invoke } from '@tauri-apps/api/core';
+import { PackageScore, SearchFilters } from '../types/alpm';
 
 interface SearchPageProps {
     query: string;
     onQueryChange: (query: string) => void;
     packages: Package[];
+    /** Backend "did you mean" for typos; the alias table below takes precedence. */
+    suggestion?: string | null;
+    /** Applied by the backend; changing them re-runs the search. */
+    filters?: SearchFilters;
+    onFiltersChange?: (filters: SearchFilters) => void;
     loading: boolean;
     onSelectPackage: (pkg: Package) => void;
     enabledRepos: { name: string; enabled: boolean; source: any }[];
@@ -23,6 +30,9 @@ export default function SearchPage({
     query,
     onQueryChange,
     packages,
+    suggestion,
+    filters = {},
+    onFiltersChange,
     loading,
     onSelectPackage,
     enabledRepos,
@@ -32,7 +42,8 @@ export default function SearchPage({
     const { history, removeSearch, clearHistory } = useSearchHistory();
     const { favorites } = useFavorites();
     const [activeFilter, setActiveFilter] = useState('all');
-    const [sortBy, setSortBy] = useState<'best_match' | 'name' | 'updated'>('best_match');
+    const [sortBy, setSortBy] = useState<'best_match' | 'name' | 'updated' | 'popular' | 'top_rated'>('best_match');
+    const [scores, setScores] = useState<Record<string, PackageScore>>({});
     const [displayLimit, setDisplayLimit] = useState(50);
     const filterChipsRef = useRef<HTMLDivElement>(null);
 
@@ -43,6 +54,18 @@ export default function SearchPage({
         }
     }, [query]);
 
+    // Popularity and ratings only matter for those two sorts
+    useEffect(() => {
+        if (sortBy !== 'popular' && sortBy !== 'top_rated') return;
+        const names = (packages || []).map(p => p.name);
+        if (names.length === 0) return;
+        let cancelled = false;
+        invoke<Record<string, PackageScore>>('get_package_scores', { names })
+            .then((res) => { if (!cancelled) setScores(res); })
+            .catch(() => { });
+        return () => { cancelled = true; };
+    }, [sortBy, packages]);
+
     // Filtered & Sorted results
     const getFilteredResults = () => {
         const safePackages = packages || [];
@@ -76,6 +99,10 @@ export default function SearchPage({
             return (a.display_name || a.name).localeCompare(b.display_name || b.name);
         } else if (sortBy === 'updated') {
             return (b.last_modified || 0) - (a.last_modified || 0);
+        } else if (sortBy === 'popular') {
+            return (scores[b.name]?.popularity ?? 0) - (scores[a.name]?.popularity ?? 0);
+        } else if (sortBy === 'top_rated') {
+            return (scores[b.name]?.weighted_rating ?? 0) - (scores[a.name]?.weighted_rating ?? 0);
         }
         return 0; // Default backend order
     });
@@ -96,7 +123,8 @@ export default function SearchPage({
         'paint': 'Krita',
         'task manager': 'Stacer'
     };
-    const didYouMean = aliases[query.toLowerCase()];
+    const alias = aliases[query.toLowerCase()];
+    const didYouMean = alias ?? suggestion ?? undefined;
 
     return (
         <div className="flex-1 flex flex-col h-full overflow-hidden bg-app-bg">
@@ -126,6 +154,8 @@ export default function SearchPage({
                                     <option value="best_match">Relevant</option>
                                     <option value="name">Name</option>
                                     <option value="updated">Newest</option>
+                                    <option value="popular">Most Popular</option>
+                                    <option value="top_rated">Top Rated</option>
                                 </select>
                             </div>
                         </div>
@@ -202,6 +232,35 @@ export default function SearchPage({
                     </div>
                 )}
 
+                {/* Server-side filters */}
+                {query && onFiltersChange && (
+                    <div className="flex items-center gap-2 overflow-x-auto pb-2 no-scrollbar">
+                        {([
+                            { key: 'only_installed', label: 'Installed', clears: 'only_not_installed' },
+                            { key: 'only_not_installed', label: 'Not installed', clears: 'only_installed' },
+                            { key: 'foss_only', label: 'Open source only' },
+                            { key: 'optimized_only', label: 'CPU-optimized builds' },
+                        ] as { key: keyof SearchFilters; label: string; clears?: keyof SearchFilters }[]).map(({ key, label, clears }) => (
+                            <button
+                                key={key}
+                                onClick={() => onFiltersChange({
+                                    ...filters,
+                                    [key]: !filters[key],
+                                    ...(clears ? { [clears]: false } : {}),
+                                })}
+                                className={clsx(
+                                    "px-3 py-1.5 rounded-full text-[11px] font-bold transition-all border whitespace-nowrap",
+                                    filters[key]
+                                        ? "bg-accent/20 border-accent text-accent"
+                                        : "bg-app-card border-app-border text-app-muted hover:border-app-fg/30"
+                                )}
+                            >
+                                {label}
+                            </button>
+                        ))}
+                    </div>
+                )}
+
                 {!query && (
                     <div className="flex flex-wrap gap-2 pt-2 text-[11px] text-app-muted">
                         {[
@@ -341,7 +400,7 @@ export default function SearchPage({
                             ) : displayed.length === 0 ? (
                                 <EmptyState
                                     title="No apps found"
-                                    description={didYouMean ? `Did you mean '${didYouMean}'? Arch uses different apps than Windows.` : `We couldn't find any packages matching "${query}"${activeFilter !== 'all' ? ` in the ${activeFilter} source` : ''}.`}
+                                    description={alias ? `Did you mean '${alias}'? Arch uses different apps than Windows.` : didYouMean ? `Did you mean '${didYouMean}'?` : `We couldn't find any packages matching "${query}"${activeFilter !== 'all' ? ` in the ${activeFilter} source` : ''}.`}
                                     actionLabel={didYouMean ? `Search for ${didYouMean}` : "Clear filters & search again"}
                                     onAction={() => {
                                         if (didYouMean) onQueryChange(didYouMean);
@@ -350,6 +409,18 @@ export default function SearchPage({
                                 />
                             ) : (
                                 <>
+                                    {suggestion && (
+                                        <p className="text-sm text-app-muted max-w-7xl mx-auto w-full">
+                                            Did you mean{' '}
+                                            <button
+                                                onClick={() => onQueryChange(suggestion)}
+                                                className="font-bold text-accent hover:underline"
+                                            >
+                                                {suggestion}
+                                            </button>
+                                            ?
+                                        </p>
+                                    )}
                                     <div className="grid grid-cols-1 md:grid-cols-2 lg:grid-cols-3 xl:grid-cols-4 gap-6 max-w-7xl mx-auto w-full">
                                         {displayed.map((pkg) => (
                                             <PackageCard
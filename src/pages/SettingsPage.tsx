@@ -4,7 +4,7 @@ import {
     RefreshCw, Trash2, Key, Database, Info,
     ChevronRight, Moon, Sun, Monitor,
     Eye, Lock, AlertTriangle, Zap,
-    Activity, HardDrive, Layout, Fingerprint
+    Activity, HardDrive, Layout, Fingerprint, BarChart3
 } from 'lucide-react';
 import { clsx } from 'clsx';
 import { useTheme } from '../hooks/useTheme';
@@ -13,10 +13,23 @@ import { useSettings } from '../hooks/useSettings';
 import { useDistro } from '../hooks/useDistro';
 import { useAppStore } from '../store/internal_store';
 import { invoke } from '@tauri-apps/api/core';
+import { PkgstatsSettings } from '../types/alpm';
 
 // Internal Components
 import SourcesTab from '../components/settings/SourcesTab';
 import BuilderTab from '../components/settings/BuilderTab';
+import BackupSection from '../components/settings/BackupSection';
+import CatalogLanguageSection from '../components/settings/CatalogLanguageSection';
+import LocalApiSection from '../components/settings/LocalApiSection';
+import MaintenanceSection from '../components/settings/MaintenanceSection';
+import MyReviewsSection from '../components/settings/MyReviewsSection';
+import NetworkSection from '../components/settings/NetworkSection';
+import PackageCacheSection from '../components/settings/PackageCacheSection';
+import ParentalControlsSection from '../components/settings/ParentalControlsSection';
+import PrivilegedAgentSection from '../components/settings/PrivilegedAgentSection';
+import ReviewPacksSection from '../components/settings/ReviewPacksSection';
+import UpdateConditionsSection from '../components/settings/UpdateConditionsSection';
+import UpdatePoliciesSection from '../components/settings/UpdatePoliciesSection';
 import ConfirmationModal from '../components/ConfirmationModal';
 
 type TabId = 'general' | 'sources' | 'builder' | 'maintenance' | 'about';
@@ -41,6 +54,32 @@ export default function SettingsPage({ onRestartOnboarding, onRepairComplete }:
         reducePasswordPrompts, setReducePasswordPrompts,
     } = useAppStore();
 
+    const [pkgstats, setPkgstats] = useState<PkgstatsSettings | null>(null);
+
+    React.useEffect(() => {
+        invoke<PkgstatsSettings>('get_pkgstats_settings').then(setPkgstats).catch(() => { });
+    }, [telemetryEnabled]);
+
+    const togglePkgstats = async () => {
+        if (!pkgstats) return;
+        try {
+            await invoke('set_pkgstats_enabled', { enabled: !pkgstats.enabled });
+            setPkgstats({ ...pkgstats, enabled: !pkgstats.enabled });
+        } catch (e) {
+            error(String(e));
+        }
+    };
+
+    const sendPkgstats = async () => {
+        try {
+            const count = await invoke<number>('submit_pkgstats');
+            success(`Shared ${count} package names with pkgstats`);
+            setPkgstats(await invoke<PkgstatsSettings>('get_pkgstats_settings'));
+        } catch (e) {
+            error(String(e));
+        }
+    };
+
     const handleClearCache = async () => {
         await invoke('clear_cache');
     };
@@ -186,8 +225,24 @@ export default function SettingsPage({ onRestartOnboarding, onRepairComplete }:
                                         enabled={telemetryEnabled}
                                         onToggle={() => toggleTelemetry(!telemetryEnabled)}
                                     />
+                                    {pkgstats && (
+                                        <ToggleSetting
+                                            icon={<BarChart3 size={20} className="text-sky-500" />}
+                                            title="Share Package Statistics"
+                                            description={`Send the names of your installed repo packages to pkgstats.archlinux.de once a week, like the pkgstats tool. AUR and locally built packages are left out. Needs Anonymous Telemetry.${pkgstats.last_submitted ? ` Last sent ${new Date(pkgstats.last_submitted * 1000).toLocaleDateString()}.` : ''}`}
+                                            enabled={pkgstats.enabled}
+                                            onToggle={togglePkgstats}
+                                        />
+                                    )}
+                                    {pkgstats?.enabled && telemetryEnabled && (
+                                        <button onClick={sendPkgstats} className="text-xs font-bold text-blue-500 hover:underline px-2">
+                                            Send package statistics now
+                                        </button>
+                                    )}
                                 </div>
                             </section>
+
+                            <PrivilegedAgentSection />
                         </div>
                     )}
 
@@ -253,6 +308,28 @@ export default function SettingsPage({ onRestartOnboarding, onRepairComplete }:
                                 </div>
                             </section>
 
+                            <PackageCacheSection />
+
+                            <MaintenanceSection />
+
+                            <UpdatePoliciesSection />
+
+                            <UpdateConditionsSection />
+
+                            <CatalogLanguageSection />
+
+                            <ParentalControlsSection />
+
+                            <NetworkSection />
+
+                            <LocalApiSection />
+
+                            <MyReviewsSection />
+
+                            <ReviewPacksSection />
+
+                            <BackupSection />
+
                             <section className="pt-6 border-t border-slate-100 dark:border-white/5">
                                 <div className="bg-red-500/5 dark:bg-red-500/10 border border-red-500/20 rounded-2xl p-6 flex flex-col sm:flex-row items-center justify-between gap-6">
                                     <div className="flex gap-4">
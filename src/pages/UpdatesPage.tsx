@@ -1,48 +1,95 @@
 import { useState, useEffect } from 'react';
-import { RefreshCw, ArrowRight, CheckCircle2, Download, AlertCircle, Unlock, Loader2, Terminal } from 'lucide-react';
+import { RefreshCw, ArrowRight, CheckCircle2, Download, AlertCircle, Unlock, Loader2, Terminal, Pin, GitBranch, ChevronDown, ChevronRight, PackageCheck, PackageX, Cpu, Zap, Hand, FileText } from 'lucide-react';
 import { motion, AnimatePresence } from 'framer-motion';
 import ConfirmationModal from '../components/ConfirmationModal';
 import { clsx } from 'clsx';
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
+import { openUrl } from '@tauri-apps/plugin-opener';
 import { useAppStore } from '../store/internal_store';
 import { useErrorService } from '../context/ErrorContext';
 import { useToast } from '../context/ToastContext';
 import { useSessionPassword } from '../context/useSessionPassword';
 import { friendlyError } from '../utils/friendlyError';
+import { useIcons } from '../hooks/useIcons';
 
-import { UpdateItem } from '../types/alpm';
+import { DeferredInstall, DevelUpdateSettings, DroppedPackage, MigrationSuggestion, PolicyAnnotation, UpdateCategory, UpdateChangelog, UpdateConditionsInfo, UpdateItem, UpdateSet } from '../types/alpm';
 import RepoBadge from '../components/RepoBadge';
 
 
-// Helper component for Icon
-import archLogo from '../assets/arch-logo.png';
+/** An available update held back by a package pin (get_held_updates). */
+type HeldUpdate = { name: string; old_version: string; new_version: string; repo: string };
 
-const AppIcon = ({ pkgId }: { pkgId: string }) => {
-    const [icon, setIcon] = useState<string | null>(null);
+/** Distro news published since the last upgrade (get_unread_news). */
+type NewsItem = { source: string; title: string; link: string; published: number; summary: string; breaking: boolean };
 
-    useEffect(() => {
-        if (!pkgId) return;
-        invoke<string | null>('get_package_icon', { pkgName: pkgId })
-            .then(localIcon => {
-                if (localIcon) {
-                    setIcon(localIcon);
-                } else {
-                    invoke<any>('get_metadata', { pkgName: pkgId, upstreamUrl: null })
-                        .then(meta => {
-                            if (meta && meta.icon_url) setIcon(meta.icon_url);
-                        })
-                        .catch(() => { });
-                }
-            })
-            .catch(() => { });
-    }, [pkgId]);
+/** Updates page grouping order (update_policy categories). */
+const CATEGORY_ORDER: UpdateCategory[] = ['security', 'browsers', 'kernels', 'drivers', 'other'];
+const CATEGORY_LABELS: Record<UpdateCategory, string> = {
+    security: 'Security',
+    browsers: 'Browsers',
+    kernels: 'Kernels',
+    drivers: 'Drivers',
+    other: 'Applications & Libraries',
+};
 
+// Helper component for Icon
+import archLogo from '../assets/arch-logo.png';
+
+/** Icon from the batched lookup (useIcons); the Arch logo, dimmed, when there is none. */
+const AppIcon = ({ pkgId, icon }: { pkgId: string; icon?: string }) => {
     const displayIcon = icon || archLogo;
 
     return <img src={displayIcon} alt={pkgId} className={clsx("w-full h-full object-contain", !icon && "opacity-50 grayscale")} />;
 };
 
+const CHANGELOG_SOURCE_LABELS: Record<string, string> = {
+    package: 'Changelog shipped with the package',
+    upstream_releases: 'Upstream release notes',
+    packaging_commits: 'Arch packaging commits',
+};
+
+/** What changed between the installed and the new version, fetched when expanded. */
+const ChangelogPanel = ({ item }: { item: UpdateItem }) => {
+    const [log, setLog] = useState<UpdateChangelog | null>(null);
+    const [failed, setFailed] = useState(false);
+
+    useEffect(() => {
+        invoke<UpdateChangelog>('get_update_changelog', { pkg: item.name, oldVer: item.current_version, newVer: item.new_version })
+            .then(setLog)
+            .catch(() => setFailed(true));
+    }, [item.name, item.current_version, item.new_version]);
+
+    if (!log && !failed) {
+        return <div className="flex items-center gap-2 text-sm text-app-muted"><Loader2 size={14} className="animate-spin" /> Looking for release notes...</div>;
+    }
+    if (!log || !log.source || log.entries.length === 0) {
+        return <p className="text-sm text-app-muted">No changelog found for this update.</p>;
+    }
+    return (
+        <div className="space-y-3">
+            <p className="text-xs font-bold text-app-muted uppercase tracking-wider">{CHANGELOG_SOURCE_LABELS[log.source]}</p>
+            {log.entries.map((entry, i) => (
+                <div key={i} className="space-y-1">
+                    <div className="flex items-center gap-3 text-sm">
+                        {entry.url ? (
+                            <button onClick={() => openUrl(entry.url!).catch(() => { })} className="font-bold text-blue-600 dark:text-blue-400 hover:underline text-left">
+                                {entry.title}
+                            </button>
+                        ) : (
+                            <span className="font-bold text-app-fg">{entry.title}</span>
+                        )}
+                        {entry.date && <span className="text-xs text-app-muted">{new Date(entry.date).toLocaleDateString()}</span>}
+                    </div>
+                    {entry.body && (
+                        <pre className="text-xs text-app-muted whitespace-pre-wrap font-sans max-h-48 overflow-y-auto">{entry.body}</pre>
+                    )}
+                </div>
+            ))}
+        </div>
+    );
+};
+
 export default function UpdatesPage() {
     const errorService = useErrorService();
     const { success: toastSuccess } = useToast();
@@ -61,6 +108,16 @@ export default function UpdatesPage() {
     } = useAppStore();
 
     const [updates, setUpdates] = useState<UpdateItem[]>([]);
+    // One batched icon lookup for the whole list instead of one call per row
+    const icons = useIcons(updates.filter(u => u.source.source_type !== 'firmware').map(u => u.name));
+    const [openChangelog, setOpenChangelog] = useState<string | null>(null);
+    const [policies, setPolicies] = useState<Record<string, PolicyAnnotation>>({});
+    // Backends whose check failed (e.g. flatpak missing); the rest of the list is still valid
+    const [checkErrors, setCheckErrors] = useState<Record<string, string>>({});
+    const [heldUpdates, setHeldUpdates] = useState<HeldUpdate[]>([]);
+    // Development (-git/-svn) packages excluded from routine checks (get_devel_updates)
+    const [develUpdates, setDevelUpdates] = useState<HeldUpdate[]>([]);
+    const [showDevel, setShowDevel] = useState(false);
     const [isChecking, setIsChecking] = useState(true);
     const [updateResult, setUpdateResult] = useState<string | null>(null);
     const [showConsole, setShowConsole] = useState(false);
@@ -69,6 +126,14 @@ export default function UpdatesPage() {
     const [showAuthHint, setShowAuthHint] = useState(false);
     const [orphansAfterUpdate, setOrphansAfterUpdate] = useState<string[]>([]);
     const [removingOrphans, setRemovingOrphans] = useState(false);
+    // Upgrades downloaded with "Download Only", waiting for "Install now"
+    const [deferred, setDeferred] = useState<DeferredInstall[]>([]);
+    const [deferredBusy, setDeferredBusy] = useState<'download' | 'install' | null>(null);
+    // AUR packages a binary repo now ships; reinstalling from the repo skips future builds
+    const [migrations, setMigrations] = useState<MigrationSuggestion[]>([]);
+    const [migrating, setMigrating] = useState(false);
+    // Repo binaries whose repo dropped them; switching takes future updates from the AUR
+    const [dropped, setDropped] = useState<DroppedPackage[]>([]);
 
     const isLockOrBusyError = updateResult != null && /lock|busy|database.*(locked|busy)/i.test(updateResult);
 
@@ -87,7 +152,7 @@ export default function UpdatesPage() {
     const steps = [
         "Synchronizing Databases",
         "Upgrading System",
-        "Updating Community Apps"
+        "Updating Community Apps & Firmware"
     ];
 
     // Fetch updates on mount
@@ -95,12 +160,23 @@ export default function UpdatesPage() {
         checkForUpdates();
     }, []);
 
+    // Re-check when packages or sync DBs change outside MonArch; our own update run reports
+    // through update-complete instead
+    useEffect(() => {
+        if (isUpdating) return;
+        const unlisten = listen<{ external: boolean }>('system-state-changed', (event) => {
+            if (event.payload.external) checkForUpdates();
+        });
+        return () => { unlisten.then((fn) => fn()).catch(() => { }); };
+    }, [isUpdating]);
+
     useEffect(() => {
         if (statusMessage?.toLowerCase().includes("database") || statusMessage?.toLowerCase().includes("sync")) {
             setCurrentStep(0);
         } else if (statusMessage?.toLowerCase().includes("upgrade") || statusMessage?.toLowerCase().includes("installing core")) {
             setCurrentStep(1);
-        } else if (statusMessage?.toLowerCase().includes("aur") || statusMessage?.toLowerCase().includes("community")) {
+        } else if (statusMessage?.toLowerCase().includes("aur") || statusMessage?.toLowerCase().includes("community")
+            || statusMessage?.toLowerCase().includes("flatpak") || statusMessage?.toLowerCase().includes("firmware")) {
             setCurrentStep(2);
         }
     }, [statusMessage]);
@@ -109,8 +185,17 @@ export default function UpdatesPage() {
         setIsChecking(true);
         setUpdateResult(null);
         try {
-            const pendingUpdates = await invoke<UpdateItem[]>('check_updates');
-            setUpdates(pendingUpdates);
+            const set = await invoke<UpdateSet>('check_updates');
+            const all = [...set.repo, ...set.aur, ...set.flatpak, ...set.firmware];
+            setUpdates(all);
+            const annotations = await invoke<PolicyAnnotation[]>('classify_updates', { names: all.map((u) => u.name) }).catch(() => [] as PolicyAnnotation[]);
+            setPolicies(Object.fromEntries(annotations.map((a) => [a.name, a])));
+            setCheckErrors(set.errors);
+            setHeldUpdates(await invoke<HeldUpdate[]>('get_held_updates'));
+            setDevelUpdates(await invoke<HeldUpdate[]>('get_devel_updates'));
+            setDeferred(await invoke<DeferredInstall[]>('list_deferred_installs'));
+            setMigrations(await invoke<MigrationSuggestion[]>('get_migration_suggestions'));
+            setDropped(await invoke<DroppedPackage[]>('get_dropped_packages'));
         } catch (e) {
             errorService.reportError(e as Error | string);
         } finally {
@@ -119,6 +204,8 @@ export default function UpdatesPage() {
     };
 
     const [showConfirm, setShowConfirm] = useState(false);
+    const [unreadNews, setUnreadNews] = useState<NewsItem[]>([]);
+    const [holdReason, setHoldReason] = useState<string | null>(null);
 
     // Listen for update-complete so we don't block the UI waiting for the backend.
     useEffect(() => {
@@ -148,12 +235,24 @@ export default function UpdatesPage() {
         };
     }, [setUpdating, setPacnewWarnings]);
 
-    const handleUpdateAll = () => {
+    const handleUpdateAll = async () => {
+        const [news, conditions] = await Promise.all([
+            invoke<NewsItem[]>('get_unread_news').catch(() => [] as NewsItem[]),
+            invoke<UpdateConditionsInfo>('get_update_conditions').catch(() => null),
+        ]);
+        setUnreadNews(news);
+        setHoldReason(conditions?.hold_reason ?? null);
         setShowConfirm(true);
     };
 
     const performUpdate = async () => {
         setShowConfirm(false);
+        if (unreadNews.length > 0) {
+            // Confirming the dialog is the acknowledgement; the backend refuses while breaking news is unread
+            await invoke('mark_news_read', { links: unreadNews.map(n => n.link) })
+                .catch((e) => errorService.reportWarning(e as Error | string));
+            setUnreadNews([]);
+        }
         setUpdating(true);
         setUpdateResult(null);
         clearUpdateLogs();
@@ -161,13 +260,89 @@ export default function UpdatesPage() {
 
         // Always use Polkit for privilege (no in-app password). Avoids double prompt (app + system).
         // Fire-and-forget: never await so the UI never blocks. Backend returns "started" and runs update in background.
-        invoke<string>('perform_system_update', { password: null }).catch((e) => {
+        // Flatpak and firmware updates go through apply_updates, which runs every backend in turn.
+        const targets = updates.filter((u) => !u.downgrade);
+        const needsApply = targets.some((u) => u.source.source_type === 'flatpak' || u.source.source_type === 'firmware');
+        const run = needsApply
+            ? invoke<string>('apply_updates', { targets, password: null })
+            : invoke<string>('perform_system_update', { password: null });
+        run.catch((e) => {
             errorService.reportError(e as Error | string);
             setUpdateResult(`Update failed: ${e}`);
             setUpdating(false);
         });
     };
 
+    const downloadForLater = async () => {
+        setDeferredBusy('download');
+        try {
+            const pwd = reducePasswordPrompts ? await requestSessionPassword() : null;
+            const ready = await invoke<DeferredInstall>('download_for_later', { packages: null, targetRepo: null, password: pwd });
+            setDeferred((prev) => [...prev.filter((d) => d.target.kind !== 'upgrade'), ready]);
+        } catch (e) {
+            errorService.reportError(e as Error | string);
+        } finally {
+            setDeferredBusy(null);
+        }
+    };
+
+    const installDeferred = async (token: string) => {
+        setDeferredBusy('install');
+        try {
+            const pwd = reducePasswordPrompts ? await requestSessionPassword() : null;
+            const installed = await invoke<string[]>('resume_deferred_install', { token, password: pwd });
+            toastSuccess(`Installed ${installed.length} downloaded package${installed.length !== 1 ? 's' : ''}.`);
+        } catch (e) {
+            errorService.reportError(e as Error | string);
+        } finally {
+            setDeferredBusy(null);
+            checkForUpdates();
+        }
+    };
+
+    const discardDeferred = async (token: string) => {
+        await invoke('discard_deferred_install', { token }).catch((e) => errorService.reportWarning(e as Error | string));
+        setDeferred((prev) => prev.filter((d) => d.token !== token));
+    };
+
+    // The scheduler announces newly found suggestions between visits
+    useEffect(() => {
+        const unlisten = listen<MigrationSuggestion[]>('migration-suggestions', (event) => setMigrations(event.payload));
+        const unlistenDropped = listen<DroppedPackage[]>('dropped-packages', (event) => setDropped(event.payload));
+        return () => {
+            unlisten.then((fn) => fn()).catch(() => { });
+            unlistenDropped.then((fn) => fn()).catch(() => { });
+        };
+    }, []);
+
+    const switchableDropped = dropped.filter((d) => !d.switched && d.aur_version);
+    const strandedDropped = dropped.filter((d) => !d.aur_version);
+
+    const switchDroppedToAur = async () => {
+        try {
+            const switched = await invoke<string[]>('switch_to_aur', { packages: switchableDropped.map((d) => d.name) });
+            toastSuccess(`${switched.length} package${switched.length !== 1 ? 's' : ''} will now update from the AUR.`);
+        } catch (e) {
+            errorService.reportError(e as Error | string);
+        } finally {
+            checkForUpdates();
+        }
+    };
+
+    const migrateAll = async () => {
+        setMigrating(true);
+        try {
+            const pwd = reducePasswordPrompts ? await requestSessionPassword() : null;
+            const migrated = await invoke<string[]>('migrate_to_repo', { packages: migrations.map((m) => m.name), password: pwd });
+            toastSuccess(`Switched ${migrated.length} package${migrated.length !== 1 ? 's' : ''} to prebuilt binaries.`);
+        } catch (e) {
+            errorService.reportError(e as Error | string);
+        } finally {
+            setMigrating(false);
+            checkForUpdates();
+        }
+    };
+
     const needsReboot = updates.some(u => u.name === 'linux' || u.name.startsWith('nvidia'));
 
     return (
@@ -214,6 +389,17 @@ export default function UpdatesPage() {
                         >
                             <Terminal size={18} /> Update in terminal
                         </button>
+                        {updates.length > 0 && !isUpdating && (
+                            <button
+                                onClick={downloadForLater}
+                                disabled={deferredBusy !== null}
+                                className="px-6 py-3 rounded-xl bg-black/5 dark:bg-white/5 hover:bg-black/10 dark:hover:bg-white/10 text-slate-900 dark:text-white font-bold text-sm border border-black/10 dark:border-white/10 transition-all disabled:opacity-50 flex items-center gap-2 active:scale-95"
+                                title="Download official repo updates now and install them later"
+                            >
+                                {deferredBusy === 'download' ? <Loader2 size={18} className="animate-spin" /> : <Download size={18} />}
+                                {deferredBusy === 'download' ? 'Downloading…' : 'Download Only'}
+                            </button>
+                        )}
                         {updates.length > 0 && !isUpdating && (
                             <button
                                 onClick={handleUpdateAll}
@@ -364,6 +550,121 @@ export default function UpdatesPage() {
                     )}
                 </AnimatePresence>
 
+                {/* Downloads waiting for the commit phase */}
+                <AnimatePresence>
+                    {deferred.length > 0 && !isUpdating && deferred.map((d) => (
+                        <motion.div
+                            key={d.token}
+                            initial={{ height: 0, opacity: 0 }}
+                            animate={{ height: 'auto', opacity: 1 }}
+                            exit={{ height: 0, opacity: 0 }}
+                            className="mt-6 p-4 rounded-xl bg-blue-500/10 border border-blue-500/20 flex flex-col sm:flex-row items-start sm:items-center justify-between gap-3"
+                        >
+                            <div className="flex items-center gap-3">
+                                <Download size={20} className="text-blue-500 shrink-0" />
+                                <div>
+                                    <span className="font-bold text-sm block text-slate-900 dark:text-white">Download complete — install now or later?</span>
+                                    <span className="text-xs text-app-muted">
+                                        {d.changes.length} package{d.changes.length !== 1 ? 's' : ''} ({(d.download_bytes / 1048576).toFixed(1)} MB) downloaded {new Date(d.created_at * 1000).toLocaleString()}.
+                                        Versions are checked again before installing.
+                                    </span>
+                                </div>
+                            </div>
+                            <div className="flex items-center gap-2 shrink-0">
+                                <button
+                                    onClick={() => discardDeferred(d.token)}
+                                    disabled={deferredBusy !== null}
+                                    className="px-4 py-2 rounded-lg bg-black/5 dark:bg-white/5 hover:bg-black/10 dark:hover:bg-white/10 text-slate-900 dark:text-white text-sm font-bold disabled:opacity-50"
+                                >
+                                    Discard
+                                </button>
+                                <button
+                                    onClick={() => installDeferred(d.token)}
+                                    disabled={deferredBusy !== null}
+                                    className="px-4 py-2 rounded-lg bg-blue-600 hover:bg-blue-500 text-white text-sm font-bold flex items-center gap-2 disabled:opacity-50"
+                                >
+                                    {deferredBusy === 'install' ? <Loader2 size={16} className="animate-spin" /> : null}
+                                    {deferredBusy === 'install' ? 'Installing…' : 'Install now'}
+                                </button>
+                            </div>
+                        </motion.div>
+                    ))}
+                </AnimatePresence>
+
+                {/* AUR builds now available prebuilt */}
+                <AnimatePresence>
+                    {migrations.length > 0 && !isUpdating && (
+                        <motion.div
+                            initial={{ height: 0, opacity: 0 }}
+                            animate={{ height: 'auto', opacity: 1 }}
+                            exit={{ height: 0, opacity: 0 }}
+                            className="mt-6 p-4 rounded-xl bg-emerald-500/10 border border-emerald-500/20 flex flex-col sm:flex-row items-start sm:items-center justify-between gap-3"
+                        >
+                            <div className="flex items-center gap-3">
+                                <PackageCheck size={20} className="text-emerald-500 shrink-0" />
+                                <div>
+                                    <span className="font-bold text-sm block text-slate-900 dark:text-white">
+                                        {migrations.length} AUR package{migrations.length !== 1 ? 's are' : ' is'} now available prebuilt
+                                    </span>
+                                    <span className="text-xs text-app-muted">
+                                        {migrations.map((m) => `${m.name} (${m.repo} ${m.repo_version})`).join(', ')}.
+                                        Switching skips building them on future updates.
+                                    </span>
+                                </div>
+                            </div>
+                            <button
+                                onClick={migrateAll}
+                                disabled={migrating || deferredBusy !== null}
+                                className="shrink-0 px-4 py-2 rounded-lg bg-emerald-600 hover:bg-emerald-500 text-white text-sm font-bold flex items-center gap-2 disabled:opacity-50"
+                            >
+                                {migrating ? <Loader2 size={16} className="animate-spin" /> : null}
+                                {migrating ? 'Switching…' : 'Switch to binaries'}
+                            </button>
+                        </motion.div>
+                    )}
+                </AnimatePresence>
+
+                {/* Repo binaries their repo no longer ships */}
+                <AnimatePresence>
+                    {(switchableDropped.length > 0 || strandedDropped.length > 0) && !isUpdating && (
+                        <motion.div
+                            initial={{ height: 0, opacity: 0 }}
+                            animate={{ height: 'auto', opacity: 1 }}
+                            exit={{ height: 0, opacity: 0 }}
+                            className="mt-6 p-4 rounded-xl bg-amber-500/10 border border-amber-500/20 flex flex-col sm:flex-row items-start sm:items-center justify-between gap-3"
+                        >
+                            <div className="flex items-center gap-3">
+                                <PackageX size={20} className="text-amber-500 shrink-0" />
+                                <div>
+                                    <span className="font-bold text-sm block text-slate-900 dark:text-white">
+                                        {switchableDropped.length + strandedDropped.length} package{switchableDropped.length + strandedDropped.length !== 1 ? 's were' : ' was'} dropped by {switchableDropped.length + strandedDropped.length !== 1 ? 'their repos' : 'its repo'}
+                                    </span>
+                                    {switchableDropped.length > 0 && (
+                                        <span className="text-xs text-app-muted block">
+                                            {switchableDropped.map((d) => `${d.name} (AUR ${d.aur_version})`).join(', ')}.
+                                            Switch them to the AUR so they keep getting updates.
+                                        </span>
+                                    )}
+                                    {strandedDropped.length > 0 && (
+                                        <span className="text-xs text-app-muted block">
+                                            No longer available anywhere: {strandedDropped.map((d) => d.name).join(', ')}.
+                                        </span>
+                                    )}
+                                </div>
+                            </div>
+                            {switchableDropped.length > 0 && (
+                                <button
+                                    onClick={switchDroppedToAur}
+                                    disabled={migrating || deferredBusy !== null}
+                                    className="shrink-0 px-4 py-2 rounded-lg bg-amber-600 hover:bg-amber-500 text-white text-sm font-bold flex items-center gap-2 disabled:opacity-50"
+                                >
+                                    Update from AUR
+                                </button>
+                            )}
+                        </motion.div>
+                    )}
+                </AnimatePresence>
+
                 {/* Orphan cleanup after successful update */}
                 <AnimatePresence>
                     {orphansAfterUpdate.length > 0 && !isUpdating && (
@@ -467,38 +768,168 @@ export default function UpdatesPage() {
                     </div>
                 ) : (
                     <div className="space-y-3 max-w-5xl mx-auto">
-                        {updates.map((pkg) => (
-                            <div
-                                key={pkg.name}
-                                className="bg-white dark:bg-app-card border border-black/5 dark:border-white/5 rounded-2xl p-5 flex items-center justify-between hover:bg-white/80 dark:hover:bg-white/5 transition-all group hover:scale-[1.01] hover:shadow-xl hover:border-black/10 dark:hover:border-white/10"
-                            >
-                                <div className="flex items-center gap-6">
-                                    <div className="w-14 h-14 rounded-xl bg-slate-50 dark:bg-black/20 flex items-center justify-center shrink-0 overflow-hidden relative p-2 border border-black/5 dark:border-white/5 shadow-inner">
-                                        <AppIcon pkgId={pkg.name} />
-                                    </div>
-                                    <div>
-                                        <h3 className="font-bold flex items-center gap-3 text-xl text-slate-900 dark:text-white mb-1">
-                                            {pkg.name}
-                                            <RepoBadge source={pkg.source} />
-                                        </h3>
-                                        <div className="flex items-center gap-3 text-sm font-medium">
-                                            <span className="text-slate-400 dark:text-app-muted line-through opacity-50">{pkg.current_version}</span>
-                                            <ArrowRight size={14} className="text-slate-300 dark:text-white/20" />
-                                            <span className="text-emerald-600 dark:text-emerald-400">{pkg.new_version}</span>
+                        {Object.entries(checkErrors).map(([backend, error]) => (
+                            <div key={backend} className="px-5 py-3 rounded-xl bg-amber-500/10 border border-amber-500/20 text-sm text-amber-700 dark:text-amber-400 flex items-center gap-2">
+                                <AlertCircle size={16} className="shrink-0" />
+                                <span>Could not check {backend === 'repo' ? 'repository' : backend === 'aur' ? 'AUR' : backend === 'flatpak' ? 'Flatpak' : 'firmware'} updates: {error}</span>
+                            </div>
+                        ))}
+                        {CATEGORY_ORDER.map((category) => ({ category, items: updates.filter((u) => (policies[u.name]?.category ?? 'other') === category) }))
+                            .filter(({ items }) => items.length > 0)
+                            .map(({ category, items }) => (
+                            <div key={category} className="space-y-3">
+                                <h4 className="text-sm font-bold text-app-muted uppercase tracking-wider pt-2">
+                                    {CATEGORY_LABELS[category]} ({items.length})
+                                </h4>
+                                {items.map((pkg) => (
+                                    <div key={pkg.name} className="space-y-2">
+                                        <div
+                                            className="bg-white dark:bg-app-card border border-black/5 dark:border-white/5 rounded-2xl p-5 flex items-center justify-between hover:bg-white/80 dark:hover:bg-white/5 transition-all group hover:scale-[1.01] hover:shadow-xl hover:border-black/10 dark:hover:border-white/10"
+                                        >
+                                            <div className="flex items-center gap-6">
+                                                <div className="w-14 h-14 rounded-xl bg-slate-50 dark:bg-black/20 flex items-center justify-center shrink-0 overflow-hidden relative p-2 border border-black/5 dark:border-white/5 shadow-inner">
+                                                    {pkg.source.source_type === 'firmware'
+                                                        ? <Cpu size={28} className="text-slate-400 dark:text-app-muted" />
+                                                        : <AppIcon pkgId={pkg.name} icon={icons[pkg.name]} />}
+                                                </div>
+                                                <div>
+                                                    <h3 className="font-bold flex items-center gap-3 text-xl text-slate-900 dark:text-white mb-1">
+                                                        {pkg.display_name ?? pkg.name}
+                                                        <RepoBadge source={pkg.source} />
+                                                    </h3>
+                                                    <div className="flex items-center gap-3 text-sm font-medium">
+                                                        <span className="text-slate-400 dark:text-app-muted line-through opacity-50">{pkg.current_version}</span>
+                                                        <ArrowRight size={14} className="text-slate-300 dark:text-white/20" />
+                                                        <span className={pkg.downgrade ? "text-amber-600 dark:text-amber-400" : "text-emerald-600 dark:text-emerald-400"}>{pkg.new_version}</span>
+                                                        {pkg.source.source_type !== 'firmware' && pkg.source.source_type !== 'flatpak' && (
+                                                            <button
+                                                                onClick={() => setOpenChangelog(openChangelog === pkg.name ? null : pkg.name)}
+                                                                className="flex items-center gap-1 text-xs font-bold text-app-muted hover:text-app-fg transition-colors"
+                                                            >
+                                                                <FileText size={14} />
+                                                                What changed
+                                                            </button>
+                                                        )}
+                                                    </div>
+                                                </div>
+                                            </div>
+        
+                                            <div className="flex items-center gap-6">
+                                                {policies[pkg.name]?.policy === 'auto' && (
+                                                    <div title="Applied automatically by the background updater" className="flex items-center gap-2 px-3 py-1.5 rounded-lg bg-emerald-100 dark:bg-emerald-500/10 border border-emerald-200 dark:border-emerald-500/20 text-emerald-700 dark:text-emerald-400 text-xs font-bold">
+                                                        <Zap size={14} />
+                                                        <span>Auto</span>
+                                                    </div>
+                                                )}
+                                                {policies[pkg.name]?.policy === 'hold' && (
+                                                    <div title="Held for manual confirmation: never applied in the background" className="flex items-center gap-2 px-3 py-1.5 rounded-lg bg-red-100 dark:bg-red-500/10 border border-red-200 dark:border-red-500/20 text-red-700 dark:text-red-400 text-xs font-bold">
+                                                        <Hand size={14} />
+                                                        <span>Held</span>
+                                                    </div>
+                                                )}
+                                                {pkg.downgrade && (
+                                                    <div title="The available version is older than the one installed; it is not applied by Update All" className="flex items-center gap-2 px-3 py-1.5 rounded-lg bg-orange-100 dark:bg-orange-500/10 border border-orange-200 dark:border-orange-500/20 text-orange-700 dark:text-orange-400 text-xs font-bold">
+                                                        <AlertCircle size={14} />
+                                                        <span>Downgrade</span>
+                                                    </div>
+                                                )}
+                                                {pkg.source.source_type === 'aur' && (
+                                                    <div title="AUR Package: May take longer to build" className="flex items-center gap-2 px-3 py-1.5 rounded-lg bg-amber-100 dark:bg-amber-500/10 border border-amber-200 dark:border-amber-500/20 text-amber-700 dark:text-amber-500 text-xs font-bold">
+                                                        <AlertCircle size={14} />
+                                                        <span>Built from Source</span>
+                                                    </div>
+                                                )}
+                                            </div>
                                         </div>
+                                        {openChangelog === pkg.name && (
+                                            <div className="mx-5 p-4 rounded-xl bg-slate-50 dark:bg-black/20 border border-black/5 dark:border-white/5">
+                                                <ChangelogPanel item={pkg} />
+                                            </div>
+                                        )}
                                     </div>
+                                ))}
+                            </div>
+                            ))}
+                    </div>
+                )}
+
+                {/* Pinned packages: held back from upgrades until unpinned */}
+                {!isChecking && heldUpdates.length > 0 && (
+                    <div className="max-w-5xl mx-auto mt-8">
+                        <h4 className="text-sm font-bold text-app-muted uppercase tracking-wider mb-3 flex items-center gap-2">
+                            <Pin size={14} /> Held back by pins ({heldUpdates.length})
+                        </h4>
+                        <div className="space-y-2">
+                            {heldUpdates.map((pkg) => (
+                                <div key={pkg.name} className="bg-white/60 dark:bg-app-card/50 border border-black/5 dark:border-white/5 rounded-xl px-5 py-3 flex items-center justify-between">
+                                    <div className="flex items-center gap-3 text-sm">
+                                        <span className="font-bold text-slate-900 dark:text-white">{pkg.name}</span>
+                                        <span className="text-app-muted">{pkg.old_version}</span>
+                                        <ArrowRight size={12} className="text-slate-300 dark:text-white/20" />
+                                        <span className="text-app-muted">{pkg.new_version}</span>
+                                    </div>
+                                    <button
+                                        onClick={async () => {
+                                            try {
+                                                const pwd = reducePasswordPrompts ? await requestSessionPassword() : null;
+                                                await invoke('unpin_package', { name: pkg.name, password: pwd });
+                                                await checkForUpdates();
+                                            } catch (e) {
+                                                errorService.reportError(e as Error | string);
+                                            }
+                                        }}
+                                        className="px-3 py-1.5 rounded-lg bg-slate-500/10 hover:bg-slate-500/20 text-xs font-bold"
+                                    >
+                                        Unpin
+                                    </button>
                                 </div>
+                            ))}
+                        </div>
+                    </div>
+                )}
 
-                                <div className="flex items-center gap-6">
-                                    {pkg.source.source_type === 'aur' && (
-                                        <div title="AUR Package: May take longer to build" className="flex items-center gap-2 px-3 py-1.5 rounded-lg bg-amber-100 dark:bg-amber-500/10 border border-amber-200 dark:border-amber-500/20 text-amber-700 dark:text-amber-500 text-xs font-bold">
-                                            <AlertCircle size={14} />
-                                            <span>Built from Source</span>
+                {/* Development packages: excluded from routine AUR updates (Settings → Builder) */}
+                {!isChecking && develUpdates.length > 0 && (
+                    <div className="max-w-5xl mx-auto mt-8">
+                        <button
+                            onClick={() => setShowDevel(!showDevel)}
+                            className="text-sm font-bold text-app-muted uppercase tracking-wider mb-3 flex items-center gap-2 hover:text-app-fg transition-colors"
+                        >
+                            {showDevel ? <ChevronDown size={14} /> : <ChevronRight size={14} />}
+                            <GitBranch size={14} /> Development packages ({develUpdates.length})
+                        </button>
+                        {showDevel && (
+                            <div className="space-y-2">
+                                {develUpdates.map((pkg) => (
+                                    <div key={pkg.name} className="bg-white/60 dark:bg-app-card/50 border border-black/5 dark:border-white/5 rounded-xl px-5 py-3 flex items-center justify-between">
+                                        <div className="flex items-center gap-3 text-sm">
+                                            <span className="font-bold text-slate-900 dark:text-white">{pkg.name}</span>
+                                            <span className="text-app-muted">{pkg.old_version}</span>
+                                            <ArrowRight size={12} className="text-slate-300 dark:text-white/20" />
+                                            <span className="text-app-muted">{pkg.new_version}</span>
                                         </div>
-                                    )}
-                                </div>
+                                        <button
+                                            onClick={async () => {
+                                                try {
+                                                    const settings = await invoke<DevelUpdateSettings>('get_devel_update_settings');
+                                                    await invoke('set_devel_update_settings', {
+                                                        exclude: settings.exclude,
+                                                        alwaysCheck: [...settings.always_check, pkg.name]
+                                                    });
+                                                    await checkForUpdates();
+                                                } catch (e) {
+                                                    errorService.reportError(e as Error | string);
+                                                }
+                                            }}
+                                            title="Check this package like any other AUR package"
+                                            className="px-3 py-1.5 rounded-lg bg-slate-500/10 hover:bg-slate-500/20 text-xs font-bold"
+                                        >
+                                            Always check
+                                        </button>
+                                    </div>
+                                ))}
                             </div>
-                        ))}
+                        )}
                     </div>
                 )}
             </div>
@@ -509,13 +940,21 @@ export default function UpdatesPage() {
         setShowConfirm(false);
                 }}
                 onConfirm={performUpdate}
-                title="Update System"
-    message={updates.some(u => u.source.source_type === 'aur')
-        ? "This update includes AUR packages which require building from source. It may take longer to complete."
-        : "This will update all system packages. Are you ready to proceed?"
+                title={unreadNews.some(n => n.breaking) ? "Read Before Updating" : "Update System"}
+    message={[
+        unreadNews.length > 0
+            ? `News since your last update: ${unreadNews.map(n => (n.breaking ? `⚠ ${n.title} (manual intervention)` : n.title)).join(' • ')}. Details: ${unreadNews[0].link}`
+            : '',
+        holdReason
+            ? `⚠ ${holdReason}. A full update can be a large download and should not be interrupted; consider waiting.`
+            : '',
+        updates.some(u => u.source.source_type === 'aur')
+            ? "This update includes AUR packages which require building from source. It may take longer to complete."
+            : "This will update all system packages. Are you ready to proceed?",
+    ].filter(Boolean).join(' ')
                 }
-                confirmLabel="Start Update"
-                variant="info"
+                confirmLabel={unreadNews.some(n => n.breaking) ? "I've Read It, Update" : "Start Update"}
+                variant={unreadNews.some(n => n.breaking) ? "danger" : "info"}
     showPasswordInput={false}
             />
         </div>
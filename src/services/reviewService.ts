@@ -12,6 +12,8 @@ export interface Review {
     comment: string;
     date: Date;
     source: 'odrs' | 'monarch';
+    /** ODRS app id the review was found under; needed to vote on it. */
+    appId?: string;
 }
 
 export interface RatingSummary {
@@ -83,7 +85,8 @@ export async function getPackageReviews(pkgName: string, appStreamId?: string):
                             rating: (r.rating || 0) / 20,
                             comment: r.description || r.summary || '',
                             date: new Date((r.date_created || 0) * 1000),
-                            source: 'odrs'
+                            source: 'odrs',
+                            appId: id
                         });
                     });
                     break; // Found recent ODRS data
@@ -157,6 +160,29 @@ export async function submitReview(pkgName: string, rating: number, comment: str
     if (error) throw error;
 }
 
+/**
+ * Publishes a review to ODRS so it reaches GNOME Software / Discover users too.
+ * Rating is 1-5 stars. `localId` is the saved copy (submit_review), so editing or deleting
+ * it under My Reviews updates ODRS as well.
+ */
+export async function submitOdrsReview(appId: string, rating: number, summary: string, description: string, userName: string, version?: string, localId?: string) {
+    await invoke('submit_odrs_review', {
+        appId,
+        rating,
+        summary,
+        description,
+        userDisplay: userName,
+        version: version ?? null,
+        localId: localId ?? null,
+    });
+}
+
+export type OdrsVote = 'upvote' | 'downvote' | 'dismiss' | 'report';
+
+export async function voteOdrsReview(appId: string, reviewId: number, vote: OdrsVote) {
+    await invoke('vote_odrs_review', { appId, reviewId, vote });
+}
+
 /**
  * Batch fetch ratings for multiple packages (ODRS only).
  * Probes for both [name] and [name.desktop].
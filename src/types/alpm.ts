@@ -49,13 +49,594 @@ export type AlpmEventType =
     | 'package_marked'
     | 'file_added'
     | 'transaction_complete'
+    | 'dry_run_change'
+    | 'downloads_ready'
+    | 'stale_download'
+    | 'mirror'
+    | 'mirror_failed'
+    | 'mirror_switch'
     | 'error';
 
+/** AUR build environment: makepkg on the host or a devtools clean chroot. */
+export type AurBuildMode = 'host' | 'chroot';
+
+export interface AurBuildModeInfo {
+    mode: AurBuildMode;
+    ccache: boolean;
+    devtools_available: boolean;
+    ccache_available: boolean;
+}
+
+/** One package a dry run or download-only transaction resolved (helper DryRunChange). */
+export interface DryRunChange {
+    action: 'install' | 'upgrade' | 'downgrade' | 'reinstall' | 'remove';
+    name: string;
+    old_version: string | null;
+    new_version: string | null;
+    download_size: number;
+    installed_size: number;
+}
+
+/** Packages downloaded for a later install; `token` resumes it (resume_deferred_install). */
+export interface DeferredInstall {
+    token: string;
+    created_at: number;
+    target: { kind: 'upgrade' } | { kind: 'install'; packages: string[]; target_repo: string | null };
+    changes: DryRunChange[];
+    download_bytes: number;
+}
+
+/** An installable version of a package for rollback (get_available_versions). */
+export interface AvailableVersion {
+    version: string;
+    /** 'cache' = already in the pacman cache; 'archive' = downloaded from the Arch Linux Archive. */
+    source: 'cache' | 'archive';
+    filename: string;
+    installed: boolean;
+}
+
+/** An AUR package built here that a binary repo now ships (get_migration_suggestions). */
+export interface MigrationSuggestion {
+    name: string;
+    installed_version: string;
+    repo: string;
+    repo_version: string;
+    download_size: number;
+}
+
+/** A repo binary whose repo no longer ships it (get_dropped_packages). */
+export interface DroppedPackage {
+    name: string;
+    installed_version: string;
+    /** null when the AUR has no package of that name either. */
+    aur_version: string | null;
+    /** Update checks already take this package from the AUR. */
+    switched: boolean;
+}
+
+export interface DevelUpdateSettings {
+    /** Leave -git/-svn/... AUR packages out of routine update checks. */
+    exclude: boolean;
+    /** Development packages still checked like any other package. */
+    always_check: string[];
+}
+
 export interface UpdateItem {
     name: string;
+    /** Shown instead of name when that is an opaque id (firmware devices). */
+    display_name?: string;
     current_version: string;
     new_version: string;
     source: PackageSource;
     size?: number;
     icon?: string;
+    /** Candidate is older than the installed version (never applied by Update All). */
+    downgrade?: boolean;
+}
+
+/** check_updates result: one section per backend. */
+export interface UpdateSet {
+    repo: UpdateItem[];
+    aur: UpdateItem[];
+    flatpak: UpdateItem[];
+    firmware: UpdateItem[];
+    /** Backend ("repo" | "aur" | "flatpak" | "firmware") → why its check failed. */
+    errors: Record<string, string>;
+}
+
+/** A package change from the transaction history. */
+export interface PackageChange {
+    name: string;
+    action: 'installed' | 'upgraded' | 'downgraded' | 'reinstalled' | 'removed';
+    old_version: string | null;
+    new_version: string | null;
+}
+
+/** An Arch security advisory (AVG) one of the week's updates fixed. */
+export interface AdvisoryFix {
+    group: string;
+    package: string;
+    severity: string;
+    fixed_version: string;
+    issues: string[];
+    advisories: string[];
+}
+
+export interface DiskSnapshot {
+    timestamp: number;
+    package_count: number;
+    installed_bytes: number;
+    cache_bytes: number;
+}
+
+/** get_weekly_digest: the past seven days. Deltas compare with the snapshot a week ago. */
+export interface WeeklyDigest {
+    period_start: number;
+    period_end: number;
+    updated: PackageChange[];
+    installed_count: number;
+    removed_count: number;
+    advisories_fixed: AdvisoryFix[];
+    /** False when security.archlinux.org could not be reached. */
+    advisories_checked: boolean;
+    disk: {
+        installed_bytes: number;
+        installed_delta: number;
+        cache_bytes: number;
+        cache_delta: number;
+        snapshots: DiskSnapshot[];
+    };
+    maintenance: { kind: 'updates' | 'orphans' | 'pacnew' | 'cache'; title: string; detail: string }[];
+}
+
+/** preview_uninstall: what uninstall_package would take out, computed without privileges. */
+export interface UninstallPreview {
+    packages: {
+        name: string;
+        version: string;
+        installed_size: number;
+        /** "target", or "dependent" when the cascade takes it along. */
+        reason: string;
+        requires: string[];
+        protected: boolean;
+        desktop: string[];
+    }[];
+    reclaimed_bytes: number;
+    blocked_by: string[];
+    orphaned: string[];
+    warnings: string[];
+}
+
+/** Localhost API settings (get_local_api_settings / set_local_api_settings). */
+/** Proxy / bandwidth settings for the app's HTTP clients and the helper's package downloads. */
+export interface NetworkSettings {
+    /** http:// or https:// URL, optionally with user:password@. */
+    proxy: string | null;
+    /** KiB/s; null for unlimited. */
+    rate_limit_kbps: number | null;
+    ipv4_only: boolean;
+}
+
+/** get_icons_batch entry: a converted icon from the on-disk icon cache. */
+export interface CachedIcon {
+    /** Asset-protocol URL for <img src>. */
+    url: string;
+    path: string;
+    size: number;
+}
+
+/** Payload of "connectivity-changed" and the connectivity commands. */
+export interface ConnectivityState {
+    offline: boolean;
+    /** Offline mode switched on by the user (the network may be fine). */
+    forced: boolean;
+    /** Unix seconds when the app went offline. */
+    since: number | null;
+}
+
+export interface ContentRating {
+    /** "oars-1.0" or "oars-1.1". */
+    kind: string;
+    /** Attribute id (e.g. "violence-cartoon") -> "mild" | "moderate" | "intense". */
+    attributes: Record<string, string>;
+    min_age: number;
+}
+
+export interface CatalogLocale {
+    /** Locale app names and descriptions are shown in, e.g. "pt_BR.UTF-8". */
+    locale: string;
+    /** Chosen in Settings rather than taken from the system. */
+    custom: boolean;
+}
+
+export interface LocalApiSettings {
+    enabled: boolean;
+    port: number;
+    /** POST /api/v1/check and /api/v1/update are accepted. */
+    allow_updates: boolean;
+    /** Sent as "Authorization: Bearer <token>". */
+    token: string;
+}
+
+export interface PrivilegedAgentSettings {
+    /** Keep a root agent running after the first authentication. */
+    enabled: boolean;
+    /** The agent exits after this many seconds without a request. */
+    idle_timeout_secs: number;
+}
+
+/** A review kept in the local store (get_my_reviews). Rating is 0-100; submit_review and update_review take 1-5 stars. */
+export interface LocalReview {
+    id: string;
+    app_id: string;
+    rating: number;
+    summary: string;
+    description: string;
+    user_display: string;
+    date_created: number;
+    date_edited?: number;
+    author?: string;
+    /** Also published to ODRS; edits and deletes go there too. */
+    odrs?: boolean;
+}
+
+export type RuleInterval = 'daily' | 'weekly' | 'monthly';
+
+export type MaintenanceAction =
+    | { action: 'remove_orphans' }
+    | { action: 'trim_package_cache'; keep: number }
+    | { action: 'clean_build_cache'; over_bytes: number };
+
+export type MaintenanceRule = MaintenanceAction & { interval: RuleInterval };
+
+export interface MaintenancePolicy {
+    rules: MaintenanceRule[];
+    /** Only report what the rules would clean. */
+    dry_run: boolean;
+}
+
+/** One run of a maintenance rule. */
+export type RuleRun = MaintenanceAction & {
+    ran_at: number;
+    dry_run: boolean;
+    /** Packages or paths removed, or that would have been. */
+    items: string[];
+    bytes: number;
+    error: string | null;
+};
+
+export interface MaintenanceReport {
+    dry_run: boolean;
+    rules: { rule: MaintenanceRule; last_run: RuleRun | null; next_run: number }[];
+}
+
+export interface ReviewPackInfo {
+    origin: {
+        name: string;
+        /** Fingerprint of the signing key. */
+        signer: string;
+        signer_uid: string;
+    };
+    created: number;
+    imported: number;
+    review_count: number;
+}
+
+/** Result of import_settings. */
+export interface SettingsImport {
+    /** Keys restored into settings.json. */
+    settings: number;
+    /** Repos from the backup that this machine also has. */
+    repos: number;
+    pins: number;
+    /** Parts that could not be restored, and why. */
+    warnings: string[];
+}
+
+export interface RepoHealth {
+    name: string;
+    /** Unix seconds the local sync DB was written; null if never synced. */
+    last_sync: number | null;
+    upstream_modified: number | null;
+    mirror: string | null;
+    mirror_reachable: boolean;
+    package_count: number;
+    siglevel: string;
+    signature: 'valid' | 'unsigned' | 'invalid' | 'missing';
+    warnings: string[];
+}
+
+export type UpdatePolicy = 'auto' | 'notify' | 'hold';
+export type UpdateCategory = 'browsers' | 'security' | 'kernels' | 'drivers' | 'other';
+
+export interface UpdatePolicies {
+    categories: Partial<Record<UpdateCategory, UpdatePolicy>>;
+    /** Package name → policy, overriding its category. */
+    packages: Record<string, UpdatePolicy>;
+}
+
+/** When background syncs and auto updates wait (update_conditions.rs). */
+export interface UpdateConditions {
+    defer_on_battery: boolean;
+    min_battery_percent: number;
+    defer_on_metered: boolean;
+}
+
+export interface UpdateConditionsInfo {
+    conditions: UpdateConditions;
+    state: { on_battery: boolean; battery_percent: number | null; metered: boolean };
+    /** Why big updates should wait right now, if they should. */
+    hold_reason: string | null;
+}
+
+export interface PolicyAnnotation {
+    name: string;
+    category: UpdateCategory;
+    policy: UpdatePolicy;
+}
+
+export type ChaoticBuildState = 'queued' | 'building' | 'failed' | 'success' | 'unknown';
+
+export interface ChaoticBuildStatus {
+    pkgname: string;
+    state: ChaoticBuildState;
+    /** Version of the last finished build, if any. */
+    last_version: string | null;
+    last_build_time: number | null;
+    log_url: string | null;
+}
+
+export interface UpstreamRelease {
+    tag: string;
+    /** The tag as a pkgver ("v1.2.3" -> "1.2.3"); null when it isn't a version. */
+    version: string | null;
+    name: string | null;
+    /** Release notes (or tag) page. */
+    url: string;
+    published_at: string | null;
+    /** Release notes (Markdown); null for bare tags. */
+    body: string | null;
+    prerelease: boolean;
+}
+
+export interface UpstreamStatus {
+    pkgname: string;
+    upstream_url: string | null;
+    packaged_version: string | null;
+    latest_stable: UpstreamRelease | null;
+    /** Newest beta/nightly release ahead of the latest stable one. */
+    latest_prerelease: UpstreamRelease | null;
+    releases: UpstreamRelease[];
+    /** The packaged version is older than the latest stable upstream release. */
+    lagging: boolean;
+}
+
+export type ChangelogSource = 'package' | 'upstream_releases' | 'packaging_commits';
+
+export interface ChangelogEntry {
+    title: string;
+    body: string | null;
+    url: string | null;
+    date: string | null;
+}
+
+export interface UpdateChangelog {
+    pkg: string;
+    old_version: string;
+    new_version: string;
+    /** null when nothing was found. */
+    source: ChangelogSource | null;
+    /** Newest first. */
+    entries: ChangelogEntry[];
+}
+
+export type RescueAction = 'regenerate_initramfs' | 'reinstall_boot_packages' | 'restore_local_db' | 'restore_pacman_conf';
+
+export interface RescueOperation {
+    action: RescueAction;
+    title: string;
+    description: string;
+    risk: 'low' | 'medium' | 'high';
+    /** What can go wrong, shown before the user confirms. */
+    risk_description: string;
+    available: boolean;
+    /** Why it is unavailable, or when the backup it uses was taken. */
+    note: string | null;
+}
+
+export interface PkgstatsSettings {
+    enabled: boolean;
+    /** Unix seconds of the last successful submission. */
+    last_submitted: number | null;
+    /** Submissions also need the telemetry toggle. */
+    telemetry_enabled: boolean;
+}
+
+export interface CachedScreenshot {
+    /** Absolute path in the screenshot cache (for convertFileSrc). */
+    path: string;
+    /** Inlined JPEG, set for thumbnails. */
+    data_uri: string | null;
+}
+
+/** Server-side narrowing for search_packages / search_index_query; omitted fields mean "any". */
+export interface SearchFilters {
+    repos?: string[];
+    limit?: number;
+    /** 'repo' | 'aur' | 'flatpak' */
+    source_types?: string[];
+    only_installed?: boolean;
+    only_not_installed?: boolean;
+    foss_only?: boolean;
+    /** Only -v3/-v4/-znver4 builds. */
+    optimized_only?: boolean;
+}
+
+export interface CuratedCollection {
+    id: string;
+    title: string;
+    description: string;
+    /** Icon hint: "gamepad" | "clapperboard" | "terminal" | "briefcase" | "shield" */
+    icon: string;
+    /** Package names available from the enabled sources, in curated order. */
+    packages: string[];
+    /** Curated members no enabled source carries. */
+    skipped: string[];
+}
+
+export interface CollectionsResponse {
+    collections: CuratedCollection[];
+    /** Lowercase AppStream category ("game", "audiovideo", "utility", ...) -> app count. */
+    category_counts: Record<string, number>;
+}
+
+export type ImportSource = 'official' | 'chaotic' | 'aur' | 'not_found' | 'lookup_failed';
+
+export interface ImportEntry {
+    name: string;
+    source: ImportSource;
+    repo: string | null;
+    version: string | null;
+    /** Already installed here; not part of the targets. */
+    installed: boolean;
+}
+
+/** import_package_list: a reviewable plan, nothing installed yet. */
+export interface ImportPlan {
+    entries: ImportEntry[];
+    /** Installed together in one transaction. */
+    repo_targets: string[];
+    /** Built one by one afterwards. */
+    aur_targets: string[];
+    not_found: string[];
+    /** The AUR lookup failed for these; import the list again to retry. */
+    lookup_failed: string[];
+    invalid_lines: string[];
+}
+
+export interface CachedPackageFile {
+    name: string;
+    version: string;
+    path: string;
+    /** Package plus signature, in bytes. */
+    size: number;
+}
+
+export interface CacheCleanReport {
+    dry_run: boolean;
+    /** Files removed, or that would be removed in a dry run. */
+    removed: CachedPackageFile[];
+    reclaimed_bytes: number;
+    kept: number;
+}
+
+export interface PackageService {
+    unit: string;
+    description: string | null;
+    /** `systemctl is-enabled` state: enabled, disabled, static, masked, ... */
+    enabled: string;
+    active: boolean;
+}
+
+/** Payload of `services-available`, sent after an install that shipped disabled units. */
+export interface ServicesAvailable {
+    package: string;
+    services: PackageService[];
+}
+
+/** A license agreement to accept before a fresh install (eula.rs EulaPrompt). */
+export interface EulaPrompt {
+    eula_id: string;
+    revision: number;
+    package: string;
+    title: string;
+    url: string;
+    summary: string;
+}
+
+/** Payload of `license-prompt`: an AUR build is paused on a license question. */
+export interface LicensePrompt {
+    id: string;
+    package: string;
+    /** The question, e.g. "Do you accept the license? [y/N]". */
+    prompt: string;
+    /** Build output printed before the question, usually the license itself. */
+    text: string;
+}
+
+export type NoteKind = 'reboot' | 'firmware' | 'warning' | 'error';
+
+/** One entry of `post-transaction-notes`: a notable line printed by a hook or install script. */
+export interface PostTransactionNote {
+    kind: NoteKind;
+    /** Hook description or package name. */
+    source: string;
+    message: string;
+}
+
+export type BatchStep = 'keyrings' | 'refresh_db' | 'upgrade' | 'remove' | 'install' | 'install_local';
+
+/** One step of the helper's last batch; `running` means it was cut off mid-step. */
+export interface BatchStepState {
+    step: BatchStep;
+    status: 'pending' | 'running' | 'done' | 'failed';
+    error?: string;
+}
+
+/** Checkpoint of the helper's last batch (get_last_batch_state). */
+export interface BatchState {
+    manifest: {
+        update_system: boolean;
+        install_targets: string[];
+        remove_targets: string[];
+        local_paths: string[];
+    };
+    steps: BatchStepState[];
+    /** Unix seconds. */
+    started_at: number;
+    updated_at: number;
+    resumed: number;
+}
+
+export type TrialKind = 'flatpak' | 'distrobox';
+
+export interface TrialSupport {
+    flatpak: boolean;
+    /** distrobox plus a terminal emulator to open the container in. */
+    distrobox: boolean;
+}
+
+/** Payload of `trial-status`. */
+export interface TrialStatus {
+    app_id: string;
+    kind: TrialKind;
+    state: 'preparing' | 'running' | 'cleaning' | 'finished' | 'failed';
+    message: string;
+}
+
+/** A build of a package in a CachyOS x86-64-v3/v4 or znver4 repo. */
+export interface OptimizedVariant {
+    repo: string;
+    level: 'v3' | 'v4' | 'znver4';
+    version: string;
+    /** This CPU can run it. */
+    supported: boolean;
+}
+
+/** pkgstats popularity and ODRS rating of a package (get_package_scores). */
+export interface PackageScore {
+    /** Percent of pkgstats-reporting systems with it installed. */
+    popularity: number | null;
+    /** Average stars, 1-5. */
+    rating: number | null;
+    rating_count: number;
+    /** The average with the catalog's prior votes mixed in; what "top rated" orders by. */
+    weighted_rating: number | null;
+}
+
+/** One entry of the `packages-changed` event. */
+export interface PackageStateChange {
+    name: string;
+    /** Installed version now; null when it was removed. */
+    version: string | null;
 }